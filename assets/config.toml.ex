@@ -1,14 +1,324 @@
+# Run with `--setup` to be asked for the credentials and have them written
+# here, which also happens on the first start without a config.
+#
+# Any key can be overridden, from the highest precedence to the lowest, by:
+#
+# - the `--set section.key=value` flags, such as `--set telegram.api_id=123`;
+# - the `GRYMBB_` environment variables, the sections split by `__`, such as
+#   `GRYMBB_TELEGRAM__API_ID=123` or `GRYMBB_PLUGINS__EVAL__TIMEOUT=60`, the
+#   accounts indexed from 0, such as `GRYMBB_USER__1__PHONE_NUMBER`, the keys
+#   without an index going to the main one;
+# - this file, `--config <path>` to read another one;
+# - the defaults.
+#
+# The overrides are read as TOML values, so a string that looks like a number,
+# such as a phone number, must be quoted unless this file already sets it. The
+# file can be left out, the whole config coming from the overrides.
+
 [telegram]
 api_id = 0
 api_hash = ""
+# In seconds, the longest flood wait slept through before retrying. The
+# clients keep the value they started with, the retries of the bot follow
+# the reloaded one.
 flood_sleep_threshold = 180
+# In minutes, the commands older than this are ignored when catching up on
+# the updates after a restart. 0 runs them regardless of age. Reloaded when
+# this file changes.
+max_catchup_age = 10
+# The IDs of the users allowed to use the commands, besides the ones added
+# with .addsudo. Reloaded when this file changes.
+sudoers = []
 
 [bot]
 token = ""
 catch_up = false
 session_file = "./assets/bot.session"
+# Signs the payloads exchanged between the clients. A random one is used
+# on each run if empty.
+secret = ""
+
+[bot.reconnection]
+# Ignored when `never_give_up` is set.
+max_attempts = 5
+never_give_up = false
+# "linear" or "exponential"
+backoff = "exponential"
+# In seconds.
+base_delay = 5
+max_delay = 300
+# The fraction of the delay randomly added or removed.
+jitter = 0.2
+
+# The messages the user sends through the inline mode of the bot.
+[bot.via_bot]
+# In seconds, how long an attempt can take.
+timeout = 30
+attempts = 3
 
+# The user account. Several can run at once, sharing the bot, by writing each
+# one as `[[user]]`, with its own session file, followed by its own
+# `[user.reconnection]`. The first one is the main account: it owns the bot
+# and runs the background tasks, such as the folders and the profile clock.
 [user]
 phone_number = ""
 catch_up = false
 session_file = "./assets/user.session"
+
+[user.reconnection]
+max_attempts = 5
+never_give_up = true
+backoff = "exponential"
+base_delay = 5
+max_delay = 300
+jitter = 0.2
+
+# The messages the clients send through each other, such as the game boards.
+[bus]
+# The most messages waiting in each priority level. When the low one is full,
+# its oldest message is dropped.
+capacity = 10
+# In seconds, how long a message waits for room before the command tells the
+# bot is busy.
+send_timeout = 10
+
+
+[games]
+# In seconds.
+move_timeout = 120
+# In minutes, how long a game can go without moves before it expires.
+expire_after = 30
+
+[trivia]
+# How many questions a game has.
+questions = 10
+# In seconds.
+answer_time = 20
+# Each JSON file is a question pack named after it, without it the questions
+# come from Open Trivia DB.
+packs_dir = "./assets/trivia"
+
+[alerts]
+cooldown = 60
+
+[folders]
+# In minutes, how often the auto-foldering rules are applied.
+sync_interval = 60
+
+[usernames]
+# In seconds, the pause between two checks, to avoid flood waits.
+delay = 2
+# In minutes, how often the watched usernames are checked.
+interval = 30
+
+[tags]
+presets = ["ler", "links", "ideias"]
+page_size = 10
+
+[songs]
+cache_ttl = 3600
+
+[ytdl]
+path = "yt-dlp"
+output_dir = "./assets/downloads"
+
+[lyrics]
+# "lrclib" or "lyrics_ovh"
+provider = "lrclib"
+page_length = 3000
+
+[reverse_search]
+# The engines searched when none is chosen, among "sauce_nao", "yandex" and "tin_eye".
+engines = ["sauce_nao", "yandex", "tin_eye"]
+# Get one at https://saucenao.com/user.php, SauceNAO is skipped without it.
+saucenao_key = ""
+# Extracts the frames of the videos and GIFs searched.
+ffmpeg_path = "ffmpeg"
+
+[digest.currency]
+base = "USD"
+targets = ["BRL", "EUR"]
+
+[digest.rss]
+feeds = []
+headlines = 3
+
+[digest.weather]
+# The place the weather widget shows the forecast for, such as "São Paulo".
+place = ""
+
+[calendar]
+# An ICS feed or a CalDAV calendar export URL.
+url = ""
+# username = ""
+# password = ""
+# In minutes.
+sync_interval = 15
+lead_times = [60, 10]
+
+[mail]
+# Leave the host empty to disable the bridge.
+host = ""
+port = 993
+username = ""
+password = ""
+mailboxes = ["INBOX"]
+# In seconds.
+poll_interval = 60
+# In bytes.
+attachment_size_cap = 10485760
+
+[profile]
+# The strftime format of the clock shown in the last name.
+clock_format = "%H:%M"
+# The bios rotated daily, in order.
+bios = []
+
+[business]
+# Lets business accounts connect the bot to reply on their behalf. This
+# section is reloaded when this file changes.
+enabled = false
+# The users allowed to connect the bot, none if empty.
+owners = []
+
+[webapp]
+# The public HTTPS URL proxied to the server, leave empty to disable the web app.
+url = ""
+bind = "127.0.0.1:8080"
+assets_dir = "./assets/webapp"
+# In seconds, how long the data sent by the web app stays valid.
+max_age = 86400
+
+[raid]
+# The raid mode is enabled when this many users join within the window.
+joins = 10
+# In seconds.
+window = 60
+# In minutes, how long the raid mode lasts once enabled automatically.
+duration = 15
+
+[nsfw]
+# The classifier that scores the photos, either a hosted API or a local model
+# server, which takes the photo as a multipart file. Empty disables the detection.
+url = ""
+# Sent as a bearer token, if not empty.
+api_key = ""
+file_field = "image"
+# The JSON pointer of the score in the response.
+score_pointer = "/nsfw"
+# Whether the score goes from 0 to 1, instead of 0 to 100.
+normalized = true
+
+[transcriber]
+# The speech to text endpoint the voice notes are sent to, compatible with the
+# OpenAI transcriptions API, such as
+# "https://api.openai.com/v1/audio/transcriptions" or a local Whisper server.
+# Empty disables the transcription.
+url = ""
+# Sent as a bearer token, if not empty.
+api_key = ""
+model = "whisper-1"
+# In seconds, the longer voice notes are not transcribed.
+max_duration = 300
+# The most characters of a transcript, the rest being cut.
+max_length = 3500
+
+[url_scanner]
+# "safe_browsing", "virus_total" or "urlscan".
+provider = "safe_browsing"
+# The API key of the provider. Empty disables the scanner.
+api_key = ""
+# For how many hours a scanned URL is not scanned again.
+cache_hours = 24
+
+[virustotal]
+# Whether the mirrored, uploaded and downloaded files are scanned before being sent.
+# Reloaded when this file changes.
+enabled = false
+# Get one at https://www.virustotal.com/gui/my-apikey.
+api_key = ""
+# In megabytes, the biggest unknown file submitted to be analyzed.
+max_upload_size = 32
+
+[media_cache]
+# In megabytes, how much the recently downloaded media can take in memory.
+max_size = 32
+
+[sandbox]
+# What the evaluated code runs inside of, among "none", "firejail", "bubblewrap"
+# and "container". It always runs in a temporary directory, with the limits below.
+wrapper = "none"
+# The extra arguments given to the wrapper, such as more `--bind` for bubblewrap.
+wrapper_args = []
+# Only used by the "container" wrapper, the image must have the interpreters.
+container_runtime = "docker"
+container_image = ""
+# In seconds, how long the code can run before its processes are killed.
+timeout = 30
+# In kilobytes, how much of each output is kept.
+max_output = 64
+# In megabytes, the most memory the code can take, 0 for no limit.
+memory_limit = 2048
+
+[doctor]
+# Whether the self-test runs at startup, its results being sent to the owner.
+on_startup = true
+# In megabytes, the least free disk space before the disk check fails.
+min_free_space = 1024
+
+[usage]
+# In megabytes, how much a plugin can download and upload a day before it is
+# paused until the next day, see `.usage`. Counts `ytdl`, `mirror` and `upload`.
+budgets = { ytdl = 0, mirror = 0, upload = 0 }
+
+# Each plugin with settings reads them from its own `[plugins.<name>]` section,
+# see `.config <plugin>` for the effective values. They are reloaded when this
+# file changes, or with `.reloadconfig`, the other sections needing a restart
+# apart from `telegram.max_catchup_age`.
+[plugins.donate]
+default_amount = 50
+max_amount = 10000
+
+[plugins.eval]
+# Whether the status message shows the output while the code runs.
+live_output = true
+# In seconds, how often the status message is updated.
+update_interval = 3
+
+[plugins.giveaway]
+max_winners = 50
+
+[plugins.mirror]
+dir = "./assets/mirror"
+
+[plugins.pdf]
+max_messages = 200
+# Whether the photos are drawn in the document, not only mentioned.
+include_images = true
+
+[plugins.purge]
+# In seconds, how long the reply of each command is kept.
+delete_ttl = 4
+purge_ttl = 4
+purgeme_ttl = 4
+# Above how many messages a purge must be confirmed, zero for never.
+confirm_threshold = 100
+
+[plugins.sed]
+search_limit = 20
+
+[plugins.term]
+# The shell the `.sh` and `.term` commands run in.
+shell = "bash"
+# The directory the commands run in, empty for the bot's own.
+working_dir = ""
+# In seconds, how long a command can run, zero for no limit.
+timeout = 300
+# In seconds, how often the status message is updated.
+update_interval = 3
+
+[plugins.unread]
+limit = 30
+
+[plugins.upload]
+parallelism = 3
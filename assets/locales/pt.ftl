@@ -0,0 +1,944 @@
+reload_button = Recarregar 🔃
+
+start_text = Olá, eu sou o seu assistente virtual. Como posso ajudar você hoje?
+info_text = Aqui estão algumas informações sobre mim e meu host:
+
+    <b>OS</b>: <code>{ $os }</code> ({ $arch })
+    <b>CPU</b>: <code>{ $cpu_usage }%</code>
+    <b>Host</b>: <code>{ $host }</code>
+    <b>Versão</b>: <code>{ $version }</code> (k <code>{ $kernel_version }</code>)
+    <b>Memória</b>: <code>{ $memory_usage }%</code> (<code>{ $used_memory } GB</code> / <code>{ $total_memory } GB</code>)
+
+purged = { $count ->
+        [one] Purgada <code>{ $count }</code> mensagem!
+       *[other] Purgadas <code>{ $count }</code> mensagens!
+    }
+deleted = Mensagem deletada!
+purging = Purgando ~<code>{ $count }</code> mensagens...
+purged_me = { $count ->
+        [one] Purgada <code>{ $count }</code> mensagem minha!
+       *[other] Purgadas <code>{ $count }</code> mensagens minhas!
+    }
+purging_me = Purgando mensagens minhas...
+purged_you = { $count ->
+        [one] Purgada <code>{ $count }</code> mensagem sua!
+       *[other] Purgadas <code>{ $count }</code> mensagens suas!
+    }
+purging_you = Purgando mensagens suas...
+purge_error = Ocorreu um erro ao purgar mensagens.
+purge_usage = Responda à primeira <b>mensagem</b> a purgar, ou use <code>.purge 50</code> para as últimas mensagens ou <code>.purge 2h</code> para as mais recentes que isso.
+purge_confirmation = ⚠️ Purgar <code>{ $count }</code> { $count ->
+        [one] mensagem
+       *[other] mensagens
+    }, de <b>{ $from }</b> até <b>{ $to }</b>?
+purge_confirm = ✅ Confirmar
+purge_cancel = ❌ Cancelar
+purge_cancelled = Purga cancelada.
+purge_expired = Essa purga expirou, use o comando novamente.
+delete_error = Ocorreu um erro ao deletar a mensagem.
+
+evaluating = Executando o seu código...
+
+    <b>Entrada</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+eval_partial = Executando o seu código...
+
+    <b>Entrada</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Saída parcial</b>:
+    <blockquote><code>{ $output }</code></blockquote>
+eval_input = <b>Entrada</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Tempo de execução</b>: <code>{ $time }</code>s.
+eval_output = <b>Entrada</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Saída</b>:
+    <blockquote><code>{ $output }</code></blockquote>
+
+    <b>Tempo de execução</b>: <code>{ $time }</code>s.
+eval_no_code = Você não digitou nenhum código para executar.
+eval_failure = Ocorreu um erro ao executar o seu código.
+eval_output_file = Este arquivo contém o resultado da execução do seu código.
+eval_timeout = <b>Aviso</b>: o código excedeu o tempo limite de <code>{ $timeout }</code>s e foi interrompido.
+eval_truncated = <b>Aviso</b>: a saída foi cortada por ser longa demais.
+eval_exit_code = <b>Código de saída</b>: <code>{ $code }</code>.
+eval_signal = <b>Aviso</b>: o processo foi encerrado por um sinal.
+eval_unknown_lang = A linguagem <code>{ $lang }</code> não é suportada, as disponíveis são: { $langs }.
+term_running = Executando o comando...
+
+    <b>Comando</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+term_partial = Executando o comando...
+
+    <b>Comando</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Saída parcial</b>:
+    <blockquote><code>{ $output }</code></blockquote>
+term_input = <b>Comando</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Tempo de execução</b>: <code>{ $time }</code>s.
+term_output = <b>Comando</b>:
+    <blockquote><code>{ $input }</code></blockquote>
+
+    <b>Saída</b>:
+    <blockquote><code>{ $output }</code></blockquote>
+
+    <b>Tempo de execução</b>: <code>{ $time }</code>s.
+term_output_file = Este arquivo contém a saída do comando.
+term_no_command = Você não digitou nenhum comando para executar.
+term_failure = Não foi possível executar o comando: <code>{ $error }</code>
+term_timeout = <b>Aviso</b>: o comando excedeu o tempo limite de <code>{ $timeout }</code>s e foi interrompido.
+term_truncated = <b>Aviso</b>: a saída foi cortada por ser longa demais.
+term_exit_code = <b>Código de saída</b>: <code>{ $code }</code>.
+term_signal = <b>Aviso</b>: o processo foi encerrado por um sinal.
+
+search_error = Ocorreu um erro ao procurar a foto.
+searching_photo = Procurando a imagem...
+downloading_photo = Baixando a mídia...
+
+screenshot_error = Ocorreu um erro ao capturar a página.
+screenshot_no_url = Você não digitou nenhum URL para capturar.
+screenshot_many_urls = Você digitou muitos URLs para capturar.
+screenshot_invalid_flags = <b>Uso</b>: <code>.ss &lt;url&gt; [--mobile] [--full] [--delay &lt;segundos&gt;] [--selector &lt;css&gt;] [--dark]</code>, com o atraso de até 10 segundos.
+screenshot_processing = Processando sua captura...
+
+reply_needed = Este comando deve ser usado como resposta a uma <b>mensagem</b>.
+reply_not_url = Este comando só pode ser usado em mensagens com URL.
+reply_not_media = Este comando só pode ser usado em mensagens com mídia.
+reply_not_photo = Este comando só pode ser usado em mensagens com fotos, figurinhas, vídeos ou GIFs.
+reply_not_url_or_media = Este comando só pode ser usado em mensagens com URL ou mídia.
+
+upload_info = Enviando <code>{ $name }</code>...
+
+    <b>Tipo</b>: <code>{ $type }</code>.
+    <b>Tamanho</b>: <code>{ $size }</code>.
+upload_time = Enviado em <code>{ $time }</code>s.
+upload_error = Ocorreu um erro ao enviar o arquivo.
+upload_summary = <b>Enviando { $count } arquivos...</b>
+
+    { $files }
+upload_summary_done = <b>{ $done } de { $count } arquivos enviados em <code>{ $time }</code>s.</b>
+
+    { $files }
+upload_status_pending = ⏳ <code>{ $url }</code>
+upload_status_downloading = ⬇️ <code>{ $url }</code>
+upload_status_scanning = 🔎 <code>{ $name }</code>
+upload_status_uploading = ⬆️ <code>{ $name }</code>
+upload_status_done = ✅ <code>{ $name }</code>
+upload_status_failed = ❌ <code>{ $url }</code>: { $reason }
+usage_header_today = 📊 <b>Uso de hoje</b>
+    {""}
+usage_header = 📊 <b>Uso dos últimos { $days } dias</b>
+    {""}
+usage_line = <b>{ $plugin }</b>: ⬇️ <code>{ $downloaded }</code> · ⬆️ <code>{ $uploaded }</code> · 📡 <code>{ $calls }</code> requisições
+usage_budget = {""} · <code>{ $percent }%</code> de <code>{ $budget }</code>
+usage_empty = Nenhum uso registrado nesse período.
+usage_invalid_days = Informe um número de dias entre 1 e { $max }.
+usage_budget_exceeded = ⏸ O plugin <code>{ $plugin }</code> atingiu o limite diário de <code>{ $budget }</code> e volta amanhã.
+bus_busy = ⏳ O bot está ocupado agora, tente novamente em instantes.
+
+download_empty = O arquivo está vazio.
+download_error = Ocorreu um erro ao baixar o arquivo.
+download_not_url = Você não digitou nenhum URL para baixar.
+download_processing = Baixando o arquivo...
+download_size_limit = O arquivo é muito grande.
+download_size_mismatch = O tamanho do arquivo não corresponde ao esperado.
+
+you_win = Você venceu o jogo!
+game_draw = O jogo empatou.
+game_full = O jogo está cheio.
+joined_game = Você entrou no jogo.
+not_in_game = Você não está participando do jogo.
+player_wins = { $player } venceu o jogo!
+ocupied_cell = Essa célula já está ocupada.
+full_column = Essa coluna já está cheia.
+not_your_turn = Não é sua vez.
+game_timeout = ⏰ { $player } demorou demais para jogar e perdeu a partida.
+game_not_found = Não foi possível encontrar o jogo.
+
+alert_added = Alerta <code>{ $id }</code> adicionado para <code>{ $keyword }</code>.
+alert_removed = Alerta <code>{ $id }</code> removido.
+alert_not_found = Não foi possível encontrar o alerta.
+alert_no_keyword = Você não digitou nenhuma palavra-chave.
+alert_invalid_regex = A expressão regular é inválida.
+alert_list = <b>Alertas</b>:
+    {""}
+alert_list_empty = Nenhum alerta cadastrado.
+alert_chat_muted = Este chat não vai mais disparar alertas.
+alert_chat_already_muted = Este chat já está silenciado.
+alert_chat_unmuted = Este chat voltou a disparar alertas.
+alert_chat_not_muted = Este chat não está silenciado.
+alert_usage = <b>Uso</b>: <code>.alert add &lt;palavra|/regex/&gt;</code>, <code>.alert del &lt;id&gt;</code>, <code>.alert list</code>, <code>.alert mute</code>, <code>.alert unmute</code>.
+alert_notification = 🔔 <b>Alerta</b>: <code>{ $keyword }</code>
+
+    <b>Chat</b>: { $chat }
+    <b>Remetente</b>: { $sender }
+
+    <blockquote>{ $text }</blockquote>
+
+    <a href="{ $link }">Ir para a mensagem</a>
+
+tag_list = <b>Etiquetas conhecidas</b>:
+tag_added = Etiqueta "{ $tag }" adicionada.
+tag_choose = Escolha as etiquetas desta mensagem:
+tag_no_name = Você não digitou nenhuma etiqueta.
+tag_removed = Etiqueta "{ $tag }" removida.
+tag_not_tracked = Esta mensagem não está no índice.
+tagged_empty = {""}
+    Nenhuma mensagem com esta etiqueta.
+tagged_header = 🏷 <b>{ $tag }</b> (<code>{ $count }</code> { $count ->
+        [one] mensagem
+       *[other] mensagens
+    })
+    {""}
+
+song_result = 🎵 <b>{ $title }</b> — { $artist }
+
+    { $links }
+
+    <a href="{ $page_url }">song.link</a>
+song_no_query = Você não digitou nenhum link ou nome de música.
+song_not_found = Não foi possível encontrar a música.
+song_searching = Procurando a música...
+song_downloading = Baixando o áudio...
+song_download_error = Ocorreu um erro ao baixar o áudio.
+
+lyrics_text = 🎤 <b>{ $title }</b> — { $artist }
+    <b>Idioma</b>: <code>{ $language }</code>
+
+    <blockquote>{ $lyrics }</blockquote>
+lyrics_usage = <b>Uso</b>: <code>.lyrics artista - título</code>, ou responda a um áudio.
+lyrics_expired = Esta letra não está mais disponível.
+lyrics_not_found = Não foi possível encontrar a letra.
+lyrics_searching = Procurando a letra...
+
+digest_rss = 📰 <b>Manchetes</b>
+    { $headlines }
+digest_usage = <b>Uso</b>: <code>.digest [list]</code>, <code>.digest add &lt;widget&gt;</code>, <code>.digest del &lt;widget&gt;</code>, <code>.digest time HH:MM</code>, <code>.digest header [modelo]</code>, <code>.digest now</code>.
+digest_header = ☀️ <b>Bom dia!</b> Aqui está o seu resumo diário.
+digest_header_set = Cabeçalho do resumo atualizado.
+digest_header_reset = O resumo voltou ao cabeçalho padrão.
+digest_status = <b>Resumo diário</b> às <code>{ $time }</code>
+
+    { $widgets }
+digest_currency = 💱 <b>Câmbio</b> (1 { $base })
+    { $rates }
+digest_time_set = O resumo diário será enviado às <code>{ $time }</code>.
+digest_rendering = Gerando o resumo...
+digest_widget_error = ⚠️ Não foi possível carregar esta seção.
+digest_invalid_time = Horário inválido, use o formato HH:MM.
+digest_widget_invalid = O widget <code>{ $widget }</code> não existe ou já está nesse estado.
+digest_widget_enabled = Widget <code>{ $widget }</code> adicionado ao resumo.
+digest_widget_disabled = Widget <code>{ $widget }</code> removido do resumo.
+digest_todo = ✅ <b>Tarefas</b>
+    { $items }
+digest_todo_empty = ✅ Nenhuma tarefa pendente.
+todo_usage = <b>Uso</b>: <code>.todo [list]</code>, <code>.todo add &lt;texto&gt;</code>, <code>.todo done &lt;id&gt;</code>.
+todo_added = Tarefa <code>{ $id }</code> adicionada: { $text }
+todo_done = ✅ Tarefa concluída: { $text }
+todo_not_found = Nenhuma tarefa com o ID <code>{ $id }</code>.
+todo_empty = Nenhuma tarefa pendente.
+todo_list = <b>Tarefas</b>
+    { $items }
+
+agenda_empty = {""}
+    Nenhum evento.
+agenda_header = 📅 <b>Agenda de { $day }</b>
+    {""}
+agenda_all_day = dia todo
+agenda_syncing = Sincronizando a agenda...
+agenda_upcoming = ⏰ <b>Em { $minutes } minutos</b>:
+    { $event }
+agenda_sync_error = Ocorreu um erro ao sincronizar a agenda.
+agenda_invalid_day = Dia inválido, use <code>hoje</code>, <code>amanhã</code> ou uma data como <code>DD/MM</code>.
+agenda_not_configured = Nenhuma agenda foi configurada.
+
+mail_summary = 📧 <b>Novo email</b> em <code>{ $mailbox }</code> <code>#{ $id }</code>
+    <b>De:</b> { $from }
+    <b>Assunto:</b> { $subject }
+
+    { $preview }
+
+    📎 Anexos: <code>{ $attachments }</code>
+    Use <code>.mail read { $id }</code> para ler completo.
+mail_full = 📧 <b>De:</b> { $from }
+    <b>Assunto:</b> { $subject }
+
+    { $body }
+mail_not_found = Email não encontrado, talvez ele já tenha saído do cache.
+mail_not_configured = Nenhuma conta de email foi configurada.
+mail_usage = Uso: <code>.mail read [id]</code>
+inline_usage = Uso: <code>.inline [-k] @bot [consulta]</code> ou <code>.inline [atalho] [consulta]</code>
+
+    <code>.inline save [nome] @bot [prefixo]</code> — salva um atalho.
+    <code>.inline del [nome]</code> — remove um atalho.
+    <code>.inline list</code> — lista os atalhos.
+
+    Use <code>-k</code> para escolher o resultado por um teclado.
+inline_shortcut_saved = Atalho <code>{ $name }</code> salvo para { $bot }.
+inline_shortcut_deleted = Atalho <code>{ $name }</code> removido.
+inline_shortcut_not_found = Atalho <code>{ $name }</code> não encontrado.
+inline_no_shortcuts = Nenhum atalho salvo.
+inline_shortcuts = <b>Atalhos</b>
+
+    { $shortcuts }
+inline_bot_not_found = Bot <code>@{ $bot }</code> não encontrado.
+inline_query_error = Ocorreu um erro ao consultar o bot.
+inline_no_results = Nenhum resultado encontrado.
+inline_choose = Escolha um resultado:
+inline_expired = Essa consulta expirou.
+inline_sent = ✅ Resultado enviado.
+tournament_usage = Uso: <code>/tournament create [jogo] [vagas]</code>
+
+    Jogos disponíveis: <code>ttt</code>.
+tournament_invalid_slots = O número de vagas deve estar entre 2 e { $max }.
+tournament_sign_up = 🏆 <b>Torneio de { $game }</b>
+
+    Inscritos: <code>{ $joined }/{ $slots }</code>
+    { $players }
+tournament_join = Participar
+tournament_joined = Você entrou no torneio.
+tournament_already_joined = Você já está no torneio.
+tournament_full = O torneio está cheio.
+tournament_not_found = Não foi possível encontrar o torneio.
+tournament_bracket = 🏆 <b>Chaveamento do torneio de { $game }</b>
+tournament_match = ⚔️ <b>Rodada { $round }</b>
+tournament_champion = 👑 { $player } venceu o torneio!
+dump_output_file = Este arquivo contém o dump da mensagem.
+fetch_usage = Use <code>.fetch &lt;link&gt;</code> com o link de uma mensagem, como <code>t.me/canal/123</code> ou <code>t.me/c/123456/78</code>.
+fetch_not_found = Não foi possível encontrar a mensagem do link.
+ghost_usage = Uso: <code>.ghost [on|off] [chat]</code> ou <code>.ghost list</code>
+
+    Nos chats fantasmas a conta não envia confirmações de leitura nem indicadores de digitação.
+ghost_enabled = 👻 Modo fantasma ativado em <code>{ $chat }</code>.
+ghost_already_enabled = O modo fantasma já está ativado em <code>{ $chat }</code>.
+ghost_disabled = Modo fantasma desativado em <code>{ $chat }</code>.
+ghost_not_enabled = O modo fantasma não está ativado em <code>{ $chat }</code>.
+ghost_chat_not_found = Chat não encontrado.
+ghost_no_chats = Nenhum chat está no modo fantasma.
+ghost_status = 👻 <b>Chats fantasmas</b>
+
+    { $chats }
+folder_usage = Uso:
+    <code>.folder create [nome] [@chat]</code> — cria uma pasta.
+    <code>.folder add [nome] [@chat]</code> — adiciona um chat a uma pasta.
+    <code>.folder rule [nome] [bots|users|groups|channels]</code> — adiciona uma regra automática.
+    <code>.folder unrule [nome]</code> — remove as regras de uma pasta.
+    <code>.folder list</code> — lista as pastas.
+    <code>.archive [@chat]</code> — arquiva um chat.
+folder_created = 📁 Pasta <b>{ $folder }</b> criada com <b>{ $chat }</b>.
+folder_already_exists = A pasta <b>{ $folder }</b> já existe.
+folder_not_found = A pasta <b>{ $folder }</b> não existe.
+folder_chat_added = <b>{ $chat }</b> adicionado à pasta <b>{ $folder }</b>.
+folder_chat_already_added = <b>{ $chat }</b> já está na pasta <b>{ $folder }</b>.
+folder_chat_not_found = Chat não encontrado.
+folder_invalid_rule = Regra inválida, use <code>bots</code>, <code>users</code>, <code>groups</code> ou <code>channels</code>.
+folder_rule_added = Regra <code>{ $kind }</code> adicionada à pasta <b>{ $folder }</b>.
+folder_rule_exists = A pasta <b>{ $folder }</b> já tem a regra <code>{ $kind }</code>.
+folder_rules_removed = Regras da pasta <b>{ $folder }</b> removidas.
+folder_no_rules = A pasta <b>{ $folder }</b> não tem regras.
+folder_no_folders = Nenhuma pasta encontrada.
+folder_list = 📁 <b>Pastas</b>
+
+    { $folders }
+chat_archived = 🗄 <b>{ $chat }</b> foi arquivado.
+unread_scanning = Procurando mensagens não lidas...
+unread_none = Nenhuma mensagem não lida.
+unread_header = 📬 <b>Mensagens não lidas</b>
+unread_users = 👤 <b>Privados</b>
+unread_groups = 👥 <b>Grupos</b>
+unread_channels = 📢 <b>Canais</b>
+unread_mark_read = ✅ { $chat }
+unread_mark_all_read = ✅ Marcar tudo como lido
+unread_marked = Marcado como lido.
+unread_marked_ghosted = Marcado como lido, exceto { $count } chat(s) no modo fantasma.
+unread_all_read = 📭 Tudo foi lido.
+unread_expired = Esse resumo expirou, use <code>.unread</code> novamente.
+addcontact_usage = Uso: <code>.addcontact [@usuário] [nome] [sobrenome]</code>, respondendo a alguém, ou <code>.addcontact +[telefone] [nome] [sobrenome]</code>
+delcontact_usage = Uso: <code>.delcontact [@usuário]</code> ou respondendo a alguém.
+contact_added = 👤 <b>{ $name }</b> foi adicionado aos contatos.
+contact_duplicate = <b>{ $name }</b> já está nos contatos.
+contact_deleted = <b>{ $name }</b> foi removido dos contatos.
+contact_not_found = <b>{ $name }</b> não está nos contatos.
+contacts_usage = Uso:
+    <code>.contacts search [busca]</code> — busca nos contatos.
+    <code>.contacts export</code> — exporta os contatos em vCard.
+    <code>.contacts import</code> — importa os contatos de um vCard, respondendo ao arquivo.
+contacts_found = 👥 <b>Contatos encontrados</b>
+
+    { $contacts }
+contacts_none_found = Nenhum contato encontrado.
+contacts_exported = { $count ->
+        [one] 📇 <code>{ $count }</code> contato exportado.
+       *[other] 📇 <code>{ $count }</code> contatos exportados.
+    }
+contacts_import_no_file = Responda a um arquivo vCard.
+contacts_importing = Importando contatos...
+contacts_import_empty = Nenhum contato encontrado no arquivo.
+contacts_imported = 📇 <code>{ $imported }</code> contatos importados, <code>{ $duplicates }</code> duplicados ignorados.
+clock_usage = Uso:
+    <code>.clock on|off</code> — mostra um relógio no sobrenome.
+    <code>.clock bio on|off</code> — troca a bio diariamente.
+clock_status = 🕐 <b>Perfil</b>
+
+    Relógio: { $clock }
+    Rotação da bio: { $bio }
+clock_state_on = ativado
+clock_state_off = desativado
+clock_enabled = 🕐 Relógio ativado no sobrenome.
+clock_already_enabled = O relógio já está ativado.
+clock_disabled = Relógio desativado, o sobrenome foi restaurado.
+clock_not_enabled = O relógio não está ativado.
+bio_rotation_enabled = 📝 Rotação diária da bio ativada.
+bio_rotation_already_enabled = A rotação da bio já está ativada.
+bio_rotation_disabled = Rotação da bio desativada.
+bio_rotation_not_enabled = A rotação da bio não está ativada.
+bio_rotation_no_bios = Nenhuma bio configurada em <code>[profile]</code>.
+topics_usage = Uso:
+    <code>/topics create [título]</code> — cria um tópico.
+    <code>/topics rename [título]</code> — renomeia o tópico atual.
+    <code>/topics close</code> — fecha o tópico atual.
+    <code>/topics reopen</code> — reabre o tópico atual.
+topics_not_forum = Este chat não possui tópicos.
+topics_not_in_topic = Use este comando dentro de um tópico.
+topics_error = Não foi possível gerenciar o tópico, verifique minhas permissões.
+topic_created = 💬 Tópico <b>{ $title }</b> criado.
+topic_renamed = 💬 Tópico renomeado para <b>{ $title }</b>.
+topic_closed = 🔒 Tópico fechado.
+topic_reopened = 🔓 Tópico reaberto.
+business_connected = 💼 Conta comercial conectada, as mensagens dos clientes serão encaminhadas aqui.
+business_rejected = Esta conta não tem permissão para conectar o bot.
+business_disconnected = 💼 Conta comercial desconectada.
+business_message = 💼 <b>Mensagem de</b> <code>{ $user }</code>
+
+    { $text }
+
+    <i>Responda com</i> <code>/reply [texto]</code>.
+business_message_read_only = 💼 <b>Mensagem de</b> <code>{ $user }</code>
+
+    { $text }
+business_reply_usage = Uso: <code>/reply [texto]</code>, respondendo a uma mensagem comercial.
+business_replied = Resposta enviada.
+business_cannot_reply = O bot não tem permissão para responder por esta conta.
+business_not_owner = Apenas a conta comercial que recebeu a mensagem pode respondê-la.
+business_unknown_user = O bot ainda não conhece este cliente, então não consegue respondê-lo.
+donate_title = Doação
+donate_description = Apoie o desenvolvimento do bot com { $amount } estrelas.
+donate_invalid_amount = Informe uma quantia entre <code>1</code> e <code>{ $max }</code> estrelas.
+donate_thanks = 💖 Obrigado, <b>{ $name }</b>, pela doação de <code>{ $amount }</code> ⭐!
+supporters_list = 💖 <b>Apoiadores</b>
+
+    { $supporters }
+supporters_empty = Ainda não há apoiadores, seja o primeiro com <code>/donate</code>!
+app_text = 📱 Abra o app pelo botão abaixo.
+app_button = Abrir app
+app_disabled = O web app não está configurado.
+app_pong = 🏓 Pong! Os dados do app foram validados.
+app_data_received = 📱 Dados recebidos do app (<code>{ $kind }</code>):
+    <code>{ $payload }</code>
+addsudo_usage = Uso: <code>.addsudo [@usuário|id]</code> ou respondendo a alguém.
+delsudo_usage = Uso: <code>.delsudo [@usuário|id]</code> ou respondendo a alguém.
+sudo_added = 🔑 <b>{ $name }</b> agora é um sudoer.
+sudo_already_added = <b>{ $name }</b> já é um sudoer.
+sudo_removed = <b>{ $name }</b> não é mais um sudoer.
+sudo_not_found = <b>{ $name }</b> não é um sudoer.
+sudo_owner = O dono não pode ser removido dos sudoers.
+sudo_configured = <b>{ $name }</b> está nos sudoers da configuração e só pode ser removido de lá.
+sudoers_list = 🔑 <b>Sudoers</b>
+
+    { $sudoers }
+sudoers_empty = Nenhum sudoer além do dono.
+giveaway_usage = Uso: <code>/giveaway [--winners N] [--channel @canal] &lt;prêmio&gt; &lt;duração&gt;</code>
+    A duração aceita <code>30s</code>, <code>10m</code>, <code>2h</code> ou <code>1d</code>.
+giveaway_channel_not_found = Canal não encontrado.
+giveaway_text = 🎁 <b>Sorteio</b>
+
+    <b>Prêmio</b>: { $prize }
+    <b>Ganhadores</b>: { $winners }
+    <b>Participantes</b>: { $entrants }
+    <b>Termina em</b>: { $ends_at }
+giveaway_requirement = {""}
+    <b>Requisito</b>: ser membro de { $channel }
+giveaway_join = 🎟 Participar
+giveaway_joined = Você está participando do sorteio.
+giveaway_already_joined = Você já está participando deste sorteio.
+giveaway_must_join = Você precisa ser membro de { $channel } para participar.
+giveaway_not_found = Este sorteio já terminou.
+giveaway_ended = 🎁 <b>Sorteio encerrado</b>
+
+    <b>Prêmio</b>: { $prize }
+    <b>Participantes</b>: { $entrants }
+giveaway_no_entrants = O sorteio terminou sem participantes.
+giveaway_winners = 🎉 <b>Ganhadores de</b> { $prize }
+
+    { $winners }
+
+    <b>Sorteio</b>: { $link }
+    <b>Semente</b>: <code>{ $seed }</code>
+    <i>Os participantes são ordenados por ID e embaralhados com a semente.</i>
+note_usage = Uso: <code>/note @usuário &lt;texto&gt;</code>, ou <code>/note &lt;texto&gt;</code> respondendo ao usuário.
+note_added = 📝 Nota adicionada sobre <b>{ $name }</b>.
+notes_usage = Uso: <code>/notes @usuário [clear]</code>, ou respondendo ao usuário.
+notes_list = 📝 <b>Notas sobre</b> { $name }
+
+    { $notes }
+notes_empty = Nenhuma nota sobre <b>{ $name }</b>.
+notes_cleared = { $count ->
+        [one] <code>{ $count }</code> nota sobre <b>{ $name }</b> foi removida.
+       *[other] <code>{ $count }</code> notas sobre <b>{ $name }</b> foram removidas.
+    }
+report_received = 🚨 <b>Denúncia em</b> { $chat }
+
+    <b>Denunciante</b>: { $reporter }
+    <b>Denunciado</b>: { $reported } (<code>{ $reported_id }</code>)
+    <b>Mensagem</b>: { $link }
+report_notes = {""}
+
+    📝 <b>Notas sobre o denunciado</b>
+    { $notes }
+report_sent = Denúncia enviada aos administradores.
+schedule_usage = Uso:
+    <code>.schedule &lt;duração&gt; &lt;texto&gt;</code>
+    <code>.schedule cron &lt;seg&gt; &lt;min&gt; &lt;hora&gt; &lt;dia&gt; &lt;mês&gt; &lt;dia da semana&gt; &lt;texto&gt;</code>
+    <code>.schedule list</code>
+    <code>.schedule del &lt;id&gt;</code>
+schedule_added = ⏰ Agendamento <code>{ $id }</code> criado.
+schedule_invalid = Este agendamento nunca seria executado.
+schedule_list = ⏰ <b>Agendamentos</b>
+
+    { $jobs }
+schedule_empty = Nenhum agendamento pendente.
+schedule_cancelled = Agendamento <code>{ $id }</code> cancelado.
+schedule_not_found = Agendamento <code>{ $id }</code> não encontrado.
+remind_usage = Uso: <code>.remind &lt;quando&gt; &lt;texto&gt;</code>
+    Quando pode ser <code>10m</code>, <code>18:30</code>, <code>15h</code>, <code>25/12 09:00</code>, <code>amanhã 9h</code>, <code>próxima sexta 15h</code> ou <code>toda segunda 8h</code>.
+remind_added = ⏰ Lembrete <code>{ $id }</code> para <b>{ $at }</b>.
+reminders_list = ⏰ <b>Lembretes</b>
+
+    { $reminders }
+reminders_empty = Nenhum lembrete pendente.
+delremind_usage = Uso: <code>.delremind &lt;id&gt;</code>
+remind_cancelled = Lembrete <code>{ $id }</code> cancelado.
+remind_not_found = Lembrete <code>{ $id }</code> não encontrado.
+remind_added_recurring = 🔁 Lembrete <code>{ $id }</code> recorrente, o próximo em <b>{ $at }</b>.
+editremind_usage = Uso: <code>.editremind &lt;id&gt; [quando] [texto]</code>
+remind_edited = ✏️ Lembrete <code>{ $id }</code> para <b>{ $at }</b>: { $text }
+remind_snoozed = 💤 Adiado para <b>{ $at }</b> (<code>{ $id }</code>).
+remind_snooze_expired = Esse lembrete já foi adiado ou é antigo demais.
+raid_supergroup_only = O modo raid só funciona em supergrupos.
+raid_mode_current = Modo raid: <code>{ $mode }</code>
+    Uso: <code>/raidmode on|off|auto</code>
+raid_mode_set = Modo raid definido como <code>{ $mode }</code>.
+raid_detected = 🚨 <b>Raid detectado!</b>
+
+    Novos membros serão silenciados e precisarão de aprovação pelos próximos <code>{ $minutes }</code> minutos.
+raid_disable = Desativar modo raid
+raid_disabled = ✅ Modo raid desativado.
+raid_ended = ✅ O modo raid terminou.
+raid_admins_only = Apenas administradores podem fazer isso.
+save_usage = Uso: <code>.save &lt;nome&gt;</code> respondendo a uma mensagem, ou <code>.save &lt;nome&gt; &lt;texto&gt;</code>
+
+    O texto aceita variáveis, como <code>{"{{"}user.first_name{"}}"}</code>, <code>{"{{"}user.mention{"}}"}</code>, <code>{"{{"}chat.title{"}}"}</code> e <code>{"{{"}date{"}}"}</code>, e condições, como <code>{"{{"}#if premium{"}}"}...{"{{"}else{"}}"}...{"{{"}/if{"}}"}</code>.
+template_invalid = Modelo inválido: <code>{ $error }</code>.
+get_usage = Uso: <code>.get &lt;nome&gt;</code>
+clear_usage = Uso: <code>.clear &lt;nome&gt;</code>
+note_saved = 📌 Nota <code>{ $name }</code> salva.
+note_replaced = 📌 Nota <code>{ $name }</code> substituída.
+note_not_found = Nota <code>{ $name }</code> não encontrada.
+note_media_missing = A mídia da nota <code>{ $name }</code> foi apagada das mensagens salvas.
+note_cleared = Nota <code>{ $name }</code> removida.
+notes_saved_list = 📌 <b>Notas</b>
+
+    { $notes }
+notes_saved_empty = Nenhuma nota salva.
+afk_set = 💤 Agora estou ausente.
+afk_set_reason = 💤 Agora estou ausente: <i>{ $reason }</i>
+afk_reply = 💤 Estou ausente há <b>{ $elapsed }</b>.
+afk_reply_reason = 💤 Estou ausente há <b>{ $elapsed }</b>: <i>{ $reason }</i>
+afk_back = 👋 Bem-vindo de volta! Você ficou ausente por <b>{ $elapsed }</b> { $messages ->
+        [0] e não recebeu mensagens.
+        [one] e recebeu <code>{ $messages }</code> mensagem.
+       *[other] e recebeu <code>{ $messages }</code> mensagens.
+    }
+config_usage = Uso: <code>.config &lt;plugin&gt;</code>
+    Plugins: { $plugins }
+config_plugin_not_found = O plugin <code>{ $plugin }</code> não tem configurações.
+config_values = ⚙️ <b>plugins.{ $plugin }</b>
+
+    { $values }
+config_reloaded = 🔄 Configuração recarregada.
+config_reload_unchanged = A configuração não mudou.
+config_reload_applied = <b>Aplicadas</b>: { $keys }
+config_reload_pending = <b>Aplicadas após reiniciar</b>: { $keys }
+config_reload_failed = Não foi possível recarregar a configuração, a atual foi mantida. O erro está no log.
+sudoku_usage = <b>Uso:</b> <code>sudoku [easy|medium|hard]</code>
+sudoku_given_cell = Essa casa faz parte do tabuleiro e não pode ser alterada.
+sudoku_wrong_digit = O número { $digit } não vai nessa casa!
+sudoku_lost = Você errou demais e perdeu o jogo.
+sudoku_solved = Parabéns, você completou o sudoku!
+user_client_down = ⚠️ A sessão da conta <code>{ $account }</code> foi encerrada pelo Telegram (<code>{ $error }</code>). Use <code>/relogin</code> aqui para entrar novamente.
+bot_client_down = ⚠️ A sessão do bot foi encerrada pelo Telegram (<code>{ $error }</code>). Use <code>.relogin</code> para entrar novamente.
+user_client_restored = ✅ A sessão da conta <code>{ $account }</code> foi restaurada.
+bot_client_restored = ✅ A sessão do bot foi restaurada.
+relogin_not_needed = A sessão está funcionando, não é preciso entrar novamente.
+relogin_code_sent = Código enviado para a conta. Envie <code>/relogin 1 2 3 4 5</code>, com espaços entre os dígitos para o Telegram não invalidá-lo.
+relogin_password_required = A conta tem verificação em duas etapas. Envie <code>/relogin &lt;senha&gt;</code>.
+relogin_not_started = Nenhum login em andamento, envie <code>/relogin</code> primeiro.
+relogin_done = ✅ Sessão restaurada com sucesso!
+relogin_failed = Não foi possível entrar novamente: <code>{ $error }</code>
+chess_illegal_move = Movimento inválido.
+chess_draw_offered = Empate proposto, aguardando o oponente aceitar.
+chess_resigned = Você desistiu da partida.
+game_expired = ⌛ O jogo expirou por falta de jogadas.
+game_interrupted = ⏹️ O jogo foi interrompido porque o bot foi desligado.
+panic_report = 💥 <b>{ $count ->
+        [one] Uma tarefa entrou em pânico
+       *[other] { $count } tarefas entraram em pânico
+    }</b>:
+    {""}
+    { $panics }
+panic_line = • <code>{ $location }</code>: { $message }
+searching_album = Procurando as { $count } fotos do álbum...
+search_album_result = Resultados da pesquisa das { $count } fotos:
+    { $results }
+search_results = <b>Melhores resultados</b>:
+    { $results }
+search_match = { $index }. <a href="{ $url }">{ $title }</a> — { $engine } · { $similarity }%
+search_match_no_score = { $index }. <a href="{ $url }">{ $title }</a> — { $engine }
+search_no_results = Nenhum resultado foi encontrado para a foto.
+transcribe_usage = <b>Uso</b>: <code>.transcribe</code> respondendo a uma mensagem de voz, <code>.transcribe on|off</code> para transcrever as recebidas neste chat, <code>.transcribe lang [idioma]</code> para definir o idioma delas, sem idioma para usar o do bot.
+transcribe_not_configured = A transcrição não está configurada, defina <code>transcriber.url</code> na configuração.
+transcribe_not_voice = Responda a uma mensagem de voz.
+transcribe_too_long = A mensagem de voz é longa demais, o máximo é de { $max } segundos.
+transcribing = Transcrevendo...
+transcription = 🗣 <b>Transcrição</b>:
+    <blockquote>{ $text }</blockquote>
+transcription_empty = Nenhuma fala foi reconhecida.
+transcribe_error = Falha ao transcrever a mensagem de voz.
+transcribe_enabled = As mensagens de voz recebidas neste chat serão transcritas.
+transcribe_disabled = As mensagens de voz recebidas neste chat não serão mais transcritas.
+transcribe_language = As mensagens de voz deste chat serão transcritas em <code>{ $language }</code>.
+transcribe_language_reset = As mensagens de voz deste chat serão transcritas no idioma do bot, <code>{ $language }</code>.
+wayback_usage = <b>Uso</b>: <code>.wayback &lt;url&gt;</code>, ou respondendo a uma mensagem com links.
+    Use <code>.wayback on|off</code> para arquivar todos os links enviados neste chat.
+wayback_archiving = Enviando para o Wayback Machine...
+wayback_archived = 🗄 <a href="{ $url }">{ $url }</a> arquivado em <a href="{ $snapshot }">{ $snapshot }</a>.
+wayback_queued = ⏳ Não foi possível arquivar <code>{ $url }</code> agora, vou tentar de novo e avisar.
+wayback_gave_up = ❌ Desisti de arquivar <code>{ $url }</code> após { $attempts } tentativas.
+wayback_enabled = Os links enviados neste chat serão arquivados no Wayback Machine.
+wayback_disabled = Os links enviados neste chat não serão mais arquivados.
+seen_usage = <b>Uso</b>: <code>.seen @usuario</code>, ou respondendo a uma mensagem.
+seen_status = 👁 <b>{ $name }</b> { $status }.
+seen_online = está online agora
+seen_offline = foi visto por último em { $date } (há { $ago })
+seen_recently = foi visto recentemente
+seen_last_week = foi visto na última semana
+seen_last_month = foi visto no último mês
+seen_hidden = esconde quando foi visto por último
+autodl_usage = <b>Uso</b>:
+    <code>.autodl add &lt;chat&gt; [tipos] [caminho|@canal] [-m &lt;tamanho&gt;]</code>
+    <code>.autodl del &lt;chat&gt;</code>
+    <code>.autodl list</code>
+    {""}
+    O chat é <code>here</code>, um <code>@usuario</code> ou um ID. Os tipos são <code>photo</code>, <code>video</code>, <code>gif</code>, <code>audio</code>, <code>voice</code>, <code>document</code> e <code>sticker</code>, separados por vírgulas, ou <code>all</code>. O tamanho máximo é como <code>500k</code>, <code>20m</code> ou <code>1g</code>.
+autodl_chat_not_found = Chat não encontrado.
+autodl_channel_not_found = Canal não encontrado.
+autodl_added = 📥 Regra de download automático adicionada.
+autodl_replaced = 📥 Regra de download automático substituída.
+autodl_removed = A regra de download automático deste chat foi removida.
+autodl_not_found = Este chat não tem uma regra de download automático.
+autodl_no_rules = Nenhuma regra de download automático.
+autodl_list = 📥 <b>Regras de download automático</b>:
+    { $rules }
+autodl_rule = • <b>{ $chat }</b>: { $kinds } para { $target }, até { $max_size }
+autodl_all_kinds = todas as mídias
+autodl_no_limit = qualquer tamanho
+autodl_summary = 📥 <b>Downloads automáticos de hoje</b>: { $files } { $files ->
+        [one] arquivo
+       *[other] arquivos
+    } ({ $size })
+autodl_summary_chat = • { $chat }: { $files } { $files ->
+        [one] arquivo
+       *[other] arquivos
+    } ({ $size })
+autodl_summary_skipped = Ignorados: { $duplicates } { $duplicates ->
+        [one] repetido
+       *[other] repetidos
+    }, { $too_big } grandes demais, { $over_budget } acima da cota e { $failed } { $failed ->
+        [one] falha
+       *[other] falhas
+    }.
+readstats_usage = <b>Uso</b>: <code>.readstats</code> para ver quanto tempo cada usuário leva para ler as mensagens privadas, ou <code>.readstats on|off</code> para registrar as leituras.
+readstats_enabled = As leituras das mensagens privadas serão registradas.
+readstats_disabled = As leituras das mensagens privadas não serão mais registradas.
+readstats_off = O registro das leituras está desativado. Use <code>.readstats on</code> para ativá-lo.
+readstats_empty = Nenhuma mensagem privada foi lida desde que o registro foi ativado.
+readstats_header = 📬 <b>Tempo de leitura das mensagens privadas</b> ({ $waiting } { $waiting ->
+        [one] mensagem aguardando
+       *[other] mensagens aguardando
+    }):
+readstats_line = • <a href="tg://user?id={ $id }">{ $name }</a>: { $reads } { $reads ->
+        [one] leitura
+       *[other] leituras
+    }, média de { $average }, mediana de { $median }, última em { $last_read }
+search_invalid_engine = <b>Uso</b>: <code>.rs [--engine saucenao|yandex|tineye]</code>, respondendo a uma foto.
+search_engine_unavailable = O SauceNAO precisa de uma chave de API na configuração.
+search_album_item_error = { $index }. Ocorreu um erro ao procurar a foto.
+info_output_file = Este arquivo contém as informações sobre mim e meu host.
+i18n_usage = Uso:
+    <code>.i18n set &lt;locale&gt; &lt;chave&gt; &lt;valor&gt;</code>
+    <code>.i18n reset &lt;chave&gt;</code>
+    <code>.i18n export [locale]</code>
+    <code>.i18ncheck</code>
+    <code>.reloadlocales</code>
+    {""}
+    O valor segue o formato Fluent, com os argumentos como <code>{"{ $nome }"}</code>.
+i18n_locale_not_found = O locale <code>{ $locale }</code> não existe.
+i18n_key_not_found = A chave <code>{ $key }</code> não existe.
+i18n_invalid_value = O texto não é válido no formato Fluent: <code>{ $error }</code>
+i18n_set = A chave <code>{ $key }</code> de <code>{ $locale }</code> foi alterada.
+i18n_reset = A chave <code>{ $key }</code> voltou ao texto original.
+i18n_not_overridden = A chave <code>{ $key }</code> não foi alterada.
+i18n_exported = Este arquivo contém o locale { $locale } com as alterações.
+i18n_check_ok = ✅ Todos os locales têm as mesmas chaves de <code>{ $locale }</code>.
+i18n_check_header = 🔎 <b>Chaves comparadas com</b> <code>{ $locale }</code>:
+i18n_check_locale = <b>{ $locale }</b>: { $missing } faltando, { $extra } a mais
+i18n_check_missing = Faltando: { $keys }
+i18n_check_extra = A mais: { $keys }
+i18n_reloaded = 🔄 { $count ->
+        [one] Locale recarregado
+       *[other] { $count } locales recarregados
+    }: { $locales }
+i18n_reload_failed = Falha ao recarregar os locales, os atuais foram mantidos: <code>{ $error }</code>
+cmd_usage = Uso:
+    <code>.cmd [list]</code>
+    <code>.cmd disable &lt;comando&gt;</code>
+    <code>.cmd enable &lt;comando&gt;</code>
+    <code>.cmd rename &lt;comando&gt; &lt;novo nome&gt;</code>
+    <code>.cmd reset &lt;comando&gt;</code>
+    <code>.cmd prefixes [prefixo...]</code>
+    {""}
+    Sem prefixos, os padrões são restaurados.
+cmd_list = ⌨️ <b>Comandos</b>: { $count }
+    <b>Prefixos</b>: { $prefixes }
+    {""}
+    { $commands }
+cmd_none_changed = Nenhum comando foi renomeado ou desativado.
+cmd_entry_renamed = • <code>{ $name }</code> → <code>{ $new_name }</code>
+cmd_entry_disabled = • <code>{ $name }</code> (desativado)
+cmd_not_found = O comando <code>{ $name }</code> não existe.
+cmd_enabled = ✅ O comando <code>{ $name }</code> foi ativado.
+cmd_disabled = 🚫 O comando <code>{ $name }</code> foi desativado.
+cmd_already_enabled = O comando <code>{ $name }</code> já está ativado.
+cmd_already_disabled = O comando <code>{ $name }</code> já está desativado.
+cmd_protected = O comando <code>{ $name }</code> não pode ser desativado.
+cmd_invalid_name = O nome <code>{ $name }</code> não é válido, use apenas letras, números e <code>_</code>.
+cmd_renamed = O comando <code>{ $name }</code> agora é <code>{ $new_name }</code>.
+cmd_name_taken = Já existe um comando chamado <code>{ $new_name }</code>.
+cmd_reset = O comando <code>{ $name }</code> voltou ao original.
+cmd_not_changed = O comando <code>{ $name }</code> não foi alterado.
+cmd_prefixes_set = Os prefixos agora são { $prefixes }.
+weather_usage = <b>Uso</b>: <code>/weather &lt;cidade&gt;</code>
+weather_not_found = Não encontrei a cidade <b>{ $city }</b>.
+weather_error = Ocorreu um erro ao buscar a previsão do tempo.
+weather_expired = Esta previsão expirou, use /weather novamente.
+weather_updated = Previsão atualizada!
+weather_card = 📍 <b>{ $place }</b>
+    {""}
+    { $emoji } { $condition }, <b>{ $temperature }°C</b> (sensação de { $feels_like }°C)
+    💧 Umidade: { $humidity }%
+    💨 Vento: { $wind_speed } km/h
+    {""}
+    <b>Previsão</b>:
+    { $forecast }
+weather_day = • { $date }: { $emoji } { $condition }, { $min }°C a { $max }°C, { $precipitation } de chance de chuva
+weather_clear = Céu limpo
+weather_partly_cloudy = Parcialmente nublado
+weather_overcast = Nublado
+weather_fog = Neblina
+weather_drizzle = Garoa
+weather_rain = Chuva
+weather_snow = Neve
+weather_showers = Pancadas de chuva
+weather_thunderstorm = Tempestade
+weather_unknown = Tempo desconhecido
+language_name = 🇧🇷 Português
+language_current = O idioma deste chat é <b>{ $language }</b>. Escolha outro:
+language_default = Usar o idioma padrão
+language_admins_only = Apenas os administradores podem escolher o idioma deste chat.
+dl_no_url = Envie ou responda a um link para baixar.
+dl_starting = Iniciando o download...
+dl_progress = ⬇️ Baixando: { $percent } ({ $speed }, faltam { $eta })
+dl_uploading = Enviando o arquivo...
+dl_error = Ocorreu um erro ao baixar a mídia.
+dl_caption = <a href="{ $url }">{ $title }</a>
+game_settings = Símbolos do jogo da velha neste chat: { $symbols }
+
+    Use <code>/gamesettings symbols &lt;jogador 1&gt; &lt;jogador 2&gt; &lt;vazio&gt;</code> para alterá-los ou <code>/gamesettings reset</code> para voltar aos padrões.
+game_settings_usage = Uso: <code>/gamesettings symbols &lt;jogador 1&gt; &lt;jogador 2&gt; &lt;vazio&gt;</code>, com emojis curtos ou emojis personalizados.
+game_settings_repeated = Os símbolos devem ser diferentes entre si.
+game_settings_updated = Os símbolos do jogo da velha agora são: { $symbols }
+game_settings_reset = Os símbolos do jogo da velha voltaram aos padrões.
+invalid_callback = Este botão não é válido.
+game_wrong_message = Este botão não pertence à mensagem do jogo.
+trivia_pack_not_found = O pacote <code>{ $pack }</code> não existe.
+
+    <b>Pacotes</b>: { $packs }.
+trivia_error = Não foi possível obter as perguntas, tente novamente mais tarde.
+trivia_answered = Resposta registrada!
+trivia_already_answered = Você já respondeu esta pergunta.
+trivia_round_over = O tempo desta pergunta já acabou.
+trivia_over = <b>Fim de jogo!</b>
+browse_header = <b>Histórico de</b> { $chat }
+browse_empty = {""}
+
+    Nenhuma mensagem encontrada.
+browse_media = <i>[mídia]</i>
+browse_invalid_date = Data inválida, use o formato dd/mm ou dd/mm/aaaa.
+browse_chat_not_found = Chat não encontrado.
+browse_expired = Esta navegação expirou, use o comando novamente.
+dlm_no_media = Responda a uma mensagem com mídia para salvá-la.
+dlm_downloading = Salvando a mídia...
+dlm_error = Ocorreu um erro ao salvar a mídia.
+dlm_saved = Mídia salva em <code>{ $path }</code> ({ $size }).
+trace_no_forward = Responda a uma mensagem encaminhada, ou passe o link dela, para rastrear sua origem.
+trace_header = <b>Origem da mensagem</b>
+    {""}
+trace_date = <b>Data original</b>: <code>{ $date }</code>
+trace_user = <b>Usuário</b>: { $name } [<code>{ $id }</code>]
+trace_chat = <b>Chat</b>: { $name } [<code>{ $id }</code>]
+trace_unknown = <i>inacessível</i>
+trace_link = <b>Link</b>: { $link }
+trace_link_private = <b>Link</b>: <i>privado, você não participa do canal.</i>
+trace_hidden = <b>Remetente</b>: { $name } <i>(a conta oculta o link nos encaminhamentos)</i>
+trace_author = <b>Assinatura</b>: { $author }
+trace_imported = <i>Mensagem importada de outro aplicativo, a data é a original.</i>
+usernames_usage = <b>Uso</b>: <code>.usernames check &lt;usernames&gt;</code> (ou respondendo a uma lista ou arquivo), <code>.usernames watch &lt;username&gt;</code>, <code>.usernames unwatch &lt;username&gt;</code>, <code>.usernames list</code>.
+usernames_too_many = Você só pode verificar até { $max } usernames de uma vez.
+usernames_file_too_big = O arquivo é grande demais para ser uma lista de usernames.
+usernames_checking = Verificando { $count } usernames...
+usernames_result = <b>Usernames</b>: { $free } de { $count } livres.
+
+    { $usernames }
+usernames_status_free = 🟢 <code>@{ $username }</code>
+usernames_status_taken = 🔴 <code>@{ $username }</code>
+usernames_status_for_sale = 💎 <code>@{ $username }</code> <i>(à venda no Fragment)</i>
+usernames_status_invalid = ⚪ <code>{ $username }</code> <i>(inválido)</i>
+usernames_status_error = ⚠️ <code>@{ $username }</code> <i>(falha ao verificar)</i>
+usernames_watched = Você será avisado quando <code>@{ $username }</code> ficar livre.
+usernames_already_watched = Este username já está sendo monitorado.
+usernames_unwatched = <code>@{ $username }</code> não está mais sendo monitorado.
+usernames_not_watched = Este username não está sendo monitorado.
+usernames_list = <b>Usernames monitorados</b>:
+
+    { $usernames }
+usernames_list_empty = Nenhum username está sendo monitorado.
+usernames_free = 🟢 <b>Username livre</b>: <code>@{ $username }</code>
+reposts_usage = <b>Uso</b>: <code>/noreposts [on|off]</code>, <code>/noreposts delete|flag</code>, <code>/noreposts days &lt;1-90&gt;</code>, <code>/noreposts threshold &lt;50-100&gt;</code>, <code>/noreposts exempt on|off</code>.
+reposts_settings = <b>Anti-repost</b>: { $enabled }
+    <b>Ação</b>: <code>{ $action }</code>
+    <b>Janela</b>: { $days } dias
+    <b>Similaridade mínima</b>: { $threshold }%
+    <b>Admins isentos</b>: { $exempt }
+reposts_on = ativado
+reposts_off = desativado
+reposts_flagged = ♻️ Esta imagem já foi postada <a href="{ $link }">aqui</a>.
+reposts_flagged_no_link = ♻️ Esta imagem já foi postada neste chat.
+nsfw_not_configured = A detecção de conteúdo adulto não está configurada.
+nsfw_usage = <b>Uso</b>: <code>/nsfw [on|off]</code>, <code>/nsfw delete|flag</code>, <code>/nsfw threshold &lt;1-100&gt;</code>.
+nsfw_settings = <b>Detecção de conteúdo adulto</b>: { $enabled }
+    <b>Ação</b>: <code>{ $action }</code>
+    <b>Pontuação mínima</b>: { $threshold }
+nsfw_deleted = 🔞 Uma foto de { $sender } foi removida por conteúdo adulto (pontuação { $score }).
+nsfw_flagged = 🔞 Esta foto de { $sender } pode ter conteúdo adulto (pontuação { $score }).
+nsfw_restore = ♻️ Restaurar
+nsfw_dismiss = ✅ Falso positivo
+nsfw_restored = Foto de { $sender } restaurada por um administrador.
+nsfw_restored_notice = A foto foi restaurada.
+nsfw_appeal_expired = Esta foto não pode mais ser restaurada.
+nsfw_admins_only = Só os administradores podem fazer isso.
+search_animated_sticker = Figurinhas animadas (.tgs) ainda não podem ser pesquisadas.
+search_convert_error = Não foi possível extrair uma imagem desta mídia.
+links_not_configured = O verificador de links não está configurado.
+links_usage = <b>Uso</b>: <code>/scanlinks [on|off|delete|flag]</code>.
+links_settings = <b>Verificação de links</b>
+
+    <b>Ativada</b>: { $enabled }.
+    <b>Ação</b>: <code>{ $action }</code>.
+    <b>Serviço</b>: { $provider }.
+links_deleted = A mensagem de { $sender } foi apagada por conter um link malicioso: <code>{ $url }</code> ({ $details }).
+links_flagged = ⚠️ Este link é malicioso, não abra: <code>{ $url }</code> ({ $details }).
+links_suspicious = ⚠️ Este link parece suspeito, tome cuidado: <code>{ $url }</code> ({ $details }).
+scanurl_usage = <b>Uso</b>: <code>.scanurl &lt;url&gt;</code>, ou responda a uma mensagem com links.
+scanurl_scanning = Verificando no { $provider }...
+scanurl_result = <b>Resultado do { $provider }</b>
+
+    { $results }
+scanurl_clean = ✅ <code>{ $url }</code>: nada encontrado.
+scanurl_unknown = ❔ <code>{ $url }</code>: ainda desconhecido, tente novamente mais tarde.
+scanurl_suspicious = ⚠️ <code>{ $url }</code>: suspeito ({ $details }).
+scanurl_malicious = ⛔ <code>{ $url }</code>: malicioso ({ $details }).
+scanurl_error = ❌ <code>{ $url }</code>: ocorreu um erro ao verificar.
+sed_invalid_flag = A flag <code>{ $flag }</code> não existe. Use <code>g</code>, <code>i</code>, <code>m</code> ou <code>x</code>.
+sed_invalid_pattern = O padrão é inválido:
+    <pre>{ $error }</pre>
+sed_timeout = A substituição demorou demais.
+sed_no_match = O padrão não foi encontrado na mensagem.
+calc_usage = <b>Uso</b>: <code>.calc &lt;expressão&gt;</code>, ou responda a uma mensagem com a expressão.
+calc_result = <code>{ $expression }</code> = <code>{ $result }</code>
+calc_invalid = A expressão é inválida perto de <code>{ $token }</code>.
+calc_invalid_end = A expressão terminou antes do esperado.
+calc_unknown_function = A função <code>{ $function }</code> não existe.
+calc_too_deep = A expressão tem parênteses demais.
+calc_undefined = O resultado da expressão não é definido.
+tr_usage = <b>Uso</b>: <code>.tr [idioma] &lt;texto&gt;</code>, ou responda a uma mensagem.
+tr_result = <b>{ $source }</b> → <b>{ $target }</b>
+    { $text }
+tr_error = Ocorreu um erro ao traduzir o texto.
+virustotal_scanning = 🔎 Verificando no VirusTotal...
+virustotal_clean = 🛡 <b>VirusTotal</b>: nenhuma ameaça (<a href="{ $url }">0/{ $total }</a>).
+virustotal_suspicious = ⚠️ <b>VirusTotal</b>: suspeito (<a href="{ $url }">{ $detected }/{ $total }</a>).
+virustotal_malicious = ⛔ <b>VirusTotal</b>: malicioso (<a href="{ $url }">{ $detected }/{ $total }</a>).
+virustotal_queued = ⏳ <b>VirusTotal</b>: a análise ainda não terminou, veja o <a href="{ $url }">relatório</a> mais tarde.
+virustotal_unknown = ❔ <b>VirusTotal</b>: arquivo desconhecido e grande demais para ser enviado.
+virustotal_error = ❌ <b>VirusTotal</b>: ocorreu um erro ao verificar o arquivo.
+doctor_running = Executando o diagnóstico...
+doctor_report = <b>Diagnóstico</b>
+
+    { $checks }
+
+    <b>{ $passed }</b> de <b>{ $total }</b> verificações passaram.
+doctor_locales = Traduções
+doctor_storage = Armazenamento
+doctor_ffmpeg = ffmpeg
+doctor_ytdl = yt-dlp
+doctor_screenshot = Capturas de tela
+doctor_bot = Sessão do bot
+doctor_user = Sessão do usuário
+doctor_disk = Espaço em disco
+doctor_bus = Fila entre os clientes
+pdf_usage = Use <code>.pdf &lt;n&gt;</code> para as últimas mensagens, <code>.pdf &lt;início&gt;-&lt;fim&gt;</code> para as mensagens entre dois IDs, ou responda a uma mensagem para exportar a partir dela.
+pdf_too_many = Só é possível exportar até { $max } mensagens de uma vez.
+pdf_collecting = Coletando as mensagens...
+pdf_rendering = Gerando o PDF com { $count } { $count ->
+        [one] mensagem
+       *[other] mensagens
+    }...
+pdf_no_messages = Nenhuma mensagem encontrada para exportar.
+pdf_title = { $chat } — exportado em { $date }
+pdf_media = [mídia]
+pdf_caption = Exportação de <b>{ $chat }</b>, com { $count } { $count ->
+        [one] mensagem
+       *[other] mensagens
+    }.
+flood_wait = Aguardando <code>{ $seconds }</code> segundos para continuar...
+old_message = Esta mensagem é muito antiga.
+info_updated = Informações atualizadas com sucesso!
+
+i_dont_have_perms = Eu não tenho permissões suficientes para fazer isso.
+you_dont_have_perms = Você não tem permissões suficientes para fazer isso.
@@ -0,0 +1,329 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the channel between the clients.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{sync::Notify, time::Instant};
+
+use crate::Message;
+
+/// The number of priority levels.
+const LEVELS: usize = 3;
+
+/// How many times a waiting message can be passed over by the higher levels
+/// before it goes first.
+const MAX_SKIPPED: u32 = 4;
+
+/// How soon a message is handled, relative to the others waiting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Bulk work, such as the scheduled jobs and the notifications.
+    ///
+    /// When its level is full, the oldest message waiting is dropped instead
+    /// of waiting for room.
+    Low,
+    /// Any other message.
+    #[default]
+    Normal,
+    /// Interactive responses, someone waiting on them.
+    High,
+}
+
+impl Priority {
+    /// The levels, the highest first.
+    pub const ALL: [Self; LEVELS] = [Self::High, Self::Normal, Self::Low];
+
+    /// Returns the index of the level, the highest first.
+    fn index(self) -> usize {
+        match self {
+            Self::High => 0,
+            Self::Normal => 1,
+            Self::Low => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::High => write!(f, "high"),
+            Self::Normal => write!(f, "normal"),
+            Self::Low => write!(f, "low"),
+        }
+    }
+}
+
+/// Why a message was not sent between the clients.
+#[derive(Debug)]
+pub enum SendError {
+    /// Its level stayed full for longer than the send timeout.
+    Busy,
+    /// The receiver was dropped, or the channel closed on shutdown.
+    Closed,
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Busy => write!(f, "the channel between the clients is full"),
+            Self::Closed => write!(f, "the channel between the clients is closed"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// Creates a channel between the clients, each level holding up to `capacity`
+/// messages and the senders waiting up to `send_timeout` for room.
+pub fn channel(capacity: usize, send_timeout: Duration) -> (Sender, Receiver) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {
+            levels: Default::default(),
+            skipped: [0; LEVELS],
+            dropped: 0,
+            senders: 1,
+            closed: false,
+        }),
+        queued: Notify::new(),
+        freed: Notify::new(),
+        capacity: capacity.max(1),
+        send_timeout,
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+            account: None,
+        },
+        Receiver { shared },
+    )
+}
+
+/// The state shared by the ends of the channel.
+struct Shared {
+    /// The waiting messages.
+    queue: Mutex<Queue>,
+    /// Wakes the receiver when a message is queued or the senders are gone.
+    queued: Notify,
+    /// Wakes the senders waiting for room.
+    freed: Notify,
+    /// The most messages waiting in each level.
+    capacity: usize,
+    /// How long a sender waits for room.
+    send_timeout: Duration,
+}
+
+impl Shared {
+    /// Locks the waiting messages.
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().expect("failed to lock the channel")
+    }
+}
+
+/// The waiting messages.
+struct Queue {
+    /// The messages of each level, the highest first, in the order they were sent.
+    levels: [VecDeque<Message>; LEVELS],
+    /// How many times the next message of each level was passed over.
+    skipped: [u32; LEVELS],
+    /// How many low priority messages were dropped to make room.
+    dropped: u64,
+    /// How many senders are alive.
+    senders: usize,
+    /// Whether the receiver was dropped, or the channel closed on shutdown.
+    closed: bool,
+}
+
+impl Queue {
+    /// Takes the next message, by priority, unless a lower level was passed
+    /// over too many times.
+    fn take(&mut self) -> Option<Message> {
+        let waiting = |level: &usize| !self.levels[*level].is_empty();
+        let level = (0..LEVELS)
+            .filter(waiting)
+            .find(|level| self.skipped[*level] >= MAX_SKIPPED)
+            .or_else(|| (0..LEVELS).find(waiting))?;
+
+        for other in (0..LEVELS).filter(|other| *other != level) {
+            if !self.levels[other].is_empty() {
+                self.skipped[other] += 1;
+            }
+        }
+        self.skipped[level] = 0;
+
+        self.levels[level].pop_front()
+    }
+}
+
+/// The sender of the channel.
+pub struct Sender {
+    /// The state shared with the receiver.
+    shared: Arc<Shared>,
+    /// The user account the messages are sent from via the bot, if set.
+    account: Option<usize>,
+}
+
+impl Sender {
+    /// Sends a message at its priority.
+    ///
+    /// While its level is full, waits for room up to the send timeout, except
+    /// for the low priority ones, which replace the oldest message waiting.
+    pub async fn send(&self, mut message: Message) -> Result<(), SendError> {
+        if let Some(account) = self.account {
+            message = message.from_account(account);
+        }
+        let level = message.priority().index();
+        let deadline = Instant::now() + self.shared.send_timeout;
+
+        loop {
+            // Registered before looking, so the room made meanwhile is not missed.
+            let freed = self.shared.freed.notified();
+            tokio::pin!(freed);
+            freed.as_mut().enable();
+
+            let rejected = {
+                let mut queue = self.shared.lock();
+                if queue.closed {
+                    return Err(SendError::Closed);
+                }
+
+                let full = queue.levels[level].len() >= self.shared.capacity;
+                if full && level == Priority::Low.index() {
+                    queue.levels[level].pop_front();
+                    queue.dropped += 1;
+                    log::warn!("Dropped the oldest low priority message, the channel is full");
+                }
+
+                if queue.levels[level].len() < self.shared.capacity {
+                    queue.levels[level].push_back(message);
+                    None
+                } else {
+                    Some(message)
+                }
+            };
+
+            match rejected {
+                Some(rejected) => message = rejected,
+                None => {
+                    self.shared.queued.notify_one();
+                    return Ok(());
+                }
+            }
+
+            if tokio::time::timeout_at(deadline, freed).await.is_err() {
+                log::warn!(
+                    "Gave up sending a message after {:?}, the channel is full",
+                    self.shared.send_timeout
+                );
+                return Err(SendError::Busy);
+            }
+        }
+    }
+
+    /// Returns a sender whose via bot messages are sent from a user account.
+    pub fn for_account(&self, account: usize) -> Self {
+        let mut sender = self.clone();
+        sender.account = Some(account);
+
+        sender
+    }
+
+    /// Closes the channel, so no more messages are sent and the receiver ends
+    /// once the waiting ones are received.
+    pub fn close(&self) {
+        self.shared.lock().closed = true;
+        self.shared.queued.notify_one();
+        self.shared.freed.notify_waiters();
+    }
+
+    /// Returns how many messages are waiting in a level.
+    pub fn depth(&self, priority: Priority) -> usize {
+        self.shared.lock().levels[priority.index()].len()
+    }
+
+    /// Returns the most messages waiting in each level.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Returns how many low priority messages were dropped to make room.
+    pub fn dropped(&self) -> u64 {
+        self.shared.lock().dropped
+    }
+}
+
+impl Clone for Sender {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+            account: self.account,
+        }
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut queue = self.shared.lock();
+        queue.senders -= 1;
+
+        if queue.senders == 0 {
+            drop(queue);
+            self.shared.queued.notify_one();
+        }
+    }
+}
+
+/// The receiver of the channel.
+///
+/// The messages are received by priority, in the order they were sent within
+/// a level. So the bulk work is not starved, a message passed over too many
+/// times goes first.
+pub struct Receiver {
+    /// The state shared with the senders.
+    shared: Arc<Shared>,
+}
+
+impl Receiver {
+    /// Receives the next message, waiting for one.
+    ///
+    /// Returns `None` once every sender is dropped, or the channel is closed,
+    /// and no message is left.
+    pub async fn recv(&mut self) -> Option<Message> {
+        loop {
+            {
+                let mut queue = self.shared.lock();
+                if let Some(message) = queue.take() {
+                    drop(queue);
+                    self.shared.freed.notify_waiters();
+
+                    return Some(message);
+                }
+                if queue.senders == 0 || queue.closed {
+                    return None;
+                }
+            }
+
+            self.shared.queued.notified().await;
+        }
+    }
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.freed.notify_waiters();
+    }
+}
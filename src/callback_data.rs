@@ -0,0 +1,48 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the signed callback data of the game keyboards.
+
+use std::sync::OnceLock;
+
+use crate::modules::signer::Signer;
+
+/// The signer of the callback data, set on startup.
+static SIGNER: OnceLock<Signer> = OnceLock::new();
+
+/// Sets the signer of the callback data, as the keyboards are built outside the handlers.
+///
+/// Its key gets a nonce of the run, so the keyboards of the games of an earlier
+/// run, whose IDs may be given again, cannot drive the new ones.
+pub fn init(signer: &Signer) {
+    if SIGNER.set(signer.for_run()).is_err() {
+        log::warn!("The callback data signer was already set");
+    }
+}
+
+/// Returns the signer of the callback data.
+fn signer() -> &'static Signer {
+    SIGNER.get().expect("callback data signer not set")
+}
+
+/// Signs the callback data of a button.
+///
+/// The MAC takes 33 bytes of the 64 Telegram allows, so the data must fit in 31.
+pub fn sign(data: impl AsRef<str>) -> String {
+    signer().sign(data.as_ref())
+}
+
+/// Verifies the callback data of a button, returning it without the MAC.
+///
+/// Returns `None` if the data was not signed by the bot, so it was crafted
+/// by hand or for another run.
+pub fn verify(data: &[u8]) -> Option<String> {
+    let data = std::str::from_utf8(data).ok()?;
+
+    signer().verify(data).map(str::to_string)
+}
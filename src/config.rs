@@ -8,30 +8,781 @@
 
 //! This module contains the configuration module.
 
-use std::{fs::File, io::Read};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind, IsTerminal},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
 
+use chrono::format::{Item, StrftimeItems};
+use clap::Parser;
 use ferogram::Result;
-use serde::{Deserialize, Serialize};
+use notify::{RecursiveMode, Watcher};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use tokio::sync::mpsc;
+
+use crate::{
+    modules::{
+        lyrics::Provider, reverse_search::Engine, sandbox::Wrapper, shutdown::Shutdown, url_scanner,
+    },
+    plugins,
+};
 
 const PATH: &str = "./assets/config.toml";
 
+/// The prefix of the environment variables overriding the config.
+const ENV_PREFIX: &str = "GRYMBB_";
+
+/// Separates the sections in the names of the environment variables, as in
+/// `GRYMBB_TELEGRAM__API_ID`.
+const ENV_SEPARATOR: &str = "__";
+
+/// The command-line flags, parsed once.
+static ARGS: LazyLock<Args> = LazyLock::new(Args::parse);
+
+/// How long the changes to the file are gathered before reloading, as editors
+/// write it in several steps.
+const RELOAD_DELAY: Duration = Duration::from_millis(500);
+
+/// The keys applied without a restart, besides the ones of the plugins.
+const LIVE_KEYS: [&str; 6] = [
+    "telegram.flood_sleep_threshold",
+    "telegram.max_catchup_age",
+    "telegram.sudoers",
+    "business.enabled",
+    "business.owners",
+    "virustotal.enabled",
+];
+
 /// Configuration.
 #[derive(Deserialize, Serialize)]
 pub struct Config {
     pub telegram: Telegram,
     pub bot: Bot,
-    pub user: User,
+    /// The user accounts, `[user]` for one or `[[user]]` for several, the first
+    /// being the main one.
+    #[serde(deserialize_with = "one_or_many")]
+    pub user: Vec<User>,
+    #[serde(default)]
+    pub bus: Bus,
+    #[serde(default)]
+    pub games: Games,
+    #[serde(default)]
+    pub trivia: Trivia,
+    #[serde(default)]
+    pub alerts: Alerts,
+    #[serde(default)]
+    pub folders: Folders,
+    #[serde(default)]
+    pub tags: Tags,
+    #[serde(default)]
+    pub usernames: Usernames,
+    #[serde(default)]
+    pub songs: Songs,
+    #[serde(default)]
+    pub ytdl: Ytdl,
+    #[serde(default)]
+    pub lyrics: Lyrics,
+    #[serde(default)]
+    pub reverse_search: ReverseSearch,
+    #[serde(default)]
+    pub digest: Digest,
+    #[serde(default)]
+    pub calendar: Calendar,
+    #[serde(default)]
+    pub mail: Mail,
+    #[serde(default)]
+    pub profile: Profile,
+    #[serde(default)]
+    pub business: Business,
+    #[serde(default)]
+    pub webapp: WebApp,
+    #[serde(default)]
+    pub raid: Raid,
+    #[serde(default)]
+    pub nsfw: Nsfw,
+    #[serde(default)]
+    pub transcriber: Transcriber,
+    #[serde(default)]
+    pub url_scanner: UrlScanner,
+    #[serde(default)]
+    pub virustotal: VirusTotal,
+    #[serde(default)]
+    pub media_cache: MediaCache,
+    #[serde(default)]
+    pub sandbox: Sandbox,
+    #[serde(default)]
+    pub doctor: Doctor,
+    #[serde(default)]
+    pub usage: Usage,
+    #[serde(default)]
+    pub plugins: HashMap<String, toml::Table>,
 }
 
 impl Config {
+    /// Loads the config.
+    ///
+    /// The values are taken, from the highest precedence to the lowest, from the
+    /// `--set` flags, the `GRYMBB_` environment variables, the config file and the
+    /// defaults. A missing file is allowed, so the config can come only from the
+    /// environment, as in containers.
     pub fn load() -> Result<Self> {
-        let mut file = File::open(PATH)?;
+        let mut table = match fs::read_to_string(&ARGS.config) {
+            Ok(content) => toml::from_str::<toml::Table>(&content)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                log::warn!("Config file {:?} not found", ARGS.config);
+                toml::Table::new()
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut vars = std::env::vars()
+            .filter_map(|(name, value)| {
+                let path = name.strip_prefix(ENV_PREFIX)?;
+                let path = path
+                    .split(ENV_SEPARATOR)
+                    .map(str::to_lowercase)
+                    .collect::<Vec<_>>();
+
+                Some((path, value))
+            })
+            .collect::<Vec<_>>();
+        // Applied in a stable order, as the environment has none.
+        vars.sort();
+        for (path, value) in vars {
+            set_value(&mut table, &path, &value)?;
+        }
+
+        for flag in ARGS.overrides.iter() {
+            let (key, value) = flag
+                .split_once('=')
+                .ok_or_else(|| format!("--set {} must be KEY=VALUE", flag))?;
+            let path = key
+                .trim()
+                .split('.')
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+
+            set_value(&mut table, &path, value)?;
+        }
+
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+
+    /// Returns the path of the config file.
+    pub fn path() -> &'static Path {
+        &ARGS.config
+    }
+
+    /// Checks if the first-time setup should run, either asked for with `--setup`
+    /// or when there is no config to start from and someone at the terminal to
+    /// answer it.
+    pub fn needs_setup() -> bool {
+        ARGS.setup
+            || (!ARGS.config.exists()
+                && ARGS.overrides.is_empty()
+                && !std::env::vars().any(|(name, _)| name.starts_with(ENV_PREFIX))
+                && io::stdin().is_terminal())
+    }
+
+    /// Checks the values the clients are built from, so a mistake is told at
+    /// startup instead of failing inside the connection.
+    ///
+    /// Returns every problem found at once.
+    pub fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self.telegram.api_id <= 0 {
+            errors
+                .push("telegram.api_id must be set, get it at https://my.telegram.org".to_string());
+        }
+        if self.telegram.api_hash.is_empty() {
+            errors.push(
+                "telegram.api_hash must be set, get it at https://my.telegram.org".to_string(),
+            );
+        } else if self.telegram.api_hash.len() != 32
+            || !self
+                .telegram
+                .api_hash
+                .chars()
+                .all(|c| c.is_ascii_hexdigit())
+        {
+            errors.push("telegram.api_hash must be 32 hexadecimal characters".to_string());
+        }
+
+        if self.bot.token.is_empty() {
+            errors.push("bot.token must be set, get it from @BotFather".to_string());
+        } else if !is_bot_token(&self.bot.token) {
+            errors.push(
+                "bot.token must look like 123456:ABC-DEF..., as given by @BotFather".to_string(),
+            );
+        }
+
+        if self.user.is_empty() {
+            errors.push("at least one [[user]] account must be set".to_string());
+        }
+        // The accounts are told apart only when there are several.
+        let users = self
+            .user
+            .iter()
+            .enumerate()
+            .map(|(index, user)| match self.user.len() {
+                1 => ("user".to_string(), user),
+                _ => (format!("user[{}]", index), user),
+            })
+            .collect::<Vec<_>>();
+
+        for (name, user) in users.iter() {
+            if user.phone_number.is_empty() {
+                errors.push(format!("{}.phone_number must be set", name));
+            } else if !is_phone_number(&user.phone_number) {
+                errors.push(format!(
+                    "{0}.phone_number must be in the international format, such as +5511912345678, not {1:?}",
+                    name, user.phone_number
+                ));
+            }
+        }
+
+        let mut session_files = vec![("bot".to_string(), &self.bot.session_file)];
+        session_files.extend(
+            users
+                .iter()
+                .map(|(name, user)| (name.clone(), &user.session_file)),
+        );
+        for (index, (name, session_file)) in session_files.iter().enumerate() {
+            if session_file.is_empty() {
+                errors.push(format!("{}.session_file must be set", name));
+                continue;
+            }
+
+            match Path::new(session_file).parent() {
+                Some(dir) if !dir.as_os_str().is_empty() && !dir.is_dir() => errors.push(format!(
+                    "{0}.session_file is in {1:?}, which does not exist",
+                    name, dir
+                )),
+                _ => {}
+            }
+            if let Some((other, _)) = session_files[..index]
+                .iter()
+                .find(|(_, other)| other == session_file)
+            {
+                errors.push(format!(
+                    "{0}.session_file and {1}.session_file must be different files",
+                    other, name
+                ));
+            }
+        }
+
+        let mut reconnections = vec![("bot".to_string(), &self.bot.reconnection)];
+        reconnections.extend(
+            users
+                .iter()
+                .map(|(name, user)| (name.clone(), &user.reconnection)),
+        );
+        for (name, reconnection) in reconnections {
+            if !(0.0..=1.0).contains(&reconnection.jitter) {
+                errors.push(format!(
+                    "{}.reconnection.jitter must be between 0 and 1",
+                    name
+                ));
+            }
+            if reconnection.base_delay > reconnection.max_delay {
+                errors.push(format!(
+                    "{}.reconnection.base_delay must not be greater than max_delay",
+                    name
+                ));
+            }
+        }
+
+        if self.bus.capacity == 0 {
+            errors.push("bus.capacity must be greater than 0".to_string());
+        }
+
+        if !is_time_format(&self.profile.clock_format) {
+            errors.push(format!(
+                "profile.clock_format must be a strftime format, such as \"%H:%M\", not {:?}",
+                self.profile.clock_format
+            ));
+        }
+
+        if !errors.is_empty() {
+            return Err(format!("Invalid config:\n- {}", errors.join("\n- ")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the sections of the plugins, filling the defaults and
+    /// validating the values.
+    pub fn plugin_settings(&self) -> Result<PluginSettings> {
+        let mut sections = HashMap::new();
+        let mut errors = Vec::new();
+
+        for schema in plugins::configs() {
+            let name = schema.name();
+            let section = self.plugins.get(name).cloned().unwrap_or_default();
+            let keys = schema.keys();
+
+            for key in section.keys() {
+                if !keys.iter().any(|k| k.name == key) {
+                    log::warn!("Unknown config key plugins.{}.{}", name, key);
+                }
+            }
+
+            let mut values = Vec::with_capacity(keys.len());
+            for key in keys {
+                let value = section
+                    .get(key.name)
+                    .cloned()
+                    .unwrap_or_else(|| key.default.clone());
+
+                if value.type_str() != key.default.type_str() {
+                    errors.push(format!(
+                        "plugins.{}.{} must be a {}",
+                        name,
+                        key.name,
+                        key.default.type_str()
+                    ));
+                } else if let Err(e) = (key.validate)(&value) {
+                    errors.push(format!("plugins.{}.{}: {}", name, key.name, e));
+                } else {
+                    values.push((key, value));
+                }
+            }
+
+            sections.insert(name, values);
+        }
+
+        if !errors.is_empty() {
+            return Err(format!("Invalid plugins config:\n{}", errors.join("\n")).into());
+        }
+
+        Ok(PluginSettings {
+            sections: Arc::new(RwLock::new(Arc::new(sections))),
+        })
+    }
+}
+
+/// The command-line flags.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// The config file.
+    #[arg(short, long, default_value = PATH)]
+    pub config: PathBuf,
+    /// Overrides a config key, such as `--set telegram.api_id=123`, over the
+    /// environment variables and the config file.
+    #[arg(short, long = "set", value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+    /// Runs the first-time setup, asking for the credentials and logging the
+    /// clients in, before starting.
+    #[arg(long)]
+    pub setup: bool,
+}
+
+/// Checks if a bot token looks like `<bot id>:<secret>`.
+pub fn is_bot_token(token: &str) -> bool {
+    match token.split_once(':') {
+        Some((id, secret)) => {
+            !id.is_empty()
+                && id.chars().all(|c| c.is_ascii_digit())
+                && secret.len() >= 30
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        None => false,
+    }
+}
+
+/// Checks if a phone number is in the international format, ignoring the
+/// spaces, dashes and parentheses.
+pub fn is_phone_number(phone_number: &str) -> bool {
+    let digits = phone_number
+        .trim_start_matches('+')
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')'))
+        .collect::<String>();
+
+    (7..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks if a format is a valid strftime format, as formatting the time with an
+/// invalid one panics.
+fn is_time_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Sets a value at a path of the config, creating the missing sections.
+///
+/// The value is read as TOML, such as `123`, `true` or `["a", "b"]`, falling back
+/// to a string. A value replacing a string is kept as one, so a secret made of
+/// digits stays a string.
+fn set_value(table: &mut toml::Table, path: &[String], value: &str) -> Result<()> {
+    let name = path.join(".");
+    let Some((key, sections)) = path.split_last() else {
+        return Ok(());
+    };
+    if path.iter().any(String::is_empty) {
+        log::warn!("Ignoring the invalid config override {}", name);
+        return Ok(());
+    }
+
+    let mut table = table;
+    let mut sections = sections.iter().peekable();
+    while let Some(section) = sections.next() {
+        let index = sections
+            .peek()
+            .and_then(|index| index.parse::<usize>().ok());
+        let value = table.entry(section.clone()).or_insert_with(|| match index {
+            Some(_) => toml::Value::Array(Vec::new()),
+            None => toml::Value::Table(toml::Table::new()),
+        });
+
+        // A single `[user]` becomes the first entry when another one is indexed.
+        if index.is_some() && value.is_table() {
+            let first = std::mem::replace(value, toml::Value::Array(Vec::new()));
+            *value = toml::Value::Array(vec![first]);
+        }
+
+        // The arrays of tables, such as `[[user]]`, are indexed, `user.1.phone_number`,
+        // the keys without an index going to the first entry. The index right after
+        // the last entry adds one.
+        let value = match value {
+            toml::Value::Array(array) => {
+                if index.is_some() {
+                    sections.next();
+                }
+                let index = index.unwrap_or(0);
+                if index == array.len() {
+                    array.push(toml::Value::Table(toml::Table::new()));
+                }
+
+                match array.get_mut(index) {
+                    Some(value) => value,
+                    None => {
+                        return Err(
+                            format!("Cannot override {}, there is no such entry", name).into()
+                        )
+                    }
+                }
+            }
+            value => value,
+        };
+
+        table = match value {
+            toml::Value::Table(section) => section,
+            _ => {
+                return Err(format!("Cannot override {}, its parent is not a section", name).into())
+            }
+        };
+    }
+
+    let value = match table.get(key) {
+        Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+        _ => toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut parsed| parsed.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string())),
+    };
+    table.insert(key.clone(), value);
+
+    Ok(())
+}
+
+/// A plugin that declares its own config section, `[plugins.<name>]`.
+pub trait PluginConfig {
+    /// Returns the name of the plugin.
+    fn name(&self) -> &'static str;
+
+    /// Returns the keys of the section.
+    fn keys(&self) -> Vec<Key>;
+}
+
+/// A config key of a plugin.
+pub struct Key {
+    /// The name of the key.
+    pub name: &'static str,
+    /// The default value, which also sets the type.
+    pub default: toml::Value,
+    /// Whether the value is masked when displayed.
+    pub secret: bool,
+    /// Validates the value, returning why it is invalid.
+    pub validate: fn(&toml::Value) -> std::result::Result<(), String>,
+}
+
+impl Key {
+    /// Creates a key with its default value.
+    pub fn new(name: &'static str, default: impl Into<toml::Value>) -> Self {
+        Self {
+            name,
+            default: default.into(),
+            secret: false,
+            validate: |_| Ok(()),
+        }
+    }
+
+    /// Masks the value when displayed.
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    /// Sets the validation of the value.
+    pub fn validate(mut self, f: fn(&toml::Value) -> std::result::Result<(), String>) -> Self {
+        self.validate = f;
+        self
+    }
+}
+
+/// A function applying the live keys of the config.
+type Listener = Box<dyn Fn(&Config) + Send + Sync>;
+
+/// The config, reloaded when its file changes or on demand.
+#[derive(Clone)]
+pub struct LiveConfig {
+    /// The current config.
+    config: Arc<RwLock<Arc<Config>>>,
+    /// The effective config of the plugins, updated in place.
+    plugin_settings: PluginSettings,
+    /// The functions applying the live keys, called on each reload.
+    listeners: Arc<RwLock<Vec<Listener>>>,
+}
+
+impl LiveConfig {
+    /// Creates a new `LiveConfig` instance, updating the given plugin settings on
+    /// each reload.
+    pub fn new(config: Config, plugin_settings: PluginSettings) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(Arc::new(config))),
+            plugin_settings,
+            listeners: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Applies the live keys of the current config with the given function, and
+    /// again on each reload.
+    pub fn on_reload(&self, f: impl Fn(&Config) + Send + Sync + 'static) {
+        f(&self.get());
+        self.listeners
+            .write()
+            .expect("failed to lock listeners")
+            .push(Box::new(f));
+    }
+
+    /// Returns the current config.
+    pub fn get(&self) -> Arc<Config> {
+        self.config.read().expect("failed to lock config").clone()
+    }
+
+    /// Loads the config file again, applying the keys that can change while
+    /// running: the ones of the plugins and the `LIVE_KEYS`.
+    ///
+    /// The current config is kept if the file is invalid.
+    pub fn reload(&self) -> Result<Reload> {
+        let config = Config::load()?;
+        config.validate()?;
+        let plugin_settings = config.plugin_settings()?;
+
+        let mut changed = Vec::new();
+        diff(
+            "",
+            &toml::Value::try_from(&*self.get())?,
+            &toml::Value::try_from(&config)?,
+            &mut changed,
+        );
+        let (applied, pending) = changed
+            .into_iter()
+            .partition(|key| key.starts_with("plugins.") || LIVE_KEYS.contains(&key.as_str()));
+
+        self.plugin_settings.replace(&plugin_settings);
+        for listener in self
+            .listeners
+            .read()
+            .expect("failed to lock listeners")
+            .iter()
+        {
+            listener(&config);
+        }
+        *self.config.write().expect("failed to lock config") = Arc::new(config);
+
+        Ok(Reload { applied, pending })
+    }
+
+    /// Starts reloading the config when its file changes.
+    pub fn watch(&self) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let config_changed = (event.kind.is_create() || event.kind.is_modify())
+                    && event
+                        .paths
+                        .iter()
+                        .any(|path| path.file_name() == ARGS.config.file_name());
+                if config_changed {
+                    let _ = tx.send(());
+                }
+            }
+        });
+        // The directory is watched, as some editors replace the file.
+        let watcher = watcher.and_then(|mut watcher| {
+            let dir = match ARGS.config.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+            Ok(watcher)
+        });
+        let watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log::warn!("Failed to watch the config file: {}", e);
+                return;
+            }
+        };
+
+        let config = self.clone();
+        Shutdown::shared().spawn(async move {
+            // The watcher stops when dropped.
+            let _watcher = watcher;
+
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DELAY).await;
+                while rx.try_recv().is_ok() {}
+
+                match config.reload() {
+                    Ok(reload) => reload.log(),
+                    Err(e) => log::error!("Failed to reload the config: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// The keys changed by a reload.
+pub struct Reload {
+    /// The keys applied right away.
+    pub applied: Vec<String>,
+    /// The keys that only apply after a restart.
+    pub pending: Vec<String>,
+}
+
+impl Reload {
+    /// Checks if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.pending.is_empty()
+    }
+
+    /// Logs the changed keys.
+    fn log(&self) {
+        if !self.applied.is_empty() {
+            log::info!("Reloaded the config keys: {}", self.applied.join(", "));
+        }
+        if !self.pending.is_empty() {
+            log::warn!(
+                "The changed config keys apply after a restart: {}",
+                self.pending.join(", ")
+            );
+        }
+    }
+}
+
+/// Collects the dotted paths of the values that differ between two configs.
+fn diff(path: &str, old: &toml::Value, new: &toml::Value, changed: &mut Vec<String>) {
+    match (old, new) {
+        (toml::Value::Table(old), toml::Value::Table(new)) => {
+            let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{0}.{1}", path, key)
+                };
+                let empty = toml::Value::Table(toml::Table::new());
+
+                diff(
+                    &path,
+                    old.get(key).unwrap_or(&empty),
+                    new.get(key).unwrap_or(&empty),
+                    changed,
+                );
+            }
+        }
+        (old, new) if old != new => changed.push(path.to_string()),
+        _ => {}
+    }
+}
+
+/// The keys and values of each plugin.
+type Sections = HashMap<&'static str, Vec<(Key, toml::Value)>>;
+
+/// The effective config values of the plugins.
+#[derive(Clone)]
+pub struct PluginSettings {
+    /// The keys and values of each plugin, replaced on a reload.
+    sections: Arc<RwLock<Arc<Sections>>>,
+}
+
+impl PluginSettings {
+    /// Returns the current keys and values.
+    fn sections(&self) -> Arc<Sections> {
+        self.sections
+            .read()
+            .expect("failed to lock plugin settings")
+            .clone()
+    }
+
+    /// Replaces the values with the ones of other settings, in every clone.
+    pub fn replace(&self, other: &PluginSettings) {
+        *self
+            .sections
+            .write()
+            .expect("failed to lock plugin settings") = other.sections();
+    }
+
+    /// Returns a value of a plugin.
+    ///
+    /// Panics if the key was not declared, as it is a programming error.
+    pub fn get<T: DeserializeOwned>(&self, plugin: &str, key: &str) -> T {
+        self.sections()
+            .get(plugin)
+            .and_then(|values| values.iter().find(|(k, _)| k.name == key))
+            .and_then(|(_, value)| value.clone().try_into().ok())
+            .unwrap_or_else(|| panic!("Undeclared config key plugins.{}.{}", plugin, key))
+    }
+
+    /// Returns the names of the plugins with a config section, sorted.
+    pub fn plugins(&self) -> Vec<&'static str> {
+        let mut names = self.sections().keys().copied().collect::<Vec<_>>();
+        names.sort();
+
+        names
+    }
 
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
+    /// Returns the values of a plugin to be displayed, with the secrets masked.
+    pub fn display(&self, plugin: &str) -> Option<Vec<(&'static str, String)>> {
+        self.sections().get(plugin).map(|values| {
+            values
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        toml::Value::String(s) if key.secret && !s.is_empty() => {
+                            "••••••".to_string()
+                        }
+                        value => value.to_string(),
+                    };
 
-        let config: Self = toml::from_str(&content)?;
-        Ok(config)
+                    (key.name, value)
+                })
+                .collect()
+        })
     }
 }
 
@@ -41,6 +792,36 @@ pub struct Telegram {
     pub api_id: i32,
     pub api_hash: String,
     pub flood_sleep_threshold: u32,
+    /// In minutes, how old a command can be to still run, `0` for any age.
+    #[serde(default = "default_max_catchup_age")]
+    pub max_catchup_age: u64,
+    /// The users allowed to use the commands, besides the ones added with `.sudo`.
+    #[serde(default)]
+    pub sudoers: Vec<i64>,
+}
+
+/// The default catch-up window, in minutes.
+fn default_max_catchup_age() -> u64 {
+    10
+}
+
+/// Deserializes a table or an array of tables.
+fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
 }
 
 /// Bot configuration.
@@ -49,6 +830,12 @@ pub struct Bot {
     pub token: String,
     pub catch_up: bool,
     pub session_file: String,
+    #[serde(default)]
+    pub secret: String,
+    #[serde(default)]
+    pub reconnection: Reconnection,
+    #[serde(default)]
+    pub via_bot: ViaBot,
 }
 
 /// User configuration.
@@ -57,4 +844,546 @@ pub struct User {
     pub phone_number: String,
     pub catch_up: bool,
     pub session_file: String,
+    #[serde(default)]
+    pub reconnection: Reconnection,
+}
+
+/// Reconnection policy configuration of a client.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Reconnection {
+    /// The attempts before giving up, ignored when `never_give_up` is set.
+    pub max_attempts: usize,
+    /// Keeps retrying forever instead of leaving a dead client.
+    pub never_give_up: bool,
+    /// How the delay grows between the attempts.
+    pub backoff: Backoff,
+    /// In seconds, the delay of the first attempt.
+    pub base_delay: u64,
+    /// In seconds, the longest delay between attempts.
+    pub max_delay: u64,
+    /// The fraction of the delay randomly added or removed, from 0 to 1.
+    pub jitter: f64,
+}
+
+impl Default for Reconnection {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            never_give_up: false,
+            backoff: Backoff::default(),
+            base_delay: 5,
+            max_delay: 300,
+            jitter: 0.2,
+        }
+    }
+}
+
+/// Delivery of the messages sent via the bot.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ViaBot {
+    /// In seconds, how long an attempt can take.
+    pub timeout: u64,
+    /// The attempts before the delivery fails, at most 5.
+    pub attempts: usize,
+}
+
+impl Default for ViaBot {
+    fn default() -> Self {
+        Self {
+            timeout: 30,
+            attempts: 3,
+        }
+    }
+}
+
+/// The channel between the clients.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Bus {
+    /// The most messages waiting in each priority level.
+    pub capacity: usize,
+    /// In seconds, how long a message waits for room before giving up.
+    pub send_timeout: u64,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self {
+            capacity: 10,
+            send_timeout: 10,
+        }
+    }
+}
+
+/// How the delay between reconnection attempts grows.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backoff {
+    /// The base delay times the attempt.
+    Linear,
+    /// The base delay doubled on each attempt.
+    #[default]
+    Exponential,
+}
+
+/// Games configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Games {
+    pub move_timeout: u64,
+    #[serde(default = "default_expire_after")]
+    pub expire_after: u64,
+}
+
+impl Default for Games {
+    fn default() -> Self {
+        Self {
+            move_timeout: 120,
+            expire_after: default_expire_after(),
+        }
+    }
+}
+
+/// The default inactivity before a game expires, in minutes.
+fn default_expire_after() -> u64 {
+    30
+}
+
+/// Trivia configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Trivia {
+    pub questions: usize,
+    pub answer_time: u64,
+    pub packs_dir: String,
+}
+
+impl Default for Trivia {
+    fn default() -> Self {
+        Self {
+            questions: 10,
+            answer_time: 20,
+            packs_dir: "./assets/trivia".to_string(),
+        }
+    }
+}
+
+/// Keyword alerts configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Alerts {
+    pub cooldown: u64,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self { cooldown: 60 }
+    }
+}
+
+/// Chat folders configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Folders {
+    pub sync_interval: u64,
+}
+
+impl Default for Folders {
+    fn default() -> Self {
+        Self { sync_interval: 60 }
+    }
+}
+
+/// Usernames checker configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Usernames {
+    pub delay: u64,
+    pub interval: u64,
+}
+
+impl Default for Usernames {
+    fn default() -> Self {
+        Self {
+            delay: 2,
+            interval: 30,
+        }
+    }
+}
+
+/// Saved messages tags configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Tags {
+    pub presets: Vec<String>,
+    pub page_size: usize,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            presets: vec!["ler".to_string(), "links".to_string(), "ideias".to_string()],
+            page_size: 10,
+        }
+    }
+}
+
+/// Songs configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Songs {
+    pub cache_ttl: u64,
+}
+
+impl Default for Songs {
+    fn default() -> Self {
+        Self { cache_ttl: 3600 }
+    }
+}
+
+/// yt-dlp configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Ytdl {
+    pub path: String,
+    pub output_dir: String,
+}
+
+impl Default for Ytdl {
+    fn default() -> Self {
+        Self {
+            path: "yt-dlp".to_string(),
+            output_dir: "./assets/downloads".to_string(),
+        }
+    }
+}
+
+/// Lyrics configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Lyrics {
+    pub provider: Provider,
+    pub page_length: usize,
+}
+
+impl Default for Lyrics {
+    fn default() -> Self {
+        Self {
+            provider: Provider::default(),
+            page_length: 3000,
+        }
+    }
+}
+
+/// Reverse image search configuration.
+#[derive(Deserialize, Serialize)]
+pub struct ReverseSearch {
+    pub engines: Vec<Engine>,
+    pub saucenao_key: String,
+    #[serde(default = "default_ffmpeg_path")]
+    pub ffmpeg_path: String,
+}
+
+impl Default for ReverseSearch {
+    fn default() -> Self {
+        Self {
+            engines: vec![Engine::SauceNao, Engine::Yandex, Engine::TinEye],
+            saucenao_key: String::new(),
+            ffmpeg_path: default_ffmpeg_path(),
+        }
+    }
+}
+
+fn default_ffmpeg_path() -> String {
+    "ffmpeg".to_string()
+}
+
+/// Daily digest configuration.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Digest {
+    #[serde(default)]
+    pub currency: Currency,
+    #[serde(default)]
+    pub rss: Rss,
+    #[serde(default)]
+    pub weather: DigestWeather,
+}
+
+/// Exchange rates widget configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Currency {
+    pub base: String,
+    pub targets: Vec<String>,
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self {
+            base: "USD".to_string(),
+            targets: vec!["BRL".to_string(), "EUR".to_string()],
+        }
+    }
+}
+
+/// RSS headlines widget configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Rss {
+    pub feeds: Vec<String>,
+    pub headlines: usize,
+}
+
+impl Default for Rss {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            headlines: 3,
+        }
+    }
+}
+
+/// Weather widget configuration.
+#[derive(Default, Deserialize, Serialize)]
+pub struct DigestWeather {
+    /// The place the forecast is shown for, none if empty.
+    pub place: String,
+}
+
+/// Calendar configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Calendar {
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub sync_interval: u64,
+    pub lead_times: Vec<u64>,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            username: None,
+            password: None,
+            sync_interval: 15,
+            lead_times: vec![60, 10],
+        }
+    }
+}
+
+/// Mail configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Mail {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub mailboxes: Vec<String>,
+    pub poll_interval: u64,
+    pub attachment_size_cap: usize,
+}
+
+impl Default for Mail {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 993,
+            username: String::new(),
+            password: String::new(),
+            mailboxes: vec!["INBOX".to_string()],
+            poll_interval: 60,
+            attachment_size_cap: 10 * 1024 * 1024,
+        }
+    }
+}
+
+/// Profile rotation configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Profile {
+    pub clock_format: String,
+    pub bios: Vec<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            clock_format: "%H:%M".to_string(),
+            bios: Vec::new(),
+        }
+    }
+}
+
+/// Business connections configuration.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Business {
+    pub enabled: bool,
+    pub owners: Vec<i64>,
+}
+
+/// Web app configuration.
+#[derive(Deserialize, Serialize)]
+pub struct WebApp {
+    pub url: String,
+    pub bind: String,
+    pub assets_dir: String,
+    pub max_age: u64,
+}
+
+impl Default for WebApp {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            bind: "127.0.0.1:8080".to_string(),
+            assets_dir: "./assets/webapp".to_string(),
+            max_age: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Raid detection configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Raid {
+    pub joins: usize,
+    pub window: u64,
+    pub duration: u64,
+}
+
+impl Default for Raid {
+    fn default() -> Self {
+        Self {
+            joins: 10,
+            window: 60,
+            duration: 15,
+        }
+    }
+}
+
+/// NSFW detection configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Nsfw {
+    pub url: String,
+    pub api_key: String,
+    pub file_field: String,
+    pub score_pointer: String,
+    pub normalized: bool,
+}
+
+impl Default for Nsfw {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            api_key: String::new(),
+            file_field: "image".to_string(),
+            score_pointer: "/nsfw".to_string(),
+            normalized: true,
+        }
+    }
+}
+
+/// Voice transcription configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Transcriber {
+    pub url: String,
+    pub api_key: String,
+    pub model: String,
+    pub max_duration: i32,
+    pub max_length: usize,
+}
+
+impl Default for Transcriber {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            api_key: String::new(),
+            model: "whisper-1".to_string(),
+            max_duration: 300,
+            max_length: 3500,
+        }
+    }
+}
+
+/// URL scanner configuration.
+#[derive(Deserialize, Serialize)]
+pub struct UrlScanner {
+    pub provider: url_scanner::Provider,
+    pub api_key: String,
+    pub cache_hours: u64,
+}
+
+impl Default for UrlScanner {
+    fn default() -> Self {
+        Self {
+            provider: url_scanner::Provider::default(),
+            api_key: String::new(),
+            cache_hours: 24,
+        }
+    }
+}
+
+/// VirusTotal file scanner configuration.
+#[derive(Deserialize, Serialize)]
+pub struct VirusTotal {
+    pub enabled: bool,
+    pub api_key: String,
+    pub max_upload_size: u64,
+}
+
+impl Default for VirusTotal {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_key: String::new(),
+            max_upload_size: 32,
+        }
+    }
+}
+
+/// Downloaded media cache configuration.
+#[derive(Deserialize, Serialize)]
+pub struct MediaCache {
+    pub max_size: usize,
+}
+
+impl Default for MediaCache {
+    fn default() -> Self {
+        Self { max_size: 32 }
+    }
+}
+
+/// Untrusted code sandbox configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Sandbox {
+    pub wrapper: Wrapper,
+    pub wrapper_args: Vec<String>,
+    pub container_runtime: String,
+    pub container_image: String,
+    pub timeout: u64,
+    pub max_output: usize,
+    pub memory_limit: u64,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            wrapper: Wrapper::default(),
+            wrapper_args: Vec::new(),
+            container_runtime: "docker".to_string(),
+            container_image: String::new(),
+            timeout: 30,
+            max_output: 64,
+            memory_limit: 2048,
+        }
+    }
+}
+
+/// Self-test configuration.
+#[derive(Deserialize, Serialize)]
+pub struct Doctor {
+    pub on_startup: bool,
+    pub min_free_space: u64,
+}
+
+impl Default for Doctor {
+    fn default() -> Self {
+        Self {
+            on_startup: true,
+            min_free_space: 1024,
+        }
+    }
+}
+
+/// Usage accounting configuration.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub budgets: HashMap<String, u64>,
 }
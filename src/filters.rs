@@ -8,27 +8,58 @@
 
 //! This module contains some custom filters.
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
 
 use ferogram::{filter, Filter};
-use grammers_client::{types::inline, Update};
+use grammers_client::{
+    grammers_tl_types as tl,
+    types::{inline, Chat, Media},
+    Client, Update,
+};
 
-const SUDOER_LIST: [i64; 1] = [1155717290];
+use crate::{
+    modules::{
+        afk::Afk, alerts::Alerts, business, commands::Commands, generated::Generated,
+        sudoers::Sudoers, transcriber, updates::Updates,
+    },
+    utils,
+};
+
+/// The minimum interval between two runs triggered by the same message.
+const RERUN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a message is tracked after its last run.
+const RUNS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The runs triggered by the messages, by account, scope, chat and message.
+type RunKey = (i64, &'static str, i64, i32);
+
+/// The last text seen of each message, and when it last triggered a run.
+static RUNS: LazyLock<Mutex<HashMap<RunKey, (String, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// Custom filter that checks if the user is a sudoer.
+///
+/// The sudoers are read from the shared store, managed at runtime.
 pub fn sudoers() -> impl Filter {
     filter::me.or(Arc::new(move |_client, update| async move {
+        let sudoers = Sudoers::shared();
+
         match update {
             Update::NewMessage(message) | Update::MessageEdited(message) => {
                 if let Some(sender) = message.sender() {
-                    SUDOER_LIST.contains(&sender.id())
+                    sudoers.contains(sender.id()).await
                 } else {
                     false
                 }
             }
             Update::CallbackQuery(query) => {
                 let sender = query.sender();
-                let value = SUDOER_LIST.contains(&sender.id());
+                let value = sudoers.contains(sender.id()).await;
 
                 if !value {
                     query
@@ -43,7 +74,7 @@ pub fn sudoers() -> impl Filter {
             }
             Update::InlineQuery(query) => {
                 let sender = query.sender();
-                let value = SUDOER_LIST.contains(&sender.id());
+                let value = sudoers.contains(sender.id()).await;
 
                 if !value {
                     query
@@ -64,12 +95,411 @@ pub fn sudoers() -> impl Filter {
     }))
 }
 
+/// Custom filter that skips the commands caught up on a restart, either stale
+/// or already processed, so destructive ones do not run twice.
+///
+/// Edits are only checked against the catch-up window, as they re-run commands.
+pub fn fresh(scope: &'static str) -> impl Filter {
+    Arc::new(move |_client, update| async move { is_fresh(scope, &update).await })
+}
+
+/// Custom filter like [`fresh`], scoped to the user account receiving the
+/// update, as each numbers the messages of the users and basic groups its own
+/// way, so a message ID seen by one says nothing about the others.
+pub fn fresh_for_account() -> impl Filter {
+    Arc::new(move |client: Client, update| async move {
+        let Some(account_id) = account_id(&client).await else {
+            return false;
+        };
+
+        is_fresh(&format!("user:{}", account_id), &update).await
+    })
+}
+
+/// Checks if the update is neither stale nor already processed in the scope.
+async fn is_fresh(scope: &str, update: &Update) -> bool {
+    let updates = Updates::shared();
+
+    match update {
+        Update::NewMessage(message) => {
+            !updates.is_stale(message.date())
+                && updates
+                    .process(scope, message.chat().id(), message.id())
+                    .await
+        }
+        Update::MessageEdited(message) => {
+            !updates.is_stale(message.edit_date().unwrap_or(message.date()))
+        }
+        _ => true,
+    }
+}
+
+/// Returns the ID of the account of the client.
+async fn account_id(client: &Client) -> Option<i64> {
+    match client.get_me().await {
+        Ok(me) => Some(me.id()),
+        Err(e) => {
+            log::error!("Failed to get the account of the client: {}", e);
+            None
+        }
+    }
+}
+
+/// Custom filter that skips the messages generated by the handlers, so the
+/// ones reacting to the account's own messages do not trigger on their output.
+pub fn not_generated() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) | Update::MessageEdited(message) => {
+                !Generated::shared().contains(message.chat().id(), message.id())
+            }
+            _ => true,
+        }
+    })
+}
+
+/// Custom filter that checks if the sender is an administrator of the group.
+pub fn admins() -> impl Filter {
+    Arc::new(move |client, update| async move {
+        let Update::NewMessage(message) = update else {
+            return false;
+        };
+        let (Chat::Group(_), Some(sender)) = (message.chat(), message.sender()) else {
+            return false;
+        };
+
+        match client.get_permissions(message.chat(), sender).await {
+            Ok(permissions) => permissions.is_creator() || permissions.is_admin(),
+            Err(e) => {
+                log::error!("Failed to get the permissions: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Custom filter that checks if the message matters while away: an outgoing
+/// message, or an incoming private message or mention.
+pub fn afk() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        let Update::NewMessage(message) = update else {
+            return false;
+        };
+
+        if !Afk::shared().is_away().await {
+            return false;
+        }
+
+        message.outgoing()
+            || message.mentioned()
+            || matches!(message.chat(), Chat::User(user) if !user.is_bot())
+    })
+}
+
+/// Custom filter that checks if the message is an incoming text matching one of
+/// the alert keywords, out of the muted chats and the ones the alerts are
+/// posted to.
+pub fn alert_match() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) if !message.outgoing() => {
+                let alerts = Alerts::shared();
+
+                !message.text().is_empty()
+                    && !alerts.is_ignored(message.chat().id()).await
+                    && alerts.find_match(message.text()).await.is_some()
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is an incoming voice note.
+pub fn incoming_voice() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                !message.outgoing()
+                    && message
+                        .media()
+                        .as_ref()
+                        .and_then(transcriber::voice_duration)
+                        .is_some()
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is an incoming message with a file,
+/// a photo, a document or a sticker.
+pub fn incoming_media() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                !message.outgoing()
+                    && matches!(
+                        message.media(),
+                        Some(Media::Photo(_) | Media::Document(_) | Media::Sticker(_))
+                    )
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is an incoming photo in a group.
+pub fn group_photo() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                !message.outgoing()
+                    && matches!(message.chat(), Chat::Group(_))
+                    && matches!(message.media(), Some(Media::Photo(_)))
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is an incoming message with URLs in a group.
+///
+/// The commands are left to their handlers and the photos to the photos handler.
+pub fn group_link() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                !message.outgoing()
+                    && matches!(message.chat(), Chat::Group(_))
+                    && !matches!(message.media(), Some(Media::Photo(_)))
+                    && !message.text().starts_with('/')
+                    && !utils::message_urls(&message).is_empty()
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message has a link and is not one of my
+/// commands.
+pub fn posted_link() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                !(message.outgoing() && Commands::shared().has_prefix(message.text()))
+                    && !utils::message_urls(&message).is_empty()
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message was forwarded to my saved messages.
+pub fn saved_forward() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                message.outgoing()
+                    && message.forward_header().is_some()
+                    && matches!(message.chat(), Chat::User(user) if user.is_self())
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that makes a router respond to edits, meant to be combined
+/// with the command filter of a `message_edited` handler.
+///
+/// New messages only have their text recorded. Edits pass when the text has
+/// changed, which skips the ones that only load a link preview, and when the
+/// message has not triggered a run in the last seconds, so long-running
+/// commands are not duplicated.
+///
+/// The `scope` tells the routers apart, the `new_message` and `message_edited`
+/// handlers of a router sharing it, and the runs are tracked per account.
+pub fn respond_to_edits(scope: &'static str) -> impl Filter {
+    Arc::new(move |client: Client, update| async move {
+        let (Update::NewMessage(message) | Update::MessageEdited(message)) = &update else {
+            return false;
+        };
+        let Some(account_id) = account_id(&client).await else {
+            return false;
+        };
+
+        let key = (account_id, scope, message.chat().id(), message.id());
+        let text = message.text().to_string();
+        let mut runs = RUNS.lock().expect("failed to lock runs");
+
+        if matches!(update, Update::NewMessage(_)) {
+            // Forgets the messages too old to be edited into a command again.
+            runs.retain(|_, (_, last_run)| last_run.elapsed() < RUNS_TTL);
+            runs.insert(key, (text, Instant::now()));
+            return true;
+        }
+
+        match runs.get(&key) {
+            Some((last_text, _)) if *last_text == text => false,
+            Some((_, last_run)) if last_run.elapsed() < RERUN_INTERVAL => false,
+            _ => {
+                runs.insert(key, (text, Instant::now()));
+                true
+            }
+        }
+    })
+}
+
+/// Custom filter that checks if the update is a business connection or message.
+pub fn business() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        matches!(
+            update,
+            Update::Raw(
+                tl::enums::Update::BotBusinessConnect(_)
+                    | tl::enums::Update::BotNewBusinessMessage(_)
+            )
+        )
+    })
+}
+
+/// Custom filter that remembers the senders seen by the bot with their access
+/// hash, so the business replies can address them. It never matches.
+pub fn seen_users() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        if let Update::NewMessage(message) = update {
+            if let Some(sender @ Chat::User(_)) = message.sender() {
+                business::remember_peer(sender.pack());
+            }
+        }
+
+        false
+    })
+}
+
+/// Custom filter that checks if the message is an outgoing private message to
+/// another user.
+pub fn outgoing_private() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => {
+                message.outgoing()
+                    && matches!(message.chat(), Chat::User(user) if !user.is_self() && !user.is_bot())
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the update tells that a user read my private
+/// messages.
+///
+/// The dispatcher has no handlers for the read history, so these come as raw
+/// updates.
+pub fn read_outbox() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        matches!(
+            update,
+            Update::Raw(tl::enums::Update::ReadHistoryOutbox(
+                tl::types::UpdateReadHistoryOutbox {
+                    peer: tl::enums::Peer::User(_),
+                    ..
+                }
+            ))
+        )
+    })
+}
+
+/// Custom filter that checks if the update is a payment pre-checkout query.
+///
+/// The dispatcher has no payment handlers, so these come as raw updates.
+pub fn precheckout_query() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        matches!(
+            update,
+            Update::Raw(tl::enums::Update::BotPrecheckoutQuery(_))
+        )
+    })
+}
+
+/// Custom filter that checks if the message is a successful payment.
+pub fn successful_payment() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => matches!(
+                message.action(),
+                Some(tl::enums::MessageAction::PaymentSentMe(_))
+            ),
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is the service message of users joining.
+pub fn new_members() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => matches!(
+                message.action(),
+                Some(
+                    tl::enums::MessageAction::ChatAddUser(_)
+                        | tl::enums::MessageAction::ChatJoinedByLink(_)
+                        | tl::enums::MessageAction::ChatJoinedByRequest
+                )
+            ),
+            _ => false,
+        }
+    })
+}
+
+/// Custom filter that checks if the message is the service message of a group
+/// becoming a supergroup.
+pub fn chat_migrated() -> impl Filter {
+    Arc::new(move |_client, update| async move {
+        match update {
+            Update::NewMessage(message) => matches!(
+                message.action(),
+                Some(
+                    tl::enums::MessageAction::ChatMigrateTo(_)
+                        | tl::enums::MessageAction::ChannelMigrateFrom(_)
+                )
+            ),
+            _ => false,
+        }
+    })
+}
+
 /// Custom `command` filter with prefixes to user instance.
+///
+/// The name and prefixes come from the command registry, so the command can be
+/// renamed or disabled at runtime.
 pub fn command(pat: &'static str) -> impl Filter {
-    filter::command_with(&[";", ",", "."], pat)
+    registered(vec![pat]).and(fresh_for_account())
 }
 
 /// Custom `commands` filter with prefixes to user instance.
+///
+/// The names and prefixes come from the command registry, so the commands can
+/// be renamed or disabled at runtime.
 pub fn commands(pats: &'static [&'static str]) -> impl Filter {
-    filter::commands_with(&[";", ",", "."], pats)
+    registered(pats.to_vec()).and(fresh_for_account())
+}
+
+/// Custom filter that checks if the message triggers one of the commands, as
+/// the registry currently names them.
+fn registered(names: Vec<&'static str>) -> impl Filter {
+    Commands::shared().register(&names);
+    let names = Arc::new(names);
+
+    Arc::new(move |_client, update| {
+        let names = Arc::clone(&names);
+
+        async move {
+            match update {
+                Update::NewMessage(message) | Update::MessageEdited(message) => {
+                    Commands::shared().matches(message.text(), &names)
+                }
+                _ => false,
+            }
+        }
+    })
 }
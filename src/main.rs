@@ -8,49 +8,186 @@
 
 //! This is the main module of the bot.
 
-use std::{ops::ControlFlow, time::Duration};
+use std::{ops::ControlFlow, sync::Arc, time::Duration};
 
 use ferogram::{Client, Context, Injector, Result};
 use grammers_client::{
+    session::{PackedChat, PackedType},
     types::{self, inline},
     ReconnectionPolicy,
 };
-use tokio::sync::mpsc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
 
+mod bus;
+mod callback_data;
 mod config;
 pub mod dump;
 mod filters;
 mod modules;
+mod output;
 mod plugins;
+mod setup;
 pub mod utils;
 
-use config::Config;
+pub use bus::{Priority, Receiver, SendError, Sender};
+use config::{Backoff, Config, LiveConfig, Reconnection, ViaBot};
 pub use dump::Dump;
-use modules::{games::GameManager, i18n::I18n};
+use modules::{
+    afk::Afk,
+    alerts::Alerts,
+    auto_downloads::AutoDownloads,
+    business::Business,
+    calendar::Calendar,
+    chat_settings::ChatSettings,
+    currency::ExchangeRates,
+    digest::Digest,
+    doctor::Doctor,
+    folders::Folders,
+    games::GameManager,
+    generated::Generated,
+    ghost::Ghost,
+    giveaways::Giveaways,
+    history::History,
+    i18n::I18n,
+    inline::InlineProxy,
+    lyrics::Lyrics,
+    mail::{Account, Mail},
+    media_cache::MediaCache,
+    migrations::Migrations,
+    mod_notes::ModNotes,
+    notes::Notes,
+    nsfw::{Classifier, Nsfw},
+    panics::Panics,
+    profile::Profile,
+    purges::Purges,
+    raids::Raids,
+    receipts::Receipts,
+    reposts::Reposts,
+    reverse_search::ReverseSearch,
+    rss::Feeds,
+    sandbox::Sandbox,
+    scheduler::{self, Scheduler},
+    shutdown::Shutdown,
+    signer::Signer,
+    songs::Songs,
+    sudoers::Sudoers,
+    supervisor::{Credentials, Supervisor, UserCredentials},
+    supporters::Supporters,
+    tags::Tags,
+    todo::Todos,
+    tournaments::Tournaments,
+    transcriber::{Endpoint, Transcriber},
+    translator::Translator,
+    trivia::QuestionBank,
+    unread::Unread,
+    updates::Updates,
+    url_scanner::UrlScanner,
+    usage::Usage,
+    usernames::Usernames,
+    virustotal::VirusTotal,
+    wayback::Wayback,
+    weather::{PlaceWeather, Weather},
+    webapp::WebApp,
+    ytdl::Ytdl,
+};
+
+/// The longest the handler of the messages between the clients sleeps through the
+/// flood waits of a message, so one long wait does not hold the queued ones.
+const MAX_FLOOD_WAIT: Duration = Duration::from_secs(5);
+
+/// The most attempts of a via bot message, whatever the configuration says.
+const MAX_VIA_BOT_ATTEMPTS: usize = 5;
 
-/// The receiver of the channel.
-pub type Receiver = mpsc::Receiver<crate::Message>;
+/// The result of a message sent between the clients.
+pub type Response = std::result::Result<types::Message, DeliveryError>;
+
+/// Why a message sent between the clients was not delivered.
+#[derive(Debug)]
+pub enum DeliveryError {
+    /// A request to Telegram failed.
+    Invocation(grammers_client::InvocationError),
+    /// The bot did not answer the inline query of the message.
+    NotAnswered,
+    /// The delivery took too long.
+    TimedOut,
+    /// Any other failure.
+    Other(String),
+}
+
+impl std::fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invocation(e) => write!(f, "{}", e),
+            Self::NotAnswered => write!(f, "the bot did not answer the inline query"),
+            Self::TimedOut => write!(f, "the delivery timed out"),
+            Self::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+impl From<grammers_client::InvocationError> for DeliveryError {
+    fn from(error: grammers_client::InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+/// A reconnection policy configured per client.
+struct MyPolicy {
+    /// The name of the client, shown in the reconnect events.
+    client: String,
+    /// The policy configuration.
+    config: Reconnection,
+}
+
+impl MyPolicy {
+    /// Creates a policy that lives as long as the client.
+    fn leak(client: impl Into<String>, config: Reconnection) -> &'static Self {
+        Box::leak(Box::new(Self {
+            client: client.into(),
+            config,
+        }))
+    }
 
-/// The sender of the channel.
-pub type Sender = mpsc::Sender<crate::Message>;
+    /// Returns the delay before an attempt, without the jitter.
+    fn delay(&self, attempt: usize) -> Duration {
+        let base = self.config.base_delay;
+        let seconds = match self.config.backoff {
+            Backoff::Linear => base.saturating_mul(attempt as u64 + 1),
+            Backoff::Exponential => base.saturating_mul(1 << attempt.min(32)),
+        };
 
-/// A custom reconnection policy.
-struct MyPolicy;
+        Duration::from_secs(seconds.min(self.config.max_delay))
+    }
+}
 
 impl ReconnectionPolicy for MyPolicy {
     fn should_retry(&self, attempt: usize) -> ControlFlow<(), Duration> {
-        let max_attempts = 5;
+        if !self.config.never_give_up && attempt >= self.config.max_attempts {
+            log::error!(
+                "Max attempts reached, stopping reconnection policy of the {} client",
+                self.client
+            );
 
-        if attempt >= max_attempts {
-            log::error!("Max attempts reached, stopping reconnection policy");
+            return ControlFlow::Break(());
+        }
 
-            ControlFlow::Break(())
-        } else {
-            let time = 5 * attempt;
-            log::warn!("Failed to reconnect, retrying in {} seconds", time);
+        // The jitter keeps both clients from reconnecting at the same time.
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + rand::thread_rng().gen_range(-jitter..=jitter);
+        let time = self.delay(attempt).mul_f64(factor);
+        log::warn!(
+            "Failed to reconnect the {0} client (attempt {1}), retrying in {2:.1} seconds",
+            self.client,
+            attempt + 1,
+            time.as_secs_f64()
+        );
 
-            ControlFlow::Continue(Duration::from_secs(time as u64))
-        }
+        ControlFlow::Continue(time)
     }
 }
 
@@ -66,8 +203,18 @@ fn main() -> Result<()> {
         // Initializes the logger.
         env_logger::init();
 
-        // Loads the configuration.
+        // Logs the panics and reports them to the owner, instead of only printing them.
+        Panics::install_hook();
+
+        // Runs the first-time setup when asked for, or when there is no config.
+        if Config::needs_setup() {
+            setup::run().await?;
+        }
+
+        // Loads and checks the configuration.
         let config = Config::load()?;
+        config.validate()?;
+        let plugin_settings = config.plugin_settings()?;
 
         // Sets shared values.
         let api_id = config.telegram.api_id;
@@ -76,6 +223,23 @@ fn main() -> Result<()> {
         let lang_code = "pt";
         let flood_sleep_threshold = config.telegram.flood_sleep_threshold;
 
+        // Keeps the bot token to validate the web app data.
+        let bot_token = config.bot.token.clone();
+
+        // Keeps what the clients log in again with if their session is lost.
+        let credentials = Credentials {
+            bot_token: config.bot.token.clone(),
+            bot_session: config.bot.session_file.clone(),
+            users: config
+                .user
+                .iter()
+                .map(|user| UserCredentials {
+                    phone_number: user.phone_number.clone(),
+                    session: user.session_file.clone(),
+                })
+                .collect(),
+        };
+
         // Constructs and connect bot instance.
         let mut bot = Client::bot(config.bot.token)
             .api_id(api_id)
@@ -85,101 +249,678 @@ fn main() -> Result<()> {
             .lang_code(lang_code)
             .catch_up(config.bot.catch_up)
             .flood_sleep_threshold(flood_sleep_threshold)
-            .reconnection_policy(&MyPolicy)
+            .reconnection_policy(MyPolicy::leak("bot", config.bot.reconnection))
             .on_err(|_, _, err| async move {
                 log::error!("An error occurred whitin bot instance: {}", err)
             })
             .build_and_connect()
             .await?;
 
-        // Constructs and connect user instance.
-        let mut user = Client::user(config.user.phone_number)
-            .api_id(api_id)
-            .api_hash(api_hash)
-            .session_file(config.user.session_file)
-            .app_version(app_version)
-            .lang_code(lang_code)
-            .catch_up(config.user.catch_up)
-            .flood_sleep_threshold(flood_sleep_threshold)
-            .reconnection_policy(&MyPolicy)
-            .on_err(|_, _, err| async move {
-                log::error!("An error occurred whitin user instance: {}", err)
-            })
-            .build_and_connect()
-            .await?;
+        // Constructs and connect a user instance for each account, the first being the
+        // main one.
+        let mut users = Vec::with_capacity(config.user.len());
+        for (index, account) in config.user.into_iter().enumerate() {
+            let name = match index {
+                0 => "user".to_string(),
+                _ => format!("user {}", index + 1),
+            };
+
+            let user = Client::user(account.phone_number)
+                .api_id(api_id)
+                .api_hash(api_hash)
+                .session_file(account.session_file)
+                .app_version(app_version)
+                .lang_code(lang_code)
+                .catch_up(account.catch_up)
+                .flood_sleep_threshold(flood_sleep_threshold)
+                .reconnection_policy(MyPolicy::leak(name.clone(), account.reconnection))
+                .on_err(move |_, _, err| {
+                    let name = name.clone();
+                    async move { log::error!("An error occurred whitin {} instance: {}", name, err) }
+                })
+                .build_and_connect()
+                .await?;
+            users.push(user);
+        }
+        let user_clients = users
+            .iter()
+            .map(|user| user.inner().clone())
+            .collect::<Vec<_>>();
 
         // Creates a dependency injector.
         let mut injector = Injector::default();
 
         // Constructs the i18n module, load and inject it.
-        let mut i18n = I18n::with(lang_code);
+        let i18n = I18n::with(lang_code);
         i18n.load();
-        injector.insert(i18n);
+        injector.insert(i18n.clone());
+
+        // Constructs the sudoers module, with the owner and the other accounts always
+        // allowed, and inject it.
+        let sudoers = Sudoers::shared();
+        let mut account_ids = Vec::with_capacity(user_clients.len());
+        for client in user_clients.iter() {
+            account_ids.push(client.get_me().await?.id());
+        }
+        sudoers.set_owner(account_ids[0]);
+        sudoers.set_accounts(account_ids[1..].to_vec());
+
+        // Constructs the supervisor of the clients and inject it.
+        let supervisor = Supervisor::new(
+            bot.inner().clone(),
+            user_clients.clone(),
+            credentials,
+            i18n.clone(),
+        );
+        supervisor.set_owner(sudoers.owner());
+        injector.insert(supervisor.clone());
+        injector.insert(sudoers);
+
+        // Constructs the self-test module and inject it.
+        let doctor = Doctor::new(
+            bot.inner().clone(),
+            user_clients.clone(),
+            config.reverse_search.ffmpeg_path.clone(),
+            config.ytdl.path.clone(),
+            config.doctor.min_free_space * 1024 * 1024,
+        );
+        injector.insert(doctor.clone());
+
+        // Keeps a copy of the config to be reloaded, the loaded one being taken apart
+        // here, and inject it.
+        let live_config = LiveConfig::new(Config::load()?, plugin_settings.clone());
+        injector.insert(live_config.clone());
+
+        // Injects the effective config of the plugins.
+        injector.insert(plugin_settings);
+
+        // Constructs the AFK module, shared with its filter, and inject it.
+        injector.insert(Afk::shared());
 
         // Constructs the games module and inject it.
-        let manager = GameManager::new();
-        injector.insert(manager);
+        let manager = GameManager::new(
+            Duration::from_secs(config.games.move_timeout),
+            Duration::from_secs(config.games.expire_after * 60),
+        );
+        injector.insert(manager.clone());
+
+        // Constructs the trivia questions module and inject it.
+        let questions = QuestionBank::new(
+            &config.trivia.packs_dir,
+            config.trivia.questions,
+            Duration::from_secs(config.trivia.answer_time),
+        );
+        injector.insert(questions);
+
+        // Constructs the tournaments module and inject it.
+        let tournaments = Tournaments::new();
+        injector.insert(tournaments);
+
+        // Constructs the folders module and inject it.
+        let folders = Folders::new(Duration::from_secs(config.folders.sync_interval * 60));
+        injector.insert(folders.clone());
+
+        // Injects the ghost mode module.
+        let ghost = Ghost::shared();
+        injector.insert(ghost.clone());
+
+        // Constructs the alerts module, ignoring the chats the alerts are posted to,
+        // and inject it.
+        let alerts = Alerts::shared();
+        alerts.set_cooldown(Duration::from_secs(config.alerts.cooldown));
+        let mut ignored_chats = account_ids.clone();
+        ignored_chats.push(bot.inner().get_me().await?.id());
+        alerts.set_ignored_chats(ignored_chats);
+        injector.insert(alerts.clone());
+
+        // Constructs the inline proxy module and inject it.
+        let inline_proxy = InlineProxy::new();
+        injector.insert(inline_proxy);
+
+        // Constructs the history browser module and inject it.
+        let history = History::new();
+        injector.insert(history);
+
+        // Constructs the unread digest module and inject it.
+        let unread = Unread::new();
+        injector.insert(unread);
+
+        // Constructs the purge confirmation module and inject it.
+        let purges = Purges::new();
+        injector.insert(purges);
+
+        // Constructs the tags module and inject it.
+        let tags = Tags::new(config.tags.presets, config.tags.page_size);
+        injector.insert(tags);
+
+        // Constructs the usernames checker module and inject it.
+        let usernames = Usernames::new(
+            Duration::from_secs(config.usernames.delay),
+            Duration::from_secs(config.usernames.interval * 60),
+        );
+        injector.insert(usernames.clone());
+
+        // Constructs the songs module and inject it.
+        let songs = Songs::new(Duration::from_secs(config.songs.cache_ttl));
+        injector.insert(songs);
+
+        // Constructs the yt-dlp module and inject it.
+        let ytdl = Ytdl::new(config.ytdl.path, config.ytdl.output_dir);
+        injector.insert(ytdl);
+
+        // Constructs the lyrics module and inject it.
+        let lyrics = Lyrics::new(config.lyrics.provider, config.lyrics.page_length);
+        injector.insert(lyrics);
+
+        // Constructs the weather module and inject it.
+        let weather = Weather::new();
+        injector.insert(weather.clone());
+
+        // Constructs the translator module and inject it.
+        let translator = Translator::new();
+        injector.insert(translator);
+
+        // Constructs the reverse image search module and inject it.
+        let reverse_search = ReverseSearch::new(
+            config.reverse_search.engines,
+            (!config.reverse_search.saucenao_key.is_empty())
+                .then_some(config.reverse_search.saucenao_key),
+            config.reverse_search.ffmpeg_path,
+        );
+        injector.insert(reverse_search);
+
+        // Constructs the calendar module and inject it.
+        let calendar = Calendar::new(
+            config.calendar.url,
+            config.calendar.username.zip(config.calendar.password),
+            Duration::from_secs(config.calendar.sync_interval * 60),
+            config
+                .calendar
+                .lead_times
+                .into_iter()
+                .map(|minutes| Duration::from_secs(minutes * 60))
+                .collect(),
+        );
+        injector.insert(calendar.clone());
+
+        // Constructs the todo list module and inject it.
+        let todos = Todos::new();
+        injector.insert(todos.clone());
+
+        // Constructs the digest module with its widgets and inject it.
+        let digest = Digest::new()
+            .widget(PlaceWeather::new(weather, config.digest.weather.place))
+            .widget(calendar.clone())
+            .widget(ExchangeRates::new(
+                config.digest.currency.base,
+                config.digest.currency.targets,
+            ))
+            .widget(Feeds::new(
+                config.digest.rss.feeds,
+                config.digest.rss.headlines,
+            ))
+            .widget(todos);
+        injector.insert(digest.clone());
+
+        // Constructs the mail module and inject it.
+        let mail = Mail::new(
+            (!config.mail.host.is_empty()).then(|| Account {
+                host: config.mail.host,
+                port: config.mail.port,
+                username: config.mail.username,
+                password: config.mail.password,
+            }),
+            config.mail.mailboxes,
+            Duration::from_secs(config.mail.poll_interval),
+            config.mail.attachment_size_cap,
+        );
+        injector.insert(mail.clone());
+
+        // Constructs the business connections module and inject it.
+        let business = Business::new(config.business.enabled, config.business.owners);
+        injector.insert(business.clone());
+
+        // Constructs the supporters module and inject it.
+        let supporters = Supporters::new();
+        injector.insert(supporters);
+
+        // Constructs the giveaways module and inject it.
+        let giveaways = Giveaways::new();
+        injector.insert(giveaways);
+
+        // Constructs the chat settings module and inject it.
+        let chat_settings = ChatSettings::new();
+        injector.insert(chat_settings.clone());
+
+        // Constructs the moderation notes module and inject it.
+        let mod_notes = ModNotes::new();
+        injector.insert(mod_notes.clone());
+
+        // Constructs the raids module and inject it.
+        let raids = Raids::new(
+            config.raid.joins,
+            Duration::from_secs(config.raid.window),
+            Duration::from_secs(config.raid.duration * 60),
+        );
+        injector.insert(raids.clone());
+
+        // Constructs the read receipts module and inject it.
+        let receipts = Receipts::new();
+        injector.insert(receipts);
+
+        // Constructs the reposts module and inject it.
+        let reposts = Reposts::new();
+        injector.insert(reposts.clone());
+
+        // Constructs the NSFW detection module and inject it.
+        let nsfw = Nsfw::new((!config.nsfw.url.is_empty()).then(|| Classifier {
+            url: config.nsfw.url,
+            api_key: (!config.nsfw.api_key.is_empty()).then_some(config.nsfw.api_key),
+            file_field: config.nsfw.file_field,
+            score_pointer: config.nsfw.score_pointer,
+            normalized: config.nsfw.normalized,
+        }));
+        injector.insert(nsfw.clone());
+
+        // Constructs the URL scanner module and inject it.
+        let url_scanner = UrlScanner::new(
+            config.url_scanner.provider,
+            (!config.url_scanner.api_key.is_empty()).then_some(config.url_scanner.api_key),
+            Duration::from_secs(config.url_scanner.cache_hours * 60 * 60),
+        );
+        injector.insert(url_scanner.clone());
+
+        // Constructs the voice transcription module and inject it.
+        let transcriber = Transcriber::new(
+            (!config.transcriber.url.is_empty()).then(|| Endpoint {
+                url: config.transcriber.url,
+                api_key: (!config.transcriber.api_key.is_empty())
+                    .then_some(config.transcriber.api_key),
+                model: config.transcriber.model,
+            }),
+            config.transcriber.max_duration,
+            config.transcriber.max_length,
+        );
+        injector.insert(transcriber.clone());
+
+        // Constructs the Wayback Machine archiver module and inject it.
+        let wayback = Wayback::new();
+        injector.insert(wayback.clone());
+
+        // Constructs the auto-download rules module and inject it.
+        let auto_downloads = AutoDownloads::new();
+        injector.insert(auto_downloads.clone());
+
+        // Constructs the chat migrations module with the modules kept by chat and inject it.
+        let migrations = Migrations::new()
+            .hook(alerts)
+            .hook(auto_downloads.clone())
+            .hook(chat_settings)
+            .hook(ghost)
+            .hook(i18n.clone())
+            .hook(mod_notes)
+            .hook(nsfw)
+            .hook(raids)
+            .hook(reposts)
+            .hook(transcriber)
+            .hook(url_scanner)
+            .hook(wayback.clone());
+        injector.insert(migrations);
+
+        // Constructs the VirusTotal file scanner module and inject it.
+        let virustotal = VirusTotal::new(
+            config.virustotal.enabled,
+            (!config.virustotal.api_key.is_empty()).then_some(config.virustotal.api_key),
+            config.virustotal.max_upload_size * 1024 * 1024,
+        );
+        injector.insert(virustotal.clone());
+
+        // Constructs the usage accounting module and inject it.
+        let usage = Usage::new(config.usage.budgets);
+        injector.insert(usage);
+
+        // Constructs the media cache module and inject it.
+        let media_cache = MediaCache::new(config.media_cache.max_size * 1024 * 1024);
+        injector.insert(media_cache);
+
+        // Constructs the code sandbox module and inject it.
+        let sandbox = Sandbox::new(
+            config.sandbox.wrapper,
+            config.sandbox.wrapper_args,
+            config.sandbox.container_runtime,
+            config.sandbox.container_image,
+            Duration::from_secs(config.sandbox.timeout),
+            config.sandbox.max_output * 1024,
+            config.sandbox.memory_limit,
+        );
+        injector.insert(sandbox);
+
+        // Constructs the signer of the payloads between the clients and inject it.
+        let signer = Signer::new(&config.bot.secret);
+        injector.insert(signer.clone());
+        callback_data::init(&signer);
+
+        // Constructs the notes module and inject it.
+        let notes = Notes::new();
+        injector.insert(notes);
+
+        // Constructs the scheduler module and inject it.
+        let scheduler = Scheduler::new();
+        injector.insert(scheduler.clone());
+
+        // Constructs the web app module and inject it.
+        let webapp = WebApp::new(
+            config.webapp.bind,
+            (!config.webapp.url.is_empty()).then_some(config.webapp.url),
+            config.webapp.assets_dir.into(),
+            bot_token,
+            Duration::from_secs(config.webapp.max_age),
+        );
+        injector.insert(webapp.clone());
+
+        // Constructs the profile rotation module and inject it.
+        let profile = Profile::new(config.profile.clock_format, config.profile.bios);
+        injector.insert(profile.clone());
 
         // Creates a channel to communicate between the clients.
-        let (tx, rx) = mpsc::channel::<Message>(10);
+        let (tx, rx) = bus::channel(
+            config.bus.capacity,
+            Duration::from_secs(config.bus.send_timeout),
+        );
 
         // Injects the channel's sender into the injector.
-        injector.insert(tx);
+        injector.insert(tx.clone());
+
+        // Starts sending the daily digest.
+        digest.start(i18n.clone(), tx.clone());
+
+        // Starts synchronizing the calendar.
+        calendar.start(i18n.clone(), tx.clone());
+
+        // Starts polling the mailboxes.
+        mail.start(i18n.clone(), tx.clone());
+
+        // Starts running the scheduled jobs.
+        scheduler.start(tx.clone());
+
+        // Starts archiving the queued links.
+        wayback.start(i18n.clone(), tx.clone());
+
+        // Starts summing up the auto-downloads every day.
+        auto_downloads.start(i18n.clone(), tx.clone());
+
+        // Starts reporting the panics to the owner.
+        Panics::shared().start(i18n.clone(), tx.clone());
+
+        // Starts checking the sessions of the clients.
+        supervisor.clone().start();
+
+        // Applies the keys that can change while running, now and on each reload.
+        live_config.on_reload(move |config| {
+            utils::set_flood_threshold(Duration::from_secs(
+                config.telegram.flood_sleep_threshold as u64,
+            ));
+            Updates::shared().set_max_age(config.telegram.max_catchup_age * 60);
+            Sudoers::shared().set_configured(config.telegram.sudoers.clone());
+            business.configure(config.business.enabled, config.business.owners.clone());
+            virustotal.set_enabled(config.virustotal.enabled);
+        });
+
+        // Starts reloading the config when its file changes.
+        live_config.watch();
+
+        // Runs the self-test once, sending the results to the owner.
+        if config.doctor.on_startup {
+            doctor.start(i18n.clone(), tx.clone());
+        }
 
-        // Clones the bot and user inner instances to be used inside the plugins.
+        // Starts serving the web app.
+        let webapp_rx = webapp.start();
+
+        // Clones the bot and main user inner instances to be used inside the plugins.
         let bot_inner = bot.inner().clone();
-        let user_inner = user.inner().clone();
+        let user_inner = user_clients[0].clone();
+
+        // Register the dispatcher of each client, each knowing which client it runs in.
+        let mut bot_injector = injector.clone();
+        bot_injector.insert(Recipient::Bot);
+        bot = bot.dispatcher(|_| plugins::bot(user_inner, bot_injector));
+        let users = users
+            .into_iter()
+            .enumerate()
+            .map(|(index, user)| {
+                // The via bot messages of the account are sent from it.
+                let mut injector = injector.clone();
+                injector.insert(Recipient::User(index));
+                injector.insert(tx.for_account(index));
 
-        // Register the dispatcher of each client.
-        bot = bot.dispatcher(|_| plugins::bot(user_inner, injector.clone()));
-        user = user.dispatcher(|_| plugins::user(bot_inner, injector));
+                let bot_inner = bot_inner.clone();
+                user.dispatcher(|_| plugins::user(bot_inner, injector))
+            })
+            .collect::<Vec<_>>();
 
         // Clones the bot and user instances to be used inside the task.
         let bot_inner = bot.inner().clone();
-        let user_inner = user.inner().clone();
+        let user_clients = users
+            .iter()
+            .map(|user| user.inner().clone())
+            .collect::<Vec<_>>();
+        let user_inner = user_clients[0].clone();
+
+        // Starts applying the auto-foldering rules.
+        folders.start(user_inner.clone());
+
+        // Starts rotating the profile clock and bio.
+        profile.start(user_inner.clone());
+
+        // Starts watching the usernames.
+        usernames.start(user_inner.clone(), i18n.clone(), tx.clone());
+
+        // Starts expiring the abandoned games.
+        manager.clone().start(bot_inner.clone(), i18n.clone());
+
+        // Spawn a task to handle the data sent by the web app, restarted if it panics.
+        let shutdown = Shutdown::shared();
+        let task_bot = bot_inner.clone();
+        let task_i18n = i18n.clone();
+        let webapp_rx = Arc::new(Mutex::new(webapp_rx));
+        shutdown.supervise("web app handler", move || {
+            let bot = task_bot.clone();
+            let i18n = task_i18n.clone();
+            let rx = webapp_rx.clone();
+
+            async move {
+                plugins::web_app(bot, i18n, &mut *rx.lock().await).await;
+                Ok(())
+            }
+        });
 
         // Creates a new bot's context.
         let bot_ctx = bot.new_ctx();
 
-        // Spawn a task to handle the messages.
-        tokio::task::spawn(async move {
-            handle_message(bot_inner, user_inner, rx, bot_ctx)
+        // Spawn a task to handle the messages, which sends the ones left on shutdown.
+        //
+        // It is restarted if it panics or fails, keeping the messages waiting in
+        // the channel.
+        let via_bot = config.bot.via_bot;
+        let task_bot = bot_inner.clone();
+        let task_supervisor = supervisor.clone();
+        let rx = Arc::new(Mutex::new(rx));
+        shutdown.supervise("message handler", move || {
+            let bot = task_bot.clone();
+            let users = user_clients.clone();
+            let rx = rx.clone();
+            let bot_ctx = bot_ctx.clone();
+            let signer = signer.clone();
+            let supervisor = task_supervisor.clone();
+            let via_bot = via_bot.clone();
+
+            async move {
+                handle_message(
+                    bot,
+                    users,
+                    &mut *rx.lock().await,
+                    bot_ctx,
+                    signer,
+                    supervisor,
+                    via_bot,
+                )
                 .await
-                .expect("Failed to handle message between the clients");
+                .map_err(|e| e.to_string())
+            }
         });
 
         // Run the clients.
         bot.run().await?;
-        user.run().await?;
+        for user in users {
+            user.run().await?;
+        }
 
         // Waits for a Ctrl+C signal to stop the clients.
         ferogram::wait_for_ctrl_c().await;
+        log::info!("Shutting down...");
+
+        // Stops the background tasks and closes the channel, so the messages
+        // waiting in it are still sent.
+        shutdown.cancel();
+        tx.close();
+
+        // Ends the active games, as they are not kept across restarts.
+        let games = manager.interrupt(&bot_inner, &i18n).await;
+        if games > 0 {
+            log::info!("Interrupted {} active games", games);
+        }
+
+        if !shutdown.wait().await {
+            log::warn!("Gave up waiting for the tasks to finish");
+        }
+
+        // Saves the sessions, so the updates handled are not caught up again.
+        supervisor.save_sessions();
+        log::info!("Shut down cleanly");
 
         Ok(())
     })
 }
 
 /// The action to be taken with the message.
-#[derive(Default)]
+///
+/// The actions without client types, or with their stored forms, can be
+/// persisted, as the scheduled jobs are.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum Action {
     /// Sends a message.
-    SendMessage(types::Chat, types::InputMessage),
+    #[serde(skip)]
+    SendMessage(PackedChat, types::InputMessage),
+    /// Sends a message written in HTML, the stored form of `SendMessage`.
+    SendHtml(#[serde(with = "packed_chat")] PackedChat, String),
     /// Sends a via bot message.
+    #[serde(skip)]
     SendViaBotMessage(types::Chat, types::InputMessage),
     /// Edits a message.
-    EditMessage(types::Chat, i32, types::InputMessage),
+    #[serde(skip)]
+    EditMessage(PackedChat, i32, types::InputMessage),
+    /// Sends a media already uploaded, with its MIME type.
+    #[serde(skip)]
+    SendMedia(
+        PackedChat,
+        types::media::Uploaded,
+        String,
+        types::InputMessage,
+    ),
+    /// Deletes messages.
+    DeleteMessages(#[serde(with = "packed_chat")] PackedChat, Vec<i32>),
+    /// Forwards messages from a chat, the second, to another.
+    ForwardMessages(
+        #[serde(with = "packed_chat")] PackedChat,
+        #[serde(with = "packed_chat")] PackedChat,
+        Vec<i32>,
+    ),
+    /// Pins a message.
+    PinMessage(#[serde(with = "packed_chat")] PackedChat, i32),
+    /// Notifies the owner through the bot's private chat.
+    #[serde(skip)]
+    Notify(types::InputMessage),
+    /// Notifies the owner with a message written in HTML, the stored form of
+    /// `Notify`.
+    NotifyHtml(String),
+    /// Reminds the owner through the bot's private chat, with the link to the
+    /// message the reminder was set from, if any.
+    Remind(String, Option<String>),
+    /// Notifies the owner with a file through the bot's private chat.
+    #[serde(skip)]
+    NotifyWithFile(types::InputMessage, String, Vec<u8>),
     /// Undefined action.
     #[default]
     Undefined,
 }
 
+impl Action {
+    /// Turns the stored forms into the actions taken.
+    fn into_taken(self) -> Self {
+        match self {
+            Self::SendHtml(chat, text) => Self::SendMessage(chat, types::InputMessage::html(text)),
+            Self::NotifyHtml(text) => Self::Notify(types::InputMessage::html(text)),
+            Self::Remind(text, link) => Self::Notify(types::InputMessage::html(
+                scheduler::reminder_text(&text, link.as_deref()),
+            )),
+            action => action,
+        }
+    }
+}
+
+/// Persists a packed chat as hex.
+mod packed_chat {
+    use grammers_client::session::PackedChat;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(chat: &PackedChat, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&chat.to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PackedChat, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+
+        PackedChat::from_hex(&hex).ok_or_else(|| D::Error::custom("invalid packed chat"))
+    }
+}
+
 /// The type of the message.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredRecipient")]
 pub enum Recipient {
     /// A message from the user to the bot.
     Bot,
-    /// A message from the bot to the user.
-    User,
+    /// A message from the bot to a user account, by its position in the config.
+    User(usize),
+}
+
+impl Recipient {
+    /// The main user account, the first in the config.
+    pub const MAIN_USER: Self = Self::User(0);
+}
+
+/// A recipient as persisted, before and after there were several accounts.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredRecipient {
+    /// `"Bot"` or `"User"`, the latter being the main account.
+    Name(String),
+    /// `{"User": 1}`.
+    Account {
+        #[serde(rename = "User")]
+        user: usize,
+    },
+}
+
+impl From<StoredRecipient> for Recipient {
+    fn from(stored: StoredRecipient) -> Self {
+        match stored {
+            StoredRecipient::Name(name) if name == "Bot" => Self::Bot,
+            StoredRecipient::Name(_) => Self::MAIN_USER,
+            StoredRecipient::Account { user } => Self::User(user),
+        }
+    }
 }
 
 /// A message to be sent between the clients.
@@ -188,6 +929,14 @@ pub struct Message {
     action: Action,
     /// The recipient of the message.
     recipient: Recipient,
+    /// The forum topic the message is sent to.
+    topic: Option<i32>,
+    /// How soon the message is handled.
+    priority: Priority,
+    /// The user account that sends the via bot message.
+    account: usize,
+    /// Where the resulting message is sent back to, if awaited.
+    responder: Option<oneshot::Sender<Response>>,
 }
 
 impl Message {
@@ -196,14 +945,23 @@ impl Message {
         Self {
             action: Action::default(),
             recipient: Recipient::Bot,
+            topic: None,
+            priority: Priority::default(),
+            account: 0,
+            responder: None,
         }
     }
 
-    /// Creates a message to be sent from the bot to the user.
-    pub fn to_user() -> Self {
+    /// Creates a message to be sent from the bot to a user account, `0` being
+    /// the main one.
+    pub fn to_user(account: usize) -> Self {
         Self {
             action: Action::default(),
-            recipient: Recipient::User,
+            recipient: Recipient::User(account),
+            topic: None,
+            priority: Priority::default(),
+            account: 0,
+            responder: None,
         }
     }
 
@@ -217,20 +975,84 @@ impl Message {
         &self.recipient
     }
 
+    /// Gets the forum topic the message is sent to.
+    pub fn topic(&self) -> Option<i32> {
+        self.topic
+    }
+
+    /// Gets how soon the message is handled.
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Gets the user account that sends the via bot message.
+    pub fn account(&self) -> usize {
+        self.account
+    }
+
     /// Unwraps the message into its components.
-    pub fn unwrap(self) -> (Action, Recipient) {
-        (self.action, self.recipient)
+    pub fn unwrap(
+        self,
+    ) -> (
+        Action,
+        Recipient,
+        Option<i32>,
+        usize,
+        Option<oneshot::Sender<Response>>,
+    ) {
+        (
+            self.action,
+            self.recipient,
+            self.topic,
+            self.account,
+            self.responder,
+        )
+    }
+
+    /// Asks for the resulting message, returning the receiver to await it on.
+    ///
+    /// Only the actions that send a message answer, the receiver of the
+    /// others is closed once the action is taken.
+    pub fn with_response(mut self) -> (Self, oneshot::Receiver<Response>) {
+        let (tx, rx) = oneshot::channel();
+        self.responder = Some(tx);
+
+        (self, rx)
+    }
+
+    /// Sends the message to a forum topic, `None` being the General topic.
+    pub fn in_topic(mut self, topic: Option<i32>) -> Self {
+        self.topic = topic;
+        self
+    }
+
+    /// Sends the via bot message from a user account, the main one by default.
+    pub fn from_account(mut self, account: usize) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Sets how soon the message is handled, `Normal` by default.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Takes the given action.
+    pub fn with_action(mut self, action: Action) -> Self {
+        self.action = action;
+        self
     }
 
     /// Sends a message to a chat.
-    pub fn send_message(mut self, chat: types::Chat, input: types::InputMessage) -> Self {
-        self.action = Action::SendMessage(chat, input);
+    pub fn send_message(mut self, chat: impl Into<PackedChat>, input: types::InputMessage) -> Self {
+        self.action = Action::SendMessage(chat.into(), input);
         self
     }
 
     /// Sends a via bot message to a chat.
     pub fn send_via_bot_message(mut self, chat: types::Chat, input: types::InputMessage) -> Self {
-        if self.recipient == Recipient::User {
+        if matches!(self.recipient, Recipient::User(_)) {
             panic!("Cannot send a via bot message from the bot to the user");
         }
 
@@ -238,109 +1060,345 @@ impl Message {
         self
     }
 
+    /// Sends a media already uploaded to a chat.
+    ///
+    /// The images are sent as photos and the other files as documents.
+    pub fn send_media(
+        mut self,
+        chat: impl Into<PackedChat>,
+        file: types::media::Uploaded,
+        mime_type: impl Into<String>,
+        input: types::InputMessage,
+    ) -> Self {
+        self.action = Action::SendMedia(chat.into(), file, mime_type.into(), input);
+        self
+    }
+
+    /// Deletes messages of a chat.
+    pub fn delete_messages(mut self, chat: impl Into<PackedChat>, message_ids: Vec<i32>) -> Self {
+        self.action = Action::DeleteMessages(chat.into(), message_ids);
+        self
+    }
+
+    /// Forwards messages from the `source` chat to another.
+    pub fn forward_messages(
+        mut self,
+        chat: impl Into<PackedChat>,
+        source: impl Into<PackedChat>,
+        message_ids: Vec<i32>,
+    ) -> Self {
+        self.action = Action::ForwardMessages(chat.into(), source.into(), message_ids);
+        self
+    }
+
+    /// Pins a message of a chat.
+    pub fn pin_message(mut self, chat: impl Into<PackedChat>, message_id: i32) -> Self {
+        self.action = Action::PinMessage(chat.into(), message_id);
+        self
+    }
+
+    /// Notifies the owner through the bot's private chat.
+    pub fn notify(mut self, input: types::InputMessage) -> Self {
+        if matches!(self.recipient, Recipient::User(_)) {
+            panic!("Cannot notify the owner from the bot to the user");
+        }
+
+        self.action = Action::Notify(input);
+        self
+    }
+
+    /// Notifies the owner with a file through the bot's private chat.
+    pub fn notify_with_file(
+        mut self,
+        input: types::InputMessage,
+        file_name: impl Into<String>,
+        bytes: Vec<u8>,
+    ) -> Self {
+        if matches!(self.recipient, Recipient::User(_)) {
+            panic!("Cannot notify the owner from the bot to the user");
+        }
+
+        self.action = Action::NotifyWithFile(input, file_name.into(), bytes);
+        self
+    }
+
     /// Edits a message.
     pub fn edit_message(
         mut self,
-        chat: types::Chat,
+        chat: impl Into<PackedChat>,
         message_id: i32,
         input: types::InputMessage,
     ) -> Self {
-        self.action = Action::EditMessage(chat, message_id, input);
+        self.action = Action::EditMessage(chat.into(), message_id, input);
         self
     }
 }
 
+/// A user account, as the messages between the clients see it.
+struct UserAccount {
+    /// The client of the account.
+    client: grammers_client::Client,
+    /// The bot, as resolved by the account.
+    bot_chat: types::Chat,
+    /// The ID of the account.
+    id: i64,
+}
+
 async fn handle_message(
     bot: grammers_client::Client,
-    user: grammers_client::Client,
-    mut rx: Receiver,
+    users: Vec<grammers_client::Client>,
+    rx: &mut Receiver,
     bot_ctx: Context,
+    signer: Signer,
+    supervisor: Supervisor,
+    via_bot: ViaBot,
 ) -> Result<()> {
     let bot_me = bot.get_me().await?;
-    let bot_username = bot_me.username().unwrap().to_owned();
+    let bot_username = bot_me
+        .username()
+        .ok_or("The bot has no username")?
+        .to_owned();
 
-    let bot_chat = user.resolve_username(&bot_username).await?.unwrap();
+    let mut accounts = Vec::with_capacity(users.len());
+    for client in users {
+        accounts.push(UserAccount {
+            bot_chat: client
+                .resolve_username(&bot_username)
+                .await?
+                .ok_or("A user account cannot find the bot")?,
+            id: client.get_me().await?.id(),
+            client,
+        });
+    }
+
+    // The owner is the main account.
+    let owner_chat = PackedChat {
+        ty: PackedType::User,
+        id: accounts[0].id,
+        access_hash: None,
+    };
 
     while let Some(message) = rx.recv().await {
-        let (action, recipient) = message.unwrap();
+        let (action, recipient, topic, account, responder) = message.unwrap();
+        let action = action.into_taken();
+
+        // The account taking the action, or sending the via bot message.
+        let index = match recipient {
+            Recipient::Bot => account,
+            Recipient::User(index) => index,
+        };
+        let Some(account) = accounts.get(index) else {
+            log::warn!("Dropping a message, there is no user account {}", index);
+            continue;
+        };
+        let user = &account.client;
+
+        // The messages of a client whose session was lost are dropped.
+        if supervisor.is_down(recipient) {
+            log::warn!("Dropping a message, the session of its client was lost");
+            continue;
+        }
 
         match action {
             Action::SendMessage(chat, input) => {
-                match recipient {
+                // Replying to the root message of a topic sends the message inside it.
+                let input = match topic {
+                    Some(topic) => input.reply_to(Some(topic)),
+                    None => input,
+                };
+
+                let result = match recipient {
                     Recipient::Bot => {
                         // Sends the message to the bot.
-                        bot.send_message(chat, input).await?;
+                        utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                            bot.send_message(chat, input.clone())
+                        })
+                        .await
                     }
-                    Recipient::User => {
+                    Recipient::User(_) => {
                         // Sends the message to the user.
-                        user.send_message(chat, input).await?;
+                        utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                            user.send_message(chat, input.clone())
+                        })
+                        .await
+                    }
+                };
+
+                match &result {
+                    // Tagged so the handlers of the account do not react to it.
+                    Ok(message) => Generated::shared().register(message),
+                    Err(e) => {
+                        if !supervisor.report(recipient, e).await {
+                            log::error!("Failed to send a message: {}", e);
+                        }
                     }
                 }
+
+                respond(responder, result);
             }
             Action::SendViaBotMessage(chat, input) => {
-                let number = rand::random::<i64>();
-
-                let bot_chat = bot_chat.clone();
-                let client = user.clone();
+                // The delivery runs on its own, so its retries do not hold the
+                // queued messages.
+                let user = user.clone();
+                let bot_chat = account.bot_chat.clone();
+                let user_id = account.id;
+                let bot_ctx = bot_ctx.clone();
+                let signer = signer.clone();
+                let via_bot = via_bot.clone();
                 tokio::task::spawn(async move {
-                    let mut results = client
-                        .inline_query(&bot_chat, &number.to_string())
-                        .chat(&chat);
-
-                    loop {
-                        match results.next().await {
-                            Ok(Some(result)) => {
-                                let title = result.title().expect("Title not found");
-
-                                if *title == number.to_string() {
-                                    result.send(&chat).await.expect("Failed to send message");
-                                }
-
-                                break;
-                            }
-                            Ok(None) => tokio::time::sleep(Duration::from_secs(1)).await,
-
-                            Err(e) if e.is("BOT_RESPONSE_TIMEOUT") => {
-                                tokio::time::sleep(Duration::from_secs(1)).await
-                            }
-                            Err(e) => {
-                                log::error!("Error: {}", e);
-                                break;
-                            }
-                        }
-                    }
-                });
+                    let result = deliver_via_bot(
+                        &user, &bot_ctx, &bot_chat, &chat, input, topic, &signer, user_id, &via_bot,
+                    )
+                    .await;
 
-                loop {
-                    if let Ok(query) = bot_ctx.wait_for_inline_query(Some(10)).await {
-                        if query.text() == number.to_string() {
-                            query
-                                .answer(vec![inline::query::Article::new(
-                                    number.to_string(),
-                                    input,
-                                )
-                                .into()])
-                                .send()
-                                .await?;
-
-                            break;
+                    match result {
+                        Ok(message) => {
+                            Generated::shared().register(&message);
+                            respond(responder, Ok::<_, DeliveryError>(message));
+                        }
+                        Err(e) => {
+                            log::error!("Failed to send a via bot message: {}", e);
+                            respond(responder, Err(e));
                         }
                     }
-                }
+                });
             }
             Action::EditMessage(chat, message_id, input) => {
-                match recipient {
+                let result = match recipient {
                     Recipient::Bot => {
                         // Edits the message from the bot.
-                        bot.edit_message(chat, message_id, input).await?;
+                        utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                            bot.edit_message(chat, message_id, input.clone())
+                        })
+                        .await
                     }
-                    Recipient::User => {
+                    Recipient::User(_) => {
                         // Edits the message from the user.
-                        user.edit_message(chat, message_id, input).await?;
+                        utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                            user.edit_message(chat, message_id, input.clone())
+                        })
+                        .await
+                    }
+                };
+
+                if let Err(e) = result {
+                    if !supervisor.report(recipient, &e).await {
+                        log::error!("Failed to edit a message: {}", e);
+                    }
+                }
+            }
+            Action::SendMedia(chat, file, mime_type, input) => {
+                let input = match topic {
+                    Some(topic) => input.reply_to(Some(topic)),
+                    None => input,
+                };
+                // Only the formats Telegram takes as photos are sent as such.
+                let input = match mime_type.as_str() {
+                    "image/jpeg" | "image/png" | "image/webp" => input.photo(file),
+                    _ => input.document(file).mime_type(&mime_type),
+                };
+
+                let client = match recipient {
+                    Recipient::Bot => &bot,
+                    Recipient::User(_) => user,
+                };
+                let result = utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                    client.send_message(chat, input.clone())
+                })
+                .await;
+
+                match &result {
+                    Ok(message) => Generated::shared().register(message),
+                    Err(e) => {
+                        if !supervisor.report(recipient, e).await {
+                            log::error!("Failed to send a media: {}", e);
+                        }
+                    }
+                }
+
+                respond(responder, result);
+            }
+            Action::DeleteMessages(chat, message_ids) => {
+                let client = match recipient {
+                    Recipient::Bot => &bot,
+                    Recipient::User(_) => user,
+                };
+
+                if let Err(e) = utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                    client.delete_messages(chat, &message_ids)
+                })
+                .await
+                {
+                    if !supervisor.report(recipient, &e).await {
+                        log::error!("Failed to delete messages: {}", e);
+                    }
+                }
+            }
+            Action::ForwardMessages(chat, source, message_ids) => {
+                let client = match recipient {
+                    Recipient::Bot => &bot,
+                    Recipient::User(_) => user,
+                };
+
+                if let Err(e) = utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                    client.forward_messages(chat, &message_ids, source)
+                })
+                .await
+                {
+                    if !supervisor.report(recipient, &e).await {
+                        log::error!("Failed to forward messages: {}", e);
+                    }
+                }
+            }
+            Action::PinMessage(chat, message_id) => {
+                let client = match recipient {
+                    Recipient::Bot => &bot,
+                    Recipient::User(_) => user,
+                };
+
+                if let Err(e) =
+                    utils::retry_on_flood(MAX_FLOOD_WAIT, || client.pin_message(chat, message_id))
+                        .await
+                {
+                    if !supervisor.report(recipient, &e).await {
+                        log::error!("Failed to pin a message: {}", e);
                     }
                 }
             }
-            Action::Undefined => {
+            Action::Notify(input) => {
+                let result = utils::retry_on_flood(MAX_FLOOD_WAIT, || {
+                    bot.send_message(owner_chat, input.clone())
+                })
+                .await;
+                if let Err(e) = &result {
+                    if !supervisor.report(recipient, e).await {
+                        log::error!("Failed to notify the owner: {}", e);
+                    }
+                }
+
+                respond(responder, result);
+            }
+            Action::NotifyWithFile(input, file_name, bytes) => {
+                let size = bytes.len();
+                let mut stream = std::io::Cursor::new(bytes);
+
+                match bot.upload_stream(&mut stream, size, file_name).await {
+                    Ok(file) => {
+                        let result = bot.send_message(owner_chat, input.document(file)).await;
+                        if let Err(e) = &result {
+                            log::error!("Failed to notify the owner: {}", e);
+                        }
+
+                        respond(responder, result);
+                    }
+                    Err(e) => log::error!("Failed to upload the notification file: {}", e),
+                }
+            }
+            // The stored forms were turned into the actions taken above.
+            Action::SendHtml(..)
+            | Action::NotifyHtml(..)
+            | Action::Remind(..)
+            | Action::Undefined => {
                 log::error!("Undefined action");
             }
         }
@@ -348,3 +1406,133 @@ async fn handle_message(
 
     Ok(())
 }
+
+/// Sends a message via the bot, retrying the failed attempts.
+///
+/// Each attempt is bounded by the configured timeout, and the attempts by
+/// [`MAX_VIA_BOT_ATTEMPTS`].
+#[allow(clippy::too_many_arguments)]
+async fn deliver_via_bot(
+    user: &grammers_client::Client,
+    bot_ctx: &Context,
+    bot_chat: &types::Chat,
+    chat: &types::Chat,
+    input: types::InputMessage,
+    topic: Option<i32>,
+    signer: &Signer,
+    user_id: i64,
+    via_bot: &ViaBot,
+) -> std::result::Result<types::Message, DeliveryError> {
+    let attempts = via_bot.attempts.clamp(1, MAX_VIA_BOT_ATTEMPTS);
+    let timeout = Duration::from_secs(via_bot.timeout);
+
+    let mut result = Err(DeliveryError::TimedOut);
+    for attempt in 1..=attempts {
+        let delivery = send_via_bot(
+            user,
+            bot_ctx,
+            bot_chat,
+            chat,
+            input.clone(),
+            topic,
+            signer,
+            user_id,
+        );
+        result = tokio::time::timeout(timeout, delivery)
+            .await
+            .unwrap_or(Err(DeliveryError::TimedOut));
+
+        match &result {
+            Ok(_) => break,
+            Err(e) => log::warn!(
+                "Failed to send a via bot message, attempt {0} of {1}: {2}",
+                attempt,
+                attempts,
+                e
+            ),
+        }
+    }
+
+    result
+}
+
+/// Makes an attempt to send a message via the bot.
+///
+/// The user sends an inline query to the bot, which answers it with the
+/// message, so both sides run at once.
+#[allow(clippy::too_many_arguments)]
+async fn send_via_bot(
+    user: &grammers_client::Client,
+    bot_ctx: &Context,
+    bot_chat: &types::Chat,
+    chat: &types::Chat,
+    input: types::InputMessage,
+    topic: Option<i32>,
+    signer: &Signer,
+    user_id: i64,
+) -> std::result::Result<types::Message, DeliveryError> {
+    // The query is signed, so only the answer to this very query is sent.
+    let nonce = Uuid::new_v4().to_string();
+    let signed = signer.sign(&nonce);
+
+    let send = async {
+        if let Some(topic) = topic {
+            return match utils::send_inline_result_to_topic(
+                user, bot_chat, chat, &signed, &signed, topic,
+            )
+            .await
+            {
+                Ok(Some(message)) => Ok(message),
+                Ok(None) => Err(DeliveryError::NotAnswered),
+                Err(e) => Err(DeliveryError::Other(e.to_string())),
+            };
+        }
+
+        let results = utils::inline_query(user, bot_chat, chat, &signed, 1)
+            .await
+            .map_err(|e| DeliveryError::Other(e.to_string()))?;
+        let Some(result) = results
+            .into_iter()
+            .find(|r| r.title().is_some_and(|t| *t == signed))
+        else {
+            return Err(DeliveryError::NotAnswered);
+        };
+
+        result.send(chat).await.map_err(DeliveryError::from)
+    };
+
+    let answer = async {
+        loop {
+            if let Ok(query) = bot_ctx.wait_for_inline_query(Some(10)).await {
+                // Queries from anyone else, or forged ones, are ignored.
+                if query.sender().id() == user_id
+                    && signer.verify(query.text()) == Some(nonce.as_str())
+                {
+                    query
+                        .answer(vec![
+                            inline::query::Article::new(signed.clone(), input).into()
+                        ])
+                        .send()
+                        .await?;
+
+                    return Ok::<_, DeliveryError>(());
+                }
+            }
+        }
+    };
+
+    let (message, _) = tokio::try_join!(send, answer)?;
+
+    Ok(message)
+}
+
+/// Sends the resulting message back, if it was asked for.
+fn respond<E: Into<DeliveryError>>(
+    responder: Option<oneshot::Sender<Response>>,
+    result: std::result::Result<types::Message, E>,
+) {
+    if let Some(responder) = responder {
+        // The sender may not be waiting anymore, which is fine.
+        let _ = responder.send(result.map_err(Into::into));
+    }
+}
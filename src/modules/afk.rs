@@ -0,0 +1,122 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the AFK module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::storage::Storage;
+
+/// The minimum interval between two automatic replies to the same user.
+const REPLY_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// The state shared by the filter and the handlers.
+static SHARED: LazyLock<Afk> = LazyLock::new(|| Afk {
+    storage: Storage::open("afk"),
+    replied: Arc::new(Mutex::new(HashMap::new())),
+});
+
+/// The away state of the user.
+#[derive(Clone)]
+pub struct Afk {
+    /// The persisted away state.
+    storage: Storage<Data>,
+    /// When each user was last replied to.
+    replied: Arc<Mutex<HashMap<i64, Instant>>>,
+}
+
+impl Afk {
+    /// Returns the shared state, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Checks if the user is away.
+    pub async fn is_away(&self) -> bool {
+        self.storage.read(|data| data.away.is_some()).await
+    }
+
+    /// Returns the current away state, if any.
+    pub async fn away(&self) -> Option<Away> {
+        self.storage.read(|data| data.away.clone()).await
+    }
+
+    /// Marks the user as away.
+    pub async fn set_away(&self, reason: Option<String>) {
+        self.replied.lock().await.clear();
+        self.storage
+            .write(|data| {
+                data.away = Some(Away {
+                    reason,
+                    since: Utc::now(),
+                    messages: 0,
+                })
+            })
+            .await;
+    }
+
+    /// Marks the user as back, returning the away state that ended.
+    pub async fn set_back(&self) -> Option<Away> {
+        self.storage.write(|data| data.away.take()).await
+    }
+
+    /// Records a message from a user while away.
+    ///
+    /// Returns `true` if they should get an automatic reply.
+    pub async fn record(&self, user_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                if let Some(away) = data.away.as_mut() {
+                    away.messages += 1;
+                }
+            })
+            .await;
+
+        let mut replied = self.replied.lock().await;
+        match replied.get(&user_id) {
+            Some(last) if last.elapsed() < REPLY_COOLDOWN => false,
+            _ => {
+                replied.insert(user_id, Instant::now());
+                true
+            }
+        }
+    }
+}
+
+/// The away state.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Away {
+    /// Why the user is away.
+    pub reason: Option<String>,
+    /// When the user went away.
+    pub since: DateTime<Utc>,
+    /// The messages received while away.
+    pub messages: usize,
+}
+
+impl Away {
+    /// Returns for how long the user has been away.
+    pub fn elapsed(&self) -> Duration {
+        (Utc::now() - self.since).to_std().unwrap_or_default()
+    }
+}
+
+/// The persisted away state.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The current away state.
+    away: Option<Away>,
+}
@@ -0,0 +1,278 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the keyword alerts module.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The manager shared by the filters.
+static SHARED: LazyLock<Alerts> = LazyLock::new(Alerts::open);
+
+/// The keyword alerts manager.
+#[derive(Clone)]
+pub struct Alerts {
+    /// The persisted data.
+    storage: Storage<Data>,
+    /// The compiled patterns of the keywords.
+    patterns: Arc<Mutex<Vec<(i32, Regex)>>>,
+    /// The last time each chat triggered an alert.
+    last_alerts: Arc<Mutex<HashMap<i64, Instant>>>,
+    /// In seconds, the minimum interval between two alerts of the same chat.
+    cooldown: Arc<AtomicU64>,
+    /// The chats that never trigger alerts, as the bot's and the accounts' own,
+    /// where the alerts themselves are posted.
+    ignored_chats: Arc<RwLock<Vec<i64>>>,
+}
+
+impl Alerts {
+    /// Returns the shared manager, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Opens the alerts storage.
+    fn open() -> Self {
+        let storage = Storage::<Data>::open("alerts");
+        let patterns = storage.read_now(|data| {
+            data.keywords
+                .iter()
+                .filter_map(|k| k.compile().map(|re| (k.id, re)))
+                .collect()
+        });
+
+        Self {
+            storage,
+            patterns: Arc::new(Mutex::new(patterns)),
+            last_alerts: Arc::new(Mutex::new(HashMap::new())),
+            cooldown: Arc::new(AtomicU64::new(0)),
+            ignored_chats: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Sets the minimum interval between two alerts of the same chat.
+    pub fn set_cooldown(&self, cooldown: Duration) {
+        self.cooldown.store(cooldown.as_secs(), Ordering::Relaxed);
+    }
+
+    /// Sets the chats that never trigger alerts, besides the muted ones.
+    pub fn set_ignored_chats(&self, chat_ids: Vec<i64>) {
+        *self
+            .ignored_chats
+            .write()
+            .expect("failed to lock ignored chats") = chat_ids;
+    }
+
+    /// Checks if a chat is ignored, either muted or where the alerts are posted.
+    pub async fn is_ignored(&self, chat_id: i64) -> bool {
+        self.ignored_chats
+            .read()
+            .expect("failed to lock ignored chats")
+            .contains(&chat_id)
+            || self.is_muted(chat_id).await
+    }
+
+    /// Adds a keyword.
+    ///
+    /// Returns the keyword ID, or `None` if the pattern is invalid.
+    pub async fn add_keyword(&self, pattern: impl Into<String>) -> Option<i32> {
+        let pattern = pattern.into();
+        let (pattern, regex) = match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+            Some(p) if !p.is_empty() => (p.to_string(), true),
+            _ => (pattern, false),
+        };
+
+        let mut keyword = Keyword {
+            id: 0,
+            pattern,
+            regex,
+        };
+        let re = keyword.compile()?;
+
+        let id = self
+            .storage
+            .write(|data| {
+                keyword.id = data.keywords.last().map(|k| k.id).unwrap_or(0) + 1;
+                data.keywords.push(keyword.clone());
+
+                keyword.id
+            })
+            .await;
+        self.patterns.lock().await.push((id, re));
+
+        Some(id)
+    }
+
+    /// Removes a keyword.
+    ///
+    /// Returns `true` if the keyword was removed, `false` otherwise.
+    pub async fn remove_keyword(&self, id: i32) -> bool {
+        self.patterns.lock().await.retain(|(i, _)| *i != id);
+
+        self.storage
+            .write(|data| {
+                let len = data.keywords.len();
+                data.keywords.retain(|k| k.id != id);
+
+                data.keywords.len() != len
+            })
+            .await
+    }
+
+    /// Returns the keywords.
+    pub async fn keywords(&self) -> Vec<Keyword> {
+        self.storage.read(|data| data.keywords.clone()).await
+    }
+
+    /// Returns the first keyword matching the given text.
+    pub async fn find_match(&self, text: &str) -> Option<Keyword> {
+        let id = self
+            .patterns
+            .lock()
+            .await
+            .iter()
+            .find(|(_, re)| re.is_match(text))
+            .map(|(id, _)| *id)?;
+
+        self.storage
+            .read(|data| data.keywords.iter().find(|k| k.id == id).cloned())
+            .await
+    }
+
+    /// Mutes a chat, so it never triggers alerts.
+    ///
+    /// Returns `true` if the chat was muted, `false` if it already was.
+    pub async fn mute(&self, chat_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                if data.muted_chats.contains(&chat_id) {
+                    false
+                } else {
+                    data.muted_chats.push(chat_id);
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Unmutes a chat.
+    ///
+    /// Returns `true` if the chat was unmuted, `false` if it was not muted.
+    pub async fn unmute(&self, chat_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.muted_chats.len();
+                data.muted_chats.retain(|id| *id != chat_id);
+
+                data.muted_chats.len() != len
+            })
+            .await
+    }
+
+    /// Checks if a chat is muted.
+    pub async fn is_muted(&self, chat_id: i64) -> bool {
+        self.storage
+            .read(|data| data.muted_chats.contains(&chat_id))
+            .await
+    }
+
+    /// Checks if a chat is allowed to trigger an alert now, registering it if so.
+    pub async fn should_notify(&self, chat_id: i64) -> bool {
+        let mut last_alerts = self.last_alerts.lock().await;
+        let cooldown = Duration::from_secs(self.cooldown.load(Ordering::Relaxed));
+        let now = Instant::now();
+
+        match last_alerts.get(&chat_id) {
+            Some(last) if now.duration_since(*last) < cooldown => false,
+            _ => {
+                last_alerts.insert(chat_id, now);
+                true
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Alerts {
+    fn name(&self) -> &'static str {
+        "muted chats"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::replace_id(&mut data.muted_chats, from, to))
+            .await
+    }
+}
+
+/// The persisted alerts data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The watched keywords.
+    keywords: Vec<Keyword>,
+    /// The chats that never trigger alerts.
+    muted_chats: Vec<i64>,
+}
+
+/// A watched keyword.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Keyword {
+    /// The keyword ID.
+    id: i32,
+    /// The keyword pattern.
+    pattern: String,
+    /// Whether the pattern is a regex.
+    regex: bool,
+}
+
+impl Keyword {
+    /// Returns the keyword ID.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Returns the keyword pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Checks if the pattern is a regex.
+    pub fn is_regex(&self) -> bool {
+        self.regex
+    }
+
+    /// Compiles the keyword into a case-insensitive regex.
+    fn compile(&self) -> Option<Regex> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            format!(r"\b{}\b", regex::escape(&self.pattern))
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .ok()
+    }
+}
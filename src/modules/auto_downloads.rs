@@ -0,0 +1,444 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the auto-download rules module.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{Local, NaiveTime, TimeDelta};
+use grammers_client::{
+    grammers_tl_types as tl,
+    session::{PackedChat, PackedType},
+    types::Media,
+    InputMessage,
+};
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    i18n::I18n,
+    migrations::{self, ChatMigration},
+    shutdown::Shutdown,
+    storage::Storage,
+};
+use crate::{
+    utils::{escape_html, human_readable_size},
+    Priority, Sender,
+};
+
+/// The most files remembered as saved, the oldest forgotten.
+const MAX_SAVED: usize = 5000;
+
+/// Saves the media posted in the chats with a rule, to the disk or to a
+/// channel, and sums up what was saved once a day.
+#[derive(Clone)]
+pub struct AutoDownloads {
+    /// The persisted rules, saved files and summary.
+    storage: Storage<Data>,
+}
+
+impl AutoDownloads {
+    /// Creates a new `AutoDownloads` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("auto_downloads"),
+        }
+    }
+
+    /// Returns the rule of a chat, if it has one.
+    pub async fn rule(&self, chat_id: i64) -> Option<Rule> {
+        self.storage
+            .read(|data| data.rules.get(&chat_id).cloned())
+            .await
+    }
+
+    /// Returns the rules, by chat, sorted by the name of the chat.
+    pub async fn rules(&self) -> Vec<(i64, Rule)> {
+        let mut rules = self
+            .storage
+            .read(|data| {
+                data.rules
+                    .iter()
+                    .map(|(chat_id, rule)| (*chat_id, rule.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        rules.sort_by(|(_, a), (_, b)| a.chat_name.cmp(&b.chat_name));
+
+        rules
+    }
+
+    /// Sets the rule of a chat.
+    ///
+    /// Returns `true` if it replaced another one.
+    pub async fn add(&self, chat_id: i64, rule: Rule) -> bool {
+        self.storage
+            .write(|data| data.rules.insert(chat_id, rule).is_some())
+            .await
+    }
+
+    /// Removes the rule of a chat.
+    ///
+    /// Returns `false` if the chat had none.
+    pub async fn remove(&self, chat_id: i64) -> bool {
+        self.storage
+            .write(|data| data.rules.remove(&chat_id).is_some())
+            .await
+    }
+
+    /// Claims a file to be saved.
+    ///
+    /// Returns `false` if it was already saved, or is being saved, from any chat.
+    pub async fn claim(&self, file_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                if data.saved.contains(&file_id) {
+                    return false;
+                }
+
+                data.saved.push_back(file_id);
+                if data.saved.len() > MAX_SAVED {
+                    data.saved.pop_front();
+                }
+
+                true
+            })
+            .await
+    }
+
+    /// Releases a claimed file that failed to be saved, so it is tried again.
+    pub async fn release(&self, file_id: i64) {
+        self.storage
+            .write(|data| data.saved.retain(|id| *id != file_id))
+            .await
+    }
+
+    /// Adds a saved file to the summary of the day.
+    pub async fn saved(&self, chat_id: i64, size: u64) {
+        self.storage
+            .write(|data| {
+                let chat = data.summary.chats.entry(chat_id).or_default();
+                chat.files += 1;
+                chat.bytes += size;
+            })
+            .await
+    }
+
+    /// Adds a skipped file to the summary of the day.
+    pub async fn skipped(&self, skip: Skip) {
+        self.storage
+            .write(|data| {
+                let summary = &mut data.summary;
+                match skip {
+                    Skip::Duplicate => summary.duplicates += 1,
+                    Skip::TooBig => summary.too_big += 1,
+                    Skip::OverBudget => summary.over_budget += 1,
+                    Skip::Failed => summary.failed += 1,
+                }
+            })
+            .await
+    }
+
+    /// Renders the summary of the day, `None` if nothing happened.
+    async fn render_summary(&self, i18n: &I18n) -> Option<String> {
+        let (summary, names) = self
+            .storage
+            .write(|data| {
+                let names = data
+                    .rules
+                    .iter()
+                    .map(|(chat_id, rule)| (*chat_id, rule.chat_name.clone()))
+                    .collect::<HashMap<_, _>>();
+
+                (std::mem::take(&mut data.summary), names)
+            })
+            .await;
+        if summary.is_empty() {
+            return None;
+        }
+
+        let mut chats = summary.chats.iter().collect::<Vec<_>>();
+        chats.sort_by(|(_, a), (_, b)| b.files.cmp(&a.files));
+
+        let mut text = i18n.translate_with_args(
+            "autodl_summary",
+            hashmap! {
+                "files" => chats.iter().map(|(_, chat)| chat.files).sum::<usize>().to_string(),
+                "size" => human_readable_size(
+                    chats.iter().map(|(_, chat)| chat.bytes).sum::<u64>() as usize,
+                ),
+            },
+        );
+        for (chat_id, chat) in chats.iter() {
+            let name = names
+                .get(*chat_id)
+                .cloned()
+                .unwrap_or_else(|| chat_id.to_string());
+
+            text += "\n";
+            text += &i18n.translate_with_args(
+                "autodl_summary_chat",
+                hashmap! {
+                    "chat" => escape_html(&name),
+                    "files" => chat.files.to_string(),
+                    "size" => human_readable_size(chat.bytes as usize),
+                },
+            );
+        }
+        if summary.skipped() > 0 {
+            text += "\n\n";
+            text += &i18n.translate_with_args(
+                "autodl_summary_skipped",
+                hashmap! {
+                    "duplicates" => summary.duplicates.to_string(),
+                    "too_big" => summary.too_big.to_string(),
+                    "over_budget" => summary.over_budget.to_string(),
+                    "failed" => summary.failed.to_string(),
+                },
+            );
+        }
+
+        Some(text)
+    }
+
+    /// Spawns the task that sends the summary of the day to the owner, at
+    /// midnight.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                let now = Local::now().naive_local();
+                let next = (now.date() + TimeDelta::days(1)).and_time(NaiveTime::MIN);
+
+                let wait = (next - now).to_std().unwrap_or(Duration::from_secs(60));
+                tokio::time::sleep(wait).await;
+
+                let Some(text) = self.render_summary(&i18n).await else {
+                    continue;
+                };
+                if let Err(e) = tx
+                    .send(
+                        crate::Message::to_bot()
+                            .notify(InputMessage::html(text))
+                            .with_priority(Priority::Low),
+                    )
+                    .await
+                {
+                    log::error!("Failed to send the auto-download summary: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl ChatMigration for AutoDownloads {
+    fn name(&self) -> &'static str {
+        "auto-download rule"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| {
+                migrations::move_key(&mut data.summary.chats, from, to);
+                migrations::move_key(&mut data.rules, from, to)
+            })
+            .await
+    }
+}
+
+/// What is saved from a chat and where.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rule {
+    /// The name of the chat, when the rule was added.
+    pub chat_name: String,
+    /// The kinds of media saved, every one if empty.
+    pub kinds: Vec<Kind>,
+    /// Where the media is saved.
+    pub target: Target,
+    /// In bytes, the biggest file saved, if limited.
+    pub max_size: Option<u64>,
+}
+
+impl Rule {
+    /// Checks if a kind of media is saved.
+    pub fn matches(&self, kind: Kind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+}
+
+/// Where the media of a rule is saved.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    /// A directory on the disk.
+    Dir(String),
+    /// A channel the messages are forwarded to.
+    Channel {
+        /// The ID of the channel.
+        id: i64,
+        /// The access hash of the channel.
+        access_hash: Option<i64>,
+        /// The title of the channel.
+        title: String,
+    },
+}
+
+impl Target {
+    /// Returns the packed channel, if the target is one.
+    pub fn channel(&self) -> Option<PackedChat> {
+        match self {
+            Self::Dir(_) => None,
+            Self::Channel {
+                id, access_hash, ..
+            } => Some(PackedChat {
+                ty: PackedType::Broadcast,
+                id: *id,
+                access_hash: *access_hash,
+            }),
+        }
+    }
+}
+
+/// The kinds of media a rule saves.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Photo,
+    Video,
+    Gif,
+    Audio,
+    Voice,
+    Document,
+    Sticker,
+}
+
+impl Kind {
+    /// Every kind, in the order they are listed.
+    pub const ALL: [Self; 7] = [
+        Self::Photo,
+        Self::Video,
+        Self::Gif,
+        Self::Audio,
+        Self::Voice,
+        Self::Document,
+        Self::Sticker,
+    ];
+
+    /// Parses a kind of media.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == name.to_lowercase())
+    }
+
+    /// Returns the kind of a media, `None` if it has no file.
+    pub fn of(media: &Media) -> Option<Self> {
+        let document = match media {
+            Media::Photo(_) => return Some(Self::Photo),
+            Media::Sticker(_) => return Some(Self::Sticker),
+            Media::Document(document) => document,
+            _ => return None,
+        };
+        let Some(tl::enums::Document::Document(raw)) = &document.raw.document else {
+            return Some(Self::Document);
+        };
+
+        // The GIFs have a video attribute too, so they are checked first.
+        let has = |f: fn(&tl::enums::DocumentAttribute) -> bool| raw.attributes.iter().any(f);
+        let kind = if has(|a| matches!(a, tl::enums::DocumentAttribute::Animated)) {
+            Self::Gif
+        } else if has(|a| matches!(a, tl::enums::DocumentAttribute::Video(_))) {
+            Self::Video
+        } else if has(|a| matches!(a, tl::enums::DocumentAttribute::Audio(audio) if audio.voice)) {
+            Self::Voice
+        } else if has(|a| matches!(a, tl::enums::DocumentAttribute::Audio(_))) {
+            Self::Audio
+        } else {
+            Self::Document
+        };
+
+        Some(kind)
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Photo => write!(f, "photo"),
+            Self::Video => write!(f, "video"),
+            Self::Gif => write!(f, "gif"),
+            Self::Audio => write!(f, "audio"),
+            Self::Voice => write!(f, "voice"),
+            Self::Document => write!(f, "document"),
+            Self::Sticker => write!(f, "sticker"),
+        }
+    }
+}
+
+/// Why a file was not saved.
+pub enum Skip {
+    /// It was already saved.
+    Duplicate,
+    /// It is bigger than the rule allows.
+    TooBig,
+    /// The daily budget of the plugin is used up.
+    OverBudget,
+    /// It failed to be saved.
+    Failed,
+}
+
+/// What was saved and skipped since the last summary.
+#[derive(Default, Serialize, Deserialize)]
+struct Summary {
+    /// What was saved from each chat.
+    chats: HashMap<i64, ChatSummary>,
+    /// How many files were skipped for being already saved.
+    duplicates: usize,
+    /// How many files were skipped for being too big.
+    too_big: usize,
+    /// How many files were skipped for the budget being used up.
+    over_budget: usize,
+    /// How many files failed to be saved.
+    failed: usize,
+}
+
+impl Summary {
+    /// Returns how many files were not saved.
+    fn skipped(&self) -> usize {
+        self.duplicates + self.too_big + self.over_budget + self.failed
+    }
+
+    /// Checks if nothing was saved nor skipped.
+    fn is_empty(&self) -> bool {
+        self.chats.is_empty() && self.skipped() == 0
+    }
+}
+
+/// What was saved from a chat.
+#[derive(Default, Serialize, Deserialize)]
+struct ChatSummary {
+    /// How many files were saved.
+    files: usize,
+    /// In bytes, how much they take.
+    bytes: u64,
+}
+
+/// The persisted auto-download data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The rule of each chat.
+    rules: HashMap<i64, Rule>,
+    /// The IDs of the files saved, the oldest first.
+    saved: VecDeque<i64>,
+    /// What was saved and skipped since the last summary.
+    summary: Summary,
+}
@@ -0,0 +1,246 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the business connections module.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, RwLock,
+    },
+};
+
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, parsers, session::PackedChat, Client};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::storage::Storage;
+
+/// The maximum number of business messages that can still be replied to.
+const MAX_ROUTES: usize = 500;
+/// The maximum number of users remembered with their access hash.
+const MAX_PEERS: usize = 5000;
+
+/// The users seen by the bot, with the access hash it must address them with.
+///
+/// Filled by a filter, as the filters cannot be injected.
+static PEERS: LazyLock<std::sync::Mutex<HashMap<i64, PackedChat>>> =
+    LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Remembers a user seen by the bot, so the business replies can address them.
+pub fn remember_peer(chat: PackedChat) {
+    if chat.access_hash.is_none() {
+        return;
+    }
+
+    let mut peers = PEERS.lock().expect("failed to lock peers");
+    if peers.len() >= MAX_PEERS && !peers.contains_key(&chat.id) {
+        peers.clear();
+    }
+    peers.insert(chat.id, chat);
+}
+
+/// The business connections manager.
+///
+/// Business messages are forwarded to the owner tagged with their connection,
+/// so replying to them answers on behalf of the business account.
+#[derive(Clone)]
+pub struct Business {
+    /// Whether business connections are accepted.
+    enabled: Arc<AtomicBool>,
+    /// The users allowed to connect, none if empty.
+    owners: Arc<RwLock<Vec<i64>>>,
+    /// The known connections.
+    storage: Storage<Data>,
+    /// The business messages forwarded to the owner, by notification message ID.
+    routes: Arc<Mutex<HashMap<i32, Route>>>,
+}
+
+impl Business {
+    /// Creates a new `Business` instance.
+    pub fn new(enabled: bool, owners: Vec<i64>) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            owners: Arc::new(RwLock::new(owners)),
+            storage: Storage::open("business"),
+            routes: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets whether business connections are accepted, and from whom.
+    pub fn configure(&self, enabled: bool, owners: Vec<i64>) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        *self.owners.write().expect("failed to lock owners") = owners;
+    }
+
+    /// Checks if business connections are accepted.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Checks if a user is allowed to connect.
+    ///
+    /// Only the listed owners are, so enabling the feature alone lets no one in.
+    pub fn is_allowed(&self, user_id: i64) -> bool {
+        self.is_enabled()
+            && self
+                .owners
+                .read()
+                .expect("failed to lock owners")
+                .contains(&user_id)
+    }
+
+    /// Records a new, updated or disabled connection.
+    ///
+    /// Returns `false` if the connection was rejected.
+    pub async fn update(&self, raw: &tl::types::BotBusinessConnection) -> bool {
+        let accepted = !raw.disabled && self.is_allowed(raw.user_id);
+
+        self.storage
+            .write(|data| {
+                data.connections.retain(|c| c.id != raw.connection_id);
+
+                if accepted {
+                    data.connections.push(Connection {
+                        id: raw.connection_id.clone(),
+                        user_id: raw.user_id,
+                        can_reply: raw.can_reply,
+                    });
+                }
+            })
+            .await;
+
+        accepted
+    }
+
+    /// Returns an accepted connection, if its user is still allowed.
+    pub async fn connection(&self, connection_id: &str) -> Option<Connection> {
+        self.storage
+            .read(|data| {
+                data.connections
+                    .iter()
+                    .find(|c| c.id == connection_id)
+                    .cloned()
+            })
+            .await
+            .filter(|connection| self.is_allowed(connection.user_id))
+    }
+
+    /// Tags a notification with the business message it was sent for.
+    pub async fn track(&self, notification_id: i32, route: Route) {
+        let mut routes = self.routes.lock().await;
+
+        if routes.len() >= MAX_ROUTES {
+            if let Some(oldest) = routes.keys().min().copied() {
+                routes.remove(&oldest);
+            }
+        }
+
+        routes.insert(notification_id, route);
+    }
+
+    /// Returns the business message a notification was sent for.
+    pub async fn route(&self, notification_id: i32) -> Option<Route> {
+        self.routes.lock().await.get(&notification_id).cloned()
+    }
+
+    /// Returns the customer of a business message with their access hash, `None`
+    /// if the bot has not seen them yet.
+    pub fn peer(&self, user_id: i64) -> Option<PackedChat> {
+        PEERS
+            .lock()
+            .expect("failed to lock peers")
+            .get(&user_id)
+            .copied()
+    }
+
+    /// Replies to a business message on behalf of the connected account.
+    ///
+    /// Fails if the access hash of the customer is not known, as Telegram rejects
+    /// the users addressed without it.
+    pub async fn reply(&self, client: &Client, route: &Route, html: &str) -> Result<()> {
+        let peer = self.peer(route.user_id).ok_or_else(|| {
+            format!(
+                "The access hash of the user {} is not known yet",
+                route.user_id
+            )
+        })?;
+        let (message, entities) = parsers::parse_html_message(html);
+
+        let request = tl::functions::messages::SendMessage {
+            no_webpage: false,
+            silent: false,
+            background: false,
+            clear_draft: false,
+            noforwards: false,
+            update_stickersets_order: false,
+            invert_media: false,
+            allow_paid_floodskip: false,
+            peer: peer.to_input_peer(),
+            reply_to: Some(
+                tl::types::InputReplyToMessage {
+                    reply_to_msg_id: route.message_id,
+                    top_msg_id: None,
+                    reply_to_peer_id: None,
+                    quote_text: None,
+                    quote_entities: None,
+                    quote_offset: None,
+                }
+                .into(),
+            ),
+            message,
+            random_id: rand::random(),
+            reply_markup: None,
+            entities: (!entities.is_empty()).then_some(entities),
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+            effect: None,
+        };
+
+        client
+            .invoke(&tl::functions::InvokeWithBusinessConnection {
+                connection_id: route.connection_id.clone(),
+                query: request,
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A business connection.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Connection {
+    /// The connection ID.
+    pub id: String,
+    /// The business account that connected the bot.
+    pub user_id: i64,
+    /// Whether the bot can reply on behalf of the account.
+    pub can_reply: bool,
+}
+
+/// A business message forwarded to the owner.
+#[derive(Clone)]
+pub struct Route {
+    /// The connection the message was received through.
+    pub connection_id: String,
+    /// The customer who sent the message.
+    pub user_id: i64,
+    /// The ID of the message.
+    pub message_id: i32,
+}
+
+/// The persisted business connections.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The accepted connections.
+    connections: Vec<Connection>,
+}
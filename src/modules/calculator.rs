@@ -0,0 +1,223 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the calculator module.
+
+use std::{iter::Peekable, str::Chars};
+
+/// The deepest an expression can nest, so a long one cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Why an expression could not be evaluated.
+pub enum CalcError {
+    /// It does not parse, near the given token.
+    Invalid(String),
+    /// It calls a function that does not exist.
+    UnknownFunction(String),
+    /// It nests deeper than the parser goes.
+    TooDeep,
+    /// Its result is not a number, as a division by zero.
+    Undefined,
+}
+
+/// Evaluates an arithmetic expression.
+///
+/// It has the `+`, `-`, `*`, `/`, `%` and `^` operators, the parentheses, the
+/// `pi` and `e` constants and the `sqrt`, `abs`, `ln`, `log`, `sin`, `cos`,
+/// `tan`, `round`, `floor` and `ceil` functions. Both `.` and `,` are taken as
+/// the decimal separator.
+pub fn evaluate(expression: &str) -> Result<f64, CalcError> {
+    let mut parser = Parser {
+        chars: expression.chars().peekable(),
+        depth: 0,
+    };
+
+    let value = parser.expression()?;
+    parser.skip_whitespace();
+    if let Some(c) = parser.chars.peek() {
+        return Err(CalcError::Invalid(c.to_string()));
+    }
+
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(CalcError::Undefined)
+    }
+}
+
+/// Formats a result, without the decimal part of the whole numbers.
+pub fn format(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// A recursive descent parser, evaluating as it parses.
+struct Parser<'a> {
+    /// The characters left.
+    chars: Peekable<Chars<'a>>,
+    /// How deep the parser is in the expression.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Parses the sums and subtractions.
+    fn expression(&mut self) -> Result<f64, CalcError> {
+        let mut value = self.term()?;
+
+        loop {
+            match self.next_operator(&['+', '-']) {
+                Some('+') => value += self.term()?,
+                Some('-') => value -= self.term()?,
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// Parses the products, divisions and remainders.
+    fn term(&mut self) -> Result<f64, CalcError> {
+        let mut value = self.power()?;
+
+        loop {
+            match self.next_operator(&['*', '/', '%']) {
+                Some('*') => value *= self.power()?,
+                Some('/') => value /= self.power()?,
+                Some('%') => value %= self.power()?,
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// Parses the powers, which are right-associative.
+    fn power(&mut self) -> Result<f64, CalcError> {
+        let base = self.unary()?;
+
+        match self.next_operator(&['^']) {
+            Some(_) => Ok(base.powf(self.nested(Self::power)?)),
+            None => Ok(base),
+        }
+    }
+
+    /// Parses the signs.
+    fn unary(&mut self) -> Result<f64, CalcError> {
+        match self.next_operator(&['-', '+']) {
+            Some('-') => Ok(-self.nested(Self::unary)?),
+            Some(_) => self.nested(Self::unary),
+            None => self.atom(),
+        }
+    }
+
+    /// Parses the numbers, constants, functions and parentheses.
+    fn atom(&mut self) -> Result<f64, CalcError> {
+        self.skip_whitespace();
+
+        match self.chars.peek().copied() {
+            Some('(') => {
+                self.chars.next();
+                let value = self.nested(Self::expression)?;
+                self.expect(')')?;
+
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' || c == ',' => self.number(),
+            Some(c) if c.is_alphabetic() => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric()) {
+                    name.push(c.to_ascii_lowercase());
+                }
+
+                match name.as_str() {
+                    "pi" => Ok(std::f64::consts::PI),
+                    "e" => Ok(std::f64::consts::E),
+                    _ => {
+                        let function = function(&name)
+                            .ok_or_else(|| CalcError::UnknownFunction(name.clone()))?;
+                        self.skip_whitespace();
+                        self.expect('(')?;
+                        let value = self.nested(Self::expression)?;
+                        self.expect(')')?;
+
+                        Ok(function(value))
+                    }
+                }
+            }
+            Some(c) => Err(CalcError::Invalid(c.to_string())),
+            None => Err(CalcError::Invalid(String::new())),
+        }
+    }
+
+    /// Parses a number.
+    fn number(&mut self) -> Result<f64, CalcError> {
+        let mut number = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        {
+            number.push(if c == ',' { '.' } else { c });
+        }
+
+        number
+            .parse::<f64>()
+            .map_err(|_| CalcError::Invalid(number))
+    }
+
+    /// Parses a part of the expression one level deeper.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<f64, CalcError>) -> Result<f64, CalcError> {
+        if self.depth == MAX_DEPTH {
+            return Err(CalcError::TooDeep);
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    /// Takes the next operator if it is one of the given ones.
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+
+        self.chars.next_if(|c| operators.contains(c))
+    }
+
+    /// Takes the given character, failing if it is not next.
+    fn expect(&mut self, expected: char) -> Result<(), CalcError> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(CalcError::Invalid(c.to_string())),
+            None => Err(CalcError::Invalid(String::new())),
+        }
+    }
+
+    /// Skips the whitespace.
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+/// Returns the function with the given name.
+fn function(name: &str) -> Option<fn(f64) -> f64> {
+    Some(match name {
+        "sqrt" => f64::sqrt,
+        "abs" => f64::abs,
+        "ln" => f64::ln,
+        "log" => f64::log10,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "round" => f64::round,
+        "floor" => f64::floor,
+        "ceil" => f64::ceil,
+        _ => return None,
+    })
+}
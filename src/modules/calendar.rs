@@ -0,0 +1,579 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the calendar module.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{
+    DateTime, Datelike, Local, Months, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use ferogram::Result;
+use grammers_client::InputMessage;
+use maplit::hashmap;
+use tokio::sync::Mutex;
+
+use super::{digest::Widget, i18n::I18n, shutdown::Shutdown};
+use crate::{utils::escape_html, Sender};
+
+/// In days, how far ahead the recurring events are expanded.
+const RECURRENCE_HORIZON: i64 = 90;
+
+/// The most occurrences of a recurring event looked at.
+const MAX_RECURRENCES: u32 = 5000;
+
+/// The calendar synchronized from an ICS source.
+#[derive(Clone)]
+pub struct Calendar {
+    /// The URL of the ICS source.
+    url: String,
+    /// The credentials of the source, if any.
+    credentials: Option<(String, String)>,
+    /// How often the source is synchronized.
+    sync_interval: Duration,
+    /// How long before each event the owner is notified.
+    lead_times: Vec<Duration>,
+    /// The synchronized events, sorted by start.
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+impl Calendar {
+    /// Creates a new `Calendar` instance.
+    pub fn new(
+        url: impl Into<String>,
+        credentials: Option<(String, String)>,
+        sync_interval: Duration,
+        lead_times: Vec<Duration>,
+    ) -> Self {
+        Self {
+            url: url.into(),
+            credentials,
+            sync_interval,
+            lead_times,
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Checks if a source is configured.
+    pub fn is_configured(&self) -> bool {
+        !self.url.is_empty()
+    }
+
+    /// Synchronizes the events from the source.
+    pub async fn sync(&self) -> Result<usize> {
+        let mut request = reqwest::Client::new().get(&self.url);
+        if let Some((username, password)) = &self.credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+
+        let content = request.send().await?.error_for_status()?.text().await?;
+        let mut events = parse_ics(&content, Local::now());
+        events.sort_by_key(|e| e.start);
+
+        let count = events.len();
+        *self.events.lock().await = events;
+
+        Ok(count)
+    }
+
+    /// Returns the events of the given day.
+    pub async fn agenda(&self, day: NaiveDate) -> Vec<Event> {
+        self.events
+            .lock()
+            .await
+            .iter()
+            .filter(|e| e.start.date_naive() == day)
+            .cloned()
+            .collect()
+    }
+
+    /// Renders the agenda of the given day.
+    pub async fn render_agenda(&self, i18n: &I18n, day: NaiveDate) -> String {
+        let events = self.agenda(day).await;
+
+        let mut text = i18n.translate_with_args(
+            "agenda_header",
+            hashmap! { "day" => day.format("%d/%m/%Y").to_string() },
+        );
+
+        if events.is_empty() {
+            text += &i18n.translate("agenda_empty");
+        }
+
+        for event in events {
+            text += &format!("\n{}", event.render(i18n));
+        }
+
+        text
+    }
+
+    /// Spawns the tasks that synchronize the source and notify the owner of upcoming events.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        if !self.is_configured() {
+            return;
+        }
+
+        let calendar = self.clone();
+        Shutdown::shared().spawn(async move {
+            loop {
+                if let Err(e) = calendar.sync().await {
+                    log::error!("Failed to synchronize the calendar: {}", e);
+                }
+
+                tokio::time::sleep(calendar.sync_interval).await;
+            }
+        });
+
+        Shutdown::shared().spawn(async move {
+            let mut notified = HashSet::new();
+
+            loop {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+
+                let now = Local::now();
+                let events = self.events.lock().await.clone();
+
+                for event in events.iter().filter(|e| !e.all_day && e.start > now) {
+                    for lead_time in self.lead_times.iter() {
+                        let lead = TimeDelta::from_std(*lead_time).unwrap_or_default();
+                        let due = event.start - lead;
+                        if due > now || now - due > TimeDelta::minutes(1) {
+                            continue;
+                        }
+
+                        if !notified.insert((event.uid.clone(), *lead_time)) {
+                            continue;
+                        }
+
+                        let text = i18n.translate_with_args(
+                            "agenda_upcoming",
+                            hashmap! {
+                                "minutes" => (lead_time.as_secs() / 60).to_string(),
+                                "event" => event.render(&i18n),
+                            },
+                        );
+                        if let Err(e) = tx
+                            .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                            .await
+                        {
+                            log::error!("Failed to send the event notification: {}", e);
+                        }
+                    }
+                }
+
+                // Forgets the notifications of the past events.
+                notified.retain(|(uid, _)| {
+                    events
+                        .iter()
+                        .any(|e| e.uid == *uid && e.start > now - TimeDelta::hours(1))
+                });
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Widget for Calendar {
+    fn name(&self) -> &'static str {
+        "calendar"
+    }
+
+    async fn render(&self, i18n: &I18n) -> Result<String> {
+        Ok(self.render_agenda(i18n, Local::now().date_naive()).await)
+    }
+}
+
+/// A calendar event.
+#[derive(Clone)]
+pub struct Event {
+    /// The event unique ID.
+    pub uid: String,
+    /// The event summary.
+    pub summary: String,
+    /// The event location.
+    pub location: Option<String>,
+    /// The event start.
+    pub start: DateTime<Local>,
+    /// Whether the event lasts the whole day.
+    pub all_day: bool,
+}
+
+impl Event {
+    /// Renders the event as a line.
+    pub fn render(&self, i18n: &I18n) -> String {
+        let time = if self.all_day {
+            i18n.translate("agenda_all_day")
+        } else {
+            self.start.format("%H:%M").to_string()
+        };
+
+        match &self.location {
+            Some(location) => format!(
+                "• <code>{0}</code> {1} ({2})",
+                time,
+                escape_html(&self.summary),
+                escape_html(location)
+            ),
+            None => format!("• <code>{0}</code> {1}", time, escape_html(&self.summary)),
+        }
+    }
+}
+
+/// Parses the events of an ICS document, expanding the recurring ones between a
+/// day before `now` and `RECURRENCE_HORIZON` days after it.
+///
+/// The recurring events with a rule that cannot be expanded are skipped.
+fn parse_ics(content: &str, now: DateTime<Local>) -> Vec<Event> {
+    // Unfolds the continuation lines.
+    let unfolded = content
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut drafts = Vec::new();
+    let mut current: Option<Draft> = None;
+
+    for line in unfolded.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, params) = name.split_once(';').unwrap_or((name, ""));
+
+        match (key, current.as_mut()) {
+            ("BEGIN", None) if value == "VEVENT" => current = Some(Draft::default()),
+            ("END", Some(_)) if value == "VEVENT" => drafts.extend(current.take()),
+            ("UID", Some(draft)) => draft.uid = value.to_string(),
+            ("SUMMARY", Some(draft)) => draft.summary = unescape(value),
+            ("LOCATION", Some(draft)) if !value.is_empty() => {
+                draft.location = Some(unescape(value))
+            }
+            ("DTSTART", Some(draft)) => draft.start = parse_date(value, params),
+            ("RRULE", Some(draft)) => draft.rule = Some(value.to_string()),
+            ("EXDATE", Some(draft)) => draft.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|value| parse_date(value, params)?.resolve()),
+            ),
+            ("RECURRENCE-ID", Some(draft)) => {
+                draft.recurrence_id = parse_date(value, params).and_then(|date| date.resolve())
+            }
+            _ => {}
+        }
+    }
+
+    // The occurrences moved or changed are events of their own.
+    let overridden = drafts
+        .iter()
+        .filter_map(|draft| Some((draft.uid.clone(), draft.recurrence_id?)))
+        .collect::<HashSet<_>>();
+    let from = now - TimeDelta::days(1);
+    let to = now + TimeDelta::days(RECURRENCE_HORIZON);
+
+    let mut events = Vec::new();
+    for draft in drafts {
+        match draft.rule.as_deref() {
+            None => events.extend(draft.finish()),
+            Some(rule) => match Rule::parse(rule) {
+                Some(rule) => events.extend(draft.expand(&rule, &overridden, from, to)),
+                None => log::warn!(
+                    "Skipping the recurring event {0}, its rule {1} is not supported",
+                    draft.uid,
+                    rule
+                ),
+            },
+        }
+    }
+
+    events
+}
+
+/// An event being parsed.
+#[derive(Default)]
+struct Draft {
+    uid: String,
+    summary: String,
+    location: Option<String>,
+    start: Option<Start>,
+    rule: Option<String>,
+    exdates: Vec<DateTime<Local>>,
+    recurrence_id: Option<DateTime<Local>>,
+}
+
+impl Draft {
+    /// Finishes the event, if it has a start.
+    fn finish(self) -> Option<Event> {
+        let start = self.start.as_ref()?;
+        let uid = match self.recurrence_id {
+            Some(recurrence_id) => occurrence_uid(&self.uid, recurrence_id),
+            None => self.uid.clone(),
+        };
+
+        Some(Event {
+            uid,
+            summary: self.summary,
+            location: self.location,
+            start: start.resolve()?,
+            all_day: start.all_day,
+        })
+    }
+
+    /// Expands the occurrences of a recurring event starting between `from` and
+    /// `to`, without the excluded and overridden ones.
+    fn expand(
+        &self,
+        rule: &Rule,
+        overridden: &HashSet<(String, DateTime<Local>)>,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Vec<Event> {
+        let Some(start) = self.start.as_ref() else {
+            return Vec::new();
+        };
+
+        // The daily and weekly occurrences never fall on an invalid date, so the
+        // past ones can be skipped at once.
+        let mut index = match rule.frequency {
+            Frequency::Daily | Frequency::Weekly => {
+                let days = rule.frequency.days() * rule.interval as i64;
+                let elapsed = (from.date_naive() - start.date.date()).num_days();
+
+                (elapsed / days - 1).max(0) as u32
+            }
+            Frequency::Monthly | Frequency::Yearly => 0,
+        };
+
+        let mut events = Vec::new();
+        let mut count = index as usize;
+        while index < MAX_RECURRENCES {
+            if rule.count.is_some_and(|max| count >= max) {
+                break;
+            }
+
+            let Some(date) = rule.occurrence(start.date, index) else {
+                index += 1;
+                continue;
+            };
+            index += 1;
+            count += 1;
+
+            let occurrence = Start { date, ..*start };
+            let Some(at) = occurrence.resolve() else {
+                continue;
+            };
+            if at > to || rule.until.is_some_and(|until| at > until) {
+                break;
+            }
+            if at < from
+                || self.exdates.contains(&at)
+                || overridden.contains(&(self.uid.clone(), at))
+            {
+                continue;
+            }
+
+            events.push(Event {
+                uid: occurrence_uid(&self.uid, at),
+                summary: self.summary.clone(),
+                location: self.location.clone(),
+                start: at,
+                all_day: start.all_day,
+            });
+        }
+
+        events
+    }
+}
+
+/// Returns the ID of an occurrence of a recurring event, so each is notified.
+fn occurrence_uid(uid: &str, start: DateTime<Local>) -> String {
+    format!("{0}@{1}", uid, start.timestamp())
+}
+
+/// The start of an event, as written in the document.
+#[derive(Clone, Copy)]
+struct Start {
+    /// The wall time, in the time zone.
+    date: NaiveDateTime,
+    /// The time zone.
+    zone: Zone,
+    /// Whether the event lasts the whole day.
+    all_day: bool,
+}
+
+impl Start {
+    /// Resolves the start to the local time.
+    fn resolve(&self) -> Option<DateTime<Local>> {
+        match self.zone {
+            Zone::Utc => Some(Utc.from_utc_datetime(&self.date).with_timezone(&Local)),
+            Zone::Named(tz) => Some(
+                tz.from_local_datetime(&self.date)
+                    .earliest()?
+                    .with_timezone(&Local),
+            ),
+            Zone::Local => Local.from_local_datetime(&self.date).earliest(),
+        }
+    }
+}
+
+/// The time zone of a date.
+#[derive(Clone, Copy)]
+enum Zone {
+    /// A UTC time, ending with `Z`.
+    Utc,
+    /// A time in the zone named by its `TZID`.
+    Named(Tz),
+    /// A floating time, or one in an unknown zone.
+    Local,
+}
+
+/// Parses an ICS date.
+fn parse_date(value: &str, params: &str) -> Option<Start> {
+    let params = params
+        .split(';')
+        .filter_map(|param| param.split_once('='))
+        .collect::<Vec<_>>();
+
+    let is_date = params
+        .iter()
+        .any(|(key, value)| *key == "VALUE" && *value == "DATE")
+        || value.len() == 8;
+    if is_date {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+
+        return Some(Start {
+            date: date.and_hms_opt(0, 0, 0)?,
+            zone: Zone::Local,
+            all_day: true,
+        });
+    }
+
+    if let Some(value) = value.strip_suffix('Z') {
+        return Some(Start {
+            date: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+            zone: Zone::Utc,
+            all_day: false,
+        });
+    }
+
+    let zone = match params.iter().find(|(key, _)| *key == "TZID") {
+        Some((_, name)) => match name.trim_matches('"').parse::<Tz>() {
+            Ok(tz) => Zone::Named(tz),
+            Err(_) => {
+                log::warn!("Unknown time zone {}, taken as the local one", name);
+                Zone::Local
+            }
+        },
+        None => Zone::Local,
+    };
+
+    Some(Start {
+        date: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+        zone,
+        all_day: false,
+    })
+}
+
+/// A recurrence rule, only the ones repeating the start as is.
+struct Rule {
+    /// How often the event repeats.
+    frequency: Frequency,
+    /// How many frequency periods are between two occurrences.
+    interval: u32,
+    /// How many occurrences there are, if limited.
+    count: Option<usize>,
+    /// The last occurrence, if limited.
+    until: Option<DateTime<Local>>,
+}
+
+impl Rule {
+    /// Parses a rule, `None` if it is invalid or picks other days than the one
+    /// of the start, with `BYDAY` and the like.
+    fn parse(value: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+        };
+
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => rule.until = Some(parse_date(value, "")?.resolve()?),
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    /// Returns the occurrence at the given index, `None` if it falls on a date that
+    /// does not exist, such as a 31st in a shorter month.
+    fn occurrence(&self, start: NaiveDateTime, index: u32) -> Option<NaiveDateTime> {
+        let steps = index.checked_mul(self.interval)?;
+
+        match self.frequency {
+            Frequency::Daily | Frequency::Weekly => {
+                start.checked_add_signed(TimeDelta::days(self.frequency.days() * steps as i64))
+            }
+            Frequency::Monthly | Frequency::Yearly => {
+                let months = match self.frequency {
+                    Frequency::Yearly => steps.checked_mul(12)?,
+                    _ => steps,
+                };
+                let date = start.checked_add_months(Months::new(months))?;
+
+                (date.day() == start.day()).then_some(date)
+            }
+        }
+    }
+}
+
+/// How often a recurring event repeats.
+#[derive(Clone, Copy)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    /// Returns the days of a period, for the daily and weekly frequencies.
+    fn days(&self) -> i64 {
+        match self {
+            Self::Weekly => 7,
+            _ => 1,
+        }
+    }
+}
+
+/// Unescapes an ICS text value.
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
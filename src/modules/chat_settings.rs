@@ -0,0 +1,84 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chat settings module.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    games::Symbols,
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The settings each chat customizes.
+#[derive(Clone)]
+pub struct ChatSettings {
+    /// The persisted settings.
+    storage: Storage<Data>,
+}
+
+impl ChatSettings {
+    /// Creates a new `ChatSettings` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("chat_settings"),
+        }
+    }
+
+    /// Returns the tic tac toe symbols of a chat.
+    pub async fn game_symbols(&self, chat_id: i64) -> Symbols {
+        self.storage
+            .read(|data| {
+                data.chats
+                    .get(&chat_id)
+                    .and_then(|settings| settings.game_symbols.clone())
+                    .unwrap_or_default()
+            })
+            .await
+    }
+
+    /// Sets the tic tac toe symbols of a chat, `None` restoring the default ones.
+    pub async fn set_game_symbols(&self, chat_id: i64, symbols: Option<Symbols>) {
+        self.storage
+            .write(|data| {
+                data.chats.entry(chat_id).or_default().game_symbols = symbols;
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl ChatMigration for ChatSettings {
+    fn name(&self) -> &'static str {
+        "chat settings"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::move_key(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// The settings of a chat.
+#[derive(Default, Serialize, Deserialize)]
+struct Settings {
+    /// The tic tac toe symbols, the default ones if unset.
+    game_symbols: Option<Symbols>,
+}
+
+/// The persisted settings.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The settings of each chat.
+    chats: HashMap<i64, Settings>,
+}
@@ -0,0 +1,290 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the command registry module.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, LazyLock, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The prefixes of the commands, unless changed.
+const DEFAULT_PREFIXES: [&str; 3] = [";", ",", "."];
+
+/// The commands that cannot be disabled, so the registry can always be edited.
+const PROTECTED: [&str; 1] = ["cmd"];
+
+/// The registry shared by the filters and the commands.
+static SHARED: LazyLock<Commands> = LazyLock::new(|| {
+    let storage = Storage::open("commands");
+    let data = storage.read_now(|data: &Data| data.clone());
+
+    Commands {
+        storage,
+        data: Arc::new(RwLock::new(data)),
+        known: Arc::new(RwLock::new(BTreeSet::new())),
+    }
+});
+
+/// The names, prefixes and states of the user commands, edited at runtime.
+///
+/// The handlers are bound to the names written in the code, and the command
+/// filters look up here which name and prefixes trigger each of them, so the
+/// commands can be renamed or disabled without recompiling.
+#[derive(Clone)]
+pub struct Commands {
+    /// The persisted registry.
+    storage: Storage<Data>,
+    /// The registry, read by the filters of every message.
+    data: Arc<RwLock<Data>>,
+    /// The names of the commands, as written in the code.
+    known: Arc<RwLock<BTreeSet<&'static str>>>,
+}
+
+impl Commands {
+    /// Returns the shared registry, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Registers the names of a handler, done by its filter.
+    pub fn register(&self, names: &[&'static str]) {
+        self.known
+            .write()
+            .expect("failed to lock commands")
+            .extend(names.iter().copied());
+    }
+
+    /// Checks if a command cannot be disabled.
+    pub fn is_protected(&self, name: &str) -> bool {
+        PROTECTED.contains(&name)
+    }
+
+    /// Checks if a text triggers one of the given commands, with their current
+    /// names and prefixes.
+    pub fn matches(&self, text: &str, names: &[&'static str]) -> bool {
+        let data = self.data.read().expect("failed to lock commands");
+        let Some(word) = data.command_word(text) else {
+            return false;
+        };
+
+        names
+            .iter()
+            .any(|name| !data.disabled.contains(*name) && data.name_of(name) == word)
+    }
+
+    /// Returns the command a text triggers, by the name written in the code.
+    pub fn canonical(&self, text: &str) -> Option<&'static str> {
+        let data = self.data.read().expect("failed to lock commands");
+        let word = data.command_word(text)?;
+
+        self.known
+            .read()
+            .expect("failed to lock commands")
+            .iter()
+            .find(|name| data.name_of(name) == word)
+            .copied()
+    }
+
+    /// Returns the command a name refers to, either its current name or the one
+    /// written in the code.
+    pub fn resolve(&self, name: &str) -> Option<&'static str> {
+        let data = self.data.read().expect("failed to lock commands");
+        let known = self.known.read().expect("failed to lock commands");
+
+        known
+            .iter()
+            .find(|known| data.name_of(known) == name)
+            .or_else(|| known.get(name))
+            .copied()
+    }
+
+    /// Checks if a text starts with one of the prefixes.
+    pub fn has_prefix(&self, text: &str) -> bool {
+        self.data
+            .read()
+            .expect("failed to lock commands")
+            .prefixes
+            .iter()
+            .any(|prefix| text.starts_with(prefix.as_str()))
+    }
+
+    /// Returns the prefixes.
+    pub fn prefixes(&self) -> Vec<String> {
+        self.data
+            .read()
+            .expect("failed to lock commands")
+            .prefixes
+            .clone()
+    }
+
+    /// Sets the prefixes, the default ones if empty.
+    pub async fn set_prefixes(&self, prefixes: Vec<String>) {
+        let prefixes = if prefixes.is_empty() {
+            default_prefixes()
+        } else {
+            prefixes
+        };
+
+        self.update(|data| data.prefixes = prefixes).await;
+    }
+
+    /// Enables or disables a command.
+    ///
+    /// Returns `false` if it already was, or if it cannot be disabled.
+    pub async fn set_enabled(&self, name: &'static str, enabled: bool) -> bool {
+        if !enabled && self.is_protected(name) {
+            return false;
+        }
+
+        self.update(|data| {
+            if enabled {
+                data.disabled.remove(name)
+            } else {
+                data.disabled.insert(name.to_string())
+            }
+        })
+        .await
+    }
+
+    /// Renames a command, the name written in the code no longer triggering it.
+    ///
+    /// Returns `false` if another command already goes by the new name.
+    pub async fn rename(&self, name: &'static str, new_name: &str) -> bool {
+        let taken = {
+            let data = self.data.read().expect("failed to lock commands");
+            let known = self.known.read().expect("failed to lock commands");
+
+            known
+                .iter()
+                .any(|known| *known != name && data.name_of(known) == new_name)
+        };
+        if taken {
+            return false;
+        }
+
+        self.update(|data| {
+            if new_name == name {
+                data.renames.remove(name);
+            } else {
+                data.renames.insert(name.to_string(), new_name.to_string());
+            }
+        })
+        .await;
+
+        true
+    }
+
+    /// Gives a command back its name and enables it.
+    ///
+    /// Returns `false` if it was neither renamed nor disabled.
+    pub async fn reset(&self, name: &'static str) -> bool {
+        self.update(|data| {
+            let renamed = data.renames.remove(name).is_some();
+            let disabled = data.disabled.remove(name);
+
+            renamed || disabled
+        })
+        .await
+    }
+
+    /// Returns the commands, sorted by the name written in the code.
+    pub fn list(&self) -> Vec<Entry> {
+        let data = self.data.read().expect("failed to lock commands");
+
+        self.known
+            .read()
+            .expect("failed to lock commands")
+            .iter()
+            .map(|name| Entry {
+                name,
+                renamed: data.renames.get(*name).cloned(),
+                enabled: !data.disabled.contains(*name),
+            })
+            .collect()
+    }
+
+    /// Updates the registry and persists it.
+    async fn update<R>(&self, f: impl FnOnce(&mut Data) -> R) -> R {
+        let data = self
+            .storage
+            .write(|data| {
+                let result = f(data);
+
+                (result, data.clone())
+            })
+            .await;
+        *self.data.write().expect("failed to lock commands") = data.1;
+
+        data.0
+    }
+}
+
+/// A command of the registry.
+pub struct Entry {
+    /// The name written in the code.
+    pub name: &'static str,
+    /// The name it goes by, if renamed.
+    pub renamed: Option<String>,
+    /// Whether it can be triggered.
+    pub enabled: bool,
+}
+
+/// The persisted registry.
+#[derive(Clone, Serialize, Deserialize)]
+struct Data {
+    /// The prefixes of the commands.
+    prefixes: Vec<String>,
+    /// The disabled commands, by the name written in the code.
+    disabled: BTreeSet<String>,
+    /// The name each renamed command goes by, by the name written in the code.
+    renames: HashMap<String, String>,
+}
+
+impl Data {
+    /// Returns the name a command goes by.
+    fn name_of<'a>(&'a self, name: &'a str) -> &'a str {
+        self.renames.get(name).map_or(name, String::as_str)
+    }
+
+    /// Returns the command word of a text, without its prefix, `None` if it
+    /// has none of the prefixes.
+    ///
+    /// The longest prefix wins, so `..` and `.` can both be used.
+    fn command_word<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let rest = self
+            .prefixes
+            .iter()
+            .filter_map(|prefix| text.strip_prefix(prefix.as_str()))
+            .min_by_key(|rest| rest.len())?;
+
+        rest.split_whitespace().next()
+    }
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self {
+            prefixes: default_prefixes(),
+            disabled: BTreeSet::new(),
+            renames: HashMap::new(),
+        }
+    }
+}
+
+/// Returns the default prefixes of the commands.
+fn default_prefixes() -> Vec<String> {
+    DEFAULT_PREFIXES
+        .iter()
+        .map(|prefix| prefix.to_string())
+        .collect()
+}
@@ -0,0 +1,254 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the contacts module.
+
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, types::Chat, Client};
+
+use crate::utils;
+
+/// A contact of the account.
+#[derive(Clone)]
+pub struct Contact {
+    /// The user ID, `0` for contacts read from a vCard.
+    pub id: i64,
+    /// The first name.
+    pub first_name: String,
+    /// The last name.
+    pub last_name: String,
+    /// The phone number, if visible.
+    pub phone: String,
+    /// The username, if any.
+    pub username: Option<String>,
+}
+
+impl Contact {
+    /// Returns the full name.
+    pub fn name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+            .trim()
+            .to_string()
+    }
+
+    /// Checks if the contact matches a search query.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+
+        self.name().to_lowercase().contains(&query)
+            || self
+                .username
+                .as_ref()
+                .is_some_and(|u| u.to_lowercase().contains(&query))
+            || (!normalize_phone(&query).is_empty()
+                && normalize_phone(&self.phone).contains(&normalize_phone(&query)))
+    }
+}
+
+/// Keeps only the digits of a phone number, so numbers can be compared.
+pub fn normalize_phone(phone: &str) -> String {
+    phone.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Returns the contacts of the account.
+pub async fn list(client: &Client) -> Result<Vec<Contact>> {
+    let tl::enums::contacts::Contacts::Contacts(contacts) = client
+        .invoke(&tl::functions::contacts::GetContacts { hash: 0 })
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+
+    Ok(contacts
+        .users
+        .into_iter()
+        .filter_map(|user| match user {
+            tl::enums::User::User(user) => Some(Contact {
+                id: user.id,
+                first_name: user.first_name.unwrap_or_default(),
+                last_name: user.last_name.unwrap_or_default(),
+                phone: user.phone.unwrap_or_default(),
+                username: user.username,
+            }),
+            tl::enums::User::Empty(_) => None,
+        })
+        .collect())
+}
+
+/// Adds a user as a contact.
+pub async fn add_user(
+    client: &Client,
+    user: &Chat,
+    first_name: &str,
+    last_name: &str,
+) -> Result<()> {
+    client
+        .invoke(&tl::functions::contacts::AddContact {
+            add_phone_privacy_exception: false,
+            id: utils::input_user(user)?,
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            phone: String::new(),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Imports contacts by their phone numbers, skipping the ones already saved.
+///
+/// Returns the number of imported contacts and the number of duplicates.
+pub async fn import(client: &Client, contacts: Vec<Contact>) -> Result<(usize, usize)> {
+    let mut known = list(client)
+        .await?
+        .into_iter()
+        .map(|c| normalize_phone(&c.phone))
+        .filter(|phone| !phone.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut duplicates = 0;
+    let mut input = Vec::new();
+    for (i, contact) in contacts.into_iter().enumerate() {
+        let phone = normalize_phone(&contact.phone);
+
+        if phone.is_empty() || known.contains(&phone) {
+            duplicates += 1;
+            continue;
+        }
+        known.push(phone);
+
+        input.push(
+            tl::types::InputPhoneContact {
+                client_id: i as i64,
+                phone: contact.phone,
+                first_name: contact.first_name,
+                last_name: contact.last_name,
+            }
+            .into(),
+        );
+    }
+
+    if input.is_empty() {
+        return Ok((0, duplicates));
+    }
+
+    let tl::enums::contacts::ImportedContacts::Contacts(imported) = client
+        .invoke(&tl::functions::contacts::ImportContacts { contacts: input })
+        .await?;
+
+    Ok((imported.imported.len(), duplicates))
+}
+
+/// Deletes a contact.
+pub async fn delete(client: &Client, user: &Chat) -> Result<()> {
+    client
+        .invoke(&tl::functions::contacts::DeleteContacts {
+            id: vec![utils::input_user(user)?],
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Parses the contacts of a vCard file.
+pub fn parse_vcards(content: &str) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    let mut current: Option<Contact> = None;
+
+    // Folded lines continue with a leading space.
+    let content = content.replace("\r\n ", "").replace("\n ", "");
+
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = key.split(';').next().unwrap_or_default().to_uppercase();
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(Contact {
+                    id: 0,
+                    first_name: String::new(),
+                    last_name: String::new(),
+                    phone: String::new(),
+                    username: None,
+                });
+            }
+            "N" => {
+                if let Some(contact) = current.as_mut() {
+                    let mut parts = value.split(';');
+                    contact.last_name = unescape(parts.next().unwrap_or_default());
+                    contact.first_name = unescape(parts.next().unwrap_or_default());
+                }
+            }
+            "FN" => {
+                if let Some(contact) = current.as_mut().filter(|c| c.first_name.is_empty()) {
+                    contact.first_name = unescape(value);
+                }
+            }
+            "TEL" => {
+                if let Some(contact) = current.as_mut().filter(|c| c.phone.is_empty()) {
+                    contact.phone = value.trim_start_matches("tel:").to_string();
+                }
+            }
+            "END" if value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(contact) = current.take() {
+                    if !contact.phone.is_empty() {
+                        contacts.push(contact);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    contacts
+}
+
+/// Renders contacts as a vCard file.
+pub fn to_vcards(contacts: &[Contact]) -> String {
+    let mut content = String::new();
+
+    for contact in contacts {
+        content += "BEGIN:VCARD\r\nVERSION:3.0\r\n";
+        content += &format!(
+            "N:{0};{1};;;\r\nFN:{2}\r\n",
+            escape(&contact.last_name),
+            escape(&contact.first_name),
+            escape(&contact.name())
+        );
+        if !contact.phone.is_empty() {
+            content += &format!("TEL;TYPE=CELL:+{}\r\n", normalize_phone(&contact.phone));
+        }
+        if let Some(username) = contact.username.as_ref() {
+            content += &format!("URL:https://t.me/{}\r\n", username);
+        }
+        content += "END:VCARD\r\n";
+    }
+
+    content
+}
+
+/// Escapes a vCard value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+/// Unescapes a vCard value.
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+        .trim()
+        .to_string()
+}
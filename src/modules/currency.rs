@@ -0,0 +1,81 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the exchange rates module.
+
+use async_trait::async_trait;
+use ferogram::Result;
+use maplit::hashmap;
+use serde_json::Value;
+
+use super::{digest::Widget, i18n::I18n};
+
+/// The URL of the exchange rates API.
+const API_URL: &str = "https://open.er-api.com/v6/latest";
+
+/// The exchange rates widget.
+pub struct ExchangeRates {
+    /// The base currency.
+    base: String,
+    /// The currencies quoted against the base.
+    targets: Vec<String>,
+}
+
+impl ExchangeRates {
+    /// Creates a new `ExchangeRates` instance.
+    pub fn new(base: impl Into<String>, targets: Vec<String>) -> Self {
+        Self {
+            base: base.into(),
+            targets,
+        }
+    }
+
+    /// Fetches the rate of each target currency.
+    pub async fn fetch(&self) -> Result<Vec<(String, f64)>> {
+        let json = reqwest::get(format!("{0}/{1}", API_URL, self.base))
+            .await?
+            .json::<Value>()
+            .await?;
+
+        if json["result"].as_str() != Some("success") {
+            return Err("Failed to fetch exchange rates".into());
+        }
+
+        Ok(self
+            .targets
+            .iter()
+            .filter_map(|target| {
+                json["rates"][target]
+                    .as_f64()
+                    .map(|rate| (target.clone(), rate))
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Widget for ExchangeRates {
+    fn name(&self) -> &'static str {
+        "currency"
+    }
+
+    async fn render(&self, i18n: &I18n) -> Result<String> {
+        let rates = self
+            .fetch()
+            .await?
+            .into_iter()
+            .map(|(target, rate)| format!("<b>{0}</b>: <code>{1:.4}</code>", target, rate))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(i18n.translate_with_args(
+            "digest_currency",
+            hashmap! { "base" => self.base.clone(), "rates" => rates },
+        ))
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the natural-language date parser.
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::scheduler::When;
+use crate::utils;
+
+/// The time used when only the day is given.
+const DEFAULT_TIME: (u32, u32) = (9, 0);
+
+/// The words starting a recurrence, such as `every monday`.
+const EVERY: &[&str] = &["every", "cada", "toda", "todo", "todas", "todos"];
+
+/// The words that can come before a day, such as `next friday`.
+const DAY_PREFIXES: &[&str] = &[
+    "next", "on", "this", "próxima", "proxima", "próximo", "proximo", "na", "no", "nesta", "neste",
+];
+
+/// The words that can come before a time, such as `at 15h`.
+const TIME_PREFIXES: &[&str] = &["at", "às", "as", "ao", "@"];
+
+/// The words meaning every day.
+const DAYS: &[&str] = &["day", "days", "dia", "dias"];
+
+/// The names of the weekdays, in English and Portuguese.
+const WEEKDAYS: &[(&str, Weekday)] = &[
+    ("monday", Weekday::Mon),
+    ("segunda", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("terça", Weekday::Tue),
+    ("terca", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("quarta", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("quinta", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("sexta", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sábado", Weekday::Sat),
+    ("sabado", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+    ("domingo", Weekday::Sun),
+];
+
+/// A rule of a recurring schedule, in local time so it follows the daylight
+/// saving changes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// The weekday it runs on, every day if `None`.
+    pub weekday: Option<Weekday>,
+    /// The local time it runs at.
+    pub time: NaiveTime,
+}
+
+impl Recurrence {
+    /// Returns the first run after the given time.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(&Local).date_naive();
+
+        // A time skipped by the daylight saving change moves to the next match.
+        (0..=14)
+            .map(|days| start + TimeDelta::days(days))
+            .filter(|date| match self.weekday {
+                Some(weekday) => date.weekday() == weekday,
+                None => true,
+            })
+            .filter_map(|date| {
+                Local
+                    .from_local_datetime(&date.and_time(self.time))
+                    .earliest()
+            })
+            .map(|at| at.with_timezone(&Utc))
+            .find(|at| *at > after)
+    }
+}
+
+/// Parses when something happens from the first words of a text.
+///
+/// Besides the forms of [`utils::parse_when`], it accepts `15h`, `3pm`, `tomorrow 9h`,
+/// `next friday 15h`, `every monday 8:30` and `every day 22h`, in English or
+/// Portuguese. A day without a time is at 9 AM.
+///
+/// Returns the schedule and how many words were used.
+pub fn parse(words: &[&str]) -> Option<(When, usize)> {
+    let lower = words
+        .iter()
+        .map(|word| word.trim_end_matches(',').to_lowercase())
+        .collect::<Vec<_>>();
+    let lower = lower.iter().map(String::as_str).collect::<Vec<_>>();
+
+    if let Some(parsed) = parse_recurrence(&lower).or_else(|| parse_day(&lower)) {
+        return Some(parsed);
+    }
+
+    utils::parse_when(words).map(|(at, used)| (When::At(at), used))
+}
+
+/// Parses a recurrence, such as `every monday 15h`.
+fn parse_recurrence(words: &[&str]) -> Option<(When, usize)> {
+    let (first, rest) = words.split_first()?;
+    if !EVERY.contains(first) {
+        return None;
+    }
+
+    let day = rest.first()?;
+    let weekday = if DAYS.contains(day) {
+        None
+    } else {
+        Some(parse_weekday(day)?)
+    };
+    let (time, used) = parse_time_words(&rest[1..]);
+
+    let recurrence = Recurrence {
+        weekday,
+        time: time.unwrap_or_else(default_time),
+    };
+
+    Some((When::Every(recurrence), 2 + used))
+}
+
+/// Parses a day with an optional time, such as `next friday 15h`, or a time alone.
+fn parse_day(words: &[&str]) -> Option<(When, usize)> {
+    let now = Local::now();
+    let today = now.date_naive();
+
+    let skip = words
+        .first()
+        .is_some_and(|word| DAY_PREFIXES.contains(word)) as usize;
+    let (date, used) = match words.get(skip).copied()? {
+        "today" | "hoje" => (Some(today), skip + 1),
+        "tomorrow" | "amanhã" | "amanha" => (Some(today + TimeDelta::days(1)), skip + 1),
+        word => match parse_weekday(word) {
+            Some(weekday) => (Some(next_weekday(today, weekday)), skip + 1),
+            // A prefix without a day is not a date.
+            None if skip > 0 => return None,
+            None => (None, 0),
+        },
+    };
+
+    let (time, time_used) = parse_time_words(&words[used..]);
+    let at = match (date, time) {
+        (Some(date), time) => date.and_time(time.unwrap_or_else(default_time)),
+        (None, Some(time)) => {
+            let at = today.and_time(time);
+            if at <= now.naive_local() {
+                at + TimeDelta::days(1)
+            } else {
+                at
+            }
+        }
+        (None, None) => return None,
+    };
+
+    let at = Local.from_local_datetime(&at).earliest()?;
+    (at > now).then(|| (When::At(at.with_timezone(&Utc)), used + time_used))
+}
+
+/// Parses a time with an optional prefix, such as `at 15h`.
+///
+/// Returns the time, if any, and how many words were used.
+fn parse_time_words(words: &[&str]) -> (Option<NaiveTime>, usize) {
+    let skip = words
+        .first()
+        .is_some_and(|word| TIME_PREFIXES.contains(word)) as usize;
+
+    match words.get(skip).and_then(|word| parse_time(word)) {
+        Some(time) => (Some(time), skip + 1),
+        None => (None, 0),
+    }
+}
+
+/// Parses a time, such as `15h`, `15h30`, `15:30`, `3pm` or `3:30pm`.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    let (word, offset) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(word), _) => (word, Some(0)),
+        (_, Some(word)) => (word, Some(12)),
+        _ => (word, None),
+    };
+
+    let (hour, minute) = match word.split_once(['h', ':']) {
+        Some((hour, "")) => (hour, "0"),
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute),
+        None if offset.is_some() => (word, "0"),
+        _ => return None,
+    };
+    let mut hour = hour.parse::<u32>().ok()?;
+    let minute = minute.parse::<u32>().ok()?;
+
+    if let Some(offset) = offset {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Parses the name of a weekday, with or without the `-feira` suffix.
+///
+/// Also accepts the plural, such as `mondays` or `segundas-feiras`.
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    let word = word.trim_end_matches("-feiras").trim_end_matches("-feira");
+    let find = |word: &str| {
+        WEEKDAYS
+            .iter()
+            .find(|(name, _)| *name == word)
+            .map(|(_, weekday)| *weekday)
+    };
+
+    find(word).or_else(|| find(word.strip_suffix('s')?))
+}
+
+/// Returns the next date on a weekday, a week later if it is today.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+
+    today + TimeDelta::days(if days == 0 { 7 } else { days as i64 })
+}
+
+/// Returns the time used when only the day is given.
+fn default_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(DEFAULT_TIME.0, DEFAULT_TIME.1, 0).expect("valid default time")
+}
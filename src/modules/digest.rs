@@ -0,0 +1,211 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the daily digest module.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{Local, NaiveTime, TimeDelta};
+use ferogram::Result;
+use grammers_client::InputMessage;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use super::{
+    i18n::I18n,
+    shutdown::Shutdown,
+    storage::Storage,
+    templates::{self, Variables},
+};
+use crate::Sender;
+
+/// A section of the daily digest.
+#[async_trait]
+pub trait Widget: Send + Sync {
+    /// Returns the widget name, used to pick it.
+    fn name(&self) -> &'static str;
+
+    /// Renders the widget as HTML.
+    async fn render(&self, i18n: &I18n) -> Result<String>;
+}
+
+/// The daily digest builder.
+#[derive(Clone)]
+pub struct Digest {
+    /// The available widgets.
+    widgets: Arc<Vec<Arc<dyn Widget>>>,
+    /// The persisted settings.
+    storage: Storage<Data>,
+    /// Wakes the scheduled task up when the time changes.
+    rescheduled: Arc<Notify>,
+}
+
+impl Digest {
+    /// Creates a new `Digest` instance.
+    pub fn new() -> Self {
+        Self {
+            widgets: Arc::new(Vec::new()),
+            storage: Storage::open("digest"),
+            rescheduled: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Registers a widget.
+    pub fn widget(mut self, widget: impl Widget + 'static) -> Self {
+        Arc::get_mut(&mut self.widgets)
+            .expect("Widgets must be registered before cloning the digest")
+            .push(Arc::new(widget));
+        self
+    }
+
+    /// Returns the names of the available widgets.
+    pub fn available(&self) -> Vec<&'static str> {
+        self.widgets.iter().map(|w| w.name()).collect()
+    }
+
+    /// Returns the names of the enabled widgets, in order.
+    pub async fn enabled(&self) -> Vec<String> {
+        self.storage.read(|data| data.widgets.clone()).await
+    }
+
+    /// Enables a widget.
+    ///
+    /// Returns `false` if the widget does not exist or is already enabled.
+    pub async fn enable(&self, name: &str) -> bool {
+        if !self.available().contains(&name) {
+            return false;
+        }
+
+        self.storage
+            .write(|data| {
+                if data.widgets.iter().any(|w| w == name) {
+                    false
+                } else {
+                    data.widgets.push(name.to_string());
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Disables a widget.
+    ///
+    /// Returns `false` if the widget was not enabled.
+    pub async fn disable(&self, name: &str) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.widgets.len();
+                data.widgets.retain(|w| w != name);
+
+                data.widgets.len() != len
+            })
+            .await
+    }
+
+    /// Returns the time the digest is sent at.
+    pub async fn time(&self) -> NaiveTime {
+        self.storage.read(|data| data.time).await
+    }
+
+    /// Sets the time the digest is sent at, the next one being scheduled again.
+    pub async fn set_time(&self, time: NaiveTime) {
+        self.storage.write(|data| data.time = time).await;
+        self.rescheduled.notify_one();
+    }
+
+    /// Sets the template of the header, or restores the default one.
+    pub async fn set_header(&self, header: Option<String>) {
+        self.storage.write(|data| data.header = header).await;
+    }
+
+    /// Renders the digest with the enabled widgets.
+    pub async fn render(&self, i18n: &I18n) -> String {
+        let header = self
+            .storage
+            .read(|data| data.header.clone())
+            .await
+            .unwrap_or_else(|| i18n.translate("digest_header"));
+        let mut text = match templates::render(&header, &Variables::new()) {
+            Ok(text) => text,
+            Err(e) => {
+                log::warn!("Failed to render the digest header: {}", e);
+                header
+            }
+        };
+
+        for name in self.enabled().await {
+            let Some(widget) = self.widgets.iter().find(|w| w.name() == name) else {
+                continue;
+            };
+
+            match widget.render(i18n).await {
+                Ok(section) => text += &format!("\n\n{}", section),
+                Err(e) => {
+                    log::error!("Failed to render the {} widget: {}", name, e);
+                    text += &format!("\n\n{}", i18n.translate("digest_widget_error"));
+                }
+            }
+        }
+
+        text
+    }
+
+    /// Spawns the task that sends the digest to the owner every day.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                let now = Local::now().naive_local();
+                let mut next = now.date().and_time(self.time().await);
+                if next <= now {
+                    next += TimeDelta::days(1);
+                }
+
+                let wait = (next - now).to_std().unwrap_or(Duration::from_secs(60));
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = self.rescheduled.notified() => continue,
+                }
+
+                if self.enabled().await.is_empty() {
+                    continue;
+                }
+
+                let text = self.render(&i18n).await;
+                if let Err(e) = tx
+                    .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                    .await
+                {
+                    log::error!("Failed to send the daily digest: {}", e);
+                }
+            }
+        });
+    }
+}
+
+/// The persisted digest settings.
+#[derive(Serialize, Deserialize)]
+struct Data {
+    /// The enabled widgets, in order.
+    widgets: Vec<String>,
+    /// The time the digest is sent at.
+    time: NaiveTime,
+    /// The template of the header, the default one if `None`.
+    #[serde(default)]
+    header: Option<String>,
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self {
+            widgets: Vec::new(),
+            time: NaiveTime::from_hms_opt(8, 0, 0).expect("Invalid time"),
+            header: None,
+        }
+    }
+}
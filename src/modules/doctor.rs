@@ -0,0 +1,264 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the self-test module.
+
+use std::{process::Stdio, sync::Arc, time::Duration};
+
+use grammers_client::{Client, InputMessage};
+use maplit::hashmap;
+use sysinfo::Disks;
+use tokio::process::Command;
+
+use super::{i18n::I18n, shutdown::Shutdown, storage};
+use crate::{utils, Priority, Sender};
+
+/// How long a binary is given to print its version.
+const BINARY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The longest version shown of a binary.
+const MAX_VERSION_LENGTH: usize = 40;
+
+/// The result of a check, with its details either way.
+pub struct Check {
+    /// The name of the check, the suffix of its translation key.
+    pub name: &'static str,
+    /// The details of the check, `Err` if it failed.
+    pub result: Result<String, String>,
+}
+
+impl Check {
+    /// Creates a check from its result.
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        Self { name, result }
+    }
+}
+
+/// Runs the checks of the environment the bot depends on.
+#[derive(Clone)]
+pub struct Doctor {
+    /// The bot client.
+    bot: Client,
+    /// The client of each user account.
+    users: Arc<Vec<Client>>,
+    /// The path of the ffmpeg binary.
+    ffmpeg_path: Arc<String>,
+    /// The path of the yt-dlp binary.
+    ytdl_path: Arc<String>,
+    /// The least free disk space, in bytes.
+    min_free_space: u64,
+}
+
+impl Doctor {
+    /// Creates a new `Doctor` instance.
+    pub fn new(
+        bot: Client,
+        users: Vec<Client>,
+        ffmpeg_path: String,
+        ytdl_path: String,
+        min_free_space: u64,
+    ) -> Self {
+        Self {
+            bot,
+            users: Arc::new(users),
+            ffmpeg_path: Arc::new(ffmpeg_path),
+            ytdl_path: Arc::new(ytdl_path),
+            min_free_space,
+        }
+    }
+
+    /// Runs every check, the channel between the clients given by `tx`.
+    pub async fn run(&self, tx: &Sender) -> Vec<Check> {
+        let (ffmpeg, ytdl, screenshot, bot) = tokio::join!(
+            binary_version(&self.ffmpeg_path, "-version"),
+            binary_version(&self.ytdl_path, "--version"),
+            utils::check_screenshot_api(),
+            self.bot.is_authorized(),
+        );
+        let mut users = Vec::with_capacity(self.users.len());
+        for user in self.users.iter() {
+            users.push(authorized(user.is_authorized().await));
+        }
+
+        vec![
+            Check::new(
+                "locales",
+                I18n::check_files().map(|locales| locales.join(", ")),
+            ),
+            Check::new(
+                "storage",
+                storage::check().map(|files| format!("{} files", files)),
+            ),
+            Check::new("ffmpeg", ffmpeg),
+            Check::new("ytdl", ytdl),
+            Check::new(
+                "screenshot",
+                screenshot
+                    .map(|status| status.to_string())
+                    .map_err(|e| e.to_string()),
+            ),
+            Check::new("bot", authorized(bot)),
+            Check::new("user", all_authorized(users)),
+            Check::new("disk", self.free_space()),
+            Check::new("bus", bus_depth(tx)),
+        ]
+    }
+
+    /// Renders the results of the checks as a table.
+    pub fn render(&self, checks: &[Check], i18n: &I18n) -> String {
+        let passed = checks.iter().filter(|check| check.result.is_ok()).count();
+        let lines = checks
+            .iter()
+            .map(|check| {
+                let (icon, details) = match &check.result {
+                    Ok(details) => ("✅", details),
+                    Err(details) => ("❌", details),
+                };
+
+                format!(
+                    "{0} <b>{1}</b>: <code>{2}</code>",
+                    icon,
+                    i18n.translate(format!("doctor_{}", check.name)),
+                    utils::escape_html(details)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        i18n.translate_with_args(
+            "doctor_report",
+            hashmap! {
+                "checks" => lines,
+                "passed" => passed.to_string(),
+                "total" => checks.len().to_string(),
+            },
+        )
+    }
+
+    /// Checks the free space of the disk the bot runs from.
+    fn free_space(&self) -> Result<String, String> {
+        let dir = std::env::current_dir()
+            .and_then(|dir| dir.canonicalize())
+            .map_err(|e| e.to_string())?;
+
+        // The disk is the one mounted the deepest above the directory.
+        let disks = Disks::new_with_refreshed_list();
+        let disk = disks
+            .list()
+            .iter()
+            .filter(|disk| dir.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .ok_or("no disk found")?;
+
+        let available = disk.available_space();
+        let details = format!("{} free", utils::human_readable_size(available as usize));
+        if available < self.min_free_space {
+            return Err(details);
+        }
+
+        Ok(details)
+    }
+
+    /// Spawns the task that runs the checks once, notifying the owner.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            let checks = self.run(&tx).await;
+            for check in checks.iter() {
+                if let Err(e) = &check.result {
+                    log::warn!("The {} check failed: {}", check.name, e);
+                }
+            }
+
+            let text = self.render(&checks, &i18n);
+            if let Err(e) = tx
+                .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                .await
+            {
+                log::error!("Failed to send the self-test results: {}", e);
+            }
+        });
+    }
+}
+
+/// Runs a binary to print its version, returning its first line.
+async fn binary_version(path: &str, flag: &str) -> Result<String, String> {
+    let output = Command::new(path)
+        .arg(flag)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(BINARY_TIMEOUT, output)
+        .await
+        .map_err(|_| "timed out".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.lines().next().unwrap_or_default().trim();
+
+    Ok(version.chars().take(MAX_VERSION_LENGTH).collect())
+}
+
+/// Describes whether every user account is authorized, naming the ones that are
+/// not by their position.
+fn all_authorized(mut results: Vec<Result<String, String>>) -> Result<String, String> {
+    if results.len() == 1 {
+        return results.remove(0);
+    }
+
+    let failed = results
+        .iter()
+        .enumerate()
+        .filter_map(|(account, result)| {
+            result
+                .as_ref()
+                .err()
+                .map(|e| format!("#{0} {1}", account + 1, e))
+        })
+        .collect::<Vec<_>>();
+    if !failed.is_empty() {
+        return Err(failed.join(", "));
+    }
+
+    Ok(format!("{} accounts authorized", results.len()))
+}
+
+/// Describes how many messages wait in each level of the channel between the
+/// clients, failing if one is full.
+fn bus_depth(tx: &Sender) -> Result<String, String> {
+    let capacity = tx.capacity();
+    let depths = Priority::ALL.map(|priority| (priority, tx.depth(priority)));
+
+    let mut details = depths
+        .iter()
+        .map(|(priority, depth)| format!("{0} {1}/{2}", priority, depth, capacity))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let dropped = tx.dropped();
+    if dropped > 0 {
+        details.push_str(&format!(", {} dropped", dropped));
+    }
+
+    if depths.iter().any(|(_, depth)| *depth >= capacity) {
+        return Err(details);
+    }
+
+    Ok(details)
+}
+
+/// Describes whether a client is authorized.
+fn authorized(result: Result<bool, grammers_client::InvocationError>) -> Result<String, String> {
+    match result {
+        Ok(true) => Ok("authorized".to_string()),
+        Ok(false) => Err("not authorized".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
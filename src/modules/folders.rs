@@ -0,0 +1,303 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chat folders module.
+
+use std::time::Duration;
+
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, types::Chat, Client};
+use serde::{Deserialize, Serialize};
+
+use super::{shutdown::Shutdown, storage::Storage};
+
+/// The ID of the archive folder.
+const ARCHIVE_FOLDER_ID: i32 = 1;
+/// The first ID available to custom folders.
+const FIRST_FOLDER_ID: i32 = 2;
+/// The maximum number of chats explicitly included in a folder.
+const MAX_INCLUDED_PEERS: usize = 100;
+
+/// The chat folders manager.
+#[derive(Clone)]
+pub struct Folders {
+    /// The auto-foldering rules.
+    storage: Storage<Data>,
+    /// How often the rules are applied.
+    sync_interval: Duration,
+}
+
+impl Folders {
+    /// Creates a new `Folders` instance.
+    pub fn new(sync_interval: Duration) -> Self {
+        Self {
+            storage: Storage::open("folders"),
+            sync_interval,
+        }
+    }
+
+    /// Returns the folders of the account.
+    pub async fn list(&self, client: &Client) -> Result<Vec<tl::types::DialogFilter>> {
+        let tl::enums::messages::DialogFilters::Filters(filters) = client
+            .invoke(&tl::functions::messages::GetDialogFilters {})
+            .await?;
+
+        Ok(filters
+            .filters
+            .into_iter()
+            .filter_map(|filter| match filter {
+                tl::enums::DialogFilter::Filter(filter) => Some(filter),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Creates a folder with a first chat, as folders cannot be empty.
+    ///
+    /// Returns `false` if a folder with the same name already exists.
+    pub async fn create(&self, client: &Client, name: &str, chat: &Chat) -> Result<bool> {
+        let folders = self.list(client).await?;
+        if folders.iter().any(|f| f.title.eq_ignore_ascii_case(name)) {
+            return Ok(false);
+        }
+
+        let id = folders
+            .iter()
+            .map(|f| f.id + 1)
+            .max()
+            .unwrap_or(FIRST_FOLDER_ID)
+            .max(FIRST_FOLDER_ID);
+
+        let folder = tl::types::DialogFilter {
+            contacts: false,
+            non_contacts: false,
+            groups: false,
+            broadcasts: false,
+            bots: false,
+            exclude_muted: false,
+            exclude_read: false,
+            exclude_archived: false,
+            id,
+            title: name.to_string(),
+            emoticon: None,
+            color: None,
+            pinned_peers: Vec::new(),
+            include_peers: vec![chat.pack().to_input_peer()],
+            exclude_peers: Vec::new(),
+        };
+        save(client, folder).await?;
+
+        Ok(true)
+    }
+
+    /// Adds chats to a folder.
+    ///
+    /// Returns the number of chats added, or `None` if the folder does not exist.
+    pub async fn add(&self, client: &Client, name: &str, chats: &[Chat]) -> Result<Option<usize>> {
+        let Some(mut folder) = self
+            .list(client)
+            .await?
+            .into_iter()
+            .find(|f| f.title.eq_ignore_ascii_case(name))
+        else {
+            return Ok(None);
+        };
+
+        let mut added = 0;
+        for chat in chats {
+            let peer = chat.pack().to_input_peer();
+
+            if folder.include_peers.len() >= MAX_INCLUDED_PEERS {
+                break;
+            } else if !folder.include_peers.contains(&peer) {
+                folder.include_peers.push(peer);
+                added += 1;
+            }
+        }
+
+        if added > 0 {
+            save(client, folder).await?;
+        }
+
+        Ok(Some(added))
+    }
+
+    /// Moves a chat to the archive.
+    pub async fn archive(&self, client: &Client, chat: &Chat) -> Result<()> {
+        client
+            .invoke(&tl::functions::folders::EditPeerFolders {
+                folder_peers: vec![tl::types::InputFolderPeer {
+                    peer: chat.pack().to_input_peer(),
+                    folder_id: ARCHIVE_FOLDER_ID,
+                }
+                .into()],
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Adds an auto-foldering rule.
+    ///
+    /// Returns `false` if the rule already exists.
+    pub async fn add_rule(&self, rule: Rule) -> bool {
+        self.storage
+            .write(|data| {
+                if data.rules.contains(&rule) {
+                    false
+                } else {
+                    data.rules.push(rule);
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Removes the auto-foldering rules of a folder.
+    ///
+    /// Returns `false` if the folder had no rules.
+    pub async fn remove_rules(&self, folder: &str) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.rules.len();
+                data.rules
+                    .retain(|rule| !rule.folder.eq_ignore_ascii_case(folder));
+
+                data.rules.len() != len
+            })
+            .await
+    }
+
+    /// Returns the auto-foldering rules.
+    pub async fn rules(&self) -> Vec<Rule> {
+        self.storage.read(|data| data.rules.clone()).await
+    }
+
+    /// Applies the auto-foldering rules to every dialog.
+    pub async fn apply_rules(&self, client: &Client) -> Result<()> {
+        let rules = self.rules().await;
+        if rules.is_empty() {
+            return Ok(());
+        }
+
+        let mut chats = Vec::new();
+        let mut dialogs = client.iter_dialogs();
+        while let Some(dialog) = dialogs.next().await? {
+            chats.push(dialog.chat().clone());
+        }
+
+        for rule in rules {
+            let matching = chats
+                .iter()
+                .filter(|chat| rule.kind.matches(chat))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if matching.is_empty() {
+                continue;
+            }
+
+            if self.add(client, &rule.folder, &matching).await?.is_none() {
+                self.create(client, &rule.folder, &matching[0]).await?;
+                self.add(client, &rule.folder, &matching[1..]).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spawns the task that applies the auto-foldering rules periodically.
+    pub fn start(self, client: Client) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                if let Err(e) = self.apply_rules(&client).await {
+                    log::error!("Failed to apply the folder rules: {}", e);
+                }
+
+                tokio::time::sleep(self.sync_interval).await;
+            }
+        });
+    }
+}
+
+/// Saves a folder.
+async fn save(client: &Client, folder: tl::types::DialogFilter) -> Result<()> {
+    client
+        .invoke(&tl::functions::messages::UpdateDialogFilter {
+            id: folder.id,
+            filter: Some(folder.into()),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// An auto-foldering rule.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    /// The folder name.
+    pub folder: String,
+    /// The chats that go to the folder.
+    pub kind: RuleKind,
+}
+
+/// The chats an auto-foldering rule matches.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// Every bot.
+    Bots,
+    /// Every user that is not a bot.
+    Users,
+    /// Every group.
+    Groups,
+    /// Every broadcast channel.
+    Channels,
+}
+
+impl RuleKind {
+    /// Parses a rule kind.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bots" => Some(Self::Bots),
+            "users" => Some(Self::Users),
+            "groups" => Some(Self::Groups),
+            "channels" => Some(Self::Channels),
+            _ => None,
+        }
+    }
+
+    /// Checks if a chat matches the rule.
+    pub fn matches(&self, chat: &Chat) -> bool {
+        match (self, chat) {
+            (Self::Bots, Chat::User(user)) => user.is_bot(),
+            (Self::Users, Chat::User(user)) => !user.is_bot() && !user.is_self(),
+            (Self::Groups, Chat::Group(_)) => true,
+            (Self::Channels, Chat::Channel(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bots => write!(f, "bots"),
+            Self::Users => write!(f, "users"),
+            Self::Groups => write!(f, "groups"),
+            Self::Channels => write!(f, "channels"),
+        }
+    }
+}
+
+/// The persisted auto-foldering rules.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The rules, applied in order.
+    rules: Vec<Rule>,
+}
@@ -8,76 +8,350 @@
 
 //! This module contains the games module.
 
-use std::{collections::HashMap, ops::RangeInclusive, sync::Arc};
-
-use grammers_client::types::Chat;
-use tokio::sync::Mutex;
+use std::{
+    collections::HashMap,
+    future::Future,
+    ops::RangeInclusive,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use grammers_client::{
+    grammers_tl_types as tl,
+    session::PackedChat,
+    types::{CallbackQuery, Chat, Message},
+    Client, InputMessage, InvocationError,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use shakmaty::{fen::Fen, san::San, Color, EnPassantMode, Move, Outcome, Position, Role, Square};
+use tokio::{sync::Mutex, task::AbortHandle};
+
+use super::{i18n::I18n, shutdown::Shutdown, trivia::Question};
+use crate::utils::escape_html;
+
+/// How often the stale games are looked for.
+const EXPIRATION_INTERVAL: Duration = Duration::from_secs(60);
 
 /// The symbols.
 const SYMBOLS: [char; 3] = ['⭕', '❌', '🟥'];
 
+/// The connect four symbols.
+const CONNECT_FOUR_SYMBOLS: [char; 3] = ['🔴', '🟡', '⚪'];
+
+/// The connect four board size, as rows and columns.
+const CONNECT_FOUR_SIZE: (usize, usize) = (6, 7);
+
+/// The chess symbols, white and black.
+const CHESS_SYMBOLS: [char; 2] = ['⚪', '⚫'];
+
+/// The chess moves shown in the game text.
+const CHESS_HISTORY_LENGTH: usize = 30;
+
+/// The symbol of an empty sudoku cell.
+const SUDOKU_EMPTY: char = '·';
+
+/// The mistakes that lose a sudoku game.
+const SUDOKU_MAX_MISTAKES: usize = 3;
+
+/// The most players a trivia game has, everyone answering joins.
+const TRIVIA_MAX_PLAYERS: usize = 100;
+
 /// The game manager.
 #[derive(Clone)]
 pub struct GameManager {
-    /// The active games.
-    active_games: Arc<Mutex<Vec<Game>>>,
+    /// The active games, by ID.
+    active_games: Arc<Mutex<HashMap<i32, Entry>>>,
+    /// How long a player has to make a move.
+    move_timeout: Duration,
+    /// How long a game can go without moves before it expires.
+    expire_after: Duration,
+    /// The turn timers of the active games.
+    timers: Arc<Mutex<HashMap<i32, AbortHandle>>>,
+}
+
+/// An active game.
+struct Entry {
+    /// The game.
+    game: Game,
+    /// The message the game is shown in, once known.
+    ///
+    /// The games sent through inline mode are bound to their message by the
+    /// first button pressed.
+    message: Option<GameMessage>,
+    /// When the game was last updated.
+    last_active: Instant,
+}
+
+/// The message a game is shown in.
+#[derive(Clone)]
+pub enum GameMessage {
+    /// A message sent through inline mode.
+    Inline(tl::enums::InputBotInlineMessageId),
+    /// A message sent by the bot to a chat.
+    Chat(PackedChat, i32),
+}
+
+impl GameMessage {
+    /// Returns the message a callback query was sent from.
+    pub fn from_query(query: &CallbackQuery) -> Option<Self> {
+        match &query.raw {
+            tl::enums::Update::InlineBotCallbackQuery(update) => {
+                Some(Self::Inline(update.msg_id.clone()))
+            }
+            tl::enums::Update::BotCallbackQuery(update) => {
+                Some(Self::Chat(query.chat().pack(), update.msg_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the message a game was sent in.
+    pub fn from_message(message: &Message) -> Self {
+        Self::Chat(message.chat().pack(), message.id())
+    }
+
+    /// Checks if both are the same message.
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Inline(a), Self::Inline(b)) => a == b,
+            (Self::Chat(chat_a, id_a), Self::Chat(chat_b, id_b)) => {
+                chat_a.id == chat_b.id && id_a == id_b
+            }
+            _ => false,
+        }
+    }
+
+    /// Edits the message with the bot.
+    pub async fn edit(
+        &self,
+        bot: &Client,
+        input: InputMessage,
+    ) -> std::result::Result<(), InvocationError> {
+        match self {
+            Self::Inline(msg_id) => bot.edit_inline_message(msg_id.clone(), input).await,
+            Self::Chat(chat, message_id) => bot.edit_message(*chat, *message_id, input).await,
+        }
+    }
 }
 
 impl GameManager {
     /// Creates a new `GameManager` instance.
-    pub fn new() -> Self {
+    pub fn new(move_timeout: Duration, expire_after: Duration) -> Self {
         Self {
-            active_games: Arc::new(Mutex::new(Vec::new())),
+            active_games: Arc::new(Mutex::new(HashMap::new())),
+            move_timeout,
+            expire_after,
+            timers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Generates a new game ID.
-    pub fn new_id(&self) -> i32 {
-        let games = self
-            .active_games
-            .try_lock()
-            .expect("failed to lock active games");
-        let last_id = games.last().map(|g| g.id()).unwrap_or(0);
+    /// Generates a new game ID, random so the IDs of an earlier run are not
+    /// given again.
+    pub async fn new_id(&self) -> i32 {
+        let games = self.active_games.lock().await;
 
-        last_id + 1
+        loop {
+            let id = rand::thread_rng().gen_range(1..=i32::MAX);
+            if !games.contains_key(&id) {
+                return id;
+            }
+        }
     }
 
     /// Adds a game to the list of active games.
-    pub fn add_game(&self, game: Game) {
-        self.active_games
-            .try_lock()
-            .expect("failed to lock active games")
-            .push(game);
+    pub async fn add_game(&self, game: Game, message: Option<GameMessage>) {
+        self.active_games.lock().await.insert(
+            game.id(),
+            Entry {
+                game,
+                message,
+                last_active: Instant::now(),
+            },
+        );
     }
 
     /// Returns the game with the given ID.
-    pub fn get_game(&self, game_id: i32) -> Option<Game> {
+    pub async fn get_game(&self, game_id: i32) -> Option<Game> {
         self.active_games
-            .try_lock()
-            .expect("failed to lock active games")
-            .iter()
-            .find(|g| g.id() == game_id)
-            .cloned()
+            .lock()
+            .await
+            .get(&game_id)
+            .map(|entry| entry.game.clone())
     }
 
-    /// Updates a game.
-    pub fn update_game(&mut self, game: Game) {
-        let game_id = game.id();
-        *self
-            .active_games
-            .try_lock()
-            .expect("failed to lock active games")
-            .iter_mut()
-            .find(|g| g.id() == game_id)
-            .expect("failed to find game") = game;
+    /// Updates a game, postponing its expiration.
+    pub async fn update_game(&self, game: Game) {
+        if let Some(entry) = self.active_games.lock().await.get_mut(&game.id()) {
+            entry.game = game;
+            entry.last_active = Instant::now();
+        }
+    }
+
+    /// Changes a game in place, postponing its expiration, and returns what `f` returns.
+    ///
+    /// Unlike getting and updating it, no change made meanwhile is lost.
+    pub async fn with_game<R>(&self, game_id: i32, f: impl FnOnce(&mut Game) -> R) -> Option<R> {
+        let mut games = self.active_games.lock().await;
+        let entry = games.get_mut(&game_id)?;
+        entry.last_active = Instant::now();
+
+        Some(f(&mut entry.game))
+    }
+
+    /// Binds a game sent through inline mode to the message of the first button
+    /// pressed, if not yet.
+    ///
+    /// Returns `false` if the query comes from another message than the one
+    /// the game is shown in, as a copied keyboard or a replayed button, or from
+    /// a chat message for a game sent through inline mode.
+    pub async fn bind(&self, game_id: i32, query: &CallbackQuery) -> bool {
+        let mut games = self.active_games.lock().await;
+        let Some(entry) = games.get_mut(&game_id) else {
+            return false;
+        };
+        let Some(message) = GameMessage::from_query(query) else {
+            return false;
+        };
+
+        match &entry.message {
+            Some(bound) => bound.is_same(&message),
+            None if matches!(message, GameMessage::Inline(_)) => {
+                entry.message = Some(message);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Removes a game from the list of active games.
-    pub fn remove_game(&self, game: Game) {
-        self.active_games
-            .try_lock()
-            .expect("failed to lock active games")
-            .retain(|g| g.id() != game.id());
+    pub async fn remove_game(&self, game: Game) {
+        self.disarm_timer(game.id()).await;
+        self.active_games.lock().await.remove(&game.id());
+    }
+
+    /// Arms the turn timer of a game, replacing the previous one.
+    ///
+    /// When it expires, the current player forfeits, the game is removed and
+    /// `on_timeout` is called with the ended game and its message.
+    pub async fn arm_timer<F, Fut>(&self, game_id: i32, on_timeout: F)
+    where
+        F: FnOnce(Game, Option<GameMessage>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let active_games = Arc::clone(&self.active_games);
+        let timers = Arc::clone(&self.timers);
+        let timeout = self.move_timeout;
+
+        let handle = tokio::task::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            timers.lock().await.remove(&game_id);
+
+            let Some(mut entry) = active_games.lock().await.remove(&game_id) else {
+                return;
+            };
+            if let Some(id) = entry.game.current_player().map(|p| p.id()) {
+                entry.game.forfeit(id);
+            }
+
+            on_timeout(entry.game, entry.message).await;
+        })
+        .abort_handle();
+
+        if let Some(previous) = self.timers.lock().await.insert(game_id, handle) {
+            previous.abort();
+        }
+    }
+
+    /// Clears the turn timer of a game.
+    pub async fn disarm_timer(&self, game_id: i32) {
+        if let Some(handle) = self.timers.lock().await.remove(&game_id) {
+            handle.abort();
+        }
+    }
+
+    /// Removes the games without moves for too long, returning them with their message.
+    async fn expire(&self) -> Vec<(Game, Option<GameMessage>)> {
+        let expired = {
+            let mut games = self.active_games.lock().await;
+            let ids = games
+                .iter()
+                .filter(|(_, entry)| entry.last_active.elapsed() >= self.expire_after)
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+
+            ids.into_iter()
+                .filter_map(|id| games.remove(&id))
+                .collect::<Vec<_>>()
+        };
+
+        let mut games = Vec::with_capacity(expired.len());
+        for entry in expired {
+            self.disarm_timer(entry.game.id()).await;
+            games.push((entry.game, entry.message));
+        }
+
+        games
+    }
+
+    /// Ends the active games, telling so in their message, as they are not kept
+    /// across restarts.
+    ///
+    /// Returns how many games were ended.
+    pub async fn interrupt(&self, bot: &Client, i18n: &I18n) -> usize {
+        let entries = self
+            .active_games
+            .lock()
+            .await
+            .drain()
+            .map(|(_, entry)| entry)
+            .collect::<Vec<_>>();
+        for (_, timer) in self.timers.lock().await.drain() {
+            timer.abort();
+        }
+
+        for entry in entries.iter() {
+            let Some(message) = &entry.message else {
+                continue;
+            };
+            let text = format!(
+                "{0}\n\n{1}",
+                entry.game.generate_text(),
+                i18n.translate("game_interrupted")
+            );
+
+            if let Err(e) = message.edit(bot, InputMessage::html(text)).await {
+                log::error!("Failed to update the interrupted game: {}", e);
+            }
+        }
+
+        entries.len()
+    }
+
+    /// Spawns the task that removes the stale games, telling so in their message.
+    pub fn start(self, bot: Client, i18n: I18n) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                tokio::time::sleep(EXPIRATION_INTERVAL).await;
+
+                for (game, message) in self.expire().await {
+                    log::info!("{} expired", game);
+
+                    let Some(message) = message else {
+                        continue;
+                    };
+                    let text = format!(
+                        "{0}\n\n{1}",
+                        game.generate_text(),
+                        i18n.translate("game_expired")
+                    );
+
+                    if let Err(e) = message.edit(&bot, InputMessage::html(text)).await {
+                        log::error!("Failed to update the expired game: {}", e);
+                    }
+                }
+            }
+        });
     }
 }
 
@@ -86,8 +360,14 @@ impl GameManager {
 pub enum Game {
     /// The tic tac toe game.
     TicTacToe(TicTacToe),
-    /* /// The sudoku game.
-    Sudoku(Sudoku), */
+    /// The sudoku game.
+    Sudoku(Sudoku),
+    /// The connect four game.
+    ConnectFour(ConnectFour),
+    /// The chess game.
+    Chess(Chess),
+    /// The trivia game.
+    Trivia(Trivia),
 }
 
 impl Game {
@@ -95,10 +375,19 @@ impl Game {
     pub fn id(&self) -> i32 {
         match self {
             Self::TicTacToe(g) => g.id,
+            Self::Sudoku(g) => g.id,
+            Self::ConnectFour(g) => g.id,
+            Self::Chess(g) => g.id,
+            Self::Trivia(g) => g.id,
         }
     }
 
     /// Plays the game.
+    ///
+    /// In sudoku, it selects the cell the next digit goes to. In connect four,
+    /// the piece is dropped in the column and the row is ignored. In chess, it
+    /// selects a piece or moves the selected one to the square. In trivia, the
+    /// answers are given with [`Trivia::answer`] instead.
     pub fn play(&mut self, column: usize, row: usize) -> bool {
         match self {
             Self::TicTacToe(g) => {
@@ -162,13 +451,91 @@ impl Game {
 
                 false
             }
+            Self::Sudoku(g) => g.select(row, column),
+            Self::ConnectFour(g) => g.drop(column),
+            Self::Chess(g) => g.select(row * 8 + column) != Select::Illegal,
+            Self::Trivia(_) => false,
+        }
+    }
+
+    /// Returns the sudoku game, if it is one.
+    pub fn as_sudoku(&self) -> Option<&Sudoku> {
+        match self {
+            Self::Sudoku(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Returns the sudoku game mutably, if it is one.
+    pub fn as_sudoku_mut(&mut self) -> Option<&mut Sudoku> {
+        match self {
+            Self::Sudoku(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Returns the chess game, if it is one.
+    pub fn as_chess(&self) -> Option<&Chess> {
+        match self {
+            Self::Chess(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Returns the chess game mutably, if it is one.
+    pub fn as_chess_mut(&mut self) -> Option<&mut Chess> {
+        match self {
+            Self::Chess(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Returns the trivia game, if it is one.
+    pub fn as_trivia(&self) -> Option<&Trivia> {
+        match self {
+            Self::Trivia(g) => Some(g),
+            _ => None,
+        }
+    }
+
+    /// Returns the trivia game mutably, if it is one.
+    pub fn as_trivia_mut(&mut self) -> Option<&mut Trivia> {
+        match self {
+            Self::Trivia(g) => Some(g),
+            _ => None,
         }
     }
 
-    /// Returns the game board.
-    pub fn board(&self) -> Vec<Vec<char>> {
+    /// Returns the game board, as the symbols shown in the buttons.
+    pub fn board(&self) -> Vec<Vec<String>> {
+        let to_strings = |board: Vec<Vec<char>>| {
+            board
+                .into_iter()
+                .map(|row| row.into_iter().map(String::from).collect())
+                .collect()
+        };
+
         match self {
-            Self::TicTacToe(g) => g.board.clone(),
+            Self::TicTacToe(g) => g
+                .board
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|symbol| g.symbols.get(*symbol).emoji.clone())
+                        .collect()
+                })
+                .collect(),
+            Self::Sudoku(g) => to_strings(g.board_symbols()),
+            Self::ConnectFour(g) => to_strings(g.board.clone()),
+            Self::Chess(g) => (0..8)
+                .map(|row| {
+                    (0..8)
+                        .map(|column| g.symbol(row * 8 + column).to_string())
+                        .collect()
+                })
+                .collect(),
+            // The trivia answers are its buttons.
+            Self::Trivia(_) => Vec::new(),
         }
     }
 
@@ -176,6 +543,10 @@ impl Game {
     pub fn players(&self) -> Vec<Player> {
         match self {
             Self::TicTacToe(g) => g.players.clone().into_values().into_iter().collect(),
+            Self::Sudoku(g) => g.players.clone().into_values().into_iter().collect(),
+            Self::ConnectFour(g) => g.players.clone().into_values().into_iter().collect(),
+            Self::Chess(g) => g.players.clone().into_values().into_iter().collect(),
+            Self::Trivia(g) => g.players.clone().into_values().into_iter().collect(),
         }
     }
 
@@ -183,6 +554,10 @@ impl Game {
     pub fn is_over(&self) -> bool {
         match self {
             Self::TicTacToe(g) => g.state == State::End,
+            Self::Sudoku(g) => g.state == State::End,
+            Self::ConnectFour(g) => g.state == State::End,
+            Self::Chess(g) => g.state == State::End,
+            Self::Trivia(g) => g.state == State::End,
         }
     }
 
@@ -190,6 +565,10 @@ impl Game {
     pub fn winner(&self) -> Option<&Player> {
         match self {
             Self::TicTacToe(g) => self.get_player(g.winner?),
+            Self::Sudoku(g) => self.get_player(g.winner?),
+            Self::ConnectFour(g) => self.get_player(g.winner?),
+            Self::Chess(g) => self.get_player(g.winner?),
+            Self::Trivia(g) => self.get_player(g.winner?),
         }
     }
 
@@ -211,6 +590,47 @@ impl Game {
                 g.players.insert(player.id(), player);
                 g.state = State::Playing;
 
+                true
+            }
+            Self::Sudoku(g) => {
+                if g.players.contains_key(&player.id()) || g.players.len() >= limit {
+                    return false;
+                }
+
+                g.players.insert(player.id(), player);
+
+                true
+            }
+            Self::ConnectFour(g) => {
+                if g.players.contains_key(&player.id()) || g.players.len() >= limit {
+                    return false;
+                }
+
+                player.symbol = CONNECT_FOUR_SYMBOLS[1];
+                g.players.insert(player.id(), player);
+                g.state = State::Playing;
+
+                true
+            }
+            Self::Chess(g) => {
+                if g.players.contains_key(&player.id()) || g.players.len() >= limit {
+                    return false;
+                }
+
+                player.symbol = CHESS_SYMBOLS[1];
+                g.players.insert(player.id(), player);
+                g.state = State::Playing;
+
+                true
+            }
+            Self::Trivia(g) => {
+                if g.players.contains_key(&player.id()) || g.players.len() >= limit {
+                    return false;
+                }
+
+                g.scores.insert(player.id(), 0);
+                g.players.insert(player.id(), player);
+
                 true
             }
         }
@@ -220,6 +640,10 @@ impl Game {
     pub fn get_player(&self, id: i64) -> Option<&Player> {
         match self {
             Self::TicTacToe(g) => g.players.get(&id),
+            Self::Sudoku(g) => g.players.get(&id),
+            Self::ConnectFour(g) => g.players.get(&id),
+            Self::Chess(g) => g.players.get(&id),
+            Self::Trivia(g) => g.players.get(&id),
         }
     }
 
@@ -227,6 +651,10 @@ impl Game {
     pub fn has_player(&self, id: i64) -> bool {
         match self {
             Self::TicTacToe(g) => g.players.contains_key(&id),
+            Self::Sudoku(g) => g.players.contains_key(&id),
+            Self::ConnectFour(g) => g.players.contains_key(&id),
+            Self::Chess(g) => g.players.contains_key(&id),
+            Self::Trivia(g) => g.players.contains_key(&id),
         }
     }
 
@@ -237,6 +665,43 @@ impl Game {
             Self::TicTacToe(g) => {
                 g.players.remove(&id);
             }
+            Self::Sudoku(g) => {
+                g.players.remove(&id);
+            }
+            Self::ConnectFour(g) => {
+                g.players.remove(&id);
+            }
+            Self::Chess(g) => {
+                g.players.remove(&id);
+            }
+            Self::Trivia(g) => {
+                g.players.remove(&id);
+                g.scores.remove(&id);
+            }
+        }
+    }
+
+    /// Ends the game with the given player giving up.
+    pub fn forfeit(&mut self, id: i64) {
+        match self {
+            Self::TicTacToe(g) => {
+                g.winner = g.players.keys().find(|p| **p != id).copied();
+                g.state = State::End;
+            }
+            Self::Sudoku(g) => {
+                g.winner = None;
+                g.state = State::End;
+            }
+            Self::ConnectFour(g) => {
+                g.winner = g.players.keys().find(|p| **p != id).copied();
+                g.state = State::End;
+            }
+            Self::Chess(g) => {
+                g.winner = g.players.keys().find(|p| **p != id).copied();
+                g.state = State::End;
+            }
+            // Nobody takes turns in trivia, so it just ends with the current scores.
+            Self::Trivia(g) => g.end(),
         }
     }
 
@@ -244,6 +709,10 @@ impl Game {
     pub fn current_player(&self) -> Option<&Player> {
         match self {
             Self::TicTacToe(g) => g.players.get(&g.current_player),
+            Self::Sudoku(g) => g.players.get(&g.current_player),
+            Self::ConnectFour(g) => g.players.get(&g.current_player),
+            Self::Chess(g) => g.players.get(&g.current_player),
+            Self::Trivia(g) => g.players.get(&g.current_player),
         }
     }
 
@@ -256,6 +725,20 @@ impl Game {
 
                 g.players.get(next_player)
             }
+            Self::ConnectFour(g) => {
+                let next_player = g.players.keys().find(|id| **id != g.current_player)?;
+
+                g.players.get(next_player)
+            }
+            Self::Chess(g) => {
+                let next_player = g.players.keys().find(|id| **id != g.current_player)?;
+
+                g.players.get(next_player)
+            }
+            // The sudoku is played alone.
+            Self::Sudoku(_) => None,
+            // Everyone answers the trivia at once.
+            Self::Trivia(_) => None,
         }
     }
 
@@ -271,6 +754,55 @@ impl Game {
 
         match self {
             Self::TicTacToe(g) => {
+                for (i, (player_id, player)) in g.players.iter().enumerate() {
+                    let symbol = g.symbols.get(player.symbol()).html();
+
+                    if *player_id == winner_id {
+                        text += &format!("👑 <b>{0}</b> ({1})", player.mention(), symbol);
+                    } else if g.state == State::End {
+                        text += &format!("🤡 <s>{0}</s> ({1})", player.mention(), symbol);
+                    } else if *player_id == g.current_player {
+                        text += &format!("<u>{0}</u> ({1})", player.mention(), symbol);
+                    } else {
+                        text += &format!("{0} ({1})", player.mention(), symbol);
+                    }
+
+                    if i < g.players.len() - 1 {
+                        text.push_str(" vs ");
+                    }
+                }
+            }
+            Self::Sudoku(g) => {
+                for (player_id, player) in g.players.iter() {
+                    if *player_id == winner_id {
+                        text += &format!("👑 <b>{}</b>", player.mention());
+                    } else if g.state == State::End {
+                        text += &format!("🤡 <s>{}</s>", player.mention());
+                    } else {
+                        text += &player.mention();
+                    }
+                }
+
+                text += &format!(" — ❌ {0}/{1}", g.mistakes, SUDOKU_MAX_MISTAKES);
+            }
+            Self::ConnectFour(g) => {
+                for (i, (player_id, player)) in g.players.iter().enumerate() {
+                    if *player_id == winner_id {
+                        text += &format!("👑 <b>{0}</b> ({1})", player.mention(), player.symbol());
+                    } else if g.state == State::End {
+                        text += &format!("🤡 <s>{0}</s> ({1})", player.mention(), player.symbol());
+                    } else if *player_id == g.current_player {
+                        text += &format!("<u>{0}</u> ({1})", player.mention(), player.symbol());
+                    } else {
+                        text += &format!("{0} ({1})", player.mention(), player.symbol());
+                    }
+
+                    if i < g.players.len() - 1 {
+                        text.push_str(" vs ");
+                    }
+                }
+            }
+            Self::Chess(g) => {
                 for (i, (player_id, player)) in g.players.iter().enumerate() {
                     if *player_id == winner_id {
                         text += &format!("👑 <b>{0}</b> ({1})", player.mention(), player.symbol());
@@ -287,6 +819,15 @@ impl Game {
                     }
                 }
             }
+            Self::Trivia(g) => {
+                for (player, score) in g.ranking() {
+                    if player.id() == winner_id {
+                        text += &format!("👑 <b>{0}</b> — {1}\n", player.mention(), score);
+                    } else {
+                        text += &format!("{0} — {1}\n", player.mention(), score);
+                    }
+                }
+            }
         }
 
         text
@@ -296,15 +837,35 @@ impl Game {
     pub fn players_limit(&self) -> usize {
         match self {
             Self::TicTacToe(_) => 2,
+            Self::Sudoku(_) => 1,
+            Self::ConnectFour(_) => 2,
+            Self::Chess(_) => 2,
+            Self::Trivia(_) => TRIVIA_MAX_PLAYERS,
         }
     }
 
     /// Generates the game text.
     pub fn generate_text(&self) -> String {
         let mut text = match self {
-            Self::TicTacToe(_) => "<b>Tic Tac Toe</b>\n",
-        }
-        .to_string();
+            Self::TicTacToe(_) => "<b>Tic Tac Toe</b>\n".to_string(),
+            Self::Sudoku(g) => format!(
+                "<b>Sudoku</b> ({0})\n\n<pre>{1}</pre>\n",
+                g.difficulty.name(),
+                g.render()
+            ),
+            Self::ConnectFour(g) => format!("<b>Connect Four</b>\n\n{}", g.render()),
+            Self::Chess(g) => format!(
+                "<b>Chess</b>\n\n<code>{0}</code>\n{1}",
+                g.fen(),
+                g.history()
+            ),
+            Self::Trivia(g) => format!(
+                "<b>Trivia</b> ({0}/{1})\n\n{2}\n",
+                (g.round + 1).min(g.questions.len()),
+                g.questions.len(),
+                g.render()
+            ),
+        };
         text += &format!("\n{}", self.player_list());
 
         text
@@ -315,6 +876,12 @@ impl Game {
     pub fn generate_board(&mut self, size: RangeInclusive<usize>) {
         match self {
             Self::TicTacToe(g) => g.generate_board(size),
+            // The sudoku board is generated from its difficulty.
+            Self::Sudoku(_) => {}
+            // The connect four and chess boards have a fixed size.
+            Self::ConnectFour(_) | Self::Chess(_) => {}
+            // The trivia has no board.
+            Self::Trivia(_) => {}
         }
     }
 
@@ -322,6 +889,10 @@ impl Game {
     pub fn switch_player(&mut self) {
         match self {
             Self::TicTacToe(g) => g.switch_player(),
+            Self::Sudoku(_) => {}
+            Self::ConnectFour(g) => g.switch_player(),
+            Self::Chess(g) => g.switch_player(),
+            Self::Trivia(_) => {}
         }
     }
 
@@ -334,6 +905,10 @@ impl Game {
     pub fn set_current_player(&mut self, id: i64) {
         match self {
             Self::TicTacToe(g) => g.current_player = id,
+            Self::Sudoku(g) => g.current_player = id,
+            Self::ConnectFour(g) => g.current_player = id,
+            Self::Chess(g) => g.current_player = id,
+            Self::Trivia(g) => g.current_player = id,
         }
     }
 }
@@ -342,6 +917,10 @@ impl std::fmt::Display for Game {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::TicTacToe(g) => write!(f, "Tic Tac Toe (ID: {})", g.id),
+            Self::Sudoku(g) => write!(f, "Sudoku (ID: {})", g.id),
+            Self::ConnectFour(g) => write!(f, "Connect Four (ID: {})", g.id),
+            Self::Chess(g) => write!(f, "Chess (ID: {})", g.id),
+            Self::Trivia(g) => write!(f, "Trivia (ID: {})", g.id),
         }
     }
 }
@@ -371,6 +950,8 @@ pub struct TicTacToe {
     last_player: i64,
     /// The current player.
     current_player: i64,
+    /// The symbols shown for the marks of the board.
+    symbols: Symbols,
 }
 
 impl TicTacToe {
@@ -394,9 +975,16 @@ impl TicTacToe {
             winner: None,
             last_player: 0,
             current_player: first_player_id,
+            symbols: Symbols::default(),
         }
     }
 
+    /// Shows the marks of the board with the given symbols.
+    pub fn with_symbols(mut self, symbols: Symbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
     /// Generates a new board.
     pub fn generate_board(&mut self, size: RangeInclusive<usize>) {
         let columns = size.start();
@@ -434,51 +1022,922 @@ impl TicTacToe {
     }
 }
 
-#[allow(dead_code)]
+/// The sudoku difficulty.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Parses a difficulty from its name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Self::Easy),
+            "medium" => Some(Self::Medium),
+            "hard" => Some(Self::Hard),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the difficulty.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        }
+    }
+
+    /// Returns how many cells are left empty.
+    fn holes(&self) -> usize {
+        match self {
+            Self::Easy => 36,
+            Self::Medium => 46,
+            Self::Hard => 54,
+        }
+    }
+}
+
+/// The result of putting a digit in a sudoku cell.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Put {
+    /// The digit was placed.
+    Placed,
+    /// The digit was wrong and counted as a mistake.
+    Wrong,
+    /// The last mistake was made.
+    Lost,
+    /// The board was completed.
+    Solved,
+    /// No cell was selected.
+    NoSelection,
+}
+
+/// A sudoku grid, `0` being an empty cell.
+type Grid = [[u8; 9]; 9];
+
+/// The sudoku game.
 #[derive(Clone)]
 pub struct Sudoku {
     /// The game ID.
     id: i32,
+    /// The given cells.
+    puzzle: Grid,
     /// The game board.
-    board: Vec<Vec<char>>,
+    board: Grid,
+    /// The solution.
+    solution: Grid,
+    /// The difficulty.
+    difficulty: Difficulty,
     /// The game players.
     players: HashMap<i64, Player>,
     /// The game state.
     state: State,
     /// The game winner.
     winner: Option<i64>,
-    /// The last player.
-    last_player: i64,
     /// The current player.
     current_player: i64,
+    /// The selected cell, as row and column.
+    selected: Option<(usize, usize)>,
+    /// The wrong digits put.
+    mistakes: usize,
 }
 
-/// The player.
-#[derive(Clone)]
-pub struct Player {
-    /// The player ID.
-    id: i64,
-    /// The player symbol.
-    symbol: char,
-    /// The player first name.
-    first_name: String,
-}
+impl Sudoku {
+    /// Creates a new `Sudoku` instance, generating a puzzle with a single solution.
+    pub fn new(id: i32, player: Player, difficulty: Difficulty) -> Self {
+        let mut rng = rand::thread_rng();
 
-impl Player {
-    /// Creates a new `Player` instance.
-    pub fn new(user: &Chat) -> Self {
-        let id = user.id();
-        let symbol = SYMBOLS[id as usize % SYMBOLS.len()];
-        let first_name = user.name().to_string();
+        let mut solution = [[0; 9]; 9];
+        fill_grid(&mut solution, &mut rng);
+
+        // Empties cells while the solution stays unique.
+        let mut puzzle = solution;
+        let mut cells = (0..81).collect::<Vec<_>>();
+        cells.shuffle(&mut rng);
+
+        let mut holes = 0;
+        for cell in cells {
+            if holes >= difficulty.holes() {
+                break;
+            }
+
+            let (row, column) = (cell / 9, cell % 9);
+            let digit = puzzle[row][column];
+            puzzle[row][column] = 0;
+
+            if count_solutions(&mut puzzle.clone(), 2) == 1 {
+                holes += 1;
+            } else {
+                puzzle[row][column] = digit;
+            }
+        }
+
+        let current_player = player.id();
 
         Self {
             id,
-            symbol,
-            first_name,
+            puzzle,
+            board: puzzle,
+            solution,
+            difficulty,
+            players: HashMap::from([(player.id(), player)]),
+            state: State::Playing,
+            winner: None,
+            current_player,
+            selected: None,
+            mistakes: 0,
         }
     }
 
-    /// Returns the player ID.
+    /// Selects the cell the next digit goes to.
+    ///
+    /// Returns `false` if the cell was given.
+    pub fn select(&mut self, row: usize, column: usize) -> bool {
+        if row >= 9 || column >= 9 || self.is_given(row, column) {
+            return false;
+        }
+
+        self.selected = Some((row, column));
+        true
+    }
+
+    /// Returns the selected cell, as row and column.
+    pub fn selected(&self) -> Option<(usize, usize)> {
+        self.selected
+    }
+
+    /// Clears the selected cell.
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    /// Checks if a cell was given.
+    pub fn is_given(&self, row: usize, column: usize) -> bool {
+        self.puzzle[row][column] != 0
+    }
+
+    /// Returns the digit of a cell, `0` being empty.
+    pub fn cell(&self, row: usize, column: usize) -> u8 {
+        self.board[row][column]
+    }
+
+    /// Puts a digit in the selected cell, `0` clearing it.
+    pub fn put(&mut self, digit: u8) -> Put {
+        let Some((row, column)) = self.selected.take() else {
+            return Put::NoSelection;
+        };
+
+        if digit == 0 {
+            self.board[row][column] = 0;
+            return Put::Placed;
+        }
+
+        if self.solution[row][column] != digit {
+            self.mistakes += 1;
+
+            if self.mistakes >= SUDOKU_MAX_MISTAKES {
+                self.state = State::End;
+                return Put::Lost;
+            }
+
+            return Put::Wrong;
+        }
+
+        self.board[row][column] = digit;
+        if self.board == self.solution {
+            self.winner = Some(self.current_player);
+            self.state = State::End;
+
+            return Put::Solved;
+        }
+
+        Put::Placed
+    }
+
+    /// Returns the board as symbols.
+    fn board_symbols(&self) -> Vec<Vec<char>> {
+        self.board
+            .iter()
+            .map(|row| row.iter().map(|digit| digit_symbol(*digit)).collect())
+            .collect()
+    }
+
+    /// Renders the board as monospaced text.
+    fn render(&self) -> String {
+        let mut text = String::new();
+
+        for (r, row) in self.board.iter().enumerate() {
+            if r > 0 && r % 3 == 0 {
+                text += "──────┼───────┼──────\n";
+            }
+
+            for (c, digit) in row.iter().enumerate() {
+                if c > 0 && c % 3 == 0 {
+                    text += "│ ";
+                }
+
+                text.push(if self.selected == Some((r, c)) {
+                    '▣'
+                } else {
+                    digit_symbol(*digit)
+                });
+                text.push(' ');
+            }
+
+            text = text.trim_end().to_string();
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Converts sudoku into a game.
+    pub fn into_game(self) -> Game {
+        Game::Sudoku(self)
+    }
+}
+
+/// Returns the symbol of a sudoku digit.
+fn digit_symbol(digit: u8) -> char {
+    if digit == 0 {
+        SUDOKU_EMPTY
+    } else {
+        (b'0' + digit) as char
+    }
+}
+
+/// Checks if a digit can go in a cell of a sudoku grid.
+fn can_place(grid: &Grid, row: usize, column: usize, digit: u8) -> bool {
+    let (box_row, box_column) = (row / 3 * 3, column / 3 * 3);
+
+    (0..9).all(|i| {
+        grid[row][i] != digit
+            && grid[i][column] != digit
+            && grid[box_row + i / 3][box_column + i % 3] != digit
+    })
+}
+
+/// Fills a sudoku grid with random digits, backtracking.
+fn fill_grid(grid: &mut Grid, rng: &mut impl Rng) -> bool {
+    let Some(cell) = (0..81).find(|cell| grid[cell / 9][cell % 9] == 0) else {
+        return true;
+    };
+    let (row, column) = (cell / 9, cell % 9);
+
+    let mut digits = (1..=9).collect::<Vec<u8>>();
+    digits.shuffle(rng);
+
+    for digit in digits {
+        if can_place(grid, row, column, digit) {
+            grid[row][column] = digit;
+
+            if fill_grid(grid, rng) {
+                return true;
+            }
+        }
+    }
+
+    grid[row][column] = 0;
+    false
+}
+
+/// Counts the solutions of a sudoku grid, up to a limit.
+fn count_solutions(grid: &mut Grid, limit: usize) -> usize {
+    let Some(cell) = (0..81).find(|cell| grid[cell / 9][cell % 9] == 0) else {
+        return 1;
+    };
+    let (row, column) = (cell / 9, cell % 9);
+
+    let mut count = 0;
+    for digit in 1..=9 {
+        if can_place(grid, row, column, digit) {
+            grid[row][column] = digit;
+            count += count_solutions(grid, limit - count);
+
+            if count >= limit {
+                break;
+            }
+        }
+    }
+
+    grid[row][column] = 0;
+    count
+}
+
+/// The connect four game.
+#[derive(Clone)]
+pub struct ConnectFour {
+    /// The game ID.
+    id: i32,
+    /// The game board, from the top row to the bottom one.
+    board: Vec<Vec<char>>,
+    /// The game players.
+    players: HashMap<i64, Player>,
+    /// The game state.
+    state: State,
+    /// The game winner.
+    winner: Option<i64>,
+    /// The current player.
+    current_player: i64,
+}
+
+impl ConnectFour {
+    /// Creates a new `ConnectFour` instance.
+    pub fn new(id: i32, mut players: Vec<Player>) -> Self {
+        let first_player_id = players[0].id();
+
+        for player in &mut players {
+            if player.id() == first_player_id {
+                player.symbol = CONNECT_FOUR_SYMBOLS[0];
+            } else {
+                player.symbol = CONNECT_FOUR_SYMBOLS[1];
+            }
+        }
+
+        let (rows, columns) = CONNECT_FOUR_SIZE;
+        let state = if players.len() > 1 {
+            State::Playing
+        } else {
+            State::Start
+        };
+
+        Self {
+            id,
+            board: vec![vec![CONNECT_FOUR_SYMBOLS[2]; columns]; rows],
+            players: players.into_iter().map(|p| (p.id(), p)).collect(),
+            state,
+            winner: None,
+            current_player: first_player_id,
+        }
+    }
+
+    /// Drops a piece of the current player in a column.
+    ///
+    /// Returns `false` if the column is full.
+    pub fn drop(&mut self, column: usize) -> bool {
+        let Some(player) = self.players.get(&self.current_player) else {
+            return false;
+        };
+        let (symbol, player_id) = (player.symbol(), player.id());
+
+        // The piece falls to the lowest empty row.
+        let Some(row) = (0..self.board.len())
+            .rev()
+            .find(|row| self.board[*row].get(column) == Some(&CONNECT_FOUR_SYMBOLS[2]))
+        else {
+            return false;
+        };
+        self.board[row][column] = symbol;
+
+        if self.connects_four(row, column) {
+            self.winner = Some(player_id);
+            self.state = State::End;
+        } else if self.board[0].iter().all(|s| *s != CONNECT_FOUR_SYMBOLS[2]) {
+            self.state = State::End;
+        }
+
+        self.switch_player();
+
+        true
+    }
+
+    /// Checks if the piece in a cell is part of four in a row.
+    fn connects_four(&self, row: usize, column: usize) -> bool {
+        let symbol = self.board[row][column];
+
+        // Horizontal, vertical and both diagonals.
+        [(0, 1), (1, 0), (1, 1), (1, -1)]
+            .iter()
+            .any(|(row_step, column_step)| {
+                let count_towards = |sign: isize| {
+                    (1..4)
+                        .take_while(|i| {
+                            let r = row as isize + row_step * i * sign;
+                            let c = column as isize + column_step * i * sign;
+
+                            r >= 0
+                                && c >= 0
+                                && self
+                                    .board
+                                    .get(r as usize)
+                                    .and_then(|row| row.get(c as usize))
+                                    == Some(&symbol)
+                        })
+                        .count()
+                };
+
+                1 + count_towards(1) + count_towards(-1) >= 4
+            })
+    }
+
+    /// Switches the current player.
+    pub fn switch_player(&mut self) {
+        // Alone, the turn is left for whoever joins next.
+        self.current_player = self
+            .players
+            .keys()
+            .find(|id| **id != self.current_player)
+            .copied()
+            .unwrap_or(0);
+    }
+
+    /// Renders the board with the column numbers on top.
+    fn render(&self) -> String {
+        let mut text = (1..=self.board[0].len())
+            .map(|column| format!("{}\u{fe0f}\u{20e3}", column))
+            .collect::<String>();
+        text.push('\n');
+
+        for row in &self.board {
+            text.extend(row.iter());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Converts connect four into a game.
+    pub fn into_game(self) -> Game {
+        Game::ConnectFour(self)
+    }
+}
+
+/// The result of selecting a chess square.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Select {
+    /// A piece was selected.
+    Selected,
+    /// The selected piece was moved.
+    Moved,
+    /// The square holds no piece of the player and is not a legal move.
+    Illegal,
+}
+
+/// The chess game.
+#[derive(Clone)]
+pub struct Chess {
+    /// The game ID.
+    id: i32,
+    /// The position, checked for legal moves by the engine.
+    position: shakmaty::Chess,
+    /// The moves played, in standard algebraic notation.
+    moves: Vec<String>,
+    /// The game players.
+    players: HashMap<i64, Player>,
+    /// The game state.
+    state: State,
+    /// The game winner.
+    winner: Option<i64>,
+    /// The current player.
+    current_player: i64,
+    /// The square of the selected piece, from `0` at a8 to `63` at h1.
+    selected: Option<usize>,
+    /// The player who offered a draw.
+    draw_offer: Option<i64>,
+}
+
+impl Chess {
+    /// Creates a new `Chess` instance, the first player playing white.
+    pub fn new(id: i32, mut players: Vec<Player>) -> Self {
+        let first_player_id = players[0].id();
+
+        for player in &mut players {
+            if player.id() == first_player_id {
+                player.symbol = CHESS_SYMBOLS[0];
+            } else {
+                player.symbol = CHESS_SYMBOLS[1];
+            }
+        }
+
+        let state = if players.len() > 1 {
+            State::Playing
+        } else {
+            State::Start
+        };
+
+        Self {
+            id,
+            position: shakmaty::Chess::default(),
+            moves: Vec::new(),
+            players: players.into_iter().map(|p| (p.id(), p)).collect(),
+            state,
+            winner: None,
+            current_player: first_player_id,
+            selected: None,
+            draw_offer: None,
+        }
+    }
+
+    /// Converts a square of the keyboard, from a8 to h1, into an engine square.
+    fn square(index: usize) -> Square {
+        let (row, column) = (index / 8, index % 8);
+
+        Square::new(((7 - row) * 8 + column) as u32)
+    }
+
+    /// Returns the symbol of the piece in a square of the keyboard.
+    pub fn symbol(&self, index: usize) -> char {
+        let Some(piece) = self.position.board().piece_at(Self::square(index)) else {
+            return '·';
+        };
+
+        let symbols = match piece.role {
+            Role::King => ['♔', '♚'],
+            Role::Queen => ['♕', '♛'],
+            Role::Rook => ['♖', '♜'],
+            Role::Bishop => ['♗', '♝'],
+            Role::Knight => ['♘', '♞'],
+            Role::Pawn => ['♙', '♟'],
+        };
+
+        match piece.color {
+            Color::White => symbols[0],
+            Color::Black => symbols[1],
+        }
+    }
+
+    /// Returns the square of the selected piece.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Returns the legal moves of the selected piece, as squares of the keyboard.
+    pub fn targets(&self) -> Vec<usize> {
+        let Some(from) = self.selected.map(Self::square) else {
+            return Vec::new();
+        };
+
+        (0..64)
+            .filter(|index| {
+                let to = Self::square(*index);
+                self.position
+                    .legal_moves()
+                    .iter()
+                    .any(|m| m.from() == Some(from) && m.to() == to)
+            })
+            .collect()
+    }
+
+    /// Selects a piece of the player to move, or moves the selected one.
+    ///
+    /// Pawns are always promoted to queens.
+    pub fn select(&mut self, index: usize) -> Select {
+        if index >= 64 {
+            return Select::Illegal;
+        }
+        let square = Self::square(index);
+
+        if let Some(from) = self.selected.take().map(Self::square) {
+            let legal = self
+                .position
+                .legal_moves()
+                .into_iter()
+                .filter(|m| m.from() == Some(from) && m.to() == square)
+                .find(|m| matches!(m.promotion(), None | Some(Role::Queen)));
+
+            if let Some(m) = legal {
+                self.play(&m);
+                return Select::Moved;
+            }
+        }
+
+        match self.position.board().piece_at(square) {
+            Some(piece) if piece.color == self.position.turn() => {
+                self.selected = Some(index);
+                Select::Selected
+            }
+            _ => Select::Illegal,
+        }
+    }
+
+    /// Plays a legal move and checks if the game is over.
+    fn play(&mut self, m: &Move) {
+        let san = San::from_move(&self.position, m);
+        self.moves.push(san.to_string());
+        self.position.play_unchecked(m);
+        self.draw_offer = None;
+
+        match self.position.outcome() {
+            Some(Outcome::Decisive { .. }) => {
+                self.winner = Some(self.current_player);
+                self.state = State::End;
+            }
+            Some(Outcome::Draw) => self.state = State::End,
+            None => {}
+        }
+
+        self.switch_player();
+    }
+
+    /// Offers a draw, or accepts the one offered by the opponent.
+    ///
+    /// Returns `true` if the game ended in a draw.
+    pub fn offer_draw(&mut self, id: i64) -> bool {
+        match self.draw_offer {
+            Some(player) if player != id => {
+                self.winner = None;
+                self.state = State::End;
+
+                true
+            }
+            _ => {
+                self.draw_offer = Some(id);
+                false
+            }
+        }
+    }
+
+    /// Switches the current player.
+    pub fn switch_player(&mut self) {
+        // Alone, the turn is left for whoever joins next.
+        self.current_player = self
+            .players
+            .keys()
+            .find(|id| **id != self.current_player)
+            .copied()
+            .unwrap_or(0);
+    }
+
+    /// Returns the position in Forsyth-Edwards notation.
+    pub fn fen(&self) -> String {
+        Fen::from_position(self.position.clone(), EnPassantMode::Legal).to_string()
+    }
+
+    /// Returns the last moves, numbered.
+    fn history(&self) -> String {
+        let skip = self.moves.len().saturating_sub(CHESS_HISTORY_LENGTH) / 2 * 2;
+
+        let mut text = if skip > 0 {
+            "… ".to_string()
+        } else {
+            String::new()
+        };
+        for (i, pair) in self.moves[skip..].chunks(2).enumerate() {
+            text += &format!("{0}. {1} ", skip / 2 + i + 1, pair.join(" "));
+        }
+
+        text.trim_end().to_string()
+    }
+
+    /// Converts chess into a game.
+    pub fn into_game(self) -> Game {
+        Game::Chess(self)
+    }
+}
+
+/// A symbol shown for a tic tac toe mark.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    /// The emoji, also the fallback of the custom emoji.
+    pub emoji: String,
+    /// The ID of the custom emoji, if any.
+    pub custom_emoji_id: Option<i64>,
+}
+
+impl Symbol {
+    /// Creates a symbol of a plain emoji.
+    fn plain(emoji: char) -> Self {
+        Self {
+            emoji: emoji.to_string(),
+            custom_emoji_id: None,
+        }
+    }
+
+    /// Returns the symbol as HTML, the buttons only showing the fallback emoji.
+    pub fn html(&self) -> String {
+        match self.custom_emoji_id {
+            Some(id) => format!("<tg-emoji emoji-id=\"{0}\">{1}</tg-emoji>", id, self.emoji),
+            None => self.emoji.clone(),
+        }
+    }
+}
+
+/// The symbols shown for the marks of a tic tac toe board.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Symbols {
+    /// The symbols of the first and the second player.
+    pub players: [Symbol; 2],
+    /// The symbol of an empty cell.
+    pub empty: Symbol,
+}
+
+impl Symbols {
+    /// Returns the symbol shown for a mark of the board.
+    pub fn get(&self, mark: char) -> &Symbol {
+        match SYMBOLS.iter().position(|symbol| *symbol == mark) {
+            Some(0) => &self.players[0],
+            Some(1) => &self.players[1],
+            _ => &self.empty,
+        }
+    }
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Self {
+            players: [Symbol::plain(SYMBOLS[0]), Symbol::plain(SYMBOLS[1])],
+            empty: Symbol::plain(SYMBOLS[2]),
+        }
+    }
+}
+
+/// The result of answering a trivia question.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Answer {
+    /// The answer was recorded.
+    Recorded,
+    /// The player had already answered.
+    Repeated,
+    /// The round is over.
+    Closed,
+}
+
+/// The trivia game.
+#[derive(Clone)]
+pub struct Trivia {
+    /// The game ID.
+    id: i32,
+    /// The questions, one per round.
+    questions: Vec<Question>,
+    /// The current round.
+    round: usize,
+    /// Whether the current round stopped taking answers.
+    closed: bool,
+    /// The answers to the current question, by player.
+    answers: HashMap<i64, usize>,
+    /// The game players.
+    players: HashMap<i64, Player>,
+    /// The scores, by player.
+    scores: HashMap<i64, u32>,
+    /// The game state.
+    state: State,
+    /// The game winner.
+    winner: Option<i64>,
+    /// The player who started the game.
+    current_player: i64,
+}
+
+impl Trivia {
+    /// Creates a new `Trivia` instance, started by `host`.
+    pub fn new(id: i32, host: Player, questions: Vec<Question>) -> Self {
+        Self {
+            id,
+            questions,
+            round: 0,
+            closed: false,
+            answers: HashMap::new(),
+            scores: HashMap::from([(host.id(), 0)]),
+            current_player: host.id(),
+            players: HashMap::from([(host.id(), host)]),
+            state: State::Playing,
+            winner: None,
+        }
+    }
+
+    /// Returns the current round.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Returns the question of the current round.
+    pub fn question(&self) -> Option<&Question> {
+        self.questions.get(self.round)
+    }
+
+    /// Answers the current question, the player joining the game if needed.
+    pub fn answer(&mut self, player: Player, answer: usize) -> Answer {
+        if self.closed || self.state == State::End {
+            return Answer::Closed;
+        } else if self.answers.contains_key(&player.id()) {
+            return Answer::Repeated;
+        }
+
+        self.scores.entry(player.id()).or_insert(0);
+        self.answers.insert(player.id(), answer);
+        self.players.entry(player.id()).or_insert(player);
+
+        Answer::Recorded
+    }
+
+    /// Stops the current round, scoring the right answers.
+    ///
+    /// The game ends after the last round.
+    pub fn close_round(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+
+        if let Some(question) = self.questions.get(self.round) {
+            for (id, answer) in &self.answers {
+                if *answer == question.correct {
+                    *self.scores.entry(*id).or_insert(0) += 1;
+                }
+            }
+        }
+
+        if self.round + 1 >= self.questions.len() {
+            self.end();
+        }
+    }
+
+    /// Starts the next round.
+    pub fn next_round(&mut self) {
+        if self.state != State::End {
+            self.round += 1;
+            self.closed = false;
+            self.answers.clear();
+        }
+    }
+
+    /// Ends the game, the player with the highest score winning, if not tied.
+    fn end(&mut self) {
+        self.state = State::End;
+
+        let ranking = self.ranking();
+        self.winner = match ranking.as_slice() {
+            [(first, score), rest @ ..]
+                if *score > 0 && rest.first().map_or(true, |(_, s)| s < score) =>
+            {
+                Some(first.id())
+            }
+            _ => None,
+        };
+    }
+
+    /// Returns the players with their scores, the highest first.
+    pub fn ranking(&self) -> Vec<(&Player, u32)> {
+        let mut ranking = self
+            .players
+            .values()
+            .map(|player| (player, self.scores.get(&player.id()).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1));
+
+        ranking
+    }
+
+    /// Renders the current question, with the right answer and how many got it once the
+    /// round is closed.
+    fn render(&self) -> String {
+        let Some(question) = self.question() else {
+            return String::new();
+        };
+
+        let mut text = format!("<b>{}</b>\n", escape_html(&question.text));
+        if self.closed {
+            let right = self
+                .answers
+                .values()
+                .filter(|answer| **answer == question.correct)
+                .count();
+
+            text += &format!(
+                "\n✅ {0} ({1}/{2})\n",
+                escape_html(&question.answers[question.correct]),
+                right,
+                self.answers.len()
+            );
+        }
+
+        text
+    }
+
+    /// Converts trivia into a game.
+    pub fn into_game(self) -> Game {
+        Game::Trivia(self)
+    }
+}
+
+/// The player.
+#[derive(Clone)]
+pub struct Player {
+    /// The player ID.
+    id: i64,
+    /// The player symbol.
+    symbol: char,
+    /// The player first name.
+    first_name: String,
+}
+
+impl Player {
+    /// Creates a new `Player` instance.
+    pub fn new(user: &Chat) -> Self {
+        let id = user.id();
+        let symbol = SYMBOLS[id as usize % SYMBOLS.len()];
+        let first_name = user.name().to_string();
+
+        Self {
+            id,
+            symbol,
+            first_name,
+        }
+    }
+
+    /// Returns the player ID.
     pub fn id(&self) -> i64 {
         self.id
     }
@@ -496,7 +1955,6 @@ impl Player {
         )
     }
 
-    #[allow(dead_code)]
     /// Returns the player first name.
     pub fn first_name(&self) -> &str {
         &self.first_name
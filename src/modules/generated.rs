@@ -0,0 +1,61 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the generated messages module.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use grammers_client::types::Message;
+
+/// How long a generated message is remembered.
+const TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The registry shared by the filter and the handlers.
+static SHARED: LazyLock<Generated> = LazyLock::new(|| Generated {
+    messages: Arc::new(Mutex::new(HashMap::new())),
+});
+
+/// The messages recently sent or edited by the handlers, so the handlers
+/// that react to the account's own messages do not loop on them.
+#[derive(Clone)]
+pub struct Generated {
+    /// When each message, by chat and ID, was generated.
+    messages: Arc<Mutex<HashMap<(i64, i32), Instant>>>,
+}
+
+impl Generated {
+    /// Returns the shared registry, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Tags a message as generated by a handler.
+    pub fn register(&self, message: &Message) {
+        let mut messages = self
+            .messages
+            .lock()
+            .expect("failed to lock generated messages");
+
+        // Forgets the messages too old to come back as updates.
+        messages.retain(|_, generated| generated.elapsed() < TTL);
+        messages.insert((message.chat().id(), message.id()), Instant::now());
+    }
+
+    /// Checks if a message was generated by a handler.
+    pub fn contains(&self, chat_id: i64, message_id: i32) -> bool {
+        self.messages
+            .lock()
+            .expect("failed to lock generated messages")
+            .get(&(chat_id, message_id))
+            .is_some_and(|generated| generated.elapsed() < TTL)
+    }
+}
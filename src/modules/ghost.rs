@@ -0,0 +1,140 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the ghost mode module.
+
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, types::Chat, Client};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The ghost mode shared by every sender of read acknowledgements and chat
+/// actions.
+static SHARED: LazyLock<Ghost> = LazyLock::new(|| Ghost {
+    storage: Storage::open("ghost"),
+});
+
+/// The ghost mode, which keeps the user account from showing activity.
+///
+/// Read acknowledgements and chat actions from the user account must go
+/// through it, so the ghosted chats never receive them.
+#[derive(Clone)]
+pub struct Ghost {
+    /// The ghosted chats.
+    storage: Storage<Data>,
+}
+
+impl Ghost {
+    /// Returns the shared ghost mode, so the senders that cannot be injected
+    /// also go through it.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Ghosts a chat.
+    ///
+    /// Returns `false` if it was already ghosted.
+    pub async fn enable(&self, chat_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                if data.chats.contains(&chat_id) {
+                    false
+                } else {
+                    data.chats.push(chat_id);
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Stops ghosting a chat.
+    ///
+    /// Returns `false` if it was not ghosted.
+    pub async fn disable(&self, chat_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.chats.len();
+                data.chats.retain(|id| *id != chat_id);
+
+                data.chats.len() != len
+            })
+            .await
+    }
+
+    /// Checks if a chat is ghosted.
+    pub async fn is_ghosted(&self, chat_id: i64) -> bool {
+        self.storage
+            .read(|data| data.chats.contains(&chat_id))
+            .await
+    }
+
+    /// Returns the ghosted chats.
+    pub async fn chats(&self) -> Vec<i64> {
+        self.storage.read(|data| data.chats.clone()).await
+    }
+
+    /// Marks a chat as read, unless it is ghosted.
+    ///
+    /// Returns `false` if the read acknowledgement was suppressed.
+    pub async fn read(&self, client: &Client, chat: &Chat) -> Result<bool> {
+        if self.is_ghosted(chat.id()).await {
+            log::debug!("Suppressed the read acknowledgement of {}", chat.id());
+            return Ok(false);
+        }
+
+        client.mark_as_read(chat.pack()).await?;
+
+        Ok(true)
+    }
+
+    /// Shows a chat action, such as typing, unless the chat is ghosted.
+    ///
+    /// Returns `false` if the chat action was suppressed.
+    pub async fn action(
+        &self,
+        client: &Client,
+        chat: &Chat,
+        action: tl::enums::SendMessageAction,
+    ) -> Result<bool> {
+        if self.is_ghosted(chat.id()).await {
+            log::debug!("Suppressed a chat action in {}", chat.id());
+            return Ok(false);
+        }
+
+        client.action(chat.pack()).oneshot(action).await?;
+
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Ghost {
+    fn name(&self) -> &'static str {
+        "ghosted chats"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::replace_id(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// The persisted ghosted chats.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The IDs of the ghosted chats.
+    chats: Vec<i64>,
+}
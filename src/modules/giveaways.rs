@@ -0,0 +1,139 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the giveaways module.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use grammers_client::types::Chat;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use tokio::sync::Mutex;
+
+/// The giveaways manager.
+#[derive(Clone)]
+pub struct Giveaways {
+    /// The running giveaways.
+    giveaways: Arc<Mutex<Vec<Giveaway>>>,
+}
+
+impl Giveaways {
+    /// Creates a new `Giveaways` instance.
+    pub fn new() -> Self {
+        Self {
+            giveaways: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Creates a giveaway, returning its ID.
+    pub async fn create(
+        &self,
+        chat: Chat,
+        prize: String,
+        winners: usize,
+        required_channel: Option<Chat>,
+        ends_at: DateTime<Utc>,
+    ) -> i32 {
+        let mut giveaways = self.giveaways.lock().await;
+        let id = giveaways.last().map(|g| g.id).unwrap_or(0) + 1;
+
+        giveaways.push(Giveaway {
+            id,
+            chat,
+            message_id: None,
+            prize,
+            winners,
+            required_channel,
+            ends_at,
+            entrants: Vec::new(),
+        });
+
+        id
+    }
+
+    /// Runs a closure with the giveaway with the given ID.
+    pub async fn with<R>(&self, id: i32, f: impl FnOnce(&mut Giveaway) -> R) -> Option<R> {
+        let mut giveaways = self.giveaways.lock().await;
+
+        giveaways.iter_mut().find(|g| g.id == id).map(f)
+    }
+
+    /// Ends a giveaway, drawing its winners.
+    pub async fn draw(&self, id: i32) -> Option<Draw> {
+        let mut giveaways = self.giveaways.lock().await;
+        let index = giveaways.iter().position(|g| g.id == id)?;
+        let giveaway = giveaways.remove(index);
+
+        // The entrants are sorted before shuffling, so anyone can reproduce
+        // the draw from the published seed.
+        let seed = rand::random::<u64>();
+        let mut entrants = giveaway.entrants.clone();
+        entrants.sort_by_key(|e| e.id);
+        entrants.shuffle(&mut StdRng::seed_from_u64(seed));
+        entrants.truncate(giveaway.winners);
+
+        Some(Draw {
+            giveaway,
+            winners: entrants,
+            seed,
+        })
+    }
+}
+
+/// A giveaway.
+pub struct Giveaway {
+    /// The ID of the giveaway.
+    pub id: i32,
+    /// The chat the giveaway runs in.
+    pub chat: Chat,
+    /// The message with the join button.
+    pub message_id: Option<i32>,
+    /// The prize.
+    pub prize: String,
+    /// The number of winners.
+    pub winners: usize,
+    /// The channel the entrants must be members of, if any.
+    pub required_channel: Option<Chat>,
+    /// When the winners are drawn.
+    pub ends_at: DateTime<Utc>,
+    /// The entrants, in joining order.
+    pub entrants: Vec<Entrant>,
+}
+
+impl Giveaway {
+    /// Adds an entrant.
+    ///
+    /// Returns `false` if they had already joined.
+    pub fn join(&mut self, entrant: Entrant) -> bool {
+        if self.entrants.iter().any(|e| e.id == entrant.id) {
+            return false;
+        }
+
+        self.entrants.push(entrant);
+        true
+    }
+}
+
+/// An entrant of a giveaway.
+#[derive(Clone)]
+pub struct Entrant {
+    /// The user ID.
+    pub id: i64,
+    /// The name of the user.
+    pub name: String,
+}
+
+/// The result of a giveaway.
+pub struct Draw {
+    /// The ended giveaway.
+    pub giveaway: Giveaway,
+    /// The winners.
+    pub winners: Vec<Entrant>,
+    /// The seed the entrants were shuffled with.
+    pub seed: u64,
+}
@@ -0,0 +1,226 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chat history browser module.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use chrono::Local;
+use ferogram::Result;
+use grammers_client::{
+    button::{self, Inline},
+    types::Chat,
+    Client,
+};
+use maplit::hashmap;
+use tokio::sync::Mutex;
+
+use super::i18n::I18n;
+use crate::utils::{escape_html, message_link, pagination_buttons};
+
+/// How many messages a page has.
+const PAGE_SIZE: usize = 10;
+
+/// How many browsing sessions are kept, the oldest being dropped.
+const MAX_SESSIONS: usize = 20;
+
+/// The maximum length of a message preview.
+const PREVIEW_LENGTH: usize = 80;
+
+/// The jumps back in time offered, in days, with their labels.
+const JUMPS: [(i64, &str); 3] = [(1, "⏪ 1d"), (7, "⏪ 7d"), (30, "⏪ 30d")];
+
+/// The chat history browser.
+#[derive(Clone)]
+pub struct History {
+    /// The browsing sessions, the newest last.
+    sessions: Arc<Mutex<VecDeque<Session>>>,
+    /// The last session ID given.
+    last_id: Arc<AtomicU32>,
+}
+
+/// A chat being browsed.
+struct Session {
+    /// The session ID.
+    id: u32,
+    /// The chat.
+    chat: Chat,
+    /// Where each page seen starts.
+    pages: Vec<Anchor>,
+}
+
+/// Where a page starts, its messages being the ones before it.
+#[derive(Clone, Copy)]
+enum Anchor {
+    /// Before a message, `0` being the latest.
+    Message(i32),
+    /// Before a date, as a timestamp.
+    Date(i32),
+}
+
+impl History {
+    /// Creates a new `History` instance.
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(VecDeque::new())),
+            last_id: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Starts browsing a chat, from the latest messages or from before a date.
+    ///
+    /// Returns the session ID.
+    pub async fn open(&self, chat: Chat, before: Option<i32>) -> u32 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut sessions = self.sessions.lock().await;
+        if sessions.len() >= MAX_SESSIONS {
+            sessions.pop_front();
+        }
+        sessions.push_back(Session {
+            id,
+            chat,
+            pages: vec![before.map_or(Anchor::Message(0), Anchor::Date)],
+        });
+
+        id
+    }
+
+    /// Restarts a session from before a date, `None` going back to the latest messages.
+    ///
+    /// Returns `false` if the session is gone.
+    pub async fn jump(&self, id: u32, before: Option<i32>) -> bool {
+        let mut sessions = self.sessions.lock().await;
+        let Some(session) = sessions.iter_mut().find(|s| s.id == id) else {
+            return false;
+        };
+
+        session.pages = vec![before.map_or(Anchor::Message(0), Anchor::Date)];
+
+        true
+    }
+
+    /// Renders a page of a session, the messages from the oldest to the newest.
+    ///
+    /// Returns `None` if the session is gone or the page was not reached yet.
+    pub async fn render(
+        &self,
+        client: &Client,
+        id: u32,
+        page: usize,
+        i18n: &I18n,
+    ) -> Result<Option<(String, Vec<Vec<Inline>>)>> {
+        let (chat, anchor) = {
+            let sessions = self.sessions.lock().await;
+            let Some(session) = sessions.iter().find(|s| s.id == id) else {
+                return Ok(None);
+            };
+            let Some(anchor) = session.pages.get(page) else {
+                return Ok(None);
+            };
+
+            (session.chat.clone(), *anchor)
+        };
+
+        let mut iter = client.iter_messages(chat.pack()).limit(PAGE_SIZE + 1);
+        iter = match anchor {
+            Anchor::Message(message_id) => iter.offset_id(message_id),
+            Anchor::Date(date) => iter.max_date(date),
+        };
+
+        let mut messages = Vec::with_capacity(PAGE_SIZE + 1);
+        while let Some(message) = iter.next().await? {
+            messages.push(message);
+        }
+
+        let has_more = messages.len() > PAGE_SIZE;
+        messages.truncate(PAGE_SIZE);
+
+        // Remembers where the next page starts.
+        if let Some(last) = messages.last().filter(|_| has_more) {
+            let mut sessions = self.sessions.lock().await;
+            if let Some(session) = sessions.iter_mut().find(|s| s.id == id) {
+                session.pages.truncate(page + 1);
+                session.pages.push(Anchor::Message(last.id()));
+            }
+        }
+
+        let mut text = i18n.translate_with_args(
+            "browse_header",
+            hashmap! { "chat" => escape_html(chat.name()) },
+        );
+        if messages.is_empty() {
+            text += &i18n.translate("browse_empty");
+        }
+
+        for message in messages.iter().rev() {
+            let date = message
+                .date()
+                .with_timezone(&Local)
+                .format("%d/%m/%Y %H:%M")
+                .to_string();
+            let date = match message_link(&chat, message.id()) {
+                Some(link) => format!("<a href=\"{0}\">{1}</a>", link, date),
+                None => date,
+            };
+
+            let sender = message
+                .sender()
+                .map(|sender| sender.name().to_string())
+                .unwrap_or_default();
+            let preview = if message.text().is_empty() && message.media().is_some() {
+                i18n.translate("browse_media")
+            } else {
+                let text = message.text().replace('\n', " ");
+                if text.chars().count() > PREVIEW_LENGTH {
+                    format!(
+                        "{}…",
+                        escape_html(&text.chars().take(PREVIEW_LENGTH).collect::<String>())
+                    )
+                } else {
+                    escape_html(&text)
+                }
+            };
+
+            text += &format!(
+                "\n\n{0} — <b>{1}</b>\n{2}",
+                date,
+                escape_html(&sender),
+                preview
+            );
+        }
+
+        let pages = if has_more { page + 2 } else { page + 1 };
+        let mut buttons = vec![pagination_buttons(
+            &format!("browse {} page", id),
+            page,
+            pages,
+        )];
+
+        let mut jumps = Vec::with_capacity(JUMPS.len() + 1);
+        if let Some(oldest) = messages.last() {
+            for (days, label) in JUMPS {
+                let before = oldest.date().timestamp() - days * 24 * 60 * 60;
+                jumps.push(button::inline(
+                    label,
+                    format!("browse {0} jump {1}", id, before),
+                ));
+            }
+        }
+        jumps.push(button::inline("⏭", format!("browse {} latest", id)));
+        buttons.push(jumps);
+
+        Ok(Some((text, buttons)))
+    }
+}
@@ -8,20 +8,65 @@
 
 //! This module contains the internationalization module.
 
-use std::{collections::HashMap, fs, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
-use serde_json::Value;
-use tokio::sync::Mutex;
+use async_trait::async_trait;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use include_dir::{include_dir, Dir};
+use serde::{Deserialize, Serialize};
+use unic_langid::LanguageIdentifier;
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
 
 const PATH: &str = "./assets/locales/";
 
+/// The extension of the locale files.
+const EXTENSION: &str = "ftl";
+
+/// The locale files built into the binary, used for the ones missing on disk.
+static EMBEDDED: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/assets/locales");
+
+/// A locale, its file layered with its overrides.
+type Bundle = FluentBundle<FluentResource>;
+
 /// Internationalization module.
+///
+/// The locales are Fluent files, so the translations can have plural rules and
+/// selectors, such as `{ $count -> [one] ... *[other] ... }`.
 #[derive(Clone)]
 pub struct I18n {
-    current_locale: Arc<Mutex<String>>,
+    current_locale: Arc<RwLock<String>>,
     default_locale: String,
 
-    locales: HashMap<String, Value>,
+    /// The content of the locale files, replaced when reloaded.
+    sources: Arc<RwLock<HashMap<String, String>>>,
+    /// The locales, rebuilt when their overrides change.
+    bundles: Arc<RwLock<HashMap<String, Arc<Bundle>>>>,
+
+    /// The persisted overrides of the translations and locales of the chats.
+    storage: Storage<Data>,
+    /// The overridden translations of each locale, layered on top of the files.
+    overrides: Arc<RwLock<HashMap<String, HashMap<String, String>>>>,
+    /// The locale chosen by each chat or user, the current one if unset.
+    chat_locales: Arc<RwLock<HashMap<i64, String>>>,
+}
+
+/// The persisted i18n data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The overridden translations, by locale and key.
+    locales: HashMap<String, HashMap<String, String>>,
+    /// The locale chosen by each chat or user.
+    #[serde(default)]
+    chats: HashMap<i64, String>,
 }
 
 impl I18n {
@@ -30,61 +75,228 @@ impl I18n {
         let default_locale = default_locale.into();
 
         Self {
-            current_locale: Arc::new(Mutex::new(default_locale.clone())),
+            current_locale: Arc::new(RwLock::new(default_locale.clone())),
             default_locale,
 
-            locales: HashMap::new(),
+            sources: Arc::new(RwLock::new(HashMap::new())),
+            bundles: Arc::new(RwLock::new(HashMap::new())),
+
+            storage: Storage::open("i18n"),
+            overrides: Arc::new(RwLock::new(HashMap::new())),
+            chat_locales: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
     /// Loads the locales.
-    pub fn load(&mut self) {
-        let locales = fs::read_dir(PATH)
-            .expect("Failed to read locales directory.")
-            .map(|f| {
-                f.expect("Failed to read file.")
-                    .file_name()
-                    .to_str()
-                    .expect("Failed to convert file name.")
-                    .split_once(".")
-                    .expect("Failed to split file name.")
-                    .0
-                    .to_owned()
+    ///
+    /// The embedded locales are used if the locale files can not be read, so a
+    /// fresh deployment still has its translations.
+    pub fn load(&self) {
+        // The overrides saved before the Fluent files used `${arg}`.
+        let mut overrides = self.storage.read_now(|data| data.locales.clone());
+        for value in overrides.values_mut().flat_map(|o| o.values_mut()) {
+            *value = legacy_pattern(value);
+        }
+        *self.overrides.write().expect("failed to lock overrides") = overrides;
+
+        let chat_locales = self.storage.read_now(|data| data.chats.clone());
+        *self
+            .chat_locales
+            .write()
+            .expect("failed to lock chat locales") = chat_locales;
+
+        let sources = read_sources().unwrap_or_else(|e| {
+            log::warn!("Failed to read the locales, using the embedded ones: {}", e);
+            HashMap::new()
+        });
+        self.replace_sources(sources);
+    }
+
+    /// Reloads the locale files, keeping the current locales if any of them
+    /// fails to read or parse, or if the locales directory is missing.
+    ///
+    /// Returns the locales loaded.
+    pub fn reload(&self) -> std::result::Result<Vec<String>, String> {
+        if Path::new(PATH).is_dir() {
+            let sources = read_sources()?;
+            validate(&sources)?;
+            self.replace_sources(sources);
+        } else {
+            log::warn!("The locales directory is missing, keeping the loaded locales");
+        }
+
+        let mut locales = self.locales();
+        locales.sort();
+
+        Ok(locales)
+    }
+
+    /// Replaces the locale files and rebuilds every locale.
+    ///
+    /// The embedded locales fill the ones missing from the files.
+    fn replace_sources(&self, files: HashMap<String, String>) {
+        let mut sources = embedded_sources();
+        for locale in sources.keys().filter(|locale| !files.contains_key(*locale)) {
+            log::warn!(
+                "The {} locale file is missing, using the embedded one",
+                locale
+            );
+        }
+        sources.extend(files);
+        *self.sources.write().expect("failed to lock sources") = sources;
+
+        let bundles = self
+            .locales()
+            .into_iter()
+            .map(|locale| {
+                let bundle = Arc::new(self.build(&locale));
+                (locale, bundle)
             })
-            .collect::<Vec<String>>();
+            .collect();
+        *self.bundles.write().expect("failed to lock bundles") = bundles;
+    }
+
+    /// Builds a locale from its file and its overrides.
+    ///
+    /// The messages that do not parse are left out and logged.
+    fn build(&self, locale: &str) -> Bundle {
+        let language = locale.parse::<LanguageIdentifier>().unwrap_or_default();
+        let mut bundle = FluentBundle::new_concurrent(vec![language]);
+        // The translations are HTML, so no bidi marks around the arguments.
+        bundle.set_use_isolating(false);
+
+        let source = self
+            .sources
+            .read()
+            .expect("failed to lock sources")
+            .get(locale)
+            .cloned()
+            .unwrap_or_default();
+        let resource = FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+            log::error!("Failed to parse locale {}: {:?}", locale, errors);
+            resource
+        });
+        if let Err(errors) = bundle.add_resource(resource) {
+            log::error!("Failed to load locale {}: {:?}", locale, errors);
+        }
 
-        for locale in locales.into_iter() {
-            let path = format!("{0}/{1}.json", PATH, locale);
-            let content = fs::read_to_string(&path).expect("Failed to read file.");
-            let object = serde_json::from_str::<Value>(&content).expect("Failed to parse JSON.");
-            self.locales.insert(locale, object);
+        let overrides = self.overrides.read().expect("failed to lock overrides");
+        for (key, value) in overrides.get(locale).into_iter().flatten() {
+            match FluentResource::try_new(message_source(key, value)) {
+                Ok(resource) => bundle.add_resource_overriding(resource),
+                Err(_) => log::error!("Ignoring the invalid override of {0} in {1}", key, locale),
+            }
         }
+
+        bundle
     }
 
-    #[allow(dead_code)]
-    /// Reloads the locales.
-    pub fn reload(&mut self) {
-        self.locales.clear();
-        self.load();
+    /// Rebuilds a locale after its overrides changed.
+    fn rebuild(&self, locale: &str) {
+        if !self.has_locale(locale) {
+            return;
+        }
+
+        let bundle = Arc::new(self.build(locale));
+        self.bundles
+            .write()
+            .expect("failed to lock bundles")
+            .insert(locale.to_string(), bundle);
+    }
+
+    /// Checks that every locale file parses, returning the locales.
+    pub fn check_files() -> std::result::Result<Vec<String>, String> {
+        let sources = read_sources()?;
+        validate(&sources)?;
+
+        let mut locales = sources
+            .keys()
+            .map(|locale| format!("{0}.{1}", locale, EXTENSION))
+            .collect::<Vec<_>>();
+        locales.sort();
+
+        Ok(locales)
     }
 
-    #[allow(dead_code)]
     /// Gets the current locale.
     pub fn locale(&self) -> String {
-        self.current_locale.try_lock().unwrap().clone()
+        self.current_locale
+            .read()
+            .expect("failed to lock current locale")
+            .clone()
+    }
+
+    /// Gets the locale of a chat or user, the current one if it chose none.
+    pub fn locale_for(&self, chat_id: i64) -> String {
+        self.chat_locales
+            .read()
+            .expect("failed to lock chat locales")
+            .get(&chat_id)
+            .filter(|locale| self.has_locale(locale))
+            .cloned()
+            .unwrap_or_else(|| self.locale())
+    }
+
+    /// Sets the locale of a chat or user, `None` going back to the current one.
+    pub async fn set_locale_for(&self, chat_id: i64, locale: Option<String>) {
+        self.storage
+            .write(|data| match &locale {
+                Some(locale) => {
+                    data.chats.insert(chat_id, locale.clone());
+                }
+                None => {
+                    data.chats.remove(&chat_id);
+                }
+            })
+            .await;
+
+        let mut chat_locales = self
+            .chat_locales
+            .write()
+            .expect("failed to lock chat locales");
+        match locale {
+            Some(locale) => chat_locales.insert(chat_id, locale),
+            None => chat_locales.remove(&chat_id),
+        };
+    }
+
+    /// Checks if a chat or user chose a locale.
+    pub fn has_locale_for(&self, chat_id: i64) -> bool {
+        self.chat_locales
+            .read()
+            .expect("failed to lock chat locales")
+            .contains_key(&chat_id)
+    }
+
+    /// Gets the default locale, the one the others fall back to.
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
     }
 
-    #[allow(dead_code)]
     /// Gets the avaiable locales.
     pub fn locales(&self) -> Vec<String> {
-        self.locales.keys().cloned().collect()
+        self.sources
+            .read()
+            .expect("failed to lock sources")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Checks if a locale is available.
+    fn has_locale(&self, locale: &str) -> bool {
+        self.sources
+            .read()
+            .expect("failed to lock sources")
+            .contains_key(locale)
     }
 
     /// Sets the current locale.
     pub fn set_locale(&self, locale: impl Into<String>) {
-        let mut current_locale = self.current_locale.try_lock().unwrap();
-
-        *current_locale = locale.into();
+        *self
+            .current_locale
+            .write()
+            .expect("failed to lock current locale") = locale.into();
     }
 
     #[allow(dead_code)]
@@ -95,9 +307,7 @@ impl I18n {
 
     /// Translates a key.
     pub fn translate(&self, key: impl Into<String>) -> String {
-        let current_locale = self.current_locale.try_lock().unwrap();
-
-        self.translate_from_locale(key, current_locale.to_string())
+        self.translate_from_locale(key, self.locale())
     }
 
     /// Translates a key with arguments.
@@ -106,9 +316,22 @@ impl I18n {
         key: impl Into<String>,
         args: HashMap<&str, impl Into<String>>,
     ) -> String {
-        let current_locale = self.current_locale.try_lock().unwrap();
+        self.translate_from_locale_with_args(key, self.locale(), args)
+    }
 
-        self.translate_from_locale_with_args(key, current_locale.to_string(), args)
+    /// Translates a key in the locale of a chat or user.
+    pub fn translate_for(&self, chat_id: i64, key: impl Into<String>) -> String {
+        self.translate_from_locale(key, self.locale_for(chat_id))
+    }
+
+    /// Translates a key in the locale of a chat or user, with arguments.
+    pub fn translate_for_with_args(
+        &self,
+        chat_id: i64,
+        key: impl Into<String>,
+        args: HashMap<&str, impl Into<String>>,
+    ) -> String {
+        self.translate_from_locale_with_args(key, self.locale_for(chat_id), args)
     }
 
     /// Translates a key from a specific locale.
@@ -117,39 +340,353 @@ impl I18n {
         key: impl Into<String>,
         locale: impl Into<String>,
     ) -> String {
-        let key = key.into();
-        let locale = locale.into();
-
-        let object = self.locales.get(&locale).map_or_else(
-            || {
-                self.locales
-                    .get(&self.default_locale)
-                    .expect("Default locale not found.")
-            },
-            |v| v,
-        );
-        let value = object.get(&key).map_or("KEY_NOT_FOUND", |v| {
-            v.as_str().expect("Failed to convert value.")
-        });
-
-        value.to_string()
+        self.format(&key.into(), &locale.into(), None)
     }
 
     /// Translates a key from a specific locale with arguments.
+    ///
+    /// The integer arguments are passed as numbers, so they select the plural
+    /// forms.
     pub fn translate_from_locale_with_args(
         &self,
         key: impl Into<String>,
         locale: impl Into<String>,
         args: HashMap<&str, impl Into<String>>,
     ) -> String {
-        let mut result = self.translate_from_locale(key, locale);
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args.into_iter() {
+            let value = value.into();
+            let value = match value.parse::<i64>() {
+                Ok(number) if is_integer(&value) => FluentValue::from(number),
+                _ => FluentValue::from(value),
+            };
 
-        for (key, value) in args.into_iter() {
-            result = result.replace(&format!("${{{}}}", key), &value.into());
+            fluent_args.set(name.to_string(), value);
         }
 
-        result
+        self.format(&key.into(), &locale.into(), Some(&fluent_args))
     }
+
+    /// Formats a message of a locale.
+    ///
+    /// A message missing in the locale is taken from the default one, and only
+    /// if missing there too is `KEY_NOT_FOUND` returned.
+    fn format(&self, key: &str, locale: &str, args: Option<&FluentArgs>) -> String {
+        let bundles = self.bundles.read().expect("failed to lock bundles");
+        let found = [locale, self.default_locale.as_str()]
+            .into_iter()
+            .filter_map(|locale| bundles.get(locale).map(|bundle| (locale, bundle)))
+            .find_map(|(locale, bundle)| {
+                let pattern = bundle.get_message(key)?.value()?;
+                Some((locale, bundle, pattern))
+            });
+        let Some((found_in, bundle, pattern)) = found else {
+            return "KEY_NOT_FOUND".to_string();
+        };
+        if found_in != locale {
+            log::debug!("Missing {0} in {1}, using {2}", key, locale, found_in);
+        }
+
+        let mut errors = Vec::new();
+        let value = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            log::debug!("Failed to format {0}: {1:?}", key, errors);
+        }
+
+        value.into_owned()
+    }
+
+    /// Compares the keys of each locale file with the ones of the default
+    /// locale, returning the locales with missing or extra keys.
+    ///
+    /// Returns `None` if the default locale has no file.
+    pub fn audit(&self) -> Option<Vec<Audit>> {
+        let sources = self.sources.read().expect("failed to lock sources");
+        let default_keys = keys(sources.get(&self.default_locale)?);
+
+        let mut audits = sources
+            .iter()
+            .filter(|(locale, _)| **locale != self.default_locale)
+            .map(|(locale, source)| {
+                let locale_keys = keys(source);
+
+                Audit {
+                    locale: locale.clone(),
+                    missing: default_keys
+                        .iter()
+                        .filter(|key| !locale_keys.contains(key))
+                        .map(|key| key.to_string())
+                        .collect(),
+                    extra: locale_keys
+                        .iter()
+                        .filter(|key| !default_keys.contains(key))
+                        .map(|key| key.to_string())
+                        .collect(),
+                }
+            })
+            .filter(|audit| !audit.missing.is_empty() || !audit.extra.is_empty())
+            .collect::<Vec<_>>();
+        audits.sort_by(|a, b| a.locale.cmp(&b.locale));
+
+        Some(audits)
+    }
+
+    /// Checks if a locale has a key in its file.
+    pub fn has_key(&self, locale: &str, key: &str) -> bool {
+        self.bundles
+            .read()
+            .expect("failed to lock bundles")
+            .get(locale)
+            .is_some_and(|bundle| bundle.has_message(key))
+    }
+
+    /// Overrides the translation of a key in a locale, written as a Fluent
+    /// pattern, such as `{ $count } mensagens`.
+    ///
+    /// Returns why the pattern is invalid, if it is.
+    pub async fn set_override(
+        &self,
+        locale: &str,
+        key: &str,
+        value: impl Into<String>,
+    ) -> Result<(), String> {
+        let value = value.into();
+        if let Err((_, errors)) = FluentResource::try_new(message_source(key, &value)) {
+            return Err(format!("{:?}", errors[0].kind));
+        }
+
+        self.storage
+            .write(|data| {
+                data.locales
+                    .entry(locale.to_string())
+                    .or_default()
+                    .insert(key.to_string(), value.clone());
+            })
+            .await;
+        self.overrides
+            .write()
+            .expect("failed to lock overrides")
+            .entry(locale.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+        self.rebuild(locale);
+
+        Ok(())
+    }
+
+    /// Removes the overrides of a key in every locale.
+    ///
+    /// Returns `false` if the key was not overridden.
+    pub async fn reset_override(&self, key: &str) -> bool {
+        let removed = self
+            .storage
+            .write(|data| {
+                let mut removed = false;
+                for overrides in data.locales.values_mut() {
+                    removed |= overrides.remove(key).is_some();
+                }
+                data.locales.retain(|_, overrides| !overrides.is_empty());
+
+                removed
+            })
+            .await;
+
+        let mut changed = Vec::new();
+        {
+            let mut overrides = self.overrides.write().expect("failed to lock overrides");
+            for (locale, keys) in overrides.iter_mut() {
+                if keys.remove(key).is_some() {
+                    changed.push(locale.clone());
+                }
+            }
+            overrides.retain(|_, locale| !locale.is_empty());
+        }
+        for locale in changed {
+            self.rebuild(&locale);
+        }
+
+        removed
+    }
+
+    /// Returns a locale file with the overridden messages replaced.
+    pub fn export(&self, locale: &str) -> Option<String> {
+        let sources = self.sources.read().expect("failed to lock sources");
+        let source = sources.get(locale)?;
+        let overrides = self.overrides.read().expect("failed to lock overrides");
+        let Some(overrides) = overrides.get(locale) else {
+            return Some(source.clone());
+        };
+
+        let mut output = String::with_capacity(source.len());
+        let mut blank_lines = 0;
+        let mut replaced = false;
+        for line in source.lines() {
+            if line.trim().is_empty() {
+                blank_lines += 1;
+                continue;
+            }
+
+            // A line that is not indented starts a message or a comment, the
+            // others continue the previous one.
+            if !line.starts_with(char::is_whitespace) {
+                replaced = false;
+
+                let key = line.split('=').next().unwrap_or_default().trim();
+                if let Some(value) = overrides.get(key) {
+                    output.push_str(&"\n".repeat(blank_lines));
+                    output.push_str(&message_source(key, value));
+                    output.push('\n');
+                    blank_lines = 0;
+                    replaced = true;
+                    continue;
+                }
+            }
+            // The blank lines inside a replaced message go with it.
+            if replaced {
+                blank_lines = 0;
+                continue;
+            }
+
+            output.push_str(&"\n".repeat(blank_lines));
+            output.push_str(line);
+            output.push('\n');
+            blank_lines = 0;
+        }
+
+        Some(output)
+    }
+}
+
+#[async_trait]
+impl ChatMigration for I18n {
+    fn name(&self) -> &'static str {
+        "chat locale"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        let moved = self
+            .storage
+            .write(|data| migrations::move_key(&mut data.chats, from, to))
+            .await;
+
+        let mut chat_locales = self
+            .chat_locales
+            .write()
+            .expect("failed to lock chat locales");
+        migrations::move_key(&mut chat_locales, from, to);
+
+        moved
+    }
+}
+
+/// The keys a locale file misses or has in excess, compared to the default one.
+pub struct Audit {
+    /// The locale.
+    pub locale: String,
+    /// The keys of the default locale it misses, in the order of its file.
+    pub missing: Vec<String>,
+    /// The keys the default locale does not have, in the order of the file.
+    pub extra: Vec<String>,
+}
+
+/// Reads the locale files, by locale.
+fn read_sources() -> std::result::Result<HashMap<String, String>, String> {
+    let entries = fs::read_dir(PATH).map_err(|e| e.to_string())?;
+
+    let mut sources = HashMap::new();
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().is_none_or(|e| e != EXTENSION) {
+            continue;
+        }
+        let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("{0}: {1}", path.display(), e))?;
+        sources.insert(locale.to_string(), content);
+    }
+
+    Ok(sources)
+}
+
+/// Returns the locale files built into the binary, by locale.
+fn embedded_sources() -> HashMap<String, String> {
+    EMBEDDED
+        .files()
+        .filter(|file| file.path().extension().is_some_and(|e| e == EXTENSION))
+        .filter_map(|file| {
+            let locale = file.path().file_stem()?.to_str()?;
+
+            Some((locale.to_string(), file.contents_utf8()?.to_string()))
+        })
+        .collect()
+}
+
+/// Checks that there are locale files and that every one of them parses.
+fn validate(sources: &HashMap<String, String>) -> std::result::Result<(), String> {
+    if sources.is_empty() {
+        return Err("no locale found".to_string());
+    }
+
+    for (locale, source) in sources.iter() {
+        FluentResource::try_new(source.clone())
+            .map_err(|(_, errors)| format!("{0}.{1}: {2:?}", locale, EXTENSION, errors[0]))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the keys of the messages of a locale file, in order.
+fn keys(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim())
+        .collect()
+}
+
+/// Writes a message in the Fluent syntax, indenting the lines of its value.
+fn message_source(key: &str, value: &str) -> String {
+    format!("{0} = {1}", key, value.replace('\n', "\n    "))
+}
+
+/// Checks if an argument is written as an integer, so it is passed as a number
+/// without changing how it looks, unlike `007` or `+55`.
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+
+    !digits.is_empty()
+        && digits.len() <= 15
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// Turns the `${arg}` of a translation into the Fluent `{ $arg }`.
+fn legacy_pattern(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + length];
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            output.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+        output.push_str(&format!("{{ ${} }}", name));
+        rest = &rest[start + length + 1..];
+    }
+    output.push_str(rest);
+
+    output
 }
 
 /// Contextual use of i18n.
@@ -0,0 +1,153 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the inline bots proxy module.
+
+use std::{collections::HashMap, sync::Arc};
+
+use grammers_client::types::Chat;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::storage::Storage;
+
+/// The maximum number of queries kept waiting for a selection.
+const CACHE_SIZE: usize = 50;
+
+/// The inline bots proxy.
+#[derive(Clone)]
+pub struct InlineProxy {
+    /// The named query shortcuts.
+    storage: Storage<Data>,
+    /// The queries waiting for a result to be selected.
+    pending: Arc<Mutex<Vec<Pending>>>,
+}
+
+impl InlineProxy {
+    /// Creates a new `InlineProxy` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("inline"),
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Saves a shortcut to a bot, with an optional query prefix.
+    pub async fn save(&self, name: &str, bot: &str, prefix: &str) {
+        self.storage
+            .write(|data| {
+                data.shortcuts.insert(
+                    name.to_lowercase(),
+                    Shortcut {
+                        bot: bot.trim_start_matches('@').to_string(),
+                        prefix: prefix.to_string(),
+                    },
+                )
+            })
+            .await;
+    }
+
+    /// Deletes a shortcut.
+    ///
+    /// Returns `false` if the shortcut does not exist.
+    pub async fn delete(&self, name: &str) -> bool {
+        self.storage
+            .write(|data| data.shortcuts.remove(&name.to_lowercase()).is_some())
+            .await
+    }
+
+    /// Returns the saved shortcuts, sorted by name.
+    pub async fn shortcuts(&self) -> Vec<(String, Shortcut)> {
+        let mut shortcuts = self
+            .storage
+            .read(|data| {
+                data.shortcuts
+                    .iter()
+                    .map(|(name, shortcut)| (name.clone(), shortcut.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        shortcuts.sort_by(|a, b| a.0.cmp(&b.0));
+
+        shortcuts
+    }
+
+    /// Resolves the target of a query, either a `@username` or a shortcut.
+    ///
+    /// Returns the bot username and the full query.
+    pub async fn resolve(&self, target: &str, query: &str) -> Option<(String, String)> {
+        if let Some(username) = target.strip_prefix('@') {
+            return Some((username.to_string(), query.to_string()));
+        }
+
+        self.storage
+            .read(|data| {
+                data.shortcuts.get(&target.to_lowercase()).map(|shortcut| {
+                    let query = format!("{} {}", shortcut.prefix, query);
+                    (shortcut.bot.clone(), query.trim().to_string())
+                })
+            })
+            .await
+    }
+
+    /// Keeps a query waiting for a result to be selected.
+    ///
+    /// Returns the ID of the pending query.
+    pub async fn wait_selection(&self, chat: Chat, bot: Chat, query: String) -> i32 {
+        let mut pending = self.pending.lock().await;
+        let id = pending.last().map(|p| p.id).unwrap_or(0) + 1;
+
+        pending.push(Pending {
+            id,
+            chat,
+            bot,
+            query,
+        });
+        if pending.len() > CACHE_SIZE {
+            pending.remove(0);
+        }
+
+        id
+    }
+
+    /// Takes the pending query with the given ID.
+    pub async fn take_selection(&self, id: i32) -> Option<Pending> {
+        let mut pending = self.pending.lock().await;
+        let index = pending.iter().position(|p| p.id == id)?;
+
+        Some(pending.remove(index))
+    }
+}
+
+/// A named query shortcut.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Shortcut {
+    /// The bot username, without the `@`.
+    pub bot: String,
+    /// The text prepended to the queries.
+    pub prefix: String,
+}
+
+/// A query waiting for a result to be selected.
+pub struct Pending {
+    /// The pending query ID.
+    pub id: i32,
+    /// The chat the result will be sent to.
+    pub chat: Chat,
+    /// The queried bot.
+    pub bot: Chat,
+    /// The query text.
+    pub query: String,
+}
+
+/// The persisted shortcuts.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The shortcuts by name.
+    shortcuts: HashMap<String, Shortcut>,
+}
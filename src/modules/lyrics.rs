@@ -0,0 +1,190 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the lyrics module.
+
+use std::sync::Arc;
+
+use ferogram::Result;
+use grammers_client::button::Inline;
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use super::i18n::I18n;
+use crate::utils::{escape_html, pagination_buttons};
+
+/// The URL of the LRCLIB API.
+const LRCLIB_URL: &str = "https://lrclib.net/api/search";
+/// The URL of the lyrics.ovh API.
+const LYRICS_OVH_URL: &str = "https://api.lyrics.ovh/v1";
+
+/// The maximum number of cached lyrics.
+const CACHE_SIZE: usize = 50;
+
+/// The lyrics provider.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// The LRCLIB provider.
+    #[default]
+    Lrclib,
+    /// The lyrics.ovh provider.
+    LyricsOvh,
+}
+
+/// The lyrics fetcher.
+#[derive(Clone)]
+pub struct Lyrics {
+    /// The provider to fetch the lyrics from.
+    provider: Provider,
+    /// The maximum length of each page.
+    page_length: usize,
+    /// The fetched lyrics, kept so their pages can be browsed.
+    cache: Arc<Mutex<Vec<Song>>>,
+}
+
+impl Lyrics {
+    /// Creates a new `Lyrics` instance.
+    pub fn new(provider: Provider, page_length: usize) -> Self {
+        Self {
+            provider,
+            page_length,
+            cache: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Fetches the lyrics of a song.
+    ///
+    /// Returns the ID of the cached song.
+    pub async fn fetch(&self, artist: &str, title: &str) -> Result<i32> {
+        let lyrics = match self.provider {
+            Provider::Lrclib => fetch_lrclib(artist, title).await?,
+            Provider::LyricsOvh => fetch_lyrics_ovh(artist, title).await?,
+        };
+        let language = whatlang::detect(&lyrics)
+            .map(|info| info.lang().eng_name().to_string())
+            .unwrap_or_else(|| "?".to_string());
+
+        let mut cache = self.cache.lock().await;
+        let id = cache.last().map(|s| s.id).unwrap_or(0) + 1;
+
+        cache.push(Song {
+            id,
+            artist: artist.to_string(),
+            title: title.to_string(),
+            language,
+            pages: paginate(&lyrics, self.page_length),
+        });
+        if cache.len() > CACHE_SIZE {
+            cache.remove(0);
+        }
+
+        Ok(id)
+    }
+
+    /// Returns the cached song with the given ID.
+    pub async fn get(&self, id: i32) -> Option<Song> {
+        self.cache.lock().await.iter().find(|s| s.id == id).cloned()
+    }
+}
+
+/// A song with its lyrics split into pages.
+#[derive(Clone)]
+pub struct Song {
+    /// The song ID.
+    pub id: i32,
+    /// The song artist.
+    pub artist: String,
+    /// The song title.
+    pub title: String,
+    /// The detected language of the lyrics.
+    pub language: String,
+    /// The pages of the lyrics.
+    pub pages: Vec<String>,
+}
+
+impl Song {
+    /// Renders a page of the lyrics.
+    pub fn render_page(&self, i18n: &I18n, page: usize) -> (String, Vec<Vec<Inline>>) {
+        let pages = self.pages.len().max(1);
+        let page = page.min(pages - 1);
+
+        let text = i18n.translate_with_args(
+            "lyrics_text",
+            hashmap! {
+                "artist" => escape_html(&self.artist),
+                "title" => escape_html(&self.title),
+                "language" => self.language.clone(),
+                "lyrics" => escape_html(self.pages.get(page).map_or("", |p| p.as_str())),
+            },
+        );
+        let buttons = pagination_buttons(&format!("lyrics {}", self.id), page, pages);
+
+        (text, vec![buttons])
+    }
+}
+
+/// Splits the lyrics into pages, breaking on lines.
+fn paginate(lyrics: &str, page_length: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+
+    for line in lyrics.lines() {
+        if !page.is_empty() && page.len() + line.len() + 1 > page_length {
+            pages.push(page.trim_end().to_string());
+            page.clear();
+        }
+
+        page.push_str(line);
+        page.push('\n');
+    }
+
+    if !page.trim().is_empty() {
+        pages.push(page.trim_end().to_string());
+    }
+
+    pages
+}
+
+/// Fetches the lyrics from LRCLIB.
+async fn fetch_lrclib(artist: &str, title: &str) -> Result<String> {
+    let json = reqwest::Client::new()
+        .get(LRCLIB_URL)
+        .query(&[("artist_name", artist), ("track_name", title)])
+        .send()
+        .await?
+        .json::<Value>()
+        .await?;
+
+    json.as_array()
+        .and_then(|results| {
+            results
+                .iter()
+                .find_map(|result| result["plainLyrics"].as_str())
+        })
+        .map(|lyrics| lyrics.to_string())
+        .ok_or_else(|| "Lyrics not found".into())
+}
+
+/// Fetches the lyrics from lyrics.ovh.
+async fn fetch_lyrics_ovh(artist: &str, title: &str) -> Result<String> {
+    // The artist and title are path segments, so they are percent-encoded.
+    let mut url = reqwest::Url::parse(LYRICS_OVH_URL)?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid lyrics.ovh URL")?
+        .push(artist)
+        .push(title);
+    let json = reqwest::get(url).await?.json::<Value>().await?;
+
+    json["lyrics"]
+        .as_str()
+        .map(|lyrics| lyrics.replace("\r\n", "\n"))
+        .ok_or_else(|| "Lyrics not found".into())
+}
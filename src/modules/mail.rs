@@ -0,0 +1,317 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the email bridge module.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ferogram::Result;
+use grammers_client::InputMessage;
+use mailparse::{DispositionType, MailHeaderMap, ParsedMail};
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{i18n::I18n, shutdown::Shutdown, storage::Storage};
+use crate::{utils::escape_html, Priority, Sender};
+
+/// The maximum length of the body preview.
+const PREVIEW_LENGTH: usize = 300;
+/// The maximum number of emails kept to be read later.
+const CACHE_SIZE: usize = 100;
+
+/// The IMAP account settings.
+#[derive(Clone)]
+pub struct Account {
+    /// The IMAP server host.
+    pub host: String,
+    /// The IMAP server port.
+    pub port: u16,
+    /// The account username.
+    pub username: String,
+    /// The account password.
+    pub password: String,
+}
+
+/// The email bridge.
+#[derive(Clone)]
+pub struct Mail {
+    /// The IMAP account, if configured.
+    account: Option<Account>,
+    /// The watched mailboxes.
+    mailboxes: Vec<String>,
+    /// How often the mailboxes are polled.
+    poll_interval: Duration,
+    /// The maximum size of the forwarded attachments.
+    attachment_size_cap: usize,
+    /// The last seen UID of each mailbox.
+    storage: Storage<Data>,
+    /// The recently received emails, so they can be read in full.
+    cache: Arc<Mutex<Vec<Email>>>,
+}
+
+impl Mail {
+    /// Creates a new `Mail` instance.
+    pub fn new(
+        account: Option<Account>,
+        mailboxes: Vec<String>,
+        poll_interval: Duration,
+        attachment_size_cap: usize,
+    ) -> Self {
+        Self {
+            account,
+            mailboxes,
+            poll_interval,
+            attachment_size_cap,
+            storage: Storage::open("mail"),
+            cache: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Checks if an account is configured.
+    pub fn is_configured(&self) -> bool {
+        self.account.is_some()
+    }
+
+    /// Returns a recently received email.
+    pub async fn get(&self, id: i32) -> Option<Email> {
+        self.cache.lock().await.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// Polls the mailboxes, returning the new emails.
+    pub async fn poll(&self) -> Result<Vec<Email>> {
+        let Some(account) = self.account.clone() else {
+            return Ok(Vec::new());
+        };
+
+        let mut emails = Vec::new();
+
+        for mailbox in self.mailboxes.iter() {
+            let last_uid = self
+                .storage
+                .read(|data| data.last_uids.get(mailbox).copied())
+                .await;
+
+            let (uid, raw) = {
+                let mailbox = mailbox.clone();
+                let account = account.clone();
+
+                tokio::task::spawn_blocking(move || fetch_new(&account, &mailbox, last_uid))
+                    .await??
+            };
+
+            self.storage
+                .write(|data| data.last_uids.insert(mailbox.clone(), uid))
+                .await;
+
+            // The first poll only marks where the mailbox is.
+            if last_uid.is_none() {
+                continue;
+            }
+
+            for raw in raw {
+                match mailparse::parse_mail(&raw) {
+                    Ok(parsed) => emails.push(self.remember(mailbox, &parsed).await),
+                    Err(e) => log::error!("Failed to parse an email: {}", e),
+                }
+            }
+        }
+
+        Ok(emails)
+    }
+
+    /// Keeps a parsed email in the cache.
+    async fn remember(&self, mailbox: &str, parsed: &ParsedMail<'_>) -> Email {
+        let mut cache = self.cache.lock().await;
+        let id = cache.last().map(|e| e.id).unwrap_or(0) + 1;
+
+        let mut email = Email {
+            id,
+            mailbox: mailbox.to_string(),
+            from: parsed.headers.get_first_value("From").unwrap_or_default(),
+            subject: parsed
+                .headers
+                .get_first_value("Subject")
+                .unwrap_or_default(),
+            body: String::new(),
+            attachments: Vec::new(),
+        };
+        collect_parts(parsed, &mut email, self.attachment_size_cap);
+
+        cache.push(email.clone());
+        if cache.len() > CACHE_SIZE {
+            cache.remove(0);
+        }
+
+        email
+    }
+
+    /// Spawns the task that polls the mailboxes and forwards the new emails to the owner.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        if !self.is_configured() {
+            return;
+        }
+
+        Shutdown::shared().spawn(async move {
+            loop {
+                match self.poll().await {
+                    Ok(emails) => {
+                        for email in emails {
+                            let text = email.render_summary(&i18n);
+
+                            let mut result = tx
+                                .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                                .await;
+
+                            for (name, bytes) in email.attachments {
+                                if result.is_err() {
+                                    break;
+                                }
+
+                                // The files are sent after the interactive messages.
+                                result = tx
+                                    .send(
+                                        crate::Message::to_bot()
+                                            .with_priority(Priority::Low)
+                                            .notify_with_file(InputMessage::text(""), name, bytes),
+                                    )
+                                    .await;
+                            }
+
+                            if let Err(e) = result {
+                                log::error!("Failed to forward an email: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => log::error!("Failed to poll the mailboxes: {}", e),
+                }
+
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        });
+    }
+}
+
+/// A received email.
+#[derive(Clone)]
+pub struct Email {
+    /// The email ID, local to the bot.
+    pub id: i32,
+    /// The mailbox it was received in.
+    pub mailbox: String,
+    /// The sender.
+    pub from: String,
+    /// The subject.
+    pub subject: String,
+    /// The plain text body.
+    pub body: String,
+    /// The attachments under the size cap.
+    pub attachments: Vec<(String, Vec<u8>)>,
+}
+
+impl Email {
+    /// Renders the summary of the email.
+    pub fn render_summary(&self, i18n: &I18n) -> String {
+        let mut preview = self.body.split_whitespace().collect::<Vec<_>>().join(" ");
+        if preview.chars().count() > PREVIEW_LENGTH {
+            preview = preview.chars().take(PREVIEW_LENGTH).collect::<String>() + "…";
+        }
+
+        i18n.translate_with_args(
+            "mail_summary",
+            hashmap! {
+                "id" => self.id.to_string(),
+                "mailbox" => escape_html(&self.mailbox),
+                "from" => escape_html(&self.from),
+                "subject" => escape_html(&self.subject),
+                "preview" => escape_html(&preview),
+                "attachments" => self.attachments.len().to_string(),
+            },
+        )
+    }
+}
+
+/// The persisted bridge data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The last seen UID of each mailbox.
+    last_uids: HashMap<String, u32>,
+}
+
+/// Fetches the emails newer than the given UID.
+///
+/// Returns the greatest UID of the mailbox along with the raw emails.
+fn fetch_new(
+    account: &Account,
+    mailbox: &str,
+    last_uid: Option<u32>,
+) -> Result<(u32, Vec<Vec<u8>>)> {
+    let tls = native_tls::TlsConnector::builder().build()?;
+    let client = imap::connect((account.host.as_str(), account.port), &account.host, &tls)?;
+    let mut session = client
+        .login(&account.username, &account.password)
+        .map_err(|(e, _)| e)?;
+
+    session.select(mailbox)?;
+
+    let from = last_uid.map(|uid| uid + 1).unwrap_or(1);
+    let mut uids = session
+        .uid_search(format!("UID {}:*", from))?
+        .into_iter()
+        .filter(|uid| *uid >= from)
+        .collect::<Vec<_>>();
+    uids.sort_unstable();
+
+    let max_uid = uids.last().copied().or(last_uid).unwrap_or(0);
+    let mut raw = Vec::new();
+
+    if last_uid.is_some() && !uids.is_empty() {
+        let set = uids
+            .iter()
+            .map(|uid| uid.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        for fetch in session.uid_fetch(set, "RFC822")?.iter() {
+            if let Some(body) = fetch.body() {
+                raw.push(body.to_vec());
+            }
+        }
+    }
+
+    session.logout()?;
+
+    Ok((max_uid, raw))
+}
+
+/// Collects the text body and the small attachments of an email.
+fn collect_parts(part: &ParsedMail<'_>, email: &mut Email, size_cap: usize) {
+    let disposition = part.get_content_disposition();
+
+    if disposition.disposition == DispositionType::Attachment {
+        if let Ok(bytes) = part.get_body_raw() {
+            if bytes.len() <= size_cap {
+                let name = disposition
+                    .params
+                    .get("filename")
+                    .cloned()
+                    .unwrap_or_else(|| "attachment".to_string());
+
+                email.attachments.push((name, bytes));
+            }
+        }
+    } else if part.subparts.is_empty() {
+        if part.ctype.mimetype == "text/plain" && email.body.is_empty() {
+            email.body = part.get_body().unwrap_or_default();
+        }
+    } else {
+        for subpart in part.subparts.iter() {
+            collect_parts(subpart, email, size_cap);
+        }
+    }
+}
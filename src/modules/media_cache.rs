@@ -0,0 +1,129 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the media cache module.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use grammers_client::{
+    types::{Downloadable, Media},
+    Client, InvocationError,
+};
+
+/// The recently downloaded media, so the commands run on the same reply do
+/// not fetch its bytes again.
+#[derive(Clone)]
+pub struct MediaCache {
+    /// The cached buffers and their use order.
+    inner: Arc<Mutex<Inner>>,
+    /// In bytes, how much the buffers can take together.
+    max_size: usize,
+}
+
+/// The cached buffers and their use order.
+#[derive(Default)]
+struct Inner {
+    /// The buffers, by file ID.
+    buffers: HashMap<i64, Arc<Vec<u8>>>,
+    /// The file IDs, from the least to the most recently used.
+    order: VecDeque<i64>,
+    /// In bytes, how much the buffers take together.
+    size: usize,
+}
+
+impl Inner {
+    /// Marks a file as the most recently used.
+    fn touch(&mut self, file_id: i64) {
+        self.order.retain(|id| *id != file_id);
+        self.order.push_back(file_id);
+    }
+}
+
+impl MediaCache {
+    /// Creates a new `MediaCache` instance.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner::default())),
+            max_size,
+        }
+    }
+
+    /// Returns the bytes of a media, downloading them if they are not cached.
+    pub async fn download(
+        &self,
+        client: &Client,
+        media: &Media,
+    ) -> std::result::Result<Arc<Vec<u8>>, InvocationError> {
+        let file_id = file_id(media);
+
+        if let Some(bytes) = file_id.and_then(|id| self.get(id)) {
+            return Ok(bytes);
+        }
+
+        let mut bytes = Vec::new();
+        let mut download = client.iter_download(&Downloadable::Media(media.clone()));
+        while let Some(chunk) = download.next().await? {
+            bytes.extend(chunk);
+        }
+
+        let bytes = Arc::new(bytes);
+        if let Some(id) = file_id {
+            self.insert(id, Arc::clone(&bytes));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Returns the cached bytes of a file.
+    fn get(&self, file_id: i64) -> Option<Arc<Vec<u8>>> {
+        let mut inner = self.inner.lock().expect("failed to lock media cache");
+
+        let bytes = inner.buffers.get(&file_id).cloned()?;
+        inner.touch(file_id);
+
+        Some(bytes)
+    }
+
+    /// Caches the bytes of a file, evicting the least recently used ones to fit.
+    fn insert(&self, file_id: i64, bytes: Arc<Vec<u8>>) {
+        // A file bigger than the whole cache would only evict everything else.
+        if bytes.len() > self.max_size {
+            return;
+        }
+
+        let mut inner = self.inner.lock().expect("failed to lock media cache");
+
+        if let Some(previous) = inner.buffers.insert(file_id, Arc::clone(&bytes)) {
+            inner.size -= previous.len();
+        }
+        inner.size += bytes.len();
+        inner.touch(file_id);
+
+        while inner.size > self.max_size {
+            let Some(id) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = inner.buffers.remove(&id) {
+                inner.size -= evicted.len();
+            }
+        }
+    }
+}
+
+/// Returns the ID of the file of a media, which does not change between messages.
+fn file_id(media: &Media) -> Option<i64> {
+    match media {
+        Media::Photo(photo) => Some(photo.id()),
+        Media::Document(document) => Some(document.id()),
+        Media::Sticker(sticker) => Some(sticker.document.id()),
+        _ => None,
+    }
+}
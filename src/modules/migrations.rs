@@ -0,0 +1,97 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chat migrations module.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+
+/// A module whose persisted data is kept by chat ID.
+#[async_trait]
+pub trait ChatMigration: Send + Sync {
+    /// Returns the module name, used in the logs.
+    fn name(&self) -> &'static str;
+
+    /// Moves the data of a chat to its new ID, returning how many entries moved.
+    async fn migrate(&self, from: i64, to: i64) -> usize;
+}
+
+/// Moves the persisted data of the groups that became supergroups to their new IDs.
+#[derive(Clone)]
+pub struct Migrations {
+    /// The registered hooks.
+    hooks: Arc<Vec<Arc<dyn ChatMigration>>>,
+}
+
+impl Migrations {
+    /// Creates a new `Migrations` instance.
+    pub fn new() -> Self {
+        Self {
+            hooks: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Registers a hook.
+    pub fn hook(mut self, hook: impl ChatMigration + 'static) -> Self {
+        Arc::get_mut(&mut self.hooks)
+            .expect("Hooks must be registered before cloning the migrations")
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Moves the data of a chat to its new ID in every registered module.
+    ///
+    /// Running it twice for the same chats moves nothing the second time, so both
+    /// service messages of a migration can trigger it.
+    pub async fn run(&self, from: i64, to: i64) -> usize {
+        let mut total = 0;
+
+        for hook in self.hooks.iter() {
+            let moved = hook.migrate(from, to).await;
+            if moved > 0 {
+                log::info!(
+                    "Migrated {0} {1} entries from chat {2} to {3}",
+                    moved,
+                    hook.name(),
+                    from,
+                    to
+                );
+            }
+
+            total += moved;
+        }
+
+        total
+    }
+}
+
+/// Moves the value of a chat to its new ID, unless the new one already has a value.
+pub fn move_key<V>(map: &mut HashMap<i64, V>, from: i64, to: i64) -> usize {
+    match map.remove(&from) {
+        Some(value) => {
+            map.entry(to).or_insert(value);
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Replaces the ID of a chat in a list with its new one, without duplicates.
+pub fn replace_id(ids: &mut Vec<i64>, from: i64, to: i64) -> usize {
+    if !ids.contains(&from) {
+        return 0;
+    }
+
+    ids.retain(|id| *id != from);
+    if !ids.contains(&to) {
+        ids.push(to);
+    }
+
+    1
+}
@@ -8,5 +8,67 @@
 
 //! This module contains the modules setup.
 
+pub mod afk;
+pub mod alerts;
+pub mod auto_downloads;
+pub mod business;
+pub mod calculator;
+pub mod calendar;
+pub mod chat_settings;
+pub mod commands;
+pub mod contacts;
+pub mod currency;
+pub mod dates;
+pub mod digest;
+pub mod doctor;
+pub mod folders;
 pub mod games;
+pub mod generated;
+pub mod ghost;
+pub mod giveaways;
+pub mod history;
 pub mod i18n;
+pub mod inline;
+pub mod lyrics;
+pub mod mail;
+pub mod media_cache;
+pub mod migrations;
+pub mod mod_notes;
+pub mod notes;
+pub mod nsfw;
+pub mod panics;
+pub mod pdf;
+pub mod profile;
+pub mod purges;
+pub mod raids;
+pub mod receipts;
+pub mod reposts;
+pub mod reverse_search;
+pub mod rss;
+pub mod sandbox;
+pub mod scheduler;
+pub mod shutdown;
+pub mod signer;
+pub mod songs;
+pub mod storage;
+pub mod sudoers;
+pub mod supervisor;
+pub mod supporters;
+pub mod system_info;
+pub mod tags;
+pub mod templates;
+pub mod todo;
+pub mod tournaments;
+pub mod transcriber;
+pub mod translator;
+pub mod trivia;
+pub mod unread;
+pub mod updates;
+pub mod url_scanner;
+pub mod usage;
+pub mod usernames;
+pub mod virustotal;
+pub mod wayback;
+pub mod weather;
+pub mod webapp;
+pub mod ytdl;
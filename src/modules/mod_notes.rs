@@ -0,0 +1,110 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the moderation notes module.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The notes the admins keep on the users of each chat.
+#[derive(Clone)]
+pub struct ModNotes {
+    /// The persisted notes.
+    storage: Storage<Data>,
+}
+
+impl ModNotes {
+    /// Creates a new `ModNotes` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("mod_notes"),
+        }
+    }
+
+    /// Adds a note on a user of a chat.
+    pub async fn add(&self, note: ModNote) {
+        self.storage.write(|data| data.notes.push(note)).await
+    }
+
+    /// Returns the notes on a user of a chat, oldest first.
+    pub async fn of(&self, chat_id: i64, user_id: i64) -> Vec<ModNote> {
+        self.storage
+            .read(|data| {
+                data.notes
+                    .iter()
+                    .filter(|n| n.chat_id == chat_id && n.user_id == user_id)
+                    .cloned()
+                    .collect()
+            })
+            .await
+    }
+
+    /// Removes all the notes on a user of a chat, returning how many were removed.
+    pub async fn clear(&self, chat_id: i64, user_id: i64) -> usize {
+        self.storage
+            .write(|data| {
+                let len = data.notes.len();
+                data.notes
+                    .retain(|n| n.chat_id != chat_id || n.user_id != user_id);
+
+                len - data.notes.len()
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl ChatMigration for ModNotes {
+    fn name(&self) -> &'static str {
+        "moderation notes"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| {
+                let mut moved = 0;
+                for note in data.notes.iter_mut().filter(|note| note.chat_id == from) {
+                    note.chat_id = to;
+                    moved += 1;
+                }
+
+                moved
+            })
+            .await
+    }
+}
+
+/// A note on a user.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModNote {
+    /// The chat ID.
+    pub chat_id: i64,
+    /// The ID of the noted user.
+    pub user_id: i64,
+    /// The ID of the admin who wrote it.
+    pub author_id: i64,
+    /// The name of the admin who wrote it.
+    pub author_name: String,
+    /// The note.
+    pub text: String,
+    /// When it was written.
+    pub date: DateTime<Utc>,
+}
+
+/// The persisted notes.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The notes.
+    notes: Vec<ModNote>,
+}
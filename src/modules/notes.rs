@@ -0,0 +1,96 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the notes module.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The saved notes, retrievable by name in any chat.
+#[derive(Clone)]
+pub struct Notes {
+    /// The persisted notes.
+    storage: Storage<Data>,
+}
+
+impl Notes {
+    /// Creates a new `Notes` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("notes"),
+        }
+    }
+
+    /// Saves a note, replacing any with the same name.
+    ///
+    /// Returns `true` if a note was replaced.
+    pub async fn save(&self, name: &str, note: Note) -> bool {
+        self.storage
+            .write(|data| data.notes.insert(name.to_lowercase(), note).is_some())
+            .await
+    }
+
+    /// Returns a note.
+    pub async fn get(&self, name: &str) -> Option<Note> {
+        self.storage
+            .read(|data| data.notes.get(&name.to_lowercase()).cloned())
+            .await
+    }
+
+    /// Removes a note.
+    ///
+    /// Returns `false` if the note does not exist.
+    pub async fn clear(&self, name: &str) -> bool {
+        self.storage
+            .write(|data| data.notes.remove(&name.to_lowercase()).is_some())
+            .await
+    }
+
+    /// Returns the notes, sorted by name.
+    pub async fn list(&self) -> Vec<(String, Note)> {
+        self.storage
+            .read(|data| {
+                data.notes
+                    .iter()
+                    .map(|(name, note)| (name.clone(), note.clone()))
+                    .collect()
+            })
+            .await
+    }
+}
+
+/// A saved note.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Note {
+    /// The formatted text, as HTML.
+    pub text: String,
+    /// The attached media, if any.
+    pub media: Option<MediaRef>,
+}
+
+/// A reference to the media of a note.
+///
+/// The media is kept as a copy in the saved messages, as its file reference
+/// expires; the copy is fetched again when the note is sent.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MediaRef {
+    /// The ID of the file, if the media has one.
+    pub file_id: Option<i64>,
+    /// The ID of the copy in the saved messages.
+    pub saved_message_id: i32,
+}
+
+/// The persisted notes.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The notes by name.
+    notes: BTreeMap<String, Note>,
+}
@@ -0,0 +1,218 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the NSFW detection module.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use ferogram::Result;
+use grammers_client::{session::PackedChat, types::Media};
+use reqwest::{
+    header::AUTHORIZATION,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use super::{
+    migrations::{self, ChatMigration},
+    reposts::Action,
+    storage::Storage,
+};
+
+/// How many removed photos can be appealed, the oldest being dropped.
+const MAX_APPEALS: usize = 50;
+
+/// The classifier that scores the photos.
+///
+/// It is any HTTP endpoint that takes the photo as a multipart file and answers
+/// with a JSON holding the score, either a hosted API or a local model server.
+pub struct Classifier {
+    /// The URL of the endpoint.
+    pub url: String,
+    /// The bearer token sent, if any.
+    pub api_key: Option<String>,
+    /// The name of the multipart field of the photo.
+    pub file_field: String,
+    /// The JSON pointer of the score in the response, such as `/nsfw`.
+    pub score_pointer: String,
+    /// Whether the score goes from 0 to 1, instead of 0 to 100.
+    pub normalized: bool,
+}
+
+/// Scores the photos sent to the moderated chats, removing the explicit ones.
+#[derive(Clone)]
+pub struct Nsfw {
+    /// The persisted settings of each chat.
+    storage: Storage<Data>,
+    /// The classifier, `None` if not configured.
+    classifier: Option<Arc<Classifier>>,
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The removed photos that can be appealed, the newest last.
+    appeals: Arc<Mutex<VecDeque<Appeal>>>,
+    /// The last appeal ID given.
+    last_id: Arc<AtomicU32>,
+}
+
+impl Nsfw {
+    /// Creates a new `Nsfw` instance.
+    pub fn new(classifier: Option<Classifier>) -> Self {
+        Self {
+            storage: Storage::open("nsfw"),
+            classifier: classifier.map(Arc::new),
+            client: reqwest::Client::new(),
+            appeals: Arc::new(Mutex::new(VecDeque::new())),
+            last_id: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Checks if there is a classifier to score the photos.
+    pub fn is_configured(&self) -> bool {
+        self.classifier.is_some()
+    }
+
+    /// Returns the settings of a chat.
+    pub async fn settings(&self, chat_id: i64) -> Settings {
+        self.storage
+            .read(|data| data.chats.get(&chat_id).cloned().unwrap_or_default())
+            .await
+    }
+
+    /// Updates the settings of a chat, returning the new ones.
+    pub async fn update(&self, chat_id: i64, f: impl FnOnce(&mut Settings)) -> Settings {
+        self.storage
+            .write(|data| {
+                let settings = data.chats.entry(chat_id).or_default();
+                f(settings);
+
+                settings.clone()
+            })
+            .await
+    }
+
+    /// Scores a photo, from 0 to 100, the higher the more explicit.
+    pub async fn score(&self, bytes: &[u8]) -> Result<f32> {
+        let classifier = self
+            .classifier
+            .as_ref()
+            .ok_or("The NSFW classifier is not configured")?;
+
+        let part = Part::bytes(bytes.to_vec())
+            .file_name("photo.jpg")
+            .mime_str("image/jpeg")?;
+        let mut request = self
+            .client
+            .post(&classifier.url)
+            .multipart(Form::new().part(classifier.file_field.clone(), part));
+        if let Some(key) = &classifier.api_key {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", key));
+        }
+
+        let json = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        let score = json
+            .pointer(&classifier.score_pointer)
+            .and_then(|score| score.as_f64())
+            .ok_or("The NSFW classifier gave no score")? as f32;
+
+        Ok(if classifier.normalized {
+            score * 100.0
+        } else {
+            score
+        })
+    }
+
+    /// Keeps a removed photo so an admin can restore it.
+    ///
+    /// Returns the appeal ID.
+    pub async fn add_appeal(&self, appeal: Appeal) -> u32 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut appeals = self.appeals.lock().await;
+        if appeals.len() >= MAX_APPEALS {
+            appeals.pop_front();
+        }
+        appeals.push_back(Appeal { id, ..appeal });
+
+        id
+    }
+
+    /// Takes a removed photo, so it is restored once.
+    pub async fn take_appeal(&self, id: u32) -> Option<Appeal> {
+        let mut appeals = self.appeals.lock().await;
+        let index = appeals.iter().position(|appeal| appeal.id == id)?;
+
+        appeals.remove(index)
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Nsfw {
+    fn name(&self) -> &'static str {
+        "NSFW settings"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::move_key(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// A removed photo that can be restored.
+pub struct Appeal {
+    /// The appeal ID, given when added.
+    pub id: u32,
+    /// The chat the photo was sent to.
+    pub chat: PackedChat,
+    /// The photo.
+    pub media: Media,
+    /// The name of who sent it.
+    pub sender: String,
+}
+
+/// The NSFW settings of a chat.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Settings {
+    /// Whether the photos are scored.
+    pub enabled: bool,
+    /// What is done with an explicit photo.
+    pub action: Action,
+    /// The score from which a photo is explicit, lower being stricter.
+    pub threshold: u8,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: Action::default(),
+            threshold: 80,
+        }
+    }
+}
+
+/// The persisted NSFW data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The settings of each chat.
+    chats: HashMap<i64, Settings>,
+}
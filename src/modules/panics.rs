@@ -0,0 +1,177 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the panic reporting module.
+
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
+
+use grammers_client::InputMessage;
+use maplit::hashmap;
+use tokio::sync::Notify;
+
+use super::{i18n::I18n, shutdown::Shutdown};
+use crate::{utils::escape_html, Sender};
+
+/// How long the panics are gathered before being reported together.
+const REPORT_DELAY: Duration = Duration::from_secs(5);
+
+/// The most panics waiting to be reported, the others only logged.
+const MAX_PENDING: usize = 10;
+
+/// The most characters of a panic message reported.
+const MAX_MESSAGE_LENGTH: usize = 300;
+
+/// The reporter shared by the panic hook.
+static SHARED: LazyLock<Panics> = LazyLock::new(|| Panics {
+    pending: Arc::new(Mutex::new(Vec::new())),
+    panicked: Arc::new(Notify::new()),
+});
+
+/// Logs the panics of any task and reports them to the owner, so they are not
+/// lost in the output of the process.
+#[derive(Clone)]
+pub struct Panics {
+    /// The panics waiting to be reported.
+    pending: Arc<Mutex<Vec<Report>>>,
+    /// Wakes the reporter when a panic happens.
+    panicked: Arc<Notify>,
+}
+
+impl Panics {
+    /// Returns the shared reporter, as the panic hook has no other way to it.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Installs the panic hook, in place of the one printing to the standard
+    /// error.
+    pub fn install_hook() {
+        std::panic::set_hook(Box::new(|info| {
+            let message = message(info.payload());
+            let location = info
+                .location()
+                .map(|location| format!("{0}:{1}", location.file(), location.line()))
+                .unwrap_or_else(|| "unknown location".to_string());
+            let thread = std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string();
+
+            // Only captured if asked for, with `RUST_BACKTRACE`.
+            let backtrace = Backtrace::capture();
+            if backtrace.status() == BacktraceStatus::Captured {
+                log::error!(
+                    "Panicked at {0} in the {1} thread: {2}\n{3}",
+                    location,
+                    thread,
+                    message,
+                    backtrace
+                );
+            } else {
+                log::error!(
+                    "Panicked at {0} in the {1} thread: {2}",
+                    location,
+                    thread,
+                    message
+                );
+            }
+
+            SHARED.push(Report { message, location });
+        }));
+    }
+
+    /// Queues a panic to be reported.
+    fn push(&self, report: Report) {
+        // A panic while the list is locked is only logged, not to deadlock.
+        let Ok(mut pending) = self.pending.try_lock() else {
+            return;
+        };
+        if pending.len() < MAX_PENDING {
+            pending.push(report);
+        }
+        drop(pending);
+
+        self.panicked.notify_one();
+    }
+
+    /// Spawns the task that reports the panics to the owner.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                self.panicked.notified().await;
+                // Gathers the panics of the same failure.
+                tokio::time::sleep(REPORT_DELAY).await;
+
+                let reports =
+                    std::mem::take(&mut *self.pending.lock().expect("failed to lock panics"));
+                if reports.is_empty() {
+                    continue;
+                }
+
+                let lines = reports
+                    .iter()
+                    .map(|report| {
+                        i18n.translate_with_args(
+                            "panic_line",
+                            hashmap! {
+                                "location" => escape_html(&report.location),
+                                "message" => escape_html(&truncate(&report.message)),
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let text = i18n.translate_with_args(
+                    "panic_report",
+                    hashmap! {
+                        "count" => reports.len().to_string(),
+                        "panics" => lines.join("\n"),
+                    },
+                );
+
+                if let Err(e) = tx
+                    .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                    .await
+                {
+                    log::error!("Failed to report the panics: {}", e);
+                }
+            }
+        });
+    }
+}
+
+/// A panic waiting to be reported.
+struct Report {
+    /// What the panic said.
+    message: String,
+    /// Where it happened, as `file:line`.
+    location: String,
+}
+
+/// Returns the message of a panic, from its payload.
+pub fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Cuts a panic message to the most characters reported.
+fn truncate(message: &str) -> String {
+    match message.char_indices().nth(MAX_MESSAGE_LENGTH) {
+        Some((end, _)) => format!("{}…", &message[..end]),
+        None => message.to_string(),
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains a small PDF writer, enough to lay out text and photos.
+//!
+//! The text uses the standard Helvetica fonts, so nothing is embedded, in the
+//! WinAnsi encoding: the characters outside of it are written as `?`.
+
+use std::{fmt::Write as _, io::Cursor};
+
+use ferogram::Result;
+use image::codecs::jpeg::JpegEncoder;
+
+/// The width of an A4 page, in points.
+const PAGE_WIDTH: f32 = 595.0;
+/// The height of an A4 page, in points.
+const PAGE_HEIGHT: f32 = 842.0;
+/// The margin around the page, in points.
+const MARGIN: f32 = 50.0;
+
+/// The size of the title.
+const TITLE_SIZE: f32 = 16.0;
+/// The size of the body text.
+const TEXT_SIZE: f32 = 10.0;
+/// The size of the small text, such as the timestamps.
+const SMALL_SIZE: f32 = 8.0;
+/// The line height, relative to the font size.
+const LINE_HEIGHT: f32 = 1.4;
+
+/// The largest side of a photo, in points.
+const MAX_IMAGE_SIDE: f32 = 300.0;
+/// The largest side of a photo, in pixels, so the document stays small.
+const MAX_IMAGE_PIXELS: u32 = 1000;
+/// The quality the photos are encoded with.
+const IMAGE_QUALITY: u8 = 80;
+
+/// The widths of the printable ASCII characters in Helvetica, in thousandths of
+/// the font size.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// How much wider the bold font is, roughly.
+const BOLD_FACTOR: f32 = 1.08;
+
+/// A font of the document.
+#[derive(Clone, Copy)]
+enum Font {
+    /// Helvetica.
+    Regular,
+    /// Helvetica-Bold.
+    Bold,
+}
+
+impl Font {
+    /// Returns the name of the font resource.
+    fn resource(&self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+        }
+    }
+
+    /// Measures the width of a text, in points.
+    fn width(&self, text: &str, size: f32) -> f32 {
+        let width = text
+            .chars()
+            .map(|c| match c {
+                ' '..='~' => HELVETICA_WIDTHS[c as usize - 32] as f32,
+                _ => 556.0,
+            })
+            .sum::<f32>()
+            * size
+            / 1000.0;
+
+        match self {
+            Self::Regular => width,
+            Self::Bold => width * BOLD_FACTOR,
+        }
+    }
+}
+
+/// A photo encoded as JPEG.
+struct Image {
+    /// The JPEG bytes.
+    bytes: Vec<u8>,
+    /// The width, in pixels.
+    width: u32,
+    /// The height, in pixels.
+    height: u32,
+}
+
+/// A document being laid out, top to bottom.
+pub struct Pdf {
+    /// The content streams of the finished pages.
+    pages: Vec<Page>,
+    /// The page being laid out.
+    page: Page,
+    /// The photos, referred by their index.
+    images: Vec<Image>,
+    /// Where the next line goes, from the bottom of the page.
+    y: f32,
+}
+
+/// A page of the document.
+#[derive(Default)]
+struct Page {
+    /// The content stream.
+    content: String,
+    /// The photos drawn in it.
+    images: Vec<usize>,
+}
+
+impl Default for Pdf {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            page: Page::default(),
+            images: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+}
+
+impl Pdf {
+    /// Adds a title.
+    pub fn title(&mut self, text: &str) {
+        self.paragraph(text, Font::Bold, TITLE_SIZE, 0.0);
+        self.space(TITLE_SIZE / 2.0);
+    }
+
+    /// Adds a heading, with a gray note after it on the same line.
+    pub fn heading(&mut self, text: &str, note: &str) {
+        let height = TEXT_SIZE * LINE_HEIGHT;
+        self.reserve(height);
+        self.y -= height;
+
+        self.draw(MARGIN, self.y, Font::Bold, TEXT_SIZE, 0.0, text);
+        let x = MARGIN + Font::Bold.width(text, TEXT_SIZE) + TEXT_SIZE / 2.0;
+        self.draw(x, self.y, Font::Regular, SMALL_SIZE, 0.5, note);
+    }
+
+    /// Adds a text, wrapped to the width of the page.
+    pub fn text(&mut self, text: &str) {
+        self.paragraph(text, Font::Regular, TEXT_SIZE, 0.0);
+    }
+
+    /// Adds a gray text, wrapped to the width of the page.
+    pub fn note(&mut self, text: &str) {
+        self.paragraph(text, Font::Regular, SMALL_SIZE, 0.5);
+    }
+
+    /// Adds a vertical space, in points.
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Adds a photo, scaled down to fit.
+    ///
+    /// Any format the image crate reads is accepted, it is encoded again as JPEG.
+    pub fn image(&mut self, bytes: &[u8]) -> Result<()> {
+        let mut image = image::load_from_memory(bytes)?;
+        if image.width().max(image.height()) > MAX_IMAGE_PIXELS {
+            image = image.thumbnail(MAX_IMAGE_PIXELS, MAX_IMAGE_PIXELS);
+        }
+        let image = image.to_rgb8();
+        let (width, height) = image.dimensions();
+
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(Cursor::new(&mut jpeg), IMAGE_QUALITY)
+            .encode_image(&image)?;
+
+        let scale = (MAX_IMAGE_SIDE / width.max(height) as f32)
+            .min((PAGE_WIDTH - 2.0 * MARGIN) / width as f32)
+            .min(1.0);
+        let (w, h) = (width as f32 * scale, height as f32 * scale);
+
+        self.reserve(h);
+        self.y -= h;
+
+        let index = self.images.len();
+        self.images.push(Image {
+            bytes: jpeg,
+            width,
+            height,
+        });
+        self.page.images.push(index);
+        let _ = writeln!(
+            self.page.content,
+            "q {0:.2} 0 0 {1:.2} {2:.2} {3:.2} cm /Im{4} Do Q",
+            w, h, MARGIN, self.y, index
+        );
+        self.space(TEXT_SIZE / 2.0);
+
+        Ok(())
+    }
+
+    /// Lays out a paragraph, line by line.
+    fn paragraph(&mut self, text: &str, font: Font, size: f32, gray: f32) {
+        let height = size * LINE_HEIGHT;
+
+        for line in wrap(text, font, size, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.reserve(height);
+            self.y -= height;
+            self.draw(MARGIN, self.y, font, size, gray, &line);
+        }
+    }
+
+    /// Draws a line of text at a position.
+    fn draw(&mut self, x: f32, y: f32, font: Font, size: f32, gray: f32, text: &str) {
+        let _ = writeln!(
+            self.page.content,
+            "BT {0} g /{1} {2} Tf {3:.2} {4:.2} Td ({5}) Tj ET",
+            gray,
+            font.resource(),
+            size,
+            x,
+            y,
+            encode(text)
+        );
+    }
+
+    /// Starts a new page if `height` does not fit in the current one.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN && !self.page.content.is_empty() {
+            self.pages.push(std::mem::take(&mut self.page));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    /// Writes the document.
+    pub fn finish(mut self) -> Vec<u8> {
+        if !self.page.content.is_empty() || self.pages.is_empty() {
+            self.pages.push(std::mem::take(&mut self.page));
+        }
+
+        // The objects are the catalog, the page tree, the two fonts, the photos
+        // and then the content and the page of each page.
+        let images_start = 5;
+        let pages_start = images_start + self.images.len();
+        let page_ids = (0..self.pages.len())
+            .map(|index| pages_start + index * 2 + 1)
+            .collect::<Vec<_>>();
+
+        let mut writer = Writer::default();
+        writer.object(1, b"<< /Type /Catalog /Pages 2 0 R >>");
+        writer.object(
+            2,
+            format!(
+                "<< /Type /Pages /Kids [{0}] /Count {1} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                page_ids.len()
+            )
+            .as_bytes(),
+        );
+        writer.object(
+            3,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        writer.object(
+            4,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+
+        for (index, image) in self.images.iter().enumerate() {
+            writer.stream(
+                images_start + index,
+                &format!(
+                    "/Type /XObject /Subtype /Image /Width {0} /Height {1} /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode",
+                    image.width, image.height
+                ),
+                &image.bytes,
+            );
+        }
+
+        for (index, page) in self.pages.iter().enumerate() {
+            let content_id = pages_start + index * 2;
+            writer.stream(content_id, "", page.content.as_bytes());
+
+            let images = page
+                .images
+                .iter()
+                .map(|image| format!("/Im{0} {1} 0 R", image, images_start + image))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writer.object(
+                content_id + 1,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {0} {1}] /Contents {2} 0 R /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {3} >> >> >>",
+                    PAGE_WIDTH, PAGE_HEIGHT, content_id, images
+                )
+                .as_bytes(),
+            );
+        }
+
+        writer.finish()
+    }
+}
+
+/// Writes the objects of a document, keeping where each one starts.
+struct Writer {
+    /// The document so far.
+    bytes: Vec<u8>,
+    /// The offset of each object, by its ID minus one.
+    offsets: Vec<usize>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self {
+            // The comment with high bytes tells it is a binary file.
+            bytes: b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec(),
+            offsets: Vec::new(),
+        }
+    }
+}
+
+impl Writer {
+    /// Writes an object, which must come in order.
+    fn object(&mut self, id: usize, body: &[u8]) {
+        self.begin(id);
+        self.bytes.extend_from_slice(body);
+        self.bytes.extend_from_slice(b"\nendobj\n");
+    }
+
+    /// Writes a stream object, with the given dictionary entries.
+    fn stream(&mut self, id: usize, entries: &str, data: &[u8]) {
+        self.begin(id);
+        self.bytes.extend_from_slice(
+            format!("<< {0} /Length {1} >>\nstream\n", entries, data.len()).as_bytes(),
+        );
+        self.bytes.extend_from_slice(data);
+        self.bytes.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    /// Starts an object, keeping its offset.
+    fn begin(&mut self, id: usize) {
+        debug_assert_eq!(id, self.offsets.len() + 1, "objects must come in order");
+
+        self.offsets.push(self.bytes.len());
+        self.bytes
+            .extend_from_slice(format!("{} 0 obj\n", id).as_bytes());
+    }
+
+    /// Writes the cross-reference table and the trailer.
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.bytes.len();
+
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in self.offsets.iter() {
+            let _ = write!(table, "{:010} 00000 n \n", offset);
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {0} /Root 1 0 R >>\nstartxref\n{1}\n%%EOF\n",
+            self.offsets.len() + 1,
+            xref
+        );
+
+        self.bytes.extend_from_slice(table.as_bytes());
+        self.bytes
+    }
+}
+
+/// Wraps a text to a width, breaking on the spaces or inside the words too long.
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{0} {1}", line, word)
+            };
+            if font.width(&candidate, size) <= width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if font.width(&line, size) > width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Encodes a text as a PDF string in the WinAnsi encoding, without parentheses.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        let byte = match c {
+            '(' | ')' | '\\' => {
+                encoded.push('\\');
+                c as u8
+            }
+            ' '..='~' => c as u8,
+            '\u{A0}'..='\u{FF}' => c as u32 as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '\t' => b' ',
+            _ => b'?',
+        };
+
+        match byte {
+            b' '..=b'~' => encoded.push(byte as char),
+            _ => {
+                let _ = write!(encoded, "\\{:03o}", byte);
+            }
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,236 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the profile rotation module.
+
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, Timelike};
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, Client, InvocationError};
+use serde::{Deserialize, Serialize};
+
+use super::{shutdown::Shutdown, storage::Storage};
+use crate::utils::flood_wait;
+
+/// The maximum length of a last name.
+const MAX_LAST_NAME_LENGTH: usize = 64;
+/// The clock faces of each full hour, starting at twelve.
+const FULL_HOURS: [&str; 12] = [
+    "🕛", "🕐", "🕑", "🕒", "🕓", "🕔", "🕕", "🕖", "🕗", "🕘", "🕙", "🕚",
+];
+/// The clock faces of each half hour, starting at twelve and a half.
+const HALF_HOURS: [&str; 12] = [
+    "🕧", "🕜", "🕝", "🕞", "🕟", "🕠", "🕡", "🕢", "🕣", "🕤", "🕥", "🕦",
+];
+
+/// The profile rotation, which keeps a clock in the last name and rotates the bio.
+#[derive(Clone)]
+pub struct Profile {
+    /// The rotation state.
+    storage: Storage<Data>,
+    /// The format of the time shown in the last name.
+    clock_format: String,
+    /// The bios rotated daily.
+    bios: Vec<String>,
+}
+
+impl Profile {
+    /// Creates a new `Profile` instance.
+    pub fn new(clock_format: String, bios: Vec<String>) -> Self {
+        Self {
+            storage: Storage::open("profile"),
+            clock_format,
+            bios,
+        }
+    }
+
+    /// Checks if the clock is enabled.
+    pub async fn clock_enabled(&self) -> bool {
+        self.storage.read(|data| data.last_name.is_some()).await
+    }
+
+    /// Checks if the bio rotation is enabled.
+    pub async fn bio_enabled(&self) -> bool {
+        self.storage.read(|data| data.bio).await
+    }
+
+    /// Returns whether there are bios to be rotated.
+    pub fn has_bios(&self) -> bool {
+        !self.bios.is_empty()
+    }
+
+    /// Enables the clock, remembering the current last name.
+    ///
+    /// Returns `false` if it was already enabled.
+    pub async fn enable_clock(&self, client: &Client) -> Result<bool> {
+        if self.clock_enabled().await {
+            return Ok(false);
+        }
+
+        let me = client.get_me().await?;
+        let last_name = me.last_name().unwrap_or_default().to_string();
+        self.storage
+            .write(|data| data.last_name = Some(last_name))
+            .await;
+
+        self.tick(client).await?;
+
+        Ok(true)
+    }
+
+    /// Disables the clock, restoring the remembered last name.
+    ///
+    /// Returns `false` if it was not enabled.
+    pub async fn disable_clock(&self, client: &Client) -> Result<bool> {
+        let Some(last_name) = self.storage.write(|data| data.last_name.take()).await else {
+            return Ok(false);
+        };
+
+        update_profile(client, Some(last_name), None).await?;
+
+        Ok(true)
+    }
+
+    /// Enables or disables the bio rotation.
+    ///
+    /// Returns `false` if it was already in the given state.
+    pub async fn set_bio(&self, client: &Client, enabled: bool) -> Result<bool> {
+        let changed = self
+            .storage
+            .write(|data| {
+                let changed = data.bio != enabled;
+                data.bio = enabled;
+                data.rotated_at = None;
+
+                changed
+            })
+            .await;
+
+        if changed && enabled {
+            self.tick(client).await?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Renders the last name with the current time.
+    async fn clock(&self) -> Option<String> {
+        let base = self.storage.read(|data| data.last_name.clone()).await?;
+
+        let now = Local::now();
+        let hour = (now.hour() % 12) as usize;
+        let face = if now.minute() < 30 {
+            FULL_HOURS[hour]
+        } else {
+            HALF_HOURS[hour]
+        };
+
+        let clock = format!("{} {}", face, now.format(&self.clock_format));
+        let base = base
+            .chars()
+            .take(MAX_LAST_NAME_LENGTH.saturating_sub(clock.chars().count() + 1))
+            .collect::<String>();
+
+        Some(format!("{} {}", base, clock).trim().to_string())
+    }
+
+    /// Returns the bio of the day, if it has not been set yet.
+    async fn bio(&self) -> Option<String> {
+        if self.bios.is_empty() {
+            return None;
+        }
+
+        let today = Local::now().date_naive();
+        self.storage
+            .write(|data| {
+                if !data.bio || data.rotated_at == Some(today) {
+                    return None;
+                }
+
+                let bio = self.bios[data.index % self.bios.len()].clone();
+                data.index = (data.index + 1) % self.bios.len();
+                data.rotated_at = Some(today);
+
+                Some(bio)
+            })
+            .await
+    }
+
+    /// Updates the profile with the current clock and bio.
+    async fn tick(&self, client: &Client) -> std::result::Result<(), InvocationError> {
+        let last_name = self.clock().await;
+        let bio = self.bio().await;
+
+        if last_name.is_some() || bio.is_some() {
+            update_profile(client, last_name, bio).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Spawns the task that updates the profile every minute.
+    ///
+    /// Flood waits postpone the next update instead of retrying right away.
+    pub fn start(self, client: Client) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                let delay = match self.tick(&client).await {
+                    Ok(()) => {
+                        // Aligns the updates with the start of each minute.
+                        let second = Local::now().second() as u64;
+                        Duration::from_secs(60 - second.min(59))
+                    }
+                    Err(e) => match flood_wait(&e) {
+                        Some(wait) => {
+                            log::warn!("Profile rotation hit a flood wait of {}s", wait.as_secs());
+
+                            wait + Duration::from_secs(1)
+                        }
+                        None => {
+                            log::error!("Failed to update the profile: {}", e);
+                            Duration::from_secs(60)
+                        }
+                    },
+                };
+
+                tokio::time::sleep(delay).await;
+            }
+        });
+    }
+}
+
+/// Updates the last name and the bio of the account.
+async fn update_profile(
+    client: &Client,
+    last_name: Option<String>,
+    about: Option<String>,
+) -> std::result::Result<(), InvocationError> {
+    client
+        .invoke(&tl::functions::account::UpdateProfile {
+            first_name: None,
+            last_name,
+            about,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// The persisted rotation state.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The original last name, set while the clock is enabled.
+    last_name: Option<String>,
+    /// Whether the bio rotation is enabled.
+    bio: bool,
+    /// The index of the next bio.
+    index: usize,
+    /// The day the bio was last rotated.
+    rotated_at: Option<NaiveDate>,
+}
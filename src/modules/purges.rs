@@ -0,0 +1,270 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the purge confirmation module.
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use chrono::{Local, TimeDelta, Utc};
+use ferogram::Result;
+use grammers_client::{
+    button, reply_markup, session::PackedChat, types::Chat, Client, InputMessage, InvocationError,
+};
+use maplit::hashmap;
+use tokio::{sync::Mutex, time::Instant};
+
+use super::i18n::I18n;
+use crate::{utils, Recipient};
+
+/// The maximum number of purges kept waiting for a confirmation.
+const CACHE_SIZE: usize = 20;
+
+/// How long a purge waits for a confirmation.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// The most messages a purge without a reply spans.
+const MAX_SPAN: usize = 10_000;
+
+/// The purges waiting for a confirmation, asked for the large ones.
+#[derive(Clone)]
+pub struct Purges {
+    /// The pending purges.
+    pending: Arc<Mutex<Vec<Pending>>>,
+    /// The last ID given to a purge, never reused so an old button cannot confirm
+    /// another purge.
+    last_id: Arc<AtomicU32>,
+}
+
+impl Purges {
+    /// Creates a new `Purges` instance.
+    pub fn new() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(Vec::new())),
+            last_id: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    /// Keeps a purge waiting for a confirmation.
+    ///
+    /// Returns the ID of the pending purge and the message asking for it, which
+    /// tells how many messages are purged and the time range they span, with the
+    /// buttons to confirm or cancel it.
+    pub async fn ask(
+        &self,
+        client: &Client,
+        purge: Purge,
+        i18n: &I18n,
+    ) -> Result<(u32, InputMessage)> {
+        let first = purge.message_ids.first().copied().unwrap_or_default();
+        let last = purge.message_ids.last().copied().unwrap_or_default();
+        let messages = client
+            .get_messages_by_id(purge.chat, &[first, last])
+            .await?;
+        let dates = messages
+            .iter()
+            .flatten()
+            .map(|message| {
+                message
+                    .date()
+                    .with_timezone(&Local)
+                    .format("%d/%m/%Y %H:%M")
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        let (from, to) = match dates.as_slice() {
+            [from, .., to] => (from.clone(), to.clone()),
+            [date] => (date.clone(), date.clone()),
+            [] => ("?".to_string(), "?".to_string()),
+        };
+        let count = purge.message_ids.len();
+
+        let mut pending = self.pending.lock().await;
+        pending.retain(|p| p.created.elapsed() < CONFIRM_TIMEOUT);
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        pending.push(Pending {
+            id,
+            purge,
+            created: Instant::now(),
+        });
+        if pending.len() > CACHE_SIZE {
+            pending.remove(0);
+        }
+
+        let buttons = vec![
+            button::inline(
+                i18n.translate("purge_confirm"),
+                format!("purge confirm {}", id),
+            ),
+            button::inline(
+                i18n.translate("purge_cancel"),
+                format!("purge cancel {}", id),
+            ),
+        ];
+
+        let input = InputMessage::html(i18n.translate_with_args(
+            "purge_confirmation",
+            hashmap! {
+                "count" => count.to_string(),
+                "from" => from,
+                "to" => to,
+            },
+        ))
+        .reply_markup(&reply_markup::inline(vec![buttons]));
+
+        Ok((id, input))
+    }
+
+    /// Attaches the message asking for a confirmation to a pending purge, so it is
+    /// deleted along with the command.
+    pub async fn attach(&self, id: u32, message_id: i32) {
+        let mut pending = self.pending.lock().await;
+
+        if let Some(p) = pending.iter_mut().find(|p| p.id == id) {
+            p.purge.command_ids.push(message_id);
+        }
+    }
+
+    /// Takes the purge with the given ID, if it was not given up on.
+    pub async fn take(&self, id: u32) -> Option<Purge> {
+        let mut pending = self.pending.lock().await;
+        let index = pending.iter().position(|p| p.id == id)?;
+        let pending = pending.remove(index);
+
+        (pending.created.elapsed() < CONFIRM_TIMEOUT).then_some(pending.purge)
+    }
+}
+
+/// A purge to be confirmed.
+pub struct Purge {
+    /// Which client purges the messages.
+    pub recipient: Recipient,
+    /// The client that purges the messages, the user account it was asked from.
+    pub client: Client,
+    /// The chat of the messages.
+    pub chat: PackedChat,
+    /// The IDs of the messages, in order.
+    pub message_ids: Vec<i32>,
+    /// The IDs of the command and confirmation messages, deleted along with the result.
+    pub command_ids: Vec<i32>,
+}
+
+/// A purge waiting for a confirmation.
+struct Pending {
+    /// The ID of the pending purge.
+    id: u32,
+    /// The purge.
+    purge: Purge,
+    /// When it was asked for.
+    created: Instant,
+}
+
+/// The messages a purge spans when it is not anchored to a reply.
+pub enum Span {
+    /// The last messages.
+    Last(usize),
+    /// The messages newer than a duration.
+    Since(Duration),
+}
+
+impl Span {
+    /// Parses the argument of a purge command, `50` or `2h`.
+    pub fn parse(arg: &str) -> Option<Self> {
+        match arg.parse::<usize>() {
+            Ok(count) => (count > 0).then_some(Self::Last(count)),
+            Err(_) => utils::parse_duration(arg).map(Self::Since),
+        }
+    }
+}
+
+/// Collects the IDs of the messages a span covers before a message, in order.
+///
+/// The messages are fetched by ID walking back, which the bot can do too. In
+/// forums, only the messages of the given topic are collected.
+pub async fn collect(
+    client: &Client,
+    chat: &Chat,
+    before: i32,
+    topic: Option<i32>,
+    span: &Span,
+) -> Result<Vec<i32>> {
+    let (limit, since) = match span {
+        Span::Last(count) => ((*count).min(MAX_SPAN), None),
+        Span::Since(duration) => (MAX_SPAN, Some(Utc::now() - TimeDelta::from_std(*duration)?)),
+    };
+    let forum = utils::is_forum(chat);
+
+    let mut message_ids = Vec::new();
+    let mut end = before;
+    'walk: while end > 1 {
+        let start = (end - 100).max(1);
+        let chunk = (start..end).rev().collect::<Vec<_>>();
+
+        for message in client
+            .get_messages_by_id(chat, &chunk)
+            .await?
+            .into_iter()
+            .flatten()
+        {
+            if since.is_some_and(|since| message.date() < since) {
+                break 'walk;
+            }
+            if forum && utils::topic_id(&message) != topic {
+                continue;
+            }
+
+            message_ids.push(message.id());
+            if message_ids.len() >= limit {
+                break 'walk;
+            }
+        }
+
+        end = start;
+    }
+    message_ids.reverse();
+
+    Ok(message_ids)
+}
+
+/// Deletes messages in chunks, waiting out the flood waits.
+///
+/// Returns how many messages were deleted.
+pub async fn delete_messages(
+    client: &Client,
+    chat: PackedChat,
+    message_ids: &[i32],
+) -> std::result::Result<usize, InvocationError> {
+    let mut deleted = 0;
+
+    for chunk in message_ids.chunks(100) {
+        loop {
+            match client.delete_messages(chat, chunk).await {
+                Ok(count) => deleted += count,
+                Err(e) if e.is("MESSAGE_ID_INVALID") => {}
+                Err(e) => match utils::flood_wait(&e) {
+                    // The chunk is deleted again once the wait is over.
+                    Some(wait) => {
+                        tokio::time::sleep(wait).await;
+                        continue;
+                    }
+                    None => return Err(e),
+                },
+            }
+
+            break;
+        }
+    }
+
+    Ok(deleted)
+}
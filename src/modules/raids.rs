@@ -0,0 +1,185 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the raids module.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// Detects bursts of joins and tracks the chats under raid.
+#[derive(Clone)]
+pub struct Raids {
+    /// The persisted mode of each chat.
+    storage: Storage<Data>,
+    /// The recent joins of each chat.
+    joins: Arc<Mutex<HashMap<i64, VecDeque<Instant>>>>,
+    /// When the raid mode of each chat under raid ends.
+    active: Arc<Mutex<HashMap<i64, Instant>>>,
+    /// The number of joins that triggers the raid mode.
+    threshold: usize,
+    /// The window the joins are counted in.
+    window: Duration,
+    /// How long the raid mode lasts once triggered.
+    duration: Duration,
+}
+
+impl Raids {
+    /// Creates a new `Raids` instance.
+    pub fn new(threshold: usize, window: Duration, duration: Duration) -> Self {
+        Self {
+            storage: Storage::open("raids"),
+            joins: Arc::new(Mutex::new(HashMap::new())),
+            active: Arc::new(Mutex::new(HashMap::new())),
+            threshold,
+            window,
+            duration,
+        }
+    }
+
+    /// Returns how long the raid mode lasts once triggered.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the mode of a chat.
+    pub async fn mode(&self, chat_id: i64) -> Mode {
+        self.storage
+            .read(|data| data.modes.get(&chat_id).copied().unwrap_or_default())
+            .await
+    }
+
+    /// Sets the mode of a chat, ending any raid mode triggered automatically.
+    pub async fn set_mode(&self, chat_id: i64, mode: Mode) {
+        self.storage
+            .write(|data| data.modes.insert(chat_id, mode))
+            .await;
+        self.end(chat_id).await;
+    }
+
+    /// Records joins to a chat.
+    ///
+    /// Returns `true` if they triggered the raid mode.
+    pub async fn record_joins(&self, chat_id: i64, count: usize) -> bool {
+        if self.mode(chat_id).await != Mode::Auto || self.is_active(chat_id).await {
+            return false;
+        }
+
+        let now = Instant::now();
+        let mut joins = self.joins.lock().await;
+        let recent = joins.entry(chat_id).or_default();
+
+        recent.extend(std::iter::repeat(now).take(count));
+        while recent
+            .front()
+            .is_some_and(|join| now.duration_since(*join) > self.window)
+        {
+            recent.pop_front();
+        }
+
+        if recent.len() < self.threshold {
+            return false;
+        }
+
+        joins.remove(&chat_id);
+        self.active
+            .lock()
+            .await
+            .insert(chat_id, now + self.duration);
+
+        true
+    }
+
+    /// Checks if a chat is under raid mode.
+    pub async fn is_active(&self, chat_id: i64) -> bool {
+        if self.mode(chat_id).await == Mode::On {
+            return true;
+        }
+
+        let mut active = self.active.lock().await;
+        match active.get(&chat_id) {
+            Some(until) if *until > Instant::now() => true,
+            Some(_) => {
+                active.remove(&chat_id);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Ends the raid mode triggered automatically in a chat.
+    ///
+    /// Returns `false` if it was not active.
+    pub async fn end(&self, chat_id: i64) -> bool {
+        self.active.lock().await.remove(&chat_id).is_some()
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Raids {
+    fn name(&self) -> &'static str {
+        "raid modes"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::move_key(&mut data.modes, from, to))
+            .await
+    }
+}
+
+/// The raid mode of a chat.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Mode {
+    /// No raid detection.
+    #[default]
+    Off,
+    /// The raid mode is always on.
+    On,
+    /// The raid mode is enabled on bursts of joins.
+    Auto,
+}
+
+impl Mode {
+    /// Parses a mode from its name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "on" => Some(Self::On),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::On => "on",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+/// The persisted raid modes.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The mode of each chat.
+    modes: HashMap<i64, Mode>,
+}
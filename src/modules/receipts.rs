@@ -0,0 +1,215 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the read receipts module.
+
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The most private messages of a chat waiting to be read, the oldest dropped.
+const MAX_PENDING: usize = 50;
+
+/// The most read events kept, the oldest dropped.
+const MAX_EVENTS: usize = 2000;
+
+/// Records when the outgoing private messages are read, from the read history
+/// updates, to tell how long each user takes to read them.
+#[derive(Clone)]
+pub struct Receipts {
+    /// The persisted messages waiting and the read events.
+    storage: Storage<Data>,
+}
+
+impl Receipts {
+    /// Creates a new `Receipts` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("receipts"),
+        }
+    }
+
+    /// Checks if the read receipts are recorded.
+    pub async fn is_enabled(&self) -> bool {
+        self.storage.read(|data| data.enabled).await
+    }
+
+    /// Enables or disables recording the read receipts.
+    ///
+    /// The messages waiting are forgotten when disabled, the events kept.
+    pub async fn set_enabled(&self, enabled: bool) {
+        self.storage
+            .write(|data| {
+                data.enabled = enabled;
+                if !enabled {
+                    data.pending.clear();
+                }
+            })
+            .await
+    }
+
+    /// Records an outgoing private message, waiting to be read.
+    pub async fn sent(&self, user_id: i64, name: &str, message_id: i32, at: DateTime<Utc>) {
+        self.storage
+            .write(|data| {
+                if !data.enabled {
+                    return;
+                }
+
+                data.names.insert(user_id, name.to_string());
+                let pending = data.pending.entry(user_id).or_default();
+                pending.push((message_id, at));
+                if pending.len() > MAX_PENDING {
+                    let excess = pending.len() - MAX_PENDING;
+                    pending.drain(..excess);
+                }
+            })
+            .await
+    }
+
+    /// Records that a user read the private messages up to `max_id`.
+    ///
+    /// Returns how many messages waiting were read.
+    pub async fn read(&self, user_id: i64, max_id: i32, at: DateTime<Utc>) -> usize {
+        // Avoids rewriting the file for the chats with nothing waiting.
+        if !self
+            .storage
+            .read(|data| data.pending.contains_key(&user_id))
+            .await
+        {
+            return 0;
+        }
+
+        self.storage
+            .write(|data| {
+                let Some(pending) = data.pending.get_mut(&user_id) else {
+                    return 0;
+                };
+
+                let mut read = Vec::new();
+                pending.retain(|(message_id, sent_at)| {
+                    if *message_id > max_id {
+                        return true;
+                    }
+
+                    read.push(Event {
+                        user_id,
+                        sent_at: *sent_at,
+                        read_at: at,
+                    });
+                    false
+                });
+                if pending.is_empty() {
+                    data.pending.remove(&user_id);
+                }
+
+                let count = read.len();
+                data.events.extend(read);
+                if data.events.len() > MAX_EVENTS {
+                    let excess = data.events.len() - MAX_EVENTS;
+                    data.events.drain(..excess);
+                }
+
+                count
+            })
+            .await
+    }
+
+    /// Returns how long each user takes to read the private messages, the ones
+    /// with the most reads first.
+    pub async fn stats(&self) -> Vec<Stats> {
+        let mut stats = self
+            .storage
+            .read(|data| {
+                let mut delays = HashMap::<i64, Vec<Duration>>::new();
+                let mut last_read = HashMap::<i64, DateTime<Utc>>::new();
+                for event in data.events.iter() {
+                    let delay = (event.read_at - event.sent_at).to_std().unwrap_or_default();
+                    delays.entry(event.user_id).or_default().push(delay);
+
+                    let last = last_read.entry(event.user_id).or_insert(event.read_at);
+                    *last = (*last).max(event.read_at);
+                }
+
+                delays
+                    .into_iter()
+                    .map(|(user_id, mut delays)| {
+                        delays.sort();
+
+                        Stats {
+                            name: data
+                                .names
+                                .get(&user_id)
+                                .cloned()
+                                .unwrap_or_else(|| user_id.to_string()),
+                            reads: delays.len(),
+                            average: delays.iter().sum::<Duration>() / delays.len() as u32,
+                            median: delays[delays.len() / 2],
+                            last_read: last_read[&user_id],
+                            user_id,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        stats.sort_by(|a, b| b.reads.cmp(&a.reads).then(a.name.cmp(&b.name)));
+
+        stats
+    }
+
+    /// Returns how many messages are waiting to be read.
+    pub async fn waiting(&self) -> usize {
+        self.storage
+            .read(|data| data.pending.values().map(Vec::len).sum())
+            .await
+    }
+}
+
+/// How long a user takes to read the private messages.
+pub struct Stats {
+    /// The ID of the user.
+    pub user_id: i64,
+    /// The name of the user, when last messaged.
+    pub name: String,
+    /// How many messages were read.
+    pub reads: usize,
+    /// The average time between sending and reading.
+    pub average: Duration,
+    /// The median time between sending and reading.
+    pub median: Duration,
+    /// When the last message was read.
+    pub last_read: DateTime<Utc>,
+}
+
+/// A private message that was read.
+#[derive(Serialize, Deserialize)]
+struct Event {
+    /// The user who read it.
+    user_id: i64,
+    /// When it was sent.
+    sent_at: DateTime<Utc>,
+    /// When it was read.
+    read_at: DateTime<Utc>,
+}
+
+/// The persisted read receipts data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// Whether the read receipts are recorded.
+    enabled: bool,
+    /// The messages of each private chat waiting to be read, with when they
+    /// were sent.
+    pending: HashMap<i64, Vec<(i32, DateTime<Utc>)>>,
+    /// The read messages, the oldest first.
+    events: Vec<Event>,
+    /// The names of the users messaged.
+    names: HashMap<i64, String>,
+}
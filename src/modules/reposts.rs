@@ -0,0 +1,219 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the reposts module.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use image::imageops::FilterType;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The most photos remembered in each chat, the oldest being forgotten.
+const MAX_FINGERPRINTS: usize = 5000;
+
+/// Remembers the photos posted in each chat, detecting the ones posted again.
+#[derive(Clone)]
+pub struct Reposts {
+    /// The persisted settings and fingerprints.
+    storage: Storage<Data>,
+}
+
+impl Reposts {
+    /// Creates a new `Reposts` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("reposts"),
+        }
+    }
+
+    /// Returns the settings of a chat.
+    pub async fn settings(&self, chat_id: i64) -> Settings {
+        self.storage
+            .read(|data| data.settings.get(&chat_id).cloned().unwrap_or_default())
+            .await
+    }
+
+    /// Updates the settings of a chat, returning the new ones.
+    ///
+    /// Disabling the mode forgets the photos of the chat.
+    pub async fn update(&self, chat_id: i64, f: impl FnOnce(&mut Settings)) -> Settings {
+        self.storage
+            .write(|data| {
+                let settings = data.settings.entry(chat_id).or_default();
+                f(settings);
+
+                let settings = settings.clone();
+                if !settings.enabled {
+                    data.fingerprints.remove(&chat_id);
+                }
+
+                settings
+            })
+            .await
+    }
+
+    /// Checks if a photo was already posted in a chat within its window, remembering it if not.
+    ///
+    /// Returns the ID of the message it was first posted in.
+    pub async fn check(&self, chat_id: i64, hash: u64, message_id: i32, date: i64) -> Option<i32> {
+        let settings = self.settings(chat_id).await;
+        let window = settings.days as i64 * 24 * 60 * 60;
+
+        self.storage
+            .write(|data| {
+                let fingerprints = data.fingerprints.entry(chat_id).or_default();
+                fingerprints.retain(|fingerprint| date - fingerprint.date <= window);
+
+                if let Some(original) = fingerprints
+                    .iter()
+                    .find(|fingerprint| similarity(fingerprint.hash, hash) >= settings.threshold)
+                {
+                    return Some(original.message_id);
+                }
+
+                if fingerprints.len() >= MAX_FINGERPRINTS {
+                    fingerprints.remove(0);
+                }
+                fingerprints.push(Fingerprint {
+                    hash,
+                    message_id,
+                    date,
+                });
+
+                None
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Reposts {
+    fn name(&self) -> &'static str {
+        "reposts"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| {
+                migrations::move_key(&mut data.settings, from, to)
+                    + migrations::move_key(&mut data.fingerprints, from, to)
+            })
+            .await
+    }
+}
+
+/// Computes the perceptual hash of an image, which changes little when it is
+/// resized, recompressed or slightly edited.
+///
+/// It is a difference hash: each bit says if a pixel of the shrunk grayscale
+/// image is brighter than the one on its right.
+pub fn fingerprint(bytes: &[u8]) -> Option<u64> {
+    let image = image::load_from_memory(bytes)
+        .ok()?
+        .resize_exact(9, 8, FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if image.get_pixel(x, y)[0] > image.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+
+    Some(hash)
+}
+
+/// Returns how similar two hashes are, as a percentage.
+fn similarity(a: u64, b: u64) -> u8 {
+    (100 - (a ^ b).count_ones() * 100 / 64) as u8
+}
+
+/// What is done with an unwanted photo.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// The photo is deleted.
+    #[default]
+    Delete,
+    /// The photo is answered with a warning.
+    Flag,
+}
+
+impl Action {
+    /// Parses an action from its name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "delete" => Some(Self::Delete),
+            "flag" => Some(Self::Flag),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the action.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Flag => "flag",
+        }
+    }
+}
+
+/// The reposts settings of a chat.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Settings {
+    /// Whether the reposts are detected.
+    pub enabled: bool,
+    /// What is done with a repost.
+    pub action: Action,
+    /// For how many days a photo is remembered.
+    pub days: u32,
+    /// How similar two photos must be to be the same, as a percentage.
+    pub threshold: u8,
+    /// Whether the admins can repost.
+    pub exempt_admins: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: Action::default(),
+            days: 7,
+            threshold: 90,
+            exempt_admins: true,
+        }
+    }
+}
+
+/// A photo posted in a chat.
+#[derive(Serialize, Deserialize)]
+struct Fingerprint {
+    /// The perceptual hash of the photo.
+    hash: u64,
+    /// The ID of the message it was posted in.
+    message_id: i32,
+    /// When it was posted, as a timestamp.
+    date: i64,
+}
+
+/// The persisted reposts data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The settings of each chat.
+    settings: HashMap<i64, Settings>,
+    /// The photos posted in each chat, the oldest first.
+    fingerprints: HashMap<i64, Vec<Fingerprint>>,
+}
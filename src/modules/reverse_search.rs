@@ -0,0 +1,367 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the reverse image search module.
+
+use std::{io::Cursor, process::Stdio, sync::Arc, time::Duration};
+
+use ferogram::Result;
+use image::ImageFormat;
+use reqwest::{
+    header::USER_AGENT,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{process::Command, task::JoinSet};
+use uuid::Uuid;
+
+/// The URL of the SauceNAO API.
+const SAUCENAO_URL: &str = "https://saucenao.com/search.php";
+/// The URL of the Yandex Images search by image.
+const YANDEX_URL: &str = "https://yandex.com/images/search";
+/// The URL of the TinEye search by image.
+const TINEYE_URL: &str = "https://tineye.com/api/v1/result_json/";
+
+/// The user agent of the requests, as the engines reject the unknown ones.
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+/// How many matches each engine gives.
+const MATCHES_PER_ENGINE: usize = 5;
+
+/// How long ffmpeg can take to extract a frame.
+const FFMPEG_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A reverse image search engine.
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    /// SauceNAO, good for artworks and anime, which needs an API key.
+    SauceNao,
+    /// Yandex Images.
+    Yandex,
+    /// TinEye.
+    TinEye,
+}
+
+impl Engine {
+    /// Parses an engine from its name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "saucenao" | "sauce_nao" => Some(Self::SauceNao),
+            "yandex" => Some(Self::Yandex),
+            "tineye" | "tin_eye" => Some(Self::TinEye),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the engine.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SauceNao => "SauceNAO",
+            Self::Yandex => "Yandex",
+            Self::TinEye => "TinEye",
+        }
+    }
+}
+
+/// A match of a search.
+#[derive(Clone)]
+pub struct Match {
+    /// The engine that found it.
+    pub engine: Engine,
+    /// The page the image is on.
+    pub url: String,
+    /// The title of the page.
+    pub title: String,
+    /// The URL of the thumbnail of the found image.
+    pub thumbnail: Option<String>,
+    /// How similar the found image is, as a percentage, if the engine tells.
+    pub similarity: Option<f32>,
+}
+
+/// Searches the images on several engines.
+#[derive(Clone)]
+pub struct ReverseSearch {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The engines searched when none is chosen.
+    engines: Vec<Engine>,
+    /// The SauceNAO API key.
+    saucenao_key: Option<String>,
+    /// The path of the ffmpeg binary, which extracts the frames of the videos.
+    ffmpeg_path: Arc<String>,
+}
+
+impl ReverseSearch {
+    /// Creates a new `ReverseSearch` instance.
+    pub fn new(engines: Vec<Engine>, saucenao_key: Option<String>, ffmpeg_path: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            engines,
+            saucenao_key,
+            ffmpeg_path: Arc::new(ffmpeg_path),
+        }
+    }
+
+    /// Checks if an engine can be used.
+    pub fn is_available(&self, engine: Engine) -> bool {
+        engine != Engine::SauceNao || self.saucenao_key.is_some()
+    }
+
+    /// Searches an image on an engine or, without one, on all the default engines.
+    ///
+    /// The matches are sorted by similarity, the ones without it last. Fails only if
+    /// every engine failed.
+    pub async fn search(&self, bytes: Arc<Vec<u8>>, engine: Option<Engine>) -> Result<Vec<Match>> {
+        let engines = match engine {
+            Some(engine) => vec![engine],
+            None => self
+                .engines
+                .iter()
+                .copied()
+                .filter(|engine| self.is_available(*engine))
+                .collect(),
+        };
+
+        let mut tasks = JoinSet::new();
+        for engine in engines {
+            let (search, bytes) = (self.clone(), Arc::clone(&bytes));
+
+            tasks.spawn(async move {
+                let result = match engine {
+                    Engine::SauceNao => search.saucenao(&bytes).await,
+                    Engine::Yandex => search.yandex(&bytes).await,
+                    Engine::TinEye => search.tineye(&bytes).await,
+                };
+
+                (engine, result.map_err(|e| e.to_string()))
+            });
+        }
+
+        let mut matches = Vec::new();
+        let mut error = None;
+        while let Some(joined) = tasks.join_next().await {
+            match joined? {
+                (_, Ok(found)) => matches.extend(found),
+                (engine, Err(e)) => {
+                    log::error!("Failed to search on {}: {}", engine.name(), e);
+                    error = Some(e);
+                }
+            }
+        }
+
+        if let Some(e) = error.filter(|_| matches.is_empty()) {
+            return Err(e.into());
+        }
+
+        matches.sort_by(|a, b| {
+            b.similarity
+                .unwrap_or(-1.0)
+                .total_cmp(&a.similarity.unwrap_or(-1.0))
+        });
+
+        Ok(matches)
+    }
+
+    /// Turns a visual media into an image the engines take, given its MIME type.
+    ///
+    /// JPEG and PNG images are kept as they are, the other images, such as the
+    /// stickers, are converted to PNG and a frame is extracted from the videos and
+    /// the animations.
+    pub async fn prepare(&self, bytes: Arc<Vec<u8>>, mime_type: &str) -> Result<Arc<Vec<u8>>> {
+        match mime_type {
+            "image/jpeg" | "image/png" => Ok(bytes),
+            "image/gif" => Ok(Arc::new(self.extract_frame(&bytes).await?)),
+            mime_type if mime_type.starts_with("image/") => {
+                let png = tokio::task::spawn_blocking(move || to_png(&bytes)).await??;
+                Ok(Arc::new(png))
+            }
+            mime_type if mime_type.starts_with("video/") => {
+                Ok(Arc::new(self.extract_frame(&bytes).await?))
+            }
+            _ => Err(format!("Cannot search a {} media", mime_type).into()),
+        }
+    }
+
+    /// Extracts a representative frame of a video or an animation, as PNG.
+    ///
+    /// The video is written to a temporary file, as ffmpeg cannot seek a pipe and
+    /// the index of a MP4 may be at its end.
+    async fn extract_frame(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let path = std::env::temp_dir().join(format!("grymbb-{}", Uuid::new_v4()));
+        tokio::fs::write(&path, bytes).await?;
+
+        let output = Command::new(self.ffmpeg_path.as_str())
+            .args(["-v", "error", "-i"])
+            .arg(&path)
+            .args(["-vf", "thumbnail", "-frames:v", "1"])
+            .args(["-f", "image2pipe", "-c:v", "png", "pipe:1"])
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(FFMPEG_TIMEOUT, output).await;
+        let _ = tokio::fs::remove_file(&path).await;
+
+        let output = output.map_err(|_| "ffmpeg took too long to extract a frame")??;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Err(format!(
+                "ffmpeg failed to extract a frame: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+
+        Ok(output.stdout)
+    }
+
+    /// Searches an image on SauceNAO.
+    async fn saucenao(&self, bytes: &[u8]) -> Result<Vec<Match>> {
+        let key = self
+            .saucenao_key
+            .as_deref()
+            .ok_or("The SauceNAO API key is not set")?;
+
+        let numres = MATCHES_PER_ENGINE.to_string();
+        let json = self
+            .client
+            .post(SAUCENAO_URL)
+            .query(&[
+                ("output_type", "2"),
+                ("numres", numres.as_str()),
+                ("api_key", key),
+            ])
+            .multipart(Form::new().part("file", image_part(bytes)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        Ok(json["results"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|result| {
+                let header = &result["header"];
+                let data = &result["data"];
+
+                let url = data["ext_urls"][0].as_str()?.to_string();
+                let title = ["title", "source", "material", "eng_name"]
+                    .iter()
+                    .find_map(|key| data[key].as_str().filter(|title| !title.is_empty()))
+                    .or_else(|| header["index_name"].as_str())
+                    .unwrap_or(url.as_str())
+                    .to_string();
+
+                Some(Match {
+                    engine: Engine::SauceNao,
+                    url,
+                    title,
+                    thumbnail: header["thumbnail"].as_str().map(str::to_string),
+                    similarity: header["similarity"]
+                        .as_str()
+                        .and_then(|similarity| similarity.parse().ok()),
+                })
+            })
+            .collect())
+    }
+
+    /// Searches an image on Yandex Images.
+    ///
+    /// Yandex has no similarity scores, so the match is its results page.
+    async fn yandex(&self, bytes: &[u8]) -> Result<Vec<Match>> {
+        let json = self
+            .client
+            .post(YANDEX_URL)
+            .header(USER_AGENT, BROWSER_USER_AGENT)
+            .query(&[
+                ("rpt", "imageview"),
+                ("format", "json"),
+                (
+                    "request",
+                    r#"{"blocks":[{"block":"b-page_type_search-by-image__link"}]}"#,
+                ),
+            ])
+            .multipart(Form::new().part("upfile", image_part(bytes)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let params = json["blocks"][0]["params"]["url"]
+            .as_str()
+            .ok_or("Yandex gave no results page")?;
+
+        Ok(vec![Match {
+            engine: Engine::Yandex,
+            url: format!("{0}?{1}&rpt=imageview", YANDEX_URL, params),
+            title: "Yandex Images".to_string(),
+            thumbnail: None,
+            similarity: None,
+        }])
+    }
+
+    /// Searches an image on TinEye.
+    async fn tineye(&self, bytes: &[u8]) -> Result<Vec<Match>> {
+        let json = self
+            .client
+            .post(TINEYE_URL)
+            .header(USER_AGENT, BROWSER_USER_AGENT)
+            .query(&[("sort", "score"), ("order", "desc")])
+            .multipart(Form::new().part("image", image_part(bytes)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        Ok(json["matches"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(MATCHES_PER_ENGINE)
+            .filter_map(|found| {
+                let backlink = &found["backlinks"][0];
+
+                Some(Match {
+                    engine: Engine::TinEye,
+                    url: backlink["backlink"].as_str()?.to_string(),
+                    title: found["domain"]
+                        .as_str()
+                        .or_else(|| backlink["url"].as_str())?
+                        .to_string(),
+                    thumbnail: found["image_url"].as_str().map(str::to_string),
+                    similarity: found["query_match_percent"].as_f64().map(|p| p as f32),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Converts an image to PNG.
+fn to_png(bytes: &[u8]) -> image::ImageResult<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image::load_from_memory(bytes)?.write_to(&mut png, ImageFormat::Png)?;
+
+    Ok(png.into_inner())
+}
+
+/// Builds the multipart part of an image, either JPEG or PNG.
+fn image_part(bytes: &[u8]) -> Part {
+    let (file_name, mime_type) = match image::guess_format(bytes) {
+        Ok(ImageFormat::Png) => ("image.png", "image/png"),
+        _ => ("image.jpg", "image/jpeg"),
+    };
+
+    Part::bytes(bytes.to_vec())
+        .file_name(file_name)
+        .mime_str(mime_type)
+        .expect("valid mime type")
+}
@@ -0,0 +1,72 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the RSS feeds module.
+
+use async_trait::async_trait;
+use ferogram::Result;
+use maplit::hashmap;
+
+use super::{digest::Widget, i18n::I18n};
+use crate::utils::escape_html;
+
+/// The RSS headlines widget.
+pub struct Feeds {
+    /// The URLs of the feeds.
+    urls: Vec<String>,
+    /// The number of headlines taken from each feed.
+    headlines: usize,
+}
+
+impl Feeds {
+    /// Creates a new `Feeds` instance.
+    pub fn new(urls: Vec<String>, headlines: usize) -> Self {
+        Self { urls, headlines }
+    }
+
+    /// Fetches a feed.
+    pub async fn fetch(url: &str) -> Result<rss::Channel> {
+        let bytes = reqwest::get(url).await?.bytes().await?;
+        let channel = rss::Channel::read_from(&bytes[..])?;
+
+        Ok(channel)
+    }
+}
+
+#[async_trait]
+impl Widget for Feeds {
+    fn name(&self) -> &'static str {
+        "rss"
+    }
+
+    async fn render(&self, i18n: &I18n) -> Result<String> {
+        let mut headlines = String::new();
+
+        for url in self.urls.iter() {
+            let channel = match Self::fetch(url).await {
+                Ok(channel) => channel,
+                Err(e) => {
+                    log::error!("Failed to fetch feed {}: {}", url, e);
+                    continue;
+                }
+            };
+
+            headlines += &format!("\n<b>{}</b>", escape_html(channel.title()));
+            for item in channel.items().iter().take(self.headlines) {
+                let title = escape_html(item.title().unwrap_or("?"));
+
+                match item.link() {
+                    Some(link) => headlines += &format!("\n• <a href=\"{0}\">{1}</a>", link, title),
+                    None => headlines += &format!("\n• {}", title),
+                }
+            }
+        }
+
+        Ok(i18n.translate_with_args("digest_rss", hashmap! { "headlines" => headlines }))
+    }
+}
@@ -0,0 +1,397 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the sandbox module, which runs untrusted code.
+
+use std::{
+    path::Path,
+    process::Stdio,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ferogram::Result;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Command,
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+/// How long the output is still read after the processes were killed.
+const KILL_GRACE: Duration = Duration::from_secs(1);
+
+/// The size of the chunks the output is read in.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Where the working directory is mounted inside a container.
+const CONTAINER_WORKDIR: &str = "/sandbox";
+
+/// What is given each chunk of the outputs of a job.
+type OnOutput = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// What the code runs inside of.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Wrapper {
+    /// Nothing, only the limits of the sandbox itself.
+    #[default]
+    None,
+    /// firejail, without network and with a private `/tmp`.
+    Firejail,
+    /// bubblewrap, with the system read-only and every namespace unshared.
+    Bubblewrap,
+    /// A throwaway container, without network.
+    Container,
+}
+
+/// A program to run in the sandbox.
+pub struct Job {
+    /// The program.
+    program: String,
+    /// Its arguments.
+    args: Vec<String>,
+    /// The environment variables set for it.
+    envs: Vec<(String, String)>,
+    /// The files written to the working directory first, with their contents.
+    files: Vec<(String, String)>,
+}
+
+impl Job {
+    /// Creates a new job running `program`.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Adds an argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Writes a file to the working directory before running.
+    pub fn file(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.files.push((name.into(), content.into()));
+        self
+    }
+}
+
+/// How a job ended.
+pub enum Exit {
+    /// It exited with a code.
+    Code(i32),
+    /// It was killed by a signal not sent by the sandbox.
+    Signal,
+    /// It took too long and was killed.
+    TimedOut,
+}
+
+/// The result of a job.
+pub struct Run {
+    /// How it ended.
+    pub exit: Exit,
+    /// Its standard output.
+    pub stdout: String,
+    /// Its standard error.
+    pub stderr: String,
+    /// Whether an output was cut for being too long.
+    pub truncated: bool,
+    /// How long it ran.
+    pub elapsed: Duration,
+}
+
+impl Run {
+    /// Checks if it exited successfully.
+    pub fn success(&self) -> bool {
+        matches!(self.exit, Exit::Code(0))
+    }
+}
+
+/// Runs the untrusted code in a temporary directory, with a deadline and limits.
+#[derive(Clone)]
+pub struct Sandbox {
+    /// What the code runs inside of.
+    wrapper: Wrapper,
+    /// The extra arguments given to the wrapper.
+    wrapper_args: Arc<Vec<String>>,
+    /// The container runtime, such as `docker` or `podman`.
+    container_runtime: String,
+    /// The image of the containers.
+    container_image: String,
+    /// How long a job can run.
+    timeout: Duration,
+    /// The most bytes kept of each output.
+    max_output: usize,
+    /// The most memory a job can take, in megabytes, zero for no limit.
+    memory_limit: u64,
+}
+
+impl Sandbox {
+    /// Creates a new `Sandbox` instance.
+    pub fn new(
+        wrapper: Wrapper,
+        wrapper_args: Vec<String>,
+        container_runtime: String,
+        container_image: String,
+        timeout: Duration,
+        max_output: usize,
+        memory_limit: u64,
+    ) -> Self {
+        Self {
+            wrapper,
+            wrapper_args: Arc::new(wrapper_args),
+            container_runtime,
+            container_image,
+            timeout,
+            max_output,
+            memory_limit,
+        }
+    }
+
+    /// Returns how long a job can run.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Runs a job in a new temporary directory, removed afterwards.
+    ///
+    /// Each chunk of its outputs is given to `on_output` as soon as it is read. On
+    /// timeout its whole process group is killed, and the output until then is
+    /// returned.
+    pub async fn run(
+        &self,
+        job: Job,
+        on_output: impl Fn(&str) + Send + Sync + 'static,
+    ) -> Result<Run> {
+        let workdir = std::env::temp_dir().join(format!("grymbb-sandbox-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&workdir).await?;
+
+        let result = self.run_in(&job, &workdir, Arc::new(on_output)).await;
+        if let Err(e) = tokio::fs::remove_dir_all(&workdir).await {
+            log::error!("Failed to remove the sandbox {:?}: {}", workdir, e);
+        }
+
+        result
+    }
+
+    /// Runs a job in a working directory.
+    async fn run_in(&self, job: &Job, workdir: &Path, on_output: OnOutput) -> Result<Run> {
+        for (name, content) in job.files.iter() {
+            tokio::fs::write(workdir.join(name), content).await?;
+        }
+
+        let container = format!("grymbb-{}", Uuid::new_v4());
+        let mut command = self.command(job, workdir, &container);
+        command
+            .current_dir(workdir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .kill_on_drop(true);
+
+        // The container runtime enforces the memory limit itself.
+        if self.memory_limit > 0 && !matches!(self.wrapper, Wrapper::Container) {
+            let bytes = self.memory_limit * 1024 * 1024;
+
+            // SAFETY: `setrlimit` is async-signal-safe.
+            unsafe {
+                command.pre_exec(move || {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes,
+                        rlim_max: bytes,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+
+                    Ok(())
+                });
+            }
+        }
+
+        let time = Instant::now();
+        let mut child = command.spawn()?;
+        let pid = child.id();
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        // Read apart, so neither pipe fills up and blocks the process.
+        let stdout = tokio::task::spawn(read_capped(stdout, self.max_output, on_output.clone()));
+        let stderr = tokio::task::spawn(read_capped(stderr, self.max_output, on_output));
+
+        let exit = match tokio::time::timeout(self.timeout, child.wait()).await {
+            Ok(status) => match status?.code() {
+                Some(code) => Exit::Code(code),
+                None => Exit::Signal,
+            },
+            Err(_) => {
+                if let Some(pid) = pid {
+                    // SAFETY: the group was created with the child, whose ID it has.
+                    unsafe {
+                        libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+                    }
+                }
+                if let Wrapper::Container = self.wrapper {
+                    // Killing the client leaves the container running.
+                    let _ = Command::new(&self.container_runtime)
+                        .args(["kill", container.as_str()])
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status()
+                        .await;
+                }
+                let _ = child.wait().await;
+
+                Exit::TimedOut
+            }
+        };
+        let elapsed = time.elapsed();
+
+        let (stdout, stdout_truncated) = join_output(stdout).await?;
+        let (stderr, stderr_truncated) = join_output(stderr).await?;
+
+        Ok(Run {
+            exit,
+            stdout,
+            stderr,
+            truncated: stdout_truncated || stderr_truncated,
+            elapsed,
+        })
+    }
+
+    /// Builds the command of a job, inside the wrapper.
+    fn command(&self, job: &Job, workdir: &Path, container: &str) -> Command {
+        let workdir = workdir.to_string_lossy();
+
+        let mut command = match self.wrapper {
+            Wrapper::None => {
+                let mut command = Command::new(&job.program);
+                command.args(&job.args).envs(job.envs.iter().cloned());
+
+                return command;
+            }
+            Wrapper::Firejail => {
+                let mut command = Command::new("firejail");
+                command
+                    .args(["--quiet", "--net=none", "--private-tmp", "--noroot"])
+                    .envs(job.envs.iter().cloned());
+                command
+            }
+            Wrapper::Bubblewrap => {
+                let mut command = Command::new("bwrap");
+                command
+                    .args(["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc"])
+                    .args(["--tmpfs", "/tmp", "--bind", &workdir, &workdir])
+                    .args(["--chdir", &workdir, "--unshare-all", "--die-with-parent"]);
+                for (key, value) in job.envs.iter() {
+                    command.args(["--setenv", key, value]);
+                }
+                command
+            }
+            Wrapper::Container => {
+                let mut command = Command::new(&self.container_runtime);
+                command
+                    .args(["run", "--rm", "--network", "none", "--name", container])
+                    .arg("-v")
+                    .arg(format!("{0}:{1}", workdir, CONTAINER_WORKDIR))
+                    .args(["-w", CONTAINER_WORKDIR]);
+                if self.memory_limit > 0 {
+                    command.arg(format!("--memory={}m", self.memory_limit));
+                }
+                for (key, value) in job.envs.iter() {
+                    command.arg("-e").arg(format!("{0}={1}", key, value));
+                }
+                command
+                    .args(self.wrapper_args.iter())
+                    .arg(&self.container_image)
+                    .arg(&job.program)
+                    .args(&job.args);
+
+                return command;
+            }
+        };
+
+        command
+            .args(self.wrapper_args.iter())
+            .arg("--")
+            .arg(&job.program)
+            .args(&job.args);
+
+        command
+    }
+}
+
+/// Reads an output up to `limit` bytes, draining the rest so the process does not
+/// block on a full pipe.
+///
+/// Returns the output and whether it was cut.
+async fn read_capped(
+    mut reader: impl AsyncRead + Unpin,
+    limit: usize,
+    on_output: OnOutput,
+) -> Result<(String, bool)> {
+    let mut output = Vec::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut truncated = false;
+
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+
+        let left = limit.saturating_sub(output.len());
+        if read > left {
+            truncated = true;
+        }
+        let kept = &buffer[..read.min(left)];
+        if !kept.is_empty() {
+            on_output(&String::from_utf8_lossy(kept));
+        }
+        output.extend_from_slice(kept);
+    }
+
+    Ok((String::from_utf8_lossy(&output).into_owned(), truncated))
+}
+
+/// Waits for an output being read.
+///
+/// A process that left the group may still hold the pipe, so it is given up on
+/// after a while.
+async fn join_output(mut task: JoinHandle<Result<(String, bool)>>) -> Result<(String, bool)> {
+    match tokio::time::timeout(KILL_GRACE, &mut task).await {
+        Ok(output) => output?,
+        Err(_) => {
+            task.abort();
+            Ok((String::new(), false))
+        }
+    }
+}
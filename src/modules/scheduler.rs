@@ -0,0 +1,350 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the scheduler module.
+
+use std::{str::FromStr, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use grammers_client::{button, reply_markup, InputMessage};
+use serde::{Deserialize, Serialize};
+
+use super::{dates::Recurrence, shutdown::Shutdown, storage::Storage};
+use crate::{Action, Priority, Recipient, Sender};
+
+/// How often the due jobs are checked.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The most delivered reminders kept to be snoozed.
+const MAX_SNOOZABLE: usize = 50;
+
+/// The delays of the snooze buttons, in minutes, with their labels.
+const SNOOZE_DELAYS: &[(&str, u32)] = &[("10m", 10), ("1h", 60), ("1d", 24 * 60)];
+
+/// Runs one-shot and recurring jobs, persisted across restarts.
+///
+/// The jobs are actions of the inter-client channel, sent when due. Only the
+/// actions with a stored form can be scheduled.
+#[derive(Clone)]
+pub struct Scheduler {
+    /// The persisted jobs.
+    storage: Storage<Data>,
+}
+
+impl Scheduler {
+    /// Creates a new `Scheduler` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("scheduler"),
+        }
+    }
+
+    /// Schedules an action, taken by the given client and in the given forum
+    /// topic, returning the ID of the job.
+    ///
+    /// Returns `None` if the schedule never fires.
+    pub async fn schedule(
+        &self,
+        when: When,
+        recipient: Recipient,
+        topic: Option<i32>,
+        action: Action,
+    ) -> Option<u32> {
+        let next = when.next_after(Utc::now())?;
+
+        let id = self
+            .storage
+            .write(|data| {
+                data.last_id += 1;
+                data.jobs.push(Job {
+                    id: data.last_id,
+                    when,
+                    next,
+                    recipient,
+                    topic,
+                    action,
+                });
+
+                data.last_id
+            })
+            .await;
+
+        Some(id)
+    }
+
+    /// Cancels a job.
+    ///
+    /// Returns `false` if the job was not found.
+    pub async fn cancel(&self, id: u32) -> bool {
+        self.cancel_where(id, |_| true).await
+    }
+
+    /// Cancels a job if it matches a predicate.
+    ///
+    /// Returns `false` if no such job was found.
+    pub async fn cancel_where(&self, id: u32, f: impl Fn(&Job) -> bool) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.jobs.len();
+                data.jobs.retain(|job| job.id != id || !f(job));
+
+                data.jobs.len() != len
+            })
+            .await
+    }
+
+    /// Edits a job if it matches a predicate, computing its next run again.
+    ///
+    /// Returns the edited job, or `None` if no such job was found or it would
+    /// never run.
+    pub async fn edit_where(
+        &self,
+        id: u32,
+        f: impl Fn(&Job) -> bool,
+        edit: impl FnOnce(&mut Job),
+    ) -> Option<Job> {
+        self.storage
+            .write(|data| {
+                let job = data.jobs.iter_mut().find(|job| job.id == id && f(job))?;
+
+                let mut edited = job.clone();
+                edit(&mut edited);
+                edited.next = edited.when.next_after(Utc::now())?;
+                *job = edited.clone();
+
+                Some(edited)
+            })
+            .await
+    }
+
+    /// Schedules a delivered reminder again, after a delay.
+    ///
+    /// Returns the ID of the new job and when it runs, or `None` if the reminder
+    /// was already snoozed or is too old.
+    pub async fn snooze(&self, id: u32, minutes: u32) -> Option<(u32, DateTime<Utc>)> {
+        let action = self
+            .storage
+            .write(|data| {
+                let index = data.snoozable.iter().position(|(job, _)| *job == id)?;
+
+                Some(data.snoozable.remove(index).1)
+            })
+            .await?;
+
+        let at = Utc::now() + TimeDelta::minutes(minutes as i64);
+        let id = self
+            .schedule(When::At(at), Recipient::Bot, None, action)
+            .await?;
+
+        Some((id, at))
+    }
+
+    /// Returns the pending jobs, the next to run first.
+    pub async fn jobs(&self) -> Vec<Job> {
+        self.jobs_where(|_| true).await
+    }
+
+    /// Returns the pending jobs matching a predicate, the next to run first.
+    pub async fn jobs_where(&self, f: impl Fn(&Job) -> bool) -> Vec<Job> {
+        let mut jobs = self
+            .storage
+            .read(|data| {
+                data.jobs
+                    .iter()
+                    .filter(|job| f(job))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        jobs.sort_by_key(|job| job.next);
+
+        jobs
+    }
+
+    /// Starts running the due jobs.
+    pub fn start(self, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            let mut interval = tokio::time::interval(TICK_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                for job in self.take_due(Utc::now()).await {
+                    // The jobs run in the background, after the interactive messages.
+                    let message = job.into_message().with_priority(Priority::Low);
+                    if let Err(e) = tx.send(message).await {
+                        log::error!("Failed to send the scheduled job: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Takes the due jobs, rescheduling the recurring ones.
+    ///
+    /// The reminders are kept to be snoozed.
+    async fn take_due(&self, now: DateTime<Utc>) -> Vec<Job> {
+        // Avoids rewriting the file on every tick.
+        if !self
+            .storage
+            .read(|data| data.jobs.iter().any(|job| job.next <= now))
+            .await
+        {
+            return Vec::new();
+        }
+
+        self.storage
+            .write(|data| {
+                let mut due = Vec::new();
+
+                data.jobs.retain_mut(|job| {
+                    if job.next > now {
+                        return true;
+                    }
+
+                    due.push(job.clone());
+                    match job.when.next_after(now) {
+                        Some(next) => {
+                            job.next = next;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+
+                for job in due.iter().filter(|job| job.is_reminder()) {
+                    data.snoozable.retain(|(id, _)| *id != job.id);
+                    data.snoozable.push((job.id, job.action.clone()));
+                }
+                if data.snoozable.len() > MAX_SNOOZABLE {
+                    let excess = data.snoozable.len() - MAX_SNOOZABLE;
+                    data.snoozable.drain(..excess);
+                }
+
+                due
+            })
+            .await
+    }
+}
+
+/// When a job runs.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum When {
+    /// Once, at the given time.
+    At(DateTime<Utc>),
+    /// At every match of a cron expression, with seconds, in UTC.
+    Cron(String),
+    /// At every match of a recurrence rule, in local time.
+    Every(Recurrence),
+}
+
+impl When {
+    /// Returns the first run after the given time.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::At(at) if *at > after => Some(*at),
+            // A past time still runs once, as soon as possible.
+            Self::At(_) => Some(after),
+            Self::Cron(expr) => cron::Schedule::from_str(expr).ok()?.after(&after).next(),
+            Self::Every(recurrence) => recurrence.next_after(after),
+        }
+    }
+
+    /// Checks if the job runs more than once.
+    pub fn is_recurring(&self) -> bool {
+        matches!(self, Self::Cron(_) | Self::Every(_))
+    }
+}
+
+/// A scheduled job.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// The ID of the job.
+    pub id: u32,
+    /// When the job runs.
+    pub when: When,
+    /// The next run.
+    pub next: DateTime<Utc>,
+    /// The client that takes the action.
+    pub recipient: Recipient,
+    /// The forum topic the message is sent to.
+    pub topic: Option<i32>,
+    /// The action taken, in its stored form.
+    pub action: Action,
+}
+
+impl Job {
+    /// Returns the text of the action, empty if it has none.
+    pub fn text(&self) -> &str {
+        match &self.action {
+            Action::SendHtml(_, text) | Action::NotifyHtml(text) | Action::Remind(text, _) => text,
+            _ => "",
+        }
+    }
+
+    /// Checks if the job is a reminder.
+    pub fn is_reminder(&self) -> bool {
+        matches!(self.action, Action::Remind(..))
+    }
+
+    /// Checks if the job was scheduled by the bot itself, not by a command.
+    pub fn is_internal(&self) -> bool {
+        matches!(self.action, Action::DeleteMessages(..))
+    }
+
+    /// Turns the job into a message of the inter-client channel.
+    ///
+    /// The reminders get the buttons to snooze them.
+    fn into_message(self) -> crate::Message {
+        let message = match self.recipient {
+            Recipient::Bot => crate::Message::to_bot(),
+            Recipient::User(account) => crate::Message::to_user(account),
+        };
+        let message = message.in_topic(self.topic);
+
+        match self.action {
+            Action::Remind(text, link) => {
+                let buttons = SNOOZE_DELAYS
+                    .iter()
+                    .map(|(label, minutes)| {
+                        button::inline(
+                            format!("💤 {}", label),
+                            format!("remind snooze {0} {1}", self.id, minutes),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                message.notify(
+                    InputMessage::html(reminder_text(&text, link.as_deref()))
+                        .reply_markup(&reply_markup::inline(vec![buttons])),
+                )
+            }
+            action => message.with_action(action),
+        }
+    }
+}
+
+/// Formats the text of a reminder, with the link to the message it was set from.
+pub fn reminder_text(text: &str, link: Option<&str>) -> String {
+    match link {
+        Some(link) => format!("⏰ {}\n\n{}", text, link),
+        None => format!("⏰ {}", text),
+    }
+}
+
+/// The persisted jobs.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The last ID given to a job.
+    last_id: u32,
+    /// The pending jobs.
+    jobs: Vec<Job>,
+    /// The last delivered reminders not snoozed yet, with the IDs of their jobs.
+    #[serde(default)]
+    snoozable: Vec<(u32, Action)>,
+}
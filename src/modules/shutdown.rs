@@ -0,0 +1,135 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the shutdown coordinator module.
+
+use std::{
+    future::Future,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+use super::panics;
+
+/// How long the tasks are waited for once the shutdown begins.
+const GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// The delay before restarting a supervised task, doubled at each failure in a row.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// The longest delay before restarting a supervised task.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+/// How long a supervised task must run for its failures to be forgotten.
+const STABLE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// The coordinator shared by the background tasks.
+static SHARED: LazyLock<Shutdown> = LazyLock::new(|| Shutdown {
+    token: CancellationToken::new(),
+    tracker: TaskTracker::new(),
+});
+
+/// Stops the background tasks once the bot is asked to stop, waiting for the
+/// ones finishing their work.
+#[derive(Clone)]
+pub struct Shutdown {
+    /// Cancelled once the shutdown begins.
+    token: CancellationToken,
+    /// The tasks waited for before exiting.
+    tracker: TaskTracker,
+}
+
+impl Shutdown {
+    /// Returns the shared coordinator, as the tasks are started by many modules.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Spawns a background task, stopped where it is waiting once the shutdown
+    /// begins.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.token.clone();
+
+        self.tracker.spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                _ = task => {}
+            }
+        });
+    }
+
+    /// Spawns a task that is restarted when it panics or fails, until it ends on
+    /// its own, as the one draining the channel between the clients.
+    ///
+    /// The task is made again by `make`, after a delay growing with the failures
+    /// in a row. It is not stopped once the shutdown begins, but waited for, and
+    /// no longer restarted.
+    pub fn supervise<F, Fut>(&self, name: &'static str, mut make: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let token = self.token.clone();
+
+        self.tracker.spawn(async move {
+            let mut failures = 0;
+
+            loop {
+                let started = Instant::now();
+                // Spawned apart, so a panic ends it and not the supervisor.
+                let error = match tokio::spawn(make()).await {
+                    Ok(Ok(())) => return,
+                    Ok(Err(e)) => e,
+                    Err(e) if e.is_panic() => panics::message(&*e.into_panic()),
+                    Err(_) => return,
+                };
+
+                if token.is_cancelled() {
+                    log::error!("The {0} failed while shutting down: {1}", name, error);
+                    return;
+                }
+
+                if started.elapsed() >= STABLE_AFTER {
+                    failures = 0;
+                }
+                let delay = (RESTART_DELAY * 2u32.pow(failures.min(6))).min(MAX_RESTART_DELAY);
+                failures += 1;
+                log::error!(
+                    "The {0} failed, restarting it in {1:?}: {2}",
+                    name,
+                    delay,
+                    error
+                );
+
+                tokio::select! {
+                    _ = token.cancelled() => return,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+        });
+    }
+
+    /// Begins the shutdown, stopping the background tasks.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Waits for the tasks to end, up to a grace period.
+    ///
+    /// Returns `false` if some were still running.
+    pub async fn wait(&self) -> bool {
+        self.tracker.close();
+
+        tokio::time::timeout(GRACE_PERIOD, self.tracker.wait())
+            .await
+            .is_ok()
+    }
+}
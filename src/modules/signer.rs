@@ -0,0 +1,76 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the signer module.
+
+use std::sync::Arc;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The number of bytes of the MAC appended to the payloads.
+const MAC_LENGTH: usize = 16;
+
+/// Signs the payloads exchanged between the clients through Telegram, so
+/// third parties cannot forge or race them.
+#[derive(Clone)]
+pub struct Signer {
+    /// The shared secret.
+    key: Arc<Vec<u8>>,
+}
+
+impl Signer {
+    /// Creates a new `Signer` instance.
+    ///
+    /// An empty secret is replaced by a random one, valid until the restart.
+    pub fn new(secret: &str) -> Self {
+        let key = if secret.is_empty() {
+            (0..32).map(|_| rand::random::<u8>()).collect()
+        } else {
+            secret.as_bytes().to_vec()
+        };
+
+        Self { key: Arc::new(key) }
+    }
+
+    /// Returns a signer whose key also has a random nonce, so what it signs is
+    /// only valid until the restart, even with a fixed secret.
+    pub fn for_run(&self) -> Self {
+        let mut key = self.key.to_vec();
+        key.extend((0..16).map(|_| rand::random::<u8>()));
+
+        Self { key: Arc::new(key) }
+    }
+
+    /// Signs a payload, returning it with its MAC appended.
+    pub fn sign(&self, payload: &str) -> String {
+        let mac = self.mac(payload).finalize().into_bytes();
+
+        format!("{}.{}", payload, hex::encode(&mac[..MAC_LENGTH]))
+    }
+
+    /// Verifies a signed payload, returning the payload if the MAC matches.
+    pub fn verify<'a>(&self, signed: &'a str) -> Option<&'a str> {
+        let (payload, mac) = signed.rsplit_once('.')?;
+        let mac = hex::decode(mac).ok()?;
+
+        (mac.len() == MAC_LENGTH)
+            .then(|| self.mac(payload).verify_truncated_left(&mac).ok())
+            .flatten()
+            .map(|_| payload)
+    }
+
+    /// Computes the MAC of a payload.
+    fn mac(&self, payload: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        mac.update(payload.as_bytes());
+
+        mac
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the songs module.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ferogram::Result;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+/// The URL of the Odesli (song.link) API.
+const ODESLI_URL: &str = "https://api.song.link/v1-alpha.1/links";
+/// The URL of the iTunes search API, used to resolve names into links.
+const ITUNES_URL: &str = "https://itunes.apple.com/search";
+
+/// The platforms shown, in order, with their display names.
+const PLATFORMS: [(&str, &str); 8] = [
+    ("spotify", "Spotify"),
+    ("youtube", "YouTube"),
+    ("youtubeMusic", "YouTube Music"),
+    ("appleMusic", "Apple Music"),
+    ("deezer", "Deezer"),
+    ("tidal", "Tidal"),
+    ("soundcloud", "SoundCloud"),
+    ("amazonMusic", "Amazon Music"),
+];
+
+/// The songs resolver.
+#[derive(Clone)]
+pub struct Songs {
+    /// The resolved songs, keyed by query.
+    cache: Arc<Mutex<HashMap<String, (Instant, Song)>>>,
+    /// How long a resolved song is kept in the cache.
+    ttl: Duration,
+}
+
+impl Songs {
+    /// Creates a new `Songs` instance.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+        }
+    }
+
+    /// Resolves a song from a link of any platform or from its name.
+    pub async fn resolve(&self, query: &str) -> Result<Song> {
+        let key = query.trim().to_lowercase();
+
+        if let Some((time, song)) = self.cache.lock().await.get(&key) {
+            if time.elapsed() < self.ttl {
+                return Ok(song.clone());
+            }
+        }
+
+        let url = if query.starts_with("http://") || query.starts_with("https://") {
+            query.to_string()
+        } else {
+            search_link(query).await?
+        };
+
+        let song = fetch_song(&url).await?;
+
+        let mut cache = self.cache.lock().await;
+        cache.retain(|_, (time, _)| time.elapsed() < self.ttl);
+        cache.insert(key, (Instant::now(), song.clone()));
+
+        Ok(song)
+    }
+}
+
+/// A song resolved across platforms.
+#[derive(Clone)]
+pub struct Song {
+    /// The song title.
+    pub title: String,
+    /// The song artist.
+    pub artist: String,
+    /// The song thumbnail URL.
+    pub thumbnail: Option<String>,
+    /// The song.link page URL.
+    pub page_url: String,
+    /// The links of each platform, in display order.
+    pub links: Vec<(String, String)>,
+}
+
+impl Song {
+    /// Returns the best link to download the audio from.
+    pub fn download_source(&self) -> String {
+        self.links
+            .iter()
+            .find(|(name, _)| name == "YouTube")
+            .map(|(_, url)| url.clone())
+            .unwrap_or_else(|| format!("{} - {}", self.artist, self.title))
+    }
+}
+
+/// Searches a song by name, returning its Apple Music link.
+async fn search_link(name: &str) -> Result<String> {
+    let json = reqwest::Client::new()
+        .get(ITUNES_URL)
+        .query(&[("term", name), ("entity", "song"), ("limit", "1")])
+        .send()
+        .await?
+        .json::<Value>()
+        .await?;
+
+    json["results"][0]["trackViewUrl"]
+        .as_str()
+        .map(|url| url.to_string())
+        .ok_or_else(|| "Song not found".into())
+}
+
+/// Fetches the links of a song from Odesli.
+async fn fetch_song(url: &str) -> Result<Song> {
+    let json = reqwest::Client::new()
+        .get(ODESLI_URL)
+        .query(&[("url", url), ("userCountry", "BR")])
+        .send()
+        .await?
+        .json::<Value>()
+        .await?;
+
+    let entity_id = json["entityUniqueId"].as_str().ok_or("Song not found")?;
+    let entity = &json["entitiesByUniqueId"][entity_id];
+
+    let links = PLATFORMS
+        .iter()
+        .filter_map(|(key, name)| {
+            json["linksByPlatform"][key]["url"]
+                .as_str()
+                .map(|url| (name.to_string(), url.to_string()))
+        })
+        .collect();
+
+    Ok(Song {
+        title: entity["title"].as_str().unwrap_or("?").to_string(),
+        artist: entity["artistName"].as_str().unwrap_or("?").to_string(),
+        thumbnail: entity["thumbnailUrl"].as_str().map(|url| url.to_string()),
+        page_url: json["pageUrl"].as_str().unwrap_or(url).to_string(),
+        links,
+    })
+}
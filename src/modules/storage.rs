@@ -0,0 +1,151 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the storage module.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::Mutex;
+
+const PATH: &str = "./assets/data/";
+
+/// A persistent storage backed by a JSON file.
+pub struct Storage<T> {
+    /// The path of the file.
+    path: PathBuf,
+    /// The stored data.
+    data: Arc<RwLock<T>>,
+    /// Held while writing, so the file is saved in the order of the writes.
+    saving: Arc<Mutex<()>>,
+}
+
+impl<T> Clone for Storage<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            data: Arc::clone(&self.data),
+            saving: Arc::clone(&self.saving),
+        }
+    }
+}
+
+impl<T: Default + Serialize + DeserializeOwned> Storage<T> {
+    /// Opens the storage with the given name, loading its data if it exists.
+    ///
+    /// A file that does not parse is kept aside as `<name>.json.corrupt`, so it
+    /// is not overwritten by the next write.
+    pub fn open(name: &str) -> Self {
+        let path = PathBuf::from(PATH).join(format!("{}.json", name));
+
+        let data = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<T>(&content).unwrap_or_else(|e| {
+                let corrupt = path.with_extension("json.corrupt");
+                log::error!(
+                    "Failed to parse storage {0}, moving it to {1}: {2}",
+                    name,
+                    corrupt.display(),
+                    e
+                );
+                if let Err(e) = fs::rename(&path, &corrupt) {
+                    log::error!("Failed to move the corrupt storage {}: {}", name, e);
+                }
+
+                T::default()
+            }),
+            Err(_) => T::default(),
+        };
+
+        Self {
+            path,
+            data: Arc::new(RwLock::new(data)),
+            saving: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Reads the data.
+    pub async fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.read_now(f)
+    }
+
+    /// Reads the data without waiting, meant to be used at construction time.
+    pub fn read_now<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let data = self.data.read().expect("failed to lock storage");
+
+        f(&data)
+    }
+
+    /// Writes the data and saves it to the file.
+    pub async fn write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let _saving = self.saving.lock().await;
+        let (result, content) = {
+            let mut data = self.data.write().expect("failed to lock storage");
+            let result = f(&mut data);
+
+            (result, serde_json::to_string_pretty(&*data))
+        };
+
+        match content {
+            Ok(content) => self.save(content).await,
+            Err(e) => log::error!("Failed to serialize storage: {}", e),
+        }
+
+        result
+    }
+
+    /// Saves the content to the file, through a temporary file so a failed
+    /// write does not leave it truncated.
+    async fn save(&self, content: String) {
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                log::error!("Failed to create storage directory: {}", e);
+                return;
+            }
+        }
+
+        let temp = self.path.with_extension("json.tmp");
+        if let Err(e) = tokio::fs::write(&temp, content).await {
+            log::error!("Failed to write storage file: {}", e);
+            return;
+        }
+        if let Err(e) = tokio::fs::rename(&temp, &self.path).await {
+            log::error!("Failed to replace storage file: {}", e);
+        }
+    }
+}
+
+/// Checks that the storage directory is writable and every stored file parses,
+/// returning how many there are.
+pub fn check() -> Result<usize, String> {
+    fs::create_dir_all(PATH).map_err(|e| e.to_string())?;
+
+    let probe = PathBuf::from(PATH).join(".probe");
+    fs::write(&probe, b"").map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(&probe);
+
+    let mut files = 0;
+    for entry in fs::read_dir(PATH).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let content = fs::read_to_string(&path).map_err(|e| format!("{0}: {1}", name, e))?;
+        serde_json::from_str::<serde_json::Value>(&content)
+            .map_err(|e| format!("{0}: {1}", name, e))?;
+
+        files += 1;
+    }
+
+    Ok(files)
+}
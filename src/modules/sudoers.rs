@@ -0,0 +1,149 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the sudoers module.
+
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc, LazyLock, RwLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The store shared by the filters and the commands.
+static SHARED: LazyLock<Sudoers> = LazyLock::new(|| Sudoers {
+    storage: Storage::open("sudoers"),
+    owner: Arc::new(AtomicI64::new(0)),
+    accounts: Arc::new(RwLock::new(Vec::new())),
+    configured: Arc::new(RwLock::new(Vec::new())),
+});
+
+/// The users allowed to use the commands besides the owner.
+#[derive(Clone)]
+pub struct Sudoers {
+    /// The persisted sudoers.
+    storage: Storage<Data>,
+    /// The ID of the owner, who is always a sudoer.
+    owner: Arc<AtomicI64>,
+    /// The IDs of the other user accounts, taken as the owner.
+    accounts: Arc<RwLock<Vec<i64>>>,
+    /// The IDs of the sudoers listed in the config.
+    configured: Arc<RwLock<Vec<i64>>>,
+}
+
+impl Sudoers {
+    /// Returns the shared store, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Sets the owner, who cannot be removed.
+    pub fn set_owner(&self, user_id: i64) {
+        self.owner.store(user_id, Ordering::Relaxed);
+    }
+
+    /// Returns the ID of the owner.
+    pub fn owner(&self) -> i64 {
+        self.owner.load(Ordering::Relaxed)
+    }
+
+    /// Sets the other user accounts, taken as the owner.
+    pub fn set_accounts(&self, user_ids: Vec<i64>) {
+        *self.accounts.write().expect("failed to lock accounts") = user_ids;
+    }
+
+    /// Sets the sudoers listed in the config, kept apart from the added ones.
+    pub fn set_configured(&self, user_ids: Vec<i64>) {
+        *self.configured.write().expect("failed to lock sudoers") = user_ids;
+    }
+
+    /// Checks if a user is listed in the config.
+    pub fn is_configured(&self, user_id: i64) -> bool {
+        self.configured
+            .read()
+            .expect("failed to lock sudoers")
+            .contains(&user_id)
+    }
+
+    /// Checks if a user is the owner, or one of their other accounts.
+    pub fn is_owner(&self, user_id: i64) -> bool {
+        self.owner() == user_id
+            || self
+                .accounts
+                .read()
+                .expect("failed to lock accounts")
+                .contains(&user_id)
+    }
+
+    /// Checks if a user is a sudoer.
+    pub async fn contains(&self, user_id: i64) -> bool {
+        self.is_owner(user_id)
+            || self.is_configured(user_id)
+            || self
+                .storage
+                .read(|data| data.users.iter().any(|u| u.id == user_id))
+                .await
+    }
+
+    /// Adds a sudoer.
+    ///
+    /// Returns `false` if the user already was one.
+    pub async fn add(&self, user_id: i64, name: String) -> bool {
+        if self.is_owner(user_id) {
+            return false;
+        }
+
+        self.storage
+            .write(|data| {
+                if data.users.iter().any(|u| u.id == user_id) {
+                    false
+                } else {
+                    data.users.push(Sudoer { id: user_id, name });
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Removes a sudoer.
+    ///
+    /// Returns `false` if the user was not one.
+    pub async fn remove(&self, user_id: i64) -> bool {
+        self.storage
+            .write(|data| {
+                let len = data.users.len();
+                data.users.retain(|u| u.id != user_id);
+
+                data.users.len() != len
+            })
+            .await
+    }
+
+    /// Returns the sudoers, without the owner.
+    pub async fn list(&self) -> Vec<Sudoer> {
+        self.storage.read(|data| data.users.clone()).await
+    }
+}
+
+/// A sudoer.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sudoer {
+    /// The user ID.
+    pub id: i64,
+    /// The name of the user when added.
+    pub name: String,
+}
+
+/// The persisted sudoers.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The sudoers.
+    users: Vec<Sudoer>,
+}
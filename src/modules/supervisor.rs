@@ -0,0 +1,335 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the supervisor of the clients.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use grammers_client::{
+    session::{PackedChat, PackedType},
+    types::{LoginToken, PasswordToken},
+    AuthorizationError, Client, InputMessage, InvocationError, SignInError,
+};
+use maplit::hashmap;
+use tokio::sync::Mutex;
+
+use super::{i18n::I18n, shutdown::Shutdown};
+use crate::{utils, Recipient};
+
+/// How often the clients are checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What the clients log in again with.
+pub struct Credentials {
+    /// The token of the bot.
+    pub bot_token: String,
+    /// The session file of the bot.
+    pub bot_session: String,
+    /// The credentials of each user account.
+    pub users: Vec<UserCredentials>,
+}
+
+/// What a user account logs in again with.
+pub struct UserCredentials {
+    /// The phone number of the account.
+    pub phone_number: String,
+    /// The session file of the account.
+    pub session: String,
+}
+
+/// A step of the login of a user account.
+enum Pending {
+    /// The code sent to the account is awaited.
+    Code(LoginToken),
+    /// The two-step verification password is awaited.
+    Password(PasswordToken),
+}
+
+/// The result of a step of the login of a user account.
+pub enum Login {
+    /// There is no login in progress.
+    NotStarted,
+    /// The account has a password, which is awaited.
+    PasswordRequired,
+    /// The user is logged in.
+    Done,
+}
+
+/// Keeps track of the clients whose session was lost, so the other keeps running.
+#[derive(Clone)]
+pub struct Supervisor {
+    /// The bot client.
+    bot: Client,
+    /// The client of each user account, the first being the main one.
+    users: Arc<Vec<Client>>,
+    /// The ID of the owner, notified through the surviving client.
+    owner: Arc<AtomicI64>,
+    /// Whether the bot session was lost.
+    bot_down: Arc<AtomicBool>,
+    /// Whether the session of each user account was lost.
+    users_down: Arc<Vec<AtomicBool>>,
+    /// What the clients log in again with.
+    credentials: Arc<Credentials>,
+    /// The login in progress, with the user account it is for.
+    pending: Arc<Mutex<Option<(usize, Pending)>>>,
+    /// The translations of the notices.
+    i18n: I18n,
+}
+
+impl Supervisor {
+    /// Creates a new `Supervisor` instance.
+    pub fn new(bot: Client, users: Vec<Client>, credentials: Credentials, i18n: I18n) -> Self {
+        Self {
+            bot,
+            users_down: Arc::new(users.iter().map(|_| AtomicBool::new(false)).collect()),
+            users: Arc::new(users),
+            owner: Arc::new(AtomicI64::new(0)),
+            bot_down: Arc::new(AtomicBool::new(false)),
+            credentials: Arc::new(credentials),
+            pending: Arc::new(Mutex::new(None)),
+            i18n,
+        }
+    }
+
+    /// Sets the owner, notified when a client goes down.
+    pub fn set_owner(&self, user_id: i64) {
+        self.owner.store(user_id, Ordering::Relaxed);
+    }
+
+    /// Returns the flag of a client.
+    ///
+    /// Panics if the user account does not exist, as it is a programming error.
+    fn flag(&self, recipient: Recipient) -> &AtomicBool {
+        match recipient {
+            Recipient::Bot => &self.bot_down,
+            Recipient::User(account) => &self.users_down[account],
+        }
+    }
+
+    /// Checks if the session of a client was lost.
+    pub fn is_down(&self, recipient: Recipient) -> bool {
+        self.flag(recipient).load(Ordering::Relaxed)
+    }
+
+    /// Returns the first user account whose session was lost.
+    pub fn down_user(&self) -> Option<usize> {
+        self.users_down
+            .iter()
+            .position(|down| down.load(Ordering::Relaxed))
+    }
+
+    /// Reports an error of a client.
+    ///
+    /// Returns `true` if the error was fatal, in which case the client is marked
+    /// as down and the owner is notified the first time.
+    pub async fn report(&self, recipient: Recipient, error: &InvocationError) -> bool {
+        if !utils::is_fatal(error) {
+            return false;
+        }
+
+        if !self.flag(recipient).swap(true, Ordering::Relaxed) {
+            log::error!("The {} session was lost: {}", client_name(recipient), error);
+
+            let key = match recipient {
+                Recipient::Bot => "bot_client_down",
+                Recipient::User(_) => "user_client_down",
+            };
+            self.notify(
+                recipient,
+                self.i18n.translate_with_args(
+                    key,
+                    hashmap! {
+                        "account" => utils::escape_html(&self.account_name(recipient)),
+                        "error" => utils::escape_html(&error.to_string()),
+                    },
+                ),
+            )
+            .await;
+        }
+
+        true
+    }
+
+    /// Marks a client as working again, notifying the owner if it was down.
+    pub async fn restore(&self, recipient: Recipient) {
+        if self.flag(recipient).swap(false, Ordering::Relaxed) {
+            log::info!("The {} session was restored", client_name(recipient));
+
+            let key = match recipient {
+                Recipient::Bot => "bot_client_restored",
+                Recipient::User(_) => "user_client_restored",
+            };
+            self.notify(
+                recipient,
+                self.i18n.translate_with_args(
+                    key,
+                    hashmap! {
+                        "account" => utils::escape_html(&self.account_name(recipient)),
+                    },
+                ),
+            )
+            .await;
+        }
+    }
+
+    /// Returns how a client is named to the owner, the phone number of a user
+    /// account.
+    fn account_name(&self, recipient: Recipient) -> String {
+        match recipient {
+            Recipient::Bot => "bot".to_string(),
+            Recipient::User(account) => self.credentials.users[account].phone_number.clone(),
+        }
+    }
+
+    /// Notifies the owner through the client other than `recipient`.
+    async fn notify(&self, recipient: Recipient, text: String) {
+        let user = &self.users[0];
+        let result = match recipient {
+            Recipient::Bot => match user.get_me().await {
+                // The bot is down, so the notice goes to the saved messages of the main account.
+                Ok(me) => user
+                    .send_message(me.pack(), InputMessage::html(text))
+                    .await
+                    .map(|_| ()),
+                Err(e) => Err(e),
+            },
+            Recipient::User(_) => self
+                .bot
+                .send_message(
+                    PackedChat {
+                        ty: PackedType::User,
+                        id: self.owner.load(Ordering::Relaxed),
+                        access_hash: None,
+                    },
+                    InputMessage::html(text),
+                )
+                .await
+                .map(|_| ()),
+        };
+
+        if let Err(e) = result {
+            log::error!("Failed to notify the owner about the sessions: {}", e);
+        }
+    }
+
+    /// Logs the bot in again with its token.
+    pub async fn relogin_bot(&self) -> Result<(), AuthorizationError> {
+        self.bot.bot_sign_in(&self.credentials.bot_token).await?;
+        save_session(&self.bot, &self.credentials.bot_session);
+
+        self.restore(Recipient::Bot).await;
+
+        Ok(())
+    }
+
+    /// Starts the login of a user account, sending a code to it.
+    pub async fn request_login_code(&self, account: usize) -> Result<(), AuthorizationError> {
+        let token = self.users[account]
+            .request_login_code(&self.credentials.users[account].phone_number)
+            .await?;
+        *self.pending.lock().await = Some((account, Pending::Code(token)));
+
+        Ok(())
+    }
+
+    /// Continues the login of a user account with the code or the password awaited.
+    pub async fn sign_in(&self, secret: &str) -> Result<Login, SignInError> {
+        let Some((account, pending)) = self.pending.lock().await.take() else {
+            return Ok(Login::NotStarted);
+        };
+        let user = &self.users[account];
+
+        let result = match pending {
+            Pending::Code(token) => user.sign_in(&token, secret).await,
+            Pending::Password(token) => user.check_password(token, secret).await,
+        };
+
+        match result {
+            Ok(_) => {
+                save_session(user, &self.credentials.users[account].session);
+                self.restore(Recipient::User(account)).await;
+
+                Ok(Login::Done)
+            }
+            Err(SignInError::PasswordRequired(token)) => {
+                *self.pending.lock().await = Some((account, Pending::Password(token)));
+
+                Ok(Login::PasswordRequired)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Saves the sessions of the clients still working, so the updates handled
+    /// until now are not caught up again on the next start.
+    pub fn save_sessions(&self) {
+        if !self.is_down(Recipient::Bot) {
+            save_session(&self.bot, &self.credentials.bot_session);
+        }
+
+        for (account, user) in self.users.iter().enumerate() {
+            if !self.is_down(Recipient::User(account)) {
+                save_session(user, &self.credentials.users[account].session);
+            }
+        }
+    }
+
+    /// Spawns the task that checks the clients periodically.
+    ///
+    /// A client that answers again, after a relogin, is marked as working.
+    pub fn start(self) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+
+                let clients = std::iter::once((Recipient::Bot, &self.bot)).chain(
+                    self.users
+                        .iter()
+                        .enumerate()
+                        .map(|(account, client)| (Recipient::User(account), client)),
+                );
+                for (recipient, client) in clients {
+                    match client.get_me().await {
+                        Ok(_) => self.restore(recipient).await,
+                        Err(e) => {
+                            if !self.report(recipient, &e).await {
+                                log::warn!(
+                                    "Failed to check the {} session: {}",
+                                    client_name(recipient),
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Saves the session of a client to its file.
+fn save_session(client: &Client, path: &str) {
+    if let Err(e) = client.session().save_to_file(path) {
+        log::error!("Failed to save the session to {}: {}", path, e);
+    }
+}
+
+/// Returns the name of a client in the logs.
+fn client_name(recipient: Recipient) -> String {
+    match recipient {
+        Recipient::Bot => "bot".to_string(),
+        Recipient::User(0) => "user".to_string(),
+        Recipient::User(account) => format!("user {}", account + 1),
+    }
+}
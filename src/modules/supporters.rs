@@ -0,0 +1,102 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the supporters module.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The supporters, who donated Telegram Stars.
+#[derive(Clone)]
+pub struct Supporters {
+    /// The recorded donations.
+    storage: Storage<Data>,
+}
+
+impl Supporters {
+    /// Creates a new `Supporters` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("supporters"),
+        }
+    }
+
+    /// Records a donation.
+    ///
+    /// Returns `false` if the charge was already recorded.
+    pub async fn record(&self, donation: Donation) -> bool {
+        self.storage
+            .write(|data| {
+                if data
+                    .donations
+                    .iter()
+                    .any(|d| d.charge_id == donation.charge_id)
+                {
+                    false
+                } else {
+                    data.donations.push(donation);
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Returns the supporters with their names and total amounts, highest first.
+    pub async fn ranking(&self) -> Vec<(String, i64)> {
+        self.storage
+            .read(|data| {
+                let mut ranking: Vec<(i64, String, i64)> = Vec::new();
+
+                for donation in data.donations.iter() {
+                    match ranking
+                        .iter_mut()
+                        .find(|(id, _, _)| *id == donation.user_id)
+                    {
+                        Some((_, name, total)) => {
+                            *name = donation.name.clone();
+                            *total += donation.amount;
+                        }
+                        None => {
+                            ranking.push((donation.user_id, donation.name.clone(), donation.amount))
+                        }
+                    }
+                }
+
+                ranking.sort_by(|a, b| b.2.cmp(&a.2));
+                ranking
+                    .into_iter()
+                    .map(|(_, name, total)| (name, total))
+                    .collect()
+            })
+            .await
+    }
+}
+
+/// A donation.
+#[derive(Serialize, Deserialize)]
+pub struct Donation {
+    /// The ID of the supporter.
+    pub user_id: i64,
+    /// The name of the supporter at the time of the donation.
+    pub name: String,
+    /// The amount of Stars.
+    pub amount: i64,
+    /// The Telegram charge ID, used to refund the donation.
+    pub charge_id: String,
+    /// When the donation was made.
+    pub date: DateTime<Utc>,
+}
+
+/// The persisted donations.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The donations, in order.
+    donations: Vec<Donation>,
+}
@@ -0,0 +1,77 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the system info module.
+
+use std::collections::HashMap;
+
+use maplit::hashmap;
+use serde::Serialize;
+use sysinfo::System;
+
+/// The information about the bot and its host.
+#[derive(Serialize)]
+pub struct SystemInfo {
+    /// The name of the operating system.
+    pub os: String,
+    /// The architecture of the CPU.
+    pub arch: String,
+    /// The name of the host.
+    pub host: String,
+    /// The version of the bot.
+    pub version: String,
+    /// The version of the kernel.
+    pub kernel_version: String,
+    /// In percent, the global CPU usage.
+    pub cpu_usage: f32,
+    /// In gigabytes, the memory in use.
+    pub used_memory: f64,
+    /// In gigabytes, the total memory.
+    pub total_memory: f64,
+    /// In percent, the memory in use.
+    pub memory_usage: f64,
+}
+
+impl SystemInfo {
+    /// Collects the information from the host.
+    pub fn collect() -> Self {
+        let mut info = System::new_all();
+
+        info.refresh_cpu_usage();
+
+        let used_memory = info.used_memory() as f64 / 10f64.powi(9);
+        let total_memory = info.total_memory() as f64 / 10f64.powi(9);
+
+        Self {
+            os: System::name().unwrap_or("Unknown".to_string()),
+            arch: System::cpu_arch().unwrap_or("x86_64".to_string()),
+            host: System::host_name().unwrap_or("localhost".to_string()),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            kernel_version: System::kernel_version().unwrap_or("1.0.0".to_string()),
+            cpu_usage: info.global_cpu_usage(),
+            used_memory,
+            total_memory,
+            memory_usage: (used_memory / total_memory) * 100f64,
+        }
+    }
+
+    /// Returns the arguments of the `info_text` translation.
+    pub fn to_args(&self) -> HashMap<&'static str, String> {
+        hashmap! {
+            "os" => self.os.clone(),
+            "cpu_usage" => (self.cpu_usage as u64).to_string(),
+            "arch" => self.arch.clone(),
+            "host" => self.host.clone(),
+            "version" => self.version.clone(),
+            "kernel_version" => self.kernel_version.clone(),
+            "memory_usage" => (self.memory_usage as u64).to_string(),
+            "used_memory" => format!("{:.2}", self.used_memory),
+            "total_memory" => format!("{:.2}", self.total_memory),
+        }
+    }
+}
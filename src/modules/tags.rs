@@ -0,0 +1,241 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the saved messages tags module.
+
+use grammers_client::button::{self, Inline};
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+
+use super::{i18n::I18n, storage::Storage};
+use crate::utils::{escape_html, pagination_buttons};
+
+/// The saved messages tags manager.
+#[derive(Clone)]
+pub struct Tags {
+    /// The persisted index.
+    storage: Storage<Data>,
+    /// The preset tags, always offered in the keyboard.
+    presets: Vec<String>,
+    /// The number of items per page.
+    page_size: usize,
+}
+
+impl Tags {
+    /// Creates a new `Tags` instance.
+    pub fn new(presets: Vec<String>, page_size: usize) -> Self {
+        Self {
+            storage: Storage::open("tags"),
+            presets,
+            page_size: page_size.max(1),
+        }
+    }
+
+    /// Tracks a saved message, so it can be tagged later.
+    pub async fn track(&self, message_id: i32, preview: impl Into<String>) {
+        let preview = preview.into();
+
+        self.storage
+            .write(|data| {
+                if !data.items.iter().any(|i| i.message_id == message_id) {
+                    data.items.push(Item {
+                        message_id,
+                        preview,
+                        tags: Vec::new(),
+                    });
+                }
+            })
+            .await;
+    }
+
+    /// Toggles a tag of a saved message.
+    ///
+    /// Returns `Some(true)` if the tag was added, `Some(false)` if it was removed
+    /// and `None` if the message is not tracked.
+    pub async fn toggle(&self, message_id: i32, tag: &str) -> Option<bool> {
+        self.storage
+            .write(|data| {
+                let item = data.items.iter_mut().find(|i| i.message_id == message_id)?;
+
+                if item.tags.iter().any(|t| t == tag) {
+                    item.tags.retain(|t| t != tag);
+                    Some(false)
+                } else {
+                    item.tags.push(tag.to_string());
+                    Some(true)
+                }
+            })
+            .await
+    }
+
+    /// Returns the tags of a saved message.
+    pub async fn tags_of(&self, message_id: i32) -> Vec<String> {
+        self.storage
+            .read(|data| {
+                data.items
+                    .iter()
+                    .find(|i| i.message_id == message_id)
+                    .map(|i| i.tags.clone())
+                    .unwrap_or_default()
+            })
+            .await
+    }
+
+    /// Returns the known tags: the presets followed by the used ones.
+    pub async fn known_tags(&self) -> Vec<String> {
+        let mut tags = self.presets.clone();
+
+        self.storage
+            .read(|data| {
+                for tag in data.items.iter().flat_map(|i| i.tags.iter()) {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                }
+            })
+            .await;
+
+        tags
+    }
+
+    /// Returns the saved messages with the given tag, newest first.
+    pub async fn items(&self, tag: &str) -> Vec<Item> {
+        self.storage
+            .read(|data| {
+                data.items
+                    .iter()
+                    .rev()
+                    .filter(|i| i.tags.iter().any(|t| t == tag))
+                    .cloned()
+                    .collect()
+            })
+            .await
+    }
+
+    /// Returns the ID of a tag, given to it the first time it is seen.
+    ///
+    /// The buttons carry the IDs, as a tag name can exceed the 64 bytes of the
+    /// callback data.
+    pub async fn id_of(&self, tag: &str) -> usize {
+        self.storage.write(|data| data.id_of(tag)).await
+    }
+
+    /// Returns the name of a tag by its ID.
+    pub async fn name_of(&self, id: usize) -> Option<String> {
+        self.storage.read(|data| data.names.get(id).cloned()).await
+    }
+
+    /// Renders the keyboard to tag a saved message.
+    pub async fn keyboard(&self, message_id: i32) -> Vec<Vec<Inline>> {
+        let selected = self.tags_of(message_id).await;
+        let tags = self.known_tags().await;
+        let ids = self
+            .storage
+            .write(|data| tags.iter().map(|tag| data.id_of(tag)).collect::<Vec<_>>())
+            .await;
+
+        tags.iter()
+            .zip(ids)
+            .collect::<Vec<_>>()
+            .chunks(3)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|(tag, id)| {
+                        let text = if selected.contains(tag) {
+                            format!("✅ {}", tag)
+                        } else {
+                            tag.to_string()
+                        };
+
+                        button::inline(text, format!("tag {0} {1}", message_id, id))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Renders a page of the saved messages with the given tag.
+    pub async fn render_page(
+        &self,
+        i18n: &I18n,
+        user_id: i64,
+        tag: &str,
+        page: usize,
+    ) -> (String, Vec<Vec<Inline>>) {
+        let items = self.items(tag).await;
+        let pages = items.len().div_ceil(self.page_size).max(1);
+        let page = page.min(pages - 1);
+
+        let mut text = i18n.translate_with_args(
+            "tagged_header",
+            hashmap! {
+                "tag" => escape_html(tag),
+                "count" => items.len().to_string(),
+            },
+        );
+
+        if items.is_empty() {
+            text += &i18n.translate("tagged_empty");
+        }
+
+        for (i, item) in items
+            .iter()
+            .enumerate()
+            .skip(page * self.page_size)
+            .take(self.page_size)
+        {
+            text += &format!(
+                "\n{0}. <a href=\"tg://openmessage?user_id={1}&message_id={2}\">{3}</a>",
+                i + 1,
+                user_id,
+                item.message_id,
+                escape_html(&item.preview)
+            );
+        }
+
+        let id = self.id_of(tag).await;
+        let buttons = pagination_buttons(&format!("tagged {}", id), page, pages);
+
+        (text, vec![buttons])
+    }
+}
+
+/// The persisted index of saved messages.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The tracked saved messages.
+    items: Vec<Item>,
+    /// The names of the tags seen, indexed by their IDs.
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+impl Data {
+    /// Returns the ID of a tag, giving it one if it has none.
+    fn id_of(&mut self, tag: &str) -> usize {
+        match self.names.iter().position(|name| name == tag) {
+            Some(id) => id,
+            None => {
+                self.names.push(tag.to_string());
+                self.names.len() - 1
+            }
+        }
+    }
+}
+
+/// A tracked saved message.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Item {
+    /// The saved message ID.
+    message_id: i32,
+    /// A short preview of the message.
+    preview: String,
+    /// The tags of the message.
+    tags: Vec<String>,
+}
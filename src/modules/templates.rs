@@ -0,0 +1,297 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the message templates module.
+
+use std::collections::HashMap;
+
+use chrono::Local;
+use grammers_client::types::Chat;
+
+use crate::utils::escape_html;
+
+/// The most nested conditionals of a template.
+const MAX_DEPTH: usize = 16;
+
+/// The values a template is rendered with, such as `user.first_name`.
+///
+/// The values are kept as HTML, as the templates are.
+#[derive(Clone, Default)]
+pub struct Variables {
+    /// The values by name.
+    values: HashMap<String, Value>,
+}
+
+/// A value of a template.
+#[derive(Clone)]
+struct Value {
+    /// The value, as HTML.
+    html: String,
+    /// Whether it passes a conditional.
+    truthy: bool,
+}
+
+impl Variables {
+    /// Creates the variables with the current `date` and `time`.
+    pub fn new() -> Self {
+        let now = Local::now();
+
+        Self::default()
+            .text("date", now.format("%d/%m/%Y").to_string())
+            .text("time", now.format("%H:%M").to_string())
+    }
+
+    /// Sets a text value, escaped when inserted.
+    pub fn text(mut self, name: &str, value: impl Into<String>) -> Self {
+        let value = value.into();
+        self.values.insert(
+            name.to_string(),
+            Value {
+                truthy: !value.is_empty(),
+                html: escape_html(&value),
+            },
+        );
+        self
+    }
+
+    /// Sets an HTML value, inserted as it is.
+    pub fn html(mut self, name: &str, value: impl Into<String>) -> Self {
+        let html = value.into();
+        self.values.insert(
+            name.to_string(),
+            Value {
+                truthy: !html.is_empty(),
+                html,
+            },
+        );
+        self
+    }
+
+    /// Sets a flag, shown as `yes` or `no`.
+    pub fn flag(mut self, name: &str, value: bool) -> Self {
+        self.values.insert(
+            name.to_string(),
+            Value {
+                html: if value { "yes" } else { "no" }.to_string(),
+                truthy: value,
+            },
+        );
+        self
+    }
+
+    /// Sets the `user.*` values of a sender: `id`, `first_name`, `last_name`,
+    /// `full_name`, `username`, `mention`, `premium` and `bot`.
+    pub fn user(self, user: &Chat) -> Self {
+        let (first_name, last_name, premium, bot) = match user {
+            Chat::User(u) => (
+                u.raw.first_name.clone().unwrap_or_default(),
+                u.raw.last_name.clone().unwrap_or_default(),
+                u.raw.premium,
+                u.raw.bot,
+            ),
+            _ => (user.name().to_string(), String::new(), false, false),
+        };
+        let full_name = format!("{0} {1}", first_name, last_name).trim().to_string();
+        let mention = format!(
+            "<a href=\"tg://user?id={0}\">{1}</a>",
+            user.id(),
+            escape_html(&first_name)
+        );
+
+        self.text("user.id", user.id().to_string())
+            .text("user.first_name", first_name)
+            .text("user.last_name", last_name)
+            .text("user.full_name", full_name)
+            .text("user.username", user.username().unwrap_or_default())
+            .html("user.mention", mention)
+            .flag("user.premium", premium)
+            .flag("user.bot", bot)
+    }
+
+    /// Sets the `chat.*` values of a chat: `id`, `title` and `username`.
+    pub fn chat(self, chat: &Chat) -> Self {
+        self.text("chat.id", chat.id().to_string())
+            .text("chat.title", chat.name())
+            .text("chat.username", chat.username().unwrap_or_default())
+    }
+
+    /// Returns a value by name, falling back to the `user.*` one, so `premium`
+    /// reads `user.premium`.
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .get(name)
+            .or_else(|| self.values.get(&format!("user.{}", name)))
+    }
+}
+
+/// A piece of a parsed template.
+enum Node<'a> {
+    /// Text kept as it is.
+    Text(&'a str),
+    /// A variable, such as `{{user.first_name}}`.
+    Variable(&'a str),
+    /// A conditional, `{{#if name}}...{{else}}...{{/if}}`, or its negation with
+    /// `#unless`.
+    If {
+        name: &'a str,
+        negated: bool,
+        then: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+    },
+}
+
+/// A tag between braces.
+enum Tag<'a> {
+    /// A variable.
+    Variable(&'a str),
+    /// The start of a conditional.
+    If(&'a str, bool),
+    /// The `{{else}}` of a conditional.
+    Else,
+    /// The end of a conditional, with its kind.
+    End(&'a str),
+}
+
+/// Renders a template with the given variables.
+///
+/// Supports `{{name}}`, `{{#if name}}...{{/if}}`, `{{#unless name}}...{{/unless}}`
+/// and `{{else}}` in both. The unknown variables are kept as they are, so a
+/// text that only looks like a template is not changed.
+///
+/// Returns why the template is invalid, such as a conditional left open.
+pub fn render(template: &str, variables: &Variables) -> Result<String, String> {
+    let mut rest = template;
+    let (nodes, end) = parse(&mut rest, 0)?;
+    if let Some(end) = end {
+        return Err(format!("unexpected {{{{{}}}}}", end));
+    }
+
+    let mut output = String::with_capacity(template.len());
+    write(&nodes, variables, &mut output);
+
+    Ok(output)
+}
+
+/// Parses the nodes until the end of a block.
+///
+/// Returns the nodes and the tag that ended them, `None` at the end of the text.
+fn parse<'a>(rest: &mut &'a str, depth: usize) -> Result<(Vec<Node<'a>>, Option<String>), String> {
+    let mut nodes = Vec::new();
+
+    loop {
+        let Some(start) = rest.find("{{") else {
+            if !rest.is_empty() {
+                nodes.push(Node::Text(rest));
+            }
+            *rest = "";
+            return Ok((nodes, None));
+        };
+        let Some(length) = rest[start..].find("}}") else {
+            nodes.push(Node::Text(rest));
+            *rest = "";
+            return Ok((nodes, None));
+        };
+
+        if start > 0 {
+            nodes.push(Node::Text(&rest[..start]));
+        }
+        let raw = &rest[start..start + length + 2];
+        let tag = parse_tag(&raw[2..raw.len() - 2]);
+        *rest = &rest[start + length + 2..];
+
+        match tag {
+            Some(Tag::Variable(name)) => nodes.push(Node::Variable(name)),
+            Some(Tag::If(name, negated)) => {
+                if depth >= MAX_DEPTH {
+                    return Err("too many nested conditionals".to_string());
+                }
+
+                let kind = if negated { "unless" } else { "if" };
+                let (then, end) = parse(rest, depth + 1)?;
+                let (otherwise, end) = match end.as_deref() {
+                    Some("else") => parse(rest, depth + 1)?,
+                    _ => (Vec::new(), end),
+                };
+                if end.as_deref() != Some(kind) {
+                    return Err(format!("{{{{#{0} {1}}}}} is not closed", kind, name));
+                }
+
+                nodes.push(Node::If {
+                    name,
+                    negated,
+                    then,
+                    otherwise,
+                });
+            }
+            Some(Tag::Else) => return Ok((nodes, Some("else".to_string()))),
+            Some(Tag::End(kind)) => return Ok((nodes, Some(kind.to_string()))),
+            None => nodes.push(Node::Text(raw)),
+        }
+    }
+}
+
+/// Parses the content of a tag, `None` if it is not one.
+fn parse_tag(content: &str) -> Option<Tag<'_>> {
+    let content = content.trim();
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    };
+
+    if content == "else" {
+        return Some(Tag::Else);
+    }
+    if let Some(kind) = content.strip_prefix('/') {
+        return matches!(kind.trim(), "if" | "unless").then(|| Tag::End(kind.trim()));
+    }
+    if let Some(condition) = content.strip_prefix('#') {
+        let (kind, name) = condition.split_once(char::is_whitespace)?;
+        let name = name.trim();
+
+        return match kind {
+            "if" if is_name(name) => Some(Tag::If(name, false)),
+            "unless" if is_name(name) => Some(Tag::If(name, true)),
+            _ => None,
+        };
+    }
+
+    is_name(content).then_some(Tag::Variable(content))
+}
+
+/// Writes the rendered nodes.
+fn write(nodes: &[Node], variables: &Variables, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name) => match variables.get(name) {
+                Some(value) => output.push_str(&value.html),
+                None => {
+                    output.push_str("{{");
+                    output.push_str(name);
+                    output.push_str("}}");
+                }
+            },
+            Node::If {
+                name,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let truthy = variables.get(name).is_some_and(|value| value.truthy);
+
+                if truthy != *negated {
+                    write(then, variables, output);
+                } else {
+                    write(otherwise, variables, output);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the todo list module.
+
+use async_trait::async_trait;
+use ferogram::Result;
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+
+use super::{digest::Widget, i18n::I18n, storage::Storage};
+use crate::utils::escape_html;
+
+/// The todo list of the owner, also shown in the daily digest.
+#[derive(Clone)]
+pub struct Todos {
+    /// The persisted items.
+    storage: Storage<Data>,
+}
+
+impl Todos {
+    /// Creates a new `Todos` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("todo"),
+        }
+    }
+
+    /// Adds an item, returning its ID.
+    pub async fn add(&self, text: String) -> u32 {
+        self.storage
+            .write(|data| {
+                data.next_id += 1;
+                data.items.push(Item {
+                    id: data.next_id,
+                    text,
+                });
+
+                data.next_id
+            })
+            .await
+    }
+
+    /// Removes a done item.
+    ///
+    /// Returns `None` if there is no item with the given ID.
+    pub async fn done(&self, id: u32) -> Option<Item> {
+        self.storage
+            .write(|data| {
+                let index = data.items.iter().position(|item| item.id == id)?;
+
+                Some(data.items.remove(index))
+            })
+            .await
+    }
+
+    /// Returns the items, oldest first.
+    pub async fn list(&self) -> Vec<Item> {
+        self.storage.read(|data| data.items.clone()).await
+    }
+
+    /// Renders the items as lines.
+    pub fn render_items(items: &[Item]) -> String {
+        items
+            .iter()
+            .map(|item| format!("<code>{0}</code> {1}", item.id, escape_html(&item.text)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[async_trait]
+impl Widget for Todos {
+    fn name(&self) -> &'static str {
+        "todo"
+    }
+
+    async fn render(&self, i18n: &I18n) -> Result<String> {
+        let items = self.list().await;
+        if items.is_empty() {
+            return Ok(i18n.translate("digest_todo_empty"));
+        }
+
+        Ok(i18n.translate_with_args(
+            "digest_todo",
+            hashmap! { "items" => Todos::render_items(&items) },
+        ))
+    }
+}
+
+/// An item of the todo list.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Item {
+    /// The item ID, never reused.
+    pub id: u32,
+    /// The item text.
+    pub text: String,
+}
+
+/// The persisted todo list.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The items, oldest first.
+    items: Vec<Item>,
+    /// The ID of the last added item.
+    next_id: u32,
+}
@@ -0,0 +1,360 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the tournaments module.
+
+use std::sync::Arc;
+
+use ferogram::Result;
+use grammers_client::types::Chat;
+use resvg::{tiny_skia, usvg};
+use tokio::sync::Mutex;
+
+use super::games::Player;
+use crate::utils::escape_html;
+
+/// The width of each round column of the bracket image.
+const COLUMN_WIDTH: usize = 240;
+/// The height reserved to each first round match of the bracket image.
+const MATCH_HEIGHT: usize = 80;
+
+/// The tournaments manager.
+#[derive(Clone)]
+pub struct Tournaments {
+    /// The running tournaments.
+    tournaments: Arc<Mutex<Vec<Tournament>>>,
+}
+
+impl Tournaments {
+    /// Creates a new `Tournaments` instance.
+    pub fn new() -> Self {
+        Self {
+            tournaments: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Creates a tournament, returning its ID.
+    pub async fn create(&self, chat: Chat, game: GameKind, slots: usize) -> i32 {
+        let mut tournaments = self.tournaments.lock().await;
+        let id = tournaments.last().map(|t| t.id).unwrap_or(0) + 1;
+
+        tournaments.push(Tournament {
+            id,
+            chat,
+            game,
+            slots,
+            players: Vec::new(),
+            rounds: Vec::new(),
+        });
+
+        id
+    }
+
+    /// Runs a closure with the tournament with the given ID.
+    pub async fn with<R>(&self, id: i32, f: impl FnOnce(&mut Tournament) -> R) -> Option<R> {
+        let mut tournaments = self.tournaments.lock().await;
+
+        tournaments.iter_mut().find(|t| t.id == id).map(f)
+    }
+
+    /// Removes a tournament.
+    pub async fn remove(&self, id: i32) {
+        self.tournaments.lock().await.retain(|t| t.id != id);
+    }
+
+    /// Reports the result of a game.
+    ///
+    /// Returns the ID of the tournament the game belongs to, if any.
+    pub async fn report(&self, game_id: i32, winner: Option<i64>) -> Option<i32> {
+        let mut tournaments = self.tournaments.lock().await;
+
+        tournaments
+            .iter_mut()
+            .find(|t| t.report(game_id, winner))
+            .map(|t| t.id)
+    }
+}
+
+/// The games a tournament can be played with.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GameKind {
+    /// The tic tac toe game.
+    TicTacToe,
+}
+
+impl GameKind {
+    /// Parses a game name.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ttt" | "tic_tac_toe" => Some(Self::TicTacToe),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for GameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TicTacToe => write!(f, "Tic Tac Toe"),
+        }
+    }
+}
+
+/// The result of a sign-up.
+#[derive(PartialEq)]
+pub enum SignUp {
+    /// The player joined the tournament.
+    Joined,
+    /// The player had already joined.
+    AlreadyJoined,
+    /// The tournament has no seats left.
+    Full,
+}
+
+/// A single-elimination tournament.
+pub struct Tournament {
+    /// The tournament ID.
+    pub id: i32,
+    /// The chat the tournament is played in.
+    pub chat: Chat,
+    /// The game the matches are played with.
+    pub game: GameKind,
+    /// The number of players.
+    pub slots: usize,
+    /// The signed-up players.
+    pub players: Vec<Player>,
+    /// The bracket rounds, empty until every seat is taken.
+    pub rounds: Vec<Vec<Match>>,
+}
+
+impl Tournament {
+    /// Signs a player up.
+    pub fn sign_up(&mut self, player: Player) -> SignUp {
+        if self.players.iter().any(|p| p.id() == player.id()) {
+            SignUp::AlreadyJoined
+        } else if self.is_full() {
+            SignUp::Full
+        } else {
+            self.players.push(player);
+            SignUp::Joined
+        }
+    }
+
+    /// Checks if every seat is taken.
+    pub fn is_full(&self) -> bool {
+        self.players.len() >= self.slots
+    }
+
+    /// Checks if the bracket was generated.
+    pub fn has_started(&self) -> bool {
+        !self.rounds.is_empty()
+    }
+
+    /// Generates the bracket with the players in a random order.
+    ///
+    /// When the number of players is not a power of two, the first seeds get a bye.
+    pub fn generate_bracket(&mut self) {
+        let mut players = self.players.clone();
+        players.sort_by_cached_key(|_| rand::random::<u64>());
+
+        let size = players.len().next_power_of_two().max(2);
+        let mut seeds = players.into_iter().map(Some).collect::<Vec<_>>();
+        seeds.resize(size, None);
+
+        let first_round = (0..size / 2)
+            .map(|i| Match::new([seeds[i].clone(), seeds[size - 1 - i].clone()]))
+            .collect::<Vec<_>>();
+        self.rounds = vec![first_round];
+
+        while self.rounds.last().map(|r| r.len()).unwrap_or(0) > 1 {
+            let len = self.rounds.last().unwrap().len() / 2;
+            self.rounds
+                .push((0..len).map(|_| Match::new([None, None])).collect());
+        }
+
+        // Byes advance right away.
+        for m in self.rounds[0].iter_mut() {
+            if let [Some(player), None] | [None, Some(player)] = &m.players {
+                m.winner = Some(player.id());
+            }
+        }
+
+        self.propagate();
+    }
+
+    /// Moves the winners to their next matches.
+    fn propagate(&mut self) {
+        for round in 0..self.rounds.len() - 1 {
+            for index in 0..self.rounds[round].len() {
+                let m = &self.rounds[round][index];
+                let Some(winner) = m.winner.and_then(|id| m.player(id)).cloned() else {
+                    continue;
+                };
+
+                self.rounds[round + 1][index / 2].players[index % 2] = Some(winner);
+            }
+        }
+    }
+
+    /// Returns the matches ready to be played, as `(round, index)` pairs.
+    pub fn pending_matches(&self) -> Vec<(usize, usize)> {
+        let mut matches = Vec::new();
+
+        for (round, r) in self.rounds.iter().enumerate() {
+            for (index, m) in r.iter().enumerate() {
+                if m.winner.is_none()
+                    && m.game_id.is_none()
+                    && m.players.iter().all(|p| p.is_some())
+                {
+                    matches.push((round, index));
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Links a game to a match.
+    pub fn set_game(&mut self, round: usize, index: usize, game_id: i32) {
+        self.rounds[round][index].game_id = Some(game_id);
+    }
+
+    /// Reports the result of a game, returning `false` if it is not part of the tournament.
+    ///
+    /// Drawn matches are unlinked from their game, so they are played again.
+    fn report(&mut self, game_id: i32, winner: Option<i64>) -> bool {
+        let Some(m) = self
+            .rounds
+            .iter_mut()
+            .flatten()
+            .find(|m| m.game_id == Some(game_id))
+        else {
+            return false;
+        };
+
+        m.game_id = None;
+        m.winner = winner;
+        self.propagate();
+
+        true
+    }
+
+    /// Returns the champion, once the final is over.
+    pub fn champion(&self) -> Option<&Player> {
+        let m = self.rounds.last()?.first()?;
+
+        m.player(m.winner?)
+    }
+
+    /// Renders the bracket as a PNG image.
+    pub fn render_bracket(&self) -> Result<Vec<u8>> {
+        let first_round = self.rounds.first().map(|r| r.len()).unwrap_or(1);
+        let width = COLUMN_WIDTH * (self.rounds.len() + 1);
+        let height = MATCH_HEIGHT * first_round + 20;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" font-family=\"sans-serif\" font-size=\"14\">\
+             <rect width=\"100%\" height=\"100%\" fill=\"#1e1e2e\"/>",
+            width, height
+        );
+
+        for (round, r) in self.rounds.iter().enumerate() {
+            let slot = (height - 20) / r.len();
+            let x = 10 + round * COLUMN_WIDTH;
+
+            for (index, m) in r.iter().enumerate() {
+                let y = 10 + index * slot + slot / 2 - 25;
+
+                svg += &format!(
+                    "<rect x=\"{0}\" y=\"{1}\" width=\"200\" height=\"50\" rx=\"6\" fill=\"#313244\" stroke=\"#585b70\"/>",
+                    x, y
+                );
+
+                for (i, player) in m.players.iter().enumerate() {
+                    let (name, weight, fill) = match player {
+                        Some(p) if m.winner == Some(p.id()) => (p.first_name(), "bold", "#a6e3a1"),
+                        Some(p) if m.winner.is_some() => (p.first_name(), "normal", "#6c7086"),
+                        Some(p) => (p.first_name(), "normal", "#cdd6f4"),
+                        None => ("—", "normal", "#6c7086"),
+                    };
+                    let name = name.chars().take(24).collect::<String>();
+
+                    svg += &format!(
+                        "<text x=\"{0}\" y=\"{1}\" font-weight=\"{2}\" fill=\"{3}\">{4}</text>",
+                        x + 8,
+                        y + 20 + i * 22,
+                        weight,
+                        fill,
+                        escape_html(&name)
+                    );
+                }
+
+                // Connects the match to the next round.
+                let next_slot = (height - 20) / (r.len() / 2).max(1);
+                let next_y = 10 + (index / 2) * next_slot + next_slot / 2;
+                svg += &format!(
+                    "<polyline points=\"{0},{1} {2},{1} {2},{3} {4},{3}\" fill=\"none\" stroke=\"#585b70\"/>",
+                    x + 200,
+                    y + 25,
+                    x + 220,
+                    next_y,
+                    x + COLUMN_WIDTH
+                );
+            }
+        }
+
+        let champion = self
+            .champion()
+            .map(|p| format!("🏆 {}", p.first_name()))
+            .unwrap_or_else(|| "🏆 ?".to_string());
+        svg += &format!(
+            "<text x=\"{0}\" y=\"{1}\" font-weight=\"bold\" fill=\"#f9e2af\">{2}</text></svg>",
+            10 + self.rounds.len() * COLUMN_WIDTH,
+            height / 2 + 5,
+            escape_html(&champion)
+        );
+
+        let mut options = usvg::Options::default();
+        options.fontdb_mut().load_system_fonts();
+
+        let tree = usvg::Tree::from_str(&svg, &options)?;
+        let size = tree.size().to_int_size();
+        let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+            .ok_or("Failed to allocate the bracket image")?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+/// A bracket match.
+#[derive(Clone)]
+pub struct Match {
+    /// The players, `None` while the seat is not decided.
+    pub players: [Option<Player>; 2],
+    /// The ID of the winner.
+    pub winner: Option<i64>,
+    /// The ID of the game being played.
+    pub game_id: Option<i32>,
+}
+
+impl Match {
+    /// Creates a new `Match` instance.
+    fn new(players: [Option<Player>; 2]) -> Self {
+        Self {
+            players,
+            winner: None,
+            game_id: None,
+        }
+    }
+
+    /// Returns the player with the given ID.
+    pub fn player(&self, id: i64) -> Option<&Player> {
+        self.players.iter().flatten().find(|p| p.id() == id)
+    }
+}
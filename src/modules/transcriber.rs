@@ -0,0 +1,198 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the voice transcription module.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use ferogram::Result;
+use grammers_client::{grammers_tl_types as tl, types::Media};
+use reqwest::{
+    header::AUTHORIZATION,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    migrations::{self, ChatMigration},
+    storage::Storage,
+};
+
+/// The speech to text endpoint.
+///
+/// It is any HTTP endpoint compatible with the OpenAI transcriptions API, which
+/// takes the audio as the multipart `file`, with the `model` and `language`
+/// fields, and answers with a JSON holding the `text`, either a hosted API or a
+/// local Whisper server.
+pub struct Endpoint {
+    /// The URL of the endpoint.
+    pub url: String,
+    /// The bearer token sent, if any.
+    pub api_key: Option<String>,
+    /// The model asked for.
+    pub model: String,
+}
+
+/// Transcribes the voice notes, on demand or every incoming one in the chats
+/// it is enabled in.
+#[derive(Clone)]
+pub struct Transcriber {
+    /// The persisted settings of each chat.
+    storage: Storage<Data>,
+    /// The endpoint, `None` if not configured.
+    endpoint: Option<Arc<Endpoint>>,
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// In seconds, the longest voice note transcribed.
+    max_duration: i32,
+    /// The most characters of a transcript, the rest being cut.
+    max_length: usize,
+}
+
+impl Transcriber {
+    /// Creates a new `Transcriber` instance.
+    pub fn new(endpoint: Option<Endpoint>, max_duration: i32, max_length: usize) -> Self {
+        Self {
+            storage: Storage::open("transcriber"),
+            endpoint: endpoint.map(Arc::new),
+            client: reqwest::Client::new(),
+            max_duration,
+            max_length,
+        }
+    }
+
+    /// Checks if there is an endpoint to transcribe the voice notes.
+    pub fn is_configured(&self) -> bool {
+        self.endpoint.is_some()
+    }
+
+    /// Returns the longest voice note transcribed, in seconds.
+    pub fn max_duration(&self) -> i32 {
+        self.max_duration
+    }
+
+    /// Returns the settings of a chat.
+    pub async fn settings(&self, chat_id: i64) -> Settings {
+        self.storage
+            .read(|data| data.chats.get(&chat_id).cloned().unwrap_or_default())
+            .await
+    }
+
+    /// Updates the settings of a chat, returning the new ones.
+    pub async fn update(&self, chat_id: i64, f: impl FnOnce(&mut Settings)) -> Settings {
+        self.storage
+            .write(|data| {
+                let settings = data.chats.entry(chat_id).or_default();
+                f(settings);
+
+                settings.clone()
+            })
+            .await
+    }
+
+    /// Transcribes a voice note, spoken in the given language as a hint, such
+    /// as `pt` or `pt-BR`.
+    ///
+    /// The transcript is cut to the maximum length.
+    pub async fn transcribe(
+        &self,
+        bytes: Vec<u8>,
+        mime_type: &str,
+        language: &str,
+    ) -> Result<String> {
+        let endpoint = self
+            .endpoint
+            .as_ref()
+            .ok_or("The speech to text endpoint is not configured")?;
+
+        // The endpoints take the ISO 639-1 code, without the region.
+        let language = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let part = Part::bytes(bytes)
+            .file_name("voice.ogg")
+            .mime_str(mime_type)?;
+        let mut form = Form::new()
+            .part("file", part)
+            .text("model", endpoint.model.clone());
+        if !language.is_empty() {
+            form = form.text("language", language);
+        }
+
+        let mut request = self.client.post(&endpoint.url).multipart(form);
+        if let Some(key) = &endpoint.api_key {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", key));
+        }
+
+        let json = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        let text = json
+            .get("text")
+            .and_then(|text| text.as_str())
+            .ok_or("The speech to text endpoint gave no transcript")?
+            .trim();
+
+        Ok(match text.char_indices().nth(self.max_length) {
+            Some((end, _)) => format!("{}…", text[..end].trim_end()),
+            None => text.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Transcriber {
+    fn name(&self) -> &'static str {
+        "transcription settings"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::move_key(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// Returns the duration of a voice note, in seconds, `None` if the media is
+/// not one.
+pub fn voice_duration(media: &Media) -> Option<i32> {
+    let Media::Document(document) = media else {
+        return None;
+    };
+    let Some(tl::enums::Document::Document(raw)) = &document.raw.document else {
+        return None;
+    };
+
+    raw.attributes.iter().find_map(|attribute| match attribute {
+        tl::enums::DocumentAttribute::Audio(audio) if audio.voice => Some(audio.duration),
+        _ => None,
+    })
+}
+
+/// The transcription settings of a chat.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    /// Whether every incoming voice note is transcribed.
+    pub enabled: bool,
+    /// The language the voice notes are spoken in, the current locale if unset.
+    pub language: Option<String>,
+}
+
+/// The persisted transcription data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The settings of each chat.
+    chats: HashMap<i64, Settings>,
+}
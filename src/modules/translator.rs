@@ -0,0 +1,77 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the translator module.
+
+use std::time::Duration;
+
+use ferogram::Result;
+use serde_json::Value;
+
+/// The URL of the Google Translate API.
+const API_URL: &str = "https://translate.googleapis.com/translate_a/single";
+
+/// How long each request is given.
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Translates the texts between the languages.
+#[derive(Clone)]
+pub struct Translator {
+    /// The HTTP client.
+    client: reqwest::Client,
+}
+
+impl Translator {
+    /// Creates a new `Translator` instance.
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("Failed to build the HTTP client"),
+        }
+    }
+
+    /// Translates a text to the given language, detecting its language.
+    pub async fn translate(&self, text: &str, target: &str) -> Result<Translation> {
+        let json = self
+            .client
+            .get(API_URL)
+            .query(&[
+                ("client", "gtx"),
+                ("sl", "auto"),
+                ("tl", target),
+                ("dt", "t"),
+                ("q", text),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        // The text is translated sentence by sentence.
+        let text = json[0]
+            .as_array()
+            .ok_or("Google Translate gave no translation")?
+            .iter()
+            .filter_map(|sentence| sentence[0].as_str())
+            .collect::<String>();
+        let source = json[2].as_str().unwrap_or("?").to_string();
+
+        Ok(Translation { text, source })
+    }
+}
+
+/// A translated text.
+pub struct Translation {
+    /// The translated text.
+    pub text: String,
+    /// The language the text was detected in.
+    pub source: String,
+}
@@ -0,0 +1,212 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the trivia questions module.
+
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+
+use ferogram::Result;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+/// The URL of the Open Trivia DB API.
+const OPEN_TRIVIA_URL: &str = "https://opentdb.com/api.php";
+
+/// How many questions are fetched at once, the most the API gives.
+const FETCH_AMOUNT: usize = 50;
+
+/// A trivia question, with its answers in the order shown.
+#[derive(Clone)]
+pub struct Question {
+    /// The question.
+    pub text: String,
+    /// The answers.
+    pub answers: Vec<String>,
+    /// The index of the correct answer.
+    pub correct: usize,
+}
+
+/// A question as written in the packs and given by the API.
+#[derive(Clone, Deserialize)]
+struct RawQuestion {
+    question: String,
+    correct_answer: String,
+    incorrect_answers: Vec<String>,
+}
+
+impl RawQuestion {
+    /// Converts into a question, shuffling its answers.
+    fn into_question(self) -> Question {
+        let mut answers = self.incorrect_answers;
+        answers.push(self.correct_answer.clone());
+        answers.shuffle(&mut rand::thread_rng());
+
+        Question {
+            text: self.question,
+            correct: answers
+                .iter()
+                .position(|answer| *answer == self.correct_answer)
+                .unwrap_or_default(),
+            answers,
+        }
+    }
+}
+
+/// The response of the API.
+#[derive(Deserialize)]
+struct Response {
+    response_code: u8,
+    results: Vec<RawQuestion>,
+}
+
+/// The trivia questions, from the local packs or from Open Trivia DB.
+#[derive(Clone)]
+pub struct QuestionBank {
+    /// The questions fetched and not yet asked.
+    cache: Arc<Mutex<Vec<RawQuestion>>>,
+    /// The questions of each local pack, by name.
+    packs: Arc<HashMap<String, Vec<RawQuestion>>>,
+    /// How many questions a game has.
+    amount: usize,
+    /// How long the players have to answer each question.
+    answer_time: Duration,
+}
+
+impl QuestionBank {
+    /// Creates a new `QuestionBank` instance, loading the packs from `packs_dir`.
+    ///
+    /// Each pack is a JSON file with a list of questions, named after the file.
+    pub fn new<P: AsRef<Path>>(packs_dir: P, amount: usize, answer_time: Duration) -> Self {
+        let mut packs = HashMap::new();
+
+        if let Ok(entries) = std::fs::read_dir(packs_dir) {
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                        continue;
+                    };
+
+                    match std::fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|content| {
+                            serde_json::from_str::<Vec<RawQuestion>>(&content)
+                                .map_err(|e| e.to_string())
+                        }) {
+                        Ok(questions) => {
+                            packs.insert(name.to_lowercase(), questions);
+                        }
+                        Err(e) => log::error!("Failed to load the trivia pack {:?}: {}", path, e),
+                    }
+                }
+            }
+        }
+
+        Self {
+            cache: Arc::new(Mutex::new(Vec::new())),
+            packs: Arc::new(packs),
+            amount,
+            answer_time,
+        }
+    }
+
+    /// Returns how long the players have to answer each question.
+    pub fn answer_time(&self) -> Duration {
+        self.answer_time
+    }
+
+    /// Returns the names of the packs, sorted.
+    pub fn packs(&self) -> Vec<String> {
+        let mut names = self.packs.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+
+        names
+    }
+
+    /// Returns the questions of a game from a pack, `None` if there is no such pack.
+    pub fn from_pack(&self, pack: &str) -> Option<Vec<Question>> {
+        let questions = self.packs.get(&pack.to_lowercase())?;
+
+        Some(
+            questions
+                .choose_multiple(&mut rand::thread_rng(), self.amount)
+                .cloned()
+                .map(RawQuestion::into_question)
+                .collect(),
+        )
+    }
+
+    /// Returns the questions of a game from Open Trivia DB, fetching more once the
+    /// cached ones run out.
+    pub async fn fetch(&self) -> Result<Vec<Question>> {
+        let mut cache = self.cache.lock().await;
+
+        if cache.len() < self.amount {
+            let response = reqwest::get(format!(
+                "{0}?amount={1}&type=multiple&encode=url3986",
+                OPEN_TRIVIA_URL, FETCH_AMOUNT
+            ))
+            .await?
+            .json::<Response>()
+            .await?;
+
+            if response.response_code != 0 {
+                return Err(format!(
+                    "Open Trivia DB answered with code {}",
+                    response.response_code
+                )
+                .into());
+            }
+
+            cache.extend(response.results.into_iter().map(|question| {
+                RawQuestion {
+                    question: percent_decode(&question.question),
+                    correct_answer: percent_decode(&question.correct_answer),
+                    incorrect_answers: question
+                        .incorrect_answers
+                        .iter()
+                        .map(|answer| percent_decode(answer))
+                        .collect(),
+                }
+            }));
+        }
+
+        let amount = self.amount.min(cache.len());
+        Ok(cache
+            .drain(..amount)
+            .map(RawQuestion::into_question)
+            .collect())
+    }
+}
+
+/// Decodes a percent-encoded text, as the API encodes it.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,194 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the unread digest module.
+
+use std::sync::Arc;
+
+use ferogram::Result;
+use grammers_client::{
+    button::{self, Inline},
+    grammers_tl_types as tl,
+    types::Chat,
+    Client,
+};
+use maplit::hashmap;
+use tokio::sync::Mutex;
+
+use super::i18n::I18n;
+use crate::utils::escape_html;
+
+/// The maximum length of the last message preview.
+const PREVIEW_LENGTH: usize = 60;
+
+/// The unread digest.
+#[derive(Clone)]
+pub struct Unread {
+    /// The chats with unread messages found by the last scan.
+    entries: Arc<Mutex<Vec<Entry>>>,
+}
+
+impl Unread {
+    /// Creates a new `Unread` instance.
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Scans the dialogs for unread messages, keeping up to `limit` chats.
+    ///
+    /// Returns the number of chats found.
+    pub async fn scan(&self, client: &Client, limit: usize) -> Result<usize> {
+        let mut entries = Vec::new();
+        let mut dialogs = client.iter_dialogs();
+
+        while let Some(dialog) = dialogs.next().await? {
+            let count = match &dialog.raw {
+                tl::enums::Dialog::Dialog(raw) => raw.unread_count,
+                tl::enums::Dialog::Folder(_) => 0,
+            };
+            if count == 0 {
+                continue;
+            }
+
+            let preview = dialog
+                .last_message
+                .as_ref()
+                .map(|message| {
+                    let text = message
+                        .text()
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ");
+
+                    if text.chars().count() > PREVIEW_LENGTH {
+                        text.chars().take(PREVIEW_LENGTH).collect::<String>() + "…"
+                    } else {
+                        text
+                    }
+                })
+                .unwrap_or_default();
+
+            entries.push(Entry {
+                chat: dialog.chat().clone(),
+                count,
+                preview,
+            });
+
+            if entries.len() >= limit {
+                break;
+            }
+        }
+
+        let len = entries.len();
+        *self.entries.lock().await = entries;
+
+        Ok(len)
+    }
+
+    /// Takes a chat out of the digest, so it can be marked as read.
+    pub async fn take(&self, chat_id: i64) -> Option<Chat> {
+        let mut entries = self.entries.lock().await;
+        let index = entries.iter().position(|e| e.chat.id() == chat_id)?;
+
+        Some(entries.remove(index).chat)
+    }
+
+    /// Takes every chat out of the digest.
+    pub async fn take_all(&self) -> Vec<Chat> {
+        self.entries
+            .lock()
+            .await
+            .drain(..)
+            .map(|e| e.chat)
+            .collect()
+    }
+
+    /// Renders the digest grouped by chat type, with a mark read button per chat.
+    ///
+    /// Returns `None` when there is nothing left unread.
+    pub async fn render(&self, i18n: &I18n) -> Option<(String, Vec<Vec<Inline>>)> {
+        let entries = self.entries.lock().await;
+        if entries.is_empty() {
+            return None;
+        }
+
+        let mut text = i18n.translate("unread_header");
+        for (key, kind) in [
+            ("unread_users", Kind::User),
+            ("unread_groups", Kind::Group),
+            ("unread_channels", Kind::Channel),
+        ] {
+            let lines = entries
+                .iter()
+                .filter(|e| Kind::of(&e.chat) == kind)
+                .map(|e| {
+                    format!(
+                        "• <b>{0}</b> (<code>{1}</code>): <i>{2}</i>",
+                        escape_html(e.chat.name()),
+                        e.count,
+                        escape_html(&e.preview)
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            if !lines.is_empty() {
+                text += &format!("\n\n{}\n{}", i18n.translate(key), lines.join("\n"));
+            }
+        }
+
+        let mut buttons = entries
+            .iter()
+            .map(|e| {
+                vec![button::inline(
+                    i18n.translate_with_args(
+                        "unread_mark_read",
+                        hashmap! { "chat" => e.chat.name().to_string() },
+                    ),
+                    format!("unread read {}", e.chat.id()),
+                )]
+            })
+            .collect::<Vec<_>>();
+        buttons.push(vec![button::inline(
+            i18n.translate("unread_mark_all_read"),
+            "unread all",
+        )]);
+
+        Some((text, buttons))
+    }
+}
+
+/// A chat with unread messages.
+struct Entry {
+    /// The chat.
+    chat: Chat,
+    /// The number of unread messages.
+    count: i32,
+    /// The preview of the last message.
+    preview: String,
+}
+
+/// The chat types the digest is grouped by.
+#[derive(PartialEq)]
+enum Kind {
+    User,
+    Group,
+    Channel,
+}
+
+impl Kind {
+    /// Returns the type of a chat.
+    fn of(chat: &Chat) -> Self {
+        match chat {
+            Chat::User(_) => Self::User,
+            Chat::Group(_) => Self::Group,
+            Chat::Channel(_) => Self::Channel,
+        }
+    }
+}
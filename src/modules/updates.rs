@@ -0,0 +1,82 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the processed updates module.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// The store shared by the filters.
+static SHARED: LazyLock<Updates> = LazyLock::new(|| Updates {
+    storage: Storage::open("updates"),
+    max_age: Arc::new(AtomicU64::new(0)),
+});
+
+/// The last processed message of each chat, so the updates caught up on a
+/// restart do not run the same commands again.
+#[derive(Clone)]
+pub struct Updates {
+    /// The persisted message IDs.
+    storage: Storage<Data>,
+    /// In seconds, how old a command can be to still run, `0` for any age.
+    max_age: Arc<AtomicU64>,
+}
+
+impl Updates {
+    /// Returns the shared store, as the filters cannot be injected.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    /// Sets how old a command can be to still run, `0` for any age.
+    pub fn set_max_age(&self, seconds: u64) {
+        self.max_age.store(seconds, Ordering::Relaxed);
+    }
+
+    /// Checks if a message is older than the catch-up window.
+    pub fn is_stale(&self, date: DateTime<Utc>) -> bool {
+        let max_age = self.max_age.load(Ordering::Relaxed);
+
+        max_age > 0 && (Utc::now() - date).num_seconds() > max_age as i64
+    }
+
+    /// Marks a message as processed.
+    ///
+    /// Returns `false` if it, or a later message of the chat, already was.
+    /// The `scope` tells the clients apart, as each numbers the messages its own way.
+    pub async fn process(&self, scope: &str, chat_id: i64, message_id: i32) -> bool {
+        let key = format!("{0}:{1}", scope, chat_id);
+
+        self.storage
+            .write(|data| match data.chats.get(&key) {
+                Some(last) if *last >= message_id => false,
+                _ => {
+                    data.chats.insert(key, message_id);
+                    true
+                }
+            })
+            .await
+    }
+}
+
+/// The persisted message IDs.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The last processed message ID of each chat.
+    chats: HashMap<String, i32>,
+}
@@ -0,0 +1,350 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the URL scanner module.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ferogram::Result;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use super::{
+    migrations::{self, ChatMigration},
+    reposts::Action,
+    storage::Storage,
+};
+
+/// The URL of the Google Safe Browsing lookup API.
+const SAFE_BROWSING_URL: &str = "https://safebrowsing.googleapis.com/v4/threatMatches:find";
+/// The URL of the VirusTotal API.
+const VIRUSTOTAL_URL: &str = "https://www.virustotal.com/api/v3";
+/// The URL of the urlscan.io API.
+const URLSCAN_URL: &str = "https://urlscan.io/api/v1";
+
+/// How many VirusTotal engines must flag a URL for it to be malicious.
+const VIRUSTOTAL_MALICIOUS_ENGINES: u64 = 2;
+
+/// How long urlscan.io is given before its result is first asked.
+const URLSCAN_FIRST_POLL: Duration = Duration::from_secs(10);
+/// How often the result of urlscan.io is asked after the first time.
+const URLSCAN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How many times the result of urlscan.io is asked before giving up.
+const URLSCAN_POLLS: usize = 6;
+
+/// The most verdicts kept in the cache.
+const MAX_CACHED: usize = 1000;
+
+/// A service that scans the URLs.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// Google Safe Browsing, which only knows the listed URLs but answers at once.
+    #[default]
+    SafeBrowsing,
+    /// VirusTotal, which asks dozens of engines.
+    VirusTotal,
+    /// urlscan.io, which visits the page, taking a while.
+    Urlscan,
+}
+
+impl Provider {
+    /// Returns the name of the provider.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SafeBrowsing => "Google Safe Browsing",
+            Self::VirusTotal => "VirusTotal",
+            Self::Urlscan => "urlscan.io",
+        }
+    }
+}
+
+/// What a provider thinks of a URL.
+#[derive(Clone)]
+pub enum Verdict {
+    /// Nothing wrong was found.
+    Clean,
+    /// The provider does not know it yet.
+    Unknown,
+    /// Something may be wrong, with the details.
+    Suspicious(String),
+    /// It is malicious, with the details.
+    Malicious(String),
+}
+
+/// Scans the URLs posted in the moderated chats, removing the malicious ones.
+#[derive(Clone)]
+pub struct UrlScanner {
+    /// The persisted settings of each chat.
+    storage: Storage<Data>,
+    /// The service that scans the URLs.
+    provider: Provider,
+    /// The API key of the provider, `None` if not configured.
+    api_key: Option<Arc<String>>,
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The last verdict of each URL, with when it was given.
+    cache: Arc<Mutex<HashMap<String, (Verdict, Instant)>>>,
+    /// How long a verdict is kept in the cache.
+    cache_ttl: Duration,
+}
+
+impl UrlScanner {
+    /// Creates a new `UrlScanner` instance.
+    pub fn new(provider: Provider, api_key: Option<String>, cache_ttl: Duration) -> Self {
+        Self {
+            storage: Storage::open("url_scanner"),
+            provider,
+            api_key: api_key.map(Arc::new),
+            client: reqwest::Client::new(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache_ttl,
+        }
+    }
+
+    /// Checks if there is an API key to scan the URLs.
+    pub fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    /// Returns the service that scans the URLs.
+    pub fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    /// Returns the settings of a chat.
+    pub async fn settings(&self, chat_id: i64) -> Settings {
+        self.storage
+            .read(|data| data.chats.get(&chat_id).cloned().unwrap_or_default())
+            .await
+    }
+
+    /// Updates the settings of a chat, returning the new ones.
+    pub async fn update(&self, chat_id: i64, f: impl FnOnce(&mut Settings)) -> Settings {
+        self.storage
+            .write(|data| {
+                let settings = data.chats.entry(chat_id).or_default();
+                f(settings);
+
+                settings.clone()
+            })
+            .await
+    }
+
+    /// Scans a URL, answering from the cache if it was recently scanned.
+    ///
+    /// The unknown verdicts are not cached, so the URL is scanned again.
+    pub async fn scan(&self, url: &str) -> Result<Verdict> {
+        if let Some((verdict, scanned_at)) = self.cache.lock().await.get(url) {
+            if scanned_at.elapsed() < self.cache_ttl {
+                return Ok(verdict.clone());
+            }
+        }
+
+        let key = self
+            .api_key
+            .as_deref()
+            .ok_or("The URL scanner is not configured")?;
+        let verdict = match self.provider {
+            Provider::SafeBrowsing => self.safe_browsing(key, url).await?,
+            Provider::VirusTotal => self.virustotal(key, url).await?,
+            Provider::Urlscan => self.urlscan(key, url).await?,
+        };
+
+        if !matches!(verdict, Verdict::Unknown) {
+            let mut cache = self.cache.lock().await;
+            cache.retain(|_, (_, scanned_at)| scanned_at.elapsed() < self.cache_ttl);
+            if cache.len() < MAX_CACHED {
+                cache.insert(url.to_string(), (verdict.clone(), Instant::now()));
+            }
+        }
+
+        Ok(verdict)
+    }
+
+    /// Scans a URL on Google Safe Browsing.
+    async fn safe_browsing(&self, key: &str, url: &str) -> Result<Verdict> {
+        let json = self
+            .client
+            .post(SAFE_BROWSING_URL)
+            .query(&[("key", key)])
+            .json(&json!({
+                "client": {
+                    "clientId": "grymbb",
+                    "clientVersion": env!("CARGO_PKG_VERSION"),
+                },
+                "threatInfo": {
+                    "threatTypes": [
+                        "MALWARE",
+                        "SOCIAL_ENGINEERING",
+                        "UNWANTED_SOFTWARE",
+                        "POTENTIALLY_HARMFUL_APPLICATION",
+                    ],
+                    "platformTypes": ["ANY_PLATFORM"],
+                    "threatEntryTypes": ["URL"],
+                    "threatEntries": [{ "url": url }],
+                },
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let mut threats = json["matches"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|found| found["threatType"].as_str())
+            .map(|threat| threat.to_lowercase().replace('_', " "))
+            .collect::<Vec<_>>();
+        threats.sort();
+        threats.dedup();
+
+        Ok(if threats.is_empty() {
+            Verdict::Clean
+        } else {
+            Verdict::Malicious(threats.join(", "))
+        })
+    }
+
+    /// Scans a URL on VirusTotal.
+    ///
+    /// An unknown URL is submitted, so it is known when scanned again.
+    async fn virustotal(&self, key: &str, url: &str) -> Result<Verdict> {
+        let response = self
+            .client
+            .get(format!("{0}/urls/{1}", VIRUSTOTAL_URL, URL_SAFE_NO_PAD.encode(url)))
+            .header("x-apikey", key)
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            self.client
+                .post(format!("{0}/urls", VIRUSTOTAL_URL))
+                .header("x-apikey", key)
+                .form(&[("url", url)])
+                .send()
+                .await?
+                .error_for_status()?;
+
+            return Ok(Verdict::Unknown);
+        }
+
+        let json = response.error_for_status()?.json::<Value>().await?;
+        let stats = &json["data"]["attributes"]["last_analysis_stats"];
+        let count = |key: &str| stats[key].as_u64().unwrap_or(0);
+
+        let (malicious, suspicious) = (count("malicious"), count("suspicious"));
+        let total = stats
+            .as_object()
+            .map(|stats| stats.values().filter_map(Value::as_u64).sum::<u64>())
+            .unwrap_or(0);
+        let details = format!("{0}/{1}", malicious + suspicious, total);
+
+        Ok(if malicious >= VIRUSTOTAL_MALICIOUS_ENGINES {
+            Verdict::Malicious(details)
+        } else if malicious + suspicious > 0 {
+            Verdict::Suspicious(details)
+        } else {
+            Verdict::Clean
+        })
+    }
+
+    /// Scans a URL on urlscan.io, waiting for the page to be visited.
+    async fn urlscan(&self, key: &str, url: &str) -> Result<Verdict> {
+        let json = self
+            .client
+            .post(format!("{0}/scan/", URLSCAN_URL))
+            .header("API-Key", key)
+            .json(&json!({ "url": url, "visibility": "unlisted" }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        let uuid = json["uuid"].as_str().ok_or("urlscan.io gave no scan ID")?;
+
+        tokio::time::sleep(URLSCAN_FIRST_POLL).await;
+        for _ in 0..URLSCAN_POLLS {
+            let response = self
+                .client
+                .get(format!("{0}/result/{1}/", URLSCAN_URL, uuid))
+                .send()
+                .await?;
+
+            // The result is missing until the scan finishes.
+            if response.status() == StatusCode::NOT_FOUND {
+                tokio::time::sleep(URLSCAN_POLL_INTERVAL).await;
+                continue;
+            }
+
+            let json = response.error_for_status()?.json::<Value>().await?;
+            let overall = &json["verdicts"]["overall"];
+            let categories = overall["categories"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let details = if categories.is_empty() {
+                format!("score {}", overall["score"].as_i64().unwrap_or(0))
+            } else {
+                categories
+            };
+
+            return Ok(if overall["malicious"].as_bool().unwrap_or(false) {
+                Verdict::Malicious(details)
+            } else if overall["score"].as_i64().unwrap_or(0) > 0 {
+                Verdict::Suspicious(details)
+            } else {
+                Verdict::Clean
+            });
+        }
+
+        Ok(Verdict::Unknown)
+    }
+}
+
+#[async_trait]
+impl ChatMigration for UrlScanner {
+    fn name(&self) -> &'static str {
+        "URL scanner settings"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::move_key(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// The URL scanner settings of a chat.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    /// Whether the URLs are scanned.
+    pub enabled: bool,
+    /// What is done with a message with a malicious URL.
+    pub action: Action,
+}
+
+/// The persisted URL scanner data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The settings of each chat.
+    chats: HashMap<i64, Settings>,
+}
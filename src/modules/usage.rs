@@ -0,0 +1,172 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the usage accounting module.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::AddAssign,
+    sync::Arc,
+};
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use super::storage::Storage;
+
+/// How many days of usage are kept.
+const DAYS_KEPT: i64 = 30;
+
+/// The size of the parts of a file upload, each a request.
+const UPLOAD_PART_SIZE: u64 = 512 * 1024;
+
+/// The traffic and the requests of the heavy plugins, per day.
+#[derive(Clone)]
+pub struct Usage {
+    /// The persisted counters.
+    storage: Storage<Data>,
+    /// In bytes, the daily budget of each plugin, counting both directions.
+    budgets: Arc<HashMap<String, u64>>,
+}
+
+impl Usage {
+    /// Creates a new `Usage` instance with the daily budgets, in megabytes.
+    ///
+    /// A budget of zero is no budget.
+    pub fn new(budgets: HashMap<String, u64>) -> Self {
+        let budgets = budgets
+            .into_iter()
+            .filter(|(_, megabytes)| *megabytes > 0)
+            .map(|(plugin, megabytes)| (plugin, megabytes * 1024 * 1024))
+            .collect();
+
+        Self {
+            storage: Storage::open("usage"),
+            budgets: Arc::new(budgets),
+        }
+    }
+
+    /// Adds to the counters of a plugin for today.
+    pub async fn record(&self, plugin: &str, counters: Counters) {
+        let today = Local::now().date_naive();
+
+        self.storage
+            .write(|data| {
+                *data
+                    .days
+                    .entry(today)
+                    .or_default()
+                    .entry(plugin.to_string())
+                    .or_default() += counters;
+
+                data.days
+                    .retain(|day, _| *day > today - TimeDelta::days(DAYS_KEPT));
+            })
+            .await;
+    }
+
+    /// Returns the counters of each plugin summed over the last days, today
+    /// included, sorted by name.
+    pub async fn summary(&self, days: i64) -> Vec<(String, Counters)> {
+        let since = Local::now().date_naive() - TimeDelta::days(days - 1);
+
+        self.storage
+            .read(|data| {
+                let mut summary = BTreeMap::<String, Counters>::new();
+                for (_, plugins) in data.days.range(since..) {
+                    for (plugin, counters) in plugins {
+                        *summary.entry(plugin.clone()).or_default() += *counters;
+                    }
+                }
+
+                summary.into_iter().collect()
+            })
+            .await
+    }
+
+    /// Returns the daily budget of a plugin, in bytes.
+    pub fn budget(&self, plugin: &str) -> Option<u64> {
+        self.budgets.get(plugin).copied()
+    }
+
+    /// Checks if a plugin used up its budget for today.
+    ///
+    /// Returns the budget, in bytes, if so.
+    pub async fn exceeded(&self, plugin: &str) -> Option<u64> {
+        let budget = self.budget(plugin)?;
+        let today = Local::now().date_naive();
+        let used = self
+            .storage
+            .read(|data| {
+                data.days
+                    .get(&today)
+                    .and_then(|plugins| plugins.get(plugin))
+                    .map(Counters::traffic)
+                    .unwrap_or_default()
+            })
+            .await;
+
+        (used >= budget).then_some(budget)
+    }
+}
+
+/// The traffic and the requests of a plugin.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Counters {
+    /// The bytes downloaded.
+    pub downloaded: u64,
+    /// The bytes uploaded.
+    pub uploaded: u64,
+    /// The requests made to Telegram.
+    pub calls: u64,
+}
+
+impl Counters {
+    /// Counts a download from Telegram, made of the given requests.
+    pub fn download(bytes: u64, calls: u64) -> Self {
+        Self {
+            downloaded: bytes,
+            calls,
+            ..Default::default()
+        }
+    }
+
+    /// Counts a download from elsewhere, which makes no requests to Telegram.
+    pub fn fetch(bytes: u64) -> Self {
+        Self::download(bytes, 0)
+    }
+
+    /// Counts a file upload to Telegram and the message sending it.
+    pub fn upload(bytes: u64) -> Self {
+        Self {
+            uploaded: bytes,
+            calls: bytes.div_ceil(UPLOAD_PART_SIZE) + 1,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the bytes transferred in both directions.
+    pub fn traffic(&self) -> u64 {
+        self.downloaded + self.uploaded
+    }
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.downloaded += other.downloaded;
+        self.uploaded += other.uploaded;
+        self.calls += other.calls;
+    }
+}
+
+/// The persisted counters.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The counters of each plugin, by day.
+    days: BTreeMap<NaiveDate, BTreeMap<String, Counters>>,
+}
@@ -0,0 +1,288 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the usernames checker module.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use grammers_client::{grammers_tl_types as tl, Client, InputMessage, InvocationError};
+use maplit::hashmap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{i18n::I18n, shutdown::Shutdown, storage::Storage};
+use crate::{utils::retry_on_flood, Sender};
+
+/// How long a checked username is kept in the cache.
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The shortest and the longest a username can be.
+const USERNAME_LENGTH: (usize, usize) = (5, 32);
+
+/// Whether a username can be taken.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Availability {
+    /// Nobody has it.
+    Free,
+    /// Someone has it.
+    Taken,
+    /// It is sold on Fragment.
+    ForSale,
+    /// It is not a valid username.
+    Invalid,
+}
+
+/// The usernames checker, which also watches usernames until they are free.
+#[derive(Clone)]
+pub struct Usernames {
+    /// The watched usernames.
+    storage: Storage<Data>,
+    /// The last result of each username, with when it was checked.
+    cache: Arc<Mutex<HashMap<String, (Availability, Instant)>>>,
+    /// The pause between two requests.
+    delay: Duration,
+    /// How often the watched usernames are checked.
+    interval: Duration,
+}
+
+impl Usernames {
+    /// Creates a new `Usernames` instance.
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        Self {
+            storage: Storage::open("usernames"),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            delay,
+            interval,
+        }
+    }
+
+    /// Checks many usernames, pausing between the requests and waiting out the flood waits.
+    ///
+    /// The usernames recently checked are answered from the cache, and the ones that
+    /// failed have no availability.
+    pub async fn check_many(
+        &self,
+        client: &Client,
+        usernames: &[String],
+    ) -> Vec<(String, Option<Availability>)> {
+        let mut results = Vec::with_capacity(usernames.len());
+        let mut requested = false;
+
+        for username in usernames {
+            let username = normalize(username);
+            if let Some(availability) = self.cached(&username).await {
+                results.push((username, Some(availability)));
+                continue;
+            }
+
+            if requested {
+                tokio::time::sleep(self.delay).await;
+            }
+            requested = is_valid(&username);
+
+            match self.check(client, &username).await {
+                Ok(availability) => results.push((username, Some(availability))),
+                Err(e) => {
+                    log::error!("Failed to check the username {}: {}", username, e);
+                    results.push((username, None));
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Checks a username, skipping the cache.
+    pub async fn check(
+        &self,
+        client: &Client,
+        username: &str,
+    ) -> std::result::Result<Availability, InvocationError> {
+        let username = normalize(username);
+        if !is_valid(&username) {
+            return Ok(Availability::Invalid);
+        }
+
+        // It runs out of the handler of the messages between the clients, so it
+        // can wait as long as the flood threshold allows.
+        let result = retry_on_flood(Duration::MAX, || {
+            client.invoke(&tl::functions::account::CheckUsername {
+                username: username.clone(),
+            })
+        })
+        .await;
+        let availability = match result {
+            Ok(tl::enums::Bool::BoolTrue) => Availability::Free,
+            Ok(tl::enums::Bool::BoolFalse) => Availability::Taken,
+            Err(InvocationError::Rpc(rpc)) if rpc.name == "USERNAME_PURCHASE_AVAILABLE" => {
+                Availability::ForSale
+            }
+            Err(InvocationError::Rpc(rpc)) if rpc.name == "USERNAME_INVALID" => {
+                Availability::Invalid
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.cache
+            .lock()
+            .await
+            .insert(username, (availability, Instant::now()));
+
+        Ok(availability)
+    }
+
+    /// Returns the cached availability of a username, if still fresh.
+    async fn cached(&self, username: &str) -> Option<Availability> {
+        let mut cache = self.cache.lock().await;
+        cache.retain(|_, (_, checked_at)| checked_at.elapsed() < CACHE_TTL);
+
+        cache.get(username).map(|(availability, _)| *availability)
+    }
+
+    /// Starts watching a username.
+    ///
+    /// Returns `false` if it was already watched.
+    pub async fn watch(&self, username: &str) -> bool {
+        let username = normalize(username);
+
+        self.storage
+            .write(|data| {
+                if data.watched.iter().any(|w| w.username == username) {
+                    false
+                } else {
+                    data.watched.push(Watched {
+                        username,
+                        free: false,
+                    });
+                    true
+                }
+            })
+            .await
+    }
+
+    /// Stops watching a username.
+    ///
+    /// Returns `false` if it was not watched.
+    pub async fn unwatch(&self, username: &str) -> bool {
+        let username = normalize(username);
+
+        self.storage
+            .write(|data| {
+                let len = data.watched.len();
+                data.watched.retain(|w| w.username != username);
+
+                data.watched.len() != len
+            })
+            .await
+    }
+
+    /// Returns the watched usernames, with whether they were free on the last check.
+    pub async fn watched(&self) -> Vec<(String, bool)> {
+        self.storage
+            .read(|data| {
+                data.watched
+                    .iter()
+                    .map(|w| (w.username.clone(), w.free))
+                    .collect()
+            })
+            .await
+    }
+
+    /// Spawns the task that checks the watched usernames, notifying the owner
+    /// when one of them becomes free.
+    pub fn start(self, client: Client, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                tokio::time::sleep(self.interval).await;
+
+                for (index, (username, _)) in self.watched().await.into_iter().enumerate() {
+                    if index > 0 {
+                        tokio::time::sleep(self.delay).await;
+                    }
+
+                    let free = match self.check(&client, &username).await {
+                        Ok(availability) => availability == Availability::Free,
+                        Err(e) => {
+                            log::error!("Failed to check the watched username {}: {}", username, e);
+                            continue;
+                        }
+                    };
+
+                    // It may have been unwatched meanwhile.
+                    let changed = self
+                        .storage
+                        .write(|data| {
+                            data.watched
+                                .iter_mut()
+                                .find(|w| w.username == username)
+                                .map(|w| std::mem::replace(&mut w.free, free) != free)
+                                .unwrap_or(false)
+                        })
+                        .await;
+
+                    if changed && free {
+                        let text = i18n.translate_with_args(
+                            "usernames_free",
+                            hashmap! { "username" => username.clone() },
+                        );
+
+                        if let Err(e) = tx
+                            .send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                            .await
+                        {
+                            log::error!("Failed to notify the free username: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Removes the `@` and the link prefix of a username, lowercasing it.
+pub fn normalize(username: &str) -> String {
+    username
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("t.me/")
+        .trim_start_matches('@')
+        .to_lowercase()
+}
+
+/// Checks if a username follows the rules Telegram enforces, saving the requests
+/// that could only fail.
+fn is_valid(username: &str) -> bool {
+    let length = username.chars().count();
+
+    (USERNAME_LENGTH.0..=USERNAME_LENGTH.1).contains(&length)
+        && username.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !username.ends_with('_')
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The persisted usernames data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The watched usernames.
+    watched: Vec<Watched>,
+}
+
+/// A watched username.
+#[derive(Clone, Serialize, Deserialize)]
+struct Watched {
+    /// The username, normalized.
+    username: String,
+    /// Whether it was free on the last check.
+    free: bool,
+}
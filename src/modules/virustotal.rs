@@ -0,0 +1,288 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the VirusTotal file scanner module.
+
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ferogram::Result;
+use maplit::hashmap;
+use reqwest::{
+    multipart::{Form, Part},
+    StatusCode,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::i18n::I18n;
+
+/// The URL of the VirusTotal API.
+const API_URL: &str = "https://www.virustotal.com/api/v3";
+/// The URL of the report of a file, by its SHA-256.
+const REPORT_URL: &str = "https://www.virustotal.com/gui/file";
+
+/// How often a submitted file is checked for its analysis, within the free quota.
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// How many times a submitted file is checked before giving up.
+const POLLS: usize = 6;
+
+/// The size of the chunks a file is hashed in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// What VirusTotal knows of a file.
+pub enum Report {
+    /// It was analyzed, with how many engines flagged it.
+    Analyzed {
+        /// The engines that found it malicious.
+        malicious: u64,
+        /// The engines that found it suspicious.
+        suspicious: u64,
+        /// The engines that analyzed it.
+        total: u64,
+    },
+    /// It was submitted, but not analyzed yet.
+    Queued,
+    /// It is unknown and too big to be submitted.
+    Unknown,
+}
+
+/// The scan of a file.
+pub struct Scan {
+    /// The SHA-256 of the file, in hex.
+    pub sha256: String,
+    /// What VirusTotal knows of it.
+    pub report: Report,
+}
+
+impl Scan {
+    /// Renders the verdict, linking to the full report.
+    pub fn render(&self, i18n: &I18n) -> String {
+        let url = format!("{0}/{1}", REPORT_URL, self.sha256);
+
+        match self.report {
+            Report::Analyzed {
+                malicious,
+                suspicious,
+                total,
+            } => {
+                let key = if malicious > 0 {
+                    "virustotal_malicious"
+                } else if suspicious > 0 {
+                    "virustotal_suspicious"
+                } else {
+                    "virustotal_clean"
+                };
+
+                i18n.translate_with_args(
+                    key,
+                    hashmap! {
+                        "url" => url,
+                        "detected" => (malicious + suspicious).to_string(),
+                        "total" => total.to_string(),
+                    },
+                )
+            }
+            Report::Queued => {
+                i18n.translate_with_args("virustotal_queued", hashmap! { "url" => url })
+            }
+            Report::Unknown => i18n.translate("virustotal_unknown"),
+        }
+    }
+}
+
+/// Scans the files before they are sent, on VirusTotal.
+#[derive(Clone)]
+pub struct VirusTotal {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The API key, `None` if there is none.
+    api_key: Option<Arc<String>>,
+    /// Whether the files are scanned.
+    enabled: Arc<AtomicBool>,
+    /// The biggest file submitted when unknown, in bytes.
+    max_upload_size: u64,
+}
+
+impl VirusTotal {
+    /// Creates a new `VirusTotal` instance.
+    pub fn new(enabled: bool, api_key: Option<String>, max_upload_size: u64) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.map(Arc::new),
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            max_upload_size,
+        }
+    }
+
+    /// Sets whether the files are scanned.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Checks if the files are scanned, which needs an API key.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed) && self.api_key.is_some()
+    }
+
+    /// Scans a file and renders the verdict added to its caption, `None` if the scans
+    /// are disabled.
+    ///
+    /// A failed scan is rendered as such, so it does not stop the transfer.
+    pub async fn verdict(&self, path: &Path, i18n: &I18n) -> Option<String> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        Some(match self.scan(path).await {
+            Ok(scan) => scan.render(i18n),
+            Err(e) => {
+                log::error!("Failed to scan {:?} on VirusTotal: {}", path, e);
+                i18n.translate("virustotal_error")
+            }
+        })
+    }
+
+    /// Scans a file, looking it up by its hash and submitting it if unknown.
+    ///
+    /// A submitted file is waited for a few minutes at most.
+    pub async fn scan(&self, path: &Path) -> Result<Scan> {
+        let key = self
+            .api_key
+            .as_deref()
+            .filter(|_| self.is_enabled())
+            .ok_or("The VirusTotal scans are disabled")?;
+        let sha256 = hash_file(path).await?;
+
+        if let Some(report) = self.lookup(key, &sha256).await? {
+            return Ok(Scan { sha256, report });
+        }
+
+        let size = tokio::fs::metadata(path).await?.len();
+        if size > self.max_upload_size {
+            return Ok(Scan {
+                sha256,
+                report: Report::Unknown,
+            });
+        }
+
+        let json = self
+            .client
+            .post(format!("{0}/files", API_URL))
+            .header("x-apikey", key)
+            .multipart(Form::new().part(
+                "file",
+                Part::bytes(tokio::fs::read(path).await?).file_name("file"),
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+        let id = json["data"]["id"]
+            .as_str()
+            .ok_or("VirusTotal gave no analysis ID")?;
+
+        for _ in 0..POLLS {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            let json = self
+                .client
+                .get(format!("{0}/analyses/{1}", API_URL, id))
+                .header("x-apikey", key)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await?;
+            let attributes = &json["data"]["attributes"];
+
+            if attributes["status"].as_str() == Some("completed") {
+                return Ok(Scan {
+                    sha256,
+                    report: analyzed(&attributes["stats"]),
+                });
+            }
+        }
+
+        Ok(Scan {
+            sha256,
+            report: Report::Queued,
+        })
+    }
+
+    /// Looks a file up by its hash, `None` if VirusTotal never saw it.
+    async fn lookup(&self, key: &str, sha256: &str) -> Result<Option<Report>> {
+        let response = self
+            .client
+            .get(format!("{0}/files/{1}", API_URL, sha256))
+            .header("x-apikey", key)
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let json = response.error_for_status()?.json::<Value>().await?;
+        let attributes = &json["data"]["attributes"];
+
+        // A file submitted by someone else may still be waiting for its analysis.
+        if attributes["last_analysis_date"].is_null() {
+            return Ok(Some(Report::Queued));
+        }
+
+        Ok(Some(analyzed(&attributes["last_analysis_stats"])))
+    }
+}
+
+/// Adds a verdict to a caption, if there is one.
+pub fn with_verdict(caption: String, verdict: Option<String>) -> String {
+    match verdict {
+        Some(verdict) => format!("{0}\n\n{1}", caption, verdict),
+        None => caption,
+    }
+}
+
+/// Builds the report of an analysis from its stats.
+fn analyzed(stats: &Value) -> Report {
+    let count = |key: &str| stats[key].as_u64().unwrap_or(0);
+
+    Report::Analyzed {
+        malicious: count("malicious"),
+        suspicious: count("suspicious"),
+        total: stats
+            .as_object()
+            .map(|stats| stats.values().filter_map(Value::as_u64).sum())
+            .unwrap_or(0),
+    }
+}
+
+/// Computes the SHA-256 of a file, in hex.
+async fn hash_file(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; CHUNK_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
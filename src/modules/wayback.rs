@@ -0,0 +1,290 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the Wayback Machine archiver module.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+use ferogram::Result;
+use grammers_client::InputMessage;
+use maplit::hashmap;
+use reqwest::{header::CONTENT_LOCATION, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    i18n::I18n,
+    migrations::{self, ChatMigration},
+    shutdown::Shutdown,
+    storage::Storage,
+};
+use crate::{utils::escape_html, Priority, Sender};
+
+/// The base URL of the Wayback Machine.
+const WAYBACK_URL: &str = "https://web.archive.org";
+/// The URL of the Wayback Machine availability API.
+const AVAILABLE_URL: &str = "https://archive.org/wayback/available";
+
+/// How long a submission is given, as the page is fetched while it waits.
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The pause between two submissions of the queue, as the Wayback Machine
+/// limits how many pages are saved a minute.
+const SUBMIT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many times a link is submitted before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+/// The delay before the first retry, doubled at each attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(2 * 60);
+
+/// The most links waiting to be archived.
+const MAX_QUEUED: usize = 500;
+
+/// Submits the links to the Wayback Machine, on demand or every link posted in
+/// the chats it is enabled in.
+///
+/// The links that fail are queued and retried later, with a growing delay.
+#[derive(Clone)]
+pub struct Wayback {
+    /// The persisted settings and queue.
+    storage: Storage<Data>,
+    /// The HTTP client.
+    client: reqwest::Client,
+}
+
+impl Wayback {
+    /// Creates a new `Wayback` instance.
+    pub fn new() -> Self {
+        Self {
+            storage: Storage::open("wayback"),
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("Failed to build the HTTP client"),
+        }
+    }
+
+    /// Checks if every link posted in a chat is archived.
+    pub async fn is_enabled(&self, chat_id: i64) -> bool {
+        self.storage
+            .read(|data| data.chats.contains(&chat_id))
+            .await
+    }
+
+    /// Enables or disables archiving the links posted in a chat.
+    pub async fn set_enabled(&self, chat_id: i64, enabled: bool) {
+        self.storage
+            .write(|data| {
+                data.chats.retain(|id| *id != chat_id);
+                if enabled {
+                    data.chats.push(chat_id);
+                }
+            })
+            .await
+    }
+
+    /// Queues a link to be archived, telling the owner once it is if `notify`.
+    ///
+    /// Returns `false` if it was already queued or the queue is full.
+    pub async fn enqueue(&self, url: &str, notify: bool) -> bool {
+        self.storage
+            .write(|data| {
+                if let Some(pending) = data.queue.iter_mut().find(|p| p.url == url) {
+                    pending.notify |= notify;
+                    return false;
+                }
+                if data.queue.len() >= MAX_QUEUED {
+                    log::warn!("Not archiving {}, the queue is full", url);
+                    return false;
+                }
+
+                data.queue.push(Pending {
+                    url: url.to_string(),
+                    attempts: 0,
+                    next: Utc::now(),
+                    notify,
+                });
+
+                true
+            })
+            .await
+    }
+
+    /// Submits a link to the Wayback Machine, returning the URL of its snapshot.
+    pub async fn archive(&self, url: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(format!("{0}/save/{1}", WAYBACK_URL, url))
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err("The Wayback Machine is rate limiting the submissions".into());
+        }
+        let response = response.error_for_status()?;
+
+        // The snapshot is told in the header, or redirected to.
+        if let Some(location) = response
+            .headers()
+            .get(CONTENT_LOCATION)
+            .and_then(|location| location.to_str().ok())
+        {
+            return Ok(format!("{0}{1}", WAYBACK_URL, location));
+        }
+        if response.url().path().starts_with("/web/") {
+            return Ok(response.url().to_string());
+        }
+
+        // The page was saved recently, so the latest snapshot is it.
+        self.latest_snapshot(url)
+            .await?
+            .ok_or_else(|| "The Wayback Machine gave no snapshot".into())
+    }
+
+    /// Returns the URL of the latest snapshot of a link, if any.
+    async fn latest_snapshot(&self, url: &str) -> Result<Option<String>> {
+        let json = self
+            .client
+            .get(AVAILABLE_URL)
+            .query(&[("url", url)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        Ok(json["archived_snapshots"]["closest"]["url"]
+            .as_str()
+            .map(str::to_string))
+    }
+
+    /// Spawns the task that archives the queued links, one at a time.
+    pub fn start(self, i18n: I18n, tx: Sender) {
+        Shutdown::shared().spawn(async move {
+            loop {
+                tokio::time::sleep(SUBMIT_INTERVAL).await;
+
+                let Some(pending) = self.next_due(Utc::now()).await else {
+                    continue;
+                };
+
+                let text = match self.archive(&pending.url).await {
+                    Ok(snapshot) => {
+                        self.remove(&pending.url).await;
+
+                        log::info!("Archived {0} as {1}", pending.url, snapshot);
+                        i18n.translate_with_args(
+                            "wayback_archived",
+                            hashmap! {
+                                "url" => escape_html(&pending.url),
+                                "snapshot" => escape_html(&snapshot),
+                            },
+                        )
+                    }
+                    Err(e) if pending.attempts + 1 < MAX_ATTEMPTS => {
+                        log::warn!("Failed to archive {0}, retrying later: {1}", pending.url, e);
+                        self.postpone(&pending.url).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        log::error!("Gave up archiving {0}: {1}", pending.url, e);
+                        self.remove(&pending.url).await;
+
+                        i18n.translate_with_args(
+                            "wayback_gave_up",
+                            hashmap! {
+                                "url" => escape_html(&pending.url),
+                                "attempts" => MAX_ATTEMPTS.to_string(),
+                            },
+                        )
+                    }
+                };
+
+                if pending.notify {
+                    // Archived in the background, after the interactive messages.
+                    let message = crate::Message::to_bot()
+                        .notify(InputMessage::html(text))
+                        .with_priority(Priority::Low);
+                    if let Err(e) = tx.send(message).await {
+                        log::error!("Failed to send the archived link: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Returns the queued link due the soonest, if any is due.
+    async fn next_due(&self, now: DateTime<Utc>) -> Option<Pending> {
+        self.storage
+            .read(|data| {
+                data.queue
+                    .iter()
+                    .filter(|pending| pending.next <= now)
+                    .min_by_key(|pending| pending.next)
+                    .cloned()
+            })
+            .await
+    }
+
+    /// Schedules the next attempt of a queued link, doubling the delay.
+    async fn postpone(&self, url: &str) {
+        self.storage
+            .write(|data| {
+                if let Some(pending) = data.queue.iter_mut().find(|p| p.url == url) {
+                    let delay = RETRY_DELAY * 2u32.pow(pending.attempts);
+                    pending.next = Utc::now() + TimeDelta::from_std(delay).unwrap_or_default();
+                    pending.attempts += 1;
+                }
+            })
+            .await
+    }
+
+    /// Removes a link from the queue.
+    async fn remove(&self, url: &str) {
+        self.storage
+            .write(|data| data.queue.retain(|pending| pending.url != url))
+            .await
+    }
+}
+
+#[async_trait]
+impl ChatMigration for Wayback {
+    fn name(&self) -> &'static str {
+        "Wayback Machine archiving"
+    }
+
+    async fn migrate(&self, from: i64, to: i64) -> usize {
+        self.storage
+            .write(|data| migrations::replace_id(&mut data.chats, from, to))
+            .await
+    }
+}
+
+/// A link waiting to be archived.
+#[derive(Clone, Serialize, Deserialize)]
+struct Pending {
+    /// The link.
+    url: String,
+    /// How many times it failed.
+    attempts: u32,
+    /// When it is submitted next.
+    next: DateTime<Utc>,
+    /// Whether the owner is told once it is archived, or given up on.
+    notify: bool,
+}
+
+/// The persisted Wayback Machine data.
+#[derive(Default, Serialize, Deserialize)]
+struct Data {
+    /// The chats whose links are archived.
+    chats: Vec<i64>,
+    /// The links waiting to be archived.
+    queue: Vec<Pending>,
+}
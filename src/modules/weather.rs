@@ -0,0 +1,404 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the weather module.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use ferogram::Result;
+use maplit::hashmap;
+use reqwest::header::USER_AGENT;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use super::{digest::Widget, i18n::I18n};
+use crate::utils::escape_html;
+
+/// The URL of the Open-Meteo geocoding API.
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+/// The URL of the Nominatim search API, used when Open-Meteo finds nothing.
+const NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org/search";
+/// The URL of the Open-Meteo forecast API.
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// The current weather fields asked for.
+const CURRENT_FIELDS: [&str; 5] = [
+    "temperature_2m",
+    "apparent_temperature",
+    "relative_humidity_2m",
+    "weather_code",
+    "wind_speed_10m",
+];
+/// The daily forecast fields asked for.
+const DAILY_FIELDS: [&str; 4] = [
+    "weather_code",
+    "temperature_2m_max",
+    "temperature_2m_min",
+    "precipitation_probability_max",
+];
+
+/// The user agent Nominatim asks the clients to identify with.
+const NOMINATIM_USER_AGENT: &str = concat!("grymbb/", env!("CARGO_PKG_VERSION"));
+
+/// How long each request is given.
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How many days the forecast spans, today included.
+const FORECAST_DAYS: usize = 3;
+
+/// The maximum number of cached places.
+const CACHE_SIZE: usize = 50;
+
+/// Finds the places and fetches their current weather and forecast.
+#[derive(Clone)]
+pub struct Weather {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The places found, kept so their weather can be refreshed.
+    cache: Arc<Mutex<Vec<Place>>>,
+}
+
+impl Weather {
+    /// Creates a new `Weather` instance.
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("Failed to build the HTTP client"),
+            cache: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Finds a place by its name, with the names in the given language.
+    ///
+    /// Returns `None` if neither Open-Meteo nor Nominatim know it.
+    pub async fn search(&self, query: &str, language: &str) -> Result<Option<Place>> {
+        let place = match self.search_open_meteo(query, language).await {
+            Ok(Some(place)) => Some(place),
+            Ok(None) => self.search_nominatim(query, language).await?,
+            Err(e) => {
+                log::warn!("Failed to geocode {0} with Open-Meteo: {1}", query, e);
+                self.search_nominatim(query, language).await?
+            }
+        };
+        let Some(mut place) = place else {
+            return Ok(None);
+        };
+
+        let mut cache = self.cache.lock().await;
+        place.id = cache.last().map(|p| p.id).unwrap_or(0) + 1;
+        cache.push(place.clone());
+        if cache.len() > CACHE_SIZE {
+            cache.remove(0);
+        }
+
+        Ok(Some(place))
+    }
+
+    /// Returns the cached place with the given ID.
+    pub async fn get(&self, id: i32) -> Option<Place> {
+        self.cache.lock().await.iter().find(|p| p.id == id).cloned()
+    }
+
+    /// Finds a place with the Open-Meteo geocoding API.
+    async fn search_open_meteo(&self, query: &str, language: &str) -> Result<Option<Place>> {
+        let json = self
+            .client
+            .get(GEOCODING_URL)
+            .query(&[
+                ("name", query),
+                ("count", "1"),
+                ("language", language),
+                ("format", "json"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let Some(result) = json["results"].get(0) else {
+            return Ok(None);
+        };
+        let (Some(latitude), Some(longitude)) =
+            (result["latitude"].as_f64(), result["longitude"].as_f64())
+        else {
+            return Ok(None);
+        };
+
+        let name = [&result["name"], &result["admin1"], &result["country"]]
+            .into_iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>();
+
+        Ok(Some(Place {
+            id: 0,
+            name: dedup_parts(name).join(", "),
+            latitude,
+            longitude,
+        }))
+    }
+
+    /// Finds a place with the Nominatim search API.
+    async fn search_nominatim(&self, query: &str, language: &str) -> Result<Option<Place>> {
+        let json = self
+            .client
+            .get(NOMINATIM_URL)
+            .header(USER_AGENT, NOMINATIM_USER_AGENT)
+            .query(&[
+                ("q", query),
+                ("format", "jsonv2"),
+                ("limit", "1"),
+                ("accept-language", language),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let Some(result) = json.get(0) else {
+            return Ok(None);
+        };
+        // Nominatim gives the coordinates as strings.
+        let coordinate = |key: &str| result[key].as_str().and_then(|c| c.parse::<f64>().ok());
+        let (Some(latitude), Some(longitude)) = (coordinate("lat"), coordinate("lon")) else {
+            return Ok(None);
+        };
+
+        let name = result["display_name"]
+            .as_str()
+            .unwrap_or(query)
+            .split(", ")
+            .collect::<Vec<_>>();
+        // The full address is too long, so only its first and last parts are kept.
+        let name = match name.as_slice() {
+            [first, .., last] => format!("{0}, {1}", first, last),
+            _ => name.join(", "),
+        };
+
+        Ok(Some(Place {
+            id: 0,
+            name,
+            latitude,
+            longitude,
+        }))
+    }
+
+    /// Fetches the current weather of a place and its forecast.
+    pub async fn forecast(&self, place: &Place) -> Result<Forecast> {
+        let json = self
+            .client
+            .get(FORECAST_URL)
+            .query(&[
+                ("latitude", place.latitude.to_string()),
+                ("longitude", place.longitude.to_string()),
+                ("current", CURRENT_FIELDS.join(",")),
+                ("daily", DAILY_FIELDS.join(",")),
+                ("timezone", "auto".to_string()),
+                ("forecast_days", FORECAST_DAYS.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let current = &json["current"];
+        let number = |value: &Value| value.as_f64().ok_or("Missing weather field");
+
+        let daily = &json["daily"];
+        let days = daily["time"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, date)| {
+                Some(Day {
+                    date: NaiveDate::parse_from_str(date.as_str()?, "%Y-%m-%d").ok()?,
+                    code: daily["weather_code"][i].as_u64()? as u8,
+                    min: daily["temperature_2m_min"][i].as_f64()?,
+                    max: daily["temperature_2m_max"][i].as_f64()?,
+                    precipitation: daily["precipitation_probability_max"][i].as_u64(),
+                })
+            })
+            .collect();
+
+        Ok(Forecast {
+            code: current["weather_code"]
+                .as_u64()
+                .ok_or("Missing weather code")? as u8,
+            temperature: number(&current["temperature_2m"])?,
+            feels_like: number(&current["apparent_temperature"])?,
+            humidity: number(&current["relative_humidity_2m"])?,
+            wind_speed: number(&current["wind_speed_10m"])?,
+            days,
+        })
+    }
+}
+
+/// The weather widget, showing the forecast of a place.
+pub struct PlaceWeather {
+    /// The weather module.
+    weather: Weather,
+    /// The name of the place, as searched for.
+    place: String,
+}
+
+impl PlaceWeather {
+    /// Creates a new `PlaceWeather` instance.
+    pub fn new(weather: Weather, place: impl Into<String>) -> Self {
+        Self {
+            weather,
+            place: place.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Widget for PlaceWeather {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    async fn render(&self, i18n: &I18n) -> Result<String> {
+        if self.place.is_empty() {
+            return Err("No place is set for the weather widget".into());
+        }
+
+        let locale = i18n.locale();
+        let place = self
+            .weather
+            .search(&self.place, &locale)
+            .await?
+            .ok_or_else(|| format!("Place {} not found", self.place))?;
+        let forecast = self.weather.forecast(&place).await?;
+
+        Ok(forecast.render(i18n, &locale, &place))
+    }
+}
+
+/// A place found by its name.
+#[derive(Clone)]
+pub struct Place {
+    /// The place ID, given when cached.
+    pub id: i32,
+    /// The name of the place, with its region and country.
+    pub name: String,
+    /// The latitude of the place.
+    pub latitude: f64,
+    /// The longitude of the place.
+    pub longitude: f64,
+}
+
+/// The current weather of a place and its forecast.
+pub struct Forecast {
+    /// The WMO code of the current weather.
+    pub code: u8,
+    /// In Celsius, the current temperature.
+    pub temperature: f64,
+    /// In Celsius, the current apparent temperature.
+    pub feels_like: f64,
+    /// In percent, the current relative humidity.
+    pub humidity: f64,
+    /// In km/h, the current wind speed.
+    pub wind_speed: f64,
+    /// The forecast of the next days, today included.
+    pub days: Vec<Day>,
+}
+
+impl Forecast {
+    /// Renders the weather card of a place, in the given locale.
+    pub fn render(&self, i18n: &I18n, locale: &str, place: &Place) -> String {
+        let (emoji, key) = condition(self.code);
+        let days = self
+            .days
+            .iter()
+            .map(|day| {
+                let (emoji, key) = condition(day.code);
+
+                i18n.translate_from_locale_with_args(
+                    "weather_day",
+                    locale,
+                    hashmap! {
+                        "date" => day.date.format("%d/%m").to_string(),
+                        "emoji" => emoji.to_string(),
+                        "condition" => i18n.translate_from_locale(key, locale),
+                        "min" => format!("{:.0}", day.min),
+                        "max" => format!("{:.0}", day.max),
+                        "precipitation" => day
+                            .precipitation
+                            .map(|p| format!("{}%", p))
+                            .unwrap_or_else(|| "?".to_string()),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        i18n.translate_from_locale_with_args(
+            "weather_card",
+            locale,
+            hashmap! {
+                "place" => escape_html(&place.name),
+                "emoji" => emoji.to_string(),
+                "condition" => i18n.translate_from_locale(key, locale),
+                "temperature" => format!("{:.1}", self.temperature),
+                "feels_like" => format!("{:.1}", self.feels_like),
+                "humidity" => format!("{:.0}", self.humidity),
+                "wind_speed" => format!("{:.0}", self.wind_speed),
+                "forecast" => days.join("\n"),
+            },
+        )
+    }
+}
+
+/// The forecast of a day.
+pub struct Day {
+    /// The date, in the time zone of the place.
+    pub date: NaiveDate,
+    /// The WMO code of the weather.
+    pub code: u8,
+    /// In Celsius, the lowest temperature.
+    pub min: f64,
+    /// In Celsius, the highest temperature.
+    pub max: f64,
+    /// In percent, the highest chance of precipitation, if known.
+    pub precipitation: Option<u64>,
+}
+
+/// Returns the emoji and the translation key of a WMO weather code.
+fn condition(code: u8) -> (&'static str, &'static str) {
+    match code {
+        0 => ("☀️", "weather_clear"),
+        1 | 2 => ("⛅", "weather_partly_cloudy"),
+        3 => ("☁️", "weather_overcast"),
+        45 | 48 => ("🌫", "weather_fog"),
+        51..=57 => ("🌦", "weather_drizzle"),
+        61..=67 => ("🌧", "weather_rain"),
+        71..=77 | 85 | 86 => ("🌨", "weather_snow"),
+        80..=82 => ("🌦", "weather_showers"),
+        95..=99 => ("⛈", "weather_thunderstorm"),
+        _ => ("🌡", "weather_unknown"),
+    }
+}
+
+/// Removes the repeated parts of a name, as a city named after its region.
+fn dedup_parts(parts: Vec<&str>) -> Vec<&str> {
+    let mut unique = Vec::with_capacity(parts.len());
+    for part in parts {
+        if !unique.contains(&part) {
+            unique.push(part);
+        }
+    }
+
+    unique
+}
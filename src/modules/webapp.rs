@@ -0,0 +1,410 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the web app module.
+
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use ferogram::Result;
+use grammers_client::Client;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+use super::{i18n::I18n, shutdown::Shutdown};
+
+/// The maximum size of a request.
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+/// How long a client can take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The kind that routes the data no other handler takes.
+pub const ANY_KIND: &str = "*";
+
+/// The web app server, which serves the static assets of the Mini App and
+/// receives the data it sends.
+#[derive(Clone)]
+pub struct WebApp {
+    /// The address the server listens on.
+    bind: String,
+    /// The public HTTPS URL of the web app, `None` if it is disabled.
+    url: Option<String>,
+    /// The directory of the static assets.
+    assets_dir: PathBuf,
+    /// The token of the bot, used to validate the `initData`.
+    bot_token: String,
+    /// How long an `initData` stays valid.
+    max_age: Duration,
+}
+
+impl WebApp {
+    /// Creates a new `WebApp` instance.
+    pub fn new(
+        bind: String,
+        url: Option<String>,
+        assets_dir: PathBuf,
+        bot_token: String,
+        max_age: Duration,
+    ) -> Self {
+        Self {
+            bind,
+            url,
+            assets_dir,
+            bot_token,
+            max_age,
+        }
+    }
+
+    /// Returns the public URL of the web app, if it is enabled.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Validates the `initData` sent by a web app, returning its user.
+    ///
+    /// See <https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app>.
+    pub fn validate(&self, init_data: &str) -> Option<WebAppUser> {
+        let mut pairs = init_data
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+            .collect::<Vec<_>>();
+
+        let index = pairs.iter().position(|(key, _)| key == "hash")?;
+        let hash = hex::decode(pairs.remove(index).1).ok()?;
+
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        let data_check_string = pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut secret = Hmac::<Sha256>::new_from_slice(b"WebAppData").ok()?;
+        secret.update(self.bot_token.as_bytes());
+        let secret = secret.finalize().into_bytes();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&secret).ok()?;
+        mac.update(data_check_string.as_bytes());
+        mac.verify_slice(&hash).ok()?;
+
+        let auth_date = pairs
+            .iter()
+            .find(|(key, _)| key == "auth_date")
+            .and_then(|(_, value)| value.parse::<u64>().ok())?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        if now.saturating_sub(auth_date) > self.max_age.as_secs() {
+            return None;
+        }
+
+        pairs
+            .iter()
+            .find(|(key, _)| key == "user")
+            .and_then(|(_, value)| serde_json::from_str(value).ok())
+    }
+
+    /// Spawns the server, returning the receiver of the data sent by the web app.
+    pub fn start(self) -> mpsc::Receiver<WebAppData> {
+        let (tx, rx) = mpsc::channel(10);
+
+        if self.url.is_none() {
+            return rx;
+        }
+
+        Shutdown::shared().spawn(async move {
+            let listener = match TcpListener::bind(&self.bind).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log::error!("Failed to bind the web app server: {}", e);
+                    return;
+                }
+            };
+
+            log::info!("Web app server listening on {}", self.bind);
+
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+
+                let webapp = self.clone();
+                let tx = tx.clone();
+                tokio::task::spawn(async move {
+                    if let Err(e) = webapp.serve(stream, tx).await {
+                        log::warn!("Failed to serve a web app request: {}", e);
+                    }
+                });
+            }
+        });
+
+        rx
+    }
+
+    /// Serves a single request.
+    async fn serve(&self, mut stream: TcpStream, tx: mpsc::Sender<WebAppData>) -> Result<()> {
+        // Slow clients are dropped, so they cannot hold the connection.
+        let Ok(request) = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await
+        else {
+            return Ok(());
+        };
+        let Some((head, body)) = request? else {
+            return Ok(());
+        };
+
+        let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default();
+        let path = request_line
+            .next()
+            .unwrap_or("/")
+            .split('?')
+            .next()
+            .unwrap_or("/");
+
+        let (status, content_type, content) = match (method, path) {
+            ("POST", "/api/data") => match serde_json::from_slice::<Request>(&body) {
+                Ok(request) => match self.validate(&request.init_data) {
+                    Some(user) => {
+                        tx.send(WebAppData {
+                            user,
+                            kind: request.kind,
+                            payload: request.payload,
+                        })
+                        .await?;
+
+                        ("200 OK", "application/json", b"{\"ok\":true}".to_vec())
+                    }
+                    None => (
+                        "401 Unauthorized",
+                        "application/json",
+                        b"{\"ok\":false}".to_vec(),
+                    ),
+                },
+                Err(_) => (
+                    "400 Bad Request",
+                    "application/json",
+                    b"{\"ok\":false}".to_vec(),
+                ),
+            },
+            ("GET", path) => match self.asset(path).await {
+                Some((content_type, content)) => ("200 OK", content_type, content),
+                None => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+            },
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain",
+                b"Method not allowed".to_vec(),
+            ),
+        };
+
+        let head = format!(
+            "HTTP/1.1 {0}\r\nContent-Type: {1}\r\nContent-Length: {2}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            content.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&content).await?;
+
+        Ok(())
+    }
+
+    /// Reads a static asset, refusing paths outside the assets directory.
+    async fn asset(&self, path: &str) -> Option<(&'static str, Vec<u8>)> {
+        let path = percent_decode(path.trim_start_matches('/'));
+        let path = if path.is_empty() {
+            "index.html".to_string()
+        } else {
+            path
+        };
+
+        let relative = Path::new(&path);
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        let content = tokio::fs::read(self.assets_dir.join(relative)).await.ok()?;
+        let content_type = match relative.extension().and_then(|e| e.to_str()) {
+            Some("html") => "text/html; charset=utf-8",
+            Some("css") => "text/css",
+            Some("js") => "application/javascript",
+            Some("json") => "application/json",
+            Some("png") => "image/png",
+            Some("svg") => "image/svg+xml",
+            _ => "application/octet-stream",
+        };
+
+        Some((content_type, content))
+    }
+}
+
+/// Reads a request, its headers then its body up to its length.
+///
+/// Returns `None` if the connection was closed or the request is too large.
+async fn read_request(stream: &mut TcpStream) -> Result<Option<(String, Vec<u8>)>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 4096];
+
+    loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 || buffer.len() + read > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&buffer[..end]).to_string();
+            let length = head
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0)
+                .min(MAX_REQUEST_SIZE);
+
+            while buffer.len() < end + 4 + length {
+                let read = stream.read(&mut chunk).await?;
+                if read == 0 {
+                    break;
+                }
+                buffer.extend_from_slice(&chunk[..read]);
+            }
+
+            let body = buffer[end + 4..].to_vec();
+            return Ok(Some((head, body)));
+        }
+    }
+}
+
+/// Decodes a percent-encoded string, as sent in query strings.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// The user who opened the web app.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WebAppUser {
+    /// The user ID.
+    pub id: i64,
+    /// The first name.
+    pub first_name: String,
+    /// The username, if any.
+    pub username: Option<String>,
+}
+
+/// The data sent by the web app, after its `initData` was validated.
+pub struct WebAppData {
+    /// The user who sent the data.
+    pub user: WebAppUser,
+    /// The kind of data, used to route it.
+    pub kind: String,
+    /// The data itself.
+    pub payload: serde_json::Value,
+}
+
+/// A handler of the data sent by the web app, routed by its kind.
+#[async_trait]
+pub trait WebAppHandler: Send + Sync {
+    /// Returns the kind of data handled, or [`ANY_KIND`] to take the data no
+    /// other handler takes.
+    fn kind(&self) -> &'static str;
+
+    /// Handles the data, already validated by the server.
+    async fn handle(&self, client: &Client, i18n: &I18n, data: &WebAppData) -> Result<()>;
+}
+
+/// Routes the data sent by the web app to its handler.
+#[derive(Clone, Default)]
+pub struct WebAppRouter {
+    /// The registered handlers.
+    handlers: Arc<Vec<Arc<dyn WebAppHandler>>>,
+}
+
+impl WebAppRouter {
+    /// Registers a handler.
+    pub fn handler(mut self, handler: impl WebAppHandler + 'static) -> Self {
+        Arc::get_mut(&mut self.handlers)
+            .expect("Handlers must be registered before cloning the router")
+            .push(Arc::new(handler));
+        self
+    }
+
+    /// Merges the handlers of another router.
+    pub fn router(mut self, router: WebAppRouter) -> Self {
+        Arc::get_mut(&mut self.handlers)
+            .expect("Handlers must be registered before cloning the router")
+            .extend(router.handlers.iter().cloned());
+        self
+    }
+
+    /// Handles the data with the handler of its kind.
+    pub async fn dispatch(&self, client: &Client, i18n: &I18n, data: WebAppData) -> Result<()> {
+        let handler = self
+            .handlers
+            .iter()
+            .find(|h| h.kind() == data.kind)
+            .or_else(|| self.handlers.iter().find(|h| h.kind() == ANY_KIND));
+
+        match handler {
+            Some(handler) => handler.handle(client, i18n, &data).await,
+            None => {
+                log::warn!("Dropping web app data of unknown kind: {}", data.kind);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A request sent by the web app.
+#[derive(Deserialize)]
+struct Request {
+    /// The raw `initData` of the web app.
+    #[serde(rename = "initData")]
+    init_data: String,
+    /// The kind of data.
+    kind: String,
+    /// The data itself.
+    #[serde(default)]
+    payload: serde_json::Value,
+}
@@ -0,0 +1,251 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the yt-dlp module.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use ferogram::Result;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    process::Command,
+};
+
+/// The template of the progress lines printed by yt-dlp.
+const PROGRESS_TEMPLATE: &str =
+    "download:progress|%(progress._percent_str)s|%(progress._speed_str)s|%(progress._eta_str)s";
+
+/// The template of the line with the metadata of the downloaded file.
+const INFO_TEMPLATE: &str = "after_move:info|%(duration)s|%(width)s|%(height)s|%(title)s";
+
+/// The template of the line with the path of the downloaded file.
+const PATH_TEMPLATE: &str = "after_move:path|%(filepath)s";
+
+/// The format of a download.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    /// The video with its audio, up to the given height if any.
+    Video(Option<u32>),
+    /// Only the audio.
+    Audio,
+}
+
+/// The progress of a download, as printed by yt-dlp.
+#[derive(Clone)]
+pub struct Progress {
+    /// The downloaded percentage.
+    pub percent: String,
+    /// The download speed.
+    pub speed: String,
+    /// The estimated time left.
+    pub eta: String,
+}
+
+/// A downloaded file.
+pub struct Download {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The path of the thumbnail, if any was downloaded.
+    pub thumbnail: Option<PathBuf>,
+    /// The title of the media.
+    pub title: String,
+    /// In seconds, the duration of the media.
+    pub duration: f64,
+    /// The width of the video.
+    pub width: i32,
+    /// The height of the video.
+    pub height: i32,
+}
+
+/// The yt-dlp wrapper.
+#[derive(Clone)]
+pub struct Ytdl {
+    /// The path of the yt-dlp binary.
+    path: String,
+    /// The directory where the files are downloaded.
+    output_dir: PathBuf,
+}
+
+impl Ytdl {
+    /// Creates a new `Ytdl` instance.
+    pub fn new(path: impl Into<String>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            output_dir: output_dir.into(),
+        }
+    }
+
+    /// Returns the directory where the files are downloaded.
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    /// Downloads the audio of the given URL, or of the first YouTube result of the given query.
+    ///
+    /// Returns the path of the downloaded file.
+    pub async fn download_audio(&self, query: &str) -> Result<PathBuf> {
+        let target = if query.starts_with("http://") || query.starts_with("https://") {
+            query.to_string()
+        } else {
+            format!("ytsearch1:{}", query)
+        };
+
+        self.run(&[
+            "-x",
+            "--audio-format",
+            "mp3",
+            "--embed-metadata",
+            "--no-playlist",
+            &target,
+        ])
+        .await
+    }
+
+    /// Downloads the media of the given URL in the given format.
+    ///
+    /// `on_progress` is called with each progress line printed by yt-dlp.
+    pub async fn download(
+        &self,
+        url: &str,
+        format: Format,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<Download> {
+        tokio::fs::create_dir_all(&self.output_dir).await?;
+
+        let selector;
+        let mut args = vec![
+            "--no-playlist",
+            "--write-thumbnail",
+            "--convert-thumbnails",
+            "jpg",
+        ];
+        match format {
+            Format::Video(height) => {
+                selector = match height {
+                    Some(height) => format!("bv*[height<={0}]+ba/b[height<={0}]", height),
+                    None => "bv*+ba/b".to_string(),
+                };
+                args.extend(["-f", selector.as_str(), "--merge-output-format", "mp4"]);
+            }
+            Format::Audio => {
+                args.extend(["-x", "--audio-format", "mp3", "--embed-metadata"]);
+            }
+        }
+
+        let template = self.output_dir.join("%(id)s.%(ext)s");
+        let mut child = Command::new(&self.path)
+            .args(args)
+            .args([
+                "--newline",
+                "--progress",
+                "--progress-template",
+                PROGRESS_TEMPLATE,
+            ])
+            .args(["--print", INFO_TEMPLATE, "--print", PATH_TEMPLATE, "-o"])
+            .arg(template)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        // Read apart, so a full stderr pipe does not block yt-dlp.
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let errors = tokio::task::spawn(async move {
+            let mut errors = String::new();
+            let _ = stderr.read_to_string(&mut errors).await;
+            errors
+        });
+
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut lines = BufReader::new(stdout).lines();
+
+        let mut info = None;
+        let mut path = None;
+        while let Some(line) = lines.next_line().await? {
+            let mut fields = line.trim().split('|');
+
+            match fields.next() {
+                Some("progress") => {
+                    let mut next = || fields.next().unwrap_or_default().trim().to_string();
+                    on_progress(Progress {
+                        percent: next(),
+                        speed: next(),
+                        eta: next(),
+                    });
+                }
+                Some("info") => {
+                    let mut next = || fields.next().unwrap_or_default().trim();
+                    let duration = next().parse::<f64>().unwrap_or_default();
+                    let width = next().parse::<i32>().unwrap_or_default();
+                    let height = next().parse::<i32>().unwrap_or_default();
+                    // The title may contain the separator itself.
+                    let title = fields.collect::<Vec<_>>().join("|");
+
+                    info = Some((title, duration, width, height));
+                }
+                Some("path") => path = Some(PathBuf::from(fields.collect::<Vec<_>>().join("|"))),
+                _ => {}
+            }
+        }
+
+        let status = child.wait().await?;
+        if !status.success() {
+            let errors = errors.await.unwrap_or_default();
+            return Err(format!("yt-dlp failed: {}", errors.trim()).into());
+        }
+
+        let Some(path) = path else {
+            return Err("yt-dlp did not print the file path".into());
+        };
+        let (title, duration, width, height) = info.unwrap_or_default();
+
+        let thumbnail = path.with_extension("jpg");
+        let thumbnail = tokio::fs::try_exists(&thumbnail)
+            .await
+            .unwrap_or(false)
+            .then_some(thumbnail);
+
+        Ok(Download {
+            path,
+            thumbnail,
+            title,
+            duration,
+            width,
+            height,
+        })
+    }
+
+    /// Runs yt-dlp with the given arguments.
+    ///
+    /// Returns the path of the resulting file.
+    async fn run(&self, args: &[&str]) -> Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.output_dir).await?;
+
+        let template = self.output_dir.join("%(id)s.%(ext)s");
+        let output = Command::new(&self.path)
+            .args(args)
+            .args(["--print", "after_move:filepath", "-o"])
+            .arg(template)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("yt-dlp failed: {}", stderr.trim()).into());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match stdout.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(path) => Ok(PathBuf::from(path.trim())),
+            None => Err("yt-dlp did not print the file path".into()),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the command output layer.
+
+use std::io::Cursor;
+
+use async_trait::async_trait;
+use ferogram::{Context, Result};
+use grammers_client::{parsers, types::Message, InputMessage};
+use serde::Serialize;
+
+use crate::utils::escape_html;
+
+/// The flag that makes a command deliver its output as a document.
+pub const FILE_FLAG: &str = "--file";
+
+/// The flag that makes a command deliver its data as JSON.
+pub const JSON_FLAG: &str = "--json";
+
+/// The maximum length of an output sent as a message.
+const MAX_LENGTH: usize = 4000;
+
+/// The name of the document a response is delivered as.
+const RESPONSE_FILE_NAME: &str = "output.txt";
+
+/// The reply layer of the commands, which takes the `--file` flag out of their
+/// text and delivers their final response as a document when it is set.
+#[async_trait]
+pub trait Respond {
+    /// Returns the text of the command, without the `--file` flag.
+    fn command_text(&self) -> Option<String>;
+
+    /// Checks if the command was run with the `--file` flag.
+    fn wants_file(&self) -> bool;
+
+    /// Sends the final response of the command, editing or replying to it.
+    ///
+    /// The response is uploaded as a document when the command was run with the
+    /// `--file` flag or when it is too long.
+    async fn respond<R: Into<Response> + Send>(&self, response: R) -> Result<Message>;
+
+    /// Sends the final response of the command in place of its status message.
+    ///
+    /// When uploaded as a document, the status message is removed, or restored
+    /// to the command text if it is the command message itself.
+    async fn respond_in<R: Into<Response> + Send>(
+        &self,
+        msg: &Message,
+        response: R,
+    ) -> Result<Message>;
+}
+
+#[async_trait]
+impl Respond for Context {
+    fn command_text(&self) -> Option<String> {
+        self.text().map(|text| take_file_flag(&text).0)
+    }
+
+    fn wants_file(&self) -> bool {
+        self.text().is_some_and(|text| take_file_flag(&text).1)
+    }
+
+    async fn respond<R: Into<Response> + Send>(&self, response: R) -> Result<Message> {
+        let response = response.into();
+        if !self.wants_file() && response.content.len() <= MAX_LENGTH {
+            return Ok(self.edit_or_reply(response.message).await?);
+        }
+
+        upload(self, response.content, RESPONSE_FILE_NAME).await
+    }
+
+    async fn respond_in<R: Into<Response> + Send>(
+        &self,
+        msg: &Message,
+        response: R,
+    ) -> Result<Message> {
+        let response = response.into();
+        if !self.wants_file() && response.content.len() <= MAX_LENGTH {
+            msg.edit(response.message).await?;
+            return Ok(msg.clone());
+        }
+
+        match self.message().await {
+            Some(command) if command.id() == msg.id() => {
+                msg.edit(InputMessage::text(self.text().unwrap_or_default()))
+                    .await?;
+            }
+            _ => msg.delete().await?,
+        }
+
+        upload(self, response.content, RESPONSE_FILE_NAME).await
+    }
+}
+
+/// The final text response of a command, sent by the [`Respond`] layer.
+pub struct Response {
+    /// The message sent when the response fits.
+    message: InputMessage,
+    /// The raw text uploaded as the document otherwise.
+    content: String,
+}
+
+impl Response {
+    /// Creates a response from HTML.
+    pub fn html(html: impl AsRef<str>) -> Self {
+        let html = html.as_ref();
+        let (content, _) = parsers::parse_html_message(html);
+
+        Self {
+            message: InputMessage::html(html),
+            content,
+        }
+    }
+
+    /// Creates a response from plain text.
+    pub fn text(text: impl AsRef<str>) -> Self {
+        let text = text.as_ref();
+
+        Self {
+            message: InputMessage::text(text),
+            content: text.to_string(),
+        }
+    }
+}
+
+impl From<String> for Response {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<&str> for Response {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+/// Uploads the content as a document replying to the command.
+async fn upload(ctx: &Context, content: String, file_name: &str) -> Result<Message> {
+    let bytes = content.into_bytes();
+    let size = bytes.len();
+
+    let mut stream = Cursor::new(bytes);
+    let file = ctx
+        .upload_stream(&mut stream, size, file_name.to_string())
+        .await?;
+
+    Ok(ctx.reply(InputMessage::text("").document(file)).await?)
+}
+
+/// Removes the `--file` flag from a command text.
+///
+/// Returns the text without the flag and whether it was present.
+fn take_file_flag(text: &str) -> (String, bool) {
+    take_flag(text, FILE_FLAG)
+}
+
+/// Removes the `--json` flag from a command text.
+///
+/// Returns the text without the flag and whether it was present.
+pub fn take_json_flag(text: &str) -> (String, bool) {
+    take_flag(text, JSON_FLAG)
+}
+
+/// Removes a flag from a command text.
+fn take_flag(text: &str, flag: &str) -> (String, bool) {
+    let mut found = false;
+    let text = text
+        .split(' ')
+        .filter(|word| {
+            if *word == flag {
+                found = true;
+                false
+            } else {
+                true
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (text, found)
+}
+
+/// The final text response of a command.
+pub struct Output {
+    /// The message shown when the output fits.
+    pub html: String,
+    /// The message shown along with the document otherwise.
+    pub caption: String,
+    /// The raw content uploaded as the document.
+    pub content: String,
+    /// The name of the uploaded document.
+    pub file_name: String,
+}
+
+impl Output {
+    /// Creates the output of the data of a command as a JSON document.
+    pub fn json<T: Serialize>(data: &T, caption: String, file_name: impl Into<String>) -> Self {
+        let content = serde_json::to_string_pretty(data).unwrap_or_default();
+
+        Self {
+            html: format!(
+                "<pre><code class=\"language-json\">{}</code></pre>",
+                escape_html(&content)
+            ),
+            caption,
+            content,
+            file_name: file_name.into(),
+        }
+    }
+
+    /// Delivers the output, editing the given message or the command one.
+    ///
+    /// The content is uploaded as a document when the output is too long or
+    /// when `to_file` is set, as by [`Respond::wants_file`].
+    pub async fn send(self, ctx: &Context, msg: Option<&Message>, to_file: bool) -> Result<()> {
+        if !to_file && self.html.len() <= MAX_LENGTH {
+            match msg {
+                Some(msg) => msg.edit(InputMessage::html(self.html)).await?,
+                None => {
+                    ctx.edit_or_reply(InputMessage::html(self.html)).await?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        match msg {
+            Some(msg) => msg.edit(InputMessage::html(self.caption)).await?,
+            None => {
+                ctx.edit_or_reply(InputMessage::html(self.caption)).await?;
+            }
+        }
+        upload(ctx, self.content, &self.file_name).await?;
+
+        Ok(())
+    }
+}
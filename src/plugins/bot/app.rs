@@ -0,0 +1,110 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the web app command and data handlers.
+
+use async_trait::async_trait;
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{
+    button, reply_markup,
+    session::{PackedChat, PackedType},
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    modules::{
+        i18n::I18n,
+        webapp::{self, WebApp, WebAppData, WebAppHandler, WebAppRouter},
+    },
+    utils::escape_html,
+};
+
+/// Setup the app command.
+pub fn setup() -> Router {
+    Router::default().handler(handler::new_message(filter::command("app")).then(app))
+}
+
+/// Handles the app command, sending the button that opens the web app.
+async fn app(ctx: Context, i18n: I18n, webapp: WebApp) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let Some(url) = webapp.url() else {
+        ctx.reply(t("app_disabled")).await?;
+        return Ok(());
+    };
+
+    ctx.reply(
+        InputMessage::html(t("app_text")).reply_markup(&reply_markup::inline(vec![vec![
+            button::webview(t("app_button"), url),
+        ]])),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Setup the web app data handlers.
+pub fn web_app() -> WebAppRouter {
+    WebAppRouter::default().handler(Ping).handler(Received)
+}
+
+/// Answers the pings of the web app.
+struct Ping;
+
+#[async_trait]
+impl WebAppHandler for Ping {
+    fn kind(&self) -> &'static str {
+        "ping"
+    }
+
+    async fn handle(&self, client: &Client, i18n: &I18n, data: &WebAppData) -> Result<()> {
+        let chat = user_chat(data);
+        let text = i18n.translate_for(chat.id, "app_pong");
+
+        client.send_message(chat, InputMessage::html(text)).await?;
+
+        Ok(())
+    }
+}
+
+/// Acknowledges the data no other handler takes.
+struct Received;
+
+#[async_trait]
+impl WebAppHandler for Received {
+    fn kind(&self) -> &'static str {
+        webapp::ANY_KIND
+    }
+
+    async fn handle(&self, client: &Client, i18n: &I18n, data: &WebAppData) -> Result<()> {
+        let chat = user_chat(data);
+        let text = i18n.translate_for_with_args(
+            chat.id,
+            "app_data_received",
+            hashmap! {
+                "kind" => escape_html(&data.kind),
+                "payload" => escape_html(&data.payload.to_string()),
+            },
+        );
+
+        client.send_message(chat, InputMessage::html(text)).await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the private chat of the user who sent the data.
+fn user_chat(data: &WebAppData) -> PackedChat {
+    PackedChat {
+        ty: PackedType::User,
+        id: data.user.id,
+        access_hash: None,
+    }
+}
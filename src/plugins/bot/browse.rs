@@ -0,0 +1,64 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the history browser handlers.
+
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, Client, InputMessage};
+
+use crate::{
+    filters,
+    modules::{history::History, i18n::I18n},
+};
+
+/// Setup the history browser handlers.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::callback_query(filter::regex(r"^browse (\d+) ").and(filters::sudoers()))
+            .then(browse),
+    )
+}
+
+/// Handles the history browser buttons.
+///
+/// The data is one of `browse <id> page <page>`, `browse <id> jump <timestamp>`
+/// and `browse <id> latest`.
+async fn browse(query: CallbackQuery, i18n: I18n, history: History, user: Client) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let id = split[0].parse::<u32>()?;
+    let page = match split[1..] {
+        ["page", page] => page.parse::<usize>()?,
+        ["jump", before] => {
+            history.jump(id, Some(before.parse::<i32>()?)).await;
+            0
+        }
+        _ => {
+            history.jump(id, None).await;
+            0
+        }
+    };
+
+    match history.render(&user, id, page, &i18n).await? {
+        Some((text, buttons)) => {
+            query
+                .answer()
+                .edit(InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)))
+                .await?;
+        }
+        None => {
+            query.answer().alert(t("browse_expired")).send().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the business connections handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    grammers_tl_types as tl,
+    session::{PackedChat, PackedType},
+    types::Message,
+    InputMessage, Update,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        business::{Business, Route},
+        i18n::I18n,
+    },
+    utils::escape_html,
+};
+
+/// Setup the business handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_update(filters::seen_users().or(filters::business()))
+                .then(business_update),
+        )
+        .handler(handler::new_message(filter::command("reply").and(filters::sudoers())).then(reply))
+}
+
+/// Builds the private chat of a user with the bot.
+fn private_chat(user_id: i64) -> PackedChat {
+    PackedChat {
+        ty: PackedType::User,
+        id: user_id,
+        access_hash: None,
+    }
+}
+
+/// Handles the business connection updates and messages.
+async fn business_update(
+    ctx: Context,
+    update: Update,
+    i18n: I18n,
+    business: Business,
+) -> Result<()> {
+    // The notifications go to the private chat of each account.
+    let t = |user_id: i64, key: &str| i18n.translate_for(user_id, key);
+    let t_a = |user_id: i64, key: &str, args| i18n.translate_for_with_args(user_id, key, args);
+
+    let Update::Raw(raw) = update else {
+        return Ok(());
+    };
+    let client = ctx.client();
+
+    match raw {
+        tl::enums::Update::BotBusinessConnect(update) => {
+            let tl::enums::BotBusinessConnection::Connection(connection) = update.connection;
+
+            let accepted = business.update(&connection).await;
+            let key = if connection.disabled {
+                "business_disconnected"
+            } else if accepted {
+                "business_connected"
+            } else {
+                "business_rejected"
+            };
+
+            client
+                .send_message(
+                    private_chat(connection.user_id),
+                    InputMessage::html(t(connection.user_id, key)),
+                )
+                .await?;
+        }
+        tl::enums::Update::BotNewBusinessMessage(update) => {
+            let tl::enums::Message::Message(message) = update.message else {
+                return Ok(());
+            };
+
+            let Some(connection) = business.connection(&update.connection_id).await else {
+                return Ok(());
+            };
+            // The messages sent by the account itself are not routed.
+            if message.out || message.message.is_empty() {
+                return Ok(());
+            }
+
+            let tl::enums::Peer::User(peer) = message.peer_id else {
+                return Ok(());
+            };
+
+            let key = if connection.can_reply {
+                "business_message"
+            } else {
+                "business_message_read_only"
+            };
+            let sent = client
+                .send_message(
+                    private_chat(connection.user_id),
+                    InputMessage::html(t_a(
+                        connection.user_id,
+                        key,
+                        hashmap! {
+                            "user" => peer.user_id.to_string(),
+                            "text" => escape_html(&message.message),
+                        },
+                    )),
+                )
+                .await?;
+
+            business
+                .track(
+                    sent.id(),
+                    Route {
+                        connection_id: update.connection_id,
+                        user_id: peer.user_id,
+                        message_id: message.id,
+                    },
+                )
+                .await;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handles the reply command, answering a business message on behalf of the account.
+///
+/// Only the business account the message was received through can answer it.
+async fn reply(ctx: Context, message: Message, i18n: I18n, business: Business) -> Result<()> {
+    let chat_id = message.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let text = ctx.text().unwrap();
+    let text = text
+        .split_once(char::is_whitespace)
+        .map(|(_, text)| text.trim());
+
+    let route = match ctx.get_reply().await? {
+        Some(reply) => business.route(reply.id()).await,
+        None => None,
+    };
+
+    let (Some(route), Some(text)) = (route, text.filter(|text| !text.is_empty())) else {
+        ctx.reply(InputMessage::html(t("business_reply_usage")))
+            .await?;
+        return Ok(());
+    };
+
+    let sender_id = message.sender().map(|sender| sender.id());
+    match business.connection(&route.connection_id).await {
+        Some(connection) if Some(connection.user_id) != sender_id => {
+            ctx.reply(t("business_not_owner")).await?;
+        }
+        Some(connection) if connection.can_reply => {
+            if business.peer(route.user_id).is_none() {
+                ctx.reply(t("business_unknown_user")).await?;
+                return Ok(());
+            }
+
+            business.reply(ctx.client(), &route, text).await?;
+            ctx.reply(t("business_replied")).await?;
+        }
+        _ => {
+            ctx.reply(t("business_cannot_reply")).await?;
+        }
+    }
+
+    Ok(())
+}
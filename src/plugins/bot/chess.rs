@@ -0,0 +1,141 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chess command handler.
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+use maplit::hashmap;
+
+use super::tic_tac_toe::arm_timer;
+use crate::{
+    callback_data,
+    modules::{
+        games::{GameManager, Player, Select},
+        i18n::I18n,
+    },
+    utils::chess_to_buttons,
+};
+
+/// Setup the chess command.
+pub fn setup() -> Router {
+    Router::default().handler(handler::callback_query(filter::regex(r"^chess (\d+)")).then(chess))
+}
+
+/// Handles the chess keyboard.
+///
+/// The data is one of `chess <id> sq <square>`, `chess <id> draw` and `chess <id> resign`.
+async fn chess(ctx: Context, query: CallbackQuery, i18n: I18n, manager: GameManager) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(data) = callback_data::verify(query.data()) else {
+        query.answer().alert(t("invalid_callback")).send().await?;
+        return Ok(());
+    };
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let game_id = split[0].parse::<i32>()?;
+    let Some(mut game) = manager.get_game(game_id).await else {
+        query.answer().alert(t("game_not_found")).send().await?;
+        return Ok(());
+    };
+    if !manager.bind(game_id, &query).await {
+        query.answer().alert(t("game_wrong_message")).send().await?;
+        return Ok(());
+    }
+
+    let sender = query.sender();
+    if !game.has_player(sender.id()) {
+        if game.available_seats() == 0 || !game.add_player(Player::new(&sender)) {
+            query.answer().alert(t("not_in_game")).send().await?;
+            return Ok(());
+        }
+
+        query.answer().text(t("joined_game")).send().await?;
+
+        if game.current_player().is_none() {
+            game.set_current_player(sender.id());
+        }
+
+        let buttons = chess_to_buttons(game.as_chess().unwrap(), game.id());
+        query
+            .answer()
+            .edit(
+                InputMessage::html(game.generate_text())
+                    .reply_markup(&reply_markup::inline(buttons)),
+            )
+            .await?;
+
+        arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+        manager.update_game(game).await;
+        return Ok(());
+    }
+
+    let mut answer = query.answer();
+    let mut moved = false;
+
+    match split.get(1).copied() {
+        Some("resign") => {
+            game.forfeit(sender.id());
+            answer = answer.alert(t("chess_resigned"));
+        }
+        Some("draw") => {
+            if game.as_chess_mut().unwrap().offer_draw(sender.id()) {
+                answer = answer.alert(t("game_draw"));
+            } else {
+                query.answer().text(t("chess_draw_offered")).send().await?;
+                return Ok(());
+            }
+        }
+        Some("sq") => {
+            if game.current_player().map(|p| p.id()) != Some(sender.id()) {
+                query.answer().alert(t("not_your_turn")).send().await?;
+                return Ok(());
+            }
+
+            let square = split[2].parse::<usize>()?;
+            match game.as_chess_mut().unwrap().select(square) {
+                Select::Selected => {}
+                Select::Moved => moved = true,
+                Select::Illegal => answer = answer.alert(t("chess_illegal_move")),
+            }
+
+            if game.is_over() {
+                if let Some(player) = game.winner() {
+                    answer = answer.alert(t_a(
+                        "player_wins",
+                        hashmap! { "player" => player.mention() },
+                    ));
+                } else {
+                    answer = answer.alert(t("game_draw"));
+                }
+            }
+        }
+        _ => return Ok(()),
+    }
+
+    let mut input = InputMessage::html(game.generate_text());
+    if !game.is_over() {
+        let buttons = chess_to_buttons(game.as_chess().unwrap(), game.id());
+        input = input.reply_markup(&reply_markup::inline(buttons));
+    }
+    answer.edit(input).await?;
+
+    if game.is_over() {
+        manager.remove_game(game).await;
+    } else {
+        if moved {
+            arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+        }
+        manager.update_game(game).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the connect four command handler.
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+use maplit::hashmap;
+
+use super::tic_tac_toe::arm_timer;
+use crate::{
+    callback_data,
+    modules::{
+        games::{GameManager, Player},
+        i18n::I18n,
+    },
+    utils::{board_to_buttons, BoardLayout},
+};
+
+/// Setup the connect four command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::callback_query(filter::regex(r"^c4 (\d+)")).then(connect_four))
+}
+
+/// Handles the connect four keyboard.
+///
+/// The data is `c4 <id> <column>`.
+async fn connect_four(
+    ctx: Context,
+    query: CallbackQuery,
+    i18n: I18n,
+    manager: GameManager,
+) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(data) = callback_data::verify(query.data()) else {
+        query.answer().alert(t("invalid_callback")).send().await?;
+        return Ok(());
+    };
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let game_id = split[0].parse::<i32>()?;
+    let Some(mut game) = manager.get_game(game_id).await else {
+        query.answer().alert(t("game_not_found")).send().await?;
+        return Ok(());
+    };
+    if !manager.bind(game_id, &query).await {
+        query.answer().alert(t("game_wrong_message")).send().await?;
+        return Ok(());
+    }
+
+    let sender = query.sender();
+    if !game.has_player(sender.id()) && game.available_seats() == 0 {
+        query.answer().alert(t("not_in_game")).send().await?;
+        return Ok(());
+    } else if !game.has_player(sender.id()) && game.available_seats() > 0 {
+        if !game.add_player(Player::new(&sender)) {
+            query.answer().alert(t("game_full")).send().await?;
+            return Ok(());
+        }
+
+        query.answer().text(t("joined_game")).send().await?;
+
+        if game.current_player().is_some() {
+            let buttons = board_to_buttons(game.board(), "c4", game.id(), BoardLayout::Columns);
+            query
+                .answer()
+                .edit(
+                    InputMessage::html(game.generate_text())
+                        .reply_markup(&reply_markup::inline(buttons)),
+                )
+                .await?;
+
+            arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+            manager.update_game(game).await;
+            return Ok(());
+        }
+
+        game.set_current_player(sender.id());
+    } else if game.current_player().map(|p| p.id()) != Some(sender.id()) {
+        query.answer().alert(t("not_your_turn")).send().await?;
+        return Ok(());
+    }
+
+    let column = split[1].parse::<usize>()?;
+    if !game.play(column, 0) {
+        query.answer().alert(t("full_column")).send().await?;
+        return Ok(());
+    }
+
+    let mut answer = query.answer();
+    if game.is_over() {
+        if let Some(player) = game.winner() {
+            if player.id() == sender.id() {
+                answer = answer.alert(t("you_win"));
+            } else {
+                answer = answer.alert(t_a(
+                    "player_wins",
+                    hashmap! { "player" => player.mention() },
+                ));
+            }
+        } else {
+            answer = answer.alert(t("game_draw"));
+        }
+    }
+
+    let mut input = InputMessage::html(game.generate_text());
+    if !game.is_over() {
+        let buttons = board_to_buttons(game.board(), "c4", game.id(), BoardLayout::Columns);
+        input = input.reply_markup(&reply_markup::inline(buttons));
+    }
+    answer.edit(input).await?;
+
+    if game.is_over() {
+        manager.remove_game(game).await;
+    } else {
+        arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+        manager.update_game(game).await;
+    }
+
+    Ok(())
+}
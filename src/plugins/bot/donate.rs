@@ -0,0 +1,251 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the donate and supporters command handlers.
+
+use chrono::Utc;
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{
+    grammers_tl_types as tl,
+    types::{Chat, Message},
+    InputMessage, Update,
+};
+use maplit::hashmap;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        i18n::I18n,
+        supporters::{Donation, Supporters},
+    },
+    utils::escape_html,
+};
+
+/// The currency of Telegram Stars.
+const CURRENCY: &str = "XTR";
+/// The prefix of the invoice payloads.
+const PAYLOAD_PREFIX: &str = "donate";
+/// The maximum number of supporters listed.
+const SUPPORTERS_LIMIT: usize = 20;
+
+/// The config of the donate plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "donate"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        vec![
+            // The amount of Stars donated when none is given.
+            Key::new("default_amount", 50).validate(positive),
+            // The maximum amount of Stars of a single donation.
+            Key::new("max_amount", 10_000).validate(positive),
+        ]
+    }
+}
+
+/// Validates that an amount is positive.
+fn positive(value: &toml::Value) -> std::result::Result<(), String> {
+    match value.as_integer() {
+        Some(amount) if amount > 0 => Ok(()),
+        _ => Err("must be positive".to_string()),
+    }
+}
+
+/// Setup the donate and supporters commands, and the payment handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filter::command("donate")).then(donate))
+        .handler(handler::new_message(filter::command("supporters")).then(supporters))
+        .handler(handler::new_update(filters::precheckout_query()).then(precheckout))
+        .handler(handler::new_message(filters::successful_payment()).then(successful_payment))
+}
+
+/// Handles the donate command, sending a Stars invoice.
+async fn donate(ctx: Context, i18n: I18n, settings: PluginSettings) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let max_amount = settings.get::<i64>("donate", "max_amount");
+
+    let text = ctx.text().unwrap();
+    let amount = match text.split_whitespace().nth(1) {
+        Some(amount) => amount.parse::<i64>().ok(),
+        None => Some(settings.get::<i64>("donate", "default_amount")),
+    };
+    let Some(amount) = amount.filter(|amount| (1..=max_amount).contains(amount)) else {
+        ctx.reply(InputMessage::html(t_a(
+            "donate_invalid_amount",
+            hashmap! { "max" => max_amount.to_string() },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let chat: Chat = ctx.chat().expect("Chat not found");
+    let invoice = tl::types::InputMediaInvoice {
+        title: t("donate_title"),
+        description: t_a(
+            "donate_description",
+            hashmap! { "amount" => amount.to_string() },
+        ),
+        photo: None,
+        invoice: tl::types::Invoice {
+            test: false,
+            name_requested: false,
+            phone_requested: false,
+            email_requested: false,
+            shipping_address_requested: false,
+            flexible: false,
+            phone_to_provider: false,
+            email_to_provider: false,
+            recurring: false,
+            currency: CURRENCY.to_string(),
+            prices: vec![tl::types::LabeledPrice {
+                label: t("donate_title"),
+                amount,
+            }
+            .into()],
+            max_tip_amount: None,
+            suggested_tip_amounts: None,
+            terms_url: None,
+            subscription_period: None,
+        }
+        .into(),
+        payload: PAYLOAD_PREFIX.as_bytes().to_vec(),
+        // Stars invoices have no payment provider.
+        provider: None,
+        provider_data: tl::types::DataJson {
+            data: "{}".to_string(),
+        }
+        .into(),
+        start_param: None,
+        extended_media: None,
+    };
+
+    ctx.client()
+        .invoke(&tl::functions::messages::SendMedia {
+            silent: false,
+            background: false,
+            clear_draft: false,
+            noforwards: false,
+            update_stickersets_order: false,
+            invert_media: false,
+            allow_paid_floodskip: false,
+            peer: chat.pack().to_input_peer(),
+            reply_to: None,
+            media: invoice.into(),
+            message: String::new(),
+            random_id: rand::random(),
+            reply_markup: None,
+            entities: None,
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+            effect: None,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the pre-checkout queries, approving the donation invoices.
+async fn precheckout(ctx: Context, update: Update) -> Result<()> {
+    let Update::Raw(tl::enums::Update::BotPrecheckoutQuery(query)) = update else {
+        return Ok(());
+    };
+
+    let valid = query.currency == CURRENCY && query.payload == PAYLOAD_PREFIX.as_bytes();
+    ctx.client()
+        .invoke(&tl::functions::messages::SetBotPrecheckoutResults {
+            success: valid,
+            query_id: query.query_id,
+            error: (!valid).then(|| "Invalid invoice.".to_string()),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the successful payments, recording the supporter.
+async fn successful_payment(message: Message, i18n: I18n, supporters: Supporters) -> Result<()> {
+    let chat_id = message.chat().id();
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(tl::enums::MessageAction::PaymentSentMe(payment)) = message.action() else {
+        return Ok(());
+    };
+    let Some(sender) = message.sender() else {
+        return Ok(());
+    };
+    let tl::enums::PaymentCharge::Charge(charge) = &payment.charge;
+
+    let recorded = supporters
+        .record(Donation {
+            user_id: sender.id(),
+            name: sender.name().to_string(),
+            amount: payment.total_amount,
+            charge_id: charge.id.clone(),
+            date: Utc::now(),
+        })
+        .await;
+
+    if recorded {
+        message
+            .reply(InputMessage::html(t_a(
+                "donate_thanks",
+                hashmap! {
+                    "name" => escape_html(sender.name()),
+                    "amount" => payment.total_amount.to_string(),
+                },
+            )))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Handles the supporters command.
+async fn supporters(ctx: Context, i18n: I18n, supporters: Supporters) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let ranking = supporters.ranking().await;
+    if ranking.is_empty() {
+        ctx.reply(t("supporters_empty")).await?;
+        return Ok(());
+    }
+
+    let list = ranking
+        .iter()
+        .take(SUPPORTERS_LIMIT)
+        .enumerate()
+        .map(|(i, (name, total))| {
+            format!(
+                "{0}. <b>{1}</b> — <code>{2}</code> ⭐",
+                i + 1,
+                escape_html(name),
+                total
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.reply(InputMessage::html(t_a(
+        "supporters_list",
+        hashmap! { "supporters" => list },
+    )))
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the game settings command handler.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{grammers_tl_types::enums::MessageEntity, types::Message, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        chat_settings::ChatSettings,
+        games::{Symbol, Symbols},
+        i18n::I18n,
+    },
+};
+
+/// The most characters a symbol can have, enough for the composed emojis.
+const MAX_SYMBOL_LENGTH: usize = 8;
+
+/// Setup the game settings command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filter::command("gamesettings").and(filters::admins()))
+            .then(game_settings),
+    )
+}
+
+/// Handles the game settings command.
+async fn game_settings(
+    ctx: Context,
+    message: Message,
+    i18n: I18n,
+    settings: ChatSettings,
+) -> Result<()> {
+    let chat_id = message.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let text = message.text();
+
+    match text.split_whitespace().nth(1) {
+        Some("symbols") => {
+            let symbols = parse_symbols(&message);
+            let [first, second, empty] = symbols.as_slice() else {
+                ctx.reply(InputMessage::html(t("game_settings_usage")))
+                    .await?;
+                return Ok(());
+            };
+            if symbols
+                .iter()
+                .any(|symbol| symbol.emoji.chars().count() > MAX_SYMBOL_LENGTH)
+            {
+                ctx.reply(InputMessage::html(t("game_settings_usage")))
+                    .await?;
+                return Ok(());
+            }
+            if first.emoji == second.emoji
+                || first.emoji == empty.emoji
+                || second.emoji == empty.emoji
+            {
+                ctx.reply(t("game_settings_repeated")).await?;
+                return Ok(());
+            }
+
+            let symbols = Symbols {
+                players: [first.clone(), second.clone()],
+                empty: empty.clone(),
+            };
+            settings
+                .set_game_symbols(chat_id, Some(symbols.clone()))
+                .await;
+
+            ctx.reply(InputMessage::html(t_a(
+                "game_settings_updated",
+                hashmap! { "symbols" => render(&symbols) },
+            )))
+            .await?;
+        }
+        Some("reset") => {
+            settings.set_game_symbols(chat_id, None).await;
+
+            ctx.reply(t("game_settings_reset")).await?;
+        }
+        _ => {
+            let symbols = settings.game_symbols(chat_id).await;
+
+            ctx.reply(InputMessage::html(t_a(
+                "game_settings",
+                hashmap! { "symbols" => render(&symbols) },
+            )))
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses the symbols after the subcommand, keeping the custom emojis.
+fn parse_symbols(message: &Message) -> Vec<Symbol> {
+    let text = message.text();
+    let custom_emojis = message
+        .fmt_entities()
+        .map(|entities| {
+            entities
+                .iter()
+                .filter_map(|entity| match entity {
+                    MessageEntity::CustomEmoji(e) => Some((e.offset, e.document_id)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    // The entities are offset in UTF-16 code units.
+    let mut symbols = Vec::new();
+    let mut offset = 0;
+    let mut start = None;
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if c.is_whitespace() {
+            if let Some((start, word)) = start.take() {
+                symbols.push((start, word));
+            }
+        } else {
+            match &mut start {
+                Some((_, word)) => word.push(c),
+                None => start = Some((offset, c.to_string())),
+            }
+        }
+
+        offset += c.len_utf16() as i32;
+    }
+
+    symbols
+        .into_iter()
+        // Skips the command and the subcommand.
+        .skip(2)
+        .map(|(offset, emoji)| Symbol {
+            emoji,
+            custom_emoji_id: custom_emojis
+                .iter()
+                .find(|(o, _)| *o == offset)
+                .map(|(_, id)| *id),
+        })
+        .collect()
+}
+
+/// Renders the symbols, first player, second player and empty cell.
+fn render(symbols: &Symbols) -> String {
+    format!(
+        "{0} {1} {2}",
+        symbols.players[0].html(),
+        symbols.players[1].html(),
+        symbols.empty.html()
+    )
+}
@@ -0,0 +1,317 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the giveaway command handler.
+
+use chrono::{TimeDelta, Utc};
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    button::{self, Inline},
+    grammers_tl_types as tl, reply_markup,
+    types::{CallbackQuery, Chat},
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        giveaways::{Entrant, Giveaways},
+        i18n::I18n,
+    },
+    utils::{self, escape_html},
+};
+
+/// The config of the giveaway plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "giveaway"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // The maximum number of winners of a giveaway.
+        vec![
+            Key::new("max_winners", 50).validate(|value| match value.as_integer() {
+                Some(1..=100) => Ok(()),
+                _ => Err("must be between 1 and 100".to_string()),
+            }),
+        ]
+    }
+}
+
+/// Setup the giveaway command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::command("giveaway")
+                    .and(filters::sudoers())
+                    .and(filters::fresh("bot")),
+            )
+            .then(giveaway),
+        )
+        .handler(handler::callback_query(filter::regex(r"^giveaway (\d+)")).then(join))
+}
+
+/// Builds the join button of a giveaway.
+fn join_buttons(i18n: &I18n, chat_id: i64, id: i32) -> Vec<Vec<Inline>> {
+    vec![vec![button::inline(
+        i18n.translate_for(chat_id, "giveaway_join"),
+        format!("giveaway {}", id),
+    )]]
+}
+
+/// Renders the announcement of a running giveaway.
+async fn announcement(i18n: &I18n, giveaways: &Giveaways, id: i32) -> String {
+    giveaways
+        .with(id, |giveaway| {
+            let chat_id = giveaway.chat.id();
+            let mut text = i18n.translate_for_with_args(
+                chat_id,
+                "giveaway_text",
+                hashmap! {
+                    "prize" => escape_html(&giveaway.prize),
+                    "winners" => giveaway.winners.to_string(),
+                    "entrants" => giveaway.entrants.len().to_string(),
+                    "ends_at" => giveaway.ends_at.format("%d/%m/%Y %H:%M UTC").to_string(),
+                },
+            );
+
+            if let Some(channel) = giveaway.required_channel.as_ref() {
+                text += &i18n.translate_for_with_args(
+                    chat_id,
+                    "giveaway_requirement",
+                    hashmap! { "channel" => escape_html(channel.name()) },
+                );
+            }
+
+            text
+        })
+        .await
+        .unwrap_or_default()
+}
+
+/// Checks if a user is a member of a channel, which the bot must administrate.
+async fn is_member(client: &Client, channel: &Chat, user: &Chat) -> Result<bool> {
+    let request = tl::functions::channels::GetParticipant {
+        channel: utils::input_channel(channel)?,
+        participant: user.pack().to_input_peer(),
+    };
+
+    match client.invoke(&request).await {
+        Ok(tl::enums::channels::ChannelParticipant::Participant(participant)) => Ok(!matches!(
+            participant.participant,
+            tl::enums::ChannelParticipant::Left(_) | tl::enums::ChannelParticipant::Banned(_)
+        )),
+        Err(e) if e.is("USER_NOT_PARTICIPANT") => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handles the giveaway command.
+///
+/// Usage: `/giveaway [--winners N] [--channel @channel] <prize> <duration>`.
+async fn giveaway(
+    ctx: Context,
+    i18n: I18n,
+    giveaways: Giveaways,
+    settings: PluginSettings,
+) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let text = ctx.text().unwrap();
+    let mut args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let mut winners = 1;
+    let mut channel = None;
+    while args.len() > 1 && args[0].starts_with("--") {
+        match args.remove(0) {
+            "--winners" => winners = args.remove(0).parse::<usize>().unwrap_or(0),
+            "--channel" => channel = Some(args.remove(0).trim_start_matches('@').to_string()),
+            _ => {}
+        }
+    }
+
+    let duration = args.pop().and_then(utils::parse_duration);
+    let prize = args.join(" ");
+    let (Some(duration), false, true) = (
+        duration,
+        prize.is_empty(),
+        (1..=settings.get::<usize>("giveaway", "max_winners")).contains(&winners),
+    ) else {
+        ctx.reply(InputMessage::html(t("giveaway_usage"))).await?;
+        return Ok(());
+    };
+
+    let required_channel = match channel {
+        Some(username) => match ctx.client().resolve_username(&username).await? {
+            Some(chat @ Chat::Channel(_)) => Some(chat),
+            _ => {
+                ctx.reply(t("giveaway_channel_not_found")).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let chat = ctx.chat().expect("Chat not found");
+    let ends_at = Utc::now() + TimeDelta::from_std(duration)?;
+    let id = giveaways
+        .create(chat.clone(), prize, winners, required_channel, ends_at)
+        .await;
+
+    let sent = ctx
+        .reply(
+            InputMessage::html(announcement(&i18n, &giveaways, id).await)
+                .reply_markup(&reply_markup::inline(join_buttons(&i18n, chat_id, id))),
+        )
+        .await?;
+    giveaways
+        .with(id, |giveaway| giveaway.message_id = Some(sent.id()))
+        .await;
+
+    // Draws the winners once the giveaway ends.
+    let client = ctx.client().clone();
+    tokio::task::spawn(async move {
+        tokio::time::sleep(duration).await;
+
+        if let Err(e) = draw(&client, &i18n, &giveaways, id).await {
+            log::error!("Failed to draw the giveaway {}: {}", id, e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Draws and announces the winners of a giveaway, with the proof of the draw.
+async fn draw(client: &Client, i18n: &I18n, giveaways: &Giveaways, id: i32) -> Result<()> {
+    let Some(draw) = giveaways.draw(id).await else {
+        return Ok(());
+    };
+    let giveaway = &draw.giveaway;
+    let chat_id = giveaway.chat.id();
+    let message_id = giveaway.message_id.unwrap_or_default();
+
+    client
+        .edit_message(
+            &giveaway.chat,
+            message_id,
+            InputMessage::html(i18n.translate_for_with_args(
+                chat_id,
+                "giveaway_ended",
+                hashmap! {
+                    "prize" => escape_html(&giveaway.prize),
+                    "entrants" => giveaway.entrants.len().to_string(),
+                },
+            )),
+        )
+        .await?;
+
+    let text = if draw.winners.is_empty() {
+        i18n.translate_for(chat_id, "giveaway_no_entrants")
+    } else {
+        let winners = draw
+            .winners
+            .iter()
+            .map(|w| {
+                format!(
+                    "• <a href=\"tg://user?id={0}\">{1}</a>",
+                    w.id,
+                    escape_html(&w.name)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        i18n.translate_for_with_args(
+            chat_id,
+            "giveaway_winners",
+            hashmap! {
+                "prize" => escape_html(&giveaway.prize),
+                "winners" => winners,
+                "seed" => draw.seed.to_string(),
+                "link" => utils::message_link(&giveaway.chat, message_id).unwrap_or_default(),
+            },
+        )
+    };
+
+    client
+        .send_message(
+            &giveaway.chat,
+            InputMessage::html(text).reply_to(Some(message_id)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the join button.
+async fn join(ctx: Context, query: CallbackQuery, i18n: I18n, giveaways: Giveaways) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let id = data.split_whitespace().nth(1).unwrap().parse::<i32>()?;
+    let sender = query.sender();
+
+    let Some(channel) = giveaways
+        .with(id, |giveaway| giveaway.required_channel.clone())
+        .await
+    else {
+        query.answer().alert(t("giveaway_not_found")).send().await?;
+        return Ok(());
+    };
+
+    if let Some(channel) = channel {
+        if !is_member(ctx.client(), &channel, sender).await? {
+            query
+                .answer()
+                .alert(i18n.translate_for_with_args(
+                    chat_id,
+                    "giveaway_must_join",
+                    hashmap! { "channel" => channel.name().to_string() },
+                ))
+                .send()
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let joined = giveaways
+        .with(id, |giveaway| {
+            giveaway.join(Entrant {
+                id: sender.id(),
+                name: sender.name().to_string(),
+            })
+        })
+        .await
+        .unwrap_or(false);
+
+    if joined {
+        query
+            .answer()
+            .text(t("giveaway_joined"))
+            .edit(
+                InputMessage::html(announcement(&i18n, &giveaways, id).await)
+                    .reply_markup(&reply_markup::inline(join_buttons(&i18n, chat_id, id))),
+            )
+            .await?;
+    } else {
+        query
+            .answer()
+            .alert(t("giveaway_already_joined"))
+            .send()
+            .await?;
+    }
+
+    Ok(())
+}
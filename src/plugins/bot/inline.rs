@@ -0,0 +1,70 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the inline results selection handler.
+
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{types::CallbackQuery, Client, InputMessage};
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, inline::InlineProxy},
+    utils,
+};
+
+/// Setup the inline results selection handler.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::callback_query(filter::regex(r"^inline (\d+) (\d+)").and(filters::sudoers()))
+            .then(select),
+    )
+}
+
+/// Handles the inline results buttons.
+async fn select(query: CallbackQuery, i18n: I18n, proxy: InlineProxy, user: Client) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let id = split[0].parse::<i32>()?;
+    let index = split[1].parse::<usize>()?;
+
+    let Some(pending) = proxy.take_selection(id).await else {
+        query.answer().alert(t("inline_expired")).send().await?;
+        return Ok(());
+    };
+
+    // The results are queried again, as they are bound to the query that produced them.
+    let result = utils::inline_query(
+        &user,
+        &pending.bot,
+        &pending.chat,
+        &pending.query,
+        index + 1,
+    )
+    .await?
+    .into_iter()
+    .nth(index);
+
+    match result {
+        Some(result) => {
+            result.send(&pending.chat).await?;
+            query
+                .answer()
+                .edit(InputMessage::html(t("inline_sent")))
+                .await?;
+        }
+        None => {
+            query.answer().alert(t("inline_no_results")).send().await?;
+        }
+    }
+
+    Ok(())
+}
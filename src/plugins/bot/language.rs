@@ -0,0 +1,146 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the language command handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    button::{self, Inline},
+    reply_markup,
+    types::{CallbackQuery, Chat, Message},
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{filters, modules::i18n::I18n};
+
+/// Setup the language handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(filter::command("language").and(filters::fresh("bot")))
+                .then(language),
+        )
+        .handler(handler::callback_query(filter::regex(r"^language (set|reset)")).then(choose))
+}
+
+/// Handles the language command, showing the locales to choose from.
+///
+/// In the groups, only the admins choose the language.
+async fn language(ctx: Context, message: Message, i18n: I18n) -> Result<()> {
+    let chat = message.chat();
+
+    if !can_choose(ctx.client(), &chat, message.sender()).await? {
+        ctx.reply(i18n.translate_for(chat.id(), "language_admins_only"))
+            .await?;
+        return Ok(());
+    }
+
+    ctx.reply(
+        InputMessage::html(render(&i18n, chat.id()))
+            .reply_markup(&reply_markup::inline(keyboard(&i18n, chat.id()))),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the buttons that choose a locale.
+///
+/// The data is `language set <locale>` or `language reset`.
+async fn choose(ctx: Context, query: CallbackQuery, i18n: I18n) -> Result<()> {
+    let chat = query.chat().clone();
+
+    if !can_choose(ctx.client(), &chat, Some(query.sender().clone())).await? {
+        query
+            .answer()
+            .alert(i18n.translate_for(chat.id(), "language_admins_only"))
+            .send()
+            .await?;
+        return Ok(());
+    }
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let locale = match split.as_slice() {
+        ["set", locale] if i18n.locales().iter().any(|l| l == locale) => Some(locale.to_string()),
+        ["reset"] => None,
+        _ => {
+            query.answer().send().await?;
+            return Ok(());
+        }
+    };
+    i18n.set_locale_for(chat.id(), locale).await;
+
+    query
+        .answer()
+        .edit(
+            InputMessage::html(render(&i18n, chat.id()))
+                .reply_markup(&reply_markup::inline(keyboard(&i18n, chat.id()))),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Checks if the sender can choose the language of a chat, anyone in the
+/// private chats and the admins in the groups.
+async fn can_choose(client: &Client, chat: &Chat, sender: Option<Chat>) -> Result<bool> {
+    if let Chat::User(_) = chat {
+        return Ok(true);
+    }
+    let Some(sender) = sender else {
+        return Ok(false);
+    };
+
+    let permissions = client.get_permissions(chat, &sender).await?;
+
+    Ok(permissions.is_creator() || permissions.is_admin())
+}
+
+/// Renders the language of a chat, in that language.
+fn render(i18n: &I18n, chat_id: i64) -> String {
+    let locale = i18n.locale_for(chat_id);
+
+    i18n.translate_for_with_args(
+        chat_id,
+        "language_current",
+        hashmap! { "language" => i18n.translate_from_locale("language_name", &locale) },
+    )
+}
+
+/// Builds the buttons of the locales, the chosen one marked.
+fn keyboard(i18n: &I18n, chat_id: i64) -> Vec<Vec<Inline>> {
+    let chosen = i18n
+        .has_locale_for(chat_id)
+        .then(|| i18n.locale_for(chat_id));
+
+    let mut locales = i18n.locales();
+    locales.sort();
+
+    let mut buttons = locales
+        .into_iter()
+        .map(|locale| {
+            let name = i18n.translate_from_locale("language_name", &locale);
+            let label = if chosen.as_ref() == Some(&locale) {
+                format!("✅ {}", name)
+            } else {
+                name
+            };
+
+            vec![button::inline(label, format!("language set {}", locale))]
+        })
+        .collect::<Vec<_>>();
+    buttons.push(vec![button::inline(
+        i18n.translate_for(chat_id, "language_default"),
+        "language reset",
+    )]);
+
+    buttons
+}
@@ -0,0 +1,181 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the link scanner handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{types::Message, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        reposts::Action,
+        url_scanner::{Settings, UrlScanner, Verdict},
+    },
+    utils::{self, escape_html},
+};
+
+/// The most URLs of a message that are scanned.
+const MAX_URLS: usize = 5;
+
+/// Setup the link scanner handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::command("scanlinks")
+                    .and(filters::admins())
+                    .and(filters::fresh("bot")),
+            )
+            .then(scan_links),
+        )
+        .handler(handler::new_message(filters::group_link()).then(link))
+}
+
+/// Handles the scanlinks command.
+///
+/// Usage: `/scanlinks [on|off|delete|flag]`.
+async fn scan_links(ctx: Context, i18n: I18n, scanner: UrlScanner) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    if !scanner.is_configured() {
+        ctx.reply(t("links_not_configured")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let update: Option<Box<dyn FnOnce(&mut Settings) + Send>> = match args.as_slice() {
+        [] => None,
+        ["on"] => Some(Box::new(|settings| settings.enabled = true)),
+        ["off"] => Some(Box::new(|settings| settings.enabled = false)),
+        [name] => match Action::parse(name) {
+            Some(action) => Some(Box::new(move |settings| settings.action = action)),
+            None => {
+                ctx.reply(InputMessage::html(t("links_usage"))).await?;
+                return Ok(());
+            }
+        },
+        _ => {
+            ctx.reply(InputMessage::html(t("links_usage"))).await?;
+            return Ok(());
+        }
+    };
+
+    let settings = match update {
+        Some(f) => scanner.update(chat_id, f).await,
+        None => scanner.settings(chat_id).await,
+    };
+
+    ctx.reply(InputMessage::html(i18n.translate_for_with_args(
+        chat_id,
+        "links_settings",
+        hashmap! {
+            "enabled" => t(if settings.enabled { "reposts_on" } else { "reposts_off" }),
+            "action" => settings.action.name().to_string(),
+            "provider" => scanner.provider().name().to_string(),
+        },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the messages with URLs sent to the groups.
+async fn link(ctx: Context, i18n: I18n, scanner: UrlScanner) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+    check_links(&message, &scanner, &i18n).await?;
+
+    Ok(())
+}
+
+/// Scans the URLs of a message sent to a group, acting on it if one is malicious.
+///
+/// The suspicious URLs are only warned about. Returns `true` if it was deleted.
+pub async fn check_links(message: &Message, scanner: &UrlScanner, i18n: &I18n) -> Result<bool> {
+    let chat_id = message.chat().id();
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    if !scanner.is_configured() {
+        return Ok(false);
+    }
+    let settings = scanner.settings(chat_id).await;
+    if !settings.enabled {
+        return Ok(false);
+    }
+
+    let mut urls = utils::message_urls(message);
+    urls.sort();
+    urls.dedup();
+
+    let mut suspicious = None;
+    for url in urls.into_iter().take(MAX_URLS) {
+        let details = match scanner.scan(&url).await {
+            Ok(Verdict::Malicious(details)) => details,
+            Ok(Verdict::Suspicious(details)) => {
+                suspicious.get_or_insert((url, details));
+                continue;
+            }
+            Ok(_) => continue,
+            Err(e) => {
+                log::error!("Failed to scan a URL: {}", e);
+                continue;
+            }
+        };
+
+        let sender = message
+            .sender()
+            .map(|sender| sender.name().to_string())
+            .unwrap_or_default();
+        let args = hashmap! {
+            "sender" => escape_html(&sender),
+            "url" => escape_html(&url),
+            "details" => escape_html(&details),
+        };
+
+        return match settings.action {
+            Action::Delete => {
+                if let Err(e) = message.delete().await {
+                    log::error!("Failed to delete a malicious link: {}", e);
+                    return Ok(false);
+                }
+
+                message
+                    .respond(InputMessage::html(t_a("links_deleted", args)))
+                    .await?;
+
+                Ok(true)
+            }
+            Action::Flag => {
+                message
+                    .reply(InputMessage::html(t_a("links_flagged", args)))
+                    .await?;
+
+                Ok(false)
+            }
+        };
+    }
+
+    if let Some((url, details)) = suspicious {
+        message
+            .reply(InputMessage::html(t_a(
+                "links_suspicious",
+                hashmap! {
+                    "url" => escape_html(&url),
+                    "details" => escape_html(&details),
+                },
+            )))
+            .await?;
+    }
+
+    Ok(false)
+}
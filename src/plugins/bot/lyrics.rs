@@ -0,0 +1,50 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the lyrics pagination handler.
+
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, lyrics::Lyrics},
+};
+
+/// Setup the lyrics pagination.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::callback_query(filter::regex(r"^lyrics (\d+) (\d+)").and(filters::sudoers()))
+            .then(lyrics),
+    )
+}
+
+/// Handles the lyrics pagination buttons.
+async fn lyrics(query: CallbackQuery, i18n: I18n, lyrics: Lyrics) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let id = split[0].parse::<i32>()?;
+    let page = split[1].parse::<usize>()?;
+
+    if let Some(song) = lyrics.get(id).await {
+        let (text, buttons) = song.render_page(&i18n, page);
+
+        query
+            .answer()
+            .edit(InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)))
+            .await?;
+    } else {
+        query.answer().alert(t("lyrics_expired")).send().await?;
+    }
+
+    Ok(())
+}
@@ -10,16 +10,80 @@
 
 use ferogram::Dispatcher;
 
+use crate::{config::PluginConfig, modules::webapp::WebAppRouter};
+
+mod app;
+mod browse;
+mod business;
+mod chess;
+mod connect_four;
+mod donate;
+mod game_settings;
+mod giveaway;
 mod info;
+mod inline;
+mod language;
+mod links;
+mod lyrics;
+mod moderation;
+mod nsfw;
+mod photos;
 mod purge;
+mod raid;
+mod relogin;
+mod remind;
+mod reposts;
 mod screenshot;
 mod start;
+mod sudoku;
+mod tags;
 mod tic_tac_toe;
+mod topics;
+mod tournament;
+mod trivia;
+mod unread;
+mod weather;
+
+/// Returns the bot plugins that declare a config section.
+pub fn configs() -> Vec<Box<dyn PluginConfig>> {
+    vec![Box::new(donate::Settings), Box::new(giveaway::Settings)]
+}
+
+/// Returns the handlers of the data sent by the web app.
+pub fn web_app() -> WebAppRouter {
+    WebAppRouter::default().router(app::web_app())
+}
 
 pub fn setup(dp: Dispatcher) -> Dispatcher {
-    dp.router(|_| info::setup())
+    dp.router(|_| app::setup())
+        .router(|_| browse::setup())
+        .router(|_| business::setup())
+        .router(|_| chess::setup())
+        .router(|_| connect_four::setup())
+        .router(|_| donate::setup())
+        .router(|_| game_settings::setup())
+        .router(|_| giveaway::setup())
+        .router(|_| info::setup())
+        .router(|_| inline::setup())
+        .router(|_| language::setup())
+        .router(|_| links::setup())
+        .router(|_| lyrics::setup())
+        .router(|_| moderation::setup())
+        .router(|_| nsfw::setup())
+        .router(|_| photos::setup())
         .router(|_| purge::setup())
+        .router(|_| raid::setup())
+        .router(|_| relogin::setup())
+        .router(|_| remind::setup())
+        .router(|_| reposts::setup())
         .router(|_| screenshot::setup())
         .router(|_| start::setup())
+        .router(|_| sudoku::setup())
+        .router(|_| tags::setup())
         .router(|_| tic_tac_toe::setup())
+        .router(|_| topics::setup())
+        .router(|_| tournament::setup())
+        .router(|_| trivia::setup())
+        .router(|_| unread::setup())
+        .router(|_| weather::setup())
 }
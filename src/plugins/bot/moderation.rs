@@ -0,0 +1,213 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the moderation notes and report commands handlers.
+
+use chrono::Utc;
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    session::{PackedChat, PackedType},
+    types::Chat,
+    InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        mod_notes::{ModNote, ModNotes},
+        sudoers::Sudoers,
+    },
+    utils::{self, escape_html},
+};
+
+/// Setup the moderation commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filter::command("note").and(filters::admins())).then(note))
+        .handler(handler::new_message(filter::command("notes").and(filters::admins())).then(notes))
+        .handler(handler::new_message(filter::command("report")).then(report))
+}
+
+/// Resolves the target user and the remaining arguments.
+///
+/// The target is the replied message sender, or the first argument as a
+/// `@username` or an ID.
+async fn resolve_target(ctx: &Context) -> Result<Option<((i64, String), String)>> {
+    let text = ctx.text().unwrap();
+    let mut args = text.splitn(3, char::is_whitespace).skip(1);
+
+    if let Some(reply) = ctx.get_reply().await? {
+        let rest = text.split_once(char::is_whitespace).map(|(_, r)| r);
+
+        return Ok(reply
+            .sender()
+            .filter(|chat| matches!(chat, Chat::User(_)))
+            .map(|chat| {
+                (
+                    (chat.id(), chat.name().to_string()),
+                    rest.unwrap_or_default().trim().to_string(),
+                )
+            }));
+    }
+
+    let target = match args.next() {
+        Some(username) if username.starts_with('@') => ctx
+            .client()
+            .resolve_username(&username[1..])
+            .await?
+            .filter(|chat| matches!(chat, Chat::User(_)))
+            .map(|chat| (chat.id(), chat.name().to_string())),
+        Some(id) => id.parse::<i64>().ok().map(|id| (id, id.to_string())),
+        None => None,
+    };
+
+    Ok(target.map(|target| (target, args.next().unwrap_or_default().trim().to_string())))
+}
+
+/// Renders the notes on a user.
+fn render_notes(notes: &[ModNote]) -> String {
+    notes
+        .iter()
+        .map(|note| {
+            format!(
+                "• <i>{0}</i> — <b>{1}</b>: {2}",
+                note.date.format("%d/%m/%Y"),
+                escape_html(&note.author_name),
+                escape_html(&note.text)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Handles the note command.
+///
+/// Usage: `/note @user <text>`, or `/note <text>` replying to the user.
+async fn note(ctx: Context, i18n: I18n, notes: ModNotes) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(((user_id, name), text)) = resolve_target(&ctx)
+        .await?
+        .filter(|(_, text)| !text.is_empty())
+    else {
+        ctx.reply(InputMessage::html(t("note_usage"))).await?;
+        return Ok(());
+    };
+
+    let chat = ctx.chat().expect("Chat not found");
+    let author = ctx.sender().expect("Sender not found");
+    notes
+        .add(ModNote {
+            chat_id: chat.id(),
+            user_id,
+            author_id: author.id(),
+            author_name: author.name().to_string(),
+            text,
+            date: Utc::now(),
+        })
+        .await;
+
+    ctx.reply(InputMessage::html(t_a(
+        "note_added",
+        hashmap! { "name" => escape_html(&name) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the notes command.
+///
+/// Usage: `/notes @user [clear]`, or replying to the user.
+async fn notes(ctx: Context, i18n: I18n, notes: ModNotes) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(((user_id, name), rest)) = resolve_target(&ctx).await? else {
+        ctx.reply(InputMessage::html(t("notes_usage"))).await?;
+        return Ok(());
+    };
+    let chat = ctx.chat().expect("Chat not found");
+
+    if rest == "clear" {
+        let count = notes.clear(chat.id(), user_id).await;
+        ctx.reply(InputMessage::html(t_a(
+            "notes_cleared",
+            hashmap! { "name" => escape_html(&name), "count" => count.to_string() },
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let list = notes.of(chat.id(), user_id).await;
+    let text = if list.is_empty() {
+        t_a("notes_empty", hashmap! { "name" => escape_html(&name) })
+    } else {
+        t_a(
+            "notes_list",
+            hashmap! { "name" => escape_html(&name), "notes" => render_notes(&list) },
+        )
+    };
+    ctx.reply(InputMessage::html(text)).await?;
+
+    Ok(())
+}
+
+/// Handles the report command.
+///
+/// The report goes to the owner in private, along with the notes on the
+/// reported user, so they never show up in the group.
+async fn report(ctx: Context, i18n: I18n, notes: ModNotes, sudoers: Sudoers) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let chat = ctx.chat().expect("Chat not found");
+    let Some(reply) = ctx.get_reply().await? else {
+        ctx.reply(InputMessage::html(t("reply_needed"))).await?;
+        return Ok(());
+    };
+    let Some(reported) = reply.sender().filter(|chat| matches!(chat, Chat::User(_))) else {
+        return Ok(());
+    };
+    let reporter = ctx.sender().expect("Sender not found");
+
+    let mut text = t_a(
+        "report_received",
+        hashmap! {
+            "chat" => escape_html(chat.name()),
+            "reporter" => escape_html(reporter.name()),
+            "reported" => escape_html(reported.name()),
+            "reported_id" => reported.id().to_string(),
+            "link" => utils::message_link(&chat, reply.id()).unwrap_or_default(),
+        },
+    );
+
+    let list = notes.of(chat.id(), reported.id()).await;
+    if !list.is_empty() {
+        text += &t_a("report_notes", hashmap! { "notes" => render_notes(&list) });
+    }
+
+    let owner = PackedChat {
+        ty: PackedType::User,
+        id: sudoers.owner(),
+        access_hash: None,
+    };
+    ctx.client()
+        .send_message(owner, InputMessage::html(text))
+        .await?;
+
+    ctx.reply(t("report_sent")).await?;
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the NSFW detection handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    button, reply_markup,
+    types::{CallbackQuery, Message},
+    InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        nsfw::{Appeal, Nsfw, Settings},
+        reposts::Action,
+    },
+    utils::escape_html,
+};
+
+/// Setup the NSFW detection handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::command("nsfw")
+                    .and(filters::admins())
+                    .and(filters::fresh("bot")),
+            )
+            .then(nsfw),
+        )
+        .handler(handler::callback_query(filter::regex(r"^nsfw (restore|dismiss)")).then(appeal))
+}
+
+/// Handles the nsfw command.
+///
+/// Usage: `/nsfw [on|off|delete|flag|threshold <score>]`.
+async fn nsfw(ctx: Context, i18n: I18n, nsfw: Nsfw) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    if !nsfw.is_configured() {
+        ctx.reply(t("nsfw_not_configured")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let update: Option<Box<dyn FnOnce(&mut Settings) + Send>> = match args.as_slice() {
+        [] => None,
+        ["on"] => Some(Box::new(|settings| settings.enabled = true)),
+        ["off"] => Some(Box::new(|settings| settings.enabled = false)),
+        [name] => match Action::parse(name) {
+            Some(action) => Some(Box::new(move |settings| settings.action = action)),
+            None => {
+                ctx.reply(InputMessage::html(t("nsfw_usage"))).await?;
+                return Ok(());
+            }
+        },
+        ["threshold", threshold] => match threshold.parse::<u8>() {
+            Ok(threshold @ 1..=100) => {
+                Some(Box::new(move |settings| settings.threshold = threshold))
+            }
+            _ => {
+                ctx.reply(InputMessage::html(t("nsfw_usage"))).await?;
+                return Ok(());
+            }
+        },
+        _ => {
+            ctx.reply(InputMessage::html(t("nsfw_usage"))).await?;
+            return Ok(());
+        }
+    };
+
+    let settings = match update {
+        Some(f) => nsfw.update(chat_id, f).await,
+        None => nsfw.settings(chat_id).await,
+    };
+
+    ctx.reply(InputMessage::html(t_a(
+        "nsfw_settings",
+        hashmap! {
+            "enabled" => t(if settings.enabled { "reposts_on" } else { "reposts_off" }),
+            "action" => settings.action.name().to_string(),
+            "threshold" => settings.threshold.to_string(),
+        },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Scores a photo sent to a group, acting on it if explicit.
+///
+/// Returns `true` if it was deleted.
+pub async fn check_photo(
+    message: &Message,
+    bytes: &[u8],
+    nsfw: &Nsfw,
+    i18n: &I18n,
+) -> Result<bool> {
+    let chat_id = message.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let settings = nsfw.settings(chat_id).await;
+    let score = match nsfw.score(bytes).await {
+        Ok(score) => score,
+        Err(e) => {
+            log::error!("Failed to score a photo: {}", e);
+            return Ok(false);
+        }
+    };
+    if score < settings.threshold as f32 {
+        return Ok(false);
+    }
+
+    let sender = message
+        .sender()
+        .map(|sender| sender.name().to_string())
+        .unwrap_or_default();
+    let args = hashmap! {
+        "sender" => escape_html(&sender),
+        "score" => format!("{:.0}", score),
+    };
+
+    match settings.action {
+        Action::Delete => {
+            let Some(media) = message.media() else {
+                return Ok(false);
+            };
+            if let Err(e) = message.delete().await {
+                log::error!("Failed to delete an explicit photo: {}", e);
+                return Ok(false);
+            }
+
+            let id = nsfw
+                .add_appeal(Appeal {
+                    id: 0,
+                    chat: message.chat().pack(),
+                    media,
+                    sender,
+                })
+                .await;
+
+            message
+                .respond(InputMessage::html(t_a("nsfw_deleted", args)).reply_markup(
+                    &reply_markup::inline(vec![vec![button::inline(
+                        t("nsfw_restore"),
+                        format!("nsfw restore {}", id),
+                    )]]),
+                ))
+                .await?;
+
+            Ok(true)
+        }
+        Action::Flag => {
+            message
+                .reply(InputMessage::html(t_a("nsfw_flagged", args)).reply_markup(
+                    &reply_markup::inline(vec![vec![button::inline(
+                        t("nsfw_dismiss"),
+                        "nsfw dismiss",
+                    )]]),
+                ))
+                .await?;
+
+            Ok(false)
+        }
+    }
+}
+
+/// Handles the buttons that restore a deleted photo or dismiss a warning, for the admins.
+///
+/// The data is `nsfw restore <id>` or `nsfw dismiss`.
+async fn appeal(ctx: Context, query: CallbackQuery, i18n: I18n, nsfw: Nsfw) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let chat = query.chat().clone();
+    let permissions = ctx.client().get_permissions(&chat, query.sender()).await?;
+    if !permissions.is_creator() && !permissions.is_admin() {
+        query.answer().alert(t("nsfw_admins_only")).send().await?;
+        return Ok(());
+    }
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    match split.as_slice() {
+        ["restore", id] => {
+            let Some(appeal) = nsfw.take_appeal(id.parse::<u32>()?).await else {
+                query
+                    .answer()
+                    .alert(t("nsfw_appeal_expired"))
+                    .send()
+                    .await?;
+                return Ok(());
+            };
+
+            ctx.client()
+                .send_message(
+                    appeal.chat,
+                    InputMessage::html(i18n.translate_for_with_args(
+                        appeal.chat.id,
+                        "nsfw_restored",
+                        hashmap! { "sender" => escape_html(&appeal.sender) },
+                    ))
+                    .copy_media(&appeal.media),
+                )
+                .await?;
+            query
+                .answer()
+                .edit(InputMessage::html(t("nsfw_restored_notice")))
+                .await?;
+        }
+        _ => {
+            query.answer().send().await?;
+            query.load_message().await?.delete().await?;
+        }
+    }
+
+    Ok(())
+}
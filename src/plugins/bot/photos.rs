@@ -0,0 +1,75 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the handler of the photos sent to the groups.
+
+use ferogram::{handler, Context, Result, Router};
+
+use super::{links, nsfw, reposts};
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        media_cache::MediaCache,
+        nsfw::Nsfw,
+        reposts::{fingerprint, Reposts},
+        url_scanner::UrlScanner,
+    },
+};
+
+/// Setup the photos handler.
+///
+/// It matches every photo sent to a group, so each check runs from here.
+pub fn setup() -> Router {
+    Router::default().handler(handler::new_message(filters::group_photo()).then(photo))
+}
+
+/// Handles the photos sent to the groups, removing the explicit ones, the reposts
+/// and the ones with malicious links in their captions.
+async fn photo(
+    ctx: Context,
+    i18n: I18n,
+    nsfw: Nsfw,
+    reposts: Reposts,
+    url_scanner: UrlScanner,
+    media_cache: MediaCache,
+) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+    let chat_id = message.chat().id();
+
+    if links::check_links(&message, &url_scanner, &i18n).await? {
+        return Ok(());
+    }
+
+    let scan = nsfw.is_configured() && nsfw.settings(chat_id).await.enabled;
+    let dedupe = reposts.settings(chat_id).await.enabled;
+    if !scan && !dedupe {
+        return Ok(());
+    }
+
+    let Some(media) = message.media() else {
+        return Ok(());
+    };
+    let bytes = media_cache.download(ctx.client(), &media).await?;
+
+    // A removed photo is not remembered as posted.
+    if scan && nsfw::check_photo(&message, &bytes, &nsfw, &i18n).await? {
+        return Ok(());
+    }
+
+    if dedupe {
+        // Decoding the photo is too slow for the async workers.
+        let Some(hash) = tokio::task::spawn_blocking(move || fingerprint(&bytes)).await? else {
+            return Ok(());
+        };
+
+        reposts::check_photo(&ctx, &message, hash, &reposts, &i18n).await?;
+    }
+
+    Ok(())
+}
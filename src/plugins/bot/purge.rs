@@ -12,33 +12,64 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use ferogram::{filter, handler, Context, Filter, Result, Router};
-use grammers_client::InputMessage;
+use grammers_client::{types::CallbackQuery, InputMessage};
 use maplit::hashmap;
 
-use crate::{filters, modules::i18n::I18n};
+use crate::{
+    config::PluginSettings,
+    filters,
+    modules::{
+        i18n::I18n,
+        purges::{self, Purge, Purges},
+        scheduler::Scheduler,
+    },
+    utils, Recipient,
+};
 
 /// Setup the purge command.
 pub fn setup() -> Router {
     Router::default()
         .handler(
-            handler::new_message(filter::commands(&["del", "delete"]).and(filters::sudoers()))
-                .then(delete),
+            handler::new_message(
+                filter::commands(&["del", "delete"])
+                    .and(filters::sudoers())
+                    .and(filters::fresh("bot")),
+            )
+            .then(delete),
+        )
+        .handler(
+            handler::new_message(
+                filter::command("purge")
+                    .and(filters::sudoers())
+                    .and(filters::fresh("bot")),
+            )
+            .then(purge),
+        )
+        .handler(
+            handler::callback_query(
+                filter::regex(r"^purge (confirm|cancel) \d+$").and(filters::sudoers()),
+            )
+            .then(confirm),
         )
-        .handler(handler::new_message(filter::command("purge").and(filters::sudoers())).then(purge))
 }
 
 /// Handles the delete command.
-async fn delete(ctx: Context, i18n: I18n) -> Result<()> {
-    let t = |key: &str| i18n.translate(key);
+///
+/// The reply is kept as long as `plugins.purge.delete_ttl`, shared with my command.
+async fn delete(
+    ctx: Context,
+    i18n: I18n,
+    scheduler: Scheduler,
+    settings: PluginSettings,
+) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
 
     if let Some(reply) = ctx.get_reply().await? {
         match reply.delete().await {
             Ok(_) => {
-                let sent = ctx.reply(t("deleted")).await?;
-
-                tokio::time::sleep(Duration::from_secs(4)).await;
-                sent.delete().await?;
-                let _ = ctx.delete().await;
+                let ttl = Duration::from_secs(settings.get("purge", "delete_ttl"));
+                utils::reply_ephemeral(&ctx, &scheduler, Recipient::Bot, t("deleted"), ttl).await?;
             }
             Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
                 ctx.reply(t("i_dont_have_perms")).await?;
@@ -55,17 +86,44 @@ async fn delete(ctx: Context, i18n: I18n) -> Result<()> {
     Ok(())
 }
 
-/// Handles the purge command.
-async fn purge(ctx: Context, i18n: I18n) -> Result<()> {
-    let t = |key: &str| i18n.translate(key);
-    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+/// Handles the purge command, as `.purge` on a reply, `.purge <n>` or
+/// `.purge <duration>`.
+///
+/// The purges above `plugins.purge.confirm_threshold`, shared with my command, are
+/// only done once confirmed.
+async fn purge(ctx: Context, i18n: I18n, settings: PluginSettings, purges: Purges) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
 
-    if let Some(reply) = ctx.get_reply().await? {
+    if let Some(message_ids) = utils::purge_targets(&ctx).await? {
         let msg = ctx.message().await.unwrap();
-        let message_ids = (reply.id()..=(msg.id() - 1)).collect::<Vec<_>>();
+        let reply = ctx.get_reply().await?;
         let total_messages = message_ids.len();
         let mut purged_messages = 0;
 
+        let threshold = settings.get::<usize>("purge", "confirm_threshold");
+        if threshold > 0 && total_messages > threshold {
+            let (id, input) = purges
+                .ask(
+                    ctx.client(),
+                    Purge {
+                        recipient: Recipient::Bot,
+                        client: ctx.client().clone(),
+                        chat: msg.chat().pack(),
+                        message_ids,
+                        command_ids: Vec::new(),
+                    },
+                    &i18n,
+                )
+                .await?;
+
+            let sent = ctx.reply(input).await?;
+            purges.attach(id, sent.id()).await;
+
+            return Ok(());
+        }
+
         let sent = ctx
             .reply(InputMessage::html(t_a(
                 "purging",
@@ -75,44 +133,51 @@ async fn purge(ctx: Context, i18n: I18n) -> Result<()> {
             )))
             .await?;
 
-        let mut waited = 0;
         for chunk in message_ids.chunks(100) {
-            match ctx.delete_messages(chunk.to_vec()).await {
-                Ok(count) => purged_messages += count,
-                Err(e) if e.is("MESSAGE_ID_INVALID") => continue,
-                Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
-                    let now: DateTime<Utc> = Utc::now();
-                    let diff = now - reply.date();
-
-                    if diff.num_days() >= 2 {
-                        sent.edit(t("old_message")).await?;
-                    } else {
-                        sent.edit(t("i_dont_have_perms")).await?;
+            loop {
+                match ctx.delete_messages(chunk.to_vec()).await {
+                    Ok(count) => purged_messages += count,
+                    Err(e) if e.is("MESSAGE_ID_INVALID") => {}
+                    Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
+                        let now: DateTime<Utc> = Utc::now();
+                        let old = reply
+                            .as_ref()
+                            .is_some_and(|reply| (now - reply.date()).num_days() >= 2);
+
+                        if old {
+                            sent.edit(t("old_message")).await?;
+                        } else {
+                            sent.edit(t("i_dont_have_perms")).await?;
+                        }
+
+                        return Ok(());
                     }
+                    Err(e) => match utils::flood_wait(&e) {
+                        Some(wait) => {
+                            let sent = ctx
+                                .reply(InputMessage::html(t_a(
+                                    "flood_wait",
+                                    hashmap! { "seconds" => wait.as_secs().to_string() },
+                                )))
+                                .await?;
+
+                            // The chunk is deleted again once the wait is over.
+                            tokio::time::sleep(wait).await;
+                            sent.delete().await?;
 
-                    return Ok(());
-                }
-                Err(e) if e.is("FLOOD_WAIT") => {
-                    let time = 5 * (waited + 1);
-                    waited += 1;
-
-                    let sent = ctx
-                        .reply(InputMessage::html(t_a(
-                            "flood_wait",
-                            hashmap! { "seconds" => time.to_string() },
-                        )))
-                        .await?;
-
-                    tokio::time::sleep(Duration::from_secs(time)).await;
-                    sent.delete().await?;
-                }
-                Err(e) => {
-                    log::error!("Failed to purge messages: {}", e);
-                    sent.edit(t("purge_error")).await?;
-
-                    return Ok(());
-                }
-            };
+                            continue;
+                        }
+                        None => {
+                            log::error!("Failed to purge messages: {}", e);
+                            sent.edit(t("purge_error")).await?;
+
+                            return Ok(());
+                        }
+                    },
+                };
+
+                break;
+            }
         }
 
         sent.edit(InputMessage::html(t_a(
@@ -123,8 +188,76 @@ async fn purge(ctx: Context, i18n: I18n) -> Result<()> {
         )))
         .await?;
     } else {
-        ctx.reply(InputMessage::html(t("reply_needed"))).await?;
+        ctx.reply(InputMessage::html(t("purge_usage"))).await?;
     }
 
     Ok(())
 }
+
+/// Handles the buttons confirming or cancelling a purge, of either client.
+///
+/// The result is kept as long as `plugins.purge.purge_ttl`, deleted along with the
+/// command and the confirmation.
+async fn confirm(
+    ctx: Context,
+    query: CallbackQuery,
+    i18n: I18n,
+    purges: Purges,
+    scheduler: Scheduler,
+    settings: PluginSettings,
+) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+    let [action, id] = split.as_slice() else {
+        return Ok(());
+    };
+
+    let Some(purge) = purges.take(id.parse::<u32>()?).await else {
+        query.answer().alert(t("purge_expired")).send().await?;
+        return Ok(());
+    };
+
+    if *action == "cancel" {
+        query
+            .answer()
+            .edit(InputMessage::html(t("purge_cancelled")))
+            .await?;
+        return Ok(());
+    }
+
+    query
+        .answer()
+        .edit(InputMessage::html(t_a(
+            "purging",
+            hashmap! { "count" => purge.message_ids.len().to_string() },
+        )))
+        .await?;
+
+    let text = match purges::delete_messages(&purge.client, purge.chat, &purge.message_ids).await {
+        Ok(count) => t_a("purged", hashmap! { "count" => count.to_string() }),
+        Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => match purge.recipient {
+            Recipient::Bot => t("i_dont_have_perms"),
+            Recipient::User(_) => t("you_dont_have_perms"),
+        },
+        Err(e) => {
+            log::error!("Failed to purge messages: {}", e);
+            t("purge_error")
+        }
+    };
+
+    // The confirmation cannot be edited again, as the query was already answered.
+    let sent = client
+        .send_message(purge.chat, InputMessage::html(text))
+        .await?;
+
+    let mut message_ids = purge.command_ids;
+    message_ids.push(sent.id());
+    let ttl = Duration::from_secs(settings.get("purge", "purge_ttl"));
+    utils::delete_later(&scheduler, purge.recipient, purge.chat, message_ids, ttl).await?;
+
+    Ok(())
+}
@@ -0,0 +1,250 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the raid mode handlers.
+
+use chrono::Utc;
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    button, grammers_tl_types as tl, reply_markup,
+    types::{CallbackQuery, Chat},
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        raids::{Mode, Raids},
+    },
+    utils,
+};
+
+/// Setup the raid mode handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::command("raidmode")
+                    .and(filters::admins())
+                    .and(filters::fresh("bot")),
+            )
+            .then(raid_mode),
+        )
+        .handler(handler::callback_query(filter::regex(r"^raid off")).then(disable))
+        .handler(handler::new_message(filters::new_members()).then(new_members))
+}
+
+/// Checks if a chat is a supergroup, the only kind the raid mode works in.
+fn is_supergroup(chat: &Chat) -> bool {
+    matches!(chat, Chat::Group(group) if group.is_megagroup())
+}
+
+/// Requires the new members to be approved by an admin, or lifts the requirement.
+async fn set_join_requests(client: &Client, chat: &Chat, enabled: bool) {
+    if !is_supergroup(chat) {
+        return;
+    }
+    let channel = match utils::input_channel(chat) {
+        Ok(channel) => channel,
+        Err(e) => {
+            log::warn!("Failed to toggle the join requests: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = client
+        .invoke(&tl::functions::channels::ToggleJoinRequest { channel, enabled })
+        .await
+    {
+        // Only public groups accept join requests, the mutes still apply.
+        if !e.is("CHAT_NOT_MODIFIED") {
+            log::warn!("Failed to toggle the join requests: {}", e);
+        }
+    }
+}
+
+/// Mutes a new member until the raid mode ends.
+///
+/// The member is referenced through the message of their joining, as their
+/// access hash is not known.
+async fn mute(
+    client: &Client,
+    chat: &Chat,
+    message_id: i32,
+    user_id: i64,
+    raids: &Raids,
+) -> Result<()> {
+    if !is_supergroup(chat) {
+        return Ok(());
+    }
+    let channel = utils::input_channel(chat)?;
+    let until_date = (Utc::now().timestamp() + raids.duration().as_secs() as i64) as i32;
+
+    client
+        .invoke(&tl::functions::channels::EditBanned {
+            channel,
+            participant: tl::types::InputPeerUserFromMessage {
+                peer: chat.pack().to_input_peer(),
+                msg_id: message_id,
+                user_id,
+            }
+            .into(),
+            banned_rights: tl::types::ChatBannedRights {
+                view_messages: false,
+                send_messages: true,
+                send_media: true,
+                send_stickers: true,
+                send_gifs: true,
+                send_games: true,
+                send_inline: true,
+                embed_links: true,
+                send_polls: true,
+                change_info: true,
+                invite_users: true,
+                pin_messages: true,
+                manage_topics: true,
+                send_photos: true,
+                send_videos: true,
+                send_roundvideos: true,
+                send_audios: true,
+                send_voices: true,
+                send_docs: true,
+                send_plain: true,
+                until_date,
+            }
+            .into(),
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the raidmode command.
+///
+/// Usage: `/raidmode [on|off|auto]`.
+async fn raid_mode(ctx: Context, i18n: I18n, raids: Raids) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let chat = ctx.chat().expect("Chat not found");
+    if !is_supergroup(&chat) {
+        ctx.reply(t("raid_supergroup_only")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.text().unwrap();
+    let Some(mode) = text.split_whitespace().nth(1).and_then(Mode::parse) else {
+        let mode = raids.mode(chat.id()).await;
+        ctx.reply(InputMessage::html(t_a(
+            "raid_mode_current",
+            hashmap! { "mode" => mode.name() },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    raids.set_mode(chat.id(), mode).await;
+    set_join_requests(ctx.client(), &chat, mode == Mode::On).await;
+
+    ctx.reply(InputMessage::html(t_a(
+        "raid_mode_set",
+        hashmap! { "mode" => mode.name() },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the members joining, detecting raids.
+async fn new_members(ctx: Context, i18n: I18n, raids: Raids) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let chat = ctx.chat().expect("Chat not found");
+    let message = ctx.message().await.unwrap();
+    let users = match message.action() {
+        Some(tl::enums::MessageAction::ChatAddUser(action)) => action.users.clone(),
+        _ => message.sender().map(|s| vec![s.id()]).unwrap_or_default(),
+    };
+
+    if raids.record_joins(chat.id(), users.len()).await {
+        set_join_requests(ctx.client(), &chat, true).await;
+
+        let minutes = raids.duration().as_secs() / 60;
+        ctx.reply(
+            InputMessage::html(t_a(
+                "raid_detected",
+                hashmap! { "minutes" => minutes.to_string() },
+            ))
+            .reply_markup(&reply_markup::inline(vec![vec![button::inline(
+                t("raid_disable"),
+                "raid off",
+            )]])),
+        )
+        .await?;
+
+        // Lifts the restrictions once the raid mode ends, unless already disabled.
+        let client = ctx.client().clone();
+        let raids = raids.clone();
+        let chat = chat.clone();
+        let text = t("raid_ended");
+        tokio::task::spawn(async move {
+            tokio::time::sleep(raids.duration()).await;
+
+            if raids.end(chat.id()).await {
+                set_join_requests(&client, &chat, false).await;
+
+                if let Err(e) = client.send_message(&chat, text).await {
+                    log::error!("Failed to announce the end of the raid: {}", e);
+                }
+            }
+        });
+    }
+
+    if raids.is_active(chat.id()).await {
+        for user_id in users {
+            if let Err(e) = mute(ctx.client(), &chat, message.id(), user_id, &raids).await {
+                log::error!("Failed to mute a new member: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the button that disables the raid mode.
+async fn disable(ctx: Context, query: CallbackQuery, i18n: I18n, raids: Raids) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let chat = query.chat().clone();
+    let permissions = ctx.client().get_permissions(&chat, query.sender()).await?;
+    if !permissions.is_creator() && !permissions.is_admin() {
+        query.answer().alert(t("raid_admins_only")).send().await?;
+        return Ok(());
+    }
+
+    // A forced raid mode falls back to the automatic detection.
+    if raids.mode(chat.id()).await == Mode::On {
+        raids.set_mode(chat.id(), Mode::Auto).await;
+    } else {
+        raids.end(chat.id()).await;
+    }
+    set_join_requests(ctx.client(), &chat, false).await;
+
+    query
+        .answer()
+        .edit(InputMessage::html(t("raid_disabled")))
+        .await?;
+
+    Ok(())
+}
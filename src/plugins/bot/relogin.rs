@@ -0,0 +1,87 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the relogin command handler.
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::InputMessage;
+use maplit::hashmap;
+
+use crate::{
+    modules::{
+        i18n::I18n,
+        sudoers::Sudoers,
+        supervisor::{Login, Supervisor},
+    },
+    utils::escape_html,
+};
+
+/// Setup the relogin command.
+pub fn setup() -> Router {
+    Router::default().handler(handler::new_message(filter::command("relogin")).then(relogin))
+}
+
+/// Handles the relogin command, logging a user account in again.
+///
+/// Usage: `/relogin`, then `/relogin <code>` and, if asked, `/relogin <password>`.
+async fn relogin(ctx: Context, i18n: I18n, sudoers: Sudoers, supervisor: Supervisor) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let sender = ctx.sender().expect("Sender not found");
+    if !sudoers.is_owner(sender.id()) {
+        return Ok(());
+    }
+
+    // The accounts are logged in again one at a time, the first one down first.
+    let Some(account) = supervisor.down_user() else {
+        ctx.reply(InputMessage::html(t("relogin_not_needed")))
+            .await?;
+        return Ok(());
+    };
+
+    let text = ctx.text().unwrap();
+    let Some((_, secret)) = text.split_once(' ') else {
+        let text = match supervisor.request_login_code(account).await {
+            Ok(()) => t("relogin_code_sent"),
+            Err(e) => t_a(
+                "relogin_failed",
+                hashmap! { "error" => escape_html(&e.to_string()) },
+            ),
+        };
+        ctx.reply(InputMessage::html(text)).await?;
+
+        return Ok(());
+    };
+
+    // Neither the code nor the password are kept in the chat.
+    let _ = ctx.delete().await;
+
+    // Telegram expires the codes shared in chats, so they are sent with spaces.
+    let secret = secret.trim();
+    let code = secret.split_whitespace().collect::<String>();
+    let secret = if code.chars().all(|c| c.is_ascii_digit()) {
+        code.as_str()
+    } else {
+        secret
+    };
+
+    let text = match supervisor.sign_in(secret).await {
+        Ok(Login::NotStarted) => t("relogin_not_started"),
+        Ok(Login::PasswordRequired) => t("relogin_password_required"),
+        Ok(Login::Done) => t("relogin_done"),
+        Err(e) => t_a(
+            "relogin_failed",
+            hashmap! { "error" => escape_html(&e.to_string()) },
+        ),
+    };
+    ctx.reply(InputMessage::html(text)).await?;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the reminder snooze handlers.
+
+use chrono::Local;
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{types::CallbackQuery, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, scheduler::Scheduler},
+};
+
+/// Setup the reminder snooze handlers.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::callback_query(filter::regex(r"^remind snooze \d+ \d+$").and(filters::sudoers()))
+            .then(snooze),
+    )
+}
+
+/// Handles the snooze buttons of the delivered reminders.
+async fn snooze(query: CallbackQuery, i18n: I18n, scheduler: Scheduler) -> Result<()> {
+    let chat_id = query.chat().id();
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(2).collect::<Vec<_>>();
+    let [id, minutes] = split.as_slice() else {
+        return Ok(());
+    };
+
+    let Some((id, at)) = scheduler
+        .snooze(id.parse::<u32>()?, minutes.parse::<u32>()?)
+        .await
+    else {
+        query
+            .answer()
+            .alert(i18n.translate_for(chat_id, "remind_snooze_expired"))
+            .send()
+            .await?;
+        return Ok(());
+    };
+
+    // Keeps the reminder, without the buttons.
+    let message = query.load_message().await?;
+    let text = format!(
+        "{0}\n\n{1}",
+        message.html_text(),
+        i18n.translate_for_with_args(
+            chat_id,
+            "remind_snoozed",
+            hashmap! {
+                "id" => id.to_string(),
+                "at" => at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string(),
+            },
+        )
+    );
+    query.answer().edit(InputMessage::html(text)).await?;
+
+    Ok(())
+}
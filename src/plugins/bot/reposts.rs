@@ -0,0 +1,170 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the reposts handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{types::Message, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        reposts::{Action, Reposts, Settings},
+    },
+    utils::message_link,
+};
+
+/// The most days a photo can be remembered.
+const MAX_DAYS: u32 = 90;
+
+/// Setup the reposts handlers.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(
+            filter::command("noreposts")
+                .and(filters::admins())
+                .and(filters::fresh("bot")),
+        )
+        .then(no_reposts),
+    )
+}
+
+/// Handles the noreposts command.
+///
+/// Usage: `/noreposts [on|off|delete|flag|days <n>|threshold <percent>|exempt <on|off>]`.
+async fn no_reposts(ctx: Context, i18n: I18n, reposts: Reposts) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let text = ctx.text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let update: Option<Box<dyn FnOnce(&mut Settings) + Send>> = match args.as_slice() {
+        [] => None,
+        ["on"] => Some(Box::new(|settings| settings.enabled = true)),
+        ["off"] => Some(Box::new(|settings| settings.enabled = false)),
+        [name] => match Action::parse(name) {
+            Some(action) => Some(Box::new(move |settings| settings.action = action)),
+            None => {
+                ctx.reply(InputMessage::html(t("reposts_usage"))).await?;
+                return Ok(());
+            }
+        },
+        ["days", days] => match days.parse::<u32>() {
+            Ok(days @ 1..=MAX_DAYS) => Some(Box::new(move |settings| settings.days = days)),
+            _ => {
+                ctx.reply(InputMessage::html(t("reposts_usage"))).await?;
+                return Ok(());
+            }
+        },
+        ["threshold", threshold] => match threshold.trim_end_matches('%').parse::<u8>() {
+            Ok(threshold @ 50..=100) => {
+                Some(Box::new(move |settings| settings.threshold = threshold))
+            }
+            _ => {
+                ctx.reply(InputMessage::html(t("reposts_usage"))).await?;
+                return Ok(());
+            }
+        },
+        ["exempt", toggle @ ("on" | "off")] => {
+            let exempt = *toggle == "on";
+            Some(Box::new(move |settings| settings.exempt_admins = exempt))
+        }
+        _ => {
+            ctx.reply(InputMessage::html(t("reposts_usage"))).await?;
+            return Ok(());
+        }
+    };
+
+    let settings = match update {
+        Some(f) => reposts.update(chat_id, f).await,
+        None => reposts.settings(chat_id).await,
+    };
+
+    ctx.reply(InputMessage::html(render(&settings, &i18n, chat_id)))
+        .await?;
+
+    Ok(())
+}
+
+/// Checks if a photo sent to a group is a repost, acting on it.
+///
+/// Returns `true` if it was deleted.
+pub async fn check_photo(
+    ctx: &Context,
+    message: &Message,
+    hash: u64,
+    reposts: &Reposts,
+    i18n: &I18n,
+) -> Result<bool> {
+    let chat = message.chat();
+    let settings = reposts.settings(chat.id()).await;
+    if !settings.enabled {
+        return Ok(false);
+    }
+
+    if settings.exempt_admins {
+        if let Some(sender) = message.sender() {
+            let permissions = ctx.client().get_permissions(&chat, sender).await?;
+            if permissions.is_creator() || permissions.is_admin() {
+                return Ok(false);
+            }
+        }
+    }
+
+    let Some(original) = reposts
+        .check(chat.id(), hash, message.id(), message.date().timestamp())
+        .await
+    else {
+        return Ok(false);
+    };
+
+    match settings.action {
+        Action::Delete => {
+            if let Err(e) = message.delete().await {
+                log::error!("Failed to delete a repost: {}", e);
+                return Ok(false);
+            }
+
+            Ok(true)
+        }
+        Action::Flag => {
+            let text = match message_link(&chat, original) {
+                Some(link) => i18n.translate_for_with_args(
+                    chat.id(),
+                    "reposts_flagged",
+                    hashmap! { "link" => link },
+                ),
+                None => i18n.translate_for(chat.id(), "reposts_flagged_no_link"),
+            };
+
+            message.reply(InputMessage::html(text)).await?;
+
+            Ok(false)
+        }
+    }
+}
+
+/// Renders the settings of a chat.
+fn render(settings: &Settings, i18n: &I18n, chat_id: i64) -> String {
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    i18n.translate_for_with_args(
+        chat_id,
+        "reposts_settings",
+        hashmap! {
+            "enabled" => t(if settings.enabled { "reposts_on" } else { "reposts_off" }),
+            "action" => settings.action.name().to_string(),
+            "days" => settings.days.to_string(),
+            "threshold" => settings.threshold.to_string(),
+            "exempt" => t(if settings.exempt_admins { "reposts_on" } else { "reposts_off" }),
+        },
+    )
+}
@@ -8,76 +8,122 @@
 
 //! This module contains the screenshot command handler.
 
-use ferogram::{filter, handler, Context, Result, Router};
+use ferogram::{filter, handler, Context, Filter, Result, Router};
 use grammers_client::{grammers_tl_types::enums::MessageEntity, InputMessage};
 
-use crate::{modules::i18n::I18n, utils::take_a_screenshot};
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    utils::{self, take_a_screenshot, Args, ScreenshotOptions},
+};
 
 /// Setup the screenshot command.
+///
+/// Editing the command message takes the screenshot again, so a mistyped URL can be fixed.
 pub fn setup() -> Router {
     Router::default()
-        .handler(handler::new_message(filter::commands(&["screenshot", "print"])).then(screenshot))
+        .handler(
+            handler::new_message(
+                filter::commands(&["screenshot", "print"])
+                    .and(filters::respond_to_edits("screenshot")),
+            )
+            .then(screenshot),
+        )
+        .handler(
+            handler::message_edited(
+                filter::commands(&["screenshot", "print"])
+                    .and(filters::respond_to_edits("screenshot")),
+            )
+            .then(screenshot),
+        )
 }
 
 /// Handles the screenshot command.
+///
+/// Usage: `screenshot <url> [--mobile] [--full] [--delay <seconds>] [--selector <css>] [--dark]`,
+/// or replying to a message with a URL.
 async fn screenshot(ctx: Context, i18n: I18n) -> Result<()> {
-    let t = |key: &str| i18n.translate(key);
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
 
     let text = ctx.text().unwrap();
-    if let Some(reply) = ctx.get_reply().await? {
+    let args = text
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, args)| args);
+    let Some(args) = Args::parse(args, &ScreenshotOptions::VALUED_FLAGS) else {
+        ctx.reply(InputMessage::html(t("screenshot_invalid_flags")))
+            .await?;
+        return Ok(());
+    };
+    let options = match ScreenshotOptions::from_args(&args) {
+        Some(options) if args.unknown(&ScreenshotOptions::FLAGS).next().is_none() => options,
+        _ => {
+            ctx.reply(InputMessage::html(t("screenshot_invalid_flags")))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let url = if let Some(reply) = ctx.get_reply().await? {
         let text = reply.text().to_string();
 
-        if let Some(entities) = reply.fmt_entities() {
-            let url_entities = entities
-                .into_iter()
-                .filter(|entity| {
-                    matches!(entity, MessageEntity::Url(_) | MessageEntity::TextUrl(_))
-                })
-                .collect::<Vec<_>>();
+        let url_entities = reply
+            .fmt_entities()
+            .map(|entities| {
+                entities
+                    .iter()
+                    .filter(|entity| {
+                        matches!(entity, MessageEntity::Url(_) | MessageEntity::TextUrl(_))
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
 
-            if url_entities.is_empty() {
+        match url_entities.first() {
+            Some(MessageEntity::TextUrl(entity)) => entity.url.clone(),
+            Some(entity) => {
+                // The entities are offset in UTF-16 code units.
+                let text = text.encode_utf16().collect::<Vec<_>>();
+                let offset = entity.offset() as usize;
+                let length = entity.length() as usize;
+
+                String::from_utf16_lossy(&text[offset..(offset + length)])
+            }
+            None => {
                 ctx.reply(t("reply_not_url")).await?;
                 return Ok(());
             }
-
-            let sent = ctx.reply(t("screenshot_processing")).await?;
-
-            let entity = url_entities[0];
-            let offset = entity.offset() as usize;
-            let length = entity.length() as usize;
-
-            let url = &text[offset..(offset + length)];
-            match take_a_screenshot(url.to_string()).await {
-                Ok(photo_url) => {
-                    ctx.send(InputMessage::html("").photo_url(photo_url))
-                        .await?;
-                    sent.delete().await?;
-                }
-                Err(_) => {
-                    sent.edit(t("screenshot_error")).await?;
-                }
-            }
-        } else {
-            ctx.reply(t("reply_not_url")).await?;
         }
-    } else if text.split_whitespace().count() < 2 {
-        ctx.reply(t("screenshot_no_url")).await?;
-    } else if text.split_whitespace().count() > 2 {
-        ctx.reply(t("screenshot_many_urls")).await?;
     } else {
-        let sent = ctx.reply(t("screenshot_processing")).await?;
-
-        let url = text.split_whitespace().skip(1).next().unwrap();
-        match take_a_screenshot(url.to_string()).await {
-            Ok(photo_url) => {
-                ctx.send(InputMessage::text(url).photo_url(photo_url))
-                    .await?;
-                sent.delete().await?;
+        match args.positional() {
+            [] => {
+                ctx.reply(t("screenshot_no_url")).await?;
+                return Ok(());
             }
-            Err(_) => {
-                sent.edit(t("screenshot_error")).await?;
+            [url] => url.clone(),
+            _ => {
+                ctx.reply(t("screenshot_many_urls")).await?;
+                return Ok(());
             }
         }
+    };
+
+    let sent = ctx.reply(t("screenshot_processing")).await?;
+
+    match take_a_screenshot(url.clone(), &options).await {
+        Ok(photo_url) => {
+            ctx.send(
+                InputMessage::text(url)
+                    .photo_url(photo_url)
+                    .reply_to(utils::current_topic(&ctx).await),
+            )
+            .await?;
+            sent.delete().await?;
+        }
+        Err(_) => {
+            sent.edit(t("screenshot_error")).await?;
+        }
     }
 
     Ok(())
@@ -20,7 +20,8 @@ pub fn setup() -> Router {
 
 /// Handles the start command.
 async fn start(ctx: Context, i18n: I18n) -> Result<()> {
-    let t = |key: &str| i18n.translate(key);
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
 
     ctx.reply(t("start_text")).await?;
 
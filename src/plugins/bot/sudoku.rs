@@ -0,0 +1,144 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the sudoku command handler.
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    callback_data,
+    modules::{
+        games::{Difficulty, GameManager, GameMessage, Player, Put, Sudoku},
+        i18n::I18n,
+    },
+    utils::sudoku_to_buttons,
+};
+
+/// Setup the sudoku command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filter::command("sudoku")).then(sudoku))
+        .handler(handler::callback_query(filter::regex(r"^sdk (\d+)")).then(sudoku_callback))
+}
+
+/// Handles the sudoku command.
+///
+/// Usage: `/sudoku [easy|medium|hard]`.
+async fn sudoku(ctx: Context, i18n: I18n, manager: GameManager) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let text = ctx.text().unwrap();
+    let difficulty = match text.split_whitespace().nth(1) {
+        Some(name) => match Difficulty::parse(name) {
+            Some(difficulty) => difficulty,
+            None => {
+                ctx.reply(InputMessage::html(t("sudoku_usage"))).await?;
+                return Ok(());
+            }
+        },
+        None => Difficulty::Medium,
+    };
+
+    let sender = ctx.sender().expect("Sender not found");
+    let game = Sudoku::new(manager.new_id().await, Player::new(&sender), difficulty).into_game();
+
+    let buttons = sudoku_to_buttons(game.as_sudoku().unwrap(), game.id(), None);
+    let sent = ctx
+        .reply(
+            InputMessage::html(game.generate_text()).reply_markup(&reply_markup::inline(buttons)),
+        )
+        .await?;
+
+    manager
+        .add_game(game, Some(GameMessage::from_message(&sent)))
+        .await;
+
+    Ok(())
+}
+
+/// Handles the sudoku keyboard.
+///
+/// The data is one of `sdk <id> box <box>`, `sdk <id> cell <row> <column>`,
+/// `sdk <id> put <digit>` and `sdk <id> back`.
+async fn sudoku_callback(query: CallbackQuery, i18n: I18n, manager: GameManager) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(data) = callback_data::verify(query.data()) else {
+        query.answer().alert(t("invalid_callback")).send().await?;
+        return Ok(());
+    };
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let game_id = split[0].parse::<i32>()?;
+    let Some(mut game) = manager.get_game(game_id).await else {
+        query.answer().alert(t("game_not_found")).send().await?;
+        return Ok(());
+    };
+    if !manager.bind(game_id, &query).await {
+        query.answer().alert(t("game_wrong_message")).send().await?;
+        return Ok(());
+    }
+
+    if !game.has_player(query.sender().id()) {
+        query.answer().alert(t("not_in_game")).send().await?;
+        return Ok(());
+    }
+
+    let mut answer = query.answer();
+    let mut block = None;
+
+    match split.get(1).copied() {
+        Some("box") => block = Some(split[2].parse::<usize>()?.min(8)),
+        Some("cell") => {
+            let row = split[2].parse::<usize>()?;
+            let column = split[3].parse::<usize>()?;
+
+            if !game.play(column, row) {
+                query.answer().alert(t("sudoku_given_cell")).send().await?;
+                return Ok(());
+            }
+        }
+        Some("put") => {
+            let digit = split[2].parse::<u8>()?.min(9);
+            let sudoku = game.as_sudoku_mut().unwrap();
+
+            match sudoku.put(digit) {
+                Put::Placed | Put::NoSelection => {}
+                Put::Wrong => {
+                    answer = answer.alert(t_a(
+                        "sudoku_wrong_digit",
+                        hashmap! { "digit" => digit.to_string() },
+                    ));
+                }
+                Put::Lost => answer = answer.alert(t("sudoku_lost")),
+                Put::Solved => answer = answer.alert(t("sudoku_solved")),
+            }
+        }
+        _ => game.as_sudoku_mut().unwrap().deselect(),
+    }
+
+    let mut input = InputMessage::html(game.generate_text());
+    if !game.is_over() {
+        let buttons = sudoku_to_buttons(game.as_sudoku().unwrap(), game.id(), block);
+        input = input.reply_markup(&reply_markup::inline(buttons));
+    }
+    answer.edit(input).await?;
+
+    if game.is_over() {
+        manager.remove_game(game).await;
+    } else {
+        manager.update_game(game).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the saved messages tags handlers.
+
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, tags::Tags},
+};
+
+/// Setup the tags handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::callback_query(filter::regex(r"^tag (\d+) (\d+)$").and(filters::sudoers()))
+                .then(tag),
+        )
+        .handler(
+            handler::callback_query(filter::regex(r"^tagged (\d+) (\d+)").and(filters::sudoers()))
+                .then(tagged),
+        )
+}
+
+/// Handles the tag buttons.
+async fn tag(query: CallbackQuery, i18n: I18n, tags: Tags) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let message_id = split[0].parse::<i32>()?;
+    let Some(name) = tags.name_of(split[1].parse::<usize>()?).await else {
+        query.answer().alert(t("tag_not_tracked")).send().await?;
+        return Ok(());
+    };
+
+    let key = match tags.toggle(message_id, &name).await {
+        Some(true) => "tag_added",
+        Some(false) => "tag_removed",
+        None => {
+            query.answer().alert(t("tag_not_tracked")).send().await?;
+            return Ok(());
+        }
+    };
+
+    let buttons = tags.keyboard(message_id).await;
+    query
+        .answer()
+        .text(t_a(key, hashmap! { "tag" => name }))
+        .edit(InputMessage::html(t("tag_choose")).reply_markup(&reply_markup::inline(buttons)))
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the tagged pagination buttons.
+async fn tagged(query: CallbackQuery, i18n: I18n, tags: Tags) -> Result<()> {
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let Some(name) = tags.name_of(split[0].parse::<usize>()?).await else {
+        return Ok(());
+    };
+    let page = split[1].parse::<usize>()?;
+
+    let sender = query.sender();
+    let (text, buttons) = tags.render_page(&i18n, sender.id(), &name, page).await;
+
+    query
+        .answer()
+        .edit(InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)))
+        .await?;
+
+    Ok(())
+}
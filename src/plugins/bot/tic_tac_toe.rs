@@ -8,16 +8,19 @@
 
 //! This module contains the tic tac toe command handler.
 
-use ferogram::{filter, handler, Result, Router};
-use grammers_client::{reply_markup, types::CallbackQuery, InputMessage};
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, Client, InputMessage};
 use maplit::hashmap;
 
 use crate::{
+    callback_data,
     modules::{
-        games::{GameManager, Player},
+        chat_settings::ChatSettings,
+        games::{Game, GameManager, Player},
         i18n::I18n,
+        tournaments::Tournaments,
     },
-    utils::board_to_buttons,
+    utils::{board_to_buttons, BoardLayout},
 };
 
 /// Setup the tic tac toe command.
@@ -27,15 +30,30 @@ pub fn setup() -> Router {
 }
 
 /// Handles the tic tac toe command.
-async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager) -> Result<()> {
-    let t = |key: &str| i18n.translate(key);
-    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
-
-    let data = String::from_utf8(query.data().to_vec())?;
+async fn tic_tac_toe(
+    ctx: Context,
+    query: CallbackQuery,
+    i18n: I18n,
+    manager: GameManager,
+    tournaments: Tournaments,
+    settings: ChatSettings,
+) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let Some(data) = callback_data::verify(query.data()) else {
+        query.answer().alert(t("invalid_callback")).send().await?;
+        return Ok(());
+    };
     let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
 
     let game_id = split[0].parse::<i32>()?;
-    if let Some(mut game) = manager.get_game(game_id) {
+    if let Some(mut game) = manager.get_game(game_id).await {
+        if !manager.bind(game_id, &query).await {
+            query.answer().alert(t("game_wrong_message")).send().await?;
+            return Ok(());
+        }
         let sender = query.sender();
 
         if !game.has_player(sender.id()) && game.available_seats() == 0 {
@@ -48,7 +66,8 @@ async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager)
                 if game.current_player().is_none() {
                     game.set_current_player(sender.id());
                 } else {
-                    let buttons = board_to_buttons(game.board(), game.id());
+                    let buttons =
+                        board_to_buttons(game.board(), "ttt", game.id(), BoardLayout::Cells);
                     query
                         .answer()
                         .edit(
@@ -57,7 +76,8 @@ async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager)
                         )
                         .await?;
 
-                    manager.update_game(game);
+                    arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+                    manager.update_game(game).await;
                     return Ok(());
                 }
             } else {
@@ -81,7 +101,7 @@ async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager)
             return Ok(());
         }
 
-        let buttons = board_to_buttons(game.board(), game.id());
+        let buttons = board_to_buttons(game.board(), "ttt", game.id(), BoardLayout::Cells);
         let mut answer = query.answer();
 
         if game.is_over() {
@@ -107,9 +127,24 @@ async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager)
             .await?;
 
         if game.is_over() {
-            manager.remove_game(game);
+            let winner = game.winner().map(|p| p.id());
+            let game_id = game.id();
+            manager.remove_game(game).await;
+
+            if let Some(id) = tournaments.report(game_id, winner).await {
+                super::tournament::advance(
+                    ctx.client(),
+                    &i18n,
+                    &manager,
+                    &tournaments,
+                    &settings,
+                    id,
+                )
+                .await?;
+            }
         } else {
-            manager.update_game(game);
+            arm_timer(&manager, &game, ctx.client().clone(), i18n.clone(), chat_id).await;
+            manager.update_game(game).await;
         }
     } else {
         query.answer().alert(t("game_not_found")).send().await?;
@@ -117,3 +152,33 @@ async fn tic_tac_toe(query: CallbackQuery, i18n: I18n, mut manager: GameManager)
 
     Ok(())
 }
+
+/// Arms the turn timer of the game, so the inactive player forfeits when it expires.
+pub async fn arm_timer(manager: &GameManager, game: &Game, bot: Client, i18n: I18n, chat_id: i64) {
+    manager
+        .arm_timer(game.id(), move |game, message| async move {
+            // The message is only known once a button of it was pressed.
+            let Some(message) = message else {
+                return;
+            };
+
+            let player = game
+                .current_player()
+                .map(|p| p.mention())
+                .unwrap_or_default();
+            let text = format!(
+                "{0}\n\n{1}",
+                game.generate_text(),
+                i18n.translate_for_with_args(
+                    chat_id,
+                    "game_timeout",
+                    hashmap! { "player" => player }
+                )
+            );
+
+            if let Err(e) = message.edit(&bot, InputMessage::html(text)).await {
+                log::error!("Failed to update the timed out game: {}", e);
+            }
+        })
+        .await;
+}
@@ -0,0 +1,124 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the forum topics command handler.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{grammers_tl_types as tl, types::Chat, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    utils::{self, escape_html},
+};
+
+/// The maximum length of a topic title.
+const MAX_TITLE_LENGTH: usize = 128;
+
+/// Setup the topics command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filter::command("topics").and(filters::sudoers())).then(topics),
+    )
+}
+
+/// Edits the current topic of a forum.
+async fn edit_topic(
+    ctx: &Context,
+    chat: &Chat,
+    topic: i32,
+    title: Option<String>,
+    closed: Option<bool>,
+) -> Result<()> {
+    ctx.client()
+        .invoke(&tl::functions::channels::EditForumTopic {
+            channel: utils::input_channel(chat)?,
+            topic_id: topic,
+            title,
+            icon_emoji_id: None,
+            closed,
+            hidden: None,
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Handles the topics command.
+async fn topics(ctx: Context, i18n: I18n) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let chat = ctx.chat().expect("Chat not found");
+    if !utils::is_forum(&chat) {
+        ctx.reply(t("topics_not_forum")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let topic = utils::current_topic(&ctx).await;
+
+    let title = args
+        .iter()
+        .skip(1)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_TITLE_LENGTH)
+        .collect::<String>();
+
+    let channel = utils::input_channel(&chat)?;
+    let result: Result<&str> = match (args.first().copied(), topic) {
+        (Some("create"), _) if !title.is_empty() => ctx
+            .client()
+            .invoke(&tl::functions::channels::CreateForumTopic {
+                channel: channel.clone(),
+                title: title.clone(),
+                icon_color: None,
+                icon_emoji_id: None,
+                random_id: rand::random(),
+                send_as: None,
+            })
+            .await
+            .map(|_| "topic_created")
+            .map_err(Into::into),
+        (Some("rename"), Some(topic)) if !title.is_empty() => {
+            edit_topic(&ctx, &chat, topic, Some(title.clone()), None)
+                .await
+                .map(|_| "topic_renamed")
+        }
+        (Some("close"), Some(topic)) => edit_topic(&ctx, &chat, topic, None, Some(true))
+            .await
+            .map(|_| "topic_closed"),
+        (Some("reopen"), Some(topic)) => edit_topic(&ctx, &chat, topic, None, Some(false))
+            .await
+            .map(|_| "topic_reopened"),
+        (Some("rename" | "close" | "reopen"), None) => Ok("topics_not_in_topic"),
+        _ => Ok("topics_usage"),
+    };
+
+    match result {
+        Ok(key) => {
+            ctx.reply(InputMessage::html(t_a(
+                key,
+                hashmap! { "title" => escape_html(&title) },
+            )))
+            .await?;
+        }
+        Err(e) => {
+            log::error!("Failed to manage the topic: {}", e);
+            ctx.reply(t("topics_error")).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,304 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the tournament command handler.
+
+use std::io::Cursor;
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{
+    button::{self, Inline},
+    reply_markup,
+    types::CallbackQuery,
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    modules::{
+        chat_settings::ChatSettings,
+        games::{GameManager, GameMessage, Player, TicTacToe},
+        i18n::I18n,
+        tournaments::{GameKind, SignUp, Tournaments},
+    },
+    utils::{board_to_buttons, BoardLayout},
+};
+
+/// The maximum number of players of a tournament.
+const MAX_SLOTS: usize = 32;
+
+/// Setup the tournament command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filter::command("tournament")).then(tournament))
+        .handler(handler::callback_query(filter::regex(r"^tournament (\d+)")).then(join))
+}
+
+/// Handles the tournament command.
+async fn tournament(ctx: Context, i18n: I18n, tournaments: Tournaments) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let text = ctx.text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let (game, slots) = match args.as_slice() {
+        ["create", game, slots] => (GameKind::parse(game), slots.parse::<usize>().ok()),
+        _ => (None, None),
+    };
+
+    let Some(game) = game else {
+        ctx.reply(InputMessage::html(t("tournament_usage"))).await?;
+        return Ok(());
+    };
+    let Some(slots) = slots.filter(|s| (2..=MAX_SLOTS).contains(s)) else {
+        ctx.reply(t_a(
+            "tournament_invalid_slots",
+            hashmap! { "max" => MAX_SLOTS.to_string() },
+        ))
+        .await?;
+        return Ok(());
+    };
+
+    let chat = ctx.chat().expect("Chat not found");
+    let id = tournaments.create(chat, game, slots).await;
+
+    ctx.reply(
+        InputMessage::html(sign_up_text(&i18n, &tournaments, id).await)
+            .reply_markup(&reply_markup::inline(join_buttons(&i18n, chat_id, id))),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the join button.
+async fn join(
+    ctx: Context,
+    query: CallbackQuery,
+    i18n: I18n,
+    manager: GameManager,
+    tournaments: Tournaments,
+    settings: ChatSettings,
+) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let id = data.split_whitespace().nth(1).unwrap().parse::<i32>()?;
+
+    let sender = query.sender();
+    let Some(result) = tournaments
+        .with(id, |tournament| {
+            let result = tournament.sign_up(Player::new(&sender));
+            if result == SignUp::Joined && tournament.is_full() {
+                tournament.generate_bracket();
+            }
+
+            (result, tournament.has_started())
+        })
+        .await
+    else {
+        query
+            .answer()
+            .alert(t("tournament_not_found"))
+            .send()
+            .await?;
+        return Ok(());
+    };
+
+    match result {
+        (SignUp::Joined, started) => {
+            let text = sign_up_text(&i18n, &tournaments, id).await;
+
+            if started {
+                query.answer().edit(InputMessage::html(text)).await?;
+                advance(ctx.client(), &i18n, &manager, &tournaments, &settings, id).await?;
+            } else {
+                query
+                    .answer()
+                    .text(t("tournament_joined"))
+                    .edit(
+                        InputMessage::html(text)
+                            .reply_markup(&reply_markup::inline(join_buttons(&i18n, chat_id, id))),
+                    )
+                    .await?;
+            }
+        }
+        (SignUp::AlreadyJoined, _) => {
+            query
+                .answer()
+                .alert(t("tournament_already_joined"))
+                .send()
+                .await?;
+        }
+        (SignUp::Full, _) => {
+            query.answer().alert(t("tournament_full")).send().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders the sign-up message of a tournament.
+async fn sign_up_text(i18n: &I18n, tournaments: &Tournaments, id: i32) -> String {
+    tournaments
+        .with(id, |tournament| {
+            let players = tournament
+                .players
+                .iter()
+                .map(|p| format!("• {}", p.mention()))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            i18n.translate_for_with_args(
+                tournament.chat.id(),
+                "tournament_sign_up",
+                hashmap! {
+                    "game" => tournament.game.to_string(),
+                    "joined" => tournament.players.len().to_string(),
+                    "slots" => tournament.slots.to_string(),
+                    "players" => players,
+                },
+            )
+        })
+        .await
+        .unwrap_or_default()
+}
+
+/// Builds the join button of a tournament.
+fn join_buttons(i18n: &I18n, chat_id: i64, id: i32) -> Vec<Vec<Inline>> {
+    vec![vec![button::inline(
+        i18n.translate_for(chat_id, "tournament_join"),
+        format!("tournament {}", id),
+    )]]
+}
+
+/// Starts the matches that are ready and posts the updated bracket.
+///
+/// Called whenever the bracket changes, once every seat is taken and after each result.
+pub async fn advance(
+    client: &Client,
+    i18n: &I18n,
+    manager: &GameManager,
+    tournaments: &Tournaments,
+    settings: &ChatSettings,
+    id: i32,
+) -> Result<()> {
+    let Some((chat, game, matches, bracket, champion)) = tournaments
+        .with(id, |tournament| {
+            let matches = tournament
+                .pending_matches()
+                .into_iter()
+                .map(|(round, index)| {
+                    let players = tournament.rounds[round][index]
+                        .players
+                        .iter()
+                        .flatten()
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    (round, index, players)
+                })
+                .collect::<Vec<_>>();
+
+            (
+                tournament.chat.clone(),
+                tournament.game,
+                matches,
+                tournament.render_bracket(),
+                tournament.champion().map(|p| p.mention()),
+            )
+        })
+        .await
+    else {
+        return Ok(());
+    };
+    let chat_id = chat.id();
+
+    match bracket {
+        Ok(bytes) => {
+            let size = bytes.len();
+            let mut stream = Cursor::new(bytes);
+            let file = client
+                .upload_stream(&mut stream, size, "bracket.png".to_string())
+                .await?;
+
+            client
+                .send_message(
+                    chat.clone(),
+                    InputMessage::html(i18n.translate_for_with_args(
+                        chat_id,
+                        "tournament_bracket",
+                        hashmap! { "game" => game.to_string() },
+                    ))
+                    .photo(file),
+                )
+                .await?;
+        }
+        Err(e) => log::error!("Failed to render the bracket: {}", e),
+    }
+
+    if let Some(champion) = champion {
+        client
+            .send_message(
+                chat,
+                InputMessage::html(i18n.translate_for_with_args(
+                    chat_id,
+                    "tournament_champion",
+                    hashmap! { "player" => champion },
+                )),
+            )
+            .await?;
+        tournaments.remove(id).await;
+
+        return Ok(());
+    }
+
+    let symbols = settings.game_symbols(chat_id).await;
+    for (round, index, players) in matches {
+        let game = match game {
+            GameKind::TicTacToe => {
+                let mut ttt =
+                    TicTacToe::new(manager.new_id().await, players).with_symbols(symbols.clone());
+                ttt.generate_board(3..=3);
+                ttt.into_game()
+            }
+        };
+
+        tournaments
+            .with(id, |tournament| {
+                tournament.set_game(round, index, game.id())
+            })
+            .await;
+
+        let text = format!(
+            "{0}\n{1}",
+            i18n.translate_for_with_args(
+                chat_id,
+                "tournament_match",
+                hashmap! { "round" => (round + 1).to_string() },
+            ),
+            game.generate_text()
+        );
+        let buttons = board_to_buttons(game.board(), "ttt", game.id(), BoardLayout::Cells);
+        let sent = client
+            .send_message(
+                chat.clone(),
+                InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)),
+            )
+            .await?;
+
+        manager
+            .add_game(game, Some(GameMessage::from_message(&sent)))
+            .await;
+    }
+
+    Ok(())
+}
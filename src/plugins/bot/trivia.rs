@@ -0,0 +1,244 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the trivia command handler.
+
+use std::time::Duration;
+
+use ferogram::{filter, handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, Client, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    callback_data,
+    modules::{
+        games::{Answer, GameManager, GameMessage, Player, Trivia},
+        i18n::I18n,
+        trivia::QuestionBank,
+    },
+    utils::{escape_html, trivia_to_buttons},
+};
+
+/// How long the right answer is shown before the next question.
+const REVEAL_TIME: Duration = Duration::from_secs(5);
+
+/// Setup the trivia command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filter::command("trivia")).then(trivia))
+        .handler(handler::callback_query(filter::regex(r"^trv (\d+)")).then(trivia_callback))
+}
+
+/// Handles the trivia command.
+///
+/// Usage: `/trivia [pack]`, the questions coming from Open Trivia DB without a pack.
+async fn trivia(
+    ctx: Context,
+    i18n: I18n,
+    manager: GameManager,
+    questions: QuestionBank,
+) -> Result<()> {
+    let chat_id = ctx.chat().expect("Chat not found").id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let text = ctx.text().unwrap();
+    let list = match text.split_whitespace().nth(1) {
+        Some(pack) => match questions.from_pack(pack) {
+            Some(list) => list,
+            None => {
+                let packs = questions.packs();
+                let packs = if packs.is_empty() {
+                    "—".to_string()
+                } else {
+                    packs.join(", ")
+                };
+
+                ctx.reply(InputMessage::html(t_a(
+                    "trivia_pack_not_found",
+                    hashmap! { "pack" => escape_html(pack), "packs" => packs },
+                )))
+                .await?;
+                return Ok(());
+            }
+        },
+        None => match questions.fetch().await {
+            Ok(list) => list,
+            Err(e) => {
+                log::error!("Failed to fetch the trivia questions: {}", e);
+                ctx.reply(t("trivia_error")).await?;
+                return Ok(());
+            }
+        },
+    };
+    if list.is_empty() {
+        ctx.reply(t("trivia_error")).await?;
+        return Ok(());
+    }
+
+    let sender = ctx.sender().expect("Sender not found");
+    let game = Trivia::new(manager.new_id().await, Player::new(&sender), list).into_game();
+
+    let buttons = trivia_to_buttons(game.as_trivia().unwrap(), game.id());
+    let sent = ctx
+        .reply(
+            InputMessage::html(game.generate_text()).reply_markup(&reply_markup::inline(buttons)),
+        )
+        .await?;
+
+    let game_id = game.id();
+    let message = GameMessage::from_message(&sent);
+    manager.add_game(game, Some(message.clone())).await;
+
+    run_rounds(
+        manager,
+        game_id,
+        message,
+        ctx.client(),
+        i18n.clone(),
+        chat_id,
+        questions.answer_time(),
+    );
+
+    Ok(())
+}
+
+/// Spawns the task that closes each round once its time is up, showing the
+/// right answer and the scores before the next question.
+fn run_rounds(
+    manager: GameManager,
+    game_id: i32,
+    message: GameMessage,
+    bot: Client,
+    i18n: I18n,
+    chat_id: i64,
+    answer_time: Duration,
+) {
+    tokio::task::spawn(async move {
+        loop {
+            tokio::time::sleep(answer_time).await;
+
+            // The game is gone once expired.
+            let Some(game) = manager
+                .with_game(game_id, |game| {
+                    if let Some(trivia) = game.as_trivia_mut() {
+                        trivia.close_round();
+                    }
+
+                    game.clone()
+                })
+                .await
+            else {
+                return;
+            };
+
+            if game.is_over() {
+                let text = format!(
+                    "{0}\n\n{1}",
+                    game.generate_text(),
+                    i18n.translate_for(chat_id, "trivia_over")
+                );
+                if let Err(e) = message.edit(&bot, InputMessage::html(text)).await {
+                    log::error!("Failed to show the trivia scoreboard: {}", e);
+                }
+
+                manager.remove_game(game).await;
+                return;
+            }
+
+            if let Err(e) = message
+                .edit(&bot, InputMessage::html(game.generate_text()))
+                .await
+            {
+                log::error!("Failed to show the trivia answer: {}", e);
+            }
+
+            tokio::time::sleep(REVEAL_TIME).await;
+
+            let Some(game) = manager
+                .with_game(game_id, |game| {
+                    if let Some(trivia) = game.as_trivia_mut() {
+                        trivia.next_round();
+                    }
+
+                    game.clone()
+                })
+                .await
+            else {
+                return;
+            };
+
+            let buttons = trivia_to_buttons(game.as_trivia().unwrap(), game_id);
+            if let Err(e) = message
+                .edit(
+                    &bot,
+                    InputMessage::html(game.generate_text())
+                        .reply_markup(&reply_markup::inline(buttons)),
+                )
+                .await
+            {
+                log::error!("Failed to show the trivia question: {}", e);
+            }
+        }
+    });
+}
+
+/// Handles the trivia answers.
+///
+/// The data is `trv <id> <round> <answer>`.
+async fn trivia_callback(query: CallbackQuery, i18n: I18n, manager: GameManager) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+
+    let Some(data) = callback_data::verify(query.data()) else {
+        query.answer().alert(t("invalid_callback")).send().await?;
+        return Ok(());
+    };
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let game_id = split[0].parse::<i32>()?;
+    let round = split[1].parse::<usize>()?;
+    let answer = split[2].parse::<usize>()?;
+
+    if manager.get_game(game_id).await.is_none() {
+        query.answer().alert(t("game_not_found")).send().await?;
+        return Ok(());
+    }
+    if !manager.bind(game_id, &query).await {
+        query.answer().alert(t("game_wrong_message")).send().await?;
+        return Ok(());
+    }
+
+    let sender = query.sender();
+    let result = manager
+        .with_game(game_id, |game| {
+            let trivia = game.as_trivia_mut()?;
+            if trivia.round() != round {
+                return Some(Answer::Closed);
+            }
+
+            Some(trivia.answer(Player::new(&sender), answer))
+        })
+        .await
+        .flatten();
+
+    match result {
+        Some(Answer::Recorded) => query.answer().text(t("trivia_answered")).send().await?,
+        Some(Answer::Repeated) => {
+            query
+                .answer()
+                .alert(t("trivia_already_answered"))
+                .send()
+                .await?
+        }
+        Some(Answer::Closed) => query.answer().alert(t("trivia_round_over")).send().await?,
+        None => query.answer().alert(t("game_not_found")).send().await?,
+    }
+
+    Ok(())
+}
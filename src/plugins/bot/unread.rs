@@ -0,0 +1,89 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the unread digest handlers.
+
+use ferogram::{filter, handler, Filter, Result, Router};
+use grammers_client::{reply_markup, types::CallbackQuery, Client, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{ghost::Ghost, i18n::I18n, unread::Unread},
+};
+
+/// Setup the unread digest handlers.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::callback_query(filter::regex(r"^unread (read|all)").and(filters::sudoers()))
+            .then(mark_read),
+    )
+}
+
+/// Handles the mark read buttons.
+async fn mark_read(
+    query: CallbackQuery,
+    i18n: I18n,
+    unread: Unread,
+    ghost: Ghost,
+    user: Client,
+) -> Result<()> {
+    let chat_id = query.chat().id();
+    let t = |key: &str| i18n.translate_for(chat_id, key);
+    let t_a = |key: &str, args| i18n.translate_for_with_args(chat_id, key, args);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let split = data.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let chats = match split.as_slice() {
+        ["read", id] => unread.take(id.parse::<i64>()?).await.into_iter().collect(),
+        _ => unread.take_all().await,
+    };
+
+    if chats.is_empty() {
+        query.answer().alert(t("unread_expired")).send().await?;
+        return Ok(());
+    }
+
+    // Goes through the ghost mode, so ghosted chats stay unread.
+    let mut ghosted = 0;
+    for chat in chats.iter() {
+        if !ghost.read(&user, chat).await? {
+            ghosted += 1;
+        }
+    }
+
+    // The chats left unread by the ghost mode are reported.
+    let marked = if ghosted > 0 {
+        t_a(
+            "unread_marked_ghosted",
+            hashmap! { "count" => ghosted.to_string() },
+        )
+    } else {
+        t("unread_marked")
+    };
+
+    match unread.render(&i18n).await {
+        Some((text, buttons)) => {
+            query
+                .answer()
+                .text(marked)
+                .edit(InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)))
+                .await?;
+        }
+        None => {
+            query
+                .answer()
+                .text(marked)
+                .edit(InputMessage::html(t("unread_all_read")))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the weather command handlers.
+
+use std::time::Duration;
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::{
+    button::{self, Inline},
+    reply_markup,
+    types::{CallbackQuery, Message},
+    InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        weather::{Place, Weather},
+    },
+    utils::escape_html,
+};
+
+/// Setup the weather handlers.
+///
+/// Editing the command message looks the weather up again, so a mistyped city can be fixed.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::command("weather")
+                    .and(filters::fresh("bot"))
+                    .and(filters::respond_to_edits("weather")),
+            )
+            .then(weather),
+        )
+        .handler(
+            handler::message_edited(
+                filter::command("weather")
+                    .and(filters::fresh("bot"))
+                    .and(filters::respond_to_edits("weather")),
+            )
+            .then(weather),
+        )
+        .handler(handler::callback_query(filter::regex(r"^weather (\d+)")).then(refresh))
+}
+
+/// Handles the weather command, showing the current weather of a city and its
+/// forecast.
+///
+/// Usage: `/weather <city>`.
+async fn weather(ctx: Context, message: Message, i18n: I18n, weather: Weather) -> Result<()> {
+    let chat_id = message.chat().id();
+    let locale = i18n.locale_for(chat_id);
+    let t = |key: &str| i18n.translate_from_locale(key, &locale);
+
+    let text = message.text();
+    let query = text
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, query)| query.trim());
+    if query.is_empty() {
+        ctx.reply(InputMessage::html(t("weather_usage"))).await?;
+        return Ok(());
+    }
+
+    let place = match weather.search(query, &locale).await {
+        Ok(Some(place)) => place,
+        Ok(None) => {
+            ctx.reply(InputMessage::html(i18n.translate_from_locale_with_args(
+                "weather_not_found",
+                &locale,
+                hashmap! { "city" => escape_html(query) },
+            )))
+            .await?;
+            return Ok(());
+        }
+        Err(e) => {
+            log::error!("Failed to find the city {}: {}", query, e);
+            ctx.reply(t("weather_error")).await?;
+            return Ok(());
+        }
+    };
+
+    match weather.forecast(&place).await {
+        Ok(forecast) => {
+            ctx.reply(
+                InputMessage::html(forecast.render(&i18n, &locale, &place))
+                    .reply_markup(&reply_markup::inline(keyboard(&i18n, &locale, &place))),
+            )
+            .await?;
+        }
+        Err(e) => {
+            log::error!("Failed to fetch the weather of {}: {}", place.name, e);
+            ctx.reply(t("weather_error")).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the refresh button, fetching the weather of the place again.
+///
+/// The data is `weather <place id>`.
+async fn refresh(query: CallbackQuery, i18n: I18n, weather: Weather) -> Result<()> {
+    let locale = i18n.locale_for(query.chat().id());
+    let t = |key: &str| i18n.translate_from_locale(key, &locale);
+
+    let data = String::from_utf8(query.data().to_vec())?;
+    let id = data
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .parse::<i32>()?;
+
+    let Some(place) = weather.get(id).await else {
+        query.answer().alert(t("weather_expired")).send().await?;
+        return Ok(());
+    };
+
+    match weather.forecast(&place).await {
+        Ok(forecast) => {
+            query
+                .answer()
+                .text(t("weather_updated"))
+                .cache_time(Duration::from_secs(10))
+                .edit(
+                    InputMessage::html(forecast.render(&i18n, &locale, &place))
+                        .reply_markup(&reply_markup::inline(keyboard(&i18n, &locale, &place))),
+                )
+                .await?;
+        }
+        Err(e) => {
+            log::error!("Failed to fetch the weather of {}: {}", place.name, e);
+            query.answer().alert(t("weather_error")).send().await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the button that refreshes the weather of a place.
+fn keyboard(i18n: &I18n, locale: &str, place: &Place) -> Vec<Vec<Inline>> {
+    vec![vec![button::inline(
+        i18n.translate_from_locale("reload_button", locale),
+        format!("weather {}", place.id),
+    )]]
+}
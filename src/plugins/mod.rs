@@ -8,6 +8,12 @@
 
 use ferogram::{Dispatcher, Injector};
 use grammers_client::Client;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::PluginConfig,
+    modules::{i18n::I18n, webapp::WebAppData},
+};
 
 mod bot;
 mod user;
@@ -25,3 +31,22 @@ pub fn user(bot: Client, mut resources: Injector) -> Dispatcher {
             .allow_from_self(),
     )
 }
+
+/// Returns the plugins that declare a config section.
+pub fn configs() -> Vec<Box<dyn PluginConfig>> {
+    let mut configs = bot::configs();
+    configs.extend(user::configs());
+
+    configs
+}
+
+/// Handles the data sent by the web app with the bot instance.
+pub async fn web_app(bot: Client, i18n: I18n, rx: &mut mpsc::Receiver<WebAppData>) {
+    let router = bot::web_app();
+
+    while let Some(data) = rx.recv().await {
+        if let Err(e) = router.dispatch(&bot, &i18n, data).await {
+            log::error!("Failed to handle the web app data: {}", e);
+        }
+    }
+}
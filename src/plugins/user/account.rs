@@ -0,0 +1,346 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the account management commands handlers.
+
+use std::io::Cursor;
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{types::Chat, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        contacts::{self, Contact},
+        i18n::I18n,
+        media_cache::MediaCache,
+        profile::Profile,
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// The maximum number of contacts listed by a search.
+const SEARCH_LIMIT: usize = 30;
+
+/// Setup the account management commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(filters::command("addcontact").and(filters::sudoers()))
+                .then(add_contact),
+        )
+        .handler(
+            handler::new_message(filters::command("delcontact").and(filters::sudoers()))
+                .then(delete_contact),
+        )
+        .handler(
+            handler::new_message(filters::command("contacts").and(filters::sudoers()))
+                .then(contacts),
+        )
+        .handler(
+            handler::new_message(filters::command("clock").and(filters::sudoers())).then(clock),
+        )
+}
+
+/// Resolves the target user, either the replied message sender or a `@username`.
+async fn resolve_user(ctx: &Context, target: Option<&str>) -> Result<Option<Chat>> {
+    let chat = match target {
+        Some(username) if username.starts_with('@') => {
+            ctx.client().resolve_username(&username[1..]).await?
+        }
+        _ => match ctx.get_reply().await? {
+            Some(reply) => reply.sender(),
+            None => None,
+        },
+    };
+
+    Ok(chat.filter(|chat| matches!(chat, Chat::User(_))))
+}
+
+/// Handles the addcontact command.
+///
+/// Adds the replied or mentioned user, or imports a phone number.
+async fn add_contact(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let client = ctx.client();
+
+    if args.first().is_some_and(|arg| arg.starts_with('+')) {
+        let phone = args.remove(0).to_string();
+        let first_name = args
+            .first()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| phone.clone());
+        let last_name = args.iter().skip(1).copied().collect::<Vec<_>>().join(" ");
+
+        let (imported, _) = contacts::import(
+            client,
+            vec![Contact {
+                id: 0,
+                first_name: first_name.clone(),
+                last_name,
+                phone,
+                username: None,
+            }],
+        )
+        .await?;
+
+        let key = if imported > 0 {
+            "contact_added"
+        } else {
+            "contact_duplicate"
+        };
+        ctx.respond(Response::html(t_a(
+            key,
+            hashmap! { "name" => escape_html(&first_name) },
+        )))
+        .await?;
+
+        return Ok(());
+    }
+
+    let target = args.first().copied().filter(|arg| arg.starts_with('@'));
+    if target.is_some() {
+        args.remove(0);
+    }
+
+    let Some(user) = resolve_user(&ctx, target).await? else {
+        ctx.respond(Response::html(t("addcontact_usage"))).await?;
+        return Ok(());
+    };
+
+    let known = contacts::list(client).await?;
+    if known.iter().any(|c| c.id == user.id()) {
+        ctx.respond(Response::html(t_a(
+            "contact_duplicate",
+            hashmap! { "name" => escape_html(user.name()) },
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let (first_name, last_name) = match args.split_first() {
+        Some((first, last)) => (first.to_string(), last.join(" ")),
+        None => (user.name().to_string(), String::new()),
+    };
+    contacts::add_user(client, &user, &first_name, &last_name).await?;
+
+    ctx.respond(Response::html(t_a(
+        "contact_added",
+        hashmap! { "name" => escape_html(&format!("{} {}", first_name, last_name).trim()) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the delcontact command.
+async fn delete_contact(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let target = text.split_whitespace().nth(1);
+
+    let Some(user) = resolve_user(&ctx, target).await? else {
+        ctx.respond(Response::html(t("delcontact_usage"))).await?;
+        return Ok(());
+    };
+
+    let client = ctx.client();
+    if !contacts::list(client)
+        .await?
+        .iter()
+        .any(|c| c.id == user.id())
+    {
+        ctx.respond(Response::html(t_a(
+            "contact_not_found",
+            hashmap! { "name" => escape_html(user.name()) },
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    contacts::delete(client, &user).await?;
+    ctx.respond(Response::html(t_a(
+        "contact_deleted",
+        hashmap! { "name" => escape_html(user.name()) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the contacts command.
+async fn contacts(ctx: Context, i18n: I18n, media_cache: MediaCache) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let client = ctx.client();
+
+    match args.split_first() {
+        Some((&"search", query)) if !query.is_empty() => {
+            let query = query.join(" ");
+            let found = contacts::list(client)
+                .await?
+                .into_iter()
+                .filter(|c| c.matches(&query))
+                .take(SEARCH_LIMIT)
+                .map(|c| {
+                    let mut line = format!("• <b>{}</b>", escape_html(&c.name()));
+                    if let Some(username) = c.username.as_ref() {
+                        line += &format!(" @{}", username);
+                    }
+                    if !c.phone.is_empty() {
+                        line += &format!(" <code>+{}</code>", contacts::normalize_phone(&c.phone));
+                    }
+
+                    line
+                })
+                .collect::<Vec<_>>();
+
+            if found.is_empty() {
+                ctx.respond(t("contacts_none_found")).await?;
+            } else {
+                ctx.respond(Response::html(t_a(
+                    "contacts_found",
+                    hashmap! { "contacts" => found.join("\n") },
+                )))
+                .await?;
+            }
+        }
+        Some((&"export", [])) => {
+            let list = contacts::list(client).await?;
+            let content = contacts::to_vcards(&list);
+
+            let bytes = content.as_bytes();
+            let size = bytes.len();
+            let mut stream = Cursor::new(bytes);
+            let file = ctx
+                .upload_stream(&mut stream, size, "contacts.vcf".to_string())
+                .await?;
+
+            ctx.respond(Response::html(t_a(
+                "contacts_exported",
+                hashmap! { "count" => list.len().to_string() },
+            )))
+            .await?;
+            ctx.reply(InputMessage::text("").document(file)).await?;
+        }
+        Some((&"import", [])) => {
+            let media = match ctx.get_reply().await? {
+                Some(reply) => reply.media(),
+                None => None,
+            };
+            let Some(media) = media else {
+                ctx.respond(t("contacts_import_no_file")).await?;
+                return Ok(());
+            };
+
+            let msg = ctx.edit_or_reply(t("contacts_importing")).await?;
+
+            let bytes = media_cache.download(client, &media).await?;
+
+            let parsed = contacts::parse_vcards(&String::from_utf8_lossy(&bytes));
+            if parsed.is_empty() {
+                ctx.respond_in(&msg, t("contacts_import_empty")).await?;
+                return Ok(());
+            }
+
+            let (imported, duplicates) = contacts::import(client, parsed).await?;
+            ctx.respond_in(
+                &msg,
+                Response::html(t_a(
+                    "contacts_imported",
+                    hashmap! {
+                        "imported" => imported.to_string(),
+                        "duplicates" => duplicates.to_string(),
+                    },
+                )),
+            )
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("contacts_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the clock command.
+///
+/// Toggles the clock in the last name and the daily bio rotation.
+async fn clock(ctx: Context, i18n: I18n, profile: Profile) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let client = ctx.client();
+
+    let key = match args.as_slice() {
+        ["on"] => {
+            if profile.enable_clock(client).await? {
+                "clock_enabled"
+            } else {
+                "clock_already_enabled"
+            }
+        }
+        ["off"] => {
+            if profile.disable_clock(client).await? {
+                "clock_disabled"
+            } else {
+                "clock_not_enabled"
+            }
+        }
+        ["bio", "on"] if !profile.has_bios() => "bio_rotation_no_bios",
+        ["bio", state @ ("on" | "off")] => {
+            let enabled = *state == "on";
+
+            match (profile.set_bio(client, enabled).await?, enabled) {
+                (true, true) => "bio_rotation_enabled",
+                (true, false) => "bio_rotation_disabled",
+                (false, true) => "bio_rotation_already_enabled",
+                (false, false) => "bio_rotation_not_enabled",
+            }
+        }
+        [] => {
+            let state = |enabled: bool| {
+                if enabled {
+                    t("clock_state_on")
+                } else {
+                    t("clock_state_off")
+                }
+            };
+
+            ctx.respond(Response::html(t_a(
+                "clock_status",
+                hashmap! {
+                    "clock" => state(profile.clock_enabled().await),
+                    "bio" => state(profile.bio_enabled().await),
+                },
+            )))
+            .await?;
+
+            return Ok(());
+        }
+        _ => "clock_usage",
+    };
+
+    ctx.respond(Response::html(t(key))).await?;
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the AFK command and watcher handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::InputMessage;
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{afk::Afk, i18n::I18n},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+    Sender,
+};
+
+/// Setup the AFK command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("afk").and(filter::me)).then(afk))
+}
+
+/// Setup the AFK watcher.
+///
+/// It only matches while away, and must come before the alert watcher.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::afk()).then(watch))
+}
+
+/// Handles the AFK command.
+async fn afk(ctx: Context, i18n: I18n, afk: Afk) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let reason = text
+        .split_once(char::is_whitespace)
+        .map(|(_, reason)| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+
+    let text = match reason.as_ref() {
+        Some(reason) => t_a(
+            "afk_set_reason",
+            hashmap! { "reason" => escape_html(reason) },
+        ),
+        None => t("afk_set"),
+    };
+    afk.set_away(reason).await;
+
+    ctx.respond(Response::html(text)).await?;
+
+    Ok(())
+}
+
+/// Handles the messages while away.
+///
+/// Outgoing messages mark the user as back, the others are replied to with
+/// the reason, once in a while per user.
+async fn watch(ctx: Context, i18n: I18n, afk: Afk, tx: Sender) -> Result<()> {
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let message = ctx.message().await.unwrap();
+    if message.outgoing() {
+        if let Some(away) = afk.set_back().await {
+            let text = t_a(
+                "afk_back",
+                hashmap! {
+                    "elapsed" => utils::human_readable_duration(away.elapsed()),
+                    "messages" => away.messages.to_string(),
+                },
+            );
+
+            tx.send(crate::Message::to_bot().notify(InputMessage::html(text)))
+                .await?;
+        }
+
+        return Ok(());
+    }
+
+    let Some(sender) = message.sender() else {
+        return Ok(());
+    };
+    let Some(away) = afk.away().await else {
+        return Ok(());
+    };
+
+    if afk.record(sender.id()).await {
+        let elapsed = utils::human_readable_duration(away.elapsed());
+        let text = match away.reason.as_ref() {
+            Some(reason) => t_a(
+                "afk_reply_reason",
+                hashmap! { "elapsed" => elapsed, "reason" => escape_html(reason) },
+            ),
+            None => t_a("afk_reply", hashmap! { "elapsed" => elapsed }),
+        };
+
+        message.reply(InputMessage::html(text)).await?;
+    }
+
+    Ok(())
+}
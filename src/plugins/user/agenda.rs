@@ -0,0 +1,93 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the agenda command handler.
+
+use chrono::{Datelike, Local, NaiveDate, TimeDelta};
+use ferogram::{handler, Context, Filter, Result, Router};
+
+use crate::{
+    filters,
+    modules::{calendar::Calendar, i18n::I18n},
+    output::{Respond, Response},
+};
+
+/// Setup the agenda command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["ag", "agenda"]).and(filters::sudoers()))
+            .then(agenda),
+    )
+}
+
+/// Parses the day argument of the agenda command.
+fn parse_day(arg: Option<&str>) -> Option<NaiveDate> {
+    let today = Local::now().date_naive();
+
+    match arg {
+        None | Some("hoje") | Some("today") => Some(today),
+        Some("amanha") | Some("amanhã") | Some("tomorrow") => Some(today + TimeDelta::days(1)),
+        Some("ontem") | Some("yesterday") => Some(today - TimeDelta::days(1)),
+        Some(arg) => NaiveDate::parse_from_str(arg, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(arg, "%d/%m/%Y"))
+            .ok()
+            .or_else(|| {
+                let (day, month) = arg.split_once('/')?;
+                NaiveDate::from_ymd_opt(today.year(), month.parse().ok()?, day.parse().ok()?)
+            }),
+    }
+}
+
+/// Handles the agenda command.
+async fn agenda(ctx: Context, i18n: I18n, calendar: Calendar) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    if !calendar.is_configured() {
+        ctx.respond(t("agenda_not_configured")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    if args.first() == Some(&"sync") {
+        let msg = ctx.edit_or_reply(t("agenda_syncing")).await?;
+
+        match calendar.sync().await {
+            Ok(_) => {
+                ctx.respond_in(
+                    &msg,
+                    Response::html(
+                        calendar
+                            .render_agenda(&i18n, Local::now().date_naive())
+                            .await,
+                    ),
+                )
+                .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to synchronize the calendar: {}", e);
+                ctx.respond_in(&msg, t("agenda_sync_error")).await?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    match parse_day(args.first().copied()) {
+        Some(day) => {
+            ctx.respond(Response::html(calendar.render_agenda(&i18n, day).await))
+                .await?;
+        }
+        None => {
+            ctx.respond(Response::html(t("agenda_invalid_day"))).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the alert command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::types::{Chat, InputMessage, Message};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{alerts::Alerts, i18n::I18n},
+    output::{Respond, Response},
+    utils::{escape_html, message_link},
+    Recipient, Sender,
+};
+
+/// The maximum length of the message preview.
+const PREVIEW_LENGTH: usize = 200;
+
+/// Setup the alert command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("alert").and(filters::sudoers())).then(alert),
+    )
+}
+
+/// Setup the alert watcher.
+///
+/// It only matches the incoming messages with an alert keyword, and only the main
+/// account notifies, so a chat shared by several accounts does not alert twice.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::alert_match()).then(watch))
+}
+
+/// Handles the alert command.
+async fn alert(ctx: Context, i18n: I18n, alerts: Alerts) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1);
+
+    match args.next() {
+        Some("add") => {
+            let pattern = args.collect::<Vec<_>>().join(" ");
+
+            if pattern.is_empty() {
+                ctx.respond(t("alert_no_keyword")).await?;
+            } else if let Some(id) = alerts.add_keyword(pattern.clone()).await {
+                ctx.respond(Response::html(t_a(
+                    "alert_added",
+                    hashmap! { "id" => id.to_string(), "keyword" => escape_html(&pattern) },
+                )))
+                .await?;
+            } else {
+                ctx.respond(t("alert_invalid_regex")).await?;
+            }
+        }
+        Some("del") | Some("rm") => match args.next().and_then(|id| id.parse::<i32>().ok()) {
+            Some(id) if alerts.remove_keyword(id).await => {
+                ctx.respond(Response::html(t_a(
+                    "alert_removed",
+                    hashmap! { "id" => id.to_string() },
+                )))
+                .await?;
+            }
+            _ => {
+                ctx.respond(t("alert_not_found")).await?;
+            }
+        },
+        Some("mute") => {
+            let chat = ctx.chat().expect("Chat not found");
+
+            if alerts.mute(chat.id()).await {
+                ctx.respond(t("alert_chat_muted")).await?;
+            } else {
+                ctx.respond(t("alert_chat_already_muted")).await?;
+            }
+        }
+        Some("unmute") => {
+            let chat = ctx.chat().expect("Chat not found");
+
+            if alerts.unmute(chat.id()).await {
+                ctx.respond(t("alert_chat_unmuted")).await?;
+            } else {
+                ctx.respond(t("alert_chat_not_muted")).await?;
+            }
+        }
+        Some("list") | None => {
+            let keywords = alerts.keywords().await;
+
+            if keywords.is_empty() {
+                ctx.respond(t("alert_list_empty")).await?;
+            } else {
+                let mut text = t("alert_list");
+
+                for keyword in keywords {
+                    let pattern = if keyword.is_regex() {
+                        format!("/{}/", keyword.pattern())
+                    } else {
+                        keyword.pattern().to_string()
+                    };
+
+                    text += &format!(
+                        "\n<code>{0}</code>: <code>{1}</code>",
+                        keyword.id(),
+                        escape_html(&pattern)
+                    );
+                }
+
+                ctx.respond(Response::html(text)).await?;
+            }
+        }
+        Some(_) => {
+            ctx.respond(Response::html(t("alert_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Watches the incoming messages for the alert keywords.
+async fn watch(
+    message: Message,
+    i18n: I18n,
+    alerts: Alerts,
+    recipient: Recipient,
+    tx: Sender,
+) -> Result<()> {
+    if recipient != Recipient::MAIN_USER {
+        return Ok(());
+    }
+
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let chat = message.chat();
+    if let Some(keyword) = alerts.find_match(message.text()).await {
+        if !alerts.should_notify(chat.id()).await {
+            return Ok(());
+        }
+
+        let sender = message
+            .sender()
+            .map(|sender| sender.name().to_string())
+            .unwrap_or_else(|| chat.name().to_string());
+        let link = message_link(&chat, message.id()).unwrap_or_else(|| match chat {
+            Chat::User(_) => format!(
+                "tg://openmessage?user_id={0}&message_id={1}",
+                chat.id(),
+                message.id()
+            ),
+            _ => format!(
+                "tg://openmessage?chat_id={0}&message_id={1}",
+                chat.id(),
+                message.id()
+            ),
+        });
+        let preview = message
+            .text()
+            .chars()
+            .take(PREVIEW_LENGTH)
+            .collect::<String>();
+
+        tx.send(crate::Message::to_bot().notify(InputMessage::html(t_a(
+            "alert_notification",
+            hashmap! {
+                "keyword" => escape_html(keyword.pattern()),
+                "chat" => escape_html(chat.name()),
+                "sender" => escape_html(&sender),
+                "text" => escape_html(&preview),
+                "link" => link,
+            },
+        ))))
+        .await?;
+    }
+
+    Ok(())
+}
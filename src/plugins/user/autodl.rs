@@ -0,0 +1,373 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the auto-download command handler.
+
+use std::path::{Path, PathBuf};
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{
+    types::{Chat, Media},
+    Client,
+};
+use maplit::hashmap;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        auto_downloads::{AutoDownloads, Kind, Rule, Skip, Target},
+        i18n::I18n,
+        usage::{Counters, Usage},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html, human_readable_size},
+};
+
+/// The config of the auto-download plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "autodl"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // Where the media is saved by the rules without a path, in a directory
+        // for each chat.
+        vec![Key::new("dir", "./assets/autodl")]
+    }
+}
+
+/// Setup the auto-download command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("autodl").and(filters::sudoers())).then(autodl),
+    )
+}
+
+/// Setup the auto-download watcher.
+///
+/// It only matches the incoming messages with a file.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::incoming_media()).then(watch))
+}
+
+/// Handles the auto-download command.
+///
+/// Usage: `.autodl add <chat> [types] [path|@channel] [-m <size>]`,
+/// `.autodl del <chat>` or `.autodl list`, the chat being `here`, a
+/// `@username` or an ID.
+async fn autodl(
+    ctx: Context,
+    i18n: I18n,
+    settings: PluginSettings,
+    auto_downloads: AutoDownloads,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["add", chat, rest @ ..] => {
+            let Some((chat_id, chat_name)) = resolve_chat(&ctx, chat).await? else {
+                ctx.respond(t("autodl_chat_not_found")).await?;
+                return Ok(());
+            };
+
+            let mut kinds = None;
+            let mut target = None;
+            let mut max_size = None;
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match *arg {
+                    "-m" => match rest.next().and_then(|size| utils::parse_size(size)) {
+                        Some(size) => max_size = Some(size),
+                        None => {
+                            ctx.respond(Response::html(t("autodl_usage"))).await?;
+                            return Ok(());
+                        }
+                    },
+                    arg if kinds.is_none() && target.is_none() && parse_kinds(arg).is_some() => {
+                        kinds = parse_kinds(arg);
+                    }
+                    arg if target.is_none() => target = Some(arg),
+                    _ => {
+                        ctx.respond(Response::html(t("autodl_usage"))).await?;
+                        return Ok(());
+                    }
+                }
+            }
+
+            let target = match target {
+                Some(username) if username.starts_with('@') => {
+                    match ctx
+                        .client()
+                        .resolve_username(username.trim_start_matches('@'))
+                        .await?
+                    {
+                        Some(Chat::Channel(channel)) => {
+                            let packed = channel.pack();
+
+                            Target::Channel {
+                                id: packed.id,
+                                access_hash: packed.access_hash,
+                                title: channel.title().to_string(),
+                            }
+                        }
+                        _ => {
+                            ctx.respond(t("autodl_channel_not_found")).await?;
+                            return Ok(());
+                        }
+                    }
+                }
+                Some(path) => Target::Dir(path.to_string()),
+                None => Target::Dir(
+                    PathBuf::from(settings.get::<String>("autodl", "dir"))
+                        .join(chat_id.to_string())
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+            };
+
+            let rule = Rule {
+                chat_name,
+                kinds: kinds.unwrap_or_default(),
+                target,
+                max_size,
+            };
+            let description = describe(&i18n, &rule);
+            let key = if auto_downloads.add(chat_id, rule).await {
+                "autodl_replaced"
+            } else {
+                "autodl_added"
+            };
+
+            ctx.respond(Response::html(format!("{0}\n{1}", t(key), description)))
+                .await?;
+        }
+        ["del", chat] => {
+            let Some((chat_id, _)) = resolve_chat(&ctx, chat).await? else {
+                ctx.respond(t("autodl_chat_not_found")).await?;
+                return Ok(());
+            };
+
+            let key = if auto_downloads.remove(chat_id).await {
+                "autodl_removed"
+            } else {
+                "autodl_not_found"
+            };
+            ctx.respond(t(key)).await?;
+        }
+        ["list"] => {
+            let rules = auto_downloads.rules().await;
+            if rules.is_empty() {
+                ctx.respond(t("autodl_no_rules")).await?;
+                return Ok(());
+            }
+
+            let lines = rules
+                .iter()
+                .map(|(_, rule)| describe(&i18n, rule))
+                .collect::<Vec<_>>();
+            ctx.respond(Response::html(t_a(
+                "autodl_list",
+                hashmap! { "rules" => lines.join("\n") },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("autodl_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves the media of the chats with a rule.
+///
+/// The files already saved, from any chat, are skipped, as are the ones bigger
+/// than the rule allows. It is paused once the daily budget of the plugin is
+/// used up.
+async fn watch(ctx: Context, auto_downloads: AutoDownloads, usage: Usage) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+    let chat = message.chat();
+    let Some(rule) = auto_downloads.rule(chat.id()).await else {
+        return Ok(());
+    };
+
+    let Some(media) = message.media() else {
+        return Ok(());
+    };
+    if !Kind::of(&media).is_some_and(|kind| rule.matches(kind)) {
+        return Ok(());
+    }
+    let Some((file_name, size)) = utils::media_file(&media) else {
+        return Ok(());
+    };
+
+    if let (Some(max_size), Some(size)) = (rule.max_size, size) {
+        if size > max_size {
+            auto_downloads.skipped(Skip::TooBig).await;
+            return Ok(());
+        }
+    }
+    if usage.exceeded("autodl").await.is_some() {
+        auto_downloads.skipped(Skip::OverBudget).await;
+        return Ok(());
+    }
+
+    let Some(file_id) = file_id(&media) else {
+        return Ok(());
+    };
+    if !auto_downloads.claim(file_id).await {
+        auto_downloads.skipped(Skip::Duplicate).await;
+        return Ok(());
+    }
+
+    let client = ctx.client();
+    let result = match &rule.target {
+        Target::Dir(dir) => match save(client, &media, Path::new(dir), &file_name).await {
+            Ok((written, requests)) => {
+                usage
+                    .record("autodl", Counters::download(written, requests))
+                    .await;
+                Ok(written)
+            }
+            Err(e) => Err(e),
+        },
+        // The forwarded files are not downloaded, so they cost no traffic.
+        Target::Channel { .. } => client
+            .forward_messages(rule.target.channel().unwrap(), &[message.id()], &chat)
+            .await
+            .map(|_| size.unwrap_or(0))
+            .map_err(Into::into),
+    };
+
+    match result {
+        Ok(size) => auto_downloads.saved(chat.id(), size).await,
+        Err(e) => {
+            log::warn!(
+                "Failed to auto-download {0} from {1}: {2}",
+                file_name,
+                chat.id(),
+                e
+            );
+            auto_downloads.release(file_id).await;
+            auto_downloads.skipped(Skip::Failed).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the chat of a rule, as `here`, a `@username` or an ID.
+///
+/// Returns the ID of the chat and its name.
+async fn resolve_chat(ctx: &Context, target: &str) -> Result<Option<(i64, String)>> {
+    let chat = match target {
+        "here" => ctx.chat(),
+        username if username.starts_with('@') => {
+            ctx.client()
+                .resolve_username(username.trim_start_matches('@'))
+                .await?
+        }
+        id => return Ok(id.parse::<i64>().ok().map(|id| (id, id.to_string()))),
+    };
+
+    Ok(chat.map(|chat| (chat.id(), chat.name().to_string())))
+}
+
+/// Parses a list of kinds of media, such as `photo,video`, `all` being empty.
+fn parse_kinds(arg: &str) -> Option<Vec<Kind>> {
+    if arg == "all" {
+        return Some(Vec::new());
+    }
+
+    arg.split(',').map(Kind::parse).collect()
+}
+
+/// Describes a rule, what it saves and where.
+fn describe(i18n: &I18n, rule: &Rule) -> String {
+    let kinds = if rule.kinds.is_empty() {
+        i18n.translate("autodl_all_kinds")
+    } else {
+        rule.kinds
+            .iter()
+            .map(|kind| kind.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let target = match &rule.target {
+        Target::Dir(dir) => format!("<code>{}</code>", escape_html(dir)),
+        Target::Channel { title, .. } => format!("<b>{}</b>", escape_html(title)),
+    };
+    let max_size = match rule.max_size {
+        Some(size) => human_readable_size(size as usize),
+        None => i18n.translate("autodl_no_limit"),
+    };
+
+    i18n.translate_with_args(
+        "autodl_rule",
+        hashmap! {
+            "chat" => escape_html(&rule.chat_name),
+            "kinds" => kinds,
+            "target" => target,
+            "max_size" => max_size,
+        },
+    )
+}
+
+/// Downloads a media into a new file of a directory.
+///
+/// Returns how many bytes were written and how many requests it took.
+async fn save(client: &Client, media: &Media, dir: &Path, file_name: &str) -> Result<(u64, u64)> {
+    tokio::fs::create_dir_all(dir).await?;
+    let path = utils::unique_path(dir, &utils::sanitize_file_name(file_name));
+
+    let mut file = tokio::fs::File::create(&path).await?;
+    let mut download = client.iter_download(media);
+
+    let mut written = 0;
+    let mut requests = 0;
+    let result: Result<()> = async {
+        while let Some(chunk) = download.next().await? {
+            requests += 1;
+            written += chunk.len() as u64;
+
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        // Does not leave a partial file behind.
+        drop(file);
+        let _ = tokio::fs::remove_file(&path).await;
+
+        return Err(e);
+    }
+
+    Ok((written, requests))
+}
+
+/// Returns the ID of the file of a media, which does not change between messages.
+fn file_id(media: &Media) -> Option<i64> {
+    match media {
+        Media::Photo(photo) => Some(photo.id()),
+        Media::Document(document) => Some(document.id()),
+        Media::Sticker(sticker) => Some(sticker.document.id()),
+        _ => None,
+    }
+}
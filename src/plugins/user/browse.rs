@@ -0,0 +1,104 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the history browser command handler.
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{reply_markup, InputMessage};
+
+use crate::{
+    filters,
+    modules::{history::History, i18n::I18n},
+    output::Respond,
+    utils, Sender,
+};
+
+/// Setup the history browser command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("browse").and(filters::sudoers())).then(browse),
+    )
+}
+
+/// Handles the history browser command.
+///
+/// Usage: `.browse [@chat] [dd/mm[/yyyy]]`, the current chat from the latest
+/// messages by default.
+async fn browse(ctx: Context, i18n: I18n, history: History, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let text = ctx.command_text().unwrap();
+    let mut target = None;
+    let mut before = None;
+    for arg in text.split_whitespace().skip(1) {
+        if arg.contains('/') {
+            match parse_date(arg) {
+                Some(date) => before = Some(date),
+                None => {
+                    ctx.respond(t("browse_invalid_date")).await?;
+                    return Ok(());
+                }
+            }
+        } else {
+            target = Some(arg);
+        }
+    }
+
+    let chat = match target {
+        Some(username) => {
+            ctx.client()
+                .resolve_username(username.trim_start_matches('@'))
+                .await?
+        }
+        None => ctx.chat(),
+    };
+    let Some(chat) = chat else {
+        ctx.respond(t("browse_chat_not_found")).await?;
+        return Ok(());
+    };
+
+    let id = history.open(chat, before).await;
+    let Some((text, buttons)) = history.render(ctx.client(), id, 0, &i18n).await? else {
+        return Ok(());
+    };
+
+    if !utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .in_topic(utils::current_topic(&ctx).await)
+            .send_via_bot_message(
+                ctx.chat().expect("Chat not found"),
+                InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)),
+            ),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+    ctx.delete().await?;
+
+    Ok(())
+}
+
+/// Parses a date such as `25/12` or `25/12/2024`, returning the timestamp of the
+/// end of that day, in local time.
+fn parse_date(text: &str) -> Option<i32> {
+    let date = NaiveDate::parse_from_str(text, "%d/%m/%Y")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(&format!("{}/{}", text, Local::now().year()), "%d/%m/%Y")
+        })
+        .ok()?;
+    let end = Local
+        .from_local_datetime(&date.succ_opt()?.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+
+    i32::try_from(end.timestamp()).ok()
+}
@@ -0,0 +1,93 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the calc command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        calculator::{self, CalcError},
+        i18n::I18n,
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the calc command.
+///
+/// Editing the expression evaluates it again, so a typo can be fixed.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filters::command("calc")
+                    .and(filters::sudoers())
+                    .and(filters::respond_to_edits("calc")),
+            )
+            .then(calc),
+        )
+        .handler(
+            handler::message_edited(
+                filters::command("calc")
+                    .and(filters::sudoers())
+                    .and(filters::respond_to_edits("calc")),
+            )
+            .then(calc),
+        )
+}
+
+/// Handles the calc command.
+///
+/// Usage: `.calc <expression>`, or replying to a message with the expression.
+async fn calc(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut expression = text
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, args)| args)
+        .trim()
+        .to_string();
+    if expression.is_empty() {
+        if let Some(reply) = ctx.get_reply().await? {
+            expression = reply.text().trim().to_string();
+        }
+    }
+
+    if expression.is_empty() {
+        ctx.respond(Response::html(t("calc_usage"))).await?;
+        return Ok(());
+    }
+
+    let response = match calculator::evaluate(&expression) {
+        Ok(result) => t_a(
+            "calc_result",
+            hashmap! {
+                "expression" => escape_html(&expression),
+                "result" => calculator::format(result),
+            },
+        ),
+        Err(CalcError::Invalid(token)) if token.is_empty() => t("calc_invalid_end"),
+        Err(CalcError::Invalid(token)) => {
+            t_a("calc_invalid", hashmap! { "token" => escape_html(&token) })
+        }
+        Err(CalcError::UnknownFunction(function)) => t_a(
+            "calc_unknown_function",
+            hashmap! { "function" => escape_html(&function) },
+        ),
+        Err(CalcError::TooDeep) => t("calc_too_deep"),
+        Err(CalcError::Undefined) => t("calc_undefined"),
+    };
+    ctx.respond(Response::html(response)).await?;
+
+    Ok(())
+}
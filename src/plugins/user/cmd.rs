@@ -0,0 +1,183 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the command registry handler.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{commands::Commands, i18n::I18n},
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the command registry command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("cmd").and(filter::me)).then(cmd))
+}
+
+/// Handles the command registry command.
+///
+/// Usage: `.cmd [list]`, `.cmd disable <name>`, `.cmd enable <name>`,
+/// `.cmd rename <name> <new name>`, `.cmd reset <name>` or
+/// `.cmd prefixes [prefix...]`, the name being either the current one or the
+/// one the command was written with.
+async fn cmd(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let commands = Commands::shared();
+    let text = ctx.command_text().unwrap_or_default();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let command = match args.as_slice() {
+        ["disable" | "enable" | "rename" | "reset", name, ..] => match commands.resolve(name) {
+            Some(command) => Some(command),
+            None => {
+                ctx.respond(Response::html(t_a(
+                    "cmd_not_found",
+                    hashmap! { "name" => escape_html(name) },
+                )))
+                .await?;
+                return Ok(());
+            }
+        },
+        _ => None,
+    };
+
+    match (args.as_slice(), command) {
+        ([] | ["list"], _) => {
+            let entries = commands.list();
+            let changed = entries
+                .iter()
+                .filter_map(|entry| {
+                    let key = match (&entry.renamed, entry.enabled) {
+                        (None, true) => return None,
+                        (Some(_), true) => "cmd_entry_renamed",
+                        (_, false) => "cmd_entry_disabled",
+                    };
+
+                    let new_name = entry.renamed.as_deref().unwrap_or_default();
+
+                    Some(t_a(
+                        key,
+                        hashmap! {
+                            "name" => entry.name.to_string(),
+                            "new_name" => escape_html(new_name),
+                        },
+                    ))
+                })
+                .collect::<Vec<_>>();
+            let changed = if changed.is_empty() {
+                t("cmd_none_changed")
+            } else {
+                changed.join("\n")
+            };
+
+            ctx.respond(Response::html(t_a(
+                "cmd_list",
+                hashmap! {
+                    "count" => entries.len().to_string(),
+                    "prefixes" => format_prefixes(&commands.prefixes()),
+                    "commands" => changed,
+                },
+            )))
+            .await?;
+        }
+        (["disable" | "enable", _], Some(command)) => {
+            let enabled = args[0] == "enable";
+            let key = if commands.set_enabled(command, enabled).await {
+                if enabled {
+                    "cmd_enabled"
+                } else {
+                    "cmd_disabled"
+                }
+            } else if !enabled && commands.is_protected(command) {
+                "cmd_protected"
+            } else if enabled {
+                "cmd_already_enabled"
+            } else {
+                "cmd_already_disabled"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "name" => command.to_string() },
+            )))
+            .await?;
+        }
+        (["rename", _, new_name], Some(command)) => {
+            let new_name = new_name.to_lowercase();
+            if !new_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                ctx.respond(Response::html(t_a(
+                    "cmd_invalid_name",
+                    hashmap! { "name" => escape_html(&new_name) },
+                )))
+                .await?;
+                return Ok(());
+            }
+
+            let key = if commands.rename(command, &new_name).await {
+                "cmd_renamed"
+            } else {
+                "cmd_name_taken"
+            };
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "name" => command.to_string(), "new_name" => new_name },
+            )))
+            .await?;
+        }
+        (["reset", _], Some(command)) => {
+            let key = if commands.reset(command).await {
+                "cmd_reset"
+            } else {
+                "cmd_not_changed"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "name" => command.to_string() },
+            )))
+            .await?;
+        }
+        (["prefixes", prefixes @ ..], _) => {
+            let prefixes = prefixes
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect::<Vec<_>>();
+            commands.set_prefixes(prefixes).await;
+
+            ctx.respond(Response::html(t_a(
+                "cmd_prefixes_set",
+                hashmap! { "prefixes" => format_prefixes(&commands.prefixes()) },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("cmd_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats the prefixes, each as code.
+fn format_prefixes(prefixes: &[String]) -> String {
+    prefixes
+        .iter()
+        .map(|prefix| format!("<code>{}</code>", escape_html(prefix)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,120 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the config command handler.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    config::{LiveConfig, PluginSettings},
+    filters,
+    modules::i18n::I18n,
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the config commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("config").and(filter::me)).then(config))
+        .handler(
+            handler::new_message(filters::command("reloadconfig").and(filters::sudoers()))
+                .then(reload_config),
+        )
+}
+
+/// Handles the config command, displaying the effective config of a plugin.
+async fn config(ctx: Context, i18n: I18n, settings: PluginSettings) -> Result<()> {
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let Some(plugin) = text.split_whitespace().nth(1) else {
+        let plugins = settings
+            .plugins()
+            .iter()
+            .map(|name| format!("<code>{}</code>", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        ctx.respond(Response::html(t_a(
+            "config_usage",
+            hashmap! { "plugins" => plugins },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let Some(values) = settings.display(plugin) else {
+        ctx.respond(Response::html(t_a(
+            "config_plugin_not_found",
+            hashmap! { "plugin" => escape_html(plugin) },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let values = values
+        .iter()
+        .map(|(key, value)| format!("<b>{0}</b> = <code>{1}</code>", key, escape_html(value)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.respond(Response::html(t_a(
+        "config_values",
+        hashmap! { "plugin" => escape_html(plugin), "values" => values },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the reload config command, loading the config file again.
+async fn reload_config(ctx: Context, i18n: I18n, live_config: LiveConfig) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let reload = match live_config.reload() {
+        Ok(reload) => reload,
+        Err(e) => {
+            // The error can quote the file, secrets included, so it is only logged.
+            log::error!("Failed to reload the config: {}", e);
+            ctx.respond(t("config_reload_failed")).await?;
+            return Ok(());
+        }
+    };
+
+    if reload.is_empty() {
+        ctx.respond(t("config_reload_unchanged")).await?;
+        return Ok(());
+    }
+
+    let keys = |keys: &[String]| {
+        keys.iter()
+            .map(|key| format!("<code>{}</code>", key))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut lines = vec![t("config_reloaded")];
+    if !reload.applied.is_empty() {
+        lines.push(t_a(
+            "config_reload_applied",
+            hashmap! { "keys" => keys(&reload.applied) },
+        ));
+    }
+    if !reload.pending.is_empty() {
+        lines.push(t_a(
+            "config_reload_pending",
+            hashmap! { "keys" => keys(&reload.pending) },
+        ));
+    }
+
+    ctx.respond(Response::html(lines.join("\n\n"))).await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the connect four command handler.
+
+use ferogram::{handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::InputMessage};
+
+use crate::{
+    filters,
+    modules::{
+        games::{ConnectFour, GameManager, Player},
+        i18n::I18n,
+    },
+    utils::{self, board_to_buttons, BoardLayout},
+    Priority, Sender,
+};
+
+/// Setup the connect four command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["c4", "connect_four"])).then(connect_four),
+    )
+}
+
+/// Handles the connect four command.
+async fn connect_four(ctx: Context, i18n: I18n, manager: GameManager, tx: Sender) -> Result<()> {
+    let sender = ctx.sender().expect("Sender not found");
+    let mut players = vec![Player::new(&sender)];
+
+    if let Some(reply) = ctx.get_reply().await? {
+        players.push(Player::new(
+            &reply.sender().expect("Reply sender not found"),
+        ));
+    }
+
+    let game = ConnectFour::new(manager.new_id().await, players).into_game();
+
+    let buttons = board_to_buttons(game.board(), "c4", game.id(), BoardLayout::Columns);
+    if !utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .with_priority(Priority::High)
+            .in_topic(utils::current_topic(&ctx).await)
+            .send_via_bot_message(
+                ctx.chat().expect("Chat not found"),
+                InputMessage::html(game.generate_text())
+                    .reply_markup(&reply_markup::inline(buttons)),
+            ),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+
+    manager.add_game(game, None).await;
+
+    Ok(())
+}
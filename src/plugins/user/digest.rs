@@ -0,0 +1,135 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the digest command handler.
+
+use chrono::NaiveTime;
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        digest::Digest,
+        i18n::I18n,
+        templates::{self, Variables},
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the digest command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("digest").and(filters::sudoers())).then(digest),
+    )
+}
+
+/// Handles the digest command.
+async fn digest(ctx: Context, i18n: I18n, digest: Digest) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1);
+
+    match (args.next(), args.next()) {
+        (Some("header"), Some(_)) => {
+            // The template keeps the spaces and line breaks as typed.
+            let header = text
+                .splitn(3, char::is_whitespace)
+                .nth(2)
+                .map(|header| escape_html(header.trim()))
+                .unwrap_or_default();
+            if let Err(e) = templates::render(&header, &Variables::new()) {
+                ctx.respond(Response::html(t_a(
+                    "template_invalid",
+                    hashmap! { "error" => escape_html(&e) },
+                )))
+                .await?;
+                return Ok(());
+            }
+
+            digest.set_header(Some(header)).await;
+            ctx.respond(t("digest_header_set")).await?;
+        }
+        (Some("header"), None) => {
+            digest.set_header(None).await;
+            ctx.respond(t("digest_header_reset")).await?;
+        }
+        (Some("add"), Some(name)) => {
+            let key = if digest.enable(name).await {
+                "digest_widget_enabled"
+            } else {
+                "digest_widget_invalid"
+            };
+
+            ctx.respond(Response::html(t_a(key, hashmap! { "widget" => name })))
+                .await?;
+        }
+        (Some("del"), Some(name)) => {
+            let key = if digest.disable(name).await {
+                "digest_widget_disabled"
+            } else {
+                "digest_widget_invalid"
+            };
+
+            ctx.respond(Response::html(t_a(key, hashmap! { "widget" => name })))
+                .await?;
+        }
+        (Some("time"), Some(time)) => match NaiveTime::parse_from_str(time, "%H:%M") {
+            Ok(time) => {
+                digest.set_time(time).await;
+
+                ctx.respond(Response::html(t_a(
+                    "digest_time_set",
+                    hashmap! { "time" => time.format("%H:%M").to_string() },
+                )))
+                .await?;
+            }
+            Err(_) => {
+                ctx.respond(t("digest_invalid_time")).await?;
+            }
+        },
+        (Some("now"), None) => {
+            let msg = ctx.edit_or_reply(t("digest_rendering")).await?;
+
+            ctx.respond_in(&msg, Response::html(digest.render(&i18n).await))
+                .await?;
+        }
+        (Some("list"), None) | (None, None) => {
+            let enabled = digest.enabled().await;
+            let available = digest
+                .available()
+                .into_iter()
+                .map(|name| {
+                    if enabled.iter().any(|w| w == name) {
+                        format!("✅ <code>{}</code>", name)
+                    } else {
+                        format!("▫️ <code>{}</code>", name)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            ctx.respond(Response::html(t_a(
+                "digest_status",
+                hashmap! {
+                    "time" => digest.time().await.format("%H:%M").to_string(),
+                    "widgets" => available,
+                },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("digest_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
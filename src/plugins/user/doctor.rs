@@ -0,0 +1,36 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the self-test command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+
+use crate::{
+    filters,
+    modules::{doctor::Doctor, i18n::I18n},
+    output::{Respond, Response},
+    Sender,
+};
+
+/// Setup the doctor command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("doctor").and(filters::sudoers())).then(doctor),
+    )
+}
+
+/// Handles the doctor command, reporting which checks passed.
+async fn doctor(ctx: Context, i18n: I18n, doctor: Doctor, tx: Sender) -> Result<()> {
+    let msg = ctx.edit_or_reply(i18n.translate("doctor_running")).await?;
+
+    let checks = doctor.run(&tx).await;
+    ctx.respond_in(&msg, Response::html(doctor.render(&checks, &i18n)))
+        .await?;
+
+    Ok(())
+}
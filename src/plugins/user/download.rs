@@ -0,0 +1,189 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the media download command handler.
+
+use std::time::Duration;
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{types::Attribute, InputMessage};
+use maplit::hashmap;
+use tokio::sync::watch;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        usage::{Counters, Usage},
+        virustotal::{with_verdict, VirusTotal},
+        ytdl::{Format, Progress, Ytdl},
+    },
+    output::Respond,
+    utils::{self, escape_html},
+};
+
+/// How often the progress is shown.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Setup the media download command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["dl", "download"]).and(filters::sudoers()))
+            .then(download),
+    )
+}
+
+/// Handles the media download command.
+///
+/// `-a` downloads only the audio and `-q <height>` limits the video quality. The media
+/// is scanned on VirusTotal before being sent, if enabled. It is paused once the daily
+/// budget of the `ytdl` plugin is used up.
+async fn download(
+    ctx: Context,
+    i18n: I18n,
+    usage: Usage,
+    ytdl: Ytdl,
+    virustotal: VirusTotal,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap_or_default();
+    let mut args = text.split_whitespace().skip(1);
+
+    let mut audio = false;
+    let mut height = None;
+    let mut url = None;
+    while let Some(arg) = args.next() {
+        match arg {
+            "-a" => audio = true,
+            "-q" => {
+                height = args
+                    .next()
+                    .and_then(|h| h.trim_end_matches('p').parse().ok())
+            }
+            _ => url = Some(arg.to_string()),
+        }
+    }
+
+    if url.is_none() {
+        if let Some(reply) = ctx.get_reply().await? {
+            url = reply
+                .text()
+                .split_whitespace()
+                .find(|word| word.starts_with("http://") || word.starts_with("https://"))
+                .map(str::to_string);
+        }
+    }
+
+    let Some(url) = url else {
+        ctx.respond(t("dl_no_url")).await?;
+        return Ok(());
+    };
+    let format = if audio {
+        Format::Audio
+    } else {
+        Format::Video(height)
+    };
+
+    if utils::over_budget(&ctx, &usage, &i18n, "ytdl").await? {
+        return Ok(());
+    }
+
+    let msg = ctx.edit_or_reply(t("dl_starting")).await?;
+
+    let (tx, rx) = watch::channel(None::<Progress>);
+    let download = ytdl.download(&url, format, move |progress| {
+        tx.send_replace(Some(progress));
+    });
+    tokio::pin!(download);
+
+    let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+    let result = loop {
+        tokio::select! {
+            result = &mut download => break result,
+            _ = interval.tick() => {
+                let progress = rx.borrow().clone();
+                if let Some(progress) = progress {
+                    // A failed edit must not stop the download.
+                    let _ = msg
+                        .edit(t_a(
+                            "dl_progress",
+                            hashmap! {
+                                "percent" => progress.percent,
+                                "speed" => progress.speed,
+                                "eta" => progress.eta,
+                            },
+                        ))
+                        .await;
+                }
+            }
+        }
+    };
+
+    let download = match result {
+        Ok(download) => download,
+        Err(e) => {
+            log::error!("Failed to download the media: {}", e);
+            ctx.respond_in(&msg, t("dl_error")).await?;
+
+            return Ok(());
+        }
+    };
+    let size = tokio::fs::metadata(&download.path).await?.len();
+    usage.record("ytdl", Counters::fetch(size)).await;
+
+    if virustotal.is_enabled() {
+        msg.edit(t("virustotal_scanning")).await?;
+    }
+    let verdict = virustotal.verdict(&download.path, &i18n).await;
+
+    msg.edit(t("dl_uploading")).await?;
+
+    let client = ctx.client();
+    let file = client.upload_file(&download.path).await?;
+    let duration = Duration::from_secs_f64(download.duration.max(0.0));
+
+    let caption = t_a(
+        "dl_caption",
+        hashmap! { "title" => escape_html(&download.title), "url" => url.clone() },
+    );
+    let mut input = InputMessage::html(with_verdict(caption, verdict))
+        .reply_to(utils::current_topic(&ctx).await)
+        .document(file)
+        .attribute(match format {
+            Format::Audio => Attribute::Audio {
+                duration,
+                title: Some(download.title.clone()),
+                performer: None,
+            },
+            Format::Video(_) => Attribute::Video {
+                round_message: false,
+                supports_streaming: true,
+                duration,
+                w: download.width,
+                h: download.height,
+            },
+        });
+    let mut uploaded = size;
+    if let Some(thumbnail) = &download.thumbnail {
+        input = input.thumbnail(client.upload_file(thumbnail).await?);
+        uploaded += tokio::fs::metadata(thumbnail).await?.len();
+    }
+
+    ctx.send(input).await?;
+    msg.delete().await?;
+    usage.record("ytdl", Counters::upload(uploaded)).await;
+
+    tokio::fs::remove_file(&download.path).await?;
+    if let Some(thumbnail) = &download.thumbnail {
+        tokio::fs::remove_file(thumbnail).await?;
+    }
+
+    Ok(())
+}
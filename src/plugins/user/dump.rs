@@ -8,12 +8,14 @@
 
 //! This module contains the dump command handler.
 
-use std::io::Cursor;
-
 use ferogram::{handler, Context, Filter, Result, Router};
-use grammers_client::InputMessage;
 
-use crate::{filters, Dump};
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    output::{Output, Respond},
+    utils, Dump,
+};
 
 /// Setup the dump command.
 pub fn setup() -> Router {
@@ -23,56 +25,23 @@ pub fn setup() -> Router {
 }
 
 /// Handles the dump command.
-async fn dump(ctx: Context) -> Result<()> {
-    if let Some(reply) = ctx.get_reply().await? {
-        let json = reply.dump();
-
-        match ctx
-            .edit_or_reply(InputMessage::html(format!(
-                "<blockquote>{}</blockquote>",
-                json
-            )))
-            .await
-        {
-            Err(e) if e.is("MESSAGE_TOO_LONG") => {
-                let bytes = json.as_bytes();
-                let size = bytes.len();
-
-                let mut stream = Cursor::new(bytes);
-                let file = ctx
-                    .upload_stream(&mut stream, size, "reply_dump.json".to_string())
-                    .await?;
-
-                ctx.send(InputMessage::text("").document(file)).await?;
-            }
-            _ => {}
-        }
-    }
-
-    let msg = ctx.message().await.unwrap();
-    let json = msg.dump();
-
-    match ctx
-        .edit_or_reply(InputMessage::html(format!(
-            "<blockquote>{}</blockquote>",
-            json
-        )))
-        .await
-    {
-        Err(e) if e.is("MESSAGE_TOO_LONG") => {
-            let bytes = json.as_bytes();
-            let size = bytes.len();
-
-            let mut stream = Cursor::new(bytes);
-            let file = ctx
-                .upload_stream(&mut stream, size, "dump.json".to_string())
-                .await?;
-
-            ctx.send(InputMessage::text("").document(file)).await?;
-            ctx.delete().await?;
-        }
-        _ => {}
+///
+/// Usage: `.dump [link]`, dumping the linked or replied message, or else the
+/// command itself.
+async fn dump(ctx: Context, i18n: I18n) -> Result<()> {
+    let (json, file_name) = match utils::target_message(&ctx).await? {
+        Some(reply) => (reply.dump(), "reply_dump.json"),
+        None => (ctx.message().await.unwrap().dump(), "dump.json"),
+    };
+
+    Output {
+        html: format!("<blockquote>{}</blockquote>", json),
+        caption: i18n.translate("dump_output_file"),
+        content: json,
+        file_name: file_name.to_string(),
     }
+    .send(&ctx, None, ctx.wants_file())
+    .await?;
 
     Ok(())
 }
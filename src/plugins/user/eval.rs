@@ -8,102 +8,307 @@
 
 //! This module contains the eval command handler.
 
-use std::{
-    io::{Cursor, Read},
-    process::{Command, Stdio},
-    time::Instant,
-};
+use std::{sync::LazyLock, time::Duration};
 
 use ferogram::{handler, Context, Filter, Result, Router};
 use grammers_client::InputMessage;
 use maplit::hashmap;
+use tokio::sync::watch;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        commands::Commands,
+        i18n::I18n,
+        sandbox::{Exit, Job, Sandbox},
+    },
+    output::{Output, Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The flag that chooses the language, right after the command.
+const LANG_FLAG: &str = "--lang";
 
-use crate::{filters, modules::i18n::I18n};
+/// The most bytes of the partial output shown while the code runs, its end.
+const PREVIEW_LENGTH: usize = 1500;
+
+/// The config of the eval plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "eval"
+    }
 
-/// Setup the eval command.
+    fn keys(&self) -> Vec<Key> {
+        vec![
+            // Whether the status message shows the output while the code runs.
+            Key::new("live_output", true),
+            // In seconds, how often the status message is updated.
+            Key::new("update_interval", 3).validate(|value| match value.as_integer() {
+                Some(2..=60) => Ok(()),
+                _ => Err("must be between 2 and 60".to_string()),
+            }),
+        ]
+    }
+}
+
+/// A language the code can be evaluated in.
+struct Backend {
+    /// The name of the language.
+    name: &'static str,
+    /// The commands that evaluate in it.
+    commands: &'static [&'static str],
+    /// The names it is chosen by with `--lang`.
+    aliases: &'static [&'static str],
+    /// Builds the job running the code.
+    job: fn(&str) -> Job,
+}
+
+/// The languages, the first being the default one.
+const BACKENDS: &[Backend] = &[
+    Backend {
+        name: "rust",
+        commands: &["e", "eval", "exec"],
+        aliases: &["rust", "rs"],
+        job: |code| {
+            Job::new("rust-script")
+                .args(["-e", code])
+                .env("RUST_LOG", "off")
+        },
+    },
+    Backend {
+        name: "python",
+        commands: &["py", "python"],
+        aliases: &["python", "py"],
+        job: |code| {
+            Job::new("python3")
+                .arg("main.py")
+                .file("main.py", code)
+                .env("PYTHONDONTWRITEBYTECODE", "1")
+        },
+    },
+    Backend {
+        name: "shell",
+        commands: &["bash"],
+        aliases: &["shell", "sh", "bash"],
+        job: |code| Job::new("bash").arg("main.sh").file("main.sh", code),
+    },
+    Backend {
+        name: "javascript",
+        commands: &["js", "node"],
+        aliases: &["javascript", "js", "node"],
+        job: |code| Job::new("node").arg("main.js").file("main.js", code),
+    },
+    Backend {
+        name: "go",
+        commands: &["go"],
+        aliases: &["go", "golang"],
+        job: |code| {
+            // A snippet is run as the body of the main function.
+            let source = if code.trim_start().starts_with("package ") {
+                code.to_string()
+            } else {
+                format!("package main\n\nfunc main() {{\n{}\n}}\n", code)
+            };
+
+            Job::new("go")
+                .args(["run", "main.go"])
+                .file("main.go", source)
+        },
+    },
+];
+
+impl Backend {
+    /// Finds the backend of a command, the default one if none.
+    fn of_command(command: &str) -> &'static Self {
+        BACKENDS
+            .iter()
+            .find(|backend| backend.commands.contains(&command))
+            .unwrap_or(&BACKENDS[0])
+    }
+
+    /// Finds a backend by one of its names.
+    fn find(name: &str) -> Option<&'static Self> {
+        let name = name.to_lowercase();
+        BACKENDS
+            .iter()
+            .find(|backend| backend.aliases.contains(&name.as_str()))
+    }
+}
+
+/// The commands of every backend.
+static COMMANDS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    BACKENDS
+        .iter()
+        .flat_map(|backend| backend.commands.iter().copied())
+        .collect()
+});
+
+/// Setup the eval commands.
 pub fn setup() -> Router {
     Router::default().handler(
-        handler::new_message(filters::commands(&["e", "eval", "exec"]).and(filters::sudoers()))
+        handler::new_message(filters::commands(COMMANDS.as_slice()).and(filters::sudoers()))
             .then(eval),
     )
 }
 
-/// Handles the eval command.
-async fn eval(ctx: Context, i18n: I18n) -> Result<()> {
+/// Handles the eval commands.
+///
+/// Usage: `.eval [--lang <language>] <code>`, or the command of a language such as
+/// `.py <code>`. The code runs in the sandbox, killed if it takes too long, the
+/// status message showing its output meanwhile.
+async fn eval(ctx: Context, i18n: I18n, sandbox: Sandbox, settings: PluginSettings) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
 
-    if let Some(text) = ctx.text() {
-        let input = text
-            .trim()
-            .split_whitespace()
-            .skip(1)
-            .collect::<Vec<_>>()
-            .join(" ");
-
-        let msg = ctx
-            .edit_or_reply(InputMessage::html(t_a(
-                "evaluating",
-                hashmap! { "input" => input.clone() },
-            )))
-            .await?;
-        let time = Instant::now();
-
-        if let Ok(mut child) = Command::new("rust-script")
-            .args(["-e", &input])
-            .env("RUST_LOG", "off")
-            .stderr(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-        {
-            if let Ok(status) = child.wait() {
-                let elapsed = time.elapsed().as_secs_f64();
-
-                let mut buf = String::new();
-
-                if status.success() {
-                    let mut stdout = child.stdout.take().unwrap();
-                    stdout.read_to_string(&mut buf)?;
-                } else {
-                    let mut stderr = child.stderr.take().unwrap();
-                    stderr.read_to_string(&mut buf)?;
-                }
+    let Some(text) = ctx.command_text() else {
+        ctx.reply(t("eval_no_code")).await?;
+        return Ok(());
+    };
 
-                let output = buf.trim_ascii().to_string();
-                if output.len() > 4000 {
-                    let bytes = output.as_bytes();
-                    let size = bytes.len();
-
-                    let mut cursor = Cursor::new(bytes);
-                    let file = ctx
-                        .upload_stream(&mut cursor, size, "output.txt".to_string())
-                        .await?;
-
-                    msg.edit(InputMessage::html(t_a(
-                        "eval_input",
-                        hashmap! { "input" => input, "time" => elapsed.to_string() },
-                    )))
-                    .await?;
-                    ctx.reply(InputMessage::html(t("eval_output_file")).document(file))
-                        .await?;
-
-                    return Ok(());
-                }
+    let to_file = ctx.wants_file();
+    let text = text.trim();
+    let (command, code) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let mut backend = Backend::of_command(Commands::shared().canonical(command).unwrap_or(command));
+    let mut input = code.trim();
 
-                msg.edit(InputMessage::html(t_a(
-                "eval_output",
-                hashmap! { "input" => input, "output" => output, "time" => elapsed.to_string() },
+    if let Some(rest) = input.strip_prefix(LANG_FLAG) {
+        let rest = rest.trim_start();
+        let (name, code) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+        match Backend::find(name) {
+            Some(found) => backend = found,
+            None => {
+                let languages = BACKENDS
+                    .iter()
+                    .map(|backend| format!("<code>{}</code>", backend.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ctx.respond(Response::html(t_a(
+                    "eval_unknown_lang",
+                    hashmap! { "lang" => escape_html(name), "langs" => languages },
                 )))
                 .await?;
-            } else {
-                ctx.reply(t("eval_failure")).await?;
+                return Ok(());
+            }
+        }
+        input = code.trim();
+    }
+
+    if input.is_empty() {
+        ctx.reply(t("eval_no_code")).await?;
+        return Ok(());
+    }
+
+    let msg = ctx
+        .edit_or_reply(InputMessage::html(t_a(
+            "evaluating",
+            hashmap! { "input" => escape_html(input) },
+        )))
+        .await?;
+
+    let live_output = settings.get::<bool>("eval", "live_output");
+    let (tx, mut rx) = watch::channel(String::new());
+    let run = sandbox.run((backend.job)(input), move |chunk| {
+        if live_output {
+            tx.send_modify(|output| utils::push_tail(output, chunk, PREVIEW_LENGTH));
+        }
+    });
+    tokio::pin!(run);
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(settings.get("eval", "update_interval")));
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            _ = interval.tick() => {
+                if rx.has_changed().unwrap_or(false) {
+                    let output = rx.borrow_and_update().clone();
+                    // A failed edit must not stop the code.
+                    let _ = msg
+                        .edit(InputMessage::html(t_a(
+                            "eval_partial",
+                            hashmap! {
+                                "input" => escape_html(input),
+                                "output" => escape_html(output.trim_ascii()),
+                            },
+                        )))
+                        .await;
+                }
             }
-        } else {
+        }
+    };
+
+    let run = match result {
+        Ok(run) => run,
+        Err(e) => {
+            log::error!("Failed to run the evaluated {} code: {}", backend.name, e);
             ctx.reply(t("eval_failure")).await?;
+            return Ok(());
         }
+    };
+    let elapsed = run.elapsed.as_secs_f64();
+
+    let output = if run.success() {
+        run.stdout.trim_ascii().to_string()
     } else {
-        ctx.reply(t("eval_no_code")).await?;
+        [run.stdout.trim_ascii(), run.stderr.trim_ascii()]
+            .into_iter()
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut notes = Vec::new();
+    match run.exit {
+        Exit::Code(0) => {}
+        Exit::Code(code) => notes.push(t_a(
+            "eval_exit_code",
+            hashmap! { "code" => code.to_string() },
+        )),
+        Exit::Signal => notes.push(t("eval_signal")),
+        Exit::TimedOut => notes.push(t_a(
+            "eval_timeout",
+            hashmap! { "timeout" => sandbox.timeout().as_secs().to_string() },
+        )),
+    }
+    if run.truncated {
+        notes.push(t("eval_truncated"));
+    }
+    let notes = notes
+        .into_iter()
+        .map(|note| format!("\n\n{}", note))
+        .collect::<String>();
+
+    Output {
+        html: format!(
+            "{0}{1}",
+            t_a(
+                "eval_output",
+                hashmap! {
+                    "input" => escape_html(input),
+                    "output" => escape_html(&output),
+                    "time" => elapsed.to_string(),
+                },
+            ),
+            notes
+        ),
+        caption: format!(
+            "{0}\n\n{1}{2}",
+            t_a(
+                "eval_input",
+                hashmap! { "input" => escape_html(input), "time" => elapsed.to_string() },
+            ),
+            t("eval_output_file"),
+            notes
+        ),
+        content: output,
+        file_name: "output.txt".to_string(),
     }
+    .send(&ctx, Some(&msg), to_file)
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,62 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the message fetcher command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::InputMessage;
+
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    output::{Respond, Response},
+    utils,
+};
+
+/// Setup the message fetcher command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("fetch").and(filters::sudoers())).then(fetch),
+    )
+}
+
+/// Handles the message fetcher command.
+///
+/// Usage: `.fetch <link>`. The linked message is sent again in the current chat,
+/// with its formatting and media, replacing the command.
+async fn fetch(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let text = ctx.command_text().unwrap();
+    let Some(link) = text
+        .split_whitespace()
+        .nth(1)
+        .and_then(utils::parse_message_link)
+    else {
+        ctx.respond(Response::html(t("fetch_usage"))).await?;
+        return Ok(());
+    };
+
+    let Some(message) = utils::fetch_linked_message(ctx.client(), &link).await? else {
+        ctx.respond(t("fetch_not_found")).await?;
+        return Ok(());
+    };
+
+    let mut input = InputMessage::text(message.text())
+        .fmt_entities(message.fmt_entities().cloned().unwrap_or_default())
+        .reply_to(utils::current_topic(&ctx).await);
+    if let Some(media) = message.media() {
+        input = input.copy_media(&media);
+    }
+
+    // The command is replaced by the message.
+    ctx.delete().await?;
+    ctx.send(input).await?;
+
+    Ok(())
+}
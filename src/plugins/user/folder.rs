@@ -0,0 +1,193 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the folder and archive commands handlers.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::types::Chat;
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        folders::{Folders, Rule, RuleKind},
+        i18n::I18n,
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the folder and archive commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(filters::command("folder").and(filters::sudoers())).then(folder),
+        )
+        .handler(
+            handler::new_message(filters::command("archive").and(filters::sudoers())).then(archive),
+        )
+}
+
+/// Resolves a `@username`, defaulting to the current chat.
+async fn resolve_chat(ctx: &Context, target: Option<&str>) -> Result<Option<Chat>> {
+    match target {
+        Some(username) => Ok(ctx
+            .client()
+            .resolve_username(username.trim_start_matches('@'))
+            .await?),
+        None => Ok(ctx.chat()),
+    }
+}
+
+/// Handles the folder command.
+async fn folder(ctx: Context, i18n: I18n, folders: Folders) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let client = ctx.client();
+
+    match args.as_slice() {
+        ["create", name, target @ ..] | ["add", name, target @ ..] if target.len() <= 1 => {
+            let Some(chat) = resolve_chat(&ctx, target.first().copied()).await? else {
+                ctx.respond(t("folder_chat_not_found")).await?;
+                return Ok(());
+            };
+
+            let key = if args[0] == "create" {
+                if folders.create(client, name, &chat).await? {
+                    "folder_created"
+                } else {
+                    "folder_already_exists"
+                }
+            } else {
+                match folders.add(client, name, &[chat.clone()]).await? {
+                    Some(0) => "folder_chat_already_added",
+                    Some(_) => "folder_chat_added",
+                    None => "folder_not_found",
+                }
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! {
+                    "folder" => escape_html(name),
+                    "chat" => escape_html(chat.name()),
+                },
+            )))
+            .await?;
+        }
+        ["rule", name, kind] => {
+            let Some(kind) = RuleKind::parse(kind) else {
+                ctx.respond(Response::html(t("folder_invalid_rule")))
+                    .await?;
+                return Ok(());
+            };
+
+            let key = if folders
+                .add_rule(Rule {
+                    folder: name.to_string(),
+                    kind,
+                })
+                .await
+            {
+                "folder_rule_added"
+            } else {
+                "folder_rule_exists"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "folder" => escape_html(name), "kind" => kind.to_string() },
+            )))
+            .await?;
+        }
+        ["unrule", name] => {
+            let key = if folders.remove_rules(name).await {
+                "folder_rules_removed"
+            } else {
+                "folder_no_rules"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "folder" => escape_html(name) },
+            )))
+            .await?;
+        }
+        ["list"] | [] => {
+            let rules = folders.rules().await;
+            let list = folders
+                .list(client)
+                .await?
+                .into_iter()
+                .map(|folder| {
+                    let kinds = rules
+                        .iter()
+                        .filter(|rule| rule.folder.eq_ignore_ascii_case(&folder.title))
+                        .map(|rule| rule.kind.to_string())
+                        .collect::<Vec<_>>();
+
+                    if kinds.is_empty() {
+                        format!(
+                            "• <b>{0}</b> ({1})",
+                            escape_html(&folder.title),
+                            folder.include_peers.len()
+                        )
+                    } else {
+                        format!(
+                            "• <b>{0}</b> ({1}) — <i>{2}</i>",
+                            escape_html(&folder.title),
+                            folder.include_peers.len(),
+                            kinds.join(", ")
+                        )
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if list.is_empty() {
+                ctx.respond(t("folder_no_folders")).await?;
+            } else {
+                ctx.respond(Response::html(t_a(
+                    "folder_list",
+                    hashmap! { "folders" => list.join("\n") },
+                )))
+                .await?;
+            }
+        }
+        _ => {
+            ctx.respond(Response::html(t("folder_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the archive command.
+async fn archive(ctx: Context, i18n: I18n, folders: Folders) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let target = text.split_whitespace().nth(1);
+
+    let Some(chat) = resolve_chat(&ctx, target).await? else {
+        ctx.respond(t("folder_chat_not_found")).await?;
+        return Ok(());
+    };
+
+    folders.archive(ctx.client(), &chat).await?;
+    ctx.respond(Response::html(t_a(
+        "chat_archived",
+        hashmap! { "chat" => escape_html(chat.name()) },
+    )))
+    .await?;
+
+    Ok(())
+}
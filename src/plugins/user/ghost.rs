@@ -0,0 +1,102 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the ghost command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{ghost::Ghost, i18n::I18n},
+    output::{Respond, Response},
+};
+
+/// Setup the ghost command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("ghost").and(filters::sudoers())).then(ghost),
+    )
+}
+
+/// Handles the ghost command.
+async fn ghost(ctx: Context, i18n: I18n, ghost: Ghost) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    // Resolves the target chat, defaulting to the current one.
+    let chat_id = match args.get(1) {
+        Some(target) if target.starts_with('@') => {
+            match ctx.client().resolve_username(&target[1..]).await? {
+                Some(chat) => Some(chat.id()),
+                None => None,
+            }
+        }
+        Some(target) => target.parse::<i64>().ok(),
+        None => ctx.chat().map(|chat| chat.id()),
+    };
+
+    match (args.first().copied(), chat_id) {
+        (Some("on"), Some(chat_id)) => {
+            let key = if ghost.enable(chat_id).await {
+                "ghost_enabled"
+            } else {
+                "ghost_already_enabled"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "chat" => chat_id.to_string() },
+            )))
+            .await?;
+        }
+        (Some("off"), Some(chat_id)) => {
+            let key = if ghost.disable(chat_id).await {
+                "ghost_disabled"
+            } else {
+                "ghost_not_enabled"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "chat" => chat_id.to_string() },
+            )))
+            .await?;
+        }
+        (Some("on" | "off"), None) => {
+            ctx.respond(t("ghost_chat_not_found")).await?;
+        }
+        (Some("list") | None, _) => {
+            let chats = ghost.chats().await;
+            if chats.is_empty() {
+                ctx.respond(t("ghost_no_chats")).await?;
+                return Ok(());
+            }
+
+            let list = chats
+                .iter()
+                .map(|id| format!("• <code>{}</code>", id))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            ctx.respond(Response::html(t_a(
+                "ghost_status",
+                hashmap! { "chats" => list },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("ghost_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
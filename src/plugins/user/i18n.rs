@@ -0,0 +1,243 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the locale editor command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    output::{Output, Respond, Response},
+    utils::escape_html,
+};
+
+/// The most keys listed of each kind, the rest counted.
+const MAX_KEYS: usize = 20;
+
+/// Setup the locale editor commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("i18n").and(filters::sudoers())).then(i18n))
+        .handler(
+            handler::new_message(filters::command("i18ncheck").and(filters::sudoers()))
+                .then(i18n_check),
+        )
+        .handler(
+            handler::new_message(filters::command("reloadlocales").and(filters::sudoers()))
+                .then(reload_locales),
+        )
+}
+
+/// Handles the locale editor command.
+async fn i18n(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap_or_default();
+    let args = text.splitn(5, ' ').skip(1).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["set", locale, key, value] if !value.trim().is_empty() => {
+            if !i18n.locales().iter().any(|l| l == locale) {
+                ctx.respond(Response::html(t_a(
+                    "i18n_locale_not_found",
+                    hashmap! { "locale" => escape_html(locale) },
+                )))
+                .await?;
+                return Ok(());
+            }
+            if !i18n.has_key(locale, key) {
+                ctx.respond(Response::html(t_a(
+                    "i18n_key_not_found",
+                    hashmap! { "key" => escape_html(key) },
+                )))
+                .await?;
+                return Ok(());
+            }
+
+            if let Err(e) = i18n.set_override(locale, key, value.trim()).await {
+                ctx.respond(Response::html(t_a(
+                    "i18n_invalid_value",
+                    hashmap! { "error" => escape_html(&e) },
+                )))
+                .await?;
+                return Ok(());
+            }
+
+            ctx.respond(Response::html(t_a(
+                "i18n_set",
+                hashmap! { "locale" => escape_html(locale), "key" => escape_html(key) },
+            )))
+            .await?;
+        }
+        ["reset", key] => {
+            let removed = i18n.reset_override(key.trim()).await;
+            let key = escape_html(key.trim());
+
+            if removed {
+                ctx.respond(Response::html(t_a("i18n_reset", hashmap! { "key" => key })))
+                    .await?;
+            } else {
+                ctx.respond(Response::html(t_a(
+                    "i18n_not_overridden",
+                    hashmap! { "key" => key },
+                )))
+                .await?;
+            }
+        }
+        ["export"] | ["export", _] => {
+            let locale = args
+                .get(1)
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|| i18n.locale());
+
+            let Some(content) = i18n.export(&locale) else {
+                ctx.respond(Response::html(t_a(
+                    "i18n_locale_not_found",
+                    hashmap! { "locale" => escape_html(&locale) },
+                )))
+                .await?;
+                return Ok(());
+            };
+
+            Output {
+                html: String::new(),
+                caption: t_a("i18n_exported", hashmap! { "locale" => locale.clone() }),
+                content,
+                file_name: format!("{}.ftl", locale),
+            }
+            .send(&ctx, None, true)
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("i18n_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the locale audit command, listing the keys each locale misses or
+/// has in excess, compared to the default one.
+async fn i18n_check(ctx: Context, i18n: I18n) -> Result<()> {
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+    let default_locale = escape_html(i18n.default_locale());
+
+    let Some(audits) = i18n.audit() else {
+        ctx.respond(Response::html(t_a(
+            "i18n_locale_not_found",
+            hashmap! { "locale" => default_locale },
+        )))
+        .await?;
+        return Ok(());
+    };
+    if audits.is_empty() {
+        ctx.respond(Response::html(t_a(
+            "i18n_check_ok",
+            hashmap! { "locale" => default_locale },
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let mut html = t_a(
+        "i18n_check_header",
+        hashmap! { "locale" => default_locale.clone() },
+    );
+    let mut content = String::new();
+    for audit in audits.iter() {
+        html += &format!(
+            "\n\n{}",
+            t_a(
+                "i18n_check_locale",
+                hashmap! {
+                    "locale" => escape_html(&audit.locale),
+                    "missing" => audit.missing.len().to_string(),
+                    "extra" => audit.extra.len().to_string(),
+                },
+            )
+        );
+        for (key, keys) in [
+            ("i18n_check_missing", &audit.missing),
+            ("i18n_check_extra", &audit.extra),
+        ] {
+            if keys.is_empty() {
+                continue;
+            }
+
+            let mut list = keys
+                .iter()
+                .take(MAX_KEYS)
+                .map(|key| format!("<code>{}</code>", escape_html(key)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if keys.len() > MAX_KEYS {
+                list += &format!(" (+{})", keys.len() - MAX_KEYS);
+            }
+            html += &format!("\n{}", t_a(key, hashmap! { "keys" => list }));
+        }
+
+        content += &format!("[{}]\n", audit.locale);
+        content += &audit
+            .missing
+            .iter()
+            .map(|key| format!("- {}\n", key))
+            .collect::<String>();
+        content += &audit
+            .extra
+            .iter()
+            .map(|key| format!("+ {}\n", key))
+            .collect::<String>();
+        content += "\n";
+    }
+
+    Output {
+        html,
+        caption: t_a("i18n_check_header", hashmap! { "locale" => default_locale }),
+        content,
+        file_name: "i18n_check.txt".to_string(),
+    }
+    .send(&ctx, None, ctx.wants_file())
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the reload locales command, reading the locale files again.
+///
+/// If any of them is invalid, the current locales are kept.
+async fn reload_locales(ctx: Context, i18n: I18n) -> Result<()> {
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    match i18n.reload() {
+        Ok(locales) => {
+            let list = locales
+                .iter()
+                .map(|locale| format!("<code>{}</code>", escape_html(locale)))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            ctx.respond(Response::html(t_a(
+                "i18n_reloaded",
+                hashmap! { "count" => locales.len().to_string(), "locales" => list },
+            )))
+            .await?;
+        }
+        Err(e) => {
+            ctx.respond(Response::html(t_a(
+                "i18n_reload_failed",
+                hashmap! { "error" => escape_html(&e) },
+            )))
+            .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -8,15 +8,18 @@
 
 //! This module contains the info command handler.
 
-use ferogram::{handler, Filter, Result, Router};
+use ferogram::{handler, Context, Filter, Result, Router};
 use grammers_client::{
     button, reply_markup,
     types::{InputMessage, Message},
 };
-use maplit::hashmap;
-use sysinfo::System;
 
-use crate::{filters, modules::i18n::I18n, Sender};
+use crate::{
+    filters,
+    modules::{i18n::I18n, system_info::SystemInfo},
+    output::{self, Output, Respond},
+    utils, Sender,
+};
 
 /// Setup the info command.
 pub fn setup() -> Router {
@@ -26,34 +29,34 @@ pub fn setup() -> Router {
 }
 
 /// Handles the info command.
-async fn info(message: Message, i18n: I18n, tx: Sender) -> Result<()> {
+async fn info(ctx: Context, message: Message, i18n: I18n, tx: Sender) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
 
-    let info = System::new_all();
-
-    let cpu_usage = info.global_cpu_usage();
-    let used_memory = info.used_memory() as f64 / 10f64.powi(9);
-    let total_memory = info.total_memory() as f64 / 10f64.powi(9);
-    let memory_usage = (used_memory / total_memory) * 100f64;
-
-    let args = hashmap! {
-        "os" => System::name().unwrap_or("Unknown".to_string()),
-        "cpu_usage" => (cpu_usage as u64).to_string(),
-        "arch" => System::cpu_arch().unwrap_or("x86_64".to_string()),
-        "host" => System::host_name().unwrap_or("localhost".to_string()),
-        "version" => env!("CARGO_PKG_VERSION").to_string(),
-        "kernel_version" => System::kernel_version().unwrap_or("1.0.0".to_string()),
-        "memory_usage" => (memory_usage as u64).to_string(),
-        "used_memory" => format!("{:.2}", used_memory),
-        "total_memory" => format!("{:.2}", total_memory),
-    };
-    tx.send(crate::Message::to_bot().send_via_bot_message(
-        message.chat(),
-        InputMessage::html(t_a("info_text", args)).reply_markup(&reply_markup::inline(vec![vec![
-            button::inline(t("reload_button"), "info"),
-        ]])),
-    ))
+    let info = SystemInfo::collect();
+
+    let (_, as_json) = output::take_json_flag(message.text());
+    if as_json {
+        Output::json(&info, t("info_output_file"), "info.json")
+            .send(&ctx, None, ctx.wants_file())
+            .await?;
+
+        return Ok(());
+    }
+
+    utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .in_topic(utils::topic_id(&message))
+            .send_via_bot_message(
+                message.chat(),
+                InputMessage::html(t_a("info_text", info.to_args())).reply_markup(
+                    &reply_markup::inline(vec![vec![button::inline(t("reload_button"), "info")]]),
+                ),
+            ),
+    )
     .await?;
 
     Ok(())
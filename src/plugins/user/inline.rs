@@ -0,0 +1,179 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the inline command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{button, reply_markup, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, inline::InlineProxy},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+    Sender,
+};
+
+/// The maximum number of results offered to be selected.
+const MAX_CHOICES: usize = 8;
+
+/// Setup the inline command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("inline").and(filters::sudoers())).then(inline),
+    )
+}
+
+/// Handles the inline command.
+async fn inline(ctx: Context, i18n: I18n, proxy: InlineProxy, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["save", name, bot, prefix @ ..] if bot.starts_with('@') => {
+            proxy.save(name, bot, &prefix.join(" ")).await;
+
+            ctx.respond(Response::html(t_a(
+                "inline_shortcut_saved",
+                hashmap! { "name" => escape_html(name), "bot" => escape_html(bot) },
+            )))
+            .await?;
+            return Ok(());
+        }
+        ["del", name] => {
+            let key = if proxy.delete(name).await {
+                "inline_shortcut_deleted"
+            } else {
+                "inline_shortcut_not_found"
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "name" => escape_html(name) },
+            )))
+            .await?;
+            return Ok(());
+        }
+        ["list"] => {
+            let shortcuts = proxy.shortcuts().await;
+            if shortcuts.is_empty() {
+                ctx.respond(t("inline_no_shortcuts")).await?;
+                return Ok(());
+            }
+
+            let list = shortcuts
+                .into_iter()
+                .map(|(name, shortcut)| {
+                    format!(
+                        "• <code>{0}</code> → @{1} <i>{2}</i>",
+                        escape_html(&name),
+                        shortcut.bot,
+                        escape_html(&shortcut.prefix)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            ctx.respond(Response::html(t_a(
+                "inline_shortcuts",
+                hashmap! { "shortcuts" => list },
+            )))
+            .await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let keyboard = args.first() == Some(&"-k");
+    if keyboard {
+        args.remove(0);
+    }
+
+    let resolved = match args.split_first() {
+        Some((target, query)) => proxy.resolve(target, &query.join(" ")).await,
+        None => None,
+    };
+    let Some((username, query)) = resolved else {
+        ctx.respond(Response::html(t("inline_usage"))).await?;
+        return Ok(());
+    };
+
+    let client = ctx.client();
+    let chat = ctx.chat().expect("Chat not found");
+    let Some(bot) = client.resolve_username(&username).await? else {
+        ctx.respond(Response::html(t_a(
+            "inline_bot_not_found",
+            hashmap! { "bot" => escape_html(&username) },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let limit = if keyboard { MAX_CHOICES } else { 1 };
+    let results = match utils::inline_query(client, &bot, &chat, &query, limit).await {
+        Ok(results) => results,
+        Err(e) => {
+            log::error!("Failed to query @{}: {}", username, e);
+            ctx.respond(t("inline_query_error")).await?;
+            return Ok(());
+        }
+    };
+
+    if results.is_empty() {
+        ctx.respond(t("inline_no_results")).await?;
+        return Ok(());
+    }
+
+    if keyboard {
+        let buttons = results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| (i, result.title().cloned()))
+            .collect::<Vec<_>>();
+
+        let id = proxy.wait_selection(chat.clone(), bot, query).await;
+        let buttons = buttons
+            .into_iter()
+            .map(|(i, title)| {
+                vec![button::inline(
+                    title.unwrap_or_else(|| format!("#{}", i + 1)),
+                    format!("inline {0} {1}", id, i),
+                )]
+            })
+            .collect::<Vec<_>>();
+
+        if !utils::send_or_busy(
+            &ctx,
+            &tx,
+            &i18n,
+            crate::Message::to_bot()
+                .in_topic(utils::current_topic(&ctx).await)
+                .send_via_bot_message(
+                    chat,
+                    InputMessage::html(t("inline_choose"))
+                        .reply_markup(&reply_markup::inline(buttons)),
+                ),
+        )
+        .await?
+        {
+            return Ok(());
+        }
+        ctx.delete().await?;
+    } else {
+        let result = results.into_iter().next().expect("No results");
+
+        result.send(&chat).await?;
+        ctx.delete().await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the lyrics command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{
+    reply_markup,
+    types::{InputMessage, Media},
+};
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, lyrics::Lyrics},
+    output::{Respond, Response},
+    utils, Sender,
+};
+
+/// Setup the lyrics command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["ly", "lyrics"]).and(filters::sudoers()))
+            .then(lyrics),
+    )
+}
+
+/// Splits a `artist - title` query.
+fn split_query(query: &str) -> Option<(String, String)> {
+    let (artist, title) = query.split_once(" - ")?;
+    let (artist, title) = (artist.trim(), title.trim());
+
+    if artist.is_empty() || title.is_empty() {
+        None
+    } else {
+        Some((artist.to_string(), title.to_string()))
+    }
+}
+
+/// Handles the lyrics command.
+async fn lyrics(ctx: Context, i18n: I18n, lyrics: Lyrics, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let text = ctx.command_text().unwrap();
+    let query = text
+        .split_whitespace()
+        .skip(1)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut track = split_query(&query);
+    if track.is_none() && query.is_empty() {
+        if let Some(reply) = ctx.get_reply().await? {
+            // Infers the track from the replied audio, falling back to its text.
+            track = match reply.media() {
+                Some(Media::Document(document)) => document
+                    .performer()
+                    .zip(document.audio_title())
+                    .map(|(artist, title)| (artist.to_string(), title.to_string())),
+                _ => None,
+            }
+            .or_else(|| split_query(reply.text()));
+        }
+    }
+
+    let Some((artist, title)) = track else {
+        ctx.respond(Response::html(t("lyrics_usage"))).await?;
+        return Ok(());
+    };
+
+    let msg = ctx.edit_or_reply(t("lyrics_searching")).await?;
+
+    match lyrics.fetch(&artist, &title).await {
+        Ok(id) => {
+            let song = lyrics.get(id).await.expect("Lyrics not cached");
+            let (text, buttons) = song.render_page(&i18n, 0);
+
+            if !utils::send_or_busy(
+                &ctx,
+                &tx,
+                &i18n,
+                crate::Message::to_bot()
+                    .in_topic(utils::current_topic(&ctx).await)
+                    .send_via_bot_message(
+                        ctx.chat().expect("Chat not found"),
+                        InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)),
+                    ),
+            )
+            .await?
+            {
+                return Ok(());
+            }
+            msg.delete().await?;
+        }
+        Err(e) => {
+            log::error!("Failed to fetch lyrics: {}", e);
+            ctx.respond_in(&msg, t("lyrics_not_found")).await?;
+        }
+    }
+
+    Ok(())
+}
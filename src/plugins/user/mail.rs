@@ -0,0 +1,83 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the mail command handler.
+
+use std::io::Cursor;
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::InputMessage;
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, mail::Mail},
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the mail command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("mail").and(filters::sudoers())).then(mail))
+}
+
+/// Handles the mail command.
+async fn mail(ctx: Context, i18n: I18n, mail: Mail) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    if !mail.is_configured() {
+        ctx.respond(t("mail_not_configured")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["read", id] => {
+            let Some(email) = (match id.parse::<i32>() {
+                Ok(id) => mail.get(id).await,
+                Err(_) => None,
+            }) else {
+                ctx.respond(t("mail_not_found")).await?;
+                return Ok(());
+            };
+
+            let text = t_a(
+                "mail_full",
+                hashmap! {
+                    "from" => escape_html(&email.from),
+                    "subject" => escape_html(&email.subject),
+                    "body" => escape_html(email.body.trim()),
+                },
+            );
+
+            if text.len() > 4000 {
+                let bytes = email.body.as_bytes();
+                let size = bytes.len();
+
+                let mut cursor = Cursor::new(bytes);
+                let file = ctx
+                    .upload_stream(&mut cursor, size, format!("email_{}.txt", email.id))
+                    .await?;
+
+                ctx.edit_or_reply(InputMessage::html(email.render_summary(&i18n)).document(file))
+                    .await?;
+            } else {
+                ctx.respond(Response::html(text)).await?;
+            }
+        }
+        _ => {
+            ctx.respond(Response::html(t("mail_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
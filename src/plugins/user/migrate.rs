@@ -0,0 +1,40 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the chat migration handler.
+
+use ferogram::{handler, Context, Result, Router};
+use grammers_client::grammers_tl_types as tl;
+
+use crate::{filters, modules::migrations::Migrations};
+
+/// Setup the chat migration watcher.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::chat_migrated()).then(migrate))
+}
+
+/// Handles a group becoming a supergroup, moving its data to the new ID.
+///
+/// Both the old group and the new supergroup get a service message, so the data
+/// is moved by whichever comes first.
+async fn migrate(ctx: Context, migrations: Migrations) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+    let (from, to) = match message.action() {
+        Some(tl::enums::MessageAction::ChatMigrateTo(action)) => {
+            (message.chat().id(), action.channel_id)
+        }
+        Some(tl::enums::MessageAction::ChannelMigrateFrom(action)) => {
+            (action.chat_id, message.chat().id())
+        }
+        _ => return Ok(()),
+    };
+
+    migrations.run(from, to).await;
+
+    Ok(())
+}
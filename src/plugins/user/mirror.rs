@@ -0,0 +1,160 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the local mirror command handler.
+
+use std::path::{Path, PathBuf};
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{types::Media, Client, InputMessage};
+use maplit::hashmap;
+use tokio_uring::fs::File;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        i18n::I18n,
+        usage::{Counters, Usage},
+        virustotal::{with_verdict, VirusTotal},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html, human_readable_size, Progress},
+};
+
+/// The config of the mirror plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // Where the media is saved.
+        vec![Key::new("dir", "./assets/mirror")]
+    }
+}
+
+/// Setup the mirror command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("dlm").and(filters::sudoers())).then(mirror))
+}
+
+/// Handles the mirror command, saving the replied media to the disk.
+///
+/// The saved file is scanned on VirusTotal, if enabled. It is paused once the
+/// daily budget of the plugin is used up.
+async fn mirror(
+    ctx: Context,
+    i18n: I18n,
+    settings: PluginSettings,
+    usage: Usage,
+    virustotal: VirusTotal,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let Some(reply) = ctx.get_reply().await? else {
+        ctx.respond(t("dlm_no_media")).await?;
+        return Ok(());
+    };
+
+    let Some((file_name, size)) = reply.media().as_ref().and_then(utils::media_file) else {
+        ctx.respond(t("dlm_no_media")).await?;
+        return Ok(());
+    };
+    let media = reply.media().unwrap();
+
+    if utils::over_budget(&ctx, &usage, &i18n, "mirror").await? {
+        return Ok(());
+    }
+
+    let dir = PathBuf::from(settings.get::<String>("mirror", "dir"));
+    if !dir.exists() {
+        tokio_uring::fs::create_dir_all(&dir).await?;
+    }
+    let path = utils::unique_path(&dir, &utils::sanitize_file_name(&file_name));
+
+    let msg = ctx.edit_or_reply(t("dlm_downloading")).await?;
+    let progress = Progress::start(msg.clone(), t("dlm_downloading"), size);
+
+    let result = save(ctx.client(), &media, &path, &progress).await;
+    progress.finish().await;
+
+    let written = match result {
+        Ok((written, requests)) => {
+            usage
+                .record("mirror", Counters::download(written, requests))
+                .await;
+            written
+        }
+        Err(e) => {
+            log::error!("Failed to save the media to {:?}: {}", path, e);
+            // Does not leave a partial file behind.
+            let _ = tokio::fs::remove_file(&path).await;
+
+            ctx.respond_in(&msg, t("dlm_error")).await?;
+            return Ok(());
+        }
+    };
+
+    let saved = t_a(
+        "dlm_saved",
+        hashmap! {
+            "path" => escape_html(&path.to_string_lossy()),
+            "size" => human_readable_size(written as usize),
+        },
+    );
+    if virustotal.is_enabled() {
+        msg.edit(InputMessage::html(format!(
+            "{0}\n\n{1}",
+            saved,
+            t("virustotal_scanning")
+        )))
+        .await?;
+    }
+    let verdict = virustotal.verdict(&path, &i18n).await;
+
+    ctx.respond_in(&msg, Response::html(with_verdict(saved, verdict)))
+        .await?;
+
+    Ok(())
+}
+
+/// Downloads a media into a file.
+///
+/// Returns how many bytes were written and how many requests it took.
+async fn save(
+    client: &Client,
+    media: &Media,
+    path: &Path,
+    progress: &Progress,
+) -> Result<(u64, u64)> {
+    let file = File::create(path).await?;
+    let mut download = client.iter_download(media);
+
+    let mut written = 0;
+    let mut requests = 0;
+    while let Some(chunk) = download.next().await? {
+        let len = chunk.len() as u64;
+        requests += 1;
+
+        let (res, _) = file.write_all_at(chunk, written).await;
+        res?;
+        written += len;
+
+        progress.advance(len);
+    }
+
+    file.sync_all().await?;
+    file.close().await?;
+
+    Ok((written, requests))
+}
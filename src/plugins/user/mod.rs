@@ -10,24 +10,133 @@
 
 use ferogram::Dispatcher;
 
+use crate::config::PluginConfig;
+
+mod account;
+mod afk;
+mod agenda;
+mod alert;
+mod autodl;
+mod browse;
+mod calc;
+mod chess;
+mod cmd;
+mod config;
+mod connect_four;
+mod digest;
+mod doctor;
+mod download;
 mod dump;
 mod eval;
+mod fetch;
+mod folder;
+mod ghost;
+mod i18n;
 mod info;
+mod inline;
+mod lyrics;
+mod mail;
+mod migrate;
+mod mirror;
+mod notes;
+mod pdf;
 mod purge;
+mod relogin;
+mod remind;
 mod reverse_search;
+mod scan_url;
+mod schedule;
 mod screenshot;
 mod sed;
+mod seen;
+mod song;
+mod sudo;
+mod sudoku;
+mod tags;
+mod term;
 mod tic_tac_toe;
+mod todo;
+mod tr;
+mod trace;
+mod transcribe;
+mod unread;
 mod upload;
+mod usage;
+mod usernames;
+mod wayback;
+
+/// Returns the user plugins that declare a config section.
+pub fn configs() -> Vec<Box<dyn PluginConfig>> {
+    vec![
+        Box::new(autodl::Settings),
+        Box::new(eval::Settings),
+        Box::new(mirror::Settings),
+        Box::new(pdf::Settings),
+        Box::new(purge::Settings),
+        Box::new(sed::Settings),
+        Box::new(term::Settings),
+        Box::new(unread::Settings),
+        Box::new(upload::Settings),
+    ]
+}
 
 pub fn setup(dp: Dispatcher) -> Dispatcher {
-    dp.router(|_| dump::setup())
+    dp.router(|_| account::setup())
+        .router(|_| afk::setup())
+        .router(|_| agenda::setup())
+        .router(|_| alert::setup())
+        .router(|_| autodl::setup())
+        .router(|_| browse::setup())
+        .router(|_| calc::setup())
+        .router(|_| chess::setup())
+        .router(|_| cmd::setup())
+        .router(|_| config::setup())
+        .router(|_| connect_four::setup())
+        .router(|_| digest::setup())
+        .router(|_| doctor::setup())
+        .router(|_| download::setup())
+        .router(|_| dump::setup())
         .router(|_| eval::setup())
+        .router(|_| fetch::setup())
+        .router(|_| folder::setup())
+        .router(|_| ghost::setup())
+        .router(|_| i18n::setup())
         .router(|_| info::setup())
+        .router(|_| inline::setup())
+        .router(|_| lyrics::setup())
+        .router(|_| mail::setup())
+        .router(|_| mirror::setup())
+        .router(|_| notes::setup())
+        .router(|_| pdf::setup())
         .router(|_| purge::setup())
+        .router(|_| relogin::setup())
+        .router(|_| remind::setup())
         .router(|_| reverse_search::setup())
+        .router(|_| scan_url::setup())
+        .router(|_| schedule::setup())
         .router(|_| screenshot::setup())
         .router(|_| sed::setup())
+        .router(|_| seen::setup())
+        .router(|_| song::setup())
+        .router(|_| sudo::setup())
+        .router(|_| sudoku::setup())
+        .router(|_| tags::setup())
+        .router(|_| term::setup())
         .router(|_| tic_tac_toe::setup())
+        .router(|_| todo::setup())
+        .router(|_| tr::setup())
+        .router(|_| trace::setup())
+        .router(|_| transcribe::setup())
+        .router(|_| unread::setup())
         .router(|_| upload::setup())
+        .router(|_| usage::setup())
+        .router(|_| usernames::setup())
+        .router(|_| wayback::setup())
+        .router(|_| afk::watcher())
+        .router(|_| alert::watcher())
+        .router(|_| autodl::watcher())
+        .router(|_| transcribe::watcher())
+        .router(|_| wayback::watcher())
+        .router(|_| seen::watcher())
+        .router(|_| migrate::watcher())
 }
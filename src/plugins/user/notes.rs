@@ -0,0 +1,252 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the notes commands handlers.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{types::Media, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        notes::{MediaRef, Note, Notes},
+        templates::{self, Variables},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// Setup the notes commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("save").and(filters::sudoers())).then(save))
+        .handler(handler::new_message(filters::command("get").and(filters::sudoers())).then(get))
+        .handler(
+            handler::new_message(filters::command("notes").and(filters::sudoers())).then(notes),
+        )
+        .handler(
+            handler::new_message(filters::command("clear").and(filters::sudoers())).then(clear),
+        )
+}
+
+/// Returns the ID of the file of a media, if it has one.
+fn file_id(media: &Media) -> Option<i64> {
+    match media {
+        Media::Photo(photo) => Some(photo.id()),
+        Media::Document(document) => Some(document.id()),
+        Media::Sticker(sticker) => Some(sticker.document.id()),
+        _ => None,
+    }
+}
+
+/// Handles the save command.
+///
+/// Usage: `.save <name>` replying to a message, or `.save <name> <text>`.
+async fn save(ctx: Context, i18n: I18n, notes: Notes) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.splitn(3, char::is_whitespace).skip(1);
+    let Some(name) = args.next().filter(|name| !name.is_empty()) else {
+        ctx.respond(Response::html(t("save_usage"))).await?;
+        return Ok(());
+    };
+
+    let note = match ctx.get_reply().await? {
+        Some(reply) => {
+            // Media is copied to the saved messages, so it outlives the original.
+            let media = match reply.media() {
+                Some(media) => {
+                    let client = ctx.client();
+                    let me = client.get_me().await?;
+                    let saved = client
+                        .forward_messages(&me, &[reply.id()], &reply.chat())
+                        .await?
+                        .into_iter()
+                        .flatten()
+                        .next();
+
+                    saved.map(|saved| MediaRef {
+                        file_id: file_id(&media),
+                        saved_message_id: saved.id(),
+                    })
+                }
+                None => None,
+            };
+
+            Note {
+                text: reply.html_text(),
+                media,
+            }
+        }
+        None => match args.next().map(str::trim).filter(|text| !text.is_empty()) {
+            Some(text) => Note {
+                text: escape_html(text),
+                media: None,
+            },
+            None => {
+                ctx.respond(Response::html(t("save_usage"))).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    if let Err(e) = templates::render(&note.text, &Variables::new()) {
+        ctx.respond(Response::html(t_a(
+            "template_invalid",
+            hashmap! { "error" => escape_html(&e) },
+        )))
+        .await?;
+        return Ok(());
+    }
+
+    let key = if notes.save(name, note).await {
+        "note_replaced"
+    } else {
+        "note_saved"
+    };
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! { "name" => escape_html(name) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the get command.
+///
+/// Usage: `.get <name>`. The note is rendered as a template, its `user.*` values
+/// being the ones of the replied user, or else of the sender.
+async fn get(ctx: Context, i18n: I18n, notes: Notes) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let Some(name) = text.split_whitespace().nth(1) else {
+        ctx.respond(Response::html(t("get_usage"))).await?;
+        return Ok(());
+    };
+
+    let Some(note) = notes.get(name).await else {
+        ctx.respond(Response::html(t_a(
+            "note_not_found",
+            hashmap! { "name" => escape_html(name) },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    let message = ctx.message().await.unwrap();
+    let sender = match ctx.get_reply().await? {
+        Some(reply) => reply.sender(),
+        None => message.sender(),
+    };
+    let mut variables = Variables::new().chat(&message.chat());
+    if let Some(sender) = sender {
+        variables = variables.user(&sender);
+    }
+    let text = match templates::render(&note.text, &variables) {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("Failed to render the note {}: {}", name, e);
+            note.text
+        }
+    };
+
+    let mut input = InputMessage::html(text).reply_to(utils::current_topic(&ctx).await);
+    if let Some(media) = note.media {
+        let client = ctx.client();
+        let me = client.get_me().await?;
+
+        match client
+            .get_messages_by_id(&me, &[media.saved_message_id])
+            .await?
+            .into_iter()
+            .flatten()
+            .next()
+            .and_then(|saved| saved.media())
+        {
+            Some(media) => input = input.copy_media(&media),
+            None => {
+                ctx.respond(Response::html(t_a(
+                    "note_media_missing",
+                    hashmap! { "name" => escape_html(name) },
+                )))
+                .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    // The command is replaced by the note.
+    ctx.delete().await?;
+    ctx.send(input).await?;
+
+    Ok(())
+}
+
+/// Handles the notes command.
+async fn notes(ctx: Context, i18n: I18n, notes: Notes) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let list = notes.list().await;
+    if list.is_empty() {
+        ctx.respond(t("notes_saved_empty")).await?;
+        return Ok(());
+    }
+
+    let list = list
+        .iter()
+        .map(|(name, note)| {
+            format!(
+                "• <code>{0}</code>{1}",
+                escape_html(name),
+                if note.media.is_some() { " 📎" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.respond(Response::html(t_a(
+        "notes_saved_list",
+        hashmap! { "notes" => list },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the clear command.
+async fn clear(ctx: Context, i18n: I18n, notes: Notes) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let Some(name) = text.split_whitespace().nth(1) else {
+        ctx.respond(Response::html(t("clear_usage"))).await?;
+        return Ok(());
+    };
+
+    let key = if notes.clear(name).await {
+        "note_cleared"
+    } else {
+        "note_not_found"
+    };
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! { "name" => escape_html(name) },
+    )))
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,290 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the PDF export command handler.
+
+use std::{io::Cursor, sync::Arc};
+
+use chrono::Local;
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{
+    types::{Media, Message},
+    InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{i18n::I18n, media_cache::MediaCache, pdf::Pdf},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The format of the timestamps.
+const DATE_FORMAT: &str = "%d/%m/%Y %H:%M";
+
+/// The config of the PDF export plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        vec![
+            // The most messages exported at once.
+            Key::new("max_messages", 200).validate(|value| match value.as_integer() {
+                Some(1..=1000) => Ok(()),
+                _ => Err("must be between 1 and 1000".to_string()),
+            }),
+            // Whether the photos are drawn, not only mentioned.
+            Key::new("include_images", true),
+        ]
+    }
+}
+
+/// The messages chosen to be exported.
+enum Selection {
+    /// The last messages before the command.
+    Last(usize),
+    /// The messages between two IDs, both included.
+    Range(i32, i32),
+}
+
+impl Selection {
+    /// Parses the argument of the command, `n` or `first-last`.
+    fn parse(arg: &str) -> Option<Self> {
+        match arg.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (first.parse::<i32>().ok()?, last.parse::<i32>().ok()?);
+                (0 < first && first <= last).then_some(Self::Range(first, last))
+            }
+            None => arg
+                .parse::<usize>()
+                .ok()
+                .filter(|count| *count > 0)
+                .map(Self::Last),
+        }
+    }
+
+    /// Returns how many messages it spans at most.
+    fn len(&self) -> usize {
+        match self {
+            Self::Last(count) => *count,
+            Self::Range(first, last) => (last - first + 1) as usize,
+        }
+    }
+}
+
+/// A message laid out in the document.
+struct Entry {
+    /// The name of the sender.
+    name: String,
+    /// When it was sent.
+    date: String,
+    /// The text.
+    text: String,
+    /// The bytes of the photo, if drawn.
+    photo: Option<Arc<Vec<u8>>>,
+    /// Whether there is a media not drawn.
+    media: bool,
+}
+
+/// Setup the PDF export command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("pdf").and(filters::sudoers())).then(pdf))
+}
+
+/// Handles the PDF export command.
+///
+/// Usage: `.pdf <n>` for the last messages, `.pdf <first>-<last>` for the messages
+/// between two IDs, or replying to a message for the ones since it.
+async fn pdf(
+    ctx: Context,
+    i18n: I18n,
+    media_cache: MediaCache,
+    settings: PluginSettings,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let message = ctx.message().await.unwrap();
+    let text = ctx.command_text().unwrap();
+    let selection = match text.split_whitespace().nth(1) {
+        Some(arg) => Selection::parse(arg),
+        None => ctx
+            .get_reply()
+            .await?
+            .map(|reply| Selection::Range(reply.id(), message.id() - 1)),
+    };
+    let Some(selection) = selection else {
+        ctx.respond(Response::html(t("pdf_usage"))).await?;
+        return Ok(());
+    };
+
+    let max = settings.get::<usize>("pdf", "max_messages");
+    if selection.len() > max {
+        ctx.respond(t_a("pdf_too_many", hashmap! { "max" => max.to_string() }))
+            .await?;
+        return Ok(());
+    }
+
+    let msg = ctx.edit_or_reply(t("pdf_collecting")).await?;
+
+    let client = ctx.client();
+    let chat = message.chat();
+    let mut messages = Vec::with_capacity(selection.len());
+    match selection {
+        Selection::Last(count) => {
+            let mut iter = client
+                .iter_messages(chat.pack())
+                .offset_id(message.id())
+                .limit(count);
+            while let Some(message) = iter.next().await? {
+                messages.push(message);
+            }
+            messages.reverse();
+        }
+        Selection::Range(first, last) => {
+            let ids = (first..=last)
+                .filter(|id| *id != message.id())
+                .collect::<Vec<_>>();
+            for chunk in ids.chunks(100) {
+                messages.extend(
+                    client
+                        .get_messages_by_id(&chat, chunk)
+                        .await?
+                        .into_iter()
+                        .flatten(),
+                );
+            }
+        }
+    }
+
+    // In forums, only the messages of the current topic are exported.
+    if utils::is_forum(&chat) {
+        let topic = utils::topic_id(&message);
+        messages.retain(|message| utils::topic_id(message) == topic);
+    }
+
+    if messages.is_empty() {
+        ctx.respond_in(&msg, t("pdf_no_messages")).await?;
+        return Ok(());
+    }
+
+    msg.edit(t_a(
+        "pdf_rendering",
+        hashmap! { "count" => messages.len().to_string() },
+    ))
+    .await?;
+
+    let include_images = settings.get::<bool>("pdf", "include_images");
+    let mut entries = Vec::with_capacity(messages.len());
+    for message in messages.iter() {
+        entries.push(entry(&ctx, message, &media_cache, include_images).await);
+    }
+
+    let title = t_a(
+        "pdf_title",
+        hashmap! {
+            "chat" => chat.name().to_string(),
+            "date" => Local::now().format(DATE_FORMAT).to_string(),
+        },
+    );
+    let media = t("pdf_media");
+    let bytes = tokio::task::spawn_blocking(move || render(&title, &entries, &media)).await?;
+
+    let size = bytes.len();
+    let mut stream = Cursor::new(bytes);
+    let file = ctx
+        .upload_stream(&mut stream, size, "chat.pdf".to_string())
+        .await?;
+
+    ctx.reply(
+        InputMessage::html(t_a(
+            "pdf_caption",
+            hashmap! {
+                "chat" => escape_html(chat.name()),
+                "count" => messages.len().to_string(),
+            },
+        ))
+        .document(file),
+    )
+    .await?;
+    msg.delete().await?;
+
+    Ok(())
+}
+
+/// Collects what is laid out of a message, downloading its photo if drawn.
+async fn entry(
+    ctx: &Context,
+    message: &Message,
+    media_cache: &MediaCache,
+    include_images: bool,
+) -> Entry {
+    let name = message
+        .sender()
+        .map(|sender| sender.name().to_string())
+        .unwrap_or_else(|| message.chat().name().to_string());
+
+    let photo = match message.media() {
+        Some(media @ Media::Photo(_)) if include_images => {
+            match media_cache.download(ctx.client(), &media).await {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    log::warn!("Failed to download a photo to export: {}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    Entry {
+        name,
+        date: message
+            .date()
+            .with_timezone(&Local)
+            .format(DATE_FORMAT)
+            .to_string(),
+        text: message.text().to_string(),
+        media: message.media().is_some() && photo.is_none(),
+        photo,
+    }
+}
+
+/// Lays out the messages, writing the document.
+fn render(title: &str, entries: &[Entry], media: &str) -> Vec<u8> {
+    let mut pdf = Pdf::default();
+    pdf.title(title);
+
+    for entry in entries {
+        pdf.heading(&entry.name, &entry.date);
+
+        if let Some(photo) = &entry.photo {
+            if let Err(e) = pdf.image(photo) {
+                log::warn!("Failed to draw a photo to export: {}", e);
+                pdf.note(media);
+            }
+        }
+        if entry.media {
+            pdf.note(media);
+        }
+        if !entry.text.is_empty() {
+            pdf.text(&entry.text);
+        }
+
+        pdf.space(8.0);
+    }
+
+    pdf.finish()
+}
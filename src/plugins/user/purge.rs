@@ -14,7 +14,55 @@ use ferogram::{handler, Context, Filter, Result, Router};
 use grammers_client::types::InputMessage;
 use maplit::hashmap;
 
-use crate::{filters, modules::i18n::I18n};
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{
+        i18n::I18n,
+        purges::{Purge, Purges},
+        scheduler::Scheduler,
+    },
+    output::{Respond, Response},
+    utils, Recipient, Sender,
+};
+
+/// The config of the purge plugin.
+///
+/// It is shared by the commands of both clients.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "purge"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        vec![
+            // In seconds, how long the reply of each command is kept.
+            Key::new("delete_ttl", 4).validate(validate_ttl),
+            Key::new("purge_ttl", 4).validate(validate_ttl),
+            Key::new("purgeme_ttl", 4).validate(validate_ttl),
+            // Above how many messages a purge must be confirmed, zero for never.
+            Key::new("confirm_threshold", 100).validate(|value| match value.as_integer() {
+                Some(0..=100_000) => Ok(()),
+                _ => Err("must be between 0 and 100000".to_string()),
+            }),
+        ]
+    }
+}
+
+/// Validates how long a reply is kept.
+fn validate_ttl(value: &toml::Value) -> std::result::Result<(), String> {
+    match value.as_integer() {
+        Some(1..=3600) => Ok(()),
+        _ => Err("must be between 1 and 3600".to_string()),
+    }
+}
+
+/// Returns how long the reply of a command is kept.
+fn ttl(settings: &PluginSettings, command: &str) -> Duration {
+    Duration::from_secs(settings.get("purge", &format!("{}_ttl", command)))
+}
 
 /// Setup the purge command.
 pub fn setup() -> Router {
@@ -33,48 +81,99 @@ pub fn setup() -> Router {
 }
 
 /// Handles the delete command.
-async fn delete(ctx: Context, i18n: I18n) -> Result<()> {
+async fn delete(
+    ctx: Context,
+    i18n: I18n,
+    scheduler: Scheduler,
+    settings: PluginSettings,
+    recipient: Recipient,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
+    let ttl = ttl(&settings, "delete");
 
     if let Some(reply) = ctx.get_reply().await? {
         match reply.delete().await {
             Ok(_) => {
-                let msg = ctx.edit_or_reply(t("deleted")).await?;
-
-                tokio::time::sleep(Duration::from_secs(4)).await;
-                msg.delete().await?;
-                let _ = ctx.delete().await;
+                utils::reply_ephemeral(&ctx, &scheduler, recipient, t("deleted"), ttl).await?;
             }
             Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
-                ctx.edit_or_reply(t("you_dont_have_perms")).await?;
+                ctx.respond(t("you_dont_have_perms")).await?;
             }
             Err(e) => {
                 log::error!("failed to delete message: {}", e);
-                ctx.edit_or_reply(t("delete_error")).await?;
+                ctx.respond(t("delete_error")).await?;
             }
         };
     } else {
-        let sent = ctx.reply(InputMessage::html(t("reply_needed"))).await?;
-
-        tokio::time::sleep(Duration::from_secs(4)).await;
-        sent.delete().await?;
-        ctx.delete().await?;
+        utils::reply_ephemeral(
+            &ctx,
+            &scheduler,
+            recipient,
+            InputMessage::html(t("reply_needed")),
+            ttl,
+        )
+        .await?;
     }
 
     Ok(())
 }
 
 /// Handles the purge command.
-async fn purge(ctx: Context, i18n: I18n) -> Result<()> {
+///
+/// Usage: `.purge` replying to the first message, `.purge <n>` for the last
+/// messages or `.purge <duration>` for the ones newer than it, such as `2h`.
+///
+/// The purges above `plugins.purge.confirm_threshold` are only done once confirmed
+/// through the buttons of a message sent via the bot.
+async fn purge(
+    ctx: Context,
+    i18n: I18n,
+    scheduler: Scheduler,
+    settings: PluginSettings,
+    purges: Purges,
+    tx: Sender,
+    recipient: Recipient,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+    let ttl = ttl(&settings, "purge");
 
-    if let Some(reply) = ctx.get_reply().await? {
+    if let Some(message_ids) = utils::purge_targets(&ctx).await? {
         let msg = ctx.message().await.unwrap();
-        let message_ids = (reply.id()..=(msg.id() - 1)).collect::<Vec<_>>();
         let total_messages = message_ids.len();
         let mut purged_messages = 0;
 
+        let threshold = settings.get::<usize>("purge", "confirm_threshold");
+        if threshold > 0 && total_messages > threshold {
+            let chat = msg.chat();
+            let (id, input) = purges
+                .ask(
+                    ctx.client(),
+                    Purge {
+                        recipient,
+                        client: ctx.client().clone(),
+                        chat: chat.pack(),
+                        message_ids,
+                        command_ids: vec![msg.id()],
+                    },
+                    &i18n,
+                )
+                .await?;
+
+            let (message, rx) = crate::Message::to_bot()
+                .in_topic(utils::current_topic(&ctx).await)
+                .send_via_bot_message(chat, input)
+                .with_response();
+            if !utils::send_or_busy(&ctx, &tx, &i18n, message).await? {
+                return Ok(());
+            }
+            if let Ok(Ok(sent)) = rx.await {
+                purges.attach(id, sent.id()).await;
+            }
+
+            return Ok(());
+        }
+
         let msg = ctx
             .edit_or_reply(InputMessage::html(t_a(
                 "purging",
@@ -84,68 +183,94 @@ async fn purge(ctx: Context, i18n: I18n) -> Result<()> {
             )))
             .await?;
 
-        let mut waited = 0;
         for chunk in message_ids.chunks(100) {
-            match ctx.delete_messages(chunk.to_vec()).await {
-                Ok(count) => purged_messages += count,
-                Err(e) if e.is("MESSAGE_ID_INVALID") => continue,
-                Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
-                    msg.edit(t("you_dont_have_perms")).await?;
+            loop {
+                match ctx.delete_messages(chunk.to_vec()).await {
+                    Ok(count) => purged_messages += count,
+                    Err(e) if e.is("MESSAGE_ID_INVALID") => {}
+                    Err(e) if e.is("MESSAGE_DELETE_FORBIDDEN") => {
+                        ctx.respond_in(&msg, t("you_dont_have_perms")).await?;
+
+                        return Ok(());
+                    }
+                    Err(e) => match utils::flood_wait(&e) {
+                        Some(wait) => {
+                            let sent = ctx
+                                .reply(InputMessage::html(t_a(
+                                    "flood_wait",
+                                    hashmap! { "seconds" => wait.as_secs().to_string() },
+                                )))
+                                .await?;
+
+                            // The chunk is deleted again once the wait is over.
+                            tokio::time::sleep(wait).await;
+                            sent.delete().await?;
+
+                            continue;
+                        }
+                        None => {
+                            log::error!("failed to purge messages: {}", e);
+                            ctx.respond_in(&msg, t("purge_error")).await?;
 
-                    return Ok(());
-                }
-                Err(e) if e.is("FLOOD_WAIT") => {
-                    let time = 5 * (waited + 1);
-                    waited += 1;
-
-                    let sent = ctx
-                        .reply(InputMessage::html(t_a(
-                            "flood_wait",
-                            hashmap! { "seconds" => time.to_string() },
-                        )))
-                        .await?;
-
-                    tokio::time::sleep(Duration::from_secs(time)).await;
-                    sent.delete().await?;
-                }
-                Err(e) => {
-                    log::error!("failed to purge messages: {}", e);
-                    msg.edit(t("purge_error")).await?;
+                            return Ok(());
+                        }
+                    },
+                };
 
-                    return Ok(());
-                }
-            };
+                break;
+            }
         }
 
-        msg.edit(InputMessage::html(t_a(
-            "purged",
-            hashmap! {
-                "count" => purged_messages.to_string(),
-            },
-        )))
+        ctx.respond_in(
+            &msg,
+            Response::html(t_a(
+                "purged",
+                hashmap! {
+                    "count" => purged_messages.to_string(),
+                },
+            )),
+        )
         .await?;
 
-        tokio::time::sleep(Duration::from_secs(4)).await;
-        ctx.delete().await?;
+        let mut message_ids = vec![msg.id()];
+        if let Some(command) = ctx
+            .message()
+            .await
+            .filter(|command| command.id() != msg.id())
+        {
+            message_ids.push(command.id());
+        }
+        utils::delete_later(&scheduler, recipient, msg.chat().pack(), message_ids, ttl).await?;
     } else {
-        let sent = ctx.reply(InputMessage::html(t("reply_needed"))).await?;
-
-        tokio::time::sleep(Duration::from_secs(4)).await;
-        sent.delete().await?;
-        ctx.delete().await?;
+        utils::reply_ephemeral(
+            &ctx,
+            &scheduler,
+            recipient,
+            InputMessage::html(t("purge_usage")),
+            ttl,
+        )
+        .await?;
     }
 
     Ok(())
 }
 
 /// Handles the purgeme command.
-async fn purge_me(ctx: Context, i18n: I18n) -> Result<()> {
+async fn purge_me(
+    ctx: Context,
+    i18n: I18n,
+    scheduler: Scheduler,
+    settings: PluginSettings,
+    recipient: Recipient,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+    let ttl = ttl(&settings, "purgeme");
 
     if let Some(reply) = ctx.get_reply().await? {
         let msg = ctx.message().await.unwrap();
         let sender = msg.sender().expect("Message has no sender");
+        let topic = utils::topic_id(&msg);
         let message_ids = (reply.id()..=(msg.id() - 1)).collect::<Vec<_>>();
         let mut purged_messages = 0;
 
@@ -153,57 +278,77 @@ async fn purge_me(ctx: Context, i18n: I18n) -> Result<()> {
             .edit_or_reply(InputMessage::html(t("purging_me")))
             .await?;
 
-        let mut waited = 0;
         for message_id in message_ids {
-            match ctx.get_message(message_id).await {
-                Ok(Some(msg)) => {
-                    if let Some(snd) = msg.sender() {
-                        if snd.id() == sender.id() {
-                            purged_messages += 1;
-                            msg.delete().await?;
+            loop {
+                match ctx.get_message(message_id).await {
+                    Ok(Some(msg)) => {
+                        if let Some(snd) = msg.sender() {
+                            // In forums, only the messages of the current topic are purged.
+                            if snd.id() == sender.id() && utils::topic_id(&msg) == topic {
+                                purged_messages += 1;
+                                msg.delete().await?;
+                            }
                         }
                     }
-                }
-                Err(e) if e.is("FLOOD_WAIT") => {
-                    let time = 5 * (waited + 1);
-                    waited += 1;
-
-                    let sent = ctx
-                        .reply(InputMessage::html(t_a(
-                            "flood_wait",
-                            hashmap! { "seconds" => time.to_string() },
-                        )))
-                        .await?;
-
-                    tokio::time::sleep(Duration::from_secs(time)).await;
-                    sent.delete().await?;
-                }
-                Err(e) => {
-                    log::error!("failed to get message: {}", e);
-                    msg.edit(InputMessage::html(t("purge_error"))).await?;
+                    Err(e) => match utils::flood_wait(&e) {
+                        Some(wait) => {
+                            let sent = ctx
+                                .reply(InputMessage::html(t_a(
+                                    "flood_wait",
+                                    hashmap! { "seconds" => wait.as_secs().to_string() },
+                                )))
+                                .await?;
+
+                            // The message is fetched again once the wait is over.
+                            tokio::time::sleep(wait).await;
+                            sent.delete().await?;
+
+                            continue;
+                        }
+                        None => {
+                            log::error!("failed to get message: {}", e);
+                            ctx.respond_in(&msg, Response::html(t("purge_error")))
+                                .await?;
 
-                    return Ok(());
+                            return Ok(());
+                        }
+                    },
+                    _ => {}
                 }
-                _ => continue,
+
+                break;
             }
         }
 
-        msg.edit(InputMessage::html(t_a(
-            "purged_me",
-            hashmap! {
-                "count" => purged_messages.to_string(),
-            },
-        )))
+        ctx.respond_in(
+            &msg,
+            Response::html(t_a(
+                "purged_me",
+                hashmap! {
+                    "count" => purged_messages.to_string(),
+                },
+            )),
+        )
         .await?;
 
-        tokio::time::sleep(Duration::from_secs(4)).await;
-        ctx.delete().await?;
+        let mut message_ids = vec![msg.id()];
+        if let Some(command) = ctx
+            .message()
+            .await
+            .filter(|command| command.id() != msg.id())
+        {
+            message_ids.push(command.id());
+        }
+        utils::delete_later(&scheduler, recipient, msg.chat().pack(), message_ids, ttl).await?;
     } else {
-        let sent = ctx.reply(InputMessage::html(t("reply_needed"))).await?;
-
-        tokio::time::sleep(Duration::from_secs(4)).await;
-        sent.delete().await?;
-        ctx.delete().await?;
+        utils::reply_ephemeral(
+            &ctx,
+            &scheduler,
+            recipient,
+            InputMessage::html(t("reply_needed")),
+            ttl,
+        )
+        .await?;
     }
 
     Ok(())
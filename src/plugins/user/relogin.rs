@@ -0,0 +1,54 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the relogin command handler.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, supervisor::Supervisor},
+    output::{Respond, Response},
+    utils::escape_html,
+    Recipient,
+};
+
+/// Setup the relogin command.
+///
+/// The user logs the bot in again; the bot does the same for the user with `/relogin`.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("relogin").and(filter::me)).then(relogin))
+}
+
+/// Handles the relogin command.
+async fn relogin(ctx: Context, i18n: I18n, supervisor: Supervisor) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    if !supervisor.is_down(Recipient::Bot) {
+        ctx.respond(Response::html(t("relogin_not_needed"))).await?;
+        return Ok(());
+    }
+
+    match supervisor.relogin_bot().await {
+        Ok(()) => {
+            ctx.respond(Response::html(t("relogin_done"))).await?;
+        }
+        Err(e) => {
+            ctx.respond(Response::html(t_a(
+                "relogin_failed",
+                hashmap! { "error" => escape_html(&e.to_string()) },
+            )))
+            .await?;
+        }
+    }
+
+    Ok(())
+}
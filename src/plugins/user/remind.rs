@@ -0,0 +1,223 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the reminders commands handlers.
+
+use chrono::{DateTime, Local, Utc};
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        dates,
+        i18n::I18n,
+        scheduler::{Job, Scheduler},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html},
+    Action, Recipient,
+};
+
+/// Setup the reminders commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(filters::command("remind").and(filters::sudoers())).then(remind),
+        )
+        .handler(
+            handler::new_message(filters::command("reminders").and(filters::sudoers()))
+                .then(reminders),
+        )
+        .handler(
+            handler::new_message(filters::command("editremind").and(filters::sudoers()))
+                .then(edit_remind),
+        )
+        .handler(
+            handler::new_message(filters::command("delremind").and(filters::sudoers()))
+                .then(del_remind),
+        )
+}
+
+/// Checks if a job is a reminder.
+fn is_reminder(job: &Job) -> bool {
+    job.is_reminder()
+}
+
+/// Formats the next run of a job, in local time.
+fn format_next(next: DateTime<Utc>) -> String {
+    next.with_timezone(&Local)
+        .format("%d/%m/%Y %H:%M")
+        .to_string()
+}
+
+/// Handles the remind command.
+///
+/// Usage: `.remind <when> <text>`, where `when` is `10m`, `18:30`, `25/12 09:00`,
+/// `tomorrow 9h`, `next friday 15h` or `every monday 8h`, among others.
+async fn remind(ctx: Context, i18n: I18n, scheduler: Scheduler) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let words = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let Some((when, used)) = dates::parse(&words).filter(|(_, used)| words.len() > *used) else {
+        ctx.respond(Response::html(t("remind_usage"))).await?;
+        return Ok(());
+    };
+
+    let chat = ctx.chat().expect("Chat not found");
+    let action = Action::Remind(
+        escape_html(&words[used..].join(" ")),
+        utils::message_link(&chat, ctx.message().await.unwrap().id()),
+    );
+
+    let key = if when.is_recurring() {
+        "remind_added_recurring"
+    } else {
+        "remind_added"
+    };
+    let (Some(at), Some(id)) = (
+        when.next_after(Utc::now()),
+        scheduler.schedule(when, Recipient::Bot, None, action).await,
+    ) else {
+        ctx.respond(Response::html(t("remind_usage"))).await?;
+        return Ok(());
+    };
+
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! {
+            "id" => id.to_string(),
+            "at" => format_next(at),
+        },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the reminders command.
+async fn reminders(ctx: Context, i18n: I18n, scheduler: Scheduler) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let jobs = scheduler.jobs_where(is_reminder).await;
+    if jobs.is_empty() {
+        ctx.respond(t("reminders_empty")).await?;
+        return Ok(());
+    }
+
+    let list = jobs
+        .iter()
+        .map(|job| {
+            format!(
+                "• <code>{0}</code> — {1}{2} — {3}",
+                job.id,
+                format_next(job.next),
+                if job.when.is_recurring() { " 🔁" } else { "" },
+                job.text()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.respond(Response::html(t_a(
+        "reminders_list",
+        hashmap! { "reminders" => list },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the delremind command.
+async fn del_remind(ctx: Context, i18n: I18n, scheduler: Scheduler) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let Some(id) = text
+        .split_whitespace()
+        .nth(1)
+        .and_then(|id| id.parse::<u32>().ok())
+    else {
+        ctx.respond(Response::html(t("delremind_usage"))).await?;
+        return Ok(());
+    };
+
+    let key = if scheduler.cancel_where(id, is_reminder).await {
+        "remind_cancelled"
+    } else {
+        "remind_not_found"
+    };
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! { "id" => id.to_string() },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the editremind command, changing when a reminder runs, its text or both.
+///
+/// Usage: `.editremind <id> [when] [text]`.
+async fn edit_remind(ctx: Context, i18n: I18n, scheduler: Scheduler) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let words = text.split_whitespace().skip(1).collect::<Vec<_>>();
+    let Some((id, words)) = words
+        .split_first()
+        .and_then(|(id, words)| Some((id.parse::<u32>().ok()?, words)))
+        .filter(|(_, words)| !words.is_empty())
+    else {
+        ctx.respond(Response::html(t("editremind_usage"))).await?;
+        return Ok(());
+    };
+
+    let (when, used) = match dates::parse(words) {
+        Some((when, used)) => (Some(when), used),
+        None => (None, 0),
+    };
+    let new_text = (words.len() > used).then(|| escape_html(&words[used..].join(" ")));
+
+    let Some(job) = scheduler
+        .edit_where(id, is_reminder, |job| {
+            if let Some(when) = when {
+                job.when = when;
+            }
+            if let (Some(new_text), Action::Remind(text, _)) = (new_text, &mut job.action) {
+                *text = new_text;
+            }
+        })
+        .await
+    else {
+        ctx.respond(Response::html(t_a(
+            "remind_not_found",
+            hashmap! { "id" => id.to_string() },
+        )))
+        .await?;
+        return Ok(());
+    };
+
+    ctx.respond(Response::html(t_a(
+        "remind_edited",
+        hashmap! {
+            "id" => id.to_string(),
+            "at" => format_next(job.next),
+            "text" => job.text().to_string(),
+        },
+    )))
+    .await?;
+
+    Ok(())
+}
@@ -8,22 +8,27 @@
 
 //! This module contains the reverse search command handler.
 
+use std::sync::Arc;
+
 use ferogram::{handler, Context, Filter, Result, Router};
 use grammers_client::{
-    types::{Downloadable, Media},
-    InputMessage,
+    types::{Media, Message},
+    Client, InputMessage,
 };
 use maplit::hashmap;
-use regex::Regex;
-use reqwest::{
-    header::{
-        HeaderMap, ACCEPT, ACCEPT_LANGUAGE, CONNECTION, HOST, UPGRADE_INSECURE_REQUESTS, USER_AGENT,
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        media_cache::MediaCache,
+        reverse_search::{Engine, Match, ReverseSearch},
     },
-    multipart::{Form, Part},
+    output::{Respond, Response},
+    utils::{self, escape_html, Args},
 };
 
-use crate::{filters, modules::i18n::I18n};
-
 /// Setup the reverse search command.
 pub fn setup() -> Router {
     Router::default().handler(
@@ -32,105 +37,262 @@ pub fn setup() -> Router {
     )
 }
 
-/// The URL of the Google Images search by image.
-const GOOGLE_IMAGE_URL: &str = "http://www.google.hr/searchbyimage/upload";
-
-/// Get the headers for the Google Images search by image.
-pub fn get_headers() -> HeaderMap {
-    let mut headers = HeaderMap::new();
-
-    headers.insert(HOST, "www.google.hr".parse().unwrap());
-    headers.insert(CONNECTION, "keep-alive".parse().unwrap());
-    headers.insert(UPGRADE_INSECURE_REQUESTS, "1".parse().unwrap());
-    headers.insert(USER_AGENT, "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/103.0.0.0 Safari/537.36".parse().unwrap());
-    headers.insert(
-        ACCEPT,
-        "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.9"
-            .parse()
-            .unwrap(),
-    );
-    headers.insert(
-        ACCEPT_LANGUAGE,
-        "pt-BR,pt;q=0.9,en-US;q=0.8,en;q=0.7,zh-TW;q=0.6,zh;q=0.5"
-            .parse()
-            .unwrap(),
-    );
-
-    headers.insert("Sec-Fetch-Site", "none".parse().unwrap());
-    headers.insert("Sec-Fetch-Mode", "navigate".parse().unwrap());
-    headers.insert("Sec-Fetch-User", "?1".parse().unwrap());
-    headers.insert("Sec-Fetch-Dest", "document".parse().unwrap());
-
-    headers.insert(
-        "sec-ch-ua",
-        "\"Chromium\";v=\"103\", \"Not A(Brand\";v=\"24\", \"Google Chrome\";v=\"103\""
-            .parse()
-            .unwrap(),
-    );
-    headers.insert("sec-ch-ua-mobile", "?0".parse().unwrap());
-
-    headers
-}
+/// How many photos of an album are searched at once.
+const ALBUM_CONCURRENCY: usize = 3;
+
+/// The most messages an album can have.
+const ALBUM_MAX_SIZE: i32 = 10;
+
+/// How many matches are shown for a single photo.
+const MAX_MATCHES: usize = 5;
+
+/// The biggest video a frame is extracted from, in bytes.
+const MAX_VIDEO_SIZE: i64 = 50 * 1024 * 1024;
+
+/// The MIME type of the animated stickers, which cannot be rendered.
+const TGS_MIME_TYPE: &str = "application/x-tgsticker";
 
 /// Handles the reverse search command.
-async fn reverse_search(ctx: Context, i18n: I18n) -> Result<()> {
+///
+/// Usage: `.rs [--engine <saucenao|yandex|tineye>]`, replying to a photo, a sticker,
+/// a video, a GIF or an album.
+async fn reverse_search(
+    ctx: Context,
+    i18n: I18n,
+    media_cache: MediaCache,
+    search: ReverseSearch,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
 
     let client = ctx.client();
-    let req_client = reqwest::Client::new();
-
-    if let Some(reply) = ctx.get_reply().await? {
-        if let Some(media) = reply.media() {
-            match media {
-                Media::Photo(ref photo) => {
-                    let msg = ctx.edit_or_reply(t("downloading_photo")).await?;
-
-                    let mut bytes = Vec::with_capacity(photo.size() as usize);
-
-                    let mut iter = client.iter_download(&Downloadable::Media(media));
-                    while let Some(chunk) = iter.next().await? {
-                        bytes.extend(chunk);
-                    }
-
-                    msg.edit(t("searching_photo")).await?;
-
-                    let request = req_client
-                        .post(GOOGLE_IMAGE_URL)
-                        .headers(get_headers())
-                        .multipart(
-                            Form::new()
-                                .part("encoded_image", Part::bytes(bytes))
-                                .part("image_content", Part::text("image/jpeg")),
-                        );
-                    if let Ok(response) = request.send().await {
-                        let text = response.text().await?;
-
-                        let re = Regex::new(r#"value="(.*?)" aria-label="Pesquisar""#).unwrap();
-                        let captures = re.captures(&text).unwrap();
-
-                        let url = captures.get(0).unwrap().as_str();
-                        let title = captures.get(1).unwrap().as_str();
-
-                        msg.edit(InputMessage::html(t_a(
-                            "search_result",
-                            hashmap! {"url" => url, "title" => title},
-                        )))
-                        .await?;
-                    } else {
-                        msg.edit(t("search_error")).await?;
-                    }
+
+    let text = ctx.command_text().unwrap();
+    let args = text
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, args)| args);
+    let Some(args) = Args::parse(args, &["engine"]) else {
+        ctx.reply(InputMessage::html(t("search_invalid_engine")))
+            .await?;
+        return Ok(());
+    };
+    let engine = match args.value("engine") {
+        Some(name) => match Engine::parse(name) {
+            Some(engine) if search.is_available(engine) => Some(engine),
+            Some(_) => {
+                ctx.reply(t("search_engine_unavailable")).await?;
+                return Ok(());
+            }
+            None => {
+                ctx.reply(InputMessage::html(t("search_invalid_engine")))
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let Some(reply) = ctx.get_reply().await? else {
+        ctx.reply(t("reply_needed")).await?;
+        return Ok(());
+    };
+    if reply
+        .media()
+        .as_ref()
+        .and_then(mime_type)
+        .is_some_and(|mime_type| mime_type == TGS_MIME_TYPE)
+    {
+        ctx.reply(t("search_animated_sticker")).await?;
+        return Ok(());
+    }
+    let medias = album_medias(client, &reply).await?;
+
+    match medias.len() {
+        0 => {
+            ctx.reply(t("reply_not_photo")).await?;
+        }
+        1 => {
+            let msg = ctx.edit_or_reply(t("downloading_photo")).await?;
+
+            let (media, mime_type) = &medias[0];
+            let bytes = media_cache.download(client, media).await?;
+            let bytes = match search.prepare(bytes, mime_type).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    log::error!("Failed to turn the media into an image: {}", e);
+                    ctx.respond_in(&msg, t("search_convert_error")).await?;
+                    return Ok(());
                 }
-                _ => {
-                    ctx.reply(t("reply_not_photo")).await?;
+            };
+
+            msg.edit(t("searching_photo")).await?;
+
+            let matches = match search.search(bytes, engine).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    log::error!("Failed to search the photo: {}", e);
+                    ctx.respond_in(&msg, t("search_error")).await?;
+                    return Ok(());
+                }
+            };
+            if matches.is_empty() {
+                ctx.respond_in(&msg, t("search_no_results")).await?;
+                return Ok(());
+            }
+
+            let lines = matches
+                .iter()
+                .take(MAX_MATCHES)
+                .enumerate()
+                .map(|(index, found)| render_match(index, found, &i18n))
+                .collect::<Vec<_>>();
+            let text = t_a("search_results", hashmap! { "results" => lines.join("\n") });
+
+            // The best match is shown as the photo, when there is a thumbnail.
+            match matches[0].thumbnail.clone() {
+                Some(thumbnail) => {
+                    ctx.send(
+                        InputMessage::html(text)
+                            .photo_url(thumbnail)
+                            .reply_to(utils::current_topic(&ctx).await),
+                    )
+                    .await?;
+                    msg.delete().await?;
+                }
+                None => {
+                    ctx.respond_in(&msg, Response::html(text)).await?;
                 }
             }
-        } else {
-            ctx.reply(t("reply_not_photo")).await?;
         }
-    } else {
-        ctx.reply(t("reply_needed")).await?;
+        count => {
+            let msg = ctx
+                .edit_or_reply(t_a(
+                    "searching_album",
+                    hashmap! {"count" => count.to_string()},
+                ))
+                .await?;
+
+            let semaphore = Arc::new(Semaphore::new(ALBUM_CONCURRENCY));
+            let mut tasks = JoinSet::new();
+
+            for (index, (media, mime_type)) in medias.into_iter().enumerate() {
+                let client = client.clone();
+                let search = search.clone();
+                let media_cache = media_cache.clone();
+                let semaphore = Arc::clone(&semaphore);
+
+                tasks.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await.ok()?;
+                    let bytes = media_cache.download(&client, &media).await.ok()?;
+                    let bytes = search.prepare(bytes, &mime_type).await.ok()?;
+
+                    // Only the best match of each photo is shown.
+                    let best = search
+                        .search(bytes, engine)
+                        .await
+                        .ok()?
+                        .into_iter()
+                        .next()?;
+
+                    Some((index, best))
+                });
+            }
+
+            let mut results = vec![None; count];
+            while let Some(result) = tasks.join_next().await {
+                if let Ok(Some((index, result))) = result {
+                    results[index] = Some(result);
+                }
+            }
+
+            let lines = results
+                .into_iter()
+                .enumerate()
+                .map(|(index, result)| match result {
+                    Some(found) => render_match(index, &found, &i18n),
+                    None => t_a(
+                        "search_album_item_error",
+                        hashmap! {"index" => (index + 1).to_string()},
+                    ),
+                })
+                .collect::<Vec<_>>();
+
+            ctx.respond_in(
+                &msg,
+                Response::html(t_a(
+                    "search_album_result",
+                    hashmap! {"count" => count.to_string(), "results" => lines.join("\n")},
+                )),
+            )
+            .await?;
+        }
     }
 
     Ok(())
 }
+
+/// Renders a match, with its engine and similarity.
+fn render_match(index: usize, found: &Match, i18n: &I18n) -> String {
+    let mut args = hashmap! {
+        "index" => (index + 1).to_string(),
+        "url" => escape_html(&found.url),
+        "title" => escape_html(&found.title),
+        "engine" => found.engine.name().to_string(),
+    };
+
+    match found.similarity {
+        Some(similarity) => {
+            args.insert("similarity", format!("{:.1}", similarity));
+            i18n.translate_with_args("search_match", args)
+        }
+        None => i18n.translate_with_args("search_match_no_score", args),
+    }
+}
+
+/// Returns the MIME type of a media, if it is visual.
+///
+/// The videos too big to be downloaded for a frame are left out.
+fn mime_type(media: &Media) -> Option<String> {
+    let document = match media {
+        Media::Photo(_) => return Some("image/jpeg".to_string()),
+        Media::Sticker(sticker) => &sticker.document,
+        Media::Document(document) => document,
+        _ => return None,
+    };
+
+    let mime_type = document.mime_type()?;
+    let visual = mime_type.starts_with("image/")
+        || (mime_type.starts_with("video/") && document.size() <= MAX_VIDEO_SIZE)
+        || mime_type == TGS_MIME_TYPE;
+
+    visual.then(|| mime_type.to_string())
+}
+
+/// Returns the visual medias of the album the message is part of, or its own one,
+/// with their MIME types.
+///
+/// The animated stickers are left out, as they cannot be searched.
+async fn album_medias(client: &Client, message: &Message) -> Result<Vec<(Media, String)>> {
+    let searchable = |media: Media| {
+        let mime_type = mime_type(&media).filter(|mime_type| mime_type != TGS_MIME_TYPE)?;
+        Some((media, mime_type))
+    };
+
+    let Some(grouped_id) = message.grouped_id() else {
+        return Ok(message.media().and_then(searchable).into_iter().collect());
+    };
+
+    // The messages of an album are sent in a row, so they are around the replied one.
+    let ids = ((message.id() - ALBUM_MAX_SIZE + 1)..(message.id() + ALBUM_MAX_SIZE))
+        .filter(|id| *id > 0)
+        .collect::<Vec<_>>();
+    let messages = client.get_messages_by_id(message.chat(), &ids).await?;
+
+    Ok(messages
+        .into_iter()
+        .flatten()
+        .filter(|message| message.grouped_id() == Some(grouped_id))
+        .filter_map(|message| message.media())
+        .filter_map(searchable)
+        .collect())
+}
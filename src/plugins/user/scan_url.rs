@@ -0,0 +1,107 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the URL scanner command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        url_scanner::{UrlScanner, Verdict},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The most URLs scanned at once.
+const MAX_URLS: usize = 5;
+
+/// Setup the URL scanner command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("scanurl").and(filters::sudoers())).then(scan_url),
+    )
+}
+
+/// Handles the URL scanner command.
+///
+/// Usage: `.scanurl <url>...`, or replying to a message with URLs.
+async fn scan_url(ctx: Context, i18n: I18n, scanner: UrlScanner) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    if !scanner.is_configured() {
+        ctx.respond(t("links_not_configured")).await?;
+        return Ok(());
+    }
+
+    let text = ctx.command_text().unwrap();
+    let mut urls = text
+        .split_whitespace()
+        .skip(1)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if urls.is_empty() {
+        if let Some(reply) = ctx.get_reply().await? {
+            urls = utils::message_urls(&reply);
+        }
+    }
+    urls.dedup();
+    urls.truncate(MAX_URLS);
+
+    if urls.is_empty() {
+        ctx.respond(Response::html(t("scanurl_usage"))).await?;
+        return Ok(());
+    }
+
+    let msg = ctx
+        .edit_or_reply(t_a(
+            "scanurl_scanning",
+            hashmap! { "provider" => scanner.provider().name().to_string() },
+        ))
+        .await?;
+
+    let mut lines = Vec::with_capacity(urls.len());
+    for url in urls {
+        let (key, details) = match scanner.scan(&url).await {
+            Ok(Verdict::Clean) => ("scanurl_clean", String::new()),
+            Ok(Verdict::Unknown) => ("scanurl_unknown", String::new()),
+            Ok(Verdict::Suspicious(details)) => ("scanurl_suspicious", details),
+            Ok(Verdict::Malicious(details)) => ("scanurl_malicious", details),
+            Err(e) => {
+                log::error!("Failed to scan a URL: {}", e);
+                ("scanurl_error", String::new())
+            }
+        };
+
+        lines.push(t_a(
+            key,
+            hashmap! {
+                "url" => escape_html(&url),
+                "details" => escape_html(&details),
+            },
+        ));
+    }
+
+    ctx.respond_in(
+        &msg,
+        Response::html(t_a(
+            "scanurl_result",
+            hashmap! {
+                "provider" => scanner.provider().name().to_string(),
+                "results" => lines.join("\n"),
+            },
+        )),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the schedule command handler.
+
+use chrono::{TimeDelta, Utc};
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        scheduler::{Scheduler, When},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html},
+    Action, Recipient,
+};
+
+/// The number of fields of a cron expression, with seconds.
+const CRON_FIELDS: usize = 6;
+
+/// Setup the schedule command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("schedule").and(filters::sudoers())).then(schedule),
+    )
+}
+
+/// Handles the schedule command.
+///
+/// Usage:
+/// - `.schedule <duration> <text>`
+/// - `.schedule cron <sec> <min> <hour> <day> <month> <weekday> <text>`
+/// - `.schedule list`
+/// - `.schedule del <id>`
+async fn schedule(
+    ctx: Context,
+    i18n: I18n,
+    scheduler: Scheduler,
+    recipient: Recipient,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let (when, message) = match args.as_slice() {
+        ["list"] | [] => {
+            let jobs = scheduler.jobs_where(|job| !job.is_internal()).await;
+            if jobs.is_empty() {
+                ctx.respond(t("schedule_empty")).await?;
+                return Ok(());
+            }
+
+            let list = jobs
+                .iter()
+                .map(|job| {
+                    format!(
+                        "{0} <code>{1}</code> — {2} UTC — {3}",
+                        if job.when.is_recurring() {
+                            "🔁"
+                        } else {
+                            "⏰"
+                        },
+                        job.id,
+                        job.next.format("%d/%m/%Y %H:%M:%S"),
+                        job.text()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            ctx.respond(Response::html(t_a(
+                "schedule_list",
+                hashmap! { "jobs" => list },
+            )))
+            .await?;
+            return Ok(());
+        }
+        ["del", id] => {
+            let key = match id.parse::<u32>() {
+                Ok(id) if scheduler.cancel_where(id, |job| !job.is_internal()).await => {
+                    "schedule_cancelled"
+                }
+                _ => "schedule_not_found",
+            };
+
+            ctx.respond(Response::html(t_a(
+                key,
+                hashmap! { "id" => id.to_string() },
+            )))
+            .await?;
+            return Ok(());
+        }
+        ["cron", rest @ ..] if rest.len() > CRON_FIELDS => (
+            When::Cron(rest[..CRON_FIELDS].join(" ")),
+            rest[CRON_FIELDS..].join(" "),
+        ),
+        [duration, rest @ ..] if !rest.is_empty() => match utils::parse_duration(duration) {
+            Some(duration) => (
+                When::At(Utc::now() + TimeDelta::from_std(duration)?),
+                rest.join(" "),
+            ),
+            None => {
+                ctx.respond(Response::html(t("schedule_usage"))).await?;
+                return Ok(());
+            }
+        },
+        _ => {
+            ctx.respond(Response::html(t("schedule_usage"))).await?;
+            return Ok(());
+        }
+    };
+
+    let chat = ctx.chat().expect("Chat not found");
+    let topic = utils::current_topic(&ctx).await;
+    let action = Action::SendHtml(chat.pack(), escape_html(&message));
+
+    match scheduler.schedule(when, recipient, topic, action).await {
+        Some(id) => {
+            ctx.respond(Response::html(t_a(
+                "schedule_added",
+                hashmap! { "id" => id.to_string() },
+            )))
+            .await?;
+        }
+        None => {
+            ctx.respond(t("schedule_invalid")).await?;
+        }
+    }
+
+    Ok(())
+}
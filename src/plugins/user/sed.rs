@@ -8,47 +8,332 @@
 
 //! This module contains the sed command handler.
 
+use std::{borrow::Cow, time::Duration};
+
 use ferogram::{filter, handler, Context, Filter, Result, Router};
-use grammers_client::InputMessage;
+use grammers_client::{grammers_tl_types as tl, types::Message, InputMessage};
+use maplit::hashmap;
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{generated::Generated, i18n::I18n},
+    utils::escape_html,
+};
+
+/// The most memory a compiled pattern can take, which bounds how long it runs.
+const SIZE_LIMIT: usize = 1024 * 1024;
+
+/// How long a substitution can take.
+const TIMEOUT: Duration = Duration::from_secs(2);
 
-use crate::{filters, modules::i18n::I18n};
+/// The most messages of the chat looked at for my last ones, however few of
+/// them are mine.
+const SCAN_LIMIT: usize = 500;
+
+/// The config of the sed plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "sed"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // How many of my last messages are searched when not replying.
+        // The ones with formatting are skipped, as editing them would lose it.
+        vec![
+            Key::new("search_limit", 20).validate(|value| match value.as_integer() {
+                Some(1..=100) => Ok(()),
+                _ => Err("must be between 1 and 100".to_string()),
+            }),
+        ]
+    }
+}
 
 /// Setup the sed command.
+///
+/// Editing the expression runs it again, so a mistyped pattern can be fixed.
 pub fn setup() -> Router {
-    Router::default().handler(
-        handler::new_message(filter::regex("^s/(.*)/(.*)(/(.*))?$").and(filters::sudoers()))
+    Router::default()
+        .handler(
+            handler::new_message(
+                filter::regex("(?s)^s/(.*)/(.*)(/(.*))?$")
+                    .and(filters::sudoers())
+                    .and(filters::not_generated())
+                    .and(filters::respond_to_edits("sed")),
+            )
             .then(sed),
-    )
+        )
+        .handler(
+            handler::message_edited(
+                filter::regex("(?s)^s/(.*)/(.*)(/(.*))?$")
+                    .and(filters::sudoers())
+                    .and(filters::not_generated())
+                    .and(filters::respond_to_edits("sed")),
+            )
+            .then(sed),
+        )
 }
 
 /// Handles the sed command.
-async fn sed(ctx: Context, i18n: I18n) -> Result<()> {
+///
+/// Usage: `s/pattern/replacement/flags`. The flags are `g` for every match, `i` to
+/// ignore the case, `m` for multi-line and `x` to ignore the whitespace of the pattern.
+///
+/// Replying to a message quotes it substituted. Otherwise my last message the pattern
+/// matches is edited in place, the ones with formatting left alone.
+async fn sed(ctx: Context, i18n: I18n, settings: PluginSettings) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
 
     let text = ctx.text().unwrap();
-    let args = text.split('/').skip(1).collect::<Vec<_>>();
+    let expression = match Expression::parse(&text) {
+        Ok(expression) => expression,
+        Err(ParseError::Syntax) => return Ok(()),
+        Err(ParseError::Flag(flag)) => {
+            ctx.reply(InputMessage::html(t_a(
+                "sed_invalid_flag",
+                hashmap! { "flag" => escape_html(&flag.to_string()) },
+            )))
+            .await?;
+            return Ok(());
+        }
+        Err(ParseError::Pattern(e)) => {
+            ctx.reply(InputMessage::html(t_a(
+                "sed_invalid_pattern",
+                hashmap! { "error" => escape_html(&e.to_string()) },
+            )))
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let message = ctx.message().await.unwrap();
+    let reply = ctx.get_reply().await?;
 
-    let (pattern, replacement, flags) = match args.len() {
-        2 => (args[0], args[1], ""),
-        3 => (args[0], args[1], args[2]),
-        _ => return Ok(()),
+    let targets = match &reply {
+        Some(reply) => vec![reply.clone()],
+        None if message.outgoing() => {
+            let limit = settings.get::<usize>("sed", "search_limit");
+            let mut iter = ctx
+                .client()
+                .iter_messages(message.chat().pack())
+                .limit(SCAN_LIMIT);
+
+            let mut targets = Vec::with_capacity(limit);
+            while let Some(target) = iter.next().await? {
+                if target.outgoing()
+                    && target.id() != message.id()
+                    && !target.text().is_empty()
+                    && !has_formatting(&target)
+                {
+                    targets.push(target);
+                    if targets.len() == limit {
+                        break;
+                    }
+                }
+            }
+
+            targets
+        }
+        None => {
+            ctx.reply(InputMessage::html(t("reply_needed"))).await?;
+            return Ok(());
+        }
     };
 
-    if let Some(reply) = ctx.get_reply().await? {
-        let new_text = if flags.contains('g') {
-            reply.html_text().replace(pattern, replacement)
-        } else {
-            reply.html_text().replacen(pattern, replacement, 1)
-        };
+    // The newest message the pattern matches is the one substituted.
+    let texts = targets
+        .iter()
+        .map(|target| target.text().to_string())
+        .collect::<Vec<_>>();
+    let task = tokio::task::spawn_blocking(move || {
+        texts
+            .iter()
+            .enumerate()
+            .find_map(|(index, text)| Some((index, expression.apply(text)?)))
+    });
+    let result = match tokio::time::timeout(TIMEOUT, task).await {
+        Ok(result) => result?,
+        Err(_) => {
+            ctx.reply(t("sed_timeout")).await?;
+            return Ok(());
+        }
+    };
+    let Some((index, new_text)) = result else {
+        ctx.reply(t("sed_no_match")).await?;
+        return Ok(());
+    };
 
-        ctx.edit_or_reply(InputMessage::html(format!(
-            "<blockquote>{}</blockquote>",
-            new_text
-        )))
-        .await?;
+    if reply.is_some() {
+        // The result may itself look like a substitution.
+        let sent = ctx
+            .edit_or_reply(InputMessage::html(format!(
+                "<blockquote>{}</blockquote>",
+                escape_html(&new_text)
+            )))
+            .await?;
+        Generated::shared().register(&sent);
     } else {
-        ctx.reply(InputMessage::html(t("reply_needed"))).await?;
+        targets[index].edit(InputMessage::text(new_text)).await?;
+        message.delete().await?;
     }
 
     Ok(())
 }
+
+/// Checks if a message has formatting, which an edit with its plain text
+/// would lose.
+///
+/// The links, mentions and the like are found again by Telegram.
+fn has_formatting(message: &Message) -> bool {
+    message.fmt_entities().is_some_and(|entities| {
+        entities.iter().any(|entity| {
+            !matches!(
+                entity,
+                tl::enums::MessageEntity::Url(_)
+                    | tl::enums::MessageEntity::Mention(_)
+                    | tl::enums::MessageEntity::Hashtag(_)
+                    | tl::enums::MessageEntity::Cashtag(_)
+                    | tl::enums::MessageEntity::BotCommand(_)
+                    | tl::enums::MessageEntity::Email(_)
+                    | tl::enums::MessageEntity::Phone(_)
+                    | tl::enums::MessageEntity::BankCard(_)
+            )
+        })
+    })
+}
+
+/// A substitution, `s/pattern/replacement/flags`.
+struct Expression {
+    /// The compiled pattern.
+    regex: Regex,
+    /// The replacement, with the references in the regex syntax.
+    replacement: String,
+    /// Whether every match is replaced, not only the first.
+    global: bool,
+}
+
+/// Why an expression could not be parsed.
+enum ParseError {
+    /// It is not a substitution.
+    Syntax,
+    /// It has an unknown flag.
+    Flag(char),
+    /// Its pattern does not compile.
+    Pattern(regex::Error),
+}
+
+impl Expression {
+    /// Parses an expression.
+    ///
+    /// The slashes inside the pattern and the replacement are escaped as `\/`.
+    fn parse(text: &str) -> std::result::Result<Self, ParseError> {
+        let text = text.strip_prefix("s/").ok_or(ParseError::Syntax)?;
+
+        let parts = split_parts(text);
+        let (pattern, replacement, flags) = match parts.as_slice() {
+            [pattern, replacement] => (pattern, replacement, ""),
+            [pattern, replacement, flags] => (pattern, replacement, flags.as_str()),
+            _ => return Err(ParseError::Syntax),
+        };
+
+        let mut builder = RegexBuilder::new(pattern);
+        builder.size_limit(SIZE_LIMIT).dfa_size_limit(SIZE_LIMIT);
+
+        let mut global = false;
+        for flag in flags.trim_end().chars() {
+            match flag {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                'm' => {
+                    builder.multi_line(true);
+                }
+                'x' => {
+                    builder.ignore_whitespace(true);
+                }
+                _ => return Err(ParseError::Flag(flag)),
+            }
+        }
+
+        Ok(Self {
+            regex: builder.build().map_err(ParseError::Pattern)?,
+            replacement: replacement_references(replacement),
+            global,
+        })
+    }
+
+    /// Applies the substitution to a text.
+    ///
+    /// Returns `None` if the pattern did not match.
+    fn apply(&self, text: &str) -> Option<String> {
+        let new_text = if self.global {
+            self.regex.replace_all(text, self.replacement.as_str())
+        } else {
+            self.regex.replace(text, self.replacement.as_str())
+        };
+
+        match new_text {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(new_text) => Some(new_text),
+        }
+    }
+}
+
+/// Splits the parts of an expression on the unescaped slashes.
+///
+/// The escaped slashes are unescaped, the other escapes being kept for the regex.
+fn split_parts(text: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().expect("there is always a part");
+
+        match c {
+            '\\' => match chars.next() {
+                Some('/') => part.push('/'),
+                Some(c) => {
+                    part.push('\\');
+                    part.push(c);
+                }
+                None => part.push('\\'),
+            },
+            '/' => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+
+    parts
+}
+
+/// Turns the sed references of a replacement, such as `\1`, into the regex ones,
+/// such as `${1}`. The regex references, such as `$1`, are kept as they are.
+fn replacement_references(replacement: &str) -> String {
+    let mut result = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    result.push_str("${");
+                    result.push(digit);
+                    result.push('}');
+                }
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('$') => result.push_str("$$"),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            },
+            c => result.push(c),
+        }
+    }
+
+    result
+}
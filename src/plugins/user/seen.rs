@@ -0,0 +1,232 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the seen and read receipts commands handlers.
+
+use chrono::{DateTime, Local, Utc};
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{grammers_tl_types as tl, types::Chat, Client, Update};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, receipts::Receipts},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The most users listed by the read stats.
+const MAX_USERS: usize = 15;
+
+/// Setup the seen and read receipts commands.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("seen").and(filters::sudoers())).then(seen))
+        .handler(
+            handler::new_message(filters::command("readstats").and(filters::sudoers()))
+                .then(read_stats),
+        )
+}
+
+/// Setup the read receipts watcher.
+///
+/// It records the outgoing private messages and the read history updates.
+pub fn watcher() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::outgoing_private()).then(sent))
+        .handler(handler::new_update(filters::read_outbox()).then(read))
+}
+
+/// Handles the seen command, telling when a user was last online, as far as
+/// their privacy settings show it.
+///
+/// Usage: `.seen @username`, or replying to a message.
+async fn seen(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let target = text.split_whitespace().nth(1);
+    let chat = match target {
+        Some(username) => {
+            ctx.client()
+                .resolve_username(username.trim_start_matches('@'))
+                .await?
+        }
+        None => match ctx.get_reply().await? {
+            Some(reply) => reply.sender(),
+            None => None,
+        },
+    };
+    let Some(Chat::User(user)) = chat else {
+        ctx.respond(Response::html(t("seen_usage"))).await?;
+        return Ok(());
+    };
+
+    // The sender of a message may come without its status.
+    let status = match fetch_status(ctx.client(), &Chat::User(user.clone())).await {
+        Ok(status) => status,
+        Err(e) => {
+            log::warn!("Failed to fetch the status of {}: {}", user.id(), e);
+            user.raw.status.clone()
+        }
+    };
+
+    ctx.respond(Response::html(t_a(
+        "seen_status",
+        hashmap! {
+            "name" => escape_html(&user.full_name()),
+            "status" => render_status(&i18n, status.as_ref()),
+        },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Fetches the current status of a user.
+async fn fetch_status(client: &Client, user: &Chat) -> Result<Option<tl::enums::UserStatus>> {
+    let input_user = utils::input_user(user)?;
+
+    let users = client
+        .invoke(&tl::functions::users::GetUsers {
+            id: vec![input_user],
+        })
+        .await?;
+
+    Ok(users.into_iter().find_map(|user| match user {
+        tl::enums::User::User(user) => user.status,
+        tl::enums::User::Empty(_) => None,
+    }))
+}
+
+/// Renders the last seen status of a user.
+///
+/// The users hiding it only show roughly when, or nothing at all.
+fn render_status(i18n: &I18n, status: Option<&tl::enums::UserStatus>) -> String {
+    let t = |key: &str| i18n.translate(key);
+
+    match status {
+        Some(tl::enums::UserStatus::Online(_)) => t("seen_online"),
+        Some(tl::enums::UserStatus::Offline(status)) => {
+            let Some(at) = DateTime::<Utc>::from_timestamp(status.was_online as i64, 0) else {
+                return t("seen_hidden");
+            };
+            let ago = (Utc::now() - at).to_std().unwrap_or_default();
+
+            i18n.translate_with_args(
+                "seen_offline",
+                hashmap! {
+                    "date" => at.with_timezone(&Local).format("%d/%m/%Y %H:%M").to_string(),
+                    "ago" => utils::human_readable_duration(ago),
+                },
+            )
+        }
+        Some(tl::enums::UserStatus::Recently(_)) => t("seen_recently"),
+        Some(tl::enums::UserStatus::LastWeek(_)) => t("seen_last_week"),
+        Some(tl::enums::UserStatus::LastMonth(_)) => t("seen_last_month"),
+        Some(tl::enums::UserStatus::Empty) | None => t("seen_hidden"),
+    }
+}
+
+/// Handles the read stats command.
+///
+/// Usage: `.readstats [on|off]`.
+async fn read_stats(ctx: Context, i18n: I18n, receipts: Receipts) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    match text.split_whitespace().nth(1) {
+        Some(state @ ("on" | "off")) => {
+            let enabled = state == "on";
+            receipts.set_enabled(enabled).await;
+
+            let key = if enabled {
+                "readstats_enabled"
+            } else {
+                "readstats_disabled"
+            };
+            ctx.respond(t(key)).await?;
+        }
+        Some(_) => {
+            ctx.respond(Response::html(t("readstats_usage"))).await?;
+        }
+        None => {
+            let stats = receipts.stats().await;
+            if stats.is_empty() {
+                let key = if receipts.is_enabled().await {
+                    "readstats_empty"
+                } else {
+                    "readstats_off"
+                };
+                ctx.respond(Response::html(t(key))).await?;
+                return Ok(());
+            }
+
+            let mut text = t_a(
+                "readstats_header",
+                hashmap! { "waiting" => receipts.waiting().await.to_string() },
+            );
+            for stats in stats.iter().take(MAX_USERS) {
+                let last_read = stats
+                    .last_read
+                    .with_timezone(&Local)
+                    .format("%d/%m %H:%M")
+                    .to_string();
+
+                text += "\n";
+                text += &t_a(
+                    "readstats_line",
+                    hashmap! {
+                        "id" => stats.user_id.to_string(),
+                        "name" => escape_html(&stats.name),
+                        "reads" => stats.reads.to_string(),
+                        "average" => utils::human_readable_duration(stats.average),
+                        "median" => utils::human_readable_duration(stats.median),
+                        "last_read" => last_read,
+                    },
+                );
+            }
+
+            ctx.respond(Response::html(text)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Records an outgoing private message, to know when it is read.
+async fn sent(ctx: Context, receipts: Receipts) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+
+    receipts
+        .sent(
+            message.chat().id(),
+            message.chat().name(),
+            message.id(),
+            message.date(),
+        )
+        .await;
+
+    Ok(())
+}
+
+/// Records that a user read my private messages.
+async fn read(update: Update, receipts: Receipts) -> Result<()> {
+    let Update::Raw(tl::enums::Update::ReadHistoryOutbox(update)) = update else {
+        return Ok(());
+    };
+    let tl::enums::Peer::User(peer) = update.peer else {
+        return Ok(());
+    };
+
+    receipts.read(peer.user_id, update.max_id, Utc::now()).await;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the song command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{grammers_tl_types as tl, InputMessage};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{ghost::Ghost, i18n::I18n, songs::Songs, ytdl::Ytdl},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// Setup the song command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["song", "music"]).and(filters::sudoers()))
+            .then(song),
+    )
+}
+
+/// Handles the song command.
+async fn song(ctx: Context, i18n: I18n, songs: Songs, ytdl: Ytdl, ghost: Ghost) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let audio = args.first().is_some_and(|arg| *arg == "-a");
+    if audio {
+        args.remove(0);
+    }
+
+    let mut query = args.join(" ");
+    if query.is_empty() {
+        if let Some(reply) = ctx.get_reply().await? {
+            query = reply.text().trim().to_string();
+        }
+    }
+
+    if query.is_empty() {
+        ctx.respond(t("song_no_query")).await?;
+        return Ok(());
+    }
+
+    let msg = ctx.edit_or_reply(t("song_searching")).await?;
+
+    let song = match songs.resolve(&query).await {
+        Ok(song) => song,
+        Err(e) => {
+            log::error!("Failed to resolve song: {}", e);
+            ctx.respond_in(&msg, t("song_not_found")).await?;
+
+            return Ok(());
+        }
+    };
+
+    let links = song
+        .links
+        .iter()
+        .map(|(name, url)| format!("<a href=\"{0}\">{1}</a>", url, name))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let text = t_a(
+        "song_result",
+        hashmap! {
+            "title" => escape_html(&song.title),
+            "artist" => escape_html(&song.artist),
+            "links" => links,
+            "page_url" => song.page_url.clone(),
+        },
+    );
+
+    match song.thumbnail {
+        Some(ref thumbnail) => {
+            ctx.send(
+                InputMessage::html(&text)
+                    .photo_url(thumbnail)
+                    .reply_to(utils::current_topic(&ctx).await),
+            )
+            .await?;
+            msg.delete().await?;
+        }
+        None => {
+            ctx.respond_in(&msg, Response::html(&text)).await?;
+        }
+    }
+
+    if audio {
+        let status = ctx.reply(t("song_downloading")).await?;
+        ghost
+            .action(
+                ctx.client(),
+                &ctx.chat().expect("Chat not found"),
+                tl::enums::SendMessageAction::SendMessageUploadAudioAction(
+                    tl::types::SendMessageUploadAudioAction { progress: 0 },
+                ),
+            )
+            .await?;
+
+        match ytdl.download_audio(&song.download_source()).await {
+            Ok(path) => {
+                let file = ctx.client().upload_file(&path).await?;
+
+                ctx.send(
+                    InputMessage::html(&text)
+                        .document(file)
+                        .reply_to(utils::current_topic(&ctx).await),
+                )
+                .await?;
+                status.delete().await?;
+
+                tokio::fs::remove_file(&path).await?;
+            }
+            Err(e) => {
+                log::error!("Failed to download song: {}", e);
+                ctx.respond_in(&status, t("song_download_error")).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the sudoers management commands handlers.
+
+use ferogram::{filter, handler, Context, Filter, Result, Router};
+use grammers_client::types::Chat;
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, sudoers::Sudoers},
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the sudoers management commands.
+///
+/// Only the owner can manage the sudoers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("addsudo").and(filter::me)).then(add_sudo))
+        .handler(handler::new_message(filters::command("delsudo").and(filter::me)).then(del_sudo))
+        .handler(handler::new_message(filters::command("sudoers").and(filter::me)).then(sudoers))
+}
+
+/// Resolves the target user, either the replied message sender, a `@username` or an ID.
+async fn resolve_target(ctx: &Context) -> Result<Option<(i64, String)>> {
+    let text = ctx.command_text().unwrap();
+
+    match text.split_whitespace().nth(1) {
+        Some(username) if username.starts_with('@') => Ok(ctx
+            .client()
+            .resolve_username(&username[1..])
+            .await?
+            .filter(|chat| matches!(chat, Chat::User(_)))
+            .map(|chat| (chat.id(), chat.name().to_string()))),
+        Some(id) => Ok(id.parse::<i64>().ok().map(|id| (id, id.to_string()))),
+        None => Ok(match ctx.get_reply().await? {
+            Some(reply) => reply
+                .sender()
+                .filter(|chat| matches!(chat, Chat::User(_)))
+                .map(|chat| (chat.id(), chat.name().to_string())),
+            None => None,
+        }),
+    }
+}
+
+/// Handles the addsudo command.
+async fn add_sudo(ctx: Context, i18n: I18n, sudoers: Sudoers) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let Some((id, name)) = resolve_target(&ctx).await? else {
+        ctx.respond(Response::html(t("addsudo_usage"))).await?;
+        return Ok(());
+    };
+
+    let key = if sudoers.add(id, name.clone()).await {
+        "sudo_added"
+    } else {
+        "sudo_already_added"
+    };
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! { "name" => escape_html(&name) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the delsudo command.
+async fn del_sudo(ctx: Context, i18n: I18n, sudoers: Sudoers) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let Some((id, name)) = resolve_target(&ctx).await? else {
+        ctx.respond(Response::html(t("delsudo_usage"))).await?;
+        return Ok(());
+    };
+
+    let key = if sudoers.is_owner(id) {
+        "sudo_owner"
+    } else if sudoers.is_configured(id) {
+        "sudo_configured"
+    } else if sudoers.remove(id).await {
+        "sudo_removed"
+    } else {
+        "sudo_not_found"
+    };
+    ctx.respond(Response::html(t_a(
+        key,
+        hashmap! { "name" => escape_html(&name) },
+    )))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the sudoers command.
+async fn sudoers(ctx: Context, i18n: I18n, sudoers: Sudoers) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let list = sudoers.list().await;
+    if list.is_empty() {
+        ctx.respond(t("sudoers_empty")).await?;
+        return Ok(());
+    }
+
+    let list = list
+        .iter()
+        .map(|sudoer| {
+            format!(
+                "• <b>{0}</b> (<code>{1}</code>)",
+                escape_html(&sudoer.name),
+                sudoer.id
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ctx.respond(Response::html(t_a(
+        "sudoers_list",
+        hashmap! { "sudoers" => list },
+    )))
+    .await?;
+
+    Ok(())
+}
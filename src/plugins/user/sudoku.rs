@@ -0,0 +1,73 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the sudoku command handler.
+
+use ferogram::{handler, Context, Result, Router};
+use grammers_client::{reply_markup, types::InputMessage};
+
+use crate::{
+    filters,
+    modules::{
+        games::{Difficulty, GameManager, Player, Sudoku},
+        i18n::I18n,
+    },
+    output::{Respond, Response},
+    utils::{self, sudoku_to_buttons},
+    Priority, Sender,
+};
+
+/// Setup the sudoku command.
+pub fn setup() -> Router {
+    Router::default().handler(handler::new_message(filters::command("sudoku")).then(sudoku))
+}
+
+/// Handles the sudoku command.
+///
+/// Usage: `.sudoku [easy|medium|hard]`.
+async fn sudoku(ctx: Context, i18n: I18n, manager: GameManager, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let text = ctx.command_text().unwrap();
+    let difficulty = match text.split_whitespace().nth(1) {
+        Some(name) => match Difficulty::parse(name) {
+            Some(difficulty) => difficulty,
+            None => {
+                ctx.respond(Response::html(t("sudoku_usage"))).await?;
+                return Ok(());
+            }
+        },
+        None => Difficulty::Medium,
+    };
+
+    let sender = ctx.sender().expect("Sender not found");
+    let game = Sudoku::new(manager.new_id().await, Player::new(&sender), difficulty).into_game();
+
+    let buttons = sudoku_to_buttons(game.as_sudoku().unwrap(), game.id(), None);
+    if !utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .with_priority(Priority::High)
+            .in_topic(utils::current_topic(&ctx).await)
+            .send_via_bot_message(
+                ctx.chat().expect("Chat not found"),
+                InputMessage::html(game.generate_text())
+                    .reply_markup(&reply_markup::inline(buttons)),
+            ),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+
+    manager.add_game(game, None).await;
+
+    Ok(())
+}
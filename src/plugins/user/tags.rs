@@ -0,0 +1,141 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the saved messages tags handlers.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{
+    reply_markup,
+    types::{InputMessage, Message},
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, tags::Tags},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+    Sender,
+};
+
+/// The maximum length of the message preview.
+const PREVIEW_LENGTH: usize = 50;
+
+/// Setup the tags handlers.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::saved_forward()).then(saved_forward))
+        .handler(handler::new_message(filters::command("tag").and(filters::sudoers())).then(tag))
+        .handler(
+            handler::new_message(filters::command("tagged").and(filters::sudoers())).then(tagged),
+        )
+}
+
+/// Builds a short preview of a message.
+fn preview(message: &Message) -> String {
+    let text = message.text().trim();
+
+    if text.is_empty() {
+        format!("📎 #{}", message.id())
+    } else {
+        text.chars().take(PREVIEW_LENGTH).collect()
+    }
+}
+
+/// Handles a message forwarded to my saved messages.
+async fn saved_forward(message: Message, i18n: I18n, tags: Tags, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    tags.track(message.id(), preview(&message)).await;
+
+    let buttons = tags.keyboard(message.id()).await;
+    if buttons.is_empty() {
+        return Ok(());
+    }
+
+    tx.send(crate::Message::to_bot().send_via_bot_message(
+        message.chat(),
+        InputMessage::html(t("tag_choose")).reply_markup(&reply_markup::inline(buttons)),
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Handles the tag command.
+async fn tag(ctx: Context, i18n: I18n, tags: Tags) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let Some(name) = text.split_whitespace().nth(1) else {
+        ctx.respond(t("tag_no_name")).await?;
+        return Ok(());
+    };
+
+    if let Some(reply) = ctx.get_reply().await? {
+        tags.track(reply.id(), preview(&reply)).await;
+
+        let key = match tags.toggle(reply.id(), name).await {
+            Some(true) => "tag_added",
+            _ => "tag_removed",
+        };
+
+        ctx.respond(Response::html(t_a(
+            key,
+            hashmap! { "tag" => escape_html(name) },
+        )))
+        .await?;
+    } else {
+        ctx.respond(Response::html(t("reply_needed"))).await?;
+    }
+
+    Ok(())
+}
+
+/// Handles the tagged command.
+async fn tagged(ctx: Context, i18n: I18n, tags: Tags, tx: Sender) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let text = ctx.command_text().unwrap();
+    let Some(name) = text.split_whitespace().nth(1) else {
+        let known = tags
+            .known_tags()
+            .await
+            .iter()
+            .map(|tag| format!("<code>{}</code>", escape_html(tag)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        ctx.respond(Response::html(format!("{0}\n{1}", t("tag_list"), known)))
+            .await?;
+        return Ok(());
+    };
+
+    let sender = ctx.sender().expect("Sender not found");
+    let (text, buttons) = tags.render_page(&i18n, sender.id(), name, 0).await;
+
+    if !utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .in_topic(utils::current_topic(&ctx).await)
+            .send_via_bot_message(
+                ctx.chat().expect("Chat not found"),
+                InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)),
+            ),
+    )
+    .await?
+    {
+        return Ok(());
+    }
+    ctx.delete().await?;
+
+    Ok(())
+}
@@ -0,0 +1,285 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the terminal command handler.
+
+use std::{
+    process::Stdio,
+    time::{Duration, Instant},
+};
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::InputMessage;
+use maplit::hashmap;
+use tokio::{io::AsyncReadExt, process::Command, sync::watch};
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::i18n::I18n,
+    output::{Output, Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The most bytes of the partial output shown while the command runs, its end.
+const PREVIEW_LENGTH: usize = 1500;
+
+/// The most bytes kept of the output.
+const MAX_OUTPUT: usize = 1024 * 1024;
+
+/// How long the output is still read after the command was killed.
+const KILL_GRACE: Duration = Duration::from_secs(1);
+
+/// The config of the terminal plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "term"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        vec![
+            // The shell the commands run in.
+            Key::new("shell", "bash"),
+            // The directory the commands run in, empty for the bot's own.
+            Key::new("working_dir", ""),
+            // In seconds, how long a command can run, zero for no limit.
+            Key::new("timeout", 300).validate(|value| match value.as_integer() {
+                Some(0..) => Ok(()),
+                _ => Err("must not be negative".to_string()),
+            }),
+            // In seconds, how often the status message is updated.
+            Key::new("update_interval", 3).validate(|value| match value.as_integer() {
+                Some(2..=60) => Ok(()),
+                _ => Err("must be between 2 and 60".to_string()),
+            }),
+        ]
+    }
+}
+
+/// How a command ended.
+enum Exit {
+    /// It exited with a code.
+    Code(i32),
+    /// It was killed by a signal.
+    Signal,
+    /// It took too long and was killed.
+    TimedOut,
+}
+
+/// Setup the terminal commands.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::commands(&["sh", "term"]).and(filters::sudoers())).then(term),
+    )
+}
+
+/// Handles the terminal commands.
+///
+/// Usage: `.sh <command>` or `.term <command>`. The command runs in the shell of
+/// the host, unlike `.eval`, the status message showing its combined output
+/// meanwhile.
+async fn term(ctx: Context, i18n: I18n, settings: PluginSettings) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap_or_default();
+    let to_file = ctx.wants_file();
+    let text = text.trim();
+    let input = text
+        .split_once(char::is_whitespace)
+        .map(|(_, command)| command.trim())
+        .unwrap_or_default();
+
+    if input.is_empty() {
+        ctx.reply(t("term_no_command")).await?;
+        return Ok(());
+    }
+
+    let working_dir = settings.get::<String>("term", "working_dir");
+    let working_dir = if working_dir.is_empty() {
+        ".".to_string()
+    } else {
+        working_dir
+    };
+
+    // The standard error is sent to the same pipe, keeping the order of both.
+    let spawned = Command::new(settings.get::<String>("term", "shell"))
+        .arg("-c")
+        .arg(format!("exec 2>&1\n{}", input))
+        .current_dir(&working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            log::error!("Failed to run the terminal command: {}", e);
+            ctx.respond(Response::html(t_a(
+                "term_failure",
+                hashmap! { "error" => escape_html(&e.to_string()) },
+            )))
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let msg = ctx
+        .edit_or_reply(InputMessage::html(t_a(
+            "term_running",
+            hashmap! { "input" => escape_html(input) },
+        )))
+        .await?;
+
+    let (tx, mut rx) = watch::channel(String::new());
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut reader = tokio::task::spawn(async move {
+        let mut output = Vec::new();
+        let mut buffer = vec![0; 8 * 1024];
+        let mut truncated = false;
+
+        while let Ok(read) = stdout.read(&mut buffer).await {
+            if read == 0 {
+                break;
+            }
+
+            let kept = &buffer[..read.min(MAX_OUTPUT.saturating_sub(output.len()))];
+            truncated |= kept.len() < read;
+            tx.send_modify(|preview| {
+                utils::push_tail(
+                    preview,
+                    &String::from_utf8_lossy(&buffer[..read]),
+                    PREVIEW_LENGTH,
+                )
+            });
+            output.extend_from_slice(kept);
+        }
+
+        (String::from_utf8_lossy(&output).into_owned(), truncated)
+    });
+
+    let timeout = settings.get::<u64>("term", "timeout");
+    let time = Instant::now();
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(settings.get("term", "update_interval")));
+    let status = {
+        let wait = async {
+            if timeout == 0 {
+                return Ok(child.wait().await);
+            }
+
+            tokio::time::timeout(Duration::from_secs(timeout), child.wait()).await
+        };
+        tokio::pin!(wait);
+
+        loop {
+            tokio::select! {
+                status = &mut wait => break status,
+                _ = interval.tick() => {
+                    if rx.has_changed().unwrap_or(false) {
+                        let output = rx.borrow_and_update().clone();
+                        // A failed edit must not stop the command.
+                        let _ = msg
+                            .edit(InputMessage::html(t_a(
+                                "term_partial",
+                                hashmap! {
+                                    "input" => escape_html(input),
+                                    "output" => escape_html(output.trim_ascii()),
+                                },
+                            )))
+                            .await;
+                    }
+                }
+            }
+        }
+    };
+
+    let exit = match status {
+        Ok(status) => match status?.code() {
+            Some(code) => Exit::Code(code),
+            None => Exit::Signal,
+        },
+        Err(_) => {
+            if let Some(pid) = child.id() {
+                // SAFETY: the group was created with the child, whose ID it has.
+                unsafe {
+                    libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+                }
+            }
+            let _ = child.wait().await;
+
+            Exit::TimedOut
+        }
+    };
+    let elapsed = time.elapsed().as_secs_f64();
+
+    // A process left in the background may still hold the pipe.
+    let (output, truncated) = match tokio::time::timeout(KILL_GRACE, &mut reader).await {
+        Ok(output) => output?,
+        Err(_) => {
+            reader.abort();
+            (rx.borrow().clone(), true)
+        }
+    };
+    let output = output.trim_ascii().to_string();
+
+    let mut notes = Vec::new();
+    match exit {
+        Exit::Code(0) => {}
+        Exit::Code(code) => notes.push(t_a(
+            "term_exit_code",
+            hashmap! { "code" => code.to_string() },
+        )),
+        Exit::Signal => notes.push(t("term_signal")),
+        Exit::TimedOut => notes.push(t_a(
+            "term_timeout",
+            hashmap! { "timeout" => timeout.to_string() },
+        )),
+    }
+    if truncated {
+        notes.push(t("term_truncated"));
+    }
+    let notes = notes
+        .into_iter()
+        .map(|note| format!("\n\n{}", note))
+        .collect::<String>();
+
+    Output {
+        html: format!(
+            "{0}{1}",
+            t_a(
+                "term_output",
+                hashmap! {
+                    "input" => escape_html(input),
+                    "output" => escape_html(&output),
+                    "time" => format!("{:.2}", elapsed),
+                },
+            ),
+            notes
+        ),
+        caption: format!(
+            "{0}\n\n{1}{2}",
+            t_a(
+                "term_input",
+                hashmap! { "input" => escape_html(input), "time" => format!("{:.2}", elapsed) },
+            ),
+            t("term_output_file"),
+            notes
+        ),
+        content: output,
+        file_name: "output.txt".to_string(),
+    }
+    .send(&ctx, Some(&msg), to_file)
+    .await?;
+
+    Ok(())
+}
@@ -13,9 +13,13 @@ use grammers_client::{reply_markup, types::InputMessage};
 
 use crate::{
     filters,
-    modules::games::{GameManager, Player, TicTacToe},
-    utils::board_to_buttons,
-    Sender,
+    modules::{
+        chat_settings::ChatSettings,
+        games::{GameManager, Player, TicTacToe},
+        i18n::I18n,
+    },
+    utils::{self, board_to_buttons, BoardLayout},
+    Priority, Sender,
 };
 
 /// Setup the tic tac toe command.
@@ -25,7 +29,13 @@ pub fn setup() -> Router {
 }
 
 /// Handles the tic tac toe command.
-async fn tic_tac_toe(ctx: Context, manager: GameManager, tx: Sender) -> Result<()> {
+async fn tic_tac_toe(
+    ctx: Context,
+    i18n: I18n,
+    manager: GameManager,
+    settings: ChatSettings,
+    tx: Sender,
+) -> Result<()> {
     let sender = ctx.sender().expect("Sender not found");
     let mut players = vec![Player::new(&sender)];
 
@@ -35,18 +45,33 @@ async fn tic_tac_toe(ctx: Context, manager: GameManager, tx: Sender) -> Result<(
         ));
     }
 
-    let mut ttt = TicTacToe::new(manager.new_id(), players);
+    let chat = ctx.chat().expect("Chat not found");
+    let symbols = settings.game_symbols(chat.id()).await;
+
+    let mut ttt = TicTacToe::new(manager.new_id().await, players).with_symbols(symbols);
     ttt.generate_board(3..=3);
     let game = ttt.into_game();
 
-    let buttons = board_to_buttons(game.board(), game.id());
-    tx.send(crate::Message::to_bot().send_via_bot_message(
-        ctx.chat().expect("Chat not found"),
-        InputMessage::html(game.generate_text()).reply_markup(&reply_markup::inline(buttons)),
-    ))
-    .await?;
+    let buttons = board_to_buttons(game.board(), "ttt", game.id(), BoardLayout::Cells);
+    if !utils::send_or_busy(
+        &ctx,
+        &tx,
+        &i18n,
+        crate::Message::to_bot()
+            .with_priority(Priority::High)
+            .in_topic(utils::current_topic(&ctx).await)
+            .send_via_bot_message(
+                chat,
+                InputMessage::html(game.generate_text())
+                    .reply_markup(&reply_markup::inline(buttons)),
+            ),
+    )
+    .await?
+    {
+        return Ok(());
+    }
 
-    manager.add_game(game);
+    manager.add_game(game, None).await;
 
     Ok(())
 }
@@ -0,0 +1,87 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the todo command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, todo::Todos},
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the todo command.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(handler::new_message(filters::command("todo").and(filters::sudoers())).then(todo))
+}
+
+/// Handles the todo command.
+///
+/// Usage: `.todo [list]`, `.todo add <text>` or `.todo done <id>`.
+async fn todo(ctx: Context, i18n: I18n, todos: Todos) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.splitn(3, char::is_whitespace).skip(1);
+
+    match (args.next(), args.next().map(str::trim)) {
+        (Some("add"), Some(item)) if !item.is_empty() => {
+            let id = todos.add(item.to_string()).await;
+
+            ctx.respond(Response::html(t_a(
+                "todo_added",
+                hashmap! { "id" => id.to_string(), "text" => escape_html(item) },
+            )))
+            .await?;
+        }
+        (Some("done"), Some(id)) => match id.parse::<u32>() {
+            Ok(id) => match todos.done(id).await {
+                Some(item) => {
+                    ctx.respond(Response::html(t_a(
+                        "todo_done",
+                        hashmap! { "text" => escape_html(&item.text) },
+                    )))
+                    .await?;
+                }
+                None => {
+                    ctx.respond(Response::html(t_a(
+                        "todo_not_found",
+                        hashmap! { "id" => id.to_string() },
+                    )))
+                    .await?;
+                }
+            },
+            Err(_) => {
+                ctx.respond(Response::html(t("todo_usage"))).await?;
+            }
+        },
+        (Some("list"), None) | (None, None) => {
+            let items = todos.list().await;
+            if items.is_empty() {
+                ctx.respond(t("todo_empty")).await?;
+                return Ok(());
+            }
+
+            ctx.respond(Response::html(t_a(
+                "todo_list",
+                hashmap! { "items" => Todos::render_items(&items) },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("todo_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
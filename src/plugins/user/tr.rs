@@ -0,0 +1,104 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the tr command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, translator::Translator},
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the tr command.
+///
+/// Editing the command translates again, so a typo can be fixed.
+pub fn setup() -> Router {
+    Router::default()
+        .handler(
+            handler::new_message(
+                filters::commands(&["tr", "translate"])
+                    .and(filters::sudoers())
+                    .and(filters::respond_to_edits("tr")),
+            )
+            .then(tr),
+        )
+        .handler(
+            handler::message_edited(
+                filters::commands(&["tr", "translate"])
+                    .and(filters::sudoers())
+                    .and(filters::respond_to_edits("tr")),
+            )
+            .then(tr),
+        )
+}
+
+/// Handles the tr command.
+///
+/// Usage: `.tr [language] <text>`, or replying to a message. The text is
+/// translated to the current locale when no language is given.
+async fn tr(ctx: Context, i18n: I18n, translator: Translator) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let args = text
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, args)| args.trim());
+    let reply = ctx.get_reply().await?;
+
+    // The first word is the language when it looks like a language code and
+    // something is left to translate.
+    let (target, text) = match args.split_once(char::is_whitespace) {
+        Some((language, text)) if is_language(language) => (language.to_string(), text.trim()),
+        None if is_language(args) && reply.is_some() => (args.to_string(), ""),
+        _ => (i18n.locale(), args),
+    };
+    let text = match (text, &reply) {
+        ("", Some(reply)) => reply.text().to_string(),
+        (text, _) => text.to_string(),
+    };
+
+    if text.trim().is_empty() {
+        ctx.respond(Response::html(t("tr_usage"))).await?;
+        return Ok(());
+    }
+
+    match translator.translate(&text, &target).await {
+        Ok(translation) => {
+            ctx.respond(Response::html(t_a(
+                "tr_result",
+                hashmap! {
+                    "source" => escape_html(&translation.source),
+                    "target" => escape_html(&target),
+                    "text" => escape_html(&translation.text),
+                },
+            )))
+            .await?;
+        }
+        Err(e) => {
+            log::error!("Failed to translate the text: {}", e);
+            ctx.respond(t("tr_error")).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if the word looks like a language code, as `en` or `zh-TW`.
+fn is_language(word: &str) -> bool {
+    let (language, region) = word.split_once('-').unwrap_or((word, ""));
+
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && (region.is_empty()
+            || (2..=4).contains(&region.len()) && region.chars().all(|c| c.is_ascii_alphabetic()))
+}
@@ -0,0 +1,205 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the forward tracer command handler.
+
+use chrono::{DateTime, Local};
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{grammers_tl_types as tl, types::Message, Client};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::i18n::I18n,
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// Setup the forward tracer command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("trace").and(filters::sudoers())).then(trace),
+    )
+}
+
+/// The origin of a forwarded message.
+struct Origin {
+    /// The name of the chat or user.
+    name: String,
+    /// The username, if public.
+    username: Option<String>,
+    /// Whether it is a channel the account left or was never in.
+    left: bool,
+}
+
+/// Handles the forward tracer command.
+///
+/// Usage: `.trace [link]`, tracing the linked or replied forward.
+async fn trace(ctx: Context, i18n: I18n) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let Some(reply) = utils::target_message(&ctx).await? else {
+        ctx.respond(t("trace_no_forward")).await?;
+        return Ok(());
+    };
+    let Some(tl::enums::MessageFwdHeader::Header(header)) = reply.forward_header() else {
+        ctx.respond(t("trace_no_forward")).await?;
+        return Ok(());
+    };
+
+    let mut lines = vec![t("trace_header")];
+
+    let date = DateTime::from_timestamp(header.date as i64, 0)
+        .map(|date| {
+            date.with_timezone(&Local)
+                .format("%d/%m/%Y %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    lines.push(t_a("trace_date", hashmap! { "date" => date }));
+
+    match &header.from_id {
+        Some(peer) => {
+            let id = peer_id(peer);
+            let origin = resolve(ctx.client(), &reply, peer).await;
+
+            let name = match &origin {
+                Some(Origin {
+                    name,
+                    username: Some(username),
+                    ..
+                }) => format!("{0} (@{1})", escape_html(name), username),
+                Some(origin) => escape_html(&origin.name),
+                None => t("trace_unknown"),
+            };
+            let key = match peer {
+                tl::enums::Peer::User(_) => "trace_user",
+                _ => "trace_chat",
+            };
+            lines.push(t_a(
+                key,
+                hashmap! { "name" => name, "id" => id.to_string() },
+            ));
+
+            if let Some(post) = header.channel_post {
+                let link = match &origin {
+                    Some(Origin {
+                        username: Some(username),
+                        ..
+                    }) => Some(format!("https://t.me/{0}/{1}", username, post)),
+                    Some(Origin { left: false, .. }) => {
+                        Some(format!("https://t.me/c/{0}/{1}", id, post))
+                    }
+                    _ => None,
+                };
+
+                match link {
+                    Some(link) => lines.push(t_a("trace_link", hashmap! { "link" => link })),
+                    None => lines.push(t("trace_link_private")),
+                }
+            }
+        }
+        // The sender hides the link to the account in the forwards.
+        None => lines.push(t_a(
+            "trace_hidden",
+            hashmap! { "name" => escape_html(header.from_name.as_deref().unwrap_or("?")) },
+        )),
+    }
+
+    if let Some(author) = &header.post_author {
+        lines.push(t_a(
+            "trace_author",
+            hashmap! { "author" => escape_html(author) },
+        ));
+    }
+    if header.imported {
+        lines.push(t("trace_imported"));
+    }
+
+    ctx.respond(Response::html(lines.join("\n"))).await?;
+
+    Ok(())
+}
+
+/// Returns the ID of a peer, as shown in the links.
+fn peer_id(peer: &tl::enums::Peer) -> i64 {
+    match peer {
+        tl::enums::Peer::User(user) => user.user_id,
+        tl::enums::Peer::Chat(chat) => chat.chat_id,
+        tl::enums::Peer::Channel(channel) => channel.channel_id,
+    }
+}
+
+/// Resolves the origin of a forward through the message it was seen in, as its
+/// access hash is not known.
+async fn resolve(client: &Client, message: &Message, peer: &tl::enums::Peer) -> Option<Origin> {
+    let from = message.chat().pack().to_input_peer();
+
+    match peer {
+        tl::enums::Peer::User(user) => {
+            let users = client
+                .invoke(&tl::functions::users::GetUsers {
+                    id: vec![tl::types::InputUserFromMessage {
+                        peer: from,
+                        msg_id: message.id(),
+                        user_id: user.user_id,
+                    }
+                    .into()],
+                })
+                .await
+                .ok()?;
+
+            match users.into_iter().next()? {
+                tl::enums::User::User(user) => Some(Origin {
+                    name: [user.first_name, user.last_name]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    username: user.username,
+                    left: false,
+                }),
+                tl::enums::User::Empty(_) => None,
+            }
+        }
+        tl::enums::Peer::Channel(channel) => {
+            let chats = client
+                .invoke(&tl::functions::channels::GetChannels {
+                    id: vec![tl::types::InputChannelFromMessage {
+                        peer: from,
+                        msg_id: message.id(),
+                        channel_id: channel.channel_id,
+                    }
+                    .into()],
+                })
+                .await
+                .ok()?;
+
+            let tl::enums::messages::Chats::Chats(tl::types::messages::Chats { chats }) = chats
+            else {
+                return None;
+            };
+            match chats.into_iter().next()? {
+                tl::enums::Chat::Channel(channel) => Some(Origin {
+                    name: channel.title,
+                    username: channel.username,
+                    left: channel.left,
+                }),
+                tl::enums::Chat::ChannelForbidden(channel) => Some(Origin {
+                    name: channel.title,
+                    username: None,
+                    left: true,
+                }),
+                _ => None,
+            }
+        }
+        // The basic groups cannot be the origin of a forward.
+        tl::enums::Peer::Chat(_) => None,
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the transcribe command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{
+    types::{Media, Message},
+    Client, InputMessage,
+};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        media_cache::MediaCache,
+        transcriber::{self, Transcriber},
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// Setup the transcribe command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("transcribe").and(filters::sudoers()))
+            .then(transcribe),
+    )
+}
+
+/// Setup the transcribe watcher.
+///
+/// It only matches the incoming voice notes.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::incoming_voice()).then(watch))
+}
+
+/// Handles the transcribe command.
+///
+/// Usage: `.transcribe`, replying to a voice note, `.transcribe <on|off>` or
+/// `.transcribe lang [language]`.
+async fn transcribe(
+    ctx: Context,
+    i18n: I18n,
+    media_cache: MediaCache,
+    transcriber: Transcriber,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    if !transcriber.is_configured() {
+        ctx.respond(Response::html(t("transcribe_not_configured")))
+            .await?;
+        return Ok(());
+    }
+
+    let chat = ctx.chat().expect("Chat not found");
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        [] => {
+            let Some(reply) = ctx.get_reply().await? else {
+                ctx.respond(t("transcribe_not_voice")).await?;
+                return Ok(());
+            };
+            let Some(duration) = reply.media().as_ref().and_then(transcriber::voice_duration)
+            else {
+                ctx.respond(t("transcribe_not_voice")).await?;
+                return Ok(());
+            };
+            if duration > transcriber.max_duration() {
+                ctx.respond(t_a(
+                    "transcribe_too_long",
+                    hashmap! { "max" => transcriber.max_duration().to_string() },
+                ))
+                .await?;
+                return Ok(());
+            }
+
+            let msg = ctx.edit_or_reply(t("transcribing")).await?;
+
+            match transcribe_voice(ctx.client(), &reply, &i18n, &media_cache, &transcriber).await {
+                Ok(text) => {
+                    ctx.respond_in(&msg, Response::html(render(&i18n, &text)))
+                        .await?;
+                }
+                Err(e) => {
+                    log::error!("Failed to transcribe the voice note: {}", e);
+                    ctx.respond_in(&msg, t("transcribe_error")).await?;
+                }
+            }
+        }
+        [state @ ("on" | "off")] => {
+            let enabled = *state == "on";
+            transcriber
+                .update(chat.id(), |settings| settings.enabled = enabled)
+                .await;
+
+            let key = if enabled {
+                "transcribe_enabled"
+            } else {
+                "transcribe_disabled"
+            };
+            ctx.respond(t(key)).await?;
+        }
+        ["lang"] => {
+            transcriber
+                .update(chat.id(), |settings| settings.language = None)
+                .await;
+
+            ctx.respond(Response::html(t_a(
+                "transcribe_language_reset",
+                hashmap! { "language" => escape_html(&i18n.locale()) },
+            )))
+            .await?;
+        }
+        ["lang", language] => {
+            let language = language.to_string();
+            transcriber
+                .update(chat.id(), |settings| {
+                    settings.language = Some(language.clone())
+                })
+                .await;
+
+            ctx.respond(Response::html(t_a(
+                "transcribe_language",
+                hashmap! { "language" => escape_html(&language) },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("transcribe_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Transcribes the incoming voice notes of the chats it is enabled in,
+/// replying with the transcript.
+///
+/// The voice notes too long are left alone.
+async fn watch(
+    ctx: Context,
+    i18n: I18n,
+    media_cache: MediaCache,
+    transcriber: Transcriber,
+) -> Result<()> {
+    if !transcriber.is_configured() {
+        return Ok(());
+    }
+
+    let message = ctx.message().await.unwrap();
+    if !transcriber.settings(message.chat().id()).await.enabled {
+        return Ok(());
+    }
+    if message
+        .media()
+        .as_ref()
+        .and_then(transcriber::voice_duration)
+        .is_none_or(|duration| duration > transcriber.max_duration())
+    {
+        return Ok(());
+    }
+
+    match transcribe_voice(ctx.client(), &message, &i18n, &media_cache, &transcriber).await {
+        Ok(text) => {
+            message
+                .reply(InputMessage::html(render(&i18n, &text)))
+                .await?;
+        }
+        Err(e) => log::error!("Failed to transcribe the voice note: {}", e),
+    }
+
+    Ok(())
+}
+
+/// Transcribes the voice note of a message, in the language set for its chat
+/// or the current locale.
+async fn transcribe_voice(
+    client: &Client,
+    message: &Message,
+    i18n: &I18n,
+    media_cache: &MediaCache,
+    transcriber: &Transcriber,
+) -> Result<String> {
+    let Some(media) = message.media() else {
+        return Err("The message has no media".into());
+    };
+    let mime_type = match &media {
+        Media::Document(document) => document.mime_type().unwrap_or("audio/ogg").to_string(),
+        _ => "audio/ogg".to_string(),
+    };
+    let language = transcriber
+        .settings(message.chat().id())
+        .await
+        .language
+        .unwrap_or_else(|| i18n.locale());
+
+    let bytes = media_cache.download(client, &media).await?;
+
+    transcriber
+        .transcribe(bytes.to_vec(), &mime_type, &language)
+        .await
+}
+
+/// Renders a transcript.
+fn render(i18n: &I18n, text: &str) -> String {
+    if text.is_empty() {
+        return i18n.translate("transcription_empty");
+    }
+
+    i18n.translate_with_args("transcription", hashmap! { "text" => escape_html(text) })
+}
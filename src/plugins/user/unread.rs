@@ -0,0 +1,88 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the unread command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::{reply_markup, InputMessage};
+
+use crate::{
+    config::{Key, PluginConfig, PluginSettings},
+    filters,
+    modules::{i18n::I18n, unread::Unread},
+    output::Respond,
+    utils, Sender,
+};
+
+/// The config of the unread plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "unread"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // The maximum number of chats listed.
+        vec![
+            Key::new("limit", 30).validate(|value| match value.as_integer() {
+                Some(1..=100) => Ok(()),
+                _ => Err("must be between 1 and 100".to_string()),
+            }),
+        ]
+    }
+}
+
+/// Setup the unread command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("unread").and(filters::sudoers())).then(unread),
+    )
+}
+
+/// Handles the unread command.
+async fn unread(
+    ctx: Context,
+    i18n: I18n,
+    unread: Unread,
+    tx: Sender,
+    settings: PluginSettings,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+
+    let msg = ctx.edit_or_reply(t("unread_scanning")).await?;
+    unread
+        .scan(ctx.client(), settings.get("unread", "limit"))
+        .await?;
+
+    match unread.render(&i18n).await {
+        Some((text, buttons)) => {
+            if !utils::send_or_busy(
+                &ctx,
+                &tx,
+                &i18n,
+                crate::Message::to_bot()
+                    .in_topic(utils::current_topic(&ctx).await)
+                    .send_via_bot_message(
+                        ctx.chat().expect("Chat not found"),
+                        InputMessage::html(text).reply_markup(&reply_markup::inline(buttons)),
+                    ),
+            )
+            .await?
+            {
+                return Ok(());
+            }
+            msg.delete().await?;
+        }
+        None => {
+            ctx.respond_in(&msg, t("unread_none")).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -8,18 +8,73 @@
 
 //! This module contains the upload command handler.
 
-use std::{io::Cursor, time::Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use ferogram::{handler, Context, Filter, Result, Router};
-use grammers_client::{grammers_tl_types::enums::MessageEntity, InputMessage};
+use grammers_client::{session::PackedChat, Client, InputMessage};
 use maplit::hashmap;
+use tokio::{
+    sync::{watch, Semaphore},
+    task::JoinSet,
+};
 
 use crate::{
+    config::{Key, PluginConfig, PluginSettings},
     filters,
-    modules::i18n::I18n,
-    utils::{fetch_stream, human_readable_size},
+    modules::{
+        i18n::I18n,
+        usage::{Counters, Usage},
+        virustotal::{with_verdict, VirusTotal},
+    },
+    output::{Respond, Response},
+    utils::{self, escape_html, fetch_stream, human_readable_size, Progress, Stream},
 };
 
+/// The largest file Telegram accepts, in bytes.
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// How often the summary of several uploads is refreshed.
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The config of the upload plugin.
+pub struct Settings;
+
+impl PluginConfig for Settings {
+    fn name(&self) -> &'static str {
+        "upload"
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        // How many files are transferred at once.
+        vec![
+            Key::new("parallelism", 3).validate(|value| match value.as_integer() {
+                Some(1..=10) => Ok(()),
+                _ => Err("must be between 1 and 10".to_string()),
+            }),
+        ]
+    }
+}
+
+/// The status of a file among several uploads.
+#[derive(Clone)]
+enum Status {
+    /// Waiting for a free slot.
+    Pending,
+    /// Being downloaded.
+    Downloading,
+    /// Being scanned on VirusTotal, with its file name.
+    Scanning(String),
+    /// Being uploaded, with its file name.
+    Uploading(String),
+    /// Sent, with its file name.
+    Done(String),
+    /// Failed, with the key of the reason.
+    Failed(&'static str),
+}
+
 /// Setup the upload command.
 pub fn setup() -> Router {
     Router::default().handler(
@@ -29,99 +84,362 @@ pub fn setup() -> Router {
 }
 
 /// Handles the upload command.
-async fn upload(ctx: Context, i18n: I18n) -> Result<()> {
+///
+/// Uploads every URL after the command or, when replying, every URL of the reply.
+///
+/// The files are scanned on VirusTotal before being sent, if enabled. It is paused
+/// once the daily budget of the plugin is used up.
+async fn upload(
+    ctx: Context,
+    i18n: I18n,
+    settings: PluginSettings,
+    usage: Usage,
+    virustotal: VirusTotal,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
 
-    let text = ctx.text().unwrap();
-    if let Some(reply) = ctx.get_reply().await? {
-        let text = reply.text().to_string();
-
-        if let Some(entities) = reply.fmt_entities() {
-            let url_entities = entities
-                .into_iter()
-                .filter(|entity| {
-                    matches!(entity, MessageEntity::Url(_) | MessageEntity::TextUrl(_))
-                })
-                .collect::<Vec<_>>();
-
-            if url_entities.is_empty() {
-                ctx.reply(t("reply_not_url_or_media")).await?;
-                return Ok(());
-            }
-
-            ctx.edit(t("download_processing")).await?;
-
-            let entity = url_entities[0];
-            let offset = entity.offset() as usize;
-            let length = entity.length() as usize;
-
-            let url = &text[offset..(offset + length)];
-            upload_file(url, ctx, &i18n).await?;
-        } else {
+    let text = ctx.command_text().unwrap();
+    let urls = if let Some(reply) = ctx.get_reply().await? {
+        let urls = utils::message_urls(&reply);
+        if urls.is_empty() {
             ctx.reply(t("reply_not_url_or_media")).await?;
+            return Ok(());
         }
-    } else if text.split_whitespace().count() < 2 {
-        ctx.reply(t("download_not_url")).await?;
+
+        urls
     } else {
-        ctx.edit(t("download_processing")).await?;
+        let urls = text
+            .split_whitespace()
+            .skip(1)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if urls.is_empty() {
+            ctx.reply(t("download_not_url")).await?;
+            return Ok(());
+        }
 
-        let url = text.split_whitespace().skip(1).next().unwrap();
-        upload_file(url, ctx, &i18n).await?;
+        urls
+    };
+
+    if utils::over_budget(&ctx, &usage, &i18n, "upload").await? {
+        return Ok(());
     }
 
-    Ok(())
+    match urls.as_slice() {
+        [url] => upload_file(url, ctx, &i18n, &usage, &virustotal).await,
+        _ => {
+            let parallelism = settings.get("upload", "parallelism");
+            upload_files(urls, ctx, &i18n, &usage, &virustotal, parallelism).await
+        }
+    }
 }
 
 /// Uploads a file from a URL.
-async fn upload_file(url: &str, ctx: Context, i18n: &I18n) -> Result<()> {
+async fn upload_file(
+    url: &str,
+    ctx: Context,
+    i18n: &I18n,
+    usage: &Usage,
+    virustotal: &VirusTotal,
+) -> Result<()> {
     let t = |key: &str| i18n.translate(key);
     let t_a = |key: &str, args| i18n.translate_with_args(key, args);
 
     let time = Instant::now();
-    match fetch_stream(url).await {
-        Ok(stream) => {
+
+    let msg = ctx.edit_or_reply(t("download_processing")).await?;
+    let progress = Progress::start(msg.clone(), t("download_processing"), None);
+    let result = fetch_stream(url, MAX_FILE_SIZE, Some(&progress)).await;
+    progress.finish().await;
+
+    match result {
+        Ok(Some(stream)) => {
             if stream.is_empty() {
-                ctx.edit_or_reply(t("download_empty")).await?;
+                ctx.respond_in(&msg, t("download_empty")).await?;
                 return Ok(());
             }
 
             let file_name = stream.file_name().to_string();
             let size = stream.len();
+            usage.record("upload", Counters::fetch(size)).await;
 
-            if size > 2 * 1024 * 1024 * 1024 {
-                ctx.edit_or_reply(t("download_size_limit")).await?;
-                return Ok(());
-            } else if let Some(length) = stream.content_length() {
-                if length != size as u64 {
-                    ctx.edit_or_reply(t("download_size_mismatch")).await?;
+            if let Some(length) = stream.content_length() {
+                if length != size {
+                    ctx.respond_in(&msg, t("download_size_mismatch")).await?;
                     return Ok(());
                 }
             }
 
+            if virustotal.is_enabled() {
+                msg.edit(t("virustotal_scanning")).await?;
+            }
+            let verdict = virustotal.verdict(stream.path(), i18n).await;
+
             let content_type = stream.content_type().to_string();
-            ctx.edit_or_reply(InputMessage::html(t_a(
-                        "upload_info",
-                        hashmap! { "name" => file_name.to_string(), "type" => content_type, "size" => human_readable_size(size) },
-                    )))
-                    .await?;
+            let info = t_a(
+                "upload_info",
+                hashmap! { "name" => file_name.to_string(), "type" => content_type, "size" => human_readable_size(size as usize) },
+            );
+            msg.edit(InputMessage::html(info.clone())).await?;
 
-            let mut cursor = Cursor::new(stream.as_bytes());
-            let file = ctx.upload_stream(&mut cursor, size, file_name).await?;
+            let progress = Progress::start(msg.clone(), info, Some(size));
+            let mut reader = progress.wrap(stream.open().await?);
+            let result = ctx
+                .upload_stream(&mut reader, size as usize, file_name)
+                .await;
+            progress.finish().await;
+            let file = result?;
+            usage.record("upload", Counters::upload(size)).await;
 
+            let caption = t_a(
+                "upload_time",
+                hashmap! { "time" => time.elapsed().as_secs_f32().to_string() },
+            );
             ctx.send(
-                InputMessage::html(t_a(
-                    "upload_time",
-                    hashmap! { "time" => time.elapsed().as_secs_f32().to_string() },
-                ))
-                .document(file),
+                InputMessage::html(with_verdict(caption, verdict))
+                    .document(file)
+                    .reply_to(utils::current_topic(&ctx).await),
             )
             .await?;
-            ctx.delete().await?;
+            msg.delete().await?;
+        }
+        Ok(None) => {
+            ctx.respond_in(&msg, t("download_size_limit")).await?;
         }
         Err(_) => {
-            ctx.edit_or_reply(t("download_error")).await?;
+            ctx.respond_in(&msg, t("download_error")).await?;
         }
     }
 
     Ok(())
 }
+
+/// Uploads several files from their URLs, at most `parallelism` at once, showing the status
+/// of each one in a single message.
+async fn upload_files(
+    urls: Vec<String>,
+    ctx: Context,
+    i18n: &I18n,
+    usage: &Usage,
+    virustotal: &VirusTotal,
+    parallelism: usize,
+) -> Result<()> {
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let time = Instant::now();
+    let count = urls.len();
+
+    let (tx, mut rx) = watch::channel(vec![Status::Pending; count]);
+    let files = render_statuses(&urls, &rx.borrow(), i18n);
+    let msg = ctx
+        .edit_or_reply(InputMessage::html(t_a(
+            "upload_summary",
+            hashmap! { "count" => count.to_string(), "files" => files },
+        )))
+        .await?;
+
+    let client = ctx.client();
+    let chat = ctx.chat().expect("Chat not found").pack();
+    let topic = utils::current_topic(&ctx).await;
+
+    let tx = Arc::new(tx);
+    let semaphore = Arc::new(Semaphore::new(parallelism));
+    let mut tasks = JoinSet::new();
+    for (index, url) in urls.iter().cloned().enumerate() {
+        let (client, i18n, usage, virustotal, tx, semaphore) = (
+            client.clone(),
+            i18n.clone(),
+            usage.clone(),
+            virustotal.clone(),
+            tx.clone(),
+            semaphore.clone(),
+        );
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+
+            let set_status = |status: Status| tx.send_modify(|statuses| statuses[index] = status);
+            let status = transfer(
+                &url,
+                &client,
+                chat,
+                topic,
+                &i18n,
+                &usage,
+                &virustotal,
+                set_status,
+            )
+            .await;
+            tx.send_modify(|statuses| statuses[index] = status);
+        });
+    }
+
+    let mut interval = tokio::time::interval(SUMMARY_INTERVAL);
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            joined = tasks.join_next() => {
+                if joined.is_none() {
+                    break;
+                }
+            }
+            _ = interval.tick() => {
+                if rx.has_changed().unwrap_or(false) {
+                    let statuses = rx.borrow_and_update().clone();
+
+                    // A failed edit must not stop the uploads.
+                    let _ = msg
+                        .edit(InputMessage::html(t_a(
+                            "upload_summary",
+                            hashmap! { "count" => count.to_string(), "files" => render_statuses(&urls, &statuses, i18n) },
+                        )))
+                        .await;
+                }
+            }
+        }
+    }
+
+    let statuses = rx.borrow().clone();
+    let done = statuses
+        .iter()
+        .filter(|status| matches!(status, Status::Done(_)))
+        .count();
+
+    ctx.respond_in(
+        &msg,
+        Response::html(t_a(
+            "upload_summary_done",
+            hashmap! {
+                "done" => done.to_string(),
+                "count" => count.to_string(),
+                "time" => time.elapsed().as_secs_f32().to_string(),
+                "files" => render_statuses(&urls, &statuses, i18n),
+            },
+        )),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Downloads a file and sends it to the chat, returning its final status.
+async fn transfer(
+    url: &str,
+    client: &Client,
+    chat: PackedChat,
+    topic: Option<i32>,
+    i18n: &I18n,
+    usage: &Usage,
+    virustotal: &VirusTotal,
+    set_status: impl Fn(Status),
+) -> Status {
+    let time = Instant::now();
+
+    set_status(Status::Downloading);
+    let stream = match fetch_stream(url, MAX_FILE_SIZE, None).await {
+        Ok(Some(stream)) => stream,
+        Ok(None) => return Status::Failed("download_size_limit"),
+        Err(e) => {
+            log::error!("Failed to download {}: {}", url, e);
+            return Status::Failed("download_error");
+        }
+    };
+
+    if stream.is_empty() {
+        return Status::Failed("download_empty");
+    } else if stream
+        .content_length()
+        .is_some_and(|length| length != stream.len())
+    {
+        return Status::Failed("download_size_mismatch");
+    }
+
+    let file_name = stream.file_name().to_string();
+    usage.record("upload", Counters::fetch(stream.len())).await;
+    if virustotal.is_enabled() {
+        set_status(Status::Scanning(file_name.clone()));
+    }
+    let verdict = virustotal.verdict(stream.path(), i18n).await;
+
+    set_status(Status::Uploading(file_name.clone()));
+    match send_stream(&stream, client, chat, topic, i18n, time, verdict).await {
+        Ok(()) => {
+            usage.record("upload", Counters::upload(stream.len())).await;
+            Status::Done(file_name)
+        }
+        Err(e) => {
+            log::error!("Failed to upload {}: {}", file_name, e);
+            Status::Failed("upload_error")
+        }
+    }
+}
+
+/// Uploads a stream and sends it to the chat as a document, with its verdict if scanned.
+async fn send_stream(
+    stream: &Stream,
+    client: &Client,
+    chat: PackedChat,
+    topic: Option<i32>,
+    i18n: &I18n,
+    time: Instant,
+    verdict: Option<String>,
+) -> Result<()> {
+    let mut file = stream.open().await?;
+    let uploaded = client
+        .upload_stream(
+            &mut file,
+            stream.len() as usize,
+            stream.file_name().to_string(),
+        )
+        .await?;
+
+    let caption = i18n.translate_with_args(
+        "upload_time",
+        hashmap! { "time" => time.elapsed().as_secs_f32().to_string() },
+    );
+    client
+        .send_message(
+            chat,
+            InputMessage::html(with_verdict(caption, verdict))
+                .document(uploaded)
+                .reply_to(topic),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Renders the status of each file, one per line.
+fn render_statuses(urls: &[String], statuses: &[Status], i18n: &I18n) -> String {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    urls.iter()
+        .zip(statuses)
+        .map(|(url, status)| {
+            let url = escape_html(url);
+
+            match status {
+                Status::Pending => t_a("upload_status_pending", hashmap! { "url" => url }),
+                Status::Downloading => t_a("upload_status_downloading", hashmap! { "url" => url }),
+                Status::Scanning(name) => t_a(
+                    "upload_status_scanning",
+                    hashmap! { "name" => escape_html(name) },
+                ),
+                Status::Uploading(name) => t_a(
+                    "upload_status_uploading",
+                    hashmap! { "name" => escape_html(name) },
+                ),
+                Status::Done(name) => t_a(
+                    "upload_status_done",
+                    hashmap! { "name" => escape_html(name) },
+                ),
+                Status::Failed(reason) => t_a(
+                    "upload_status_failed",
+                    hashmap! { "url" => url, "reason" => t(reason) },
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,93 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the usage command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, usage::Usage},
+    output::{Respond, Response},
+    utils::human_readable_size,
+};
+
+/// The most days shown at once, as many as are kept.
+const MAX_DAYS: i64 = 30;
+
+/// Setup the usage command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("usage").and(filters::sudoers())).then(usage),
+    )
+}
+
+/// Handles the usage command.
+///
+/// Usage: `.usage [days]`, showing the traffic and the requests of each plugin
+/// today, or summed over the last days.
+async fn usage(ctx: Context, i18n: I18n, usage: Usage) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let days = match text.split_whitespace().nth(1) {
+        Some(arg) => match arg.parse::<i64>() {
+            Ok(days @ 1..=MAX_DAYS) => days,
+            _ => {
+                ctx.respond(Response::html(t_a(
+                    "usage_invalid_days",
+                    hashmap! { "max" => MAX_DAYS.to_string() },
+                )))
+                .await?;
+                return Ok(());
+            }
+        },
+        None => 1,
+    };
+
+    let summary = usage.summary(days).await;
+    if summary.is_empty() {
+        ctx.respond(t("usage_empty")).await?;
+        return Ok(());
+    }
+
+    let mut lines = vec![if days == 1 {
+        t("usage_header_today")
+    } else {
+        t_a("usage_header", hashmap! { "days" => days.to_string() })
+    }];
+    for (plugin, counters) in summary {
+        let mut line = t_a(
+            "usage_line",
+            hashmap! {
+                "plugin" => plugin.clone(),
+                "downloaded" => human_readable_size(counters.downloaded as usize),
+                "uploaded" => human_readable_size(counters.uploaded as usize),
+                "calls" => counters.calls.to_string(),
+            },
+        );
+        // The budgets are daily, so they are only shown for today.
+        if let Some(budget) = usage.budget(&plugin).filter(|_| days == 1) {
+            line += &t_a(
+                "usage_budget",
+                hashmap! {
+                    "percent" => (counters.traffic() * 100 / budget).to_string(),
+                    "budget" => human_readable_size(budget as usize),
+                },
+            );
+        }
+
+        lines.push(line);
+    }
+
+    ctx.respond(Response::html(lines.join("\n"))).await?;
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the usernames command handler.
+
+use std::collections::HashSet;
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use grammers_client::types::Media;
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{
+        i18n::I18n,
+        media_cache::MediaCache,
+        usernames::{normalize, Availability, Usernames},
+    },
+    output::{Respond, Response},
+    utils::escape_html,
+};
+
+/// The most usernames checked in one run.
+const MAX_USERNAMES: usize = 100;
+
+/// The largest list file accepted, in bytes.
+const MAX_LIST_SIZE: i64 = 64 * 1024;
+
+/// Setup the usernames command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("usernames").and(filters::sudoers())).then(usernames),
+    )
+}
+
+/// Handles the usernames command.
+///
+/// Usage:
+/// - `.usernames check <usernames>`, or replying to a list or a text file
+/// - `.usernames watch <username>`
+/// - `.usernames unwatch <username>`
+/// - `.usernames list`
+async fn usernames(
+    ctx: Context,
+    i18n: I18n,
+    usernames: Usernames,
+    media_cache: MediaCache,
+) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let text = ctx.command_text().unwrap();
+    let mut args = text.split_whitespace().skip(1);
+
+    match args.next() {
+        Some("check") => {
+            let mut list = args.map(str::to_string).collect::<Vec<_>>();
+            if let Some(reply) = ctx.get_reply().await? {
+                let content = match reply.media() {
+                    Some(Media::Document(document)) => {
+                        if document.size() > MAX_LIST_SIZE {
+                            ctx.respond(t("usernames_file_too_big")).await?;
+                            return Ok(());
+                        }
+
+                        let bytes = media_cache
+                            .download(ctx.client(), &Media::Document(document))
+                            .await?;
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    }
+                    _ => reply.text().to_string(),
+                };
+
+                list.extend(content.split_whitespace().map(str::to_string));
+            }
+
+            let mut seen = HashSet::new();
+            let list = list
+                .iter()
+                .flat_map(|item| item.split(','))
+                .map(normalize)
+                .filter(|username| !username.is_empty() && seen.insert(username.clone()))
+                .collect::<Vec<_>>();
+
+            if list.is_empty() {
+                ctx.respond(Response::html(t("usernames_usage"))).await?;
+                return Ok(());
+            } else if list.len() > MAX_USERNAMES {
+                ctx.respond(t_a(
+                    "usernames_too_many",
+                    hashmap! { "max" => MAX_USERNAMES.to_string() },
+                ))
+                .await?;
+                return Ok(());
+            }
+
+            let msg = ctx
+                .edit_or_reply(t_a(
+                    "usernames_checking",
+                    hashmap! { "count" => list.len().to_string() },
+                ))
+                .await?;
+
+            let results = usernames.check_many(ctx.client(), &list).await;
+            let lines = results
+                .iter()
+                .map(|(username, availability)| {
+                    let key = match availability {
+                        Some(Availability::Free) => "usernames_status_free",
+                        Some(Availability::Taken) => "usernames_status_taken",
+                        Some(Availability::ForSale) => "usernames_status_for_sale",
+                        Some(Availability::Invalid) => "usernames_status_invalid",
+                        None => "usernames_status_error",
+                    };
+
+                    t_a(key, hashmap! { "username" => escape_html(username) })
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let free = results
+                .iter()
+                .filter(|(_, availability)| *availability == Some(Availability::Free))
+                .count();
+
+            ctx.respond_in(
+                &msg,
+                Response::html(t_a(
+                    "usernames_result",
+                    hashmap! {
+                        "free" => free.to_string(),
+                        "count" => results.len().to_string(),
+                        "usernames" => lines,
+                    },
+                )),
+            )
+            .await?;
+        }
+        Some("watch") => match args.next() {
+            Some(username) => {
+                if usernames.watch(username).await {
+                    ctx.respond(Response::html(t_a(
+                        "usernames_watched",
+                        hashmap! { "username" => escape_html(&normalize(username)) },
+                    )))
+                    .await?;
+                } else {
+                    ctx.respond(t("usernames_already_watched")).await?;
+                }
+            }
+            None => {
+                ctx.respond(Response::html(t("usernames_usage"))).await?;
+            }
+        },
+        Some("unwatch") => match args.next() {
+            Some(username) if usernames.unwatch(username).await => {
+                ctx.respond(Response::html(t_a(
+                    "usernames_unwatched",
+                    hashmap! { "username" => escape_html(&normalize(username)) },
+                )))
+                .await?;
+            }
+            _ => {
+                ctx.respond(t("usernames_not_watched")).await?;
+            }
+        },
+        Some("list") => {
+            let watched = usernames.watched().await;
+            if watched.is_empty() {
+                ctx.respond(t("usernames_list_empty")).await?;
+                return Ok(());
+            }
+
+            let list = watched
+                .iter()
+                .map(|(username, free)| {
+                    format!(
+                        "{0} <code>@{1}</code>",
+                        if *free { "🟢" } else { "🔴" },
+                        escape_html(username)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            ctx.respond(Response::html(t_a(
+                "usernames_list",
+                hashmap! { "usernames" => list },
+            )))
+            .await?;
+        }
+        _ => {
+            ctx.respond(Response::html(t("usernames_usage"))).await?;
+        }
+    }
+
+    Ok(())
+}
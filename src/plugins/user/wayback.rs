@@ -0,0 +1,114 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the wayback command handler.
+
+use ferogram::{handler, Context, Filter, Result, Router};
+use maplit::hashmap;
+
+use crate::{
+    filters,
+    modules::{i18n::I18n, wayback::Wayback},
+    output::{Respond, Response},
+    utils::{self, escape_html},
+};
+
+/// The most links of a message that are archived.
+const MAX_URLS: usize = 5;
+
+/// Setup the wayback command.
+pub fn setup() -> Router {
+    Router::default().handler(
+        handler::new_message(filters::command("wayback").and(filters::sudoers())).then(wayback),
+    )
+}
+
+/// Setup the wayback watcher.
+///
+/// It only matches the messages with links.
+pub fn watcher() -> Router {
+    Router::default().handler(handler::new_message(filters::posted_link()).then(watch))
+}
+
+/// Handles the wayback command.
+///
+/// Usage: `.wayback <url>...`, or replying to a message with links, or
+/// `.wayback <on|off>`.
+async fn wayback(ctx: Context, i18n: I18n, wayback: Wayback) -> Result<()> {
+    let t = |key: &str| i18n.translate(key);
+    let t_a = |key: &str, args| i18n.translate_with_args(key, args);
+
+    let chat = ctx.chat().expect("Chat not found");
+    let text = ctx.command_text().unwrap();
+    let args = text.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    if let [state @ ("on" | "off")] = args.as_slice() {
+        let enabled = *state == "on";
+        wayback.set_enabled(chat.id(), enabled).await;
+
+        let key = if enabled {
+            "wayback_enabled"
+        } else {
+            "wayback_disabled"
+        };
+        ctx.respond(t(key)).await?;
+        return Ok(());
+    }
+
+    let mut urls = args.iter().map(|url| url.to_string()).collect::<Vec<_>>();
+    if urls.is_empty() {
+        if let Some(reply) = ctx.get_reply().await? {
+            urls = utils::message_urls(&reply);
+        }
+    }
+    urls.dedup();
+    urls.truncate(MAX_URLS);
+
+    if urls.is_empty() {
+        ctx.respond(Response::html(t("wayback_usage"))).await?;
+        return Ok(());
+    }
+
+    let msg = ctx.edit_or_reply(t("wayback_archiving")).await?;
+
+    let mut lines = Vec::with_capacity(urls.len());
+    for url in urls {
+        let line = match wayback.archive(&url).await {
+            Ok(snapshot) => t_a(
+                "wayback_archived",
+                hashmap! { "url" => escape_html(&url), "snapshot" => escape_html(&snapshot) },
+            ),
+            Err(e) => {
+                log::warn!("Failed to archive {0}, queueing it: {1}", url, e);
+                wayback.enqueue(&url, true).await;
+
+                t_a("wayback_queued", hashmap! { "url" => escape_html(&url) })
+            }
+        };
+        lines.push(line);
+    }
+
+    ctx.respond_in(&msg, Response::html(lines.join("\n")))
+        .await?;
+
+    Ok(())
+}
+
+/// Queues the links posted in the chats it is enabled in to be archived.
+async fn watch(ctx: Context, wayback: Wayback) -> Result<()> {
+    let message = ctx.message().await.unwrap();
+    if !wayback.is_enabled(message.chat().id()).await {
+        return Ok(());
+    }
+
+    for url in utils::message_urls(&message).into_iter().take(MAX_URLS) {
+        wayback.enqueue(&url, false).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+// Copyright 2024 - Andriel Ferreira
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains the first-time setup.
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+};
+
+use ferogram::{Client, Result};
+use grammers_client::{
+    session::{PackedChat, PackedType},
+    InputMessage,
+};
+
+use crate::{
+    config::{self, Config},
+    modules::sudoers::Sudoers,
+};
+
+/// Asks for the credentials in the terminal, writes them to the config file,
+/// logs both clients in and checks that they can reach each other.
+///
+/// The keys not asked for are kept from the config file, if it exists, or
+/// left to their defaults.
+pub async fn run() -> Result<()> {
+    let path = Config::path();
+    println!("Setting up grymbb, the answers are written to {:?}.", path);
+
+    let api_id = ask("API ID, from https://my.telegram.org", |answer| {
+        answer
+            .parse::<i32>()
+            .ok()
+            .filter(|api_id| *api_id > 0)
+            .ok_or("must be a positive number")
+    })?;
+    let api_hash = ask("API hash, from https://my.telegram.org", |answer| {
+        if answer.len() == 32 && answer.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(answer.to_string())
+        } else {
+            Err("must be 32 hexadecimal characters")
+        }
+    })?;
+    let bot_token = ask("Bot token, from @BotFather", |answer| {
+        if config::is_bot_token(answer) {
+            Ok(answer.to_string())
+        } else {
+            Err("must look like 123456:ABC-DEF...")
+        }
+    })?;
+    let phone_number = ask("Phone number of the user account", |answer| {
+        if config::is_phone_number(answer) {
+            Ok(answer.to_string())
+        } else {
+            Err("must be in the international format, such as +5511987654321")
+        }
+    })?;
+    let sudoer = ask(
+        "ID of a sudoer besides you, empty for none",
+        |answer| match answer {
+            "" => Ok(None),
+            _ => answer
+                .parse::<i64>()
+                .map(Some)
+                .map_err(|_| "must be a user ID"),
+        },
+    )?;
+
+    let mut table = match fs::read_to_string(path) {
+        Ok(content) => toml::from_str::<toml::Table>(&content)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let telegram = section(&mut table, "telegram");
+    telegram.insert("api_id".to_string(), api_id.into());
+    telegram.insert("api_hash".to_string(), api_hash.into());
+    section(&mut table, "bot").insert("token".to_string(), bot_token.clone().into());
+    section(&mut table, "user").insert("phone_number".to_string(), phone_number.clone().into());
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, toml::to_string_pretty(&table)?)?;
+    println!(
+        "Wrote {:?}, see assets/config.toml.ex for the other keys.",
+        path
+    );
+
+    // Loaded back, so the overrides and the defaults apply as on a normal start.
+    let config = Config::load()?;
+    config.validate()?;
+    let user_config = &config.user[0];
+
+    println!("Logging the bot in...");
+    let bot = Client::bot(bot_token)
+        .api_id(api_id)
+        .api_hash(&config.telegram.api_hash)
+        .session_file(config.bot.session_file.clone())
+        .build_and_connect()
+        .await?;
+    println!("Logging the user account in...");
+    let user = Client::user(phone_number)
+        .api_id(api_id)
+        .api_hash(&config.telegram.api_hash)
+        .session_file(user_config.session_file.clone())
+        .build_and_connect()
+        .await?;
+
+    let bot = bot.inner();
+    let user = user.inner();
+    bot.session().save_to_file(&config.bot.session_file)?;
+    user.session().save_to_file(&user_config.session_file)?;
+
+    // The user account must find the bot to send the messages through it, and
+    // the bot must be started by the account to message it.
+    let bot_me = bot.get_me().await?;
+    let bot_username = bot_me.username().unwrap_or_default();
+    let Some(bot_chat) = user.resolve_username(bot_username).await? else {
+        return Err(format!("The user account cannot find the bot @{}", bot_username).into());
+    };
+    user.send_message(bot_chat.pack(), "/start").await?;
+
+    let user_me = user.get_me().await?;
+    let user_chat = PackedChat {
+        ty: PackedType::User,
+        id: user_me.id(),
+        access_hash: None,
+    };
+    bot.send_message(user_chat, InputMessage::text("grymbb is set up."))
+        .await
+        .map_err(|e| format!("The bot cannot message the user account: {}", e))?;
+
+    if let Some(sudoer) = sudoer {
+        Sudoers::shared().add(sudoer, sudoer.to_string()).await;
+    }
+
+    println!(
+        "Done, the bot @{0} and {1} can reach each other.",
+        bot_username,
+        user_me.full_name()
+    );
+
+    Ok(())
+}
+
+/// Asks a question until `parse` accepts the answer, telling why it did not.
+fn ask<T>(
+    question: &str,
+    parse: impl Fn(&str) -> std::result::Result<T, &'static str>,
+) -> Result<T> {
+    let stdin = io::stdin();
+
+    loop {
+        print!("{}: ", question);
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Err("The setup was aborted".into());
+        }
+
+        match parse(answer.trim()) {
+            Ok(value) => return Ok(value),
+            Err(why) => println!("The answer {}.", why),
+        }
+    }
+}
+
+/// Returns a section of the config, the first entry if it is an array of
+/// tables, such as `[[user]]`, creating it if missing.
+fn section<'a>(table: &'a mut toml::Table, name: &str) -> &'a mut toml::Table {
+    let value = table
+        .entry(name)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    let value = match value {
+        toml::Value::Array(array) => {
+            if array.is_empty() {
+                array.push(toml::Value::Table(toml::Table::new()));
+            }
+            &mut array[0]
+        }
+        value => value,
+    };
+
+    if !value.is_table() {
+        *value = toml::Value::Table(toml::Table::new());
+    }
+    value.as_table_mut().expect("the section is a table")
+}
@@ -8,59 +8,1156 @@
 
 //! This module contains some utility functions.
 
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
 
-use bytes::Bytes;
-use ferogram::Result;
-use grammers_client::button::{self, Inline};
-use reqwest::header::{HeaderMap, CONTENT_DISPOSITION, CONTENT_TYPE, USER_AGENT};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use ferogram::{Context, Result};
+use grammers_client::{
+    button::{self, Inline},
+    grammers_tl_types as tl,
+    session::PackedChat,
+    types::{Chat, InlineResult, Media, Message},
+    Client, InputMessage, InvocationError,
+};
+use maplit::hashmap;
+use reqwest::{
+    header::{HeaderMap, CONTENT_DISPOSITION, CONTENT_TYPE, USER_AGENT},
+    StatusCode,
+};
 use serde_json::json;
+use tokio::{
+    io::{AsyncRead, AsyncWriteExt, ReadBuf},
+    sync::watch,
+    task::JoinHandle,
+};
 use tokio_uring::fs::File;
 use uuid::Uuid;
 
+use crate::{
+    callback_data,
+    modules::{
+        games::{Chess, Sudoku, Trivia},
+        i18n::I18n,
+        purges::{self, Span},
+        scheduler::{Scheduler, When},
+        usage::Usage,
+    },
+    output::Respond,
+    Action, Recipient, SendError, Sender,
+};
+
+/// How often the progress of a transfer is shown.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many cells the progress bar has.
+const PROGRESS_BAR_LENGTH: usize = 10;
+
+/// The errors that ask to wait, with the seconds in their value.
+const FLOOD_ERRORS: [&str; 3] = ["FLOOD_WAIT", "FLOOD_PREMIUM_WAIT", "SLOWMODE_WAIT"];
+
+/// The errors that mean the session of a client is gone.
+const FATAL_ERRORS: [&str; 7] = [
+    "AUTH_KEY_UNREGISTERED",
+    "AUTH_KEY_INVALID",
+    "AUTH_KEY_DUPLICATED",
+    "SESSION_REVOKED",
+    "SESSION_EXPIRED",
+    "USER_DEACTIVATED",
+    "USER_DEACTIVATED_BAN",
+];
+
+/// The flood waits a request is retried after.
+const FLOOD_RETRIES: usize = 3;
+
+/// In seconds, the longest flood wait a request is retried after, set from the
+/// config.
+static FLOOD_THRESHOLD: AtomicU64 = AtomicU64::new(u64::MAX);
+
 /// The URL of the API to take screenshots.
 const API_URL: &str = "https://htmlcsstoimage.com/demo_run";
 
+/// How long the screenshot API is given to answer a check.
+const SCREENSHOT_API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The longest a screenshot can wait for the page.
+const MAX_SCREENSHOT_DELAY: Duration = Duration::from_secs(10);
+
+/// The longest file name kept, in characters.
+const MAX_FILE_NAME_LENGTH: usize = 200;
+
 /// Convert a size in bytes to a human readable format.
 pub fn human_readable_size(size: usize) -> String {
     let units = ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
 
     let size = size as f64;
-    let i = (size.ln() / 1024_f64.ln()).floor() as i32;
+    let i = ((size.ln() / 1024_f64.ln()).floor() as i32).max(0);
     let size = size / 1024_f64.powi(i);
 
     format!("{:.2} {}", size, units[i as usize])
 }
 
-/// Convert a board to inline buttons.
-pub fn board_to_buttons(board: Vec<Vec<char>>, game_id: i32) -> Vec<Vec<Inline>> {
-    board
+/// Format a duration as its two largest units, such as `2h 5m`.
+pub fn human_readable_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [(24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m"), (1, "s")];
+
+    let parts = units
+        .iter()
+        .filter(|(size, _)| seconds >= *size)
+        .take(2)
+        .scan(seconds, |rest, (size, unit)| {
+            let value = *rest / size;
+            *rest %= size;
+
+            Some(format!("{}{}", value, unit))
+        })
+        .filter(|part| !part.starts_with('0'))
+        .collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Returns how long Telegram asked to wait, if the error is a flood wait.
+///
+/// The wait is relative to when the error was received, so no clock is involved.
+pub fn flood_wait(error: &InvocationError) -> Option<Duration> {
+    match error {
+        InvocationError::Rpc(rpc) if FLOOD_ERRORS.contains(&rpc.name.as_str()) => {
+            Some(Duration::from_secs(rpc.value.unwrap_or(1) as u64))
+        }
+        _ => None,
+    }
+}
+
+/// Checks if the error means the session of the client is gone.
+///
+/// The client cannot make requests until it logs in again.
+pub fn is_fatal(error: &InvocationError) -> bool {
+    matches!(error, InvocationError::Rpc(rpc) if FATAL_ERRORS.contains(&rpc.name.as_str()))
+}
+
+/// Sets the longest flood wait a request is retried after.
+pub fn set_flood_threshold(threshold: Duration) {
+    FLOOD_THRESHOLD.store(threshold.as_secs(), Ordering::Relaxed);
+}
+
+/// Runs a request, retrying it after the flood waits Telegram asks for.
+///
+/// It sleeps at most `max_wait` in total, the flood error being returned once a
+/// wait would go over it or over the flood threshold.
+pub async fn retry_on_flood<T, F, Fut>(
+    max_wait: Duration,
+    mut request: F,
+) -> std::result::Result<T, InvocationError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, InvocationError>>,
+{
+    let mut budget = max_wait;
+    for _ in 0..FLOOD_RETRIES {
+        match request().await {
+            Err(e) => match flood_wait(&e) {
+                Some(wait)
+                    if wait <= budget
+                        && wait.as_secs() <= FLOOD_THRESHOLD.load(Ordering::Relaxed) =>
+                {
+                    log::warn!("Flood wait of {}s, retrying", wait.as_secs());
+                    tokio::time::sleep(wait).await;
+                    budget -= wait;
+                }
+                _ => return Err(e),
+            },
+            result => return result,
+        }
+    }
+
+    request().await
+}
+
+/// Parse a duration such as `30s`, `10m`, `2h` or `1d`.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let unit = text.chars().last()?;
+    let value = text[..text.len() - unit.len_utf8()].parse::<u64>().ok()?;
+
+    let seconds = match unit {
+        's' => value,
+        'm' => value * 60,
+        'h' => value * 60 * 60,
+        'd' => value * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+/// Parse a size such as `500k`, `20m` or `1g`, in bytes.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim().to_lowercase();
+    let text = text.strip_suffix('b').unwrap_or(&text);
+    let unit = text.chars().last()?;
+    let value = text[..text.len() - unit.len_utf8()].parse::<u64>().ok()?;
+
+    let bytes = match unit {
+        'k' => value * 1024,
+        'm' => value * 1024 * 1024,
+        'g' => value * 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    (bytes > 0).then_some(bytes)
+}
+
+/// Parse when something is due from the start of the given words, returning
+/// the time and how many words were used.
+///
+/// Accepts a duration (`10m`), a time (`18:30`, today or tomorrow if passed)
+/// or a date with a time (`25/12 09:00` or `25/12/2025 09:00`), in local time.
+pub fn parse_when(words: &[&str]) -> Option<(DateTime<Utc>, usize)> {
+    let now = Local::now();
+
+    if let Some(duration) = words.first().and_then(|w| parse_duration(w)) {
+        return Some((Utc::now() + TimeDelta::from_std(duration).ok()?, 1));
+    }
+
+    let (date, time, used) = match words {
+        [date, time, ..] if date.contains('/') => {
+            let date = match NaiveDate::parse_from_str(date, "%d/%m/%Y") {
+                Ok(date) => date,
+                Err(_) => {
+                    NaiveDate::parse_from_str(&format!("{}/{}", date, now.year()), "%d/%m/%Y")
+                        .ok()?
+                }
+            };
+
+            (
+                Some(date),
+                NaiveTime::parse_from_str(time, "%H:%M").ok()?,
+                2,
+            )
+        }
+        [time, ..] => (None, NaiveTime::parse_from_str(time, "%H:%M").ok()?, 1),
+        [] => return None,
+    };
+
+    let mut at = date.unwrap_or(now.date_naive()).and_time(time);
+    if date.is_none() && at <= now.naive_local() {
+        at += TimeDelta::days(1);
+    }
+
+    let at = Local.from_local_datetime(&at).earliest()?;
+    (at > now).then(|| (at.with_timezone(&Utc), used))
+}
+
+/// The arguments of a command, split into the positional ones and the `--flags`.
+pub struct Args {
+    /// The arguments that are not flags, in order.
+    positional: Vec<String>,
+    /// The flags, with their values if they take one.
+    flags: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    /// Parse the arguments of a command, without the command itself.
+    ///
+    /// The arguments can be quoted to keep their spaces, and the flags in `valued`
+    /// take the next argument as their value. Returns `None` if one of them has no value.
+    pub fn parse(text: &str, valued: &[&str]) -> Option<Self> {
+        let mut positional = Vec::new();
+        let mut flags = HashMap::new();
+
+        let mut words = split_quoted(text).into_iter();
+        while let Some(word) = words.next() {
+            match word.strip_prefix("--").filter(|flag| !flag.is_empty()) {
+                Some(flag) if valued.contains(&flag) => {
+                    flags.insert(flag.to_string(), Some(words.next()?));
+                }
+                Some(flag) => {
+                    flags.insert(flag.to_string(), None);
+                }
+                None => positional.push(word),
+            }
+        }
+
+        Some(Self { positional, flags })
+    }
+
+    /// Returns the positional arguments.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// Checks if a flag was given.
+    pub fn has(&self, flag: &str) -> bool {
+        self.flags.contains_key(flag)
+    }
+
+    /// Returns the value of a flag.
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        self.flags.get(flag)?.as_deref()
+    }
+
+    /// Returns the flags that are not among the known ones.
+    pub fn unknown<'a>(&'a self, known: &'a [&str]) -> impl Iterator<Item = &'a str> {
+        self.flags
+            .keys()
+            .map(String::as_str)
+            .filter(|flag| !known.contains(flag))
+    }
+}
+
+/// Split a text into words, keeping the quoted ones together.
+fn split_quoted(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut quoted = false;
+
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                quoted = true;
+            }
+            None if c.is_whitespace() => {
+                if !word.is_empty() || quoted {
+                    words.push(std::mem::take(&mut word));
+                }
+                quoted = false;
+            }
+            None => word.push(c),
+        }
+    }
+    if !word.is_empty() || quoted {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Escape the HTML special characters of the given text.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Appends a chunk to a partial output, keeping only its last `max` bytes.
+pub fn push_tail(output: &mut String, chunk: &str, max: usize) {
+    output.push_str(chunk);
+
+    if output.len() > max {
+        let mut start = output.len() - max;
+        while !output.is_char_boundary(start) {
+            start += 1;
+        }
+        output.drain(..start);
+    }
+}
+
+/// Replies to the message of the context, or edits it if it is mine, deleting both
+/// after `ttl`.
+///
+/// The deletion is a scheduled job, so it still happens after a restart. The
+/// recipient is the client the context belongs to, which deletes the messages.
+pub async fn reply_ephemeral(
+    ctx: &Context,
+    scheduler: &Scheduler,
+    recipient: Recipient,
+    input: impl Into<InputMessage>,
+    ttl: Duration,
+) -> Result<Message> {
+    let sent = ctx.edit_or_reply(input).await?;
+
+    let mut message_ids = vec![sent.id()];
+    if let Some(message) = ctx.message().await {
+        if message.id() != sent.id() {
+            message_ids.push(message.id());
+        }
+    }
+    delete_later(scheduler, recipient, sent.chat().pack(), message_ids, ttl).await?;
+
+    Ok(sent)
+}
+
+/// Deletes messages of a chat after `ttl`.
+///
+/// The deletion is a scheduled job, so it still happens after a restart.
+pub async fn delete_later(
+    scheduler: &Scheduler,
+    recipient: Recipient,
+    chat: impl Into<PackedChat>,
+    message_ids: Vec<i32>,
+    ttl: Duration,
+) -> Result<()> {
+    let when = When::At(Utc::now() + TimeDelta::from_std(ttl)?);
+    let action = Action::DeleteMessages(chat.into(), message_ids);
+    if scheduler
+        .schedule(when, recipient, None, action)
+        .await
+        .is_none()
+    {
+        log::error!("Failed to schedule the deletion of messages");
+    }
+
+    Ok(())
+}
+
+/// Build a link to the given message, if the chat supports them.
+pub fn message_link(chat: &Chat, message_id: i32) -> Option<String> {
+    match chat {
+        Chat::User(_) => None,
+        Chat::Group(group) if !group.is_megagroup() => None,
+        _ => Some(match chat.username() {
+            Some(username) => format!("https://t.me/{0}/{1}", username, message_id),
+            None => format!("https://t.me/c/{0}/{1}", chat.id(), message_id),
+        }),
+    }
+}
+
+/// A link to a message, as parsed by [`parse_message_link`].
+pub struct MessageLink {
+    /// The chat of the message.
+    pub chat: LinkedChat,
+    /// The ID of the message.
+    pub message_id: i32,
+}
+
+/// The chat of a message link.
+pub enum LinkedChat {
+    /// A public chat, by its username.
+    Username(String),
+    /// A private chat, by its ID, as in the `t.me/c/` links.
+    Id(i64),
+}
+
+/// Parse a link to a message.
+///
+/// Accepts `t.me/username/123`, `t.me/c/123456/78`, the forum topic and thread
+/// forms, such as `t.me/c/123456/9/78` or `t.me/username/78?thread=9`, and the
+/// `tg://resolve` and `tg://privatepost` ones. The scheme and the `telegram.me`
+/// host are optional.
+pub fn parse_message_link(text: &str) -> Option<MessageLink> {
+    let text = text.trim();
+
+    if let Some(query) = text.strip_prefix("tg://") {
+        let (kind, query) = query.split_once('?')?;
+        let params = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .collect::<HashMap<_, _>>();
+        let message_id = params.get("post")?.parse::<i32>().ok()?;
+
+        let chat = match kind {
+            "resolve" => LinkedChat::Username(params.get("domain")?.to_string()),
+            "privatepost" => LinkedChat::Id(params.get("channel")?.parse().ok()?),
+            _ => return None,
+        };
+
+        return Some(MessageLink { chat, message_id });
+    }
+
+    let text = text
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.");
+    let path = ["t.me/", "telegram.me/", "telegram.dog/"]
+        .iter()
+        .find_map(|host| text.strip_prefix(host))?;
+    // The query only tells the thread, the message ID is unique in the chat.
+    let path = path.split(['?', '#']).next()?;
+
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    let chat = match segments.next()? {
+        "c" => LinkedChat::Id(segments.next()?.parse().ok()?),
+        // The links of the web preview of the channels.
+        "s" => LinkedChat::Username(segments.next()?.to_string()),
+        username => LinkedChat::Username(username.to_string()),
+    };
+    if let LinkedChat::Username(username) = &chat {
+        let valid = username.len() >= 4
+            && username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return None;
+        }
+    }
+
+    // In the forum links, the topic comes before the message.
+    let numbers = segments
+        .map(|segment| segment.parse::<i32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let message_id = match numbers.as_slice() {
+        [message_id] | [_, message_id] => *message_id,
+        _ => return None,
+    };
+
+    Some(MessageLink { chat, message_id })
+}
+
+/// Get the message a link points to, with the given client.
+///
+/// The private chats are searched among the dialogs, as their access hashes are
+/// not in the links, so only the user instance reaches them. Returns `None` if
+/// the chat or the message cannot be reached.
+pub async fn fetch_linked_message(client: &Client, link: &MessageLink) -> Result<Option<Message>> {
+    let chat = match &link.chat {
+        LinkedChat::Username(username) => client.resolve_username(username).await?,
+        LinkedChat::Id(id) => {
+            let mut found = None;
+            let mut dialogs = client.iter_dialogs();
+            while let Some(dialog) = dialogs.next().await? {
+                if dialog.chat().id() == *id {
+                    found = Some(dialog.chat().clone());
+                    break;
+                }
+            }
+
+            found
+        }
+    };
+    let Some(chat) = chat else {
+        return Ok(None);
+    };
+
+    let message = client
+        .get_messages_by_id(&chat, &[link.message_id])
+        .await?
         .into_iter()
-        .enumerate()
-        .map(|(column, row)| {
-            row.into_iter()
-                .enumerate()
-                .map(|(row, symbol)| {
-                    button::inline(symbol, format!("ttt {0} {1} {2}", game_id, column, row))
+        .flatten()
+        .next();
+
+    Ok(message)
+}
+
+/// Get the message a command acts on: the one linked in its arguments or else
+/// the replied one.
+///
+/// Returns `None` if there is neither, or if the linked message cannot be reached.
+pub async fn target_message(ctx: &Context) -> Result<Option<Message>> {
+    let text = ctx.command_text().unwrap_or_default();
+
+    match text.split_whitespace().skip(1).find_map(parse_message_link) {
+        Some(link) => fetch_linked_message(ctx.client(), &link).await,
+        None => ctx.get_reply().await,
+    }
+}
+
+/// Returns the URLs of a message, the visible and the hidden ones.
+pub fn message_urls(message: &Message) -> Vec<String> {
+    // The entities are offset in UTF-16 code units.
+    let text = message.text().encode_utf16().collect::<Vec<_>>();
+
+    message
+        .fmt_entities()
+        .map(|entities| {
+            entities
+                .iter()
+                .filter_map(|entity| match entity {
+                    tl::enums::MessageEntity::Url(e) => {
+                        let start = e.offset as usize;
+                        text.get(start..start + e.length as usize)
+                            .map(String::from_utf16_lossy)
+                    }
+                    tl::enums::MessageEntity::TextUrl(e) => Some(e.url.clone()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check if the given chat is a forum, with its messages split into topics.
+pub fn is_forum(chat: &Chat) -> bool {
+    matches!(chat, Chat::Channel(channel) if channel.raw.forum)
+}
+
+/// Get the forum topic the given message was sent in.
+///
+/// Messages of the General topic, or of chats without topics, have none.
+pub fn topic_id(message: &Message) -> Option<i32> {
+    match message.reply_header()? {
+        tl::enums::MessageReplyHeader::Header(header) if header.forum_topic => {
+            header.reply_to_top_id.or(header.reply_to_msg_id)
+        }
+        _ => None,
+    }
+}
+
+/// Get the forum topic of the message that triggered the handler.
+pub async fn current_topic(ctx: &Context) -> Option<i32> {
+    ctx.message().await.as_ref().and_then(topic_id)
+}
+
+/// Keep only the given messages that were sent in the given forum topic.
+///
+/// Chats without topics have their messages kept as they are.
+pub async fn filter_topic_messages(
+    client: &Client,
+    chat: &Chat,
+    message_ids: Vec<i32>,
+    topic: Option<i32>,
+) -> Result<Vec<i32>> {
+    if !is_forum(chat) {
+        return Ok(message_ids);
+    }
+
+    let mut kept = Vec::with_capacity(message_ids.len());
+    for chunk in message_ids.chunks(100) {
+        let messages = client.get_messages_by_id(chat, chunk).await?;
+
+        kept.extend(
+            messages
+                .into_iter()
+                .flatten()
+                .filter(|message| topic_id(message) == topic)
+                .map(|message| message.id()),
+        );
+    }
+
+    Ok(kept)
+}
+
+/// Get the IDs of the messages a purge command covers, in order.
+///
+/// They are the ones of the span given as the argument, such as `50` or `2h`, or
+/// else the ones since the replied message. In forums, only the messages of the
+/// current topic are covered. Returns `None` if there is neither.
+pub async fn purge_targets(ctx: &Context) -> Result<Option<Vec<i32>>> {
+    let msg = ctx.message().await.expect("Message not found");
+    let chat = msg.chat();
+    let topic = topic_id(&msg);
+
+    let text = ctx.command_text().unwrap_or_default();
+    if let Some(span) = text.split_whitespace().nth(1).and_then(Span::parse) {
+        let message_ids = purges::collect(ctx.client(), &chat, msg.id(), topic, &span).await?;
+
+        return Ok(Some(message_ids));
+    }
+
+    let Some(reply) = ctx.get_reply().await? else {
+        return Ok(None);
+    };
+    let message_ids = (reply.id()..=(msg.id() - 1)).collect::<Vec<_>>();
+
+    filter_topic_messages(ctx.client(), &chat, message_ids, topic)
+        .await
+        .map(Some)
+}
+
+/// Check if a plugin used up its daily budget, replying so if it did.
+pub async fn over_budget(ctx: &Context, usage: &Usage, i18n: &I18n, plugin: &str) -> Result<bool> {
+    let Some(budget) = usage.exceeded(plugin).await else {
+        return Ok(false);
+    };
+
+    ctx.edit_or_reply(InputMessage::html(i18n.translate_with_args(
+        "usage_budget_exceeded",
+        hashmap! {
+            "plugin" => plugin.to_string(),
+            "budget" => human_readable_size(budget as usize),
+        },
+    )))
+    .await?;
+
+    Ok(true)
+}
+
+/// Send a message between the clients, replying that the bot is busy if the
+/// channel stayed full.
+///
+/// Returns whether the message was sent.
+pub async fn send_or_busy(
+    ctx: &Context,
+    tx: &Sender,
+    i18n: &I18n,
+    message: crate::Message,
+) -> Result<bool> {
+    match tx.send(message).await {
+        Ok(()) => Ok(true),
+        Err(SendError::Busy) => {
+            ctx.edit_or_reply(i18n.translate("bus_busy")).await?;
+
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Perform an inline query to a bot, in the context of the given chat.
+///
+/// Retries while the bot does not answer in time, returning up to `limit` results.
+pub async fn inline_query(
+    client: &Client,
+    bot: &Chat,
+    chat: &Chat,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<InlineResult>> {
+    let results = retry_bot_response(|| async move {
+        let mut iter = client.inline_query(bot, query).chat(chat);
+        let mut results = Vec::new();
+
+        while results.len() < limit {
+            match iter.next().await? {
+                Some(result) => results.push(result),
+                None => break,
+            }
+        }
+
+        Ok(results)
+    })
+    .await?;
+
+    Ok(results.unwrap_or_default())
+}
+
+/// Run a request to an inline bot, retrying while the bot does not answer in time.
+///
+/// Returns `None` if the bot never answered.
+async fn retry_bot_response<T, F, Fut>(
+    mut request: F,
+) -> std::result::Result<Option<T>, InvocationError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, InvocationError>>,
+{
+    const ATTEMPTS: usize = 10;
+
+    for _ in 0..ATTEMPTS {
+        match request().await {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) if e.is("BOT_RESPONSE_TIMEOUT") => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Send the inline result of a bot with the given title to a forum topic.
+///
+/// The high-level inline results cannot target topics, so this goes through
+/// the raw API. Returns the sent message, or `None` if no result had the given
+/// title.
+pub async fn send_inline_result_to_topic(
+    client: &Client,
+    bot: &Chat,
+    chat: &Chat,
+    query: &str,
+    title: &str,
+    topic: i32,
+) -> Result<Option<Message>> {
+    let request = tl::functions::messages::GetInlineBotResults {
+        bot: input_user(bot)?,
+        peer: chat.pack().to_input_peer(),
+        geo_point: None,
+        query: query.to_string(),
+        offset: String::new(),
+    };
+    let Some(tl::enums::messages::BotResults::Results(results)) =
+        retry_bot_response(|| client.invoke(&request)).await?
+    else {
+        return Ok(None);
+    };
+
+    let Some(id) = results.results.into_iter().find_map(|result| match result {
+        tl::enums::BotInlineResult::Result(r) => {
+            (r.title.as_deref() == Some(title)).then_some(r.id)
+        }
+        tl::enums::BotInlineResult::BotInlineMediaResult(r) => {
+            (r.title.as_deref() == Some(title)).then_some(r.id)
+        }
+    }) else {
+        return Ok(None);
+    };
+
+    let random_id = rand::random();
+    let updates = client
+        .invoke(&tl::functions::messages::SendInlineBotResult {
+            silent: false,
+            background: false,
+            clear_draft: false,
+            hide_via: false,
+            peer: chat.pack().to_input_peer(),
+            reply_to: Some(
+                tl::types::InputReplyToMessage {
+                    reply_to_msg_id: topic,
+                    top_msg_id: Some(topic),
+                    reply_to_peer_id: None,
+                    quote_text: None,
+                    quote_entities: None,
+                    quote_offset: None,
+                }
+                .into(),
+            ),
+            random_id,
+            query_id: results.query_id,
+            id,
+            schedule_date: None,
+            send_as: None,
+            quick_reply_shortcut: None,
+        })
+        .await?;
+
+    let Some(id) = sent_message_id(&updates, random_id) else {
+        return Err("The sent message was not found in the updates".into());
+    };
+
+    match client.get_messages_by_id(chat.pack(), &[id]).await?.pop() {
+        Some(Some(message)) => Ok(Some(message)),
+        _ => Err("The sent message was not found".into()),
+    }
+}
+
+/// Build the input user of a chat.
+///
+/// Fails if the access hash of the user is unknown, which Telegram would refuse.
+pub fn input_user(chat: &Chat) -> Result<tl::enums::InputUser> {
+    let packed = chat.pack();
+    let access_hash = packed
+        .access_hash
+        .ok_or_else(|| format!("The access hash of the user {} is unknown", packed.id))?;
+
+    Ok(tl::types::InputUser {
+        user_id: packed.id,
+        access_hash,
+    }
+    .into())
+}
+
+/// Build the input channel of a chat.
+///
+/// Fails if the access hash of the channel is unknown, which Telegram would refuse.
+pub fn input_channel(chat: &Chat) -> Result<tl::enums::InputChannel> {
+    let packed = chat.pack();
+    let access_hash = packed
+        .access_hash
+        .ok_or_else(|| format!("The access hash of the channel {} is unknown", packed.id))?;
+
+    Ok(tl::types::InputChannel {
+        channel_id: packed.id,
+        access_hash,
+    }
+    .into())
+}
+
+/// Find the ID given to a sent message in the updates of its request.
+fn sent_message_id(updates: &tl::enums::Updates, random_id: i64) -> Option<i32> {
+    let updates = match updates {
+        tl::enums::Updates::Updates(updates) => &updates.updates,
+        tl::enums::Updates::Combined(updates) => &updates.updates,
+        _ => return None,
+    };
+
+    updates.iter().find_map(|update| match update {
+        tl::enums::Update::MessageId(update) if update.random_id == random_id => Some(update.id),
+        _ => None,
+    })
+}
+
+/// Build the navigation buttons of a paginated message.
+///
+/// The callback data of each button is the prefix followed by the page number.
+pub fn pagination_buttons(prefix: &str, page: usize, pages: usize) -> Vec<Inline> {
+    let mut buttons = Vec::with_capacity(3);
+
+    if page > 0 {
+        buttons.push(button::inline("⬅️", format!("{0} {1}", prefix, page - 1)));
+    }
+
+    buttons.push(button::inline(
+        format!("{0}/{1}", page + 1, pages),
+        format!("{0} {1}", prefix, page),
+    ));
+
+    if page + 1 < pages {
+        buttons.push(button::inline("➡️", format!("{0} {1}", prefix, page + 1)));
+    }
+
+    buttons
+}
+
+/// How the moves of a board are picked.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BoardLayout {
+    /// A button per cell, with the callback data `<prefix> <id> <column> <row>`.
+    Cells,
+    /// A button per column, with the callback data `<prefix> <id> <column>`.
+    Columns,
+}
+
+/// Convert a board to inline buttons.
+pub fn board_to_buttons(
+    board: Vec<Vec<String>>,
+    prefix: &str,
+    game_id: i32,
+    layout: BoardLayout,
+) -> Vec<Vec<Inline>> {
+    match layout {
+        BoardLayout::Cells => board
+            .into_iter()
+            .enumerate()
+            .map(|(column, row)| {
+                row.into_iter()
+                    .enumerate()
+                    .map(|(row, symbol)| {
+                        button::inline(
+                            symbol,
+                            callback_data::sign(format!(
+                                "{0} {1} {2} {3}",
+                                prefix, game_id, column, row
+                            )),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+        BoardLayout::Columns => {
+            let columns = board.first().map(|row| row.len()).unwrap_or(0);
+
+            vec![(0..columns)
+                .map(|column| {
+                    button::inline(
+                        (column + 1).to_string(),
+                        callback_data::sign(format!("{0} {1} {2}", prefix, game_id, column)),
+                    )
+                })
+                .collect::<Vec<_>>()]
+        }
+    }
+}
+
+/// Converts a sudoku into buttons.
+///
+/// The 9x9 board does not fit a keyboard, so it is navigated in steps: the
+/// boxes, then the cells of the `block` box, then the digits of the selected cell.
+pub fn sudoku_to_buttons(sudoku: &Sudoku, game_id: i32, block: Option<usize>) -> Vec<Vec<Inline>> {
+    if sudoku.selected().is_some() {
+        let mut buttons = (0..3)
+            .map(|row| {
+                (1..=3)
+                    .map(|digit| {
+                        let digit = row * 3 + digit;
+                        button::inline(
+                            digit.to_string(),
+                            callback_data::sign(format!("sdk {0} put {1}", game_id, digit)),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        buttons.push(vec![
+            button::inline("⌫", callback_data::sign(format!("sdk {} put 0", game_id))),
+            button::inline("↩️", callback_data::sign(format!("sdk {} back", game_id))),
+        ]);
+
+        return buttons;
+    }
+
+    match block {
+        Some(block) => {
+            let (box_row, box_column) = (block / 3 * 3, block % 3 * 3);
+
+            let mut buttons = (box_row..box_row + 3)
+                .map(|row| {
+                    (box_column..box_column + 3)
+                        .map(|column| {
+                            let symbol = match sudoku.cell(row, column) {
+                                0 => "·".to_string(),
+                                digit if sudoku.is_given(row, column) => format!("[{}]", digit),
+                                digit => digit.to_string(),
+                            };
+
+                            button::inline(
+                                symbol,
+                                callback_data::sign(format!(
+                                    "sdk {0} cell {1} {2}",
+                                    game_id, row, column
+                                )),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            buttons.push(vec![button::inline(
+                "↩️",
+                callback_data::sign(format!("sdk {} back", game_id)),
+            )]);
+
+            buttons
+        }
+        None => (0..3)
+            .map(|row| {
+                (0..3)
+                    .map(|column| {
+                        let block = row * 3 + column;
+                        button::inline(
+                            format!("▦ {}", block + 1),
+                            callback_data::sign(format!("sdk {0} box {1}", game_id, block)),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+    }
+}
+
+/// Converts a chess board into buttons, a row per rank from the 8th.
+///
+/// The selected piece is wrapped in brackets and its legal moves are marked.
+pub fn chess_to_buttons(chess: &Chess, game_id: i32) -> Vec<Vec<Inline>> {
+    let targets = chess.targets();
+
+    let mut buttons = (0..8)
+        .map(|row| {
+            (0..8)
+                .map(|column| {
+                    let square = row * 8 + column;
+                    let symbol = chess.symbol(square);
+
+                    let text = if chess.selected() == Some(square) {
+                        format!("[{}]", symbol)
+                    } else if targets.contains(&square) {
+                        if symbol == '·' {
+                            "•".to_string()
+                        } else {
+                            format!("×{}", symbol)
+                        }
+                    } else {
+                        symbol.to_string()
+                    };
+
+                    button::inline(
+                        text,
+                        callback_data::sign(format!("chess {0} sq {1}", game_id, square)),
+                    )
                 })
                 .collect::<Vec<_>>()
         })
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>();
+    buttons.push(vec![
+        button::inline("🤝", callback_data::sign(format!("chess {} draw", game_id))),
+        button::inline(
+            "🏳️",
+            callback_data::sign(format!("chess {} resign", game_id)),
+        ),
+    ]);
+
+    buttons
 }
 
-/// Take a screenshot of the given URL.
-pub async fn take_a_screenshot(url: String) -> Result<String> {
+/// Converts the answers of the current trivia question into buttons, one per row.
+pub fn trivia_to_buttons(trivia: &Trivia, game_id: i32) -> Vec<Vec<Inline>> {
+    trivia
+        .question()
+        .map(|question| {
+            question
+                .answers
+                .iter()
+                .enumerate()
+                .map(|(i, answer)| {
+                    vec![button::inline(
+                        answer,
+                        callback_data::sign(format!("trv {0} {1} {2}", game_id, trivia.round(), i)),
+                    )]
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The options of a screenshot.
+#[derive(Default)]
+pub struct ScreenshotOptions {
+    /// Whether the page is rendered as on a phone.
+    pub mobile: bool,
+    /// Whether the whole page is captured, not only the viewport.
+    pub full: bool,
+    /// How long to wait after the page loads.
+    pub delay: Option<Duration>,
+    /// The CSS selector of the only element captured.
+    pub selector: Option<String>,
+    /// Whether the page is rendered in dark mode.
+    pub dark: bool,
+}
+
+impl ScreenshotOptions {
+    /// The flags of the options.
+    pub const FLAGS: [&'static str; 5] = ["mobile", "full", "delay", "selector", "dark"];
+
+    /// The flags that take a value.
+    pub const VALUED_FLAGS: [&'static str; 2] = ["delay", "selector"];
+
+    /// Reads the options from the flags of a command.
+    ///
+    /// Returns `None` if the delay is invalid or too long.
+    pub fn from_args(args: &Args) -> Option<Self> {
+        let delay = match args.value("delay") {
+            Some(delay) => Some(
+                delay
+                    .parse::<u64>()
+                    .ok()
+                    .map(Duration::from_secs)
+                    .or_else(|| parse_duration(delay))
+                    .filter(|delay| *delay <= MAX_SCREENSHOT_DELAY)?,
+            ),
+            None => None,
+        };
+
+        Some(Self {
+            mobile: args.has("mobile"),
+            full: args.has("full"),
+            delay,
+            selector: args.value("selector").map(str::to_string),
+            dark: args.has("dark"),
+        })
+    }
+}
+
+pub async fn take_a_screenshot(url: String, options: &ScreenshotOptions) -> Result<String> {
     let mut headers = HeaderMap::new();
     headers.insert(USER_AGENT, "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/103.0.0.0 Safari/537.36".parse().unwrap());
 
-    let data = json!({
+    let (width, height, scale) = if options.mobile {
+        (390, 844, 3)
+    } else {
+        (1280, 720, 1)
+    };
+
+    let mut data = json!({
         "url": url,
         "css": format!("random-tag: {}", Uuid::new_v4()),
         "render_when_ready": false,
-        "viewport_width": 1280,
-        "viewport_height": 720,
-        "device_scale": 1,
+        "viewport_width": width,
+        "viewport_height": height,
+        "device_scale": scale,
+        "full_screen": options.full,
+        "color_scheme": if options.dark { "dark" } else { "light" },
     });
+    if let Some(delay) = options.delay {
+        data["ms_delay"] = json!(delay.as_millis() as u64);
+    }
+    if let Some(selector) = &options.selector {
+        data["selector"] = json!(selector);
+    }
 
     let request = reqwest::Client::new()
         .post(API_URL)
@@ -70,7 +1167,7 @@ pub async fn take_a_screenshot(url: String) -> Result<String> {
     match request.send().await {
         Ok(response) => {
             let json = response.json::<serde_json::Value>().await?;
-            let photo_url = json["url"].as_str().unwrap();
+            let photo_url = json["url"].as_str().ok_or("Failed to take screenshot")?;
 
             Ok(photo_url.to_string())
         }
@@ -78,6 +1175,101 @@ pub async fn take_a_screenshot(url: String) -> Result<String> {
     }
 }
 
+/// Checks that the screenshot API answers, returning its status.
+pub async fn check_screenshot_api() -> Result<StatusCode> {
+    let response = reqwest::Client::new()
+        .head(API_URL)
+        .timeout(SCREENSHOT_API_TIMEOUT)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if status.is_server_error() {
+        return Err(format!("the API answered {}", status).into());
+    }
+
+    Ok(status)
+}
+
+/// Returns the file name of a media and its size, if known, `None` if the media
+/// has no file.
+pub fn media_file(media: &Media) -> Option<(String, Option<u64>)> {
+    match media {
+        Media::Photo(photo) => Some((format!("photo_{}.jpg", photo.id()), None)),
+        Media::Document(document) => Some((
+            document_name(
+                document.name(),
+                document.mime_type(),
+                document.id(),
+                "document",
+            ),
+            Some(document.size() as u64),
+        )),
+        Media::Sticker(sticker) => Some((
+            document_name(
+                sticker.document.name(),
+                sticker.document.mime_type(),
+                sticker.document.id(),
+                "sticker",
+            ),
+            Some(sticker.document.size() as u64),
+        )),
+        _ => None,
+    }
+}
+
+/// Returns the name of a document, made up from its kind and type if it has none.
+pub fn document_name(name: &str, mime_type: Option<&str>, id: i64, kind: &str) -> String {
+    if !name.trim().is_empty() {
+        return name.to_string();
+    }
+
+    let extension = mime_type
+        .and_then(|mime| mime.split('/').nth(1))
+        .map(|subtype| subtype.split(['+', ';']).next().unwrap_or(subtype))
+        .unwrap_or("bin");
+
+    format!("{0}_{1}.{2}", kind, id, extension)
+}
+
+/// Makes a file name safe for the disk, without paths nor reserved characters.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect::<String>();
+    let name = name.trim().trim_matches('.');
+
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns a path in `dir` not taken yet, numbering the name as `name (1).ext` if needed.
+pub fn unique_path(dir: &Path, file_name: &str) -> PathBuf {
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return path;
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (file_name, String::new()),
+    };
+
+    (1..)
+        .map(|i| dir.join(format!("{0} ({1}){2}", stem, i, extension)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
 /// Download a file from the given URL to the given path.
 pub async fn download_file<U: ToString, P: AsRef<Path>>(url: U, path: P) -> Result<()> {
     let url = url.to_string();
@@ -123,11 +1315,19 @@ pub async fn download_file<U: ToString, P: AsRef<Path>>(url: U, path: P) -> Resu
     Ok(())
 }
 
-/// Fetch a stream from the given URL.
-pub async fn fetch_stream<U: ToString>(url: U) -> Result<Stream> {
+/// Fetch a stream from the given URL into a temporary file.
+///
+/// The chunks are written as they arrive, so only one of them is kept in memory. Returns
+/// `None` once the body exceeds `max_size` bytes. The `progress`, if any, is given the
+/// total size and advanced as the chunks arrive.
+pub async fn fetch_stream<U: ToString>(
+    url: U,
+    max_size: u64,
+    progress: Option<&Progress>,
+) -> Result<Option<Stream>> {
     let url = url.to_string();
 
-    let response = reqwest::get(&url).await?;
+    let mut response = reqwest::get(&url).await?;
 
     let file_name = if let Some(disposition) = response.headers().get(CONTENT_DISPOSITION) {
         let disposition = disposition.to_str().unwrap();
@@ -151,18 +1351,50 @@ pub async fn fetch_stream<U: ToString>(url: U) -> Result<Stream> {
         .to_string();
     let content_length = response.content_length();
 
-    Ok(Stream {
-        bytes: response.bytes().await?,
+    if content_length.is_some_and(|length| length > max_size) {
+        return Ok(None);
+    }
+    if let Some(progress) = progress {
+        progress.set_total(content_length);
+    }
+
+    // From here on the file is removed when the stream is dropped.
+    let mut stream = Stream {
+        path: std::env::temp_dir().join(format!("grymbb-{}", Uuid::new_v4())),
+        len: 0,
         file_name,
         content_type,
         content_length,
-    })
+    };
+
+    let mut file = tokio::fs::File::create(&stream.path).await?;
+    while let Some(chunk) = response.chunk().await? {
+        let len = chunk.len() as u64;
+        if stream.len + len > max_size {
+            return Ok(None);
+        }
+
+        file.write_all(&chunk).await?;
+        stream.len += len;
+
+        if let Some(progress) = progress {
+            progress.advance(len);
+        }
+    }
+
+    file.sync_all().await?;
+
+    Ok(Some(stream))
 }
 
-/// A stream of bytes with some metadata.
+/// A stream fetched into a temporary file, with some metadata.
+///
+/// The file is removed when the stream is dropped.
 pub struct Stream {
-    /// The bytes of the stream.
-    bytes: Bytes,
+    /// The path of the temporary file.
+    path: PathBuf,
+    /// The length of the stream.
+    len: u64,
     /// The file name of the stream.
     file_name: String,
     /// The content type of the stream.
@@ -173,8 +1405,8 @@ pub struct Stream {
 
 impl Stream {
     /// Gets the length of the stream.
-    pub fn len(&self) -> usize {
-        self.bytes.len()
+    pub fn len(&self) -> u64 {
+        self.len
     }
 
     /// Gets the file name of the stream.
@@ -187,18 +1419,153 @@ impl Stream {
         &self.content_type
     }
 
+    /// Gets the path of the temporary file of the stream.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Gets the content length of the stream.
     pub fn content_length(&self) -> Option<u64> {
         self.content_length
     }
 
-    /// Gets the bytes of the stream as a slice.
-    pub fn as_bytes(&self) -> &[u8] {
-        self.bytes.as_ref()
+    /// Opens the stream for reading.
+    pub async fn open(&self) -> std::io::Result<tokio::fs::File> {
+        tokio::fs::File::open(&self.path).await
     }
 
     /// Checks if the stream is empty.
     pub fn is_empty(&self) -> bool {
-        self.bytes.is_empty()
+        self.len == 0
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::warn!("Failed to remove the temporary file {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// The progress of a transfer, shown by editing a message at most every few seconds.
+pub struct Progress {
+    /// The transferred bytes and the total size, if known.
+    tx: watch::Sender<(u64, Option<u64>)>,
+    /// The task editing the message.
+    task: JoinHandle<()>,
+}
+
+impl Progress {
+    /// Starts showing the progress in the message, below the `label`.
+    pub fn start(message: Message, label: String, total: Option<u64>) -> Self {
+        let (tx, mut rx) = watch::channel((0, total));
+
+        let task = tokio::task::spawn(async move {
+            let started = Instant::now();
+            let mut last_edit = Instant::now();
+
+            while rx.changed().await.is_ok() {
+                if last_edit.elapsed() < PROGRESS_INTERVAL {
+                    continue;
+                }
+                last_edit = Instant::now();
+
+                let (done, total) = *rx.borrow_and_update();
+                let text = format!(
+                    "{0}\n\n{1}",
+                    label,
+                    render_progress(done, total, started.elapsed())
+                );
+
+                // A failed edit must not stop the transfer.
+                if let Err(e) = message.edit(InputMessage::html(text)).await {
+                    log::warn!("Failed to edit the progress message: {}", e);
+                }
+            }
+        });
+
+        Self { tx, task }
+    }
+
+    /// Sets the total size of the transfer.
+    pub fn set_total(&self, total: Option<u64>) {
+        self.tx.send_modify(|(_, t)| *t = total);
+    }
+
+    /// Advances the transfer by `bytes`.
+    pub fn advance(&self, bytes: u64) {
+        self.tx.send_modify(|(done, _)| *done += bytes);
+    }
+
+    /// Wraps a reader, advancing the transfer as it is read.
+    pub fn wrap<R: AsyncRead + Unpin>(&self, reader: R) -> ProgressReader<'_, R> {
+        ProgressReader {
+            inner: reader,
+            progress: self,
+        }
+    }
+
+    /// Stops showing the progress, waiting for a pending edit.
+    pub async fn finish(self) {
+        drop(self.tx);
+        let _ = self.task.await;
+    }
+}
+
+/// A reader advancing a [`Progress`] as it is read.
+pub struct ProgressReader<'a, R> {
+    /// The wrapped reader.
+    inner: R,
+    /// The progress to advance.
+    progress: &'a Progress,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProgressReader<'_, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(())) = poll {
+            self.progress.advance((buf.filled().len() - filled) as u64);
+        }
+
+        poll
+    }
+}
+
+/// Renders a progress bar with the speed and the remaining time, such as
+/// `[█████░░░░░] 50% · 1.00 MB / 2.00 MB · 512.00 KB/s · 2s`.
+pub fn render_progress(done: u64, total: Option<u64>, elapsed: Duration) -> String {
+    let speed = done as f64 / elapsed.as_secs_f64().max(1.0);
+    let speed = format!("{}/s", human_readable_size(speed as usize));
+
+    match total.filter(|total| *total > 0) {
+        Some(total) => {
+            let ratio = (done as f64 / total as f64).min(1.0);
+            let filled = (ratio * PROGRESS_BAR_LENGTH as f64).round() as usize;
+            let eta = if done > 0 {
+                let remaining = total.saturating_sub(done) as f64;
+                Duration::from_secs_f64(remaining * elapsed.as_secs_f64() / done as f64)
+            } else {
+                Duration::ZERO
+            };
+
+            format!(
+                "[{0}{1}] {2:.0}% · {3} / {4} · {5} · {6}",
+                "█".repeat(filled),
+                "░".repeat(PROGRESS_BAR_LENGTH - filled),
+                ratio * 100.0,
+                human_readable_size(done as usize),
+                human_readable_size(total as usize),
+                speed,
+                human_readable_duration(eta)
+            )
+        }
+        None => format!("{0} · {1}", human_readable_size(done as usize), speed),
     }
 }